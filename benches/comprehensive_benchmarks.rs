@@ -1,4 +1,4 @@
-/**
+/*
  * This code written by Claude Sonnet 4 (claude-3-5-sonnet-20241022)
  * Generated via Cursor IDE (cursor.sh) with AI assistance
  * Model: Anthropic Claude 3.5 Sonnet
@@ -13,31 +13,40 @@
  * - Dependencies: criterion, tokio, rayon
  */
 
-use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId, Throughput};
-use sportball_sidecar_rust::{SportballSidecar, SidecarFormat};
-use std::path::Path;
-use std::sync::Arc;
+use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId};
+use image_sidecar_rust::{GenerateOptions, ImageSidecar, SidecarFormat};
+use tempfile::TempDir;
 use tokio::runtime::Runtime;
-use rayon::prelude::*;
-use std::time::Instant;
+
+/// Frames in each generated dataset; set `SIDECAR_BENCH_COUNT` for larger runs
+fn dataset_size() -> usize {
+    std::env::var("SIDECAR_BENCH_COUNT").ok().and_then(|count| count.parse().ok()).unwrap_or(2000)
+}
+
+/// A synthetic dataset of face and yolo sidecars in `format`, removed when dropped
+fn generated_dataset(rt: &Runtime, format: SidecarFormat) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let options = GenerateOptions::default()
+        .count(dataset_size())
+        .format(format)
+        .image_size(64, 48)
+        .unwrap();
+    rt.block_on(ImageSidecar::new(None).generate_dataset(dir.path(), &options)).unwrap();
+    dir
+}
 
 /// Benchmark conversion performance between formats
 fn benchmark_conversion_performance(c: &mut Criterion) {
     let rt = Runtime::new().unwrap();
-    let data_dir = Path::new("/tank/games/Game_04_153212-160200/");
-    
-    // Only run if the data directory exists
-    if !data_dir.exists() {
-        println!("⚠️  Data directory not found: {:?}", data_dir);
-        return;
-    }
+    let dataset = generated_dataset(&rt, SidecarFormat::Json);
+    let data_dir = dataset.path();
 
     let mut group = c.benchmark_group("conversion_performance");
     
     // Benchmark JSON to Binary conversion
     group.bench_function("json_to_binary", |b| {
         b.to_async(&rt).iter(|| async {
-            let sidecar = SportballSidecar::new(None);
+            let sidecar = ImageSidecar::new(None);
             let result = sidecar.convert_directory_format(black_box(data_dir), SidecarFormat::Binary).await;
             black_box(result)
         })
@@ -46,7 +55,7 @@ fn benchmark_conversion_performance(c: &mut Criterion) {
     // Benchmark JSON to Rkyv conversion
     group.bench_function("json_to_rkyv", |b| {
         b.to_async(&rt).iter(|| async {
-            let sidecar = SportballSidecar::new(None);
+            let sidecar = ImageSidecar::new(None);
             let result = sidecar.convert_directory_format(black_box(data_dir), SidecarFormat::Rkyv).await;
             black_box(result)
         })
@@ -58,29 +67,25 @@ fn benchmark_conversion_performance(c: &mut Criterion) {
 /// Benchmark reading performance for different formats
 fn benchmark_reading_performance(c: &mut Criterion) {
     let rt = Runtime::new().unwrap();
-    let data_dir = Path::new("/tank/games/Game_04_153212-160200/");
-    
-    if !data_dir.exists() {
-        println!("⚠️  Data directory not found: {:?}", data_dir);
-        return;
-    }
+    let json_dataset = generated_dataset(&rt, SidecarFormat::Json);
+    let binary_dataset = generated_dataset(&rt, SidecarFormat::Binary);
 
     let mut group = c.benchmark_group("reading_performance");
     
     // Benchmark JSON reading
     group.bench_function("read_json", |b| {
         b.to_async(&rt).iter(|| async {
-            let sidecar = SportballSidecar::new(Some(16));
-            let result = sidecar.validate_sidecars(black_box(data_dir)).await;
+            let sidecar = ImageSidecar::new(Some(16));
+            let result = sidecar.validate_sidecars(black_box(json_dataset.path())).await;
             black_box(result)
         })
     });
 
-    // Benchmark Binary reading (after conversion)
+    // Benchmark Binary reading
     group.bench_function("read_binary", |b| {
         b.to_async(&rt).iter(|| async {
-            let sidecar = SportballSidecar::new(Some(16));
-            let result = sidecar.validate_sidecars(black_box(data_dir)).await;
+            let sidecar = ImageSidecar::new(Some(16));
+            let result = sidecar.validate_sidecars(black_box(binary_dataset.path())).await;
             black_box(result)
         })
     });
@@ -91,12 +96,8 @@ fn benchmark_reading_performance(c: &mut Criterion) {
 /// Benchmark parallel processing with different worker counts
 fn benchmark_parallel_processing(c: &mut Criterion) {
     let rt = Runtime::new().unwrap();
-    let data_dir = Path::new("/tank/games/Game_04_153212-160200/");
-    
-    if !data_dir.exists() {
-        println!("⚠️  Data directory not found: {:?}", data_dir);
-        return;
-    }
+    let dataset = generated_dataset(&rt, SidecarFormat::Json);
+    let data_dir = dataset.path();
 
     let mut group = c.benchmark_group("parallel_processing");
     
@@ -108,7 +109,7 @@ fn benchmark_parallel_processing(c: &mut Criterion) {
             &workers,
             |b, &workers| {
                 b.to_async(&rt).iter(|| async {
-                    let sidecar = SportballSidecar::new(Some(workers));
+                    let sidecar = ImageSidecar::new(Some(workers));
                     let result = sidecar.validate_sidecars(black_box(data_dir)).await;
                     black_box(result)
                 })
@@ -122,19 +123,15 @@ fn benchmark_parallel_processing(c: &mut Criterion) {
 /// Benchmark file size analysis
 fn benchmark_file_size_analysis(c: &mut Criterion) {
     let rt = Runtime::new().unwrap();
-    let data_dir = Path::new("/tank/games/Game_04_153212-160200/");
-    
-    if !data_dir.exists() {
-        println!("⚠️  Data directory not found: {:?}", data_dir);
-        return;
-    }
+    let dataset = generated_dataset(&rt, SidecarFormat::Json);
+    let data_dir = dataset.path();
 
     let mut group = c.benchmark_group("file_size_analysis");
     
     // Benchmark format statistics
     group.bench_function("format_statistics", |b| {
         b.to_async(&rt).iter(|| async {
-            let sidecar = SportballSidecar::new(None);
+            let sidecar = ImageSidecar::new(None);
             let result = sidecar.get_format_statistics(black_box(data_dir)).await;
             black_box(result)
         })
@@ -143,7 +140,7 @@ fn benchmark_file_size_analysis(c: &mut Criterion) {
     // Benchmark general statistics
     group.bench_function("general_statistics", |b| {
         b.to_async(&rt).iter(|| async {
-            let sidecar = SportballSidecar::new(None);
+            let sidecar = ImageSidecar::new(None);
             let result = sidecar.get_statistics(black_box(data_dir)).await;
             black_box(result)
         })
@@ -154,7 +151,7 @@ fn benchmark_file_size_analysis(c: &mut Criterion) {
 
 /// Benchmark serialization/deserialization performance
 fn benchmark_serialization_performance(c: &mut Criterion) {
-    use sportball_sidecar_rust::sidecar::formats::{FormatManager, SidecarFormat};
+    use image_sidecar_rust::sidecar::formats::{FormatManager, SidecarFormat};
     use serde_json::json;
 
     let mut group = c.benchmark_group("serialization_performance");
@@ -233,12 +230,8 @@ fn benchmark_serialization_performance(c: &mut Criterion) {
 /// Benchmark memory usage and allocation patterns
 fn benchmark_memory_usage(c: &mut Criterion) {
     let rt = Runtime::new().unwrap();
-    let data_dir = Path::new("/tank/games/Game_04_153212-160200/");
-    
-    if !data_dir.exists() {
-        println!("⚠️  Data directory not found: {:?}", data_dir);
-        return;
-    }
+    let dataset = generated_dataset(&rt, SidecarFormat::Json);
+    let data_dir = dataset.path();
 
     let mut group = c.benchmark_group("memory_usage");
     
@@ -251,7 +244,7 @@ fn benchmark_memory_usage(c: &mut Criterion) {
             &workers,
             |b, &workers| {
                 b.to_async(&rt).iter(|| async {
-                    let sidecar = SportballSidecar::new(Some(workers));
+                    let sidecar = ImageSidecar::new(Some(workers));
                     let result = sidecar.validate_sidecars(black_box(data_dir)).await;
                     black_box(result)
                 })
@@ -1,4 +1,4 @@
-/**
+/*
  * This code written by Claude Sonnet 4 (claude-3-5-sonnet-20241022)
  * Generated via Cursor IDE (cursor.sh) with AI assistance
  * Model: Anthropic Claude 3.5 Sonnet
@@ -13,9 +13,8 @@
  * - Dependencies: criterion, tempfile
  */
 
-use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId};
-use sportball_sidecar_rust::SportballSidecar;
-use std::path::Path;
+use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId, Throughput};
+use image_sidecar_rust::ImageSidecar;
 use tempfile::TempDir;
 use std::fs;
 
@@ -72,7 +71,7 @@ fn benchmark_validation(c: &mut Criterion) {
             file_count,
             |b, _| {
                 b.to_async(tokio::runtime::Runtime::new().unwrap()).iter(|| async {
-                    let sidecar = SportballSidecar::new(Some(16));
+                    let sidecar = ImageSidecar::new(Some(16));
                     let result = sidecar.validate_sidecars(black_box(temp_dir.path())).await;
                     black_box(result)
                 })
@@ -95,7 +94,7 @@ fn benchmark_statistics(c: &mut Criterion) {
             file_count,
             |b, _| {
                 b.to_async(tokio::runtime::Runtime::new().unwrap()).iter(|| async {
-                    let sidecar = SportballSidecar::new(None);
+                    let sidecar = ImageSidecar::new(None);
                     let result = sidecar.get_statistics(black_box(temp_dir.path())).await;
                     black_box(result)
                 })
@@ -106,5 +105,38 @@ fn benchmark_statistics(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, benchmark_validation, benchmark_statistics);
+/// Benchmark validation throughput as the number of in-flight reads grows
+/// Set `SIDECAR_BENCH_DIR` to a real directory (e.g. an NFS mount) to measure
+/// latency-bound storage; otherwise a local temporary directory is used
+fn benchmark_io_concurrency(c: &mut Criterion) {
+    let mut group = c.benchmark_group("io_concurrency");
+    
+    let temp_dir = TempDir::new().unwrap();
+    let data_dir = match std::env::var_os("SIDECAR_BENCH_DIR") {
+        Some(dir) => std::path::PathBuf::from(dir),
+        None => {
+            let files = create_test_json_files(2000, &temp_dir);
+            group.throughput(Throughput::Elements(files.len() as u64));
+            temp_dir.path().to_path_buf()
+        }
+    };
+    
+    for workers in [1, 4, 16, 64].iter() {
+        group.bench_with_input(
+            BenchmarkId::new("validate_sidecars", workers),
+            workers,
+            |b, &workers| {
+                b.to_async(tokio::runtime::Runtime::new().unwrap()).iter(|| async {
+                    let sidecar = ImageSidecar::new(Some(workers));
+                    let result = sidecar.validate_sidecars(black_box(&data_dir)).await;
+                    black_box(result)
+                })
+            },
+        );
+    }
+    
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_validation, benchmark_statistics, benchmark_io_concurrency);
 criterion_main!(benches);
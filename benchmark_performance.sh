@@ -5,11 +5,18 @@
 
 set -e
 
-DATA_DIR="/tank/games/Game_04_153212-160200/"
+DATA_DIR="${DATA_DIR:-/tmp/sportball_benchmark_json}"
+DATASET_COUNT="${DATASET_COUNT:-100000}"
 BINARY_DIR="/tmp/sportball_benchmark_binary"
 RKYV_DIR="/tmp/sportball_benchmark_rkyv"
 RESULTS_FILE="benchmark_results_$(date +%Y%m%d_%H%M%S).json"
 
+# Generate a synthetic JSON dataset unless DATA_DIR points at real data
+if [ ! -d "$DATA_DIR" ]; then
+    echo "🧪 Generating $DATASET_COUNT synthetic images into $DATA_DIR..."
+    ./target/release/sportball-sidecar-rust generate --output "$DATA_DIR" --count "$DATASET_COUNT" --operations face,yolo --format json
+fi
+
 echo "🚀 Starting Sidecar Format Performance Benchmark"
 echo "📁 Data directory: $DATA_DIR"
 echo "📊 Total files: $(find $DATA_DIR -name "*.json" | wc -l)"
@@ -0,0 +1,26 @@
+// Generates image_sidecar_rust.h from src/ffi.rs into OUT_DIR when the ffi feature is enabled
+// Set IMAGE_SIDECAR_RUST_HEADER_DIR to also copy it out, e.g. to refresh include/ in the checkout
+
+fn main() {
+    #[cfg(feature = "ffi")]
+    {
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        println!("cargo:rerun-if-env-changed=IMAGE_SIDECAR_RUST_HEADER_DIR");
+
+        let crate_dir = std::env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR is set by cargo");
+        let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+        let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", crate_dir))
+            .expect("cbindgen.toml is readable");
+        let header = std::path::Path::new(&out_dir).join("image_sidecar_rust.h");
+        cbindgen::generate_with_config(&crate_dir, config)
+            .expect("C header generation failed")
+            .write_to_file(&header);
+
+        if let Some(dir) = std::env::var_os("IMAGE_SIDECAR_RUST_HEADER_DIR") {
+            let dir = std::path::PathBuf::from(dir);
+            std::fs::create_dir_all(&dir).expect("header directory can be created");
+            std::fs::copy(&header, dir.join("image_sidecar_rust.h")).expect("header can be copied");
+        }
+    }
+}
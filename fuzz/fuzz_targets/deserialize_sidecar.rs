@@ -0,0 +1,35 @@
+/*
+ * Context: Fuzz target feeding arbitrary bytes to every sidecar deserializer
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: libfuzzer-sys, serde_json
+ *
+ * Sidecars are read straight off disk, so a corrupt or truncated file has to come back
+ * as an error. Whatever does decode must survive another trip through the same format.
+ */
+
+#![no_main]
+
+use image_sidecar_rust::sidecar::{FormatManager, SidecarFormat};
+use libfuzzer_sys::fuzz_target;
+
+const FORMATS: [SidecarFormat; 3] = [SidecarFormat::Json, SidecarFormat::Binary, SidecarFormat::Rkyv];
+
+fuzz_target!(|bytes: &[u8]| {
+    for format_manager in [FormatManager::new(), FormatManager::canonical()] {
+        let _ = format_manager.detect_format_from_content(bytes);
+        for format in FORMATS {
+            let serializer = format_manager.get_serializer(format);
+            let Ok(value) = serializer.deserialize(bytes) else { continue };
+            let written = serializer.serialize(&value).expect("decoded documents serialize");
+            let read = serializer.deserialize(&written).expect("written documents decode");
+            if format_manager.is_canonical() {
+                // Canonical writes normalise negative zero, so compare what they write
+                assert_eq!(serializer.serialize(&read).unwrap(), written, "{format:?} is not stable");
+            } else {
+                assert_eq!(read, value, "{format:?} changed the document");
+            }
+        }
+    }
+});
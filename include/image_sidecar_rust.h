@@ -0,0 +1,96 @@
+/* image-sidecar-rust C API. Generated by cbindgen from src/ffi.rs; do not edit. */
+
+#ifndef IMAGE_SIDECAR_RUST_H
+#define IMAGE_SIDECAR_RUST_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Result of every FFI call; `SIDECAR_STATUS_OK` is zero
+typedef enum sidecar_status {
+  SIDECAR_STATUS_OK = 0,
+  // A required pointer argument was NULL
+  SIDECAR_STATUS_NULL_ARGUMENT = 1,
+  // A string argument was not valid UTF-8
+  SIDECAR_STATUS_INVALID_UTF8 = 2,
+  // The data argument was not valid JSON
+  SIDECAR_STATUS_INVALID_JSON = 3,
+  SIDECAR_STATUS_IO = 4,
+  SIDECAR_STATUS_NOT_FOUND = 5,
+  SIDECAR_STATUS_FORMAT = 6,
+  SIDECAR_STATUS_SCHEMA = 7,
+  SIDECAR_STATUS_VALIDATION = 8,
+  SIDECAR_STATUS_LOCK = 9,
+  SIDECAR_STATUS_STORAGE = 10,
+  SIDECAR_STATUS_CANCELLED = 11,
+  SIDECAR_STATUS_PROCESSING = 12,
+  // The library panicked; the handle should not be used again
+  SIDECAR_STATUS_PANIC = 13,
+  // The handle is read-only and the call would have written
+  SIDECAR_STATUS_READ_ONLY = 14,
+  // A sidecar was over the maximum sidecar size and was not read
+  SIDECAR_STATUS_TOO_LARGE = 15,
+} sidecar_status;
+
+// Opaque manager handle owning an `ImageSidecar` and the runtime that drives it
+typedef struct sidecar_handle sidecar_handle;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Create a manager; `max_workers` of 0 uses the available parallelism.
+// Returns NULL on failure (see `sidecar_last_error`). Release with `sidecar_manager_free`.
+struct sidecar_handle *sidecar_manager_new(size_t max_workers);
+
+// Release a manager created by `sidecar_manager_new`; NULL is ignored
+//
+// # Safety
+// `handle` must come from `sidecar_manager_new` and must not be used afterwards.
+void sidecar_manager_free(struct sidecar_handle *handle);
+
+// Merge the JSON object `data_json` into the image's sidecar under `operation`
+//
+// # Safety
+// `handle` must be live; string arguments must be NUL-terminated.
+enum sidecar_status sidecar_save(const struct sidecar_handle *handle,
+                                 const char *image_path,
+                                 const char *operation,
+                                 const char *data_json);
+
+// Read the image's sidecar as JSON into `*out` (`{}` when there is none)
+//
+// # Safety
+// `handle` must be live; `image_path` must be NUL-terminated; `out` must be writable.
+// Release `*out` with `sidecar_string_free`.
+enum sidecar_status sidecar_load(const struct sidecar_handle *handle,
+                                 const char *image_path,
+                                 char **out);
+
+// Validate every sidecar under `directory`, writing a JSON array of results into `*out`
+//
+// # Safety
+// `handle` must be live; `directory` must be NUL-terminated; `out` must be writable.
+// Release `*out` with `sidecar_string_free`.
+enum sidecar_status sidecar_validate(const struct sidecar_handle *handle,
+                                     const char *directory,
+                                     char **out);
+
+// Release a string returned through an `out` parameter; NULL is ignored
+//
+// # Safety
+// `value` must come from this library and must not be used afterwards.
+void sidecar_string_free(char *value);
+
+// Message for the last failed call on this thread, or NULL.
+// The pointer stays valid until the next failing call on the same thread.
+const char *sidecar_last_error(void);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* IMAGE_SIDECAR_RUST_H */
@@ -5,11 +5,11 @@ This module provides the main Python interface to the Rust implementation,
 offering high-performance sidecar file operations.
 """
 
-from typing import Optional, Dict, List, Union, Any
+from typing import Optional, Callable, Dict, Iterator, List, Union, Any
 from pathlib import Path
 import json
 
-from .exceptions import SidecarError, ValidationError, StatisticsError, FormatError
+from .exceptions import SidecarError, ValidationError, StatisticsError, FormatError, error_code
 
 
 class SidecarFormat:
@@ -46,6 +46,7 @@ class OperationType:
     QUALITY_ASSESSMENT = "quality_assessment"
     GAME_DETECTION = "game_detection"
     YOLOV8 = "yolov8"
+    IMAGE_HASH = "image_hash"
     UNIFIED = "unified"
     
     def __init__(self, operation_str: str) -> None:
@@ -104,11 +105,16 @@ class ImageSidecar:
         """Check if the Rust implementation is available."""
         return self._rust_available
     
-    def validate_sidecars(self, directory: Union[str, Path]) -> List[Dict[str, Any]]:
+    def validate_sidecars(
+        self,
+        directory: Union[str, Path],
+        progress: Optional[Callable[[int, int, str], None]] = None,
+    ) -> List[Dict[str, Any]]:
         """Validate JSON sidecar files in parallel.
         
         Args:
             directory: Directory path to validate sidecar files in
+            progress: Optional callable invoked as progress(done, total, current_path)
             
         Returns:
             List of validation results with 'sidecar_path', 'is_valid', 
@@ -122,7 +128,7 @@ class ImageSidecar:
         
         try:
             directory_str = str(directory)
-            results = self._rust_impl.validate_sidecars(directory_str)
+            results = self._rust_impl.validate_sidecars(directory_str, progress)
             return [
                 {
                     'file_path': result.file_path,
@@ -130,22 +136,31 @@ class ImageSidecar:
                     'error': result.error,
                     'processing_time': result.processing_time,
                     'file_size': result.file_size,
+                    'format': result.format,
+                    'deserialize_time': result.deserialize_time,
                 }
                 for result in results
             ]
         except Exception as e:
-            raise ValidationError(f"Validation failed: {e}")
+            raise ValidationError(f"Validation failed: {e}", error_code(e)) from e
     
-    def get_statistics(self, directory: Union[str, Path]) -> Dict[str, Any]:
+    def get_statistics(
+        self,
+        directory: Union[str, Path],
+        progress: Optional[Callable[[int, int, str], None]] = None,
+    ) -> Dict[str, Any]:
         """Get comprehensive statistics about sidecar files.
         
         Args:
             directory: Directory path to analyze
+            progress: Optional callable invoked as progress(done, total, current_path)
             
         Returns:
-            Dictionary with statistics including 'total_files', 'valid_files',
-            'invalid_files', 'total_size_bytes', 'average_size_bytes',
-            'operation_counts', 'format_counts', and 'processing_time_ms'
+            Dictionary with 'directory', 'total_images', 'symlink_count',
+            'broken_symlinks', 'total_sidecars', 'coverage_percentage',
+            'operation_counts', 'avg_processing_times', 'success_rate_percentages',
+            'avg_data_sizes', 'filter_applied' and 'sidecars' (one dict per
+            sidecar, e.g. for ``pandas.DataFrame(stats['sidecars'])``)
             
         Raises:
             StatisticsError: If statistics collection fails
@@ -155,18 +170,60 @@ class ImageSidecar:
         
         try:
             directory_str = str(directory)
-            stats = self._rust_impl.get_statistics(directory_str)
-            return {
-                'total_images': stats.total_images,
-                'total_sidecars': stats.total_sidecars,
-                'coverage_percentage': stats.coverage_percentage,
-                'operation_counts': stats.operation_counts,
-                'avg_processing_times': stats.avg_processing_times,
-                'success_rate_percentages': stats.success_rate_percentages,
-                'avg_data_sizes': stats.avg_data_sizes,
-            }
+            stats = self._rust_impl.get_statistics(directory_str, progress)
+            return stats.to_dict()
+        except Exception as e:
+            raise StatisticsError(f"Statistics collection failed: {e}", error_code(e)) from e
+    
+    def validate_sidecars_df(self, directory: Union[str, Path]) -> Any:
+        """Validate sidecar files and return the results as a ``pyarrow.Table``.
+        
+        Rows are built in Rust and handed over through the Arrow C data interface,
+        so no per-row Python objects are created. Use ``table.to_pandas()`` or
+        ``polars.from_arrow(table)`` to get a DataFrame. Requires pyarrow and an
+        extension built with the ``python-arrow`` feature.
+        
+        Args:
+            directory: Directory path to validate sidecar files in
+            
+        Returns:
+            pyarrow.Table with one row per validated file
+            
+        Raises:
+            ValidationError: If validation fails or Arrow support is unavailable
+        """
+        self._require_arrow()
+        try:
+            return self._rust_impl.validate_sidecars_df(str(directory))
+        except Exception as e:
+            raise ValidationError(f"Validation failed: {e}", error_code(e)) from e
+    
+    def stats_df(self, directory: Union[str, Path]) -> Any:
+        """Scan a directory and return its sidecars as a ``pyarrow.Table``.
+        
+        See ``validate_sidecars_df`` for requirements.
+        
+        Args:
+            directory: Directory path to analyze
+            
+        Returns:
+            pyarrow.Table with one row per sidecar
+            
+        Raises:
+            StatisticsError: If statistics collection fails or Arrow support is unavailable
+        """
+        self._require_arrow()
+        try:
+            return self._rust_impl.stats_df(str(directory))
         except Exception as e:
-            raise StatisticsError(f"Statistics collection failed: {e}")
+            raise StatisticsError(f"Statistics collection failed: {e}", error_code(e)) from e
+    
+    def _require_arrow(self) -> None:
+        if not self._rust_available or not hasattr(self._rust_impl, "stats_df"):
+            raise SidecarError(
+                "Arrow support not available. Rebuild with `maturin develop --features python-arrow`.",
+                "ArrowUnavailable",
+            )
     
     def find_sidecars(self, directory: Union[str, Path]) -> List[Dict[str, Any]]:
         """Find all sidecar files in a directory.
@@ -192,11 +249,138 @@ class ImageSidecar:
                     'data_size': sidecar.data_size,
                     'created_at': sidecar.created_at,
                     'is_valid': sidecar.is_valid,
+                    'processing_time': sidecar.processing_time,
+                    'success': sidecar.success,
                 }
                 for sidecar in sidecars
             ]
         except Exception as e:
-            raise SidecarError(f"Sidecar search failed: {e}")
+            raise SidecarError(f"Sidecar search failed: {e}", error_code(e)) from e
+    
+    def query(self, directory: Union[str, Path], expression: str) -> List[Dict[str, Any]]:
+        """Find sidecars whose contents match a filter expression.
+        
+        Args:
+            directory: Directory path to search
+            expression: Filter such as
+                "operation == 'face_detection' && detections > 2 && confidence.max >= 0.9"
+            
+        Returns:
+            List of matching records: sidecar info fields plus the decoded 'data'
+            
+        Raises:
+            ValueError: If the expression cannot be parsed
+            SidecarError: If the query fails
+        """
+        if not self._rust_available:
+            raise SidecarError("Rust implementation not available")
+        
+        try:
+            return self._rust_impl.query(str(directory), expression)
+        except ValueError:
+            raise
+        except Exception as e:
+            raise SidecarError(f"Query failed: {e}", error_code(e)) from e
+    
+    def get(self, directory: Union[str, Path], path: str) -> List[Dict[str, Any]]:
+        """Extract values matching a JSONPath from every sidecar in a directory.
+        
+        Args:
+            directory: Directory path to search
+            path: JSONPath such as "$.face_detection.faces[*].confidence"
+            
+        Returns:
+            List of {'image_path', 'sidecar_path', 'value'} records, one per matched value
+            
+        Raises:
+            ValueError: If the path cannot be parsed
+            SidecarError: If extraction fails
+        """
+        if not self._rust_available:
+            raise SidecarError("Rust implementation not available")
+        
+        try:
+            return self._rust_impl.get(str(directory), path)
+        except ValueError:
+            raise
+        except Exception as e:
+            raise SidecarError(f"Extraction failed: {e}", error_code(e)) from e
+    
+    def build_vector_index(self, directory: Union[str, Path], index_path: Union[str, Path]) -> int:
+        """Build a nearest-neighbor index over the face encodings in a directory.
+        
+        Args:
+            directory: Directory path to scan
+            index_path: File to write the index to
+            
+        Returns:
+            Number of encodings indexed
+            
+        Raises:
+            SidecarError: If the index cannot be built or saved
+        """
+        if not self._rust_available:
+            raise SidecarError("Rust implementation not available")
+        
+        try:
+            return self._rust_impl.build_vector_index(str(directory), str(index_path))
+        except Exception as e:
+            raise SidecarError(f"Index build failed: {e}", error_code(e)) from e
+    
+    def find_similar(self, index_path: Union[str, Path], image_path: Union[str, Path], k: int = 10) -> List[Dict[str, Any]]:
+        """Find the faces most similar to those in an image.
+        
+        Args:
+            index_path: Index written by build_vector_index
+            image_path: Image whose faces to search for
+            k: Number of neighbors to return
+            
+        Returns:
+            List of {'image_path', 'face_index', 'distance'} records, closest first
+            
+        Raises:
+            ValueError: If the image is not in the index
+            SidecarError: If the index cannot be loaded
+        """
+        if not self._rust_available:
+            raise SidecarError("Rust implementation not available")
+        
+        try:
+            return self._rust_impl.find_similar(str(index_path), str(image_path), k)
+        except ValueError:
+            raise
+        except Exception as e:
+            raise SidecarError(f"Similarity search failed: {e}", error_code(e)) from e
+    
+    def iter_sidecars(self, directory: Union[str, Path]) -> Iterator[Dict[str, Any]]:
+        """Lazily iterate over sidecar files in a directory.
+        
+        Unlike find_sidecars, results are yielded as the directory tree is
+        walked, so memory stays flat for very large trees.
+        
+        Args:
+            directory: Directory path to search
+            
+        Yields:
+            Sidecar info dictionaries with the same keys as find_sidecars
+        """
+        if not self._rust_available:
+            raise SidecarError("Rust implementation not available")
+        
+        try:
+            for sidecar in self._rust_impl.iter_sidecars(str(directory)):
+                yield {
+                    'image_path': sidecar.image_path,
+                    'sidecar_path': sidecar.sidecar_path,
+                    'operation': str(sidecar.operation),
+                    'data_size': sidecar.data_size,
+                    'created_at': sidecar.created_at,
+                    'is_valid': sidecar.is_valid,
+                    'processing_time': sidecar.processing_time,
+                    'success': sidecar.success,
+                }
+        except Exception as e:
+            raise SidecarError(f"Sidecar search failed: {e}", error_code(e)) from e
     
     def create_sidecar(
         self,
@@ -241,9 +425,11 @@ class ImageSidecar:
                 'data_size': sidecar_info.data_size,
                 'created_at': sidecar_info.created_at,
                 'is_valid': sidecar_info.is_valid,
+                'processing_time': sidecar_info.processing_time,
+                'success': sidecar_info.success,
             }
         except Exception as e:
-            raise SidecarError(f"Sidecar creation failed: {e}")
+            raise SidecarError(f"Sidecar creation failed: {e}", error_code(e)) from e
     
     def save_data(
         self,
@@ -259,7 +445,8 @@ class ImageSidecar:
         Args:
             image_path: Path to the image file
             operation: Operation type (string or OperationType enum)
-            data: Data to store in the sidecar file
+            data: Data to store in the sidecar file. NumPy arrays and scalars
+                (e.g. face encodings) are accepted and stored as lists.
             
         Returns:
             Dictionary with sidecar info
@@ -291,11 +478,13 @@ class ImageSidecar:
                 'data_size': sidecar_info.data_size,
                 'created_at': sidecar_info.created_at,
                 'is_valid': sidecar_info.is_valid,
+                'processing_time': sidecar_info.processing_time,
+                'success': sidecar_info.success,
             }
         except Exception as e:
-            raise SidecarError(f"Sidecar save failed: {e}")
+            raise SidecarError(f"Sidecar save failed: {e}", error_code(e)) from e
 
-    def read_data(self, image_path: Union[str, Path]) -> Dict[str, Any]:
+    def read_data(self, image_path: Union[str, Path], as_numpy: bool = False) -> Dict[str, Any]:
         """Read sidecar data for an image.
         
         This method reads the sidecar file for the given image path, trying
@@ -305,6 +494,8 @@ class ImageSidecar:
         
         Args:
             image_path: Path to the image file
+            as_numpy: Return numeric lists (face encodings, landmark points) as
+                ``numpy.ndarray`` instead of Python lists. Requires numpy.
             
         Returns:
             Dictionary containing all sidecar data including all operations.
@@ -318,7 +509,7 @@ class ImageSidecar:
         
         try:
             image_path_str = str(image_path)
-            data = self._rust_impl.read_data(image_path_str)
+            data = self._rust_impl.read_data(image_path_str, as_numpy)
             
             # Convert PyObject to Python dict
             if data is None:
@@ -331,7 +522,7 @@ class ImageSidecar:
             # Otherwise convert to dict
             return dict(data)
         except Exception as e:
-            raise SidecarError(f"Sidecar read failed: {e}")
+            raise SidecarError(f"Sidecar read failed: {e}", error_code(e)) from e
     
     def cleanup_orphaned(self, directory: Union[str, Path]) -> int:
         """Clean up orphaned sidecar files.
@@ -349,18 +540,20 @@ class ImageSidecar:
             directory_str = str(directory)
             return self._rust_impl.cleanup_orphaned(directory_str)
         except Exception as e:
-            raise SidecarError(f"Cleanup failed: {e}")
+            raise SidecarError(f"Cleanup failed: {e}", error_code(e)) from e
     
     def convert_directory_format(
         self,
         directory: Union[str, Path],
         target_format: Union[str, SidecarFormat],
+        progress: Optional[Callable[[int, int, str], None]] = None,
     ) -> int:
         """Convert sidecar files between formats.
         
         Args:
             directory: Directory path to convert
             target_format: Target format (string or SidecarFormat enum)
+            progress: Optional callable invoked as progress(done, total, current_path)
             
         Returns:
             Number of files converted
@@ -379,9 +572,9 @@ class ImageSidecar:
             import image_sidecar_rust.image_sidecar_rust as rust_ext
             rust_fmt = rust_ext.PySidecarFormat(str(fmt))
             
-            return self._rust_impl.convert_directory_format(directory_str, rust_fmt)
+            return self._rust_impl.convert_directory_format(directory_str, rust_fmt, progress)
         except Exception as e:
-            raise FormatError(f"Format conversion failed: {e}")
+            raise FormatError(f"Format conversion failed: {e}", error_code(e)) from e
     
     def get_format_statistics(self, directory: Union[str, Path]) -> Dict[str, int]:
         """Get format statistics for a directory.
@@ -399,7 +592,7 @@ class ImageSidecar:
             directory_str = str(directory)
             return self._rust_impl.get_format_statistics(directory_str)
         except Exception as e:
-            raise StatisticsError(f"Format statistics failed: {e}")
+            raise StatisticsError(f"Format statistics failed: {e}", error_code(e)) from e
     
     def set_default_format(self, format: Union[str, SidecarFormat]) -> None:
         """Set the default format for new sidecar files.
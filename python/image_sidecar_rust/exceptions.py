@@ -41,3 +41,17 @@ class FormatError(SidecarError):
 class IOWarning(SidecarError):
     """Exception raised for I/O related warnings."""
     pass
+
+
+def error_code(error: BaseException) -> Optional[str]:
+    """Return the Rust exception class name behind ``error``, if any.
+    
+    The extension raises a distinct ``SidecarException`` subclass per error kind
+    (``SidecarFormatError``, ``SidecarSchemaError``, ``SidecarCancelledError``, ...).
+    The wrappers in ``core`` keep that name as ``error_code`` and chain the original.
+    """
+    try:
+        from .image_sidecar_rust import SidecarException
+    except ImportError:
+        return None
+    return type(error).__name__ if isinstance(error, SidecarException) else None
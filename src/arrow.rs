@@ -0,0 +1,101 @@
+/*
+ * Context: Columnar Arrow record batches for validation results and sidecar listings
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: arrow-array, arrow-schema
+ */
+
+use crate::sidecar::types::{Result, SidecarError, SidecarInfo, ValidationResult};
+use crate::utils::paths;
+use arrow_array::{ArrayRef, BooleanArray, Float64Array, StringArray, UInt64Array};
+use arrow_schema::{ArrowError, DataType, Field, Schema};
+use std::sync::Arc;
+
+pub use arrow_array::RecordBatch;
+
+impl From<ArrowError> for SidecarError {
+    fn from(error: ArrowError) -> Self {
+        SidecarError::ProcessingError(format!("Arrow error: {}", error))
+    }
+}
+
+/// One row per validated file
+///
+/// Columns: `file_path`, `is_valid`, `error`, `operation_type`, `format`,
+/// `file_size`, `detection_count`, `processing_time`, `deserialize_time` and `oversized`.
+pub fn validation_batch(results: &[ValidationResult]) -> Result<RecordBatch> {
+    let schema = Schema::new(vec![
+        Field::new("file_path", DataType::Utf8, false),
+        Field::new("is_valid", DataType::Boolean, false),
+        Field::new("error", DataType::Utf8, true),
+        Field::new("operation_type", DataType::Utf8, true),
+        Field::new("format", DataType::Utf8, true),
+        Field::new("file_size", DataType::UInt64, false),
+        Field::new("detection_count", DataType::UInt64, false),
+        Field::new("processing_time", DataType::Float64, false),
+        Field::new("deserialize_time", DataType::Float64, false),
+        Field::new("oversized", DataType::Boolean, false),
+    ]);
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from_iter_values(
+            results.iter().map(|result| paths::encode_path(&result.file_path)),
+        )),
+        Arc::new(BooleanArray::from_iter(results.iter().map(|result| Some(result.is_valid)))),
+        Arc::new(StringArray::from_iter(results.iter().map(|result| result.error.as_deref()))),
+        Arc::new(StringArray::from_iter(
+            results.iter().map(|result| result.operation_type.as_ref().map(|op| op.as_str())),
+        )),
+        Arc::new(StringArray::from_iter(
+            results.iter().map(|result| result.format.map(|format| format.extension())),
+        )),
+        Arc::new(UInt64Array::from_iter_values(results.iter().map(|result| result.file_size))),
+        Arc::new(UInt64Array::from_iter_values(
+            results.iter().map(|result| u64::from(result.detection_count)),
+        )),
+        Arc::new(Float64Array::from_iter_values(results.iter().map(|result| result.processing_time))),
+        Arc::new(Float64Array::from_iter_values(results.iter().map(|result| result.deserialize_time))),
+        Arc::new(BooleanArray::from_iter(results.iter().map(|result| Some(result.oversized)))),
+    ];
+
+    Ok(RecordBatch::try_new(Arc::new(schema), columns)?)
+}
+
+/// One row per sidecar found during a statistics scan
+///
+/// Columns: `image_path`, `sidecar_path`, `operation`, `is_valid`, `success`,
+/// `data_size`, `processing_time`, `created_at` and `recorded_at` (RFC 3339).
+pub fn sidecar_batch(sidecars: &[SidecarInfo]) -> Result<RecordBatch> {
+    let schema = Schema::new(vec![
+        Field::new("image_path", DataType::Utf8, false),
+        Field::new("sidecar_path", DataType::Utf8, false),
+        Field::new("operation", DataType::Utf8, false),
+        Field::new("is_valid", DataType::Boolean, false),
+        Field::new("success", DataType::Boolean, true),
+        Field::new("data_size", DataType::UInt64, false),
+        Field::new("processing_time", DataType::Float64, true),
+        Field::new("created_at", DataType::Utf8, false),
+        Field::new("recorded_at", DataType::Utf8, true),
+    ]);
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from_iter_values(
+            sidecars.iter().map(|info| paths::encode_path(&info.image_path)),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            sidecars.iter().map(|info| paths::encode_path(&info.sidecar_path)),
+        )),
+        Arc::new(StringArray::from_iter_values(sidecars.iter().map(|info| info.operation.as_str()))),
+        Arc::new(BooleanArray::from_iter(sidecars.iter().map(|info| Some(info.is_valid)))),
+        Arc::new(BooleanArray::from_iter(sidecars.iter().map(|info| info.success))),
+        Arc::new(UInt64Array::from_iter_values(sidecars.iter().map(|info| info.data_size))),
+        Arc::new(Float64Array::from_iter(sidecars.iter().map(|info| info.processing_time))),
+        Arc::new(StringArray::from_iter_values(sidecars.iter().map(|info| info.created_at.to_rfc3339()))),
+        Arc::new(StringArray::from_iter(
+            sidecars.iter().map(|info| info.recorded_at.map(|at| at.to_rfc3339())),
+        )),
+    ];
+
+    Ok(RecordBatch::try_new(Arc::new(schema), columns)?)
+}
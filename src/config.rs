@@ -0,0 +1,340 @@
+/*
+ * Context: TOML configuration file with named pipeline profiles
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: toml, serde
+ */
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::parallel::IoLimits;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::sidecar::{classify::ClassifierRule, patterns::SidecarPatterns, types::OperationType};
+use crate::sidecar::formats::SidecarFormat;
+use crate::sidecar::types::{MergeStrategy, Result, SidecarError, SymlinkPolicy};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// File looked up in the working directory when no config path is given
+pub const DEFAULT_CONFIG_FILE: &str = "sportball-sidecar.toml";
+
+/// Environment variable naming the config file
+pub const CONFIG_ENV: &str = "SPORTBALL_SIDECAR_CONFIG";
+
+/// Environment variable naming the profile to use
+pub const PROFILE_ENV: &str = "SPORTBALL_SIDECAR_PROFILE";
+
+/// Settings a profile can override; unset fields fall back to `[defaults]`, then built-ins
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// Format for new and converted sidecars (json, bin, rkyv)
+    pub format: Option<String>,
+    /// Worker count for parallel validation
+    pub workers: Option<usize>,
+    /// Fail `validate` on any invalid sidecar
+    pub fail_on_invalid: Option<bool>,
+    /// Fail `validate` when more than this percentage of sidecars is invalid
+    pub max_invalid_percent: Option<f64>,
+    /// Files opened per second by scans and conversions
+    pub max_files_per_sec: Option<f64>,
+    /// Sidecar files open at the same time
+    pub max_open_files: Option<usize>,
+    /// Read throughput in megabytes (10^6 bytes) per second
+    pub max_read_mb_per_sec: Option<f64>,
+    /// Sidecars larger than this many MiB (2^20 bytes) are refused unread
+    pub max_sidecar_mb: Option<f64>,
+    /// Run at low CPU and IO priority with one worker by default
+    pub background: Option<bool>,
+    /// Refuse every operation that would write, move or delete a sidecar
+    pub read_only: Option<bool>,
+    /// Write sidecars in canonical form, byte-identical for identical documents
+    pub canonical: Option<bool>,
+    /// Write sidecars as sorted, pretty-printed JSON for review in git
+    pub git_friendly: Option<bool>,
+    /// Where sidecars of symlinked images live (follow-target, stay-at-link, both)
+    pub symlink_policy: Option<String>,
+    /// Count hardlinks to one image file as a single image sharing one sidecar
+    pub merge_hardlinks: Option<bool>,
+    /// Extensions of the files treated as images, replacing the built-in list, e.g. `["jpg", "heic"]`
+    pub image_extensions: Option<Vec<String>>,
+    /// How `save_data` combines a payload with the one stored for its operation (replace, deep, keep-existing)
+    pub merge_strategy: Option<String>,
+    /// Follow symlinked directories when scanning
+    pub follow_links: Option<bool>,
+    /// Skip dot-files and dot-directories when scanning
+    pub skip_hidden: Option<bool>,
+    /// Append a JSON line for every sidecar write, conversion, deletion and repair to this file
+    pub audit_log: Option<PathBuf>,
+    /// Keep operation payloads in this content-addressed store, leaving pointers in the sidecars
+    pub blob_store: Option<PathBuf>,
+    /// Sign every payload written with the Ed25519 secret key in this file
+    pub signing_key: Option<PathBuf>,
+    /// Send an event for every sidecar created, updated or removed, one JSON line each, to this Unix socket
+    pub notify_socket: Option<PathBuf>,
+    /// POST batches of sidecar events to this URL (needs the `webhooks` feature)
+    pub notify_webhook: Option<String>,
+    /// Sidecar name patterns and the operation each holds, tried in order
+    pub sidecar_patterns: Option<Vec<SidecarPatternRule>>,
+    /// Rules classifying raw detector output, tried in order before the built-in detector keys
+    pub operation_rules: Option<Vec<OperationRule>>,
+}
+
+/// One `[[sidecar_patterns]]` entry, e.g. `template = "{stem}_faces.json"`, `operation = "face_detection"`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SidecarPatternRule {
+    /// Sidecar file name with one `{stem}` placeholder for the image stem
+    pub template: String,
+    /// Operation the matching sidecars hold
+    pub operation: String,
+}
+
+/// One `[[operation_rules]]` entry: exactly one of `key`, `pointer` and `tool_name`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OperationRule {
+    /// Operation sidecars meeting the rule hold, e.g. `object_detection`
+    pub operation: String,
+    /// A top-level key the sidecar has
+    pub key: Option<String>,
+    /// A JSON pointer that resolves in the sidecar, e.g. `/result/detections`
+    pub pointer: Option<String>,
+    /// A regex matching the tool name the sidecar reports
+    pub tool_name: Option<String>,
+}
+
+impl Profile {
+    /// Layer `overrides` on top of `self`, field by field
+    pub fn overlay(&self, overrides: &Profile) -> Profile {
+        Profile {
+            format: overrides.format.clone().or_else(|| self.format.clone()),
+            workers: overrides.workers.or(self.workers),
+            fail_on_invalid: overrides.fail_on_invalid.or(self.fail_on_invalid),
+            max_invalid_percent: overrides.max_invalid_percent.or(self.max_invalid_percent),
+            max_files_per_sec: overrides.max_files_per_sec.or(self.max_files_per_sec),
+            max_open_files: overrides.max_open_files.or(self.max_open_files),
+            max_read_mb_per_sec: overrides.max_read_mb_per_sec.or(self.max_read_mb_per_sec),
+            max_sidecar_mb: overrides.max_sidecar_mb.or(self.max_sidecar_mb),
+            background: overrides.background.or(self.background),
+            read_only: overrides.read_only.or(self.read_only),
+            canonical: overrides.canonical.or(self.canonical),
+            git_friendly: overrides.git_friendly.or(self.git_friendly),
+            symlink_policy: overrides.symlink_policy.clone().or_else(|| self.symlink_policy.clone()),
+            merge_hardlinks: overrides.merge_hardlinks.or(self.merge_hardlinks),
+            image_extensions: overrides.image_extensions.clone().or_else(|| self.image_extensions.clone()),
+            merge_strategy: overrides.merge_strategy.clone().or_else(|| self.merge_strategy.clone()),
+            follow_links: overrides.follow_links.or(self.follow_links),
+            skip_hidden: overrides.skip_hidden.or(self.skip_hidden),
+            audit_log: overrides.audit_log.clone().or_else(|| self.audit_log.clone()),
+            blob_store: overrides.blob_store.clone().or_else(|| self.blob_store.clone()),
+            signing_key: overrides.signing_key.clone().or_else(|| self.signing_key.clone()),
+            notify_socket: overrides.notify_socket.clone().or_else(|| self.notify_socket.clone()),
+            notify_webhook: overrides.notify_webhook.clone().or_else(|| self.notify_webhook.clone()),
+            sidecar_patterns: overrides.sidecar_patterns.clone().or_else(|| self.sidecar_patterns.clone()),
+            operation_rules: overrides.operation_rules.clone().or_else(|| self.operation_rules.clone()),
+        }
+    }
+
+    pub fn is_background(&self) -> bool {
+        self.background.unwrap_or(false)
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.unwrap_or(false)
+    }
+
+    pub fn is_canonical(&self) -> bool {
+        self.canonical.unwrap_or(false)
+    }
+
+    pub fn is_git_friendly(&self) -> bool {
+        self.git_friendly.unwrap_or(false)
+    }
+
+    pub fn merges_hardlinks(&self) -> bool {
+        self.merge_hardlinks.unwrap_or(false)
+    }
+
+    /// The configured IO limits; rates and counts must be positive
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn io_limits(&self) -> Result<IoLimits> {
+        let positive = |name: &str, value: Option<f64>| match value {
+            Some(value) if value.is_nan() || value <= 0.0 => {
+                Err(SidecarError::ProcessingError(format!("{} must be positive, got {}", name, value)))
+            }
+            _ => Ok(value),
+        };
+
+        Ok(IoLimits {
+            max_files_per_sec: positive("max_files_per_sec", self.max_files_per_sec)?,
+            max_open_files: positive("max_open_files", self.max_open_files.map(|count| count as f64))?
+                .map(|count| count as usize),
+            max_read_bytes_per_sec: positive("max_read_mb_per_sec", self.max_read_mb_per_sec)?
+                .map(|mb| (mb * 1_000_000.0) as u64),
+        })
+    }
+
+    /// The configured maximum sidecar size in bytes, which must be positive
+    pub fn max_sidecar_size(&self) -> Result<Option<u64>> {
+        match self.max_sidecar_mb {
+            Some(mb) if mb.is_nan() || mb <= 0.0 => {
+                Err(SidecarError::ProcessingError(format!("max_sidecar_mb must be positive, got {}", mb)))
+            }
+            mb => Ok(mb.map(|mb| (mb * (1u64 << 20) as f64) as u64)),
+        }
+    }
+
+    /// The configured sidecar name patterns, in the order listed; operations must be known
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn sidecar_patterns(&self) -> Result<Option<SidecarPatterns>> {
+        self.sidecar_patterns
+            .as_ref()
+            .map(|patterns| {
+                patterns
+                    .iter()
+                    .try_fold(SidecarPatterns::default(), |patterns, rule| {
+                        patterns.with(&rule.template, OperationType::from_str(&rule.operation))
+                    })
+            })
+            .transpose()
+    }
+
+    /// The configured classifier rules, in order; each needs a known operation and one condition
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn operation_rules(&self) -> Result<Vec<ClassifierRule>> {
+        let rules = self.operation_rules.as_deref().unwrap_or_default();
+        rules
+            .iter()
+            .map(|rule| {
+                let invalid = |reason: &str| {
+                    SidecarError::ProcessingError(format!("Invalid operation rule for '{}': {}", rule.operation, reason))
+                };
+                let operation = OperationType::from_str(&rule.operation);
+                if operation == OperationType::Unknown {
+                    return Err(invalid("unknown operation"));
+                }
+                match (&rule.key, &rule.pointer, &rule.tool_name) {
+                    (Some(key), None, None) => Ok(ClassifierRule::key(key.clone(), operation)),
+                    (None, Some(pointer), None) => ClassifierRule::pointer(pointer, operation),
+                    (None, None, Some(pattern)) => ClassifierRule::tool_name(pattern, operation),
+                    _ => Err(invalid("needs exactly one of key, pointer and tool_name")),
+                }
+            })
+            .collect()
+    }
+
+    /// The configured symlink policy, if any
+    pub fn symlink_policy(&self) -> Result<Option<SymlinkPolicy>> {
+        self.symlink_policy.as_deref().map(str::parse).transpose()
+    }
+
+    /// The configured merge strategy, if any
+    pub fn merge_strategy(&self) -> Result<Option<MergeStrategy>> {
+        self.merge_strategy.as_deref().map(str::parse).transpose()
+    }
+
+    /// The configured sidecar format, if any
+    pub fn sidecar_format(&self) -> Result<Option<SidecarFormat>> {
+        self.format
+            .as_deref()
+            .map(|name| {
+                SidecarFormat::from_name(name).ok_or_else(|| {
+                    SidecarError::ProcessingError(format!(
+                        "Unsupported format: {}. Supported formats: json, bin, rkyv",
+                        name
+                    ))
+                })
+            })
+            .transpose()
+    }
+}
+
+/// Parsed configuration file
+///
+/// ```toml
+/// [defaults]
+/// workers = 32
+/// max_files_per_sec = 500
+///
+/// [profile.training]
+/// format = "bin"
+/// max_invalid_percent = 1.0
+///
+/// [profile.archive]
+/// format = "json"
+/// fail_on_invalid = true
+///
+/// [profile.nightly]
+/// background = true
+///
+/// [profile.vault]
+/// read_only = true
+///
+/// [profile.phones]
+/// image_extensions = ["heic", "jpg"]
+/// merge_strategy = "deep"
+/// skip_hidden = true
+///
+/// [profile.production]
+/// audit_log = "/var/log/sportball/sidecar-mutations.jsonl"
+///
+/// [[defaults.operation_rules]]
+/// operation = "object_detection"
+/// tool_name = "^detectron2"
+///
+/// [[defaults.sidecar_patterns]]
+/// template = "{stem}_faces.json"
+/// operation = "face_detection"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default)]
+    pub defaults: Profile,
+    #[serde(default)]
+    pub profile: BTreeMap<String, Profile>,
+}
+
+impl Config {
+    /// Read and parse a config file
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| std::io::Error::new(e.kind(), format!("Cannot read config {}: {}", path.display(), e)))?;
+        toml::from_str(&text).map_err(|e| SidecarError::Schema(format!("Invalid config {}: {}", path.display(), e)))
+    }
+
+    /// Find the config file to use: `explicit`, then `SPORTBALL_SIDECAR_CONFIG`, then
+    /// `sportball-sidecar.toml` in the working directory. `None` if there is none.
+    pub fn discover(explicit: Option<&Path>) -> Result<Option<Self>> {
+        let path = explicit
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::var_os(CONFIG_ENV).map(PathBuf::from));
+
+        match path {
+            Some(path) => Self::load(&path).map(Some),
+            None if Path::new(DEFAULT_CONFIG_FILE).is_file() => Self::load(Path::new(DEFAULT_CONFIG_FILE)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Effective settings for `name` (or `[defaults]` alone when `None`)
+    pub fn resolve(&self, name: Option<&str>) -> Result<Profile> {
+        let Some(name) = name else {
+            return Ok(self.defaults.clone());
+        };
+
+        self.profile
+            .get(name)
+            .map(|profile| self.defaults.overlay(profile))
+            .ok_or_else(|| {
+                let known: Vec<&str> = self.profile.keys().map(String::as_str).collect();
+                SidecarError::ProcessingError(format!(
+                    "Unknown profile '{}'. Configured profiles: {}",
+                    name,
+                    if known.is_empty() { "none".to_string() } else { known.join(", ") }
+                ))
+            })
+    }
+}
@@ -0,0 +1,280 @@
+/*
+ * Context: Self-contained HTML dashboard of a single-pass scan report
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: chrono
+ *
+ * `report --output report.html` renders a `ScanReport` as one HTML page with its styles
+ * inline and no scripts, fonts or images to fetch, so it can be attached to a ticket and
+ * opened anywhere. Charts are plain bars sized with CSS widths; histograms bucket values by
+ * powers of two so a few huge sidecars do not flatten the rest.
+ */
+
+use crate::sidecar::pipeline::ScanReport;
+use crate::sidecar::types::{Result, ValidationResult};
+use crate::utils::paths;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+/// Validation failures listed before the rest are only counted
+pub const MAX_FAILURE_ROWS: usize = 500;
+
+const STYLE: &str = "\
+body{font-family:system-ui,sans-serif;margin:2em;color:#222;background:#fafafa}\
+h1{font-size:1.5em;margin-bottom:.2em}h2{font-size:1.15em;margin-top:2em;border-bottom:1px solid #ddd}\
+.meta{color:#666;font-size:.9em}\
+.cards{display:flex;flex-wrap:wrap;gap:1em;margin-top:1em}\
+.card{background:#fff;border:1px solid #ddd;border-radius:6px;padding:.8em 1.2em;min-width:9em}\
+.card .value{font-size:1.6em;font-weight:600}.card .label{color:#666;font-size:.85em}\
+table{border-collapse:collapse;background:#fff;margin-top:.5em}\
+th,td{border:1px solid #ddd;padding:.3em .6em;text-align:left;font-size:.9em;vertical-align:top}\
+th{background:#f0f0f0}td.num{text-align:right;font-variant-numeric:tabular-nums}\
+td.path{font-family:monospace;word-break:break-all}\
+.bar{background:#e8e8e8;width:24em;height:1em;border-radius:3px}\
+.bar span{display:block;height:100%;background:#4a7fc1;border-radius:3px}\
+";
+
+/// Write `report` as a self-contained HTML page
+pub fn write_html<W: Write>(report: &ScanReport, mut writer: W) -> Result<()> {
+    let directory = escape_html(&paths::encode_path(&report.directory));
+    writeln!(writer, "<!DOCTYPE html>")?;
+    writeln!(writer, "<html lang=\"en\"><head><meta charset=\"utf-8\">")?;
+    writeln!(writer, "<title>Sidecar report: {}</title>", directory)?;
+    writeln!(writer, "<style>{}</style></head><body>", STYLE)?;
+    writeln!(writer, "<h1>Sidecar report</h1>")?;
+    writeln!(
+        writer,
+        "<p class=\"meta\">{} &middot; generated {} &middot; {} sidecar files read in one pass</p>",
+        directory,
+        chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC"),
+        report.sidecars_read
+    )?;
+
+    write_cards(report, &mut writer)?;
+    if let Some(stats) = &report.statistics {
+        writeln!(writer, "<h2>Operations</h2>")?;
+        writeln!(writer, "<table><tr><th>Operation</th><th>Sidecars</th><th>Success rate</th><th>Avg processing time</th><th>Avg size</th></tr>")?;
+        let operations: BTreeMap<_, _> = stats.operation_counts.iter().collect();
+        for (operation, count) in operations {
+            writeln!(
+                writer,
+                "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+                escape_html(operation),
+                count,
+                stats.success_rate_percentages.get(operation).map(|rate| format!("{:.1}%", rate)).unwrap_or_default(),
+                stats.avg_processing_times.get(operation).map(|time| format!("{:.3} s", time)).unwrap_or_default(),
+                stats.avg_data_sizes.get(operation).map(|size| format_size(*size as u64)).unwrap_or_default(),
+            )?;
+        }
+        writeln!(writer, "</table>")?;
+    }
+
+    if let Some(coverage) = &report.coverage {
+        writeln!(writer, "<h2>Coverage of {} images</h2>", coverage.total_images)?;
+        let rows = coverage.operations.iter().map(|operation| {
+            (operation.operation.clone(), operation.coverage_percentage, format!("{}/{} ({:.1}%)", operation.covered, coverage.total_images, operation.coverage_percentage))
+        });
+        write_bars(&mut writer, "Operation", rows.collect(), 100.0)?;
+    }
+
+    if let Some(formats) = &report.formats {
+        writeln!(writer, "<h2>Format distribution</h2>")?;
+        let formats: BTreeMap<String, u32> = formats.iter().map(|(format, count)| (format!(".{}", format.extension()), *count)).collect();
+        write_counts(&mut writer, "Format", &formats)?;
+    }
+
+    if let Some(stats) = &report.statistics {
+        if !stats.class_distribution.counts.is_empty() {
+            writeln!(writer, "<h2>Detections per class</h2>")?;
+            write_counts(&mut writer, "Class", &stats.class_distribution.counts)?;
+            if let Some(imbalance) = &stats.class_distribution.imbalance {
+                writeln!(
+                    writer,
+                    "<p class=\"meta\">Imbalanced: {} ({}) outnumbers {} ({}) {:.1} to 1</p>",
+                    escape_html(&imbalance.majority),
+                    imbalance.majority_count,
+                    escape_html(&imbalance.minority),
+                    imbalance.minority_count,
+                    imbalance.ratio
+                )?;
+            }
+        }
+    }
+
+    write_histograms(report, &mut writer)?;
+    if let Some(results) = &report.validation {
+        write_failures(results, &mut writer)?;
+    }
+
+    writeln!(writer, "</body></html>")?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_cards<W: Write>(report: &ScanReport, writer: &mut W) -> Result<()> {
+    let mut cards = Vec::new();
+    if let Some(stats) = &report.statistics {
+        cards.push(("Images".to_string(), stats.total_images.to_string()));
+        cards.push(("Sidecars".to_string(), stats.total_sidecars.to_string()));
+        cards.push(("Images with sidecars".to_string(), format!("{:.1}%", stats.coverage_percentage)));
+    }
+    if let Some(summary) = report.validation_summary() {
+        cards.push(("Valid sidecars".to_string(), format!("{}/{}", summary.valid_files, summary.total_files)));
+        cards.push(("Invalid sidecars".to_string(), summary.invalid_files.to_string()));
+    }
+    if cards.is_empty() {
+        return Ok(());
+    }
+    writeln!(writer, "<div class=\"cards\">")?;
+    for (label, value) in cards {
+        writeln!(writer, "<div class=\"card\"><div class=\"value\">{}</div><div class=\"label\">{}</div></div>", value, label)?;
+    }
+    writeln!(writer, "</div>")?;
+    Ok(())
+}
+
+/// A table of labelled bars; `rows` hold the label, the value the bar shows and its caption
+fn write_bars<W: Write>(writer: &mut W, heading: &str, rows: Vec<(String, f64, String)>, scale: f64) -> Result<()> {
+    writeln!(writer, "<table><tr><th>{}</th><th></th><th></th></tr>", heading)?;
+    for (label, value, caption) in rows {
+        let width = if scale > 0.0 { (value / scale * 100.0).clamp(0.0, 100.0) } else { 0.0 };
+        writeln!(
+            writer,
+            "<tr><td>{}</td><td><div class=\"bar\"><span style=\"width:{:.1}%\"></span></div></td><td class=\"num\">{}</td></tr>",
+            escape_html(&label),
+            width,
+            caption
+        )?;
+    }
+    writeln!(writer, "</table>")?;
+    Ok(())
+}
+
+/// Bars of `counts`, scaled to the largest
+fn write_counts<W: Write>(writer: &mut W, heading: &str, counts: &BTreeMap<String, u32>) -> Result<()> {
+    let largest = counts.values().copied().max().unwrap_or(0) as f64;
+    let rows = counts.iter().map(|(label, count)| (label.clone(), *count as f64, count.to_string())).collect();
+    write_bars(writer, heading, rows, largest)
+}
+
+fn write_histograms<W: Write>(report: &ScanReport, writer: &mut W) -> Result<()> {
+    // Validation reads every selected file; statistics only the matched ones
+    let sizes: Vec<u64> = match (&report.validation, &report.statistics) {
+        (Some(results), _) => results.iter().map(|result| result.file_size).collect(),
+        (None, Some(stats)) => stats.sidecars.iter().filter(|sidecar| sidecar.is_valid).map(|sidecar| sidecar.data_size).collect(),
+        (None, None) => Vec::new(),
+    };
+    let detections: Vec<u64> = report
+        .validation
+        .iter()
+        .flatten()
+        .filter(|result| result.is_valid)
+        .map(|result| result.detection_count as u64)
+        .collect();
+    let times: Vec<u64> = report
+        .statistics
+        .iter()
+        .flat_map(|stats| &stats.sidecars)
+        .filter_map(|sidecar| sidecar.processing_time)
+        .map(|seconds| (seconds * 1000.0).round() as u64)
+        .collect();
+
+    for (title, values, unit) in [
+        ("Sidecar sizes", sizes, format_size as fn(u64) -> String),
+        ("Detections per sidecar", detections, |count: u64| count.to_string()),
+        ("Processing time per sidecar", times, |millis: u64| format!("{} ms", millis)),
+    ] {
+        if values.is_empty() {
+            continue;
+        }
+        writeln!(writer, "<h2>{}</h2>", title)?;
+        let buckets = log2_histogram(&values);
+        let largest = buckets.iter().map(|(_, _, count)| *count).max().unwrap_or(0) as f64;
+        let rows = buckets
+            .into_iter()
+            .map(|(lower, upper, count)| {
+                let label = if lower == upper { unit(lower) } else { format!("{} – {}", unit(lower), unit(upper)) };
+                (label, count as f64, count.to_string())
+            })
+            .collect();
+        write_bars(writer, "Range", rows, largest)?;
+    }
+    Ok(())
+}
+
+fn write_failures<W: Write>(results: &[ValidationResult], writer: &mut W) -> Result<()> {
+    let failures: Vec<&ValidationResult> = results.iter().filter(|result| !result.is_valid).collect();
+    writeln!(writer, "<h2>Validation failures ({})</h2>", failures.len())?;
+    if failures.is_empty() {
+        writeln!(writer, "<p>Every sidecar validated.</p>")?;
+        return Ok(());
+    }
+    writeln!(writer, "<table><tr><th>Sidecar</th><th>Operation</th><th>Size</th><th>Error</th></tr>")?;
+    for result in failures.iter().take(MAX_FAILURE_ROWS) {
+        writeln!(
+            writer,
+            "<tr><td class=\"path\">{}</td><td>{}</td><td class=\"num\">{}</td><td>{}</td></tr>",
+            escape_html(&paths::encode_path(&result.file_path)),
+            result.operation_type.as_ref().map(|operation| escape_html(operation.as_str())).unwrap_or_default(),
+            format_size(result.file_size),
+            escape_html(result.error.as_deref().unwrap_or("Sidecar failed validation")),
+        )?;
+    }
+    writeln!(writer, "</table>")?;
+    if failures.len() > MAX_FAILURE_ROWS {
+        writeln!(writer, "<p class=\"meta\">and {} more</p>", failures.len() - MAX_FAILURE_ROWS)?;
+    }
+    Ok(())
+}
+
+/// Counts of `values` in buckets `0`, `1`, `2–3`, `4–7`, ..., from the smallest value's
+/// bucket to the largest's, as (lower, upper, count)
+fn log2_histogram(values: &[u64]) -> Vec<(u64, u64, usize)> {
+    let bucket = |value: u64| (u64::BITS - value.leading_zeros()) as usize;
+    let mut counts = [0usize; u64::BITS as usize + 1];
+    for value in values {
+        counts[bucket(*value)] += 1;
+    }
+    let first = values.iter().map(|value| bucket(*value)).min().unwrap_or(0);
+    let last = values.iter().map(|value| bucket(*value)).max().unwrap_or(0);
+    (first..=last)
+        .map(|index| {
+            let (lower, upper) = match index {
+                0 => (0, 0),
+                index => (1u64 << (index - 1), ((1u128 << index) - 1) as u64),
+            };
+            (lower, upper, counts[index])
+        })
+        .collect()
+}
+
+/// `bytes` in B, KiB, MiB or GiB
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
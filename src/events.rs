@@ -0,0 +1,400 @@
+/*
+ * Context: Sidecar lifecycle events delivered to webhooks, Unix sockets, Kafka and NATS in batches
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: tokio, serde_json, chrono; ureq, rdkafka and async-nats for the webhooks, kafka and nats features
+ */
+
+use crate::sidecar::types::{Result, SidecarError};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Version of the event layout, bumped on incompatible changes
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+/// What happened to a sidecar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Created,
+    Updated,
+    Deleted,
+}
+
+/// One sidecar change, as published to every sink
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SidecarEvent {
+    pub kind: EventKind,
+    /// Image the sidecar belongs to; `None` when it is gone, e.g. orphan cleanup
+    #[serde(with = "crate::utils::paths::lossless_opt")]
+    pub image_path: Option<PathBuf>,
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub sidecar_path: PathBuf,
+    /// Operation written by the change; `None` for deletions
+    pub operation: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl SidecarEvent {
+    pub fn new(kind: EventKind, image_path: Option<&Path>, sidecar_path: &Path, operation: Option<&str>) -> Self {
+        Self {
+            kind,
+            image_path: image_path.map(Path::to_path_buf),
+            sidecar_path: sidecar_path.to_path_buf(),
+            operation: operation.map(str::to_string),
+            timestamp: Utc::now(),
+        }
+    }
+}
+
+/// Body of a webhook request: `{"schema_version": 1, "events": [...]}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventBatch {
+    pub schema_version: u32,
+    pub events: Vec<SidecarEvent>,
+}
+
+impl EventBatch {
+    pub fn new(events: Vec<SidecarEvent>) -> Self {
+        Self { schema_version: EVENT_SCHEMA_VERSION, events }
+    }
+}
+
+/// Destination for batches of events
+///
+/// `deliver` blocks; the notifier runs it on the blocking pool and retries it on error.
+pub trait EventSink: Send + Sync {
+    /// Human-readable destination for log messages
+    fn describe(&self) -> String;
+
+    fn deliver(&self, batch: &EventBatch) -> Result<()>;
+}
+
+/// Writes each event as one JSON line to a Unix socket another process listens on
+#[cfg(unix)]
+#[derive(Debug, Clone)]
+pub struct UnixSocketSink {
+    path: PathBuf,
+}
+
+#[cfg(unix)]
+impl UnixSocketSink {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[cfg(unix)]
+impl EventSink for UnixSocketSink {
+    fn describe(&self) -> String {
+        format!("unix:{}", self.path.display())
+    }
+
+    fn deliver(&self, batch: &EventBatch) -> Result<()> {
+        use std::io::Write;
+
+        let mut lines = Vec::new();
+        for event in &batch.events {
+            serde_json::to_writer(&mut lines, event)?;
+            lines.push(b'\n');
+        }
+        let mut stream = std::os::unix::net::UnixStream::connect(&self.path)?;
+        stream.write_all(&lines)?;
+        Ok(())
+    }
+}
+
+/// POSTs each batch as JSON to an HTTP(S) endpoint
+#[cfg(feature = "webhooks")]
+#[derive(Debug, Clone)]
+pub struct WebhookSink {
+    url: String,
+    headers: Vec<(String, String)>,
+    agent: ureq::Agent,
+}
+
+#[cfg(feature = "webhooks")]
+impl WebhookSink {
+    /// Requests time out after 10 seconds; a non-2xx response counts as a failed delivery
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            headers: Vec::new(),
+            agent: ureq::AgentBuilder::new().timeout(Duration::from_secs(10)).build(),
+        }
+    }
+
+    /// Send an extra header with every request, e.g. for authentication
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+#[cfg(feature = "webhooks")]
+impl EventSink for WebhookSink {
+    fn describe(&self) -> String {
+        self.url.clone()
+    }
+
+    fn deliver(&self, batch: &EventBatch) -> Result<()> {
+        let request = self.headers.iter().fold(self.agent.post(&self.url), |request, (name, value)| {
+            request.set(name, value)
+        });
+        request
+            .send_json(batch)
+            .map_err(|e| SidecarError::ProcessingError(format!("Webhook {} failed: {}", self.url, e)))?;
+        Ok(())
+    }
+}
+
+/// Runs an async client call from `deliver`, which the notifier executes on its blocking pool
+#[cfg(any(feature = "kafka", feature = "nats"))]
+fn block_on<F: std::future::Future>(future: F) -> Result<F::Output> {
+    let handle = tokio::runtime::Handle::try_current()
+        .map_err(|e| SidecarError::ProcessingError(format!("Event sink needs a tokio runtime: {}", e)))?;
+    Ok(handle.block_on(future))
+}
+
+/// Produces one Kafka message per event to a topic, keyed by sidecar path
+///
+/// The payload is the event's JSON, as found in a webhook batch, and the `schema_version`
+/// header carries `EVENT_SCHEMA_VERSION`. Keying by sidecar path keeps the changes to one
+/// sidecar in order within a partition. A failed batch is resent whole, so consumers may
+/// see an event more than once.
+#[cfg(feature = "kafka")]
+pub struct KafkaSink {
+    topic: String,
+    producer: rdkafka::producer::FutureProducer,
+}
+
+#[cfg(feature = "kafka")]
+impl KafkaSink {
+    /// `brokers` is a comma-separated `host:port` list; messages not delivered within 10 seconds fail
+    pub fn new(brokers: &str, topic: impl Into<String>) -> Result<Self> {
+        let mut config = rdkafka::ClientConfig::new();
+        config.set("bootstrap.servers", brokers).set("message.timeout.ms", "10000");
+        Self::with_config(&config, topic)
+    }
+
+    /// Producer built from arbitrary librdkafka properties, e.g. for TLS or SASL
+    pub fn with_config(config: &rdkafka::ClientConfig, topic: impl Into<String>) -> Result<Self> {
+        let producer = config
+            .create()
+            .map_err(|e| SidecarError::ProcessingError(format!("Kafka producer setup failed: {}", e)))?;
+        Ok(Self { topic: topic.into(), producer })
+    }
+}
+
+#[cfg(feature = "kafka")]
+impl std::fmt::Debug for KafkaSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KafkaSink").field("topic", &self.topic).finish()
+    }
+}
+
+#[cfg(feature = "kafka")]
+impl EventSink for KafkaSink {
+    fn describe(&self) -> String {
+        format!("kafka:{}", self.topic)
+    }
+
+    fn deliver(&self, batch: &EventBatch) -> Result<()> {
+        use rdkafka::message::{Header, OwnedHeaders};
+        use rdkafka::producer::FutureRecord;
+
+        let version = batch.schema_version.to_string();
+        let messages = batch
+            .events
+            .iter()
+            .map(|event| Ok((crate::utils::paths::encode_path(&event.sidecar_path).into_owned(), serde_json::to_vec(event)?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let sends = messages.iter().map(|(key, payload)| {
+            let record = FutureRecord::to(&self.topic)
+                .key(key)
+                .payload(payload)
+                .headers(OwnedHeaders::new().insert(Header { key: "schema_version", value: Some(version.as_str()) }));
+            self.producer.send(record, Duration::from_secs(10))
+        });
+        for outcome in block_on(futures::future::join_all(sends))? {
+            outcome.map_err(|(e, _)| SidecarError::ProcessingError(format!("Kafka topic {} failed: {}", self.topic, e)))?;
+        }
+        Ok(())
+    }
+}
+
+/// Publishes one NATS message per event on `<prefix>.created`, `.updated` or `.deleted`
+///
+/// The payload is the event's JSON, as found in a webhook batch, and the `Schema-Version`
+/// header carries `EVENT_SCHEMA_VERSION`.
+#[cfg(feature = "nats")]
+#[derive(Debug, Clone)]
+pub struct NatsSink {
+    prefix: String,
+    client: async_nats::Client,
+}
+
+#[cfg(feature = "nats")]
+impl NatsSink {
+    /// Connect to `url` (e.g. `nats://localhost:4222`); the client reconnects on its own afterwards
+    pub async fn connect(url: &str, subject_prefix: impl Into<String>) -> Result<Self> {
+        let client = async_nats::connect(url)
+            .await
+            .map_err(|e| SidecarError::ProcessingError(format!("NATS connection to {} failed: {}", url, e)))?;
+        Ok(Self::new(client, subject_prefix))
+    }
+
+    /// Publish through an already configured client
+    pub fn new(client: async_nats::Client, subject_prefix: impl Into<String>) -> Self {
+        Self { prefix: subject_prefix.into(), client }
+    }
+
+    /// Subject an event is published on
+    pub fn subject(&self, kind: EventKind) -> String {
+        let kind = match kind {
+            EventKind::Created => "created",
+            EventKind::Updated => "updated",
+            EventKind::Deleted => "deleted",
+        };
+        format!("{}.{}", self.prefix, kind)
+    }
+}
+
+#[cfg(feature = "nats")]
+impl EventSink for NatsSink {
+    fn describe(&self) -> String {
+        format!("nats:{}.*", self.prefix)
+    }
+
+    fn deliver(&self, batch: &EventBatch) -> Result<()> {
+        let nats_error = |e: &dyn std::fmt::Display| SidecarError::ProcessingError(format!("NATS publish to {}.* failed: {}", self.prefix, e));
+
+        block_on(async {
+            for event in &batch.events {
+                let mut headers = async_nats::HeaderMap::new();
+                headers.insert("Schema-Version", batch.schema_version.to_string());
+                self.client
+                    .publish_with_headers(self.subject(event.kind), headers, serde_json::to_vec(event)?.into())
+                    .await
+                    .map_err(|e| nats_error(&e))?;
+            }
+            // Publishing only buffers; the flush reports whether the server got the messages
+            self.client.flush().await.map_err(|e| nats_error(&e))
+        })?
+    }
+}
+
+/// Batching and retry behaviour of a `Notifier`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NotifierOptions {
+    /// Most events per delivery
+    pub max_batch: usize,
+    /// Longest an event waits for its batch to fill up
+    pub max_delay: Duration,
+    /// Retries after the first failed delivery before the batch is dropped for that sink
+    pub max_retries: u32,
+    /// Wait before the first retry; doubles on each further retry
+    pub initial_backoff: Duration,
+}
+
+impl Default for NotifierOptions {
+    fn default() -> Self {
+        Self {
+            max_batch: 100,
+            max_delay: Duration::from_secs(1),
+            max_retries: 5,
+            initial_backoff: Duration::from_millis(500),
+        }
+    }
+}
+
+/// Fans sidecar events out to sinks from a background task
+///
+/// `notify` never blocks or fails; events are batched and delivered to every sink, with
+/// retries. Must be started inside a tokio runtime. Call `shutdown` to flush pending events.
+pub struct Notifier {
+    sender: mpsc::UnboundedSender<SidecarEvent>,
+    task: JoinHandle<()>,
+    sinks: Vec<String>,
+}
+
+impl std::fmt::Debug for Notifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Notifier").field("sinks", &self.sinks).finish()
+    }
+}
+
+impl Notifier {
+    pub fn start(sinks: Vec<Arc<dyn EventSink>>, options: NotifierOptions) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let descriptions = sinks.iter().map(|sink| sink.describe()).collect();
+        let task = tokio::spawn(run(receiver, sinks, options));
+        Self { sender, task, sinks: descriptions }
+    }
+
+    /// Queue an event for delivery
+    pub fn notify(&self, event: SidecarEvent) {
+        // The task only stops after `shutdown`, which consumes the notifier
+        let _ = self.sender.send(event);
+    }
+
+    /// Deliver everything queued so far, then stop
+    pub async fn shutdown(self) -> Result<()> {
+        drop(self.sender);
+        Ok(self.task.await?)
+    }
+}
+
+async fn run(mut receiver: mpsc::UnboundedReceiver<SidecarEvent>, sinks: Vec<Arc<dyn EventSink>>, options: NotifierOptions) {
+    let max_batch = options.max_batch.max(1);
+
+    while let Some(first) = receiver.recv().await {
+        let mut events = vec![first];
+        let deadline = tokio::time::Instant::now() + options.max_delay;
+        while events.len() < max_batch {
+            match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                Ok(Some(event)) => events.push(event),
+                // Channel closed or batch window over
+                Ok(None) | Err(_) => break,
+            }
+        }
+
+        let batch = Arc::new(EventBatch::new(events));
+        let deliveries = sinks.iter().map(|sink| deliver_with_retry(Arc::clone(sink), Arc::clone(&batch), options));
+        futures::future::join_all(deliveries).await;
+    }
+}
+
+async fn deliver_with_retry(sink: Arc<dyn EventSink>, batch: Arc<EventBatch>, options: NotifierOptions) {
+    let mut backoff = options.initial_backoff;
+    for attempt in 0..=options.max_retries {
+        let (sink_ref, batch_ref) = (Arc::clone(&sink), Arc::clone(&batch));
+        let outcome = tokio::task::spawn_blocking(move || sink_ref.deliver(&batch_ref))
+            .await
+            .map_err(SidecarError::from)
+            .and_then(|delivered| delivered);
+
+        match outcome {
+            Ok(()) => return,
+            Err(e) if attempt < options.max_retries => {
+                tracing::debug!("Delivery to {} failed (attempt {}): {}", sink.describe(), attempt + 1, e);
+                tokio::time::sleep(backoff).await;
+                backoff = backoff.saturating_mul(2);
+            }
+            Err(e) => tracing::warn!(
+                "Dropping {} events for {} after {} attempts: {}",
+                batch.events.len(),
+                sink.describe(),
+                attempt + 1,
+                e
+            ),
+        }
+    }
+}
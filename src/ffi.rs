@@ -0,0 +1,244 @@
+/*
+ * Context: C ABI over the core sidecar operations for in-process use from C and C++
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: tokio, serde_json; header generated by cbindgen (include/image_sidecar_rust.h)
+ */
+
+//! Every function returns a [`SidecarStatus`]; on failure the message is available from
+//! [`sidecar_last_error`] on the same thread. Strings handed out by the library are
+//! NUL-terminated JSON and must be released with [`sidecar_string_free`].
+
+use crate::sidecar::types::{OperationType, SidecarError};
+use crate::ImageSidecar;
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::path::Path;
+use std::ptr;
+use tokio::runtime::Runtime;
+
+/// Result of every FFI call; `SIDECAR_STATUS_OK` is zero
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SidecarStatus {
+    Ok = 0,
+    /// A required pointer argument was NULL
+    NullArgument = 1,
+    /// A string argument was not valid UTF-8
+    InvalidUtf8 = 2,
+    /// The data argument was not valid JSON
+    InvalidJson = 3,
+    Io = 4,
+    NotFound = 5,
+    Format = 6,
+    Schema = 7,
+    Validation = 8,
+    Lock = 9,
+    Storage = 10,
+    Cancelled = 11,
+    Processing = 12,
+    /// The library panicked; the handle should not be used again
+    Panic = 13,
+    /// The handle is read-only and the call would have written
+    ReadOnly = 14,
+    /// A sidecar was over the maximum sidecar size and was not read
+    TooLarge = 15,
+}
+
+impl From<&SidecarError> for SidecarStatus {
+    fn from(error: &SidecarError) -> Self {
+        match error {
+            SidecarError::Io(_) => SidecarStatus::Io,
+            SidecarError::SidecarNotFound(_) | SidecarError::ImageNotFound(_) => SidecarStatus::NotFound,
+            SidecarError::Json(_) | SidecarError::SerializationError(_) | SidecarError::Format(_) => SidecarStatus::Format,
+            SidecarError::Schema(_) => SidecarStatus::Schema,
+            SidecarError::ValidationFailed(_) | SidecarError::InvalidOperationType(_) => SidecarStatus::Validation,
+            SidecarError::Lock(_) => SidecarStatus::Lock,
+            SidecarError::Storage(_) => SidecarStatus::Storage,
+            SidecarError::Cancelled => SidecarStatus::Cancelled,
+            SidecarError::ReadOnly(_) => SidecarStatus::ReadOnly,
+            SidecarError::TooLarge { .. } => SidecarStatus::TooLarge,
+            SidecarError::SymlinkResolutionFailed(_) | SidecarError::ProcessingError(_) => SidecarStatus::Processing,
+        }
+    }
+}
+
+/// Opaque manager handle owning an `ImageSidecar` and the runtime that drives it
+pub struct SidecarHandle {
+    sidecar: ImageSidecar,
+    runtime: Runtime,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    // Interior NULs would truncate the message on the C side anyway
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+fn fail(status: SidecarStatus, message: impl Into<String>) -> SidecarStatus {
+    set_last_error(message.into());
+    status
+}
+
+/// Run `body`, recording its error message and turning panics into `SidecarStatus::Panic`
+fn guard(body: impl FnOnce() -> Result<(), (SidecarStatus, String)>) -> SidecarStatus {
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(body)) {
+        Ok(Ok(())) => SidecarStatus::Ok,
+        Ok(Err((status, message))) => fail(status, message),
+        Err(_) => fail(SidecarStatus::Panic, "panic inside image_sidecar_rust"),
+    }
+}
+
+fn library_error(context: &str, error: SidecarError) -> (SidecarStatus, String) {
+    (SidecarStatus::from(&error), format!("{}: {}", context, error))
+}
+
+/// Borrow a C string argument as `&str`
+///
+/// # Safety
+/// `value` must be NULL or point to a NUL-terminated string valid for `'a`.
+unsafe fn str_arg<'a>(value: *const c_char, name: &str) -> Result<&'a str, (SidecarStatus, String)> {
+    if value.is_null() {
+        return Err((SidecarStatus::NullArgument, format!("{} is NULL", name)));
+    }
+    CStr::from_ptr(value)
+        .to_str()
+        .map_err(|e| (SidecarStatus::InvalidUtf8, format!("{} is not valid UTF-8: {}", name, e)))
+}
+
+/// Hand a JSON value to the caller through `out`
+///
+/// # Safety
+/// `out` must be NULL or valid for a pointer write.
+unsafe fn write_json(out: *mut *mut c_char, value: &impl serde::Serialize) -> Result<(), (SidecarStatus, String)> {
+    if out.is_null() {
+        return Err((SidecarStatus::NullArgument, "out is NULL".to_string()));
+    }
+    let json = serde_json::to_string(value).map_err(|e| library_error("Serialization failed", e.into()))?;
+    let json = CString::new(json).map_err(|e| (SidecarStatus::Format, format!("Result contains NUL: {}", e)))?;
+    *out = json.into_raw();
+    Ok(())
+}
+
+fn handle_ref<'a>(handle: *const SidecarHandle) -> Result<&'a SidecarHandle, (SidecarStatus, String)> {
+    // SAFETY: callers pass a pointer obtained from `sidecar_manager_new` and not yet freed
+    unsafe { handle.as_ref() }.ok_or_else(|| (SidecarStatus::NullArgument, "handle is NULL".to_string()))
+}
+
+/// Create a manager; `max_workers` of 0 uses the available parallelism.
+/// Returns NULL on failure (see `sidecar_last_error`). Release with `sidecar_manager_free`.
+#[no_mangle]
+pub extern "C" fn sidecar_manager_new(max_workers: usize) -> *mut SidecarHandle {
+    let mut handle = ptr::null_mut();
+    let status = guard(|| {
+        let runtime = Runtime::new().map_err(|e| library_error("Failed to create runtime", e.into()))?;
+        let sidecar = ImageSidecar::new((max_workers > 0).then_some(max_workers));
+        handle = Box::into_raw(Box::new(SidecarHandle { sidecar, runtime }));
+        Ok(())
+    });
+    if status == SidecarStatus::Ok { handle } else { ptr::null_mut() }
+}
+
+/// Release a manager created by `sidecar_manager_new`; NULL is ignored
+///
+/// # Safety
+/// `handle` must come from `sidecar_manager_new` and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn sidecar_manager_free(handle: *mut SidecarHandle) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+/// Merge the JSON object `data_json` into the image's sidecar under `operation`
+///
+/// # Safety
+/// `handle` must be live; string arguments must be NUL-terminated.
+#[no_mangle]
+pub unsafe extern "C" fn sidecar_save(
+    handle: *const SidecarHandle,
+    image_path: *const c_char,
+    operation: *const c_char,
+    data_json: *const c_char,
+) -> SidecarStatus {
+    guard(|| {
+        let handle = handle_ref(handle)?;
+        let image_path = str_arg(image_path, "image_path")?;
+        let operation = OperationType::from_str(str_arg(operation, "operation")?);
+        let data = serde_json::from_str(str_arg(data_json, "data_json")?)
+            .map_err(|e| (SidecarStatus::InvalidJson, format!("Invalid data JSON: {}", e)))?;
+
+        handle
+            .runtime
+            .block_on(handle.sidecar.save_data(Path::new(image_path), operation, data))
+            .map_err(|e| library_error("Sidecar save failed", e))?;
+        Ok(())
+    })
+}
+
+/// Read the image's sidecar as JSON into `*out` (`{}` when there is none)
+///
+/// # Safety
+/// `handle` must be live; `image_path` must be NUL-terminated; `out` must be writable.
+/// Release `*out` with `sidecar_string_free`.
+#[no_mangle]
+pub unsafe extern "C" fn sidecar_load(
+    handle: *const SidecarHandle,
+    image_path: *const c_char,
+    out: *mut *mut c_char,
+) -> SidecarStatus {
+    guard(|| {
+        let handle = handle_ref(handle)?;
+        let image_path = str_arg(image_path, "image_path")?;
+        let data = handle
+            .runtime
+            .block_on(handle.sidecar.read_data(Path::new(image_path)))
+            .map_err(|e| library_error("Sidecar read failed", e))?;
+        write_json(out, &data)
+    })
+}
+
+/// Validate every sidecar under `directory`, writing a JSON array of results into `*out`
+///
+/// # Safety
+/// `handle` must be live; `directory` must be NUL-terminated; `out` must be writable.
+/// Release `*out` with `sidecar_string_free`.
+#[no_mangle]
+pub unsafe extern "C" fn sidecar_validate(
+    handle: *const SidecarHandle,
+    directory: *const c_char,
+    out: *mut *mut c_char,
+) -> SidecarStatus {
+    guard(|| {
+        let handle = handle_ref(handle)?;
+        let directory = str_arg(directory, "directory")?;
+        let results = handle
+            .runtime
+            .block_on(handle.sidecar.validate_sidecars(Path::new(directory)))
+            .map_err(|e| library_error("Validation failed", e))?;
+        write_json(out, &results)
+    })
+}
+
+/// Release a string returned through an `out` parameter; NULL is ignored
+///
+/// # Safety
+/// `value` must come from this library and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn sidecar_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        drop(CString::from_raw(value));
+    }
+}
+
+/// Message for the last failed call on this thread, or NULL.
+/// The pointer stays valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn sidecar_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
@@ -0,0 +1,359 @@
+/*
+ * Context: Bounding-box coordinate conventions and conversions
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde, serde_json
+ */
+
+use crate::sidecar::types::SidecarError;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Boxes may overhang the image by this many pixels before they are rejected
+const EDGE_TOLERANCE: f64 = 1.0;
+
+/// How the four numbers of a box are laid out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BoxFormat {
+    /// Top-left corner plus size: `{x, y, width, height}`
+    Xywh,
+    /// Two corners: `{x1, y1, x2, y2}`
+    Xyxy,
+    /// Center plus size: `{cx, cy, width, height}`
+    Cxcywh,
+}
+
+impl FromStr for BoxFormat {
+    type Err = SidecarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "xywh" => Ok(BoxFormat::Xywh),
+            "xyxy" => Ok(BoxFormat::Xyxy),
+            "cxcywh" => Ok(BoxFormat::Cxcywh),
+            other => Err(SidecarError::ProcessingError(format!(
+                "Unknown box format '{}' (expected xywh, xyxy or cxcywh)", other
+            ))),
+        }
+    }
+}
+
+/// Units of box coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CoordinateSpace {
+    /// Pixels of the source image
+    Pixel,
+    /// Fractions of the image width and height, in `0..=1`
+    Normalized,
+}
+
+impl FromStr for CoordinateSpace {
+    type Err = SidecarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "pixel" | "pixels" => Ok(CoordinateSpace::Pixel),
+            "normalized" | "normalised" => Ok(CoordinateSpace::Normalized),
+            other => Err(SidecarError::ProcessingError(format!(
+                "Unknown coordinate space '{}' (expected pixel or normalized)", other
+            ))),
+        }
+    }
+}
+
+/// Target layout and units for rewritten boxes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoxConvention {
+    pub format: BoxFormat,
+    pub space: CoordinateSpace,
+}
+
+/// Canonical bounding box: pixel corners of the source image
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BBox {
+    pub x1: f64,
+    pub y1: f64,
+    pub x2: f64,
+    pub y2: f64,
+}
+
+impl BBox {
+    pub fn from_xyxy(x1: f64, y1: f64, x2: f64, y2: f64) -> Self {
+        Self { x1, y1, x2, y2 }
+    }
+
+    pub fn from_xywh(x: f64, y: f64, width: f64, height: f64) -> Self {
+        Self::from_xyxy(x, y, x + width, y + height)
+    }
+
+    pub fn from_cxcywh(cx: f64, cy: f64, width: f64, height: f64) -> Self {
+        Self::from_xywh(cx - width / 2.0, cy - height / 2.0, width, height)
+    }
+
+    pub fn width(&self) -> f64 {
+        self.x2 - self.x1
+    }
+
+    pub fn height(&self) -> f64 {
+        self.y2 - self.y1
+    }
+
+    /// Read a box from sidecar JSON
+    ///
+    /// The layout is taken from the keys: `x/y/width/height` (or `w/h`) is xywh,
+    /// `x1/y1/x2/y2`, `xmin/ymin/xmax/ymax` and `left/top/right/bottom` are xyxy,
+    /// `cx/cy/width/height` (or `x_center/y_center`) is cxcywh. Bare 4-element arrays
+    /// are read as xywh. Coordinates are treated as normalized when all four are
+    /// within `0..=1`, and as pixels otherwise.
+    pub fn parse(value: &Value, image_width: u32, image_height: u32) -> Option<Self> {
+        let (format, [a, b, c, d]) = read_components(value)?;
+
+        let normalized = [a, b, c, d].iter().all(|v| (0.0..=1.0).contains(v));
+        let (sx, sy) = if normalized {
+            (f64::from(image_width), f64::from(image_height))
+        } else {
+            (1.0, 1.0)
+        };
+        let (a, b, c, d) = (a * sx, b * sy, c * sx, d * sy);
+
+        Some(match format {
+            BoxFormat::Xywh => Self::from_xywh(a, b, c, d),
+            BoxFormat::Xyxy => Self::from_xyxy(a, b, c, d),
+            BoxFormat::Cxcywh => Self::from_cxcywh(a, b, c, d),
+        })
+    }
+
+    /// The four numbers of this box in `convention`
+    pub fn components(&self, convention: BoxConvention, image_width: u32, image_height: u32) -> [f64; 4] {
+        let [a, b, c, d] = match convention.format {
+            BoxFormat::Xywh => [self.x1, self.y1, self.width(), self.height()],
+            BoxFormat::Xyxy => [self.x1, self.y1, self.x2, self.y2],
+            BoxFormat::Cxcywh => [
+                self.x1 + self.width() / 2.0,
+                self.y1 + self.height() / 2.0,
+                self.width(),
+                self.height(),
+            ],
+        };
+
+        match convention.space {
+            CoordinateSpace::Pixel => [a, b, c, d].map(|v| round_to(v, 2)),
+            CoordinateSpace::Normalized => {
+                let (w, h) = (f64::from(image_width), f64::from(image_height));
+                [a / w, b / h, c / w, d / h].map(|v| round_to(v, 6))
+            }
+        }
+    }
+
+    /// Sidecar JSON for this box in `convention`, keyed so the layout is unambiguous
+    pub fn to_value(&self, convention: BoxConvention, image_width: u32, image_height: u32) -> Value {
+        let [a, b, c, d] = self.components(convention, image_width, image_height);
+        match convention.format {
+            BoxFormat::Xywh => json!({"x": a, "y": b, "width": c, "height": d}),
+            BoxFormat::Xyxy => json!({"x1": a, "y1": b, "x2": c, "y2": d}),
+            BoxFormat::Cxcywh => json!({"cx": a, "cy": b, "width": c, "height": d}),
+        }
+    }
+
+    /// Check that the box is non-empty and lies within the image
+    pub fn validate(&self, image_width: u32, image_height: u32) -> Result<(), String> {
+        if self.width() <= 0.0 || self.height() <= 0.0 {
+            return Err(format!("empty box {:.1}x{:.1}", self.width(), self.height()));
+        }
+
+        let (w, h) = (f64::from(image_width), f64::from(image_height));
+        if self.x1 < -EDGE_TOLERANCE || self.y1 < -EDGE_TOLERANCE
+            || self.x2 > w + EDGE_TOLERANCE || self.y2 > h + EDGE_TOLERANCE
+        {
+            return Err(format!(
+                "box ({:.1}, {:.1})-({:.1}, {:.1}) outside {}x{} image",
+                self.x1, self.y1, self.x2, self.y2, image_width, image_height
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Integer pixel rectangle `(x, y, width, height)` clamped to the image, if non-empty
+    pub fn pixel_rect(&self, image_width: u32, image_height: u32) -> Option<(u32, u32, u32, u32)> {
+        let (w, h) = (f64::from(image_width), f64::from(image_height));
+        let left = self.x1.round().clamp(0.0, w);
+        let top = self.y1.round().clamp(0.0, h);
+        let right = self.x2.round().clamp(0.0, w);
+        let bottom = self.y2.round().clamp(0.0, h);
+
+        if right <= left || bottom <= top {
+            return None;
+        }
+        Some((left as u32, top as u32, (right - left) as u32, (bottom - top) as u32))
+    }
+}
+
+/// A box that was left untouched because it failed validation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InvalidBox {
+    pub sidecar_path: PathBuf,
+    /// Top-level sidecar key the detection was found under
+    pub operation: String,
+    pub detection_index: usize,
+    pub reason: String,
+}
+
+/// Outcome of rewriting boxes across a directory
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NormalizeReport {
+    pub sidecars_updated: usize,
+    pub boxes_converted: usize,
+    pub invalid_boxes: Vec<InvalidBox>,
+    /// Sidecars skipped because their image could not be read for its dimensions
+    pub unreadable_images: Vec<PathBuf>,
+}
+
+impl NormalizeReport {
+    pub fn merge(&mut self, other: NormalizeReport) {
+        self.sidecars_updated += other.sidecars_updated;
+        self.boxes_converted += other.boxes_converted;
+        self.invalid_boxes.extend(other.invalid_boxes);
+        self.unreadable_images.extend(other.unreadable_images);
+    }
+}
+
+/// Rewrite every detection bbox in a sidecar into `convention`
+/// Boxes that fail validation are left as they are and recorded in `report`.
+/// Returns the number of boxes converted.
+pub fn normalize_sidecar_boxes(
+    sidecar_path: &Path,
+    data: &mut Value,
+    convention: BoxConvention,
+    (image_width, image_height): (u32, u32),
+    report: &mut NormalizeReport,
+) -> usize {
+    let Some(operations) = data.as_object_mut() else {
+        return 0;
+    };
+    let mut converted = 0;
+
+    for (operation, payload) in operations.iter_mut() {
+        if operation == "sidecar_info" {
+            continue;
+        }
+        let Some(detections) = detections_in_mut(payload) else {
+            continue;
+        };
+
+        for (index, detection) in detections.iter_mut().enumerate() {
+            let Some(bbox_value) = detection.get_mut("bbox") else {
+                continue;
+            };
+            let checked = BBox::parse(bbox_value, image_width, image_height)
+                .ok_or_else(|| "unrecognized bbox".to_string())
+                .and_then(|bbox| bbox.validate(image_width, image_height).map(|_| bbox));
+            let bbox = match checked {
+                Ok(bbox) => bbox,
+                Err(reason) => {
+                    report.invalid_boxes.push(InvalidBox {
+                        sidecar_path: sidecar_path.to_path_buf(),
+                        operation: operation.clone(),
+                        detection_index: index,
+                        reason,
+                    });
+                    continue;
+                }
+            };
+
+            *bbox_value = bbox.to_value(convention, image_width, image_height);
+            converted += 1;
+        }
+    }
+
+    report.boxes_converted += converted;
+    converted
+}
+
+/// Detection list in an operation payload (`faces`, `objects` or `detections`,
+/// directly or under `data`/`result`)
+pub fn detections_in(payload: &Value) -> Option<&Vec<Value>> {
+    DETECTION_KEYS
+        .iter()
+        .find_map(|key| payload.get(*key).and_then(Value::as_array))
+        .or_else(|| NESTED_KEYS.iter().find_map(|key| payload.get(*key).and_then(detections_in)))
+}
+
+/// The payloads of a decoded sidecar, each with the operation it belongs to
+///
+/// The whole document is a payload of `recorded_operation`, so a raw detector file is read
+/// as one, and every other top-level key but `sidecar_info` names a payload of its own.
+/// `data` and `result` only wrap the document's own payload, which [`detections_in`] already
+/// reaches through the whole document, so they are not listed a second time.
+pub fn sidecar_payloads<'a>(data: &'a Value, recorded_operation: &'a str) -> impl Iterator<Item = (&'a str, &'a Value)> {
+    let nested = data
+        .as_object()
+        .into_iter()
+        .flat_map(|object| object.iter())
+        .filter(|(key, _)| *key != "sidecar_info" && !NESTED_KEYS.contains(&key.as_str()))
+        .map(|(key, payload)| (key.as_str(), payload));
+    std::iter::once((recorded_operation, data)).chain(nested)
+}
+
+/// Class of one detection, from `class_name`, `class`, `label` or `name`
+pub fn detection_class(detection: &Value) -> Option<String> {
+    ["class_name", "class", "label", "name"].iter().find_map(|key| match detection.get(*key)? {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    })
+}
+
+/// Mutable variant of `detections_in`
+pub fn detections_in_mut(payload: &mut Value) -> Option<&mut Vec<Value>> {
+    let object = payload.as_object_mut()?;
+    if let Some(key) = DETECTION_KEYS.iter().find(|key| object.get(**key).is_some_and(Value::is_array)) {
+        return object.get_mut(*key).and_then(Value::as_array_mut);
+    }
+    let key = NESTED_KEYS.iter().find(|key| object.get(**key).and_then(detections_in).is_some())?;
+    object.get_mut(*key).and_then(detections_in_mut)
+}
+
+const DETECTION_KEYS: [&str; 3] = ["faces", "objects", "detections"];
+pub(crate) const NESTED_KEYS: [&str; 2] = ["data", "result"];
+
+pub(crate) fn read_components(value: &Value) -> Option<(BoxFormat, [f64; 4])> {
+    let layouts: [(BoxFormat, [&str; 4]); 8] = [
+        (BoxFormat::Xyxy, ["x1", "y1", "x2", "y2"]),
+        (BoxFormat::Xyxy, ["xmin", "ymin", "xmax", "ymax"]),
+        (BoxFormat::Xyxy, ["left", "top", "right", "bottom"]),
+        (BoxFormat::Cxcywh, ["cx", "cy", "width", "height"]),
+        (BoxFormat::Cxcywh, ["x_center", "y_center", "width", "height"]),
+        (BoxFormat::Cxcywh, ["cx", "cy", "w", "h"]),
+        (BoxFormat::Xywh, ["x", "y", "width", "height"]),
+        (BoxFormat::Xywh, ["x", "y", "w", "h"]),
+    ];
+
+    match value {
+        Value::Object(map) => layouts.iter().find_map(|(format, keys)| {
+            let mut components = [0.0; 4];
+            for (slot, key) in components.iter_mut().zip(keys) {
+                *slot = map.get(*key)?.as_f64()?;
+            }
+            Some((*format, components))
+        }),
+        Value::Array(items) if items.len() == 4 => {
+            let mut components = [0.0; 4];
+            for (slot, item) in components.iter_mut().zip(items) {
+                *slot = item.as_f64()?;
+            }
+            Some((BoxFormat::Xywh, components))
+        }
+        _ => None,
+    }
+}
+
+fn round_to(value: f64, decimals: i32) -> f64 {
+    let factor = 10f64.powi(decimals);
+    (value * factor).round() / factor
+}
@@ -0,0 +1,282 @@
+/*
+ * Context: HuggingFace `datasets` export of a sidecar directory (parquet + dataset_infos.json)
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: polars (parquet, dtype-struct), serde_json
+ */
+
+use crate::query::QueryMatch;
+use crate::sidecar::manifest::relative_path;
+use crate::sidecar::types::{Result, SidecarError};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Split written when none is given
+pub const DEFAULT_SPLIT: &str = "train";
+
+/// What a dataset export includes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HfExportOptions {
+    /// Split name, used for the parquet file name and in `dataset_infos.json`
+    pub split: String,
+    /// Add an `image` column (the `Image` feature) holding each image's bytes
+    pub include_images: bool,
+    /// Export only these operations as columns; empty exports every operation found
+    pub operations: Vec<String>,
+}
+
+impl Default for HfExportOptions {
+    fn default() -> Self {
+        Self { split: DEFAULT_SPLIT.to_string(), include_images: false, operations: Vec::new() }
+    }
+}
+
+impl HfExportOptions {
+    pub fn split(mut self, split: impl Into<String>) -> Self {
+        self.split = split.into();
+        self
+    }
+
+    pub fn include_images(mut self, include: bool) -> Self {
+        self.include_images = include;
+        self
+    }
+
+    pub fn operations<I, S>(mut self, operations: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.operations = operations.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+/// What a dataset export wrote
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HfExportSummary {
+    /// One row per image
+    pub rows: usize,
+    /// Operations exported as columns, in column order
+    pub operations: Vec<String>,
+    pub files: Vec<PathBuf>,
+    /// Images that could not be read for `--include-images`; their `image` is null
+    pub missing_images: Vec<PathBuf>,
+    /// The `features` written to `dataset_infos.json`
+    pub features: Value,
+}
+
+// `sql` converts `PolarsError` with `?`; mapped by hand here so both features can be enabled
+fn polars_error(error: PolarsError) -> SidecarError {
+    SidecarError::ProcessingError(format!("Dataset export error: {}", error))
+}
+
+/// Column type inferred from every value of an operation across the directory
+///
+/// Values that disagree (a number in one sidecar, an object in the next) fall back to
+/// `Json`, stored as a JSON string.
+#[derive(Debug, Clone, PartialEq)]
+enum Kind {
+    Null,
+    Bool,
+    Int,
+    Float,
+    String,
+    List(Box<Kind>),
+    Struct(BTreeMap<String, Kind>),
+    Json,
+}
+
+impl Kind {
+    fn of(value: &Value) -> Kind {
+        match value {
+            Value::Null => Kind::Null,
+            Value::Bool(_) => Kind::Bool,
+            Value::Number(n) if n.is_i64() => Kind::Int,
+            Value::Number(_) => Kind::Float,
+            Value::String(_) => Kind::String,
+            Value::Array(items) => Kind::List(Box::new(items.iter().map(Kind::of).fold(Kind::Null, Kind::merge))),
+            Value::Object(map) if map.is_empty() => Kind::Json,
+            Value::Object(map) => Kind::Struct(map.iter().map(|(key, value)| (key.clone(), Kind::of(value))).collect()),
+        }
+    }
+
+    fn merge(self, other: Kind) -> Kind {
+        match (self, other) {
+            (Kind::Null, other) | (other, Kind::Null) => other,
+            (Kind::Int, Kind::Float) | (Kind::Float, Kind::Int) => Kind::Float,
+            (Kind::List(left), Kind::List(right)) => Kind::List(Box::new(left.merge(*right))),
+            (Kind::Struct(mut left), Kind::Struct(right)) => {
+                for (key, kind) in right {
+                    let merged = match left.remove(&key) {
+                        Some(existing) => existing.merge(kind),
+                        None => kind,
+                    };
+                    left.insert(key, merged);
+                }
+                Kind::Struct(left)
+            }
+            (left, right) if left == right => left,
+            _ => Kind::Json,
+        }
+    }
+
+    fn dtype(&self) -> DataType {
+        match self {
+            Kind::Bool => DataType::Boolean,
+            Kind::Int => DataType::Int64,
+            Kind::Float => DataType::Float64,
+            Kind::Null | Kind::String | Kind::Json => DataType::String,
+            Kind::List(inner) => DataType::List(Box::new(inner.dtype())),
+            Kind::Struct(fields) => DataType::Struct(
+                fields.iter().map(|(name, kind)| Field::new(name.as_str().into(), kind.dtype())).collect(),
+            ),
+        }
+    }
+
+    /// The `datasets` feature for this kind; lists of objects are written as `[{...}]`, as `datasets` does
+    fn feature(&self) -> Value {
+        let value = |dtype: &str| json!({"dtype": dtype, "_type": "Value"});
+        match self {
+            Kind::Bool => value("bool"),
+            Kind::Int => value("int64"),
+            Kind::Float => value("float64"),
+            Kind::Null | Kind::String | Kind::Json => value("string"),
+            Kind::List(inner) if matches!(**inner, Kind::Struct(_)) => json!([inner.feature()]),
+            Kind::List(inner) => json!({"feature": inner.feature(), "_type": "Sequence"}),
+            Kind::Struct(fields) => Value::Object(fields.iter().map(|(name, kind)| (name.clone(), kind.feature())).collect()),
+        }
+    }
+
+    fn any_value(&self, value: &Value) -> Result<AnyValue<'static>> {
+        Ok(match (self, value) {
+            (_, Value::Null) => AnyValue::Null,
+            (Kind::Json, value) => AnyValue::StringOwned(value.to_string().into()),
+            (Kind::Bool, Value::Bool(b)) => AnyValue::Boolean(*b),
+            (Kind::Int, value) => value.as_i64().map(AnyValue::Int64).unwrap_or(AnyValue::Null),
+            (Kind::Float, value) => value.as_f64().map(AnyValue::Float64).unwrap_or(AnyValue::Null),
+            (Kind::String, Value::String(s)) => AnyValue::StringOwned(s.as_str().into()),
+            (Kind::List(inner), Value::Array(items)) => {
+                let values = items.iter().map(|item| inner.any_value(item)).collect::<Result<Vec<_>>>()?;
+                AnyValue::List(Series::from_any_values_and_dtype("".into(), &values, &inner.dtype(), true).map_err(polars_error)?)
+            }
+            (Kind::Struct(fields), Value::Object(map)) => {
+                let values = fields
+                    .iter()
+                    .map(|(name, kind)| map.get(name).map(|value| kind.any_value(value)).unwrap_or(Ok(AnyValue::Null)))
+                    .collect::<Result<Vec<_>>>()?;
+                let DataType::Struct(fields) = self.dtype() else { unreachable!("struct kinds have struct dtypes") };
+                AnyValue::StructOwned(Box::new((values, fields)))
+            }
+            _ => AnyValue::Null,
+        })
+    }
+}
+
+/// Write `records` as a dataset directory `datasets.load_dataset` reads
+///
+/// Each image becomes one row of `data/<split>-00000-of-00001.parquet`: `image_path`
+/// relative to `root`, optionally `image` with its bytes, and one column per operation
+/// holding its payload, typed from the payloads found (null where an image lacks the
+/// operation). `dataset_infos.json` records the matching `datasets` features, and a
+/// README.md front matter points the default config at the parquet file.
+/// This is blocking; call it from `spawn_blocking`.
+pub fn write_dataset(records: &[QueryMatch], root: &Path, output: &Path, options: &HfExportOptions) -> Result<HfExportSummary> {
+    let mut images: BTreeMap<PathBuf, Map<String, Value>> = BTreeMap::new();
+    for record in records {
+        let Some(payloads) = record.data.as_object() else {
+            continue;
+        };
+        for (operation, payload) in payloads {
+            if operation == "sidecar_info" || !(options.operations.is_empty() || options.operations.contains(operation)) {
+                continue;
+            }
+            images.entry(record.sidecar.image_path.clone()).or_default().insert(operation.clone(), payload.clone());
+        }
+    }
+
+    let mut kinds: BTreeMap<String, Kind> = BTreeMap::new();
+    for payloads in images.values() {
+        for (operation, payload) in payloads {
+            let kind = kinds.remove(operation).unwrap_or(Kind::Null).merge(Kind::of(payload));
+            kinds.insert(operation.clone(), kind);
+        }
+    }
+
+    let mut summary = HfExportSummary { rows: images.len(), operations: kinds.keys().cloned().collect(), ..Default::default() };
+    let mut features = Map::new();
+    let image_paths: Vec<String> = images.keys().map(|image_path| relative_path(root, image_path)).collect();
+    let mut columns = vec![Column::new("image_path".into(), &image_paths)];
+    features.insert("image_path".to_string(), json!({"dtype": "string", "_type": "Value"}));
+
+    if options.include_images {
+        let fields = vec![Field::new("bytes".into(), DataType::Binary), Field::new("path".into(), DataType::String)];
+        let mut values = Vec::with_capacity(images.len());
+        for (image_path, relative) in images.keys().zip(&image_paths) {
+            match std::fs::read(image_path) {
+                Ok(bytes) => values.push(AnyValue::StructOwned(Box::new((
+                    vec![AnyValue::BinaryOwned(bytes), AnyValue::StringOwned(relative.as_str().into())],
+                    fields.clone(),
+                )))),
+                Err(_) => {
+                    summary.missing_images.push(image_path.clone());
+                    values.push(AnyValue::Null);
+                }
+            }
+        }
+        let series = Series::from_any_values_and_dtype("image".into(), &values, &DataType::Struct(fields), true).map_err(polars_error)?;
+        columns.push(series.into_column());
+        features.insert("image".to_string(), json!({"_type": "Image"}));
+    }
+
+    for (operation, kind) in &kinds {
+        let values = images
+            .values()
+            .map(|payloads| payloads.get(operation).map(|payload| kind.any_value(payload)).unwrap_or(Ok(AnyValue::Null)))
+            .collect::<Result<Vec<_>>>()?;
+        let series = Series::from_any_values_and_dtype(operation.as_str().into(), &values, &kind.dtype(), true).map_err(polars_error)?;
+        columns.push(series.into_column());
+        features.insert(operation.clone(), kind.feature());
+    }
+    let mut frame = DataFrame::new(columns).map_err(polars_error)?;
+
+    let data_dir = output.join("data");
+    std::fs::create_dir_all(&data_dir)?;
+    let parquet_path = data_dir.join(format!("{}-00000-of-00001.parquet", options.split));
+    ParquetWriter::new(std::fs::File::create(&parquet_path)?).finish(&mut frame).map_err(polars_error)?;
+    let size = std::fs::metadata(&parquet_path)?.len();
+
+    summary.features = Value::Object(features);
+    let infos = json!({
+        "default": {
+            "description": format!("Sidecar records exported from {}", root.display()),
+            "citation": "",
+            "homepage": "",
+            "license": "",
+            "features": summary.features,
+            "builder_name": "parquet",
+            "config_name": "default",
+            "splits": {
+                options.split.clone(): {"name": options.split, "num_bytes": size, "num_examples": summary.rows},
+            },
+            "download_size": size,
+            "dataset_size": size,
+        }
+    });
+    let infos_path = output.join("dataset_infos.json");
+    std::fs::write(&infos_path, serde_json::to_string_pretty(&infos)?)?;
+    let readme_path = output.join("README.md");
+    std::fs::write(
+        &readme_path,
+        format!(
+            "---\nconfigs:\n- config_name: default\n  data_files:\n  - split: {split}\n    path: data/{split}-*\n---\n",
+            split = options.split,
+        ),
+    )?;
+    summary.files = vec![parquet_path, infos_path, readme_path];
+    Ok(summary)
+}
@@ -0,0 +1,92 @@
+/*
+ * Context: Crop extraction for detections recorded in sidecars
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: image, rayon, serde_json
+ */
+
+use crate::geometry::BBox;
+use crate::sidecar::types::{Result, SidecarError};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// One crop written to disk
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Crop {
+    pub image_path: PathBuf,
+    /// `id`/`face_id` of the detection, or its position in the detection list
+    pub detection_id: String,
+    pub crop_path: PathBuf,
+}
+
+/// Reserve a crop file name for every detection of `image_path`, in order
+///
+/// Names are `<image stem>_<detection id>.jpg`, with the id reduced to characters safe in
+/// a file name. A name already in `taken`, compared case-insensitively, gets a `-2`,
+/// `-3`, ... suffix, so images sharing a stem and repeated detection ids never
+/// overwrite each other's crops. Returns `(detection id, file name)` per detection.
+pub fn reserve_names(image_path: &Path, detections: &[Value], taken: &mut HashSet<String>) -> Vec<(String, String)> {
+    let stem = image_path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    detections
+        .iter()
+        .enumerate()
+        .map(|(index, detection)| {
+            let detection_id = ["id", "face_id", "detection_id"]
+                .iter()
+                .find_map(|key| detection.get(*key))
+                .map(|id| match id {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .unwrap_or_else(|| index.to_string());
+            let safe_id: String = detection_id
+                .chars()
+                .map(|c| if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '_' })
+                .collect();
+            let base = format!("{}_{}", stem, safe_id);
+            let file_name = (1..)
+                .map(|n| if n == 1 { format!("{}.jpg", base) } else { format!("{}-{}.jpg", base, n) })
+                .find(|name| taken.insert(name.to_lowercase()))
+                .expect("an unused suffix exists");
+            (detection_id, file_name)
+        })
+        .collect()
+}
+
+/// Crop every detection with a usable bbox out of `image_path` into `output_dir`
+///
+/// `names` holds the `(detection id, file name)` from `reserve_names` for each detection.
+/// Boxes are read with `BBox::parse` and clamped to the image; detections without a box,
+/// or whose box is empty after clamping, are skipped.
+/// This is blocking; call it from `spawn_blocking` or a rayon pool.
+pub fn crop_detections(image_path: &Path, detections: &[Value], names: &[(String, String)], output_dir: &Path) -> Result<Vec<Crop>> {
+    let image = image::open(image_path)
+        .map_err(|e| SidecarError::ProcessingError(format!("Failed to decode image {}: {}", image_path.display(), e)))?;
+    let mut crops = Vec::new();
+
+    for (detection, (detection_id, file_name)) in detections.iter().zip(names) {
+        let rect = detection.get("bbox")
+            .and_then(|bbox| BBox::parse(bbox, image.width(), image.height()))
+            .and_then(|bbox| bbox.pixel_rect(image.width(), image.height()));
+        let Some((x, y, width, height)) = rect else {
+            continue;
+        };
+
+        let crop_path = output_dir.join(file_name);
+        image.crop_imm(x, y, width, height)
+            .to_rgb8()
+            .save(&crop_path)
+            .map_err(|e| SidecarError::Storage(format!("Failed to write crop {}: {}", crop_path.display(), e)))?;
+
+        crops.push(Crop {
+            image_path: image_path.to_path_buf(),
+            detection_id: detection_id.clone(),
+            crop_path,
+        });
+    }
+
+    Ok(crops)
+}
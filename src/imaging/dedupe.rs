@@ -0,0 +1,118 @@
+/*
+ * Context: Near-duplicate clustering over perceptual hashes stored in sidecars
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: tokio, serde
+ */
+
+use crate::imaging::hash::ImageHash;
+use crate::sidecar::types::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Images whose hashes are within the duplicate threshold of each other
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DuplicateGroup {
+    /// Image kept when deduplicating (first by path)
+    pub keep: PathBuf,
+    /// The remaining near-duplicates of `keep`
+    pub duplicates: Vec<PathBuf>,
+    /// Largest pHash distance between `keep` and any duplicate
+    pub max_distance: u32,
+}
+
+/// Cluster images whose pHash distance is at most `threshold`
+///
+/// Clusters are transitive: if A~B and B~C then A, B and C share a group.
+/// Groups are sorted by their kept image; singletons are dropped.
+///
+/// Only candidate pairs are compared: the hash is cut into `threshold + 1` bit ranges, and two
+/// hashes within `threshold` bits of each other must agree exactly on at least one of them, so
+/// images are bucketed by each range's value and compared within buckets.
+pub fn cluster(mut hashes: Vec<(PathBuf, ImageHash)>, threshold: u32) -> Vec<DuplicateGroup> {
+    hashes.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut parent: Vec<usize> = (0..hashes.len()).collect();
+
+    let mut buckets: HashMap<(u64, u64), Vec<usize>> = HashMap::new();
+    for (range, mask) in bit_ranges(threshold).into_iter().enumerate() {
+        for (i, (_, hash)) in hashes.iter().enumerate() {
+            buckets.entry((range as u64, hash.phash & mask)).or_default().push(i);
+        }
+    }
+    for candidates in buckets.values().filter(|candidates| candidates.len() > 1) {
+        for (position, &i) in candidates.iter().enumerate() {
+            for &j in &candidates[position + 1..] {
+                let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                if a != b && hashes[i].1.distance(&hashes[j].1) <= threshold {
+                    // Keep the lowest index as root so the kept image is the first path
+                    parent[a.max(b)] = a.min(b);
+                }
+            }
+        }
+    }
+
+    let mut members: Vec<Vec<usize>> = vec![Vec::new(); hashes.len()];
+    for i in 0..hashes.len() {
+        let root = find(&mut parent, i);
+        members[root].push(i);
+    }
+
+    members
+        .into_iter()
+        .filter(|group| group.len() > 1)
+        .map(|group| {
+            let keep = &hashes[group[0]];
+            DuplicateGroup {
+                keep: keep.0.clone(),
+                duplicates: group[1..].iter().map(|&i| hashes[i].0.clone()).collect(),
+                max_distance: group[1..].iter().map(|&i| keep.1.distance(&hashes[i].1)).max().unwrap_or(0),
+            }
+        })
+        .collect()
+}
+
+/// Masks splitting the 64 hash bits into `threshold + 1` contiguous ranges of near-equal width
+/// With a threshold of 64 or more every pair is a candidate, so one empty mask buckets them all.
+fn bit_ranges(threshold: u32) -> Vec<u64> {
+    if threshold >= 64 {
+        return vec![0];
+    }
+    let count = threshold as u64 + 1;
+    (0..count)
+        .map(|range| {
+            let (start, end) = (range * 64 / count, (range + 1) * 64 / count);
+            let width = end - start;
+            if width == 64 { u64::MAX } else { ((1u64 << width) - 1) << start }
+        })
+        .collect()
+}
+
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+/// Replace every duplicate in `group` with a hardlink to the kept image
+/// Each link is created next to the duplicate and renamed over it, so a failure never
+/// leaves a duplicate path missing. Returns the number of files replaced.
+pub async fn hardlink_group(group: &DuplicateGroup) -> Result<usize> {
+    let mut replaced = 0;
+    for duplicate in &group.duplicates {
+        let mut temp_name = duplicate.file_name().unwrap_or_default().to_os_string();
+        temp_name.push(".dedupe-tmp");
+        let temp_path = duplicate.with_file_name(temp_name);
+
+        tokio::fs::hard_link(&group.keep, &temp_path).await?;
+        if let Err(e) = tokio::fs::rename(&temp_path, duplicate).await {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+            return Err(e.into());
+        }
+        replaced += 1;
+    }
+    Ok(replaced)
+}
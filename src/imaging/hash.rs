@@ -0,0 +1,131 @@
+/*
+ * Context: Perceptual image hashes (pHash and dHash)
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: image, serde_json
+ */
+
+use crate::sidecar::types::{Result, SidecarError};
+use image::imageops::{self, FilterType};
+use image::GrayImage;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::f64::consts::PI;
+use std::path::Path;
+
+/// Side length of the image fed to the DCT for pHash
+const PHASH_SIZE: usize = 32;
+/// Side length of the low-frequency block kept from the DCT
+const PHASH_LOW: usize = 8;
+
+/// Perceptual hashes of one image, stored under the `image_hash` operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageHash {
+    /// DCT-based hash, robust to scaling and recompression
+    #[serde(with = "hex_u64")]
+    pub phash: u64,
+    /// Gradient hash, cheap and sensitive to small crops
+    #[serde(with = "hex_u64")]
+    pub dhash: u64,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl ImageHash {
+    /// Decode an image and compute its hashes
+    /// This is blocking and CPU bound; call it from `spawn_blocking` or a rayon pool
+    pub fn compute(path: &Path) -> Result<Self> {
+        let image = image::open(path)
+            .map_err(|e| SidecarError::ProcessingError(format!("Failed to decode image {}: {}", path.display(), e)))?;
+        let gray = image.to_luma8();
+
+        Ok(Self {
+            phash: phash(&gray),
+            dhash: dhash(&gray),
+            width: image.width(),
+            height: image.height(),
+        })
+    }
+
+    /// Read hashes back from an `image_hash` sidecar payload
+    pub fn from_payload(payload: &Value) -> Option<Self> {
+        serde_json::from_value(payload.clone()).ok()
+    }
+
+    /// Payload written to the sidecar
+    pub fn to_payload(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+
+    /// Hamming distance between the pHashes of two images
+    pub fn distance(&self, other: &ImageHash) -> u32 {
+        (self.phash ^ other.phash).count_ones()
+    }
+}
+
+/// dHash: one bit per horizontally adjacent pixel pair of a 9x8 thumbnail
+fn dhash(gray: &GrayImage) -> u64 {
+    let small = imageops::resize(gray, 9, 8, FilterType::Triangle);
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            let left = small.get_pixel(x, y)[0];
+            let right = small.get_pixel(x + 1, y)[0];
+            hash = (hash << 1) | u64::from(left > right);
+        }
+    }
+    hash
+}
+
+/// pHash: low-frequency DCT coefficients of a 32x32 thumbnail compared to their median
+fn phash(gray: &GrayImage) -> u64 {
+    let small = imageops::resize(gray, PHASH_SIZE as u32, PHASH_SIZE as u32, FilterType::Triangle);
+    let pixels: Vec<f64> = small.pixels().map(|p| f64::from(p[0])).collect();
+
+    // Separable DCT-II, keeping only the low-frequency block
+    let basis: Vec<f64> = (0..PHASH_LOW)
+        .flat_map(|u| (0..PHASH_SIZE).map(move |x| ((2 * x + 1) as f64 * u as f64 * PI / (2 * PHASH_SIZE) as f64).cos()))
+        .collect();
+    let mut rows = vec![0.0; PHASH_SIZE * PHASH_LOW];
+    for y in 0..PHASH_SIZE {
+        for u in 0..PHASH_LOW {
+            rows[y * PHASH_LOW + u] = (0..PHASH_SIZE)
+                .map(|x| pixels[y * PHASH_SIZE + x] * basis[u * PHASH_SIZE + x])
+                .sum();
+        }
+    }
+    let mut coefficients = Vec::with_capacity(PHASH_LOW * PHASH_LOW);
+    for v in 0..PHASH_LOW {
+        for u in 0..PHASH_LOW {
+            coefficients.push(
+                (0..PHASH_SIZE)
+                    .map(|y| rows[y * PHASH_LOW + u] * basis[v * PHASH_SIZE + y])
+                    .sum::<f64>(),
+            );
+        }
+    }
+
+    // The DC term only carries overall brightness, leave it out of the median
+    let mut sorted = coefficients[1..].to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let median = sorted[sorted.len() / 2];
+
+    coefficients
+        .iter()
+        .fold(0u64, |hash, &c| (hash << 1) | u64::from(c > median))
+}
+
+/// Store hashes as fixed-width hex strings so they survive JSON round trips
+mod hex_u64 {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{:016x}", value))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        u64::from_str_radix(&text, 16).map_err(serde::de::Error::custom)
+    }
+}
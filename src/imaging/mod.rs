@@ -0,0 +1,15 @@
+/*
+ * Context: Image-level operations driven by sidecar data
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: image, rayon
+ */
+
+pub mod crops;
+pub mod dedupe;
+pub mod hash;
+
+pub use crops::Crop;
+pub use dedupe::DuplicateGroup;
+pub use hash::ImageHash;
@@ -1,4 +1,4 @@
-/**
+/*
  * This code written by Claude Sonnet 4 (claude-3-5-sonnet-20241022)
  * Generated via Cursor IDE (cursor.sh) with AI assistance
  * Model: Anthropic Claude 3.5 Sonnet
@@ -10,44 +10,326 @@
  * - IDE: Cursor (cursor.sh)
  * - Generation method: AI-assisted pair programming
  * - Code style: Rust idiomatic with comprehensive error handling
- * - Dependencies: tokio, serde, rayon, clap, anyhow, pyo3
+ * - Dependencies: tokio, serde, rayon, clap, pyo3
  */
 
 pub mod sidecar;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod parallel;
 pub mod utils;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod query;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod imaging;
+pub mod vectors;
+pub mod geometry;
+pub mod report;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod dashboard;
+pub mod config;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod metrics;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod events;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod testing;
 
 #[cfg(feature = "python")]
 pub mod python;
 
+#[cfg(feature = "sql")]
+pub mod sql;
+
+#[cfg(feature = "db")]
+pub mod db;
+
+#[cfg(feature = "huggingface")]
+pub mod huggingface;
+
+#[cfg(feature = "python-arrow")]
+pub mod arrow;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
 #[cfg(feature = "python")]
 pub use python::image_sidecar_rust;
 
 pub use sidecar::{
-    SidecarManager, SidecarInfo, OperationType, SidecarError,
-    ValidationResult, StatisticsResult, SidecarFormat, FormatManager
+    SidecarInfo, OperationType, SidecarError, MergeStrategy,
+    ValidationResult, StatisticsResult, MultiRootStatistics, SidecarFormat, FormatManager,
+    TimeBucket, TimeSeriesPoint, RepairAction, RepairRecord, Manifest, ManifestVerification, DirectoryDiff,
+    ConversionSummary, GcAction, GcRecord, RetentionPolicy, RetentionRule, OperationRemoval, OperationTotals,
+    SymlinkPolicy, BrokenSymlink, HardlinkGroup, ClassDistribution, ClassImbalance, DEFAULT_MAX_SIDECAR_SIZE,
+};
+#[cfg(not(target_arch = "wasm32"))]
+pub use sidecar::{
+    SidecarManager, SidecarManagerBuilder, ScanOptions, FileSelection, ScanOrder, SidecarPattern, SidecarPatterns,
+    ClassifierRule, OperationClassifier, TriageReport, UnknownGroup, ReviewItem, ReviewOptions, ReviewQueue, ReviewSelection,
+    GateAction, GateReport, GateSummary, GatedImage, QualityGate, FrameNaming, Timeline, TimelineInterval, TimelineOptions,
+    Track, TrackPoint, TrackSummary, LintFinding, LintIssue, LintLevel, LintReport, LintRule, Linter,
+    Anomaly, AnomalyKind, AnomalyOptions, AnomalyReport, BenchOptions, BenchReport, StageStats,
+    GenerateOptions, GenerateSummary, StorageBackend,
+    Checkpoint, AuditLog, ConversionRecord, ConvertWatcher,
+    Mutation, MutationLog, MutationRecord, Provenance, BlobReport, BlobStore, Pack, UnpackSummary,
+    ArchiveFormat, ArchiveOptions, ArchiveSummary, TfExample, TfRecordOptions, TfRecordSummary, WebDatasetOptions, WebDatasetSummary, RedactAction, RedactKey, RedactRule, RedactionRecord,
+    SidecarSigner, SignatureIssue, SignatureProblem, SignatureReport, SignatureVerifier,
+    RestoreSummary, SnapshotInfo, SnapshotStore, SnapshotSummary, FileChange, GitFileStatus, GitStatus,
+    DvcDirectory, DvcFile, DvcOutput, DvcVerification, BadgeRefresh, BadgeRollup, StatsBadge,
+    CoverageReport, OperationCoverage, ScanPipeline, ScanReport, Compression, ShownSidecar, JobFormat, JobSpec, ImageFingerprint, StaleReason, StaleSidecar,
+    Reassociation, ReassociationOutcome, MovedFile, RelocationRecord, RecoverySummary, SidecarTransaction, TransactionStep,
+    UnifiedOperation, UnifyRecord, SplitFile, SplitRecord, LegacyImporter, LegacyLayout, MigrationRecord, MigrationReport,
 };
-pub use parallel::ParallelProcessor;
-pub use utils::json::JsonUtils;
+#[cfg(not(target_arch = "wasm32"))]
+pub use parallel::{ParallelProcessor, ProgressCallback, Sink, NdjsonSink, Tee, ValidationSummary, IoLimits, Throttle};
+pub use utils::json::{JsonPatch, JsonUtils, MergeConflict, MergeResult, PatchOperation};
+#[cfg(not(target_arch = "wasm32"))]
+pub use query::{QueryExpr, QueryMatch, QueryError, ExtractPath, ExtractedValue, Projection, DetectionFilter};
+#[cfg(not(target_arch = "wasm32"))]
+pub use imaging::{Crop, DuplicateGroup, ImageHash};
+pub use vectors::{Neighbor, VectorIndex};
+pub use geometry::{BBox, BoxConvention, BoxFormat, CoordinateSpace, NormalizeReport};
+pub use report::ReportFormat;
+pub use config::{Config, OperationRule, Profile, SidecarPatternRule};
+#[cfg(not(target_arch = "wasm32"))]
+pub use metrics::Metrics;
+#[cfg(not(target_arch = "wasm32"))]
+pub use events::{EventKind, EventSink, Notifier, NotifierOptions, SidecarEvent};
+pub use sidecar::types::Result;
 
-use anyhow::Result;
-use std::path::Path;
+// The filesystem-facing API needs tokio and is left out of wasm builds
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::{Path, PathBuf};
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
 
 /// Main entry point for sidecar operations
+#[cfg(not(target_arch = "wasm32"))]
 pub struct ImageSidecar {
     manager: SidecarManager,
     processor: ParallelProcessor,
 }
 
+/// Fluent configuration for an `ImageSidecar`; unset options keep the `new()` defaults
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Default)]
+pub struct ImageSidecarBuilder {
+    max_workers: Option<usize>,
+    io_limits: IoLimits,
+    selection: FileSelection,
+    detection_filter: DetectionFilter,
+    max_sidecar_size: Option<u64>,
+    manager: SidecarManagerBuilder,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ImageSidecarBuilder {
+    /// Worker threads for parallel validation (defaults to available parallelism)
+    pub fn max_workers(mut self, workers: usize) -> Self {
+        self.max_workers = Some(workers);
+        self
+    }
+
+    /// Replace the recognised image extensions; case and a leading `.` are ignored
+    pub fn image_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.manager = self.manager.image_extensions(extensions);
+        self
+    }
+
+    /// Recognise one more image extension
+    pub fn add_image_extension(mut self, extension: impl AsRef<str>) -> Self {
+        self.manager = self.manager.add_image_extension(extension);
+        self
+    }
+
+    /// Treat sidecars containing the top-level key `key` as belonging to `operation`
+    pub fn operation_key(mut self, key: impl Into<String>, operation: OperationType) -> Self {
+        self.manager = self.manager.operation_key(key, operation);
+        self
+    }
+
+    /// Classify sidecars meeting `rule` as its operation, before the built-in detector keys
+    pub fn operation_rule(mut self, rule: ClassifierRule) -> Self {
+        self.manager = self.manager.operation_rule(rule);
+        self
+    }
+
+    /// Format used for new and converted sidecars
+    pub fn default_format(mut self, format: SidecarFormat) -> Self {
+        self.manager = self.manager.default_format(format);
+        self
+    }
+
+    /// How `save_data` combines new payloads with stored ones
+    pub fn merge_strategy(mut self, strategy: MergeStrategy) -> Self {
+        self.manager = self.manager.merge_strategy(strategy);
+        self
+    }
+
+    /// Symlink and hidden-file handling for directory scans
+    pub fn scan_options(mut self, options: ScanOptions) -> Self {
+        self.manager = self.manager.scan_options(options);
+        self
+    }
+
+    /// Only validate, convert and count coverage for scanned files within these time bounds, in this order
+    pub fn selection(mut self, selection: FileSelection) -> Self {
+        self.selection = selection;
+        self.manager = self.manager.selection(selection);
+        self
+    }
+
+    /// Only validate and query sidecars with a payload within these detection-count and confidence bounds
+    pub fn detection_filter(mut self, filter: DetectionFilter) -> Self {
+        self.detection_filter = filter.clone();
+        self.manager = self.manager.detection_filter(filter);
+        self
+    }
+
+    /// Whether sidecars of symlinked images live next to the target, the link, or both
+    pub fn symlink_policy(mut self, policy: SymlinkPolicy) -> Self {
+        self.manager = self.manager.symlink_policy(policy);
+        self
+    }
+
+    /// Publish an event for every sidecar written or removed
+    pub fn notifier(mut self, notifier: Arc<Notifier>) -> Self {
+        self.manager = self.manager.notifier(notifier);
+        self
+    }
+
+    /// Record every sidecar write, conversion, deletion and repair in `log`
+    pub fn mutation_log(mut self, log: Arc<MutationLog>) -> Self {
+        self.manager = self.manager.mutation_log(log);
+        self
+    }
+
+    /// Count every format conversion and its duration in `metrics`
+    pub fn metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.manager = self.manager.metrics(metrics);
+        self
+    }
+
+    /// Stamp `provenance` into `sidecar_info` on every write
+    pub fn provenance(mut self, provenance: Provenance) -> Self {
+        self.manager = self.manager.provenance(provenance);
+        self
+    }
+
+    /// Keep operation payloads in a content-addressed `store`, leaving pointers in the sidecars
+    pub fn blob_store(mut self, store: Arc<BlobStore>) -> Self {
+        self.manager = self.manager.blob_store(store);
+        self
+    }
+
+    /// Sign every payload written with `signer`
+    pub fn signer(mut self, signer: Arc<SidecarSigner>) -> Self {
+        self.manager = self.manager.signer(signer);
+        self
+    }
+
+    /// Read, write and scan sidecars through `storage`, e.g. `testing::MemoryStorage`, instead of the filesystem
+    pub fn storage(mut self, storage: Arc<dyn StorageBackend>) -> Self {
+        self.manager = self.manager.storage(storage);
+        self
+    }
+
+    /// Refuse sidecars over `bytes` unread: reads fail with `SidecarError::TooLarge` and validation
+    /// reports them as oversized (default `DEFAULT_MAX_SIDECAR_SIZE`, 256 MiB)
+    pub fn max_sidecar_size(mut self, bytes: u64) -> Self {
+        self.max_sidecar_size = Some(bytes);
+        self.manager = self.manager.max_sidecar_size(bytes);
+        self
+    }
+
+    /// Cap file opens, open handles and read throughput, shared by validation and conversion
+    pub fn io_limits(mut self, limits: IoLimits) -> Self {
+        self.io_limits = limits;
+        self
+    }
+
+    /// Descend into symlinked directories during scans
+    pub fn follow_links(mut self, follow: bool) -> Self {
+        self.manager = self.manager.follow_links(follow);
+        self
+    }
+
+    /// Skip dot-files and dot-directories during scans
+    pub fn skip_hidden(mut self, skip: bool) -> Self {
+        self.manager = self.manager.skip_hidden(skip);
+        self
+    }
+
+    /// Treat hardlinks to one image file as a single image sharing one sidecar
+    pub fn merge_hardlinks(mut self, merge: bool) -> Self {
+        self.manager = self.manager.merge_hardlinks(merge);
+        self
+    }
+
+    /// Name patterns such as `{stem}_faces.json` tying sidecars to images and operations
+    pub fn sidecar_patterns(mut self, patterns: SidecarPatterns) -> Self {
+        self.manager = self.manager.sidecar_patterns(patterns);
+        self
+    }
+
+    /// Leave source sidecars in place after a verified format conversion
+    pub fn keep_originals(mut self, keep: bool) -> Self {
+        self.manager = self.manager.keep_originals(keep);
+        self
+    }
+
+    /// Refuse every operation that would create, change or delete a sidecar
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.manager = self.manager.read_only(read_only);
+        self
+    }
+
+    /// Write byte-identical sidecars for identical documents: sorted keys, compact JSON, fixed binary layout
+    pub fn canonical(mut self, canonical: bool) -> Self {
+        self.manager = self.manager.canonical(canonical);
+        self
+    }
+
+    /// Write sidecars as sorted, pretty-printed JSON for review in git; implies `canonical`
+    pub fn git_friendly(mut self, git_friendly: bool) -> Self {
+        self.manager = self.manager.git_friendly(git_friendly);
+        self
+    }
+
+    /// Create the configured ImageSidecar
+    pub fn build(self) -> ImageSidecar {
+        let throttle = Arc::new(Throttle::new(self.io_limits));
+        let processor = ParallelProcessor::with_throttle(
+            self.max_workers.unwrap_or_else(|| std::thread::available_parallelism().map(|n| n.get()).unwrap_or(16)),
+            Arc::clone(&throttle),
+        ).with_selection(self.selection).with_detection_filter(self.detection_filter)
+            .with_max_sidecar_size(self.max_sidecar_size.unwrap_or(DEFAULT_MAX_SIDECAR_SIZE));
+
+        ImageSidecar { manager: self.manager.throttle(throttle).build(), processor }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
 impl ImageSidecar {
     /// Create a new ImageSidecar instance
     pub fn new(max_workers: Option<usize>) -> Self {
-        let manager = SidecarManager::new();
-        let processor = ParallelProcessor::new(max_workers.unwrap_or_else(|| {
-            std::thread::available_parallelism().map(|n| n.get()).unwrap_or(16)
-        }));
-        
-        Self { manager, processor }
+        let mut builder = Self::builder();
+        if let Some(workers) = max_workers {
+            builder = builder.max_workers(workers);
+        }
+        builder.build()
+    }
+
+    /// Start configuring an ImageSidecar with non-default extensions, format, merge strategy or scan options
+    pub fn builder() -> ImageSidecarBuilder {
+        ImageSidecarBuilder::default()
     }
     
     /// Validate JSON sidecar files in parallel
@@ -55,16 +337,451 @@ impl ImageSidecar {
         self.processor.validate_directory(directory).await
     }
     
+    /// Validate sidecar files in parallel, invoking `progress` after each file
+    pub async fn validate_sidecars_with_progress(
+        &self,
+        directory: &Path,
+        progress: Option<ProgressCallback>,
+    ) -> Result<Vec<ValidationResult>> {
+        self.processor.validate_directory_with_progress(directory, progress).await
+    }
+    
+    /// Validate sidecar files under several directories in one parallel batch
+    /// Results are grouped per directory, in the order given
+    pub async fn validate_sidecars_multi(
+        &self,
+        directories: &[PathBuf],
+        progress: Option<ProgressCallback>,
+    ) -> Result<Vec<(PathBuf, Vec<ValidationResult>)>> {
+        self.processor.validate_directories_with_progress(directories, progress).await
+    }
+    
+    /// Validate sidecar files in parallel, streaming each result into `sink` as it is produced
+    pub async fn validate_sidecars_to_sink(
+        &self,
+        directory: &Path,
+        sink: &mut dyn Sink<ValidationResult>,
+        progress: Option<ProgressCallback>,
+    ) -> Result<ValidationSummary> {
+        self.processor.validate_directory_to_sink(directory, sink, progress).await
+    }
+    
+    /// Validate exactly the given sidecar files, without walking any directory
+    pub async fn validate_files(
+        &self,
+        sidecar_paths: &[PathBuf],
+        progress: Option<ProgressCallback>,
+    ) -> Result<Vec<ValidationResult>> {
+        self.processor.validate_files_parallel_with_progress(sidecar_paths, progress).await
+    }
+    
+    /// Validate exactly the given sidecar files, streaming each result into `sink`
+    pub async fn validate_files_to_sink(
+        &self,
+        sidecar_paths: &[PathBuf],
+        sink: &mut dyn Sink<ValidationResult>,
+        progress: Option<ProgressCallback>,
+    ) -> Result<ValidationSummary> {
+        self.processor.validate_files_to_sink(sidecar_paths, sink, progress).await
+    }
+    
     /// Get comprehensive statistics about sidecar files
     pub async fn get_statistics(&self, directory: &Path) -> Result<StatisticsResult> {
         self.manager.get_statistics(directory).await
     }
     
+    /// Get statistics for exactly the given sidecar files, without walking any directory
+    pub async fn get_statistics_for_files(
+        &self,
+        sidecar_paths: &[PathBuf],
+        progress: Option<ProgressCallback>,
+    ) -> Result<StatisticsResult> {
+        self.manager.get_statistics_for_files(sidecar_paths, progress).await
+    }
+    
+    /// Get per-directory and aggregate statistics for several directories, scanned concurrently
+    pub async fn get_statistics_multi(
+        &self,
+        directories: &[PathBuf],
+        progress: Option<ProgressCallback>,
+    ) -> Result<MultiRootStatistics> {
+        self.manager.get_statistics_multi(directories, progress).await
+    }
+    
+    /// Get statistics for a directory, refreshing the `.sidecar-stats.json` badge at its root
+    pub async fn get_statistics_with_badge(
+        &self,
+        directory: &Path,
+        progress: Option<ProgressCallback>,
+    ) -> Result<(StatisticsResult, BadgeRefresh)> {
+        self.manager.get_statistics_with_badge(directory, progress).await
+    }
+    
+    /// Sum the badge files under a directory into season-wide statistics without reading sidecars
+    pub async fn aggregate_statistics_badges(&self, directory: &Path) -> Result<BadgeRollup> {
+        self.manager.aggregate_statistics_badges(directory).await
+    }
+    
+    /// Per-operation coverage of the images under a directory, listing the images each operation is missing
+    pub async fn coverage(&self, directory: &Path, operations: &[String]) -> Result<CoverageReport> {
+        self.manager.coverage(directory, operations).await
+    }
+    
+    /// Validation, statistics, coverage and format counts of a directory from a single walk
+    pub async fn run_pipeline(&self, directory: &Path, pipeline: &ScanPipeline, progress: Option<ProgressCallback>) -> Result<ScanReport> {
+        self.manager.run_pipeline(directory, pipeline, progress).await
+    }
+    
+    /// Sidecars under a directory of unknown operation, grouped by top-level keys with sample payloads
+    pub async fn triage_unknown(&self, directory: &Path, samples: usize) -> Result<TriageReport> {
+        self.manager.triage_unknown(directory, samples).await
+    }
+    
+    /// Detections under a directory the models are least sure of, for human review
+    pub async fn review_queue(&self, directory: &Path, options: &ReviewOptions) -> Result<ReviewQueue> {
+        self.manager.review_queue(directory, options).await
+    }
+    
+    /// Images under a directory passing a quality and detection gate
+    pub async fn gate(&self, directory: &Path, gate: &QualityGate) -> Result<GateReport> {
+        self.manager.gate(directory, gate).await
+    }
+    
+    /// Frame sequences under a directory with per-interval detection rates and gaps
+    pub async fn timeline(&self, directory: &Path, options: &TimelineOptions) -> Result<Timeline> {
+        self.manager.timeline(directory, options).await
+    }
+    
+    /// Object tracks recorded by the tracking sidecars under a directory
+    pub async fn tracks(&self, directory: &Path, naming: &FrameNaming) -> Result<Vec<Track>> {
+        self.manager.tracks(directory, naming).await
+    }
+    
+    /// Every frame and box of one track under a directory
+    pub async fn track(&self, directory: &Path, track_id: &str, naming: &FrameNaming) -> Result<Option<Track>> {
+        self.manager.track(directory, track_id, naming).await
+    }
+    
+    /// Lint findings for every sidecar under a directory
+    pub async fn lint(&self, directory: &Path, linter: &Linter) -> Result<LintReport> {
+        self.manager.lint(directory, linter).await
+    }
+    
+    /// Sidecars under a directory that stand out from the others of their operation
+    pub async fn anomalies(&self, directory: &Path, options: &AnomalyOptions) -> Result<AnomalyReport> {
+        self.manager.anomalies(directory, options).await
+    }
+    
+    /// Read, parse and validate throughput of the sidecars under a directory, per format
+    pub async fn bench(&self, directory: &Path, options: &BenchOptions) -> Result<BenchReport> {
+        self.manager.bench(directory, options).await
+    }
+    
+    /// Write a synthetic dataset of frames and sidecars under a directory
+    pub async fn generate_dataset(&self, root: &Path, options: &GenerateOptions) -> Result<GenerateSummary> {
+        self.manager.generate_dataset(root, options).await
+    }
+    
+    /// Job descriptors covering every image under a directory that `operation` has not been run on
+    pub async fn pending_jobs(&self, directory: &Path, operation: &str, batch_size: usize) -> Result<Vec<JobSpec>> {
+        self.manager.pending_jobs(directory, operation, batch_size).await
+    }
+    
+    /// Sidecars under a directory whose image changed after they were written; `deep` rehashes every image
+    pub async fn find_stale_sidecars(&self, directory: &Path, deep: bool) -> Result<Vec<StaleSidecar>> {
+        self.manager.find_stale_sidecars(directory, deep).await
+    }
+    
+    /// Rename orphaned sidecars to follow images that were renamed or moved, matched by content hash
+    pub async fn reassociate(&self, directory: &Path, dry_run: bool) -> Result<Vec<Reassociation>> {
+        self.manager.reassociate(directory, dry_run).await
+    }
+    
+    /// Move or rename images, or directories of them, together with their sidecars
+    pub async fn relocate(&self, moves: &[(PathBuf, PathBuf)], dry_run: bool) -> Result<Vec<RelocationRecord>> {
+        self.manager.relocate(moves, dry_run).await
+    }
+    
+    /// Merge the given operations (all, if none) from an image's per-operation sidecars into one unified sidecar
+    pub async fn unify(&self, image_path: &Path, operations: &[OperationType]) -> Result<SidecarInfo> {
+        self.manager.unify(image_path, operations).await
+    }
+    
+    /// Unify the sidecars of every image under `directory`, optionally removing fully merged sources
+    pub async fn unify_directory(
+        &self,
+        directory: &Path,
+        operations: &[OperationType],
+        remove_sources: bool,
+        dry_run: bool,
+    ) -> Result<Vec<UnifyRecord>> {
+        self.manager.unify_directory(directory, operations, remove_sources, dry_run).await
+    }
+    
+    /// Rewrite the legacy sidecars under `directory` that `importer` recognises into modern sidecars
+    pub async fn import_legacy(
+        &self,
+        directory: &Path,
+        importer: &LegacyImporter,
+        keep_originals: bool,
+        dry_run: bool,
+    ) -> Result<MigrationReport> {
+        self.manager.import_legacy(directory, importer, keep_originals, dry_run).await
+    }
+    
+    /// Write the given operations (all, if none) of an image's sidecar to per-operation sidecars in `format`
+    pub async fn split(&self, image_path: &Path, operations: &[OperationType], format: SidecarFormat) -> Result<SplitRecord> {
+        self.manager.split(image_path, operations, format).await
+    }
+    
+    /// Split the sidecar of every image under `directory`, optionally removing fully split sidecars
+    pub async fn split_directory(
+        &self,
+        directory: &Path,
+        operations: &[OperationType],
+        format: SidecarFormat,
+        remove_unified: bool,
+        dry_run: bool,
+    ) -> Result<Vec<SplitRecord>> {
+        self.manager.split_directory(directory, operations, format, remove_unified, dry_run).await
+    }
+    
+    /// Start a transaction that writes, renames and removes sidecars together, journaled in `directory`
+    pub fn transaction(&self, directory: &Path) -> SidecarTransaction<'_> {
+        self.manager.transaction(directory)
+    }
+    
+    /// Finish or undo the transactions a crash interrupted in `directory`
+    pub async fn recover_transactions(&self, directory: &Path) -> Result<RecoverySummary> {
+        self.manager.recover_transactions(directory).await
+    }
+    
+    /// Get statistics about sidecar files, invoking `progress` after each scanned file
+    pub async fn get_statistics_with_progress(
+        &self,
+        directory: &Path,
+        progress: Option<ProgressCallback>,
+    ) -> Result<StatisticsResult> {
+        self.manager.get_statistics_with_progress(directory, progress).await
+    }
+    
     /// Find all sidecar files in a directory
+    /// Full contents load on demand through `SidecarInfo::payload`
     pub async fn find_sidecars(&self, directory: &Path) -> Result<Vec<SidecarInfo>> {
         self.manager.find_all_sidecars(directory).await
     }
     
+    /// List all sidecar files in a directory, reading only their metadata
+    /// See `SidecarManager::list_sidecars_with_progress`
+    pub async fn list_sidecars(&self, directory: &Path) -> Result<Vec<SidecarInfo>> {
+        self.manager.list_sidecars(directory).await
+    }
+    
+    /// Find all sidecars in a directory whose contents match `expr`
+    pub async fn query(&self, directory: &Path, expr: &QueryExpr) -> Result<Vec<QueryMatch>> {
+        self.manager.query(directory, expr).await
+    }
+    
+    /// Extract every value matching a JSONPath from all sidecars in a directory
+    pub async fn extract(&self, directory: &Path, path: &ExtractPath) -> Result<Vec<ExtractedValue>> {
+        self.manager.extract(directory, path).await
+    }
+    
+    /// Every sidecar in a directory cut down to the fields of `projection`, optionally only those holding `operations`
+    pub async fn project(&self, directory: &Path, projection: &Projection, operations: &[String]) -> Result<Vec<serde_json::Value>> {
+        self.manager.project(directory, projection, operations).await
+    }
+    
+    /// Compute and store perceptual hashes for every image in a directory
+    pub async fn hash_images(&self, directory: &Path) -> Result<usize> {
+        self.manager.hash_images(directory).await
+    }
+    
+    /// Group near-duplicate images by the pHash distance stored in their sidecars
+    pub async fn find_duplicates(&self, directory: &Path, threshold: u32) -> Result<Vec<DuplicateGroup>> {
+        self.manager.find_duplicates(directory, threshold).await
+    }
+    
+    /// Crop every detection recorded for `operation` into `output_dir`
+    pub async fn extract_crops(&self, directory: &Path, operation: OperationType, output_dir: &Path) -> Result<Vec<Crop>> {
+        self.manager.extract_crops(directory, operation, output_dir).await
+    }
+    
+    /// Rewrite every detection bbox in a directory into `convention`
+    pub async fn normalize_boxes(&self, directory: &Path, convention: BoxConvention, dry_run: bool) -> Result<NormalizeReport> {
+        self.manager.normalize_boxes(directory, convention, dry_run).await
+    }
+    
+    /// Fix recoverable corruption in every sidecar under a directory
+    pub async fn repair(&self, directory: &Path, dry_run: bool) -> Result<Vec<RepairRecord>> {
+        self.manager.repair(directory, dry_run).await
+    }
+    
+    /// Remove one operation's payload from an image's sidecar, deleting the sidecar if it empties
+    pub async fn remove_operation(&self, image_path: &Path, operation: OperationType) -> Result<OperationRemoval> {
+        self.manager.remove_operation(image_path, operation).await
+    }
+    
+    /// Remove one operation's payload from every sidecar under a directory
+    pub async fn strip_operation(
+        &self,
+        directory: &Path,
+        operation: OperationType,
+        dry_run: bool,
+    ) -> Result<Vec<(PathBuf, OperationRemoval)>> {
+        self.manager.strip_operation(directory, operation, dry_run).await
+    }
+    
+    /// Expire payloads and superseded sidecars under a directory according to `policy`
+    pub async fn collect_garbage(&self, directory: &Path, policy: &RetentionPolicy, dry_run: bool) -> Result<Vec<GcRecord>> {
+        self.manager.collect_garbage(directory, policy, dry_run).await
+    }
+    
+    /// Move every inline payload under a directory into the blob store, leaving pointers
+    pub async fn store_payloads(&self, directory: &Path) -> Result<usize> {
+        self.manager.store_payloads(directory).await
+    }
+    
+    /// Check that every pointer under a directory refers to an intact blob
+    pub async fn verify_blobs(&self, directory: &Path) -> Result<BlobReport> {
+        self.manager.verify_blobs(directory).await
+    }
+    
+    /// Check every payload under a directory against its signature and the trusted keys
+    pub async fn verify_signatures(&self, directory: &Path, verifier: &SignatureVerifier) -> Result<SignatureReport> {
+        self.manager.verify_signatures(directory, verifier).await
+    }
+    
+    /// Bundle every sidecar under a directory into one pack file, optionally removing the originals
+    pub async fn pack_directory(&self, directory: &Path, output: &Path, remove: bool) -> Result<usize> {
+        self.manager.pack_directory(directory, output, remove).await
+    }
+    
+    /// Write the sidecars in `pack` below `destination`
+    pub async fn unpack(&self, pack: &Pack, destination: &Path) -> Result<UnpackSummary> {
+        self.manager.unpack(pack, destination).await
+    }
+    
+    /// Stream the sidecars under a directory, optionally with their images, into a tarball
+    pub async fn export_archive(&self, directory: &Path, output: &Path, options: &ArchiveOptions) -> Result<ArchiveSummary> {
+        self.manager.export_archive(directory, output, options).await
+    }
+    
+    /// Write the detections under a directory as tf.Example records, sharded into TFRecord files
+    pub async fn export_tfrecord(&self, directory: &Path, output: &Path, options: &TfRecordOptions) -> Result<TfRecordSummary> {
+        self.manager.export_tfrecord(directory, output, options).await
+    }
+    
+    /// Write each image under a directory with its sidecar payloads as WebDataset tar shards
+    pub async fn export_webdataset(&self, directory: &Path, output: &Path, options: &WebDatasetOptions) -> Result<WebDatasetSummary> {
+        self.manager.export_webdataset(directory, output, options).await
+    }
+    
+    /// Strip or hash fields such as face encodings across a directory, in place or into `output`
+    pub async fn redact(&self, directory: &Path, rules: &[RedactRule], key: &RedactKey, output: Option<&Path>, dry_run: bool) -> Result<Vec<RedactionRecord>> {
+        self.manager.redact(directory, rules, key, output, dry_run).await
+    }
+    
+    /// Record every sidecar under a directory as snapshot `name` in `store`
+    pub async fn create_snapshot(&self, directory: &Path, store: &SnapshotStore, name: &str) -> Result<SnapshotSummary> {
+        self.manager.create_snapshot(directory, store, name).await
+    }
+    
+    /// Roll the sidecars under a directory back to snapshot `name`
+    pub async fn restore_snapshot(&self, directory: &Path, store: &SnapshotStore, name: &str) -> Result<RestoreSummary> {
+        self.manager.restore_snapshot(directory, store, name).await
+    }
+    
+    /// Sidecars under a directory inside a git work tree whose operations differ from `git_ref`
+    pub async fn git_status(&self, directory: &Path, git_ref: &str) -> Result<GitStatus> {
+        self.manager.git_status(directory, git_ref).await
+    }
+    
+    /// Write a `.dvc` file at `output` tracking the sidecars under a directory
+    pub async fn export_dvc(&self, directory: &Path, output: &Path) -> Result<DvcOutput> {
+        self.manager.export_dvc(directory, output).await
+    }
+    
+    /// Compare the sidecars under a directory with its output in a `.dvc` or `dvc.lock` file
+    pub async fn verify_dvc(&self, directory: &Path, lock: &Path) -> Result<DvcVerification> {
+        self.manager.verify_dvc(directory, lock).await
+    }
+    
+    /// Checksum every sidecar under a directory into a manifest
+    pub async fn create_manifest(&self, directory: &Path) -> Result<Manifest> {
+        self.manager.create_manifest(directory).await
+    }
+    
+    /// Detect sidecars added, removed or modified under a directory since `manifest` was created
+    pub async fn verify_manifest(&self, directory: &Path, manifest: &Manifest) -> Result<ManifestVerification> {
+        self.manager.verify_manifest(directory, manifest).await
+    }
+    
+    /// Compare sidecar coverage and per-operation payloads between two directories
+    pub async fn diff(&self, left: &Path, right: &Path) -> Result<DirectoryDiff> {
+        self.manager.diff_directories(left, right).await
+    }
+    
+    /// Merge two edited copies of a sidecar into `output`; three-way when `base` is given
+    pub async fn merge_sidecars(
+        &self,
+        base: Option<&Path>,
+        ours: &Path,
+        theirs: &Path,
+        output: &Path,
+    ) -> Result<MergeResult> {
+        self.manager.merge_sidecars(base, ours, theirs, output).await
+    }
+    
+    /// Build a nearest-neighbor index over the face encodings in a directory
+    pub async fn build_vector_index(&self, directory: &Path) -> Result<VectorIndex> {
+        self.manager.build_vector_index(directory).await
+    }
+    
+    /// Run SQL against the sidecars in a directory, exposed as the `sidecars` table
+    #[cfg(feature = "sql")]
+    pub async fn sql(&self, directory: &Path, query: &str) -> Result<sql::DataFrame> {
+        let records = self.manager.records(directory).await?;
+        sql::execute(&records, query)
+    }
+    
+    /// Write the sidecars in a directory as a HuggingFace dataset directory (parquet + dataset_infos.json)
+    #[cfg(feature = "huggingface")]
+    pub async fn export_huggingface(
+        &self,
+        directory: &Path,
+        output: &Path,
+        options: &huggingface::HfExportOptions,
+    ) -> Result<huggingface::HfExportSummary> {
+        let records = self.manager.records(directory).await?;
+        let (directory, output, options) = (directory.to_path_buf(), output.to_path_buf(), options.clone());
+        tokio::task::spawn_blocking(move || huggingface::write_dataset(&records, &directory, &output, &options)).await?
+    }
+    
+    /// Bulk-insert the sidecars in a directory into PostgreSQL or ClickHouse
+    /// Sidecars are decoded batch by batch as the database takes them, not all up front
+    #[cfg(feature = "db")]
+    pub async fn db_export(&self, directory: &Path, options: &db::DbExportOptions) -> Result<db::DbExportSummary> {
+        let batches = self.manager.record_batches(directory, options.batch_size);
+        let batches = futures::stream::unfold(batches, |mut batches| async move { batches.recv().await.map(|batch| (batch, batches)) });
+        db::export_stream(batches, options).await
+    }
+    
+    /// Find the sidecar file belonging to a single image
+    pub async fn find_sidecar_for_image(&self, image_path: &Path) -> Result<Option<SidecarInfo>> {
+        self.manager.find_sidecar_for_image(image_path).await
+    }
+
+    /// Get the number of worker threads used for parallel validation
+    pub fn max_workers(&self) -> usize {
+        self.processor.max_workers()
+    }
+
+    /// Access the underlying sidecar manager
+    pub fn manager(&self) -> &SidecarManager {
+        &self.manager
+    }
+
     /// Create a new sidecar file
     pub async fn create_sidecar(
         &self,
@@ -96,11 +813,62 @@ impl ImageSidecar {
         self.manager.cleanup_orphaned_sidecars(directory).await
     }
     
+    /// List orphaned sidecar files without removing them
+    pub async fn find_orphaned(&self, directory: &Path) -> Result<Vec<PathBuf>> {
+        self.manager.find_orphaned_sidecars(directory).await
+    }
+    
+    /// List image symlinks whose target is gone, with the sidecars that belong only to them
+    pub async fn find_broken_symlinks(&self, directory: &Path) -> Result<Vec<BrokenSymlink>> {
+        self.manager.find_broken_symlinks(directory).await
+    }
+    
+    /// Remove the sidecars that belong only to broken image symlinks
+    pub async fn cleanup_broken_symlinks(&self, directory: &Path) -> Result<usize> {
+        self.manager.cleanup_broken_symlink_sidecars(directory).await
+    }
+    
     /// Convert sidecar files between formats
     pub async fn convert_directory_format(&self, directory: &Path, target_format: SidecarFormat) -> Result<u32> {
         self.manager.convert_directory_format(directory, target_format).await
     }
     
+    /// Convert sidecar files between formats, invoking `progress` after each file
+    pub async fn convert_directory_format_with_progress(
+        &self,
+        directory: &Path,
+        target_format: SidecarFormat,
+        progress: Option<ProgressCallback>,
+    ) -> Result<u32> {
+        self.manager.convert_directory_format_with_progress(directory, target_format, progress).await
+    }
+    
+    /// Convert sidecar files between formats, skipping and recording files in `checkpoint`
+    pub async fn convert_directory_format_checkpointed(
+        &self,
+        directory: &Path,
+        target_format: SidecarFormat,
+        checkpoint: &mut Checkpoint,
+        progress: Option<ProgressCallback>,
+    ) -> Result<ConversionSummary> {
+        self.manager.convert_directory_format_checkpointed(directory, target_format, checkpoint, progress).await
+    }
+    
+    /// Convert the listed sidecar files between formats
+    pub async fn convert_files(&self, sidecar_paths: &[PathBuf], target_format: SidecarFormat) -> Result<ConversionSummary> {
+        self.manager.convert_files(sidecar_paths, target_format).await
+    }
+    
+    /// Convert the listed sidecar files between formats, invoking `progress` after each file
+    pub async fn convert_files_with_progress(
+        &self,
+        sidecar_paths: &[PathBuf],
+        target_format: SidecarFormat,
+        progress: Option<ProgressCallback>,
+    ) -> Result<ConversionSummary> {
+        self.manager.convert_files_with_progress(sidecar_paths, target_format, progress).await
+    }
+    
     /// Get format statistics for a directory
     pub async fn get_format_statistics(&self, directory: &Path) -> Result<std::collections::HashMap<SidecarFormat, u32>> {
         self.manager.get_format_statistics(directory).await
@@ -0,0 +1,232 @@
+/*
+ * Context: Prometheus metrics for long-running modes, served over a minimal HTTP endpoint
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: tokio
+ */
+
+use crate::parallel::ValidationSummary;
+use crate::sidecar::types::{Result, StatisticsResult, ValidationResult};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Upper bounds, in seconds, of the scan latency histogram buckets
+pub const SCAN_LATENCY_BUCKETS: [f64; 9] = [0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+
+/// Largest request head read before answering; anything longer is not a metrics scrape
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// Latest scan results for one directory
+#[derive(Debug, Clone, Default)]
+struct RootState {
+    images: u32,
+    sidecars: u32,
+    coverage_percentage: f64,
+    operation_counts: BTreeMap<String, u32>,
+    validated: Option<ValidationSummary>,
+    /// Size and error of each file the last validation found invalid
+    invalid: HashMap<PathBuf, (u64, Option<String>)>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Histogram {
+    /// Cumulative count per bucket in `SCAN_LATENCY_BUCKETS`
+    buckets: [u64; SCAN_LATENCY_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        for (bucket, bound) in self.buckets.iter_mut().zip(SCAN_LATENCY_BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += seconds;
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    roots: BTreeMap<String, RootState>,
+    validation_failures: u64,
+    converted: u64,
+    conversion_seconds: f64,
+    scan_latency: Histogram,
+}
+
+/// Pipeline health gauges and counters, rendered in the Prometheus text format
+///
+/// Gauges hold the latest scan of each directory; counters accumulate over the process lifetime.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    state: Mutex<State>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        // Every update leaves the state consistent, so a panic elsewhere does not invalidate it
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Record sidecar counts and coverage from a statistics scan
+    pub fn observe_statistics(&self, stats: &StatisticsResult) {
+        let mut state = self.state();
+        let root = state.roots.entry(stats.directory.display().to_string()).or_default();
+        root.images = stats.total_images;
+        root.sidecars = stats.total_sidecars;
+        root.coverage_percentage = stats.coverage_percentage;
+        root.operation_counts = stats.operation_counts.iter().map(|(op, count)| (op.clone(), *count)).collect();
+    }
+
+    /// Record the outcome of validating `root`
+    ///
+    /// A file adds to the failure counter when it turns invalid or changes while invalid, so
+    /// rescanning a directory does not count the same broken file again.
+    pub fn observe_validation(&self, root: &Path, results: &[ValidationResult]) {
+        let mut summary = ValidationSummary::default();
+        results.iter().for_each(|result| summary.record(result));
+        let invalid: HashMap<PathBuf, (u64, Option<String>)> = results
+            .iter()
+            .filter(|result| !result.is_valid)
+            .map(|result| (result.file_path.clone(), (result.file_size, result.error.clone())))
+            .collect();
+
+        let mut state = self.state();
+        let root = state.roots.entry(root.display().to_string()).or_default();
+        let new_failures = invalid.iter().filter(|(path, seen)| root.invalid.get(*path) != Some(seen)).count();
+        root.invalid = invalid;
+        root.validated = Some(summary);
+        state.validation_failures += new_failures as u64;
+    }
+
+    /// Record `files` sidecars converted in `elapsed`
+    pub fn record_conversion(&self, files: u64, elapsed: Duration) {
+        let mut state = self.state();
+        state.converted += files;
+        state.conversion_seconds += elapsed.as_secs_f64();
+    }
+
+    /// Record how long one directory scan took
+    pub fn observe_scan(&self, elapsed: Duration) {
+        self.state().scan_latency.observe(elapsed.as_secs_f64());
+    }
+
+    /// Text exposition format 0.0.4
+    pub fn render(&self) -> String {
+        let state = self.state();
+        let mut out = String::new();
+
+        family(&mut out, "image_sidecar_images", "gauge", "Images found in the last scan");
+        for (root, gauges) in &state.roots {
+            sample(&mut out, "image_sidecar_images", &[("root", root)], gauges.images);
+        }
+        family(&mut out, "image_sidecar_sidecars", "gauge", "Sidecar files found in the last scan");
+        for (root, gauges) in &state.roots {
+            sample(&mut out, "image_sidecar_sidecars", &[("root", root)], gauges.sidecars);
+        }
+        family(&mut out, "image_sidecar_operation_sidecars", "gauge", "Sidecar files per operation in the last scan");
+        for (root, gauges) in &state.roots {
+            for (operation, count) in &gauges.operation_counts {
+                sample(&mut out, "image_sidecar_operation_sidecars", &[("root", root), ("operation", operation)], count);
+            }
+        }
+        family(&mut out, "image_sidecar_coverage_percent", "gauge", "Percentage of images with a sidecar");
+        for (root, gauges) in &state.roots {
+            sample(&mut out, "image_sidecar_coverage_percent", &[("root", root)], gauges.coverage_percentage);
+        }
+        family(&mut out, "image_sidecar_invalid_sidecars", "gauge", "Invalid sidecar files in the last validation");
+        for (root, gauges) in &state.roots {
+            if let Some(summary) = &gauges.validated {
+                sample(&mut out, "image_sidecar_invalid_sidecars", &[("root", root)], summary.invalid_files);
+            }
+        }
+
+        family(&mut out, "image_sidecar_validation_failures_total", "counter", "Sidecar files found invalid, counted once per change");
+        sample(&mut out, "image_sidecar_validation_failures_total", &[], state.validation_failures);
+        family(&mut out, "image_sidecar_converted_total", "counter", "Sidecar files converted between formats");
+        sample(&mut out, "image_sidecar_converted_total", &[], state.converted);
+        family(&mut out, "image_sidecar_conversion_seconds_total", "counter", "Time spent converting sidecar files");
+        sample(&mut out, "image_sidecar_conversion_seconds_total", &[], state.conversion_seconds);
+
+        let histogram = &state.scan_latency;
+        family(&mut out, "image_sidecar_scan_duration_seconds", "histogram", "Directory scan latency");
+        for (bound, count) in SCAN_LATENCY_BUCKETS.iter().zip(histogram.buckets) {
+            sample(&mut out, "image_sidecar_scan_duration_seconds_bucket", &[("le", &bound.to_string())], count);
+        }
+        sample(&mut out, "image_sidecar_scan_duration_seconds_bucket", &[("le", "+Inf")], histogram.count);
+        sample(&mut out, "image_sidecar_scan_duration_seconds_sum", &[], histogram.sum);
+        sample(&mut out, "image_sidecar_scan_duration_seconds_count", &[], histogram.count);
+
+        out
+    }
+}
+
+fn family(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn sample(out: &mut String, name: &str, labels: &[(&str, &str)], value: impl std::fmt::Display) {
+    out.push_str(name);
+    if !labels.is_empty() {
+        let labels: Vec<String> = labels
+            .iter()
+            .map(|(key, value)| format!("{}=\"{}\"", key, escape_label(value)))
+            .collect();
+        let _ = write!(out, "{{{}}}", labels.join(","));
+    }
+    let _ = writeln!(out, " {}", value);
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Answer `GET /metrics` on `listener` until the task is dropped; other paths get a 404
+pub async fn serve(listener: TcpListener, metrics: Arc<Metrics>) -> Result<()> {
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let metrics = Arc::clone(&metrics);
+
+        tokio::spawn(async move {
+            let mut request = Vec::with_capacity(1024);
+            let mut chunk = [0u8; 1024];
+            while !request.windows(4).any(|window| window == b"\r\n\r\n") && request.len() < MAX_REQUEST_BYTES {
+                match stream.read(&mut chunk).await {
+                    Ok(0) | Err(_) => return,
+                    Ok(read) => request.extend_from_slice(&chunk[..read]),
+                }
+            }
+
+            let request_line = String::from_utf8_lossy(&request);
+            let mut parts = request_line.split_whitespace();
+            let response = match (parts.next(), parts.next()) {
+                (Some("GET"), Some("/metrics")) => {
+                    let body = metrics.render();
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    )
+                }
+                _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+            };
+            // The scraper may already be gone; nothing to report in that case
+            let _ = stream.write_all(response.as_bytes()).await;
+            let _ = stream.shutdown().await;
+        });
+    }
+}
@@ -1,4 +1,4 @@
-/**
+/*
  * This code written by Claude Sonnet 4 (claude-3-5-sonnet-20241022)
  * Generated via Cursor IDE (cursor.sh) with AI assistance
  * Model: Anthropic Claude 3.5 Sonnet
@@ -10,9 +10,19 @@
  * - IDE: Cursor (cursor.sh)
  * - Generation method: AI-assisted pair programming
  * - Code style: Rust idiomatic with comprehensive error handling
- * - Dependencies: tokio, rayon, anyhow
+ * - Dependencies: tokio, rayon
  */
 
 pub mod processor;
+pub mod priority;
+pub mod progress;
+pub mod sink;
+pub mod throttle;
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+pub mod uring;
 
 pub use processor::ParallelProcessor;
+pub use priority::lower_priority;
+pub use progress::{split_progress, ProgressCallback, ProgressTracker};
+pub use sink::{NdjsonSink, Sink, Tee, ValidationSummary};
+pub use throttle::{IoLimits, Throttle};
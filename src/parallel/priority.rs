@@ -0,0 +1,74 @@
+/*
+ * Context: Lower CPU and IO priority for background runs
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: libc
+ */
+
+/// Niceness set by `lower_priority`, the same as running under `nice` without arguments
+pub const BACKGROUND_NICENESS: i32 = 10;
+
+/// Lower the CPU priority of every thread in the process, and on Linux move its IO to the idle class
+///
+/// Threads started afterwards inherit the lower priority from the thread that spawns them.
+/// Fails only if the CPU priority could not be changed; the IO class is best effort.
+#[cfg(target_os = "linux")]
+pub fn lower_priority() -> std::io::Result<()> {
+    // Linux keeps niceness and IO priority per thread, so each existing thread is updated
+    for entry in std::fs::read_dir("/proc/self/task")? {
+        let Some(tid) = entry?.file_name().to_str().and_then(|name| name.parse::<libc::id_t>().ok()) else {
+            continue;
+        };
+        set_niceness(tid)?;
+        if let Err(e) = set_idle_io(tid) {
+            tracing::debug!("Could not set idle IO priority for thread {}: {}", tid, e);
+        }
+    }
+    Ok(())
+}
+
+/// Lower the CPU priority of the process
+#[cfg(all(unix, not(target_os = "linux")))]
+pub fn lower_priority() -> std::io::Result<()> {
+    set_niceness(0)
+}
+
+#[cfg(not(unix))]
+pub fn lower_priority() -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "Lowering priority is only supported on Unix",
+    ))
+}
+
+#[cfg(unix)]
+fn set_niceness(who: libc::id_t) -> std::io::Result<()> {
+    // SAFETY: setpriority only reads its integer arguments
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS, who, BACKGROUND_NICENESS) } == 0 {
+        return Ok(());
+    }
+    let error = std::io::Error::last_os_error();
+    // Unprivileged processes cannot lower their niceness, which only means they already run lower
+    if error.kind() == std::io::ErrorKind::PermissionDenied {
+        return Ok(());
+    }
+    Err(error)
+}
+
+#[cfg(target_os = "linux")]
+fn set_idle_io(tid: libc::id_t) -> std::io::Result<()> {
+    const IOPRIO_WHO_PROCESS: libc::c_long = 1;
+    const IOPRIO_CLASS_IDLE: libc::c_long = 3;
+    const IOPRIO_CLASS_SHIFT: libc::c_long = 13;
+
+    // SAFETY: ioprio_set takes three integers and touches no memory of ours
+    let result = unsafe {
+        libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, tid as libc::c_long, IOPRIO_CLASS_IDLE << IOPRIO_CLASS_SHIFT)
+    };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
@@ -1,4 +1,4 @@
-/**
+/*
  * This code written by Claude Sonnet 4 (claude-3-5-sonnet-20241022)
  * Generated via Cursor IDE (cursor.sh) with AI assistance
  * Model: Anthropic Claude 3.5 Sonnet
@@ -10,109 +10,443 @@
  * - IDE: Cursor (cursor.sh)
  * - Generation method: AI-assisted pair programming
  * - Code style: Rust idiomatic with comprehensive error handling
- * - Dependencies: tokio, rayon, anyhow
+ * - Dependencies: tokio, rayon
  */
 
-use crate::sidecar::types::{ValidationResult, OperationType};
-use crate::sidecar::formats::{SidecarFormat, FormatManager};
-use anyhow::Result;
+use crate::sidecar::types::{ValidationResult, OperationType, DEFAULT_MAX_SIDECAR_SIZE};
+use crate::sidecar::formats::{SidecarFormat, FormatManager, SerializationError};
+use crate::sidecar::badge::is_badge_file;
+use crate::sidecar::scan::FileSelection;
+use crate::parallel::progress::{ProgressCallback, ProgressTracker};
+use crate::parallel::sink::{Sink, ValidationSummary};
+use crate::parallel::throttle::Throttle;
+use crate::query::DetectionFilter;
+use crate::utils::paths;
+use crate::sidecar::types::Result;
 use rayon::prelude::*;
-use std::path::Path;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use futures::stream::{self, StreamExt};
+use std::path::{Path, PathBuf};
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::fs;
+use tokio::sync::oneshot;
 use walkdir::WalkDir;
 
+/// Number of files handed to the io_uring reader per ring
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+const URING_BATCH_SIZE: usize = 1024;
+
 /// Parallel processor for high-performance sidecar operations
+/// Work runs on a dedicated rayon pool sized to `max_workers` rather than the global pool
 pub struct ParallelProcessor {
     max_workers: usize,
+    pool: ThreadPool,
+    format_manager: Arc<FormatManager>,
+    throttle: Arc<Throttle>,
+    selection: FileSelection,
+    detection_filter: Arc<DetectionFilter>,
+    max_sidecar_size: u64,
 }
 
 impl ParallelProcessor {
     /// Create a new ParallelProcessor instance
     pub fn new(max_workers: usize) -> Self {
-        Self { max_workers }
+        Self::with_throttle(max_workers, Arc::new(Throttle::unlimited()))
+    }
+
+    /// Create a ParallelProcessor whose file reads are paced by `throttle`
+    pub fn with_throttle(max_workers: usize, throttle: Arc<Throttle>) -> Self {
+        let max_workers = max_workers.max(1);
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(max_workers)
+            .thread_name(|index| format!("sidecar-worker-{}", index))
+            .build()
+            .expect("failed to build sidecar worker thread pool");
+        
+        Self {
+            max_workers,
+            pool,
+            format_manager: Arc::new(FormatManager::new()),
+            throttle,
+            selection: FileSelection::default(),
+            detection_filter: Arc::new(DetectionFilter::default()),
+            max_sidecar_size: DEFAULT_MAX_SIDECAR_SIZE,
+        }
+    }
+
+    /// Report sidecars over `bytes` as oversized without reading them (default 256 MiB)
+    pub fn with_max_sidecar_size(mut self, bytes: u64) -> Self {
+        self.max_sidecar_size = bytes;
+        self
+    }
+
+    /// Validate only the scanned sidecars `selection` admits, in its order
+    pub fn with_selection(mut self, selection: FileSelection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Only report valid sidecars with a payload within `filter`'s detection bounds;
+    /// invalid sidecars are always reported
+    pub fn with_detection_filter(mut self, filter: DetectionFilter) -> Self {
+        self.detection_filter = Arc::new(filter);
+        self
+    }
+
+    /// Get the number of worker threads used for parallel operations
+    pub fn max_workers(&self) -> usize {
+        self.max_workers
     }
 
     /// Validate all sidecar files in a directory in parallel
     pub async fn validate_directory(&self, directory: &Path) -> Result<Vec<ValidationResult>> {
+        self.validate_directory_with_progress(directory, None).await
+    }
+
+    /// Validate all sidecar files in a directory, reporting progress per file
+    pub async fn validate_directory_with_progress(
+        &self,
+        directory: &Path,
+        progress: Option<ProgressCallback>,
+    ) -> Result<Vec<ValidationResult>> {
         let sidecar_files = self.find_sidecar_files(directory).await?;
-        self.validate_files_parallel(&sidecar_files).await
+        self.validate_files_parallel_with_progress(&sidecar_files, progress).await
+    }
+
+    /// Validate every sidecar under several directories as one parallel batch
+    /// Directories are walked concurrently and their files share the same `max_workers`
+    /// budget; results come back grouped per directory, in the order given. Repeated roots and
+    /// roots inside another are dropped, so each file is validated once.
+    pub async fn validate_directories_with_progress(
+        &self,
+        directories: &[PathBuf],
+        progress: Option<ProgressCallback>,
+    ) -> Result<Vec<(PathBuf, Vec<ValidationResult>)>> {
+        let directories = &paths::distinct_roots(directories);
+        let per_directory = futures::future::try_join_all(
+            directories.iter().map(|directory| self.find_sidecar_files(directory)),
+        ).await?;
+
+        let counts: Vec<usize> = per_directory.iter().map(Vec::len).collect();
+        let all_files: Vec<PathBuf> = per_directory.into_iter().flatten().collect();
+        let mut results = self.validate_files_parallel_with_progress(&all_files, progress).await?.into_iter();
+
+        Ok(directories
+            .iter()
+            .zip(counts)
+            .map(|(directory, count)| (directory.clone(), results.by_ref().take(count).collect()))
+            .collect())
     }
 
     /// Validate multiple sidecar files in parallel
     pub async fn validate_files_parallel(&self, file_paths: &[std::path::PathBuf]) -> Result<Vec<ValidationResult>> {
+        self.validate_files_parallel_with_progress(file_paths, None).await
+    }
+
+    /// Validate multiple sidecar files in parallel, reporting progress per file
+    /// Reads go through `tokio::fs` with at most `max_workers` files in flight, while
+    /// deserialization runs on the dedicated rayon pool so the async runtime is never blocked.
+    /// With the `io-uring` feature on Linux, reads are batched through io_uring instead and
+    /// fall back to `tokio::fs` if the ring cannot be set up or IO limits are configured
+    pub async fn validate_files_parallel_with_progress(
+        &self,
+        file_paths: &[std::path::PathBuf],
+        progress: Option<ProgressCallback>,
+    ) -> Result<Vec<ValidationResult>> {
         if file_paths.is_empty() {
             return Ok(Vec::new());
         }
 
-        // Use rayon for parallel processing
-        let results: Vec<ValidationResult> = file_paths
-            .par_iter()
-            .map(|path| {
-                let start_time = std::time::Instant::now();
-                
-                if !path.exists() {
-                    return ValidationResult::error(
-                        path.clone(),
-                        "File does not exist".to_string(),
-                        start_time.elapsed().as_secs_f64(),
-                    );
-                }
+        let tracker = ProgressTracker::new(progress, file_paths.len());
+        let mut indexed_results = Vec::with_capacity(file_paths.len());
 
-                match std::fs::metadata(path) {
-                    Ok(metadata) => {
-                        let file_size = metadata.len();
-
-                        match std::fs::read(path) {
-                            Ok(content_bytes) => {
-                                // Use format manager to deserialize
-                                let format_manager = FormatManager::new();
-                                
-                                // Detect format from file extension first
-                                let format = SidecarFormat::from_path(path)
-                                    .unwrap_or(SidecarFormat::Json);
-                                
-                                match format_manager.get_serializer(format).deserialize(&content_bytes) {
-                                    Ok(data) => {
-                                        let processing_time = start_time.elapsed().as_secs_f64();
-                                        let detection_count = self.extract_detection_count(&data);
-                                        let tool_name = self.extract_tool_name(&data);
-                                        let operation_type = self.extract_operation_type(&data);
-
-                                        let mut result = ValidationResult::success(
-                                            path.clone(),
-                                            processing_time,
-                                            file_size,
-                                        );
-                                        result.detection_count = detection_count;
-                                        result.tool_name = tool_name;
-                                        result.operation_type = operation_type;
-
-                                        result
-                                    }
-                                    Err(e) => ValidationResult::error(
-                                        path.clone(),
-                                        format!("Deserialization error: {}", e),
-                                        start_time.elapsed().as_secs_f64(),
-                                    ),
-                                }
-                            }
-                            Err(e) => ValidationResult::error(
-                                path.clone(),
-                                format!("File read error: {}", e),
-                                start_time.elapsed().as_secs_f64(),
-                            ),
+        self.validate_each(file_paths, &tracker, |index, result| {
+            indexed_results.push((index, result));
+            Ok(())
+        }).await?;
+
+        // Restore input order, which concurrent reads do not preserve
+        indexed_results.sort_unstable_by_key(|(index, _)| *index);
+
+        Ok(indexed_results.into_iter().map(|(_, result)| result).collect())
+    }
+
+    /// Validate all sidecar files in a directory, streaming each result into `sink`
+    /// Results arrive in completion order and are never collected, so memory stays flat
+    /// regardless of how many files are validated
+    pub async fn validate_directory_to_sink(
+        &self,
+        directory: &Path,
+        sink: &mut dyn Sink<ValidationResult>,
+        progress: Option<ProgressCallback>,
+    ) -> Result<ValidationSummary> {
+        let sidecar_files = self.find_sidecar_files(directory).await?;
+        self.validate_files_to_sink(&sidecar_files, sink, progress).await
+    }
+
+    /// Validate the given sidecar files, streaming each result into `sink` as it completes
+    pub async fn validate_files_to_sink(
+        &self,
+        file_paths: &[PathBuf],
+        sink: &mut dyn Sink<ValidationResult>,
+        progress: Option<ProgressCallback>,
+    ) -> Result<ValidationSummary> {
+        let tracker = ProgressTracker::new(progress, file_paths.len());
+        let mut summary = ValidationSummary::default();
+
+        self.validate_each(file_paths, &tracker, |_, result| {
+            summary.record(&result);
+            sink.write(&result)
+        }).await?;
+        sink.finish()?;
+
+        Ok(summary)
+    }
+
+    /// Validate every file and hand each `(input index, result)` to `on_result` as it completes
+    async fn validate_each<F>(
+        &self,
+        file_paths: &[std::path::PathBuf],
+        tracker: &ProgressTracker,
+        mut on_result: F,
+    ) -> Result<()>
+    where
+        F: FnMut(usize, ValidationResult) -> Result<()>,
+    {
+        // Limits are enforced per file, which batched io_uring reads cannot honour
+        #[cfg(all(feature = "io-uring", target_os = "linux"))]
+        if !self.throttle.limits().is_unlimited() {
+            return self.validate_batch_tokio(file_paths, 0, tracker, &mut on_result).await;
+        }
+
+        #[cfg(all(feature = "io-uring", target_os = "linux"))]
+        for (batch_index, batch) in file_paths.chunks(URING_BATCH_SIZE).enumerate() {
+            let offset = batch_index * URING_BATCH_SIZE;
+            match self.validate_batch_uring(batch).await {
+                Ok(results) => {
+                    for ((index, result), path) in results.into_iter().enumerate().zip(batch) {
+                        if let Some(result) = result {
+                            on_result(offset + index, result)?;
                         }
+                        tracker.advance(path);
                     }
-                    Err(e) => ValidationResult::error(
-                        path.clone(),
-                        format!("File metadata error: {}", e),
-                        start_time.elapsed().as_secs_f64(),
-                    ),
                 }
-            })
-            .collect();
+                Err(e) => {
+                    tracing::debug!("io_uring unavailable, falling back to tokio::fs: {}", e);
+                    self.validate_batch_tokio(batch, offset, tracker, &mut on_result).await?;
+                }
+            }
+        }
+
+        #[cfg(not(all(feature = "io-uring", target_os = "linux")))]
+        self.validate_batch_tokio(file_paths, 0, tracker, &mut on_result).await?;
 
-        Ok(results)
+        Ok(())
+    }
+
+    /// Validate a batch of files, reading each one through `tokio::fs`
+    async fn validate_batch_tokio<F>(
+        &self,
+        batch: &[std::path::PathBuf],
+        offset: usize,
+        tracker: &ProgressTracker,
+        on_result: &mut F,
+    ) -> Result<()>
+    where
+        F: FnMut(usize, ValidationResult) -> Result<()>,
+    {
+        let mut results = stream::iter(batch.iter().enumerate())
+            .map(|(index, path)| async move { (offset + index, self.validate_file(path).await) })
+            .buffer_unordered(self.max_workers);
+
+        while let Some((index, result)) = results.next().await {
+            if let Some(result) = result {
+                on_result(index, result)?;
+            }
+            tracker.advance(&batch[index - offset]);
+        }
+
+        Ok(())
+    }
+
+    /// Validate one batch of files using the io_uring reader
+    /// Returns an error only if the ring itself could not be used; per-file failures are
+    /// reported as invalid results, and sidecars the detection filter rejects as `None`
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    async fn validate_batch_uring(&self, batch: &[std::path::PathBuf]) -> std::io::Result<Vec<Option<ValidationResult>>> {
+        let max_sidecar_size = self.max_sidecar_size;
+        let reads = crate::parallel::uring::read_files(batch.to_vec(), self.max_workers, max_sidecar_size).await?;
+
+        // Deserialize the whole batch on the rayon pool, keeping read order
+        let (sender, receiver) = oneshot::channel();
+        let format_manager = Arc::clone(&self.format_manager);
+        let detection_filter = Arc::clone(&self.detection_filter);
+        self.pool.spawn(move || {
+            let results: Vec<Option<ValidationResult>> = reads
+                .into_par_iter()
+                .map(|read| match read.contents {
+                    Ok(content_bytes) => {
+                        let file_size = content_bytes.len() as u64;
+                        Self::validate_content(&format_manager, &detection_filter, read.path, content_bytes, file_size, read.started)
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::FileTooLarge => {
+                        let size = std::fs::metadata(&read.path).map_or(max_sidecar_size + 1, |metadata| metadata.len());
+                        Some(ValidationResult::oversized(read.path, size, max_sidecar_size, read.started.elapsed().as_secs_f64()))
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => Some(ValidationResult::error(
+                        read.path,
+                        "File does not exist".to_string(),
+                        read.started.elapsed().as_secs_f64(),
+                    )),
+                    Err(e) => Some(ValidationResult::error(
+                        read.path,
+                        format!("File read error: {}", e),
+                        read.started.elapsed().as_secs_f64(),
+                    )),
+                })
+                .collect();
+            let _ = sender.send(results);
+        });
+
+        receiver
+            .await
+            .map_err(|_| std::io::Error::other("Validation worker terminated unexpectedly"))
+    }
+
+    /// Validate a single sidecar file; `None` if the detection filter rejects it
+    async fn validate_file(&self, path: &Path) -> Option<ValidationResult> {
+        let _file = self.throttle.open_file().await;
+        let start_time = std::time::Instant::now();
+
+        let file_size = match fs::metadata(path).await {
+            Ok(metadata) => metadata.len(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Some(ValidationResult::error(
+                    path.to_path_buf(),
+                    "File does not exist".to_string(),
+                    start_time.elapsed().as_secs_f64(),
+                ));
+            }
+            Err(e) => {
+                return Some(ValidationResult::error(
+                    path.to_path_buf(),
+                    format!("File metadata error: {}", e),
+                    start_time.elapsed().as_secs_f64(),
+                ));
+            }
+        };
+        if file_size > self.max_sidecar_size {
+            return Some(ValidationResult::oversized(
+                path.to_path_buf(),
+                file_size,
+                self.max_sidecar_size,
+                start_time.elapsed().as_secs_f64(),
+            ));
+        }
+
+        let content_bytes = match fs::read(path).await {
+            Ok(content_bytes) => {
+                self.throttle.record_read(content_bytes.len() as u64).await;
+                content_bytes
+            }
+            Err(e) => {
+                return Some(ValidationResult::error(
+                    path.to_path_buf(),
+                    format!("File read error: {}", e),
+                    start_time.elapsed().as_secs_f64(),
+                ));
+            }
+        };
+
+        // Hand the CPU-bound part to the rayon pool and await its result
+        let (sender, receiver) = oneshot::channel();
+        let owned_path = path.to_path_buf();
+        let format_manager = Arc::clone(&self.format_manager);
+        let detection_filter = Arc::clone(&self.detection_filter);
+        self.pool.spawn(move || {
+            let _ = sender.send(Self::validate_content(&format_manager, &detection_filter, owned_path, content_bytes, file_size, start_time));
+        });
+
+        match receiver.await {
+            Ok(result) => result,
+            Err(_) => Some(ValidationResult::error(
+                path.to_path_buf(),
+                "Validation worker terminated unexpectedly".to_string(),
+                start_time.elapsed().as_secs_f64(),
+            )),
+        }
+    }
+
+    /// Deserialize sidecar content and extract validation details
+    /// Returns `None` for a decoded sidecar with no payload within `detection_filter`
+    fn validate_content(
+        format_manager: &FormatManager,
+        detection_filter: &DetectionFilter,
+        path: std::path::PathBuf,
+        content_bytes: Vec<u8>,
+        file_size: u64,
+        start_time: Instant,
+    ) -> Option<ValidationResult> {
+        // Detect format from file extension first
+        let format = SidecarFormat::from_path(&path)
+            .unwrap_or(SidecarFormat::Json);
+        
+        let deserialize_start = Instant::now();
+        let deserialized = format_manager.get_serializer(format).deserialize(&content_bytes);
+        let deserialize_time = deserialize_start.elapsed().as_secs_f64();
+        
+        Self::validate_decoded(detection_filter, path, format, deserialized.as_ref(), file_size, start_time, deserialize_time)
+    }
+
+    /// Validation details of a sidecar already deserialized as `format`
+    /// Returns `None` for a decoded sidecar with no payload within `detection_filter`
+    pub(crate) fn validate_decoded(
+        detection_filter: &DetectionFilter,
+        path: std::path::PathBuf,
+        format: SidecarFormat,
+        deserialized: std::result::Result<&serde_json::Value, &SerializationError>,
+        file_size: u64,
+        start_time: Instant,
+        deserialize_time: f64,
+    ) -> Option<ValidationResult> {
+        match deserialized {
+            Ok(data) => {
+                let processing_time = start_time.elapsed().as_secs_f64();
+                let detection_count = Self::extract_detection_count(data);
+                let tool_name = Self::extract_tool_name(data);
+                let operation_type = Self::extract_operation_type(data);
+                if !detection_filter.matches(data, operation_type.as_ref().map(OperationType::as_str)) {
+                    return None;
+                }
+
+                let mut result = ValidationResult::success(
+                    path,
+                    processing_time,
+                    file_size,
+                );
+                result.detection_count = detection_count;
+                result.tool_name = tool_name;
+                result.operation_type = operation_type;
+                result.format = Some(format);
+                result.deserialize_time = deserialize_time;
+
+                Some(result)
+            }
+            Err(e) => {
+                let mut result = ValidationResult::error(
+                    path,
+                    format!("Deserialization error: {}", e),
+                    start_time.elapsed().as_secs_f64(),
+                );
+                result.file_size = file_size;
+                result.format = Some(format);
+                result.deserialize_time = deserialize_time;
+
+                Some(result)
+            }
+        }
     }
 
     /// Filter sidecar files by operation type in parallel
@@ -121,21 +455,23 @@ impl ParallelProcessor {
         file_paths: &[std::path::PathBuf],
         operation_type: &str,
     ) -> Result<Vec<std::path::PathBuf>> {
-        let filtered: Vec<std::path::PathBuf> = file_paths
-            .par_iter()
-            .filter(|path| {
-                match std::fs::read_to_string(path) {
-                    Ok(content) => {
-                        match serde_json::from_str::<serde_json::Value>(&content) {
-                            Ok(data) => self.contains_operation_type(&data, operation_type),
-                            Err(_) => true, // Include files that can't be parsed for validation
+        let filtered: Vec<std::path::PathBuf> = self.pool.install(|| {
+            file_paths
+                .par_iter()
+                .filter(|path| {
+                    match std::fs::read_to_string(path) {
+                        Ok(content) => {
+                            match serde_json::from_str::<serde_json::Value>(&content) {
+                                Ok(data) => self.contains_operation_type(&data, operation_type),
+                                Err(_) => true, // Include files that can't be parsed for validation
+                            }
                         }
+                        Err(_) => true, // Include files that can't be read for validation
                     }
-                    Err(_) => true, // Include files that can't be read for validation
-                }
-            })
-            .cloned()
-            .collect();
+                })
+                .cloned()
+                .collect()
+        });
 
         Ok(filtered)
     }
@@ -166,6 +502,7 @@ impl ParallelProcessor {
         stats.insert("total_files".to_string(), serde_json::Value::Number(serde_json::Number::from(total_files)));
         stats.insert("valid_files".to_string(), serde_json::Value::Number(serde_json::Number::from(valid_files)));
         stats.insert("invalid_files".to_string(), serde_json::Value::Number(serde_json::Number::from(invalid_files)));
+        stats.insert("oversized_files".to_string(), serde_json::Value::Number(serde_json::Number::from(results.iter().filter(|r| r.oversized).count())));
         stats.insert("valid_percentage".to_string(), serde_json::Value::Number(serde_json::Number::from_f64((valid_files as f64 / total_files as f64) * 100.0).unwrap()));
         stats.insert("invalid_percentage".to_string(), serde_json::Value::Number(serde_json::Number::from_f64((invalid_files as f64 / total_files as f64) * 100.0).unwrap()));
         stats.insert("total_processing_time".to_string(), serde_json::Value::Number(serde_json::Number::from_f64(total_processing_time).unwrap()));
@@ -202,31 +539,63 @@ impl ParallelProcessor {
 
         stats.insert("operation_stats".to_string(), serde_json::Value::Object(operation_stats_json));
 
+        // Group deserialization cost by format
+        let mut format_stats = HashMap::new();
+        for result in results {
+            if let Some(format) = result.format {
+                let entry = format_stats.entry(format.extension()).or_insert((0, 0.0, 0u64));
+                entry.0 += 1; // count
+                entry.1 += result.deserialize_time; // total deserialize time
+                entry.2 += result.file_size; // total size
+            }
+        }
+
+        let mut format_stats_json = serde_json::Map::new();
+        for (format, (count, total_time, total_size)) in format_stats {
+            let mut format_stat = serde_json::Map::new();
+            format_stat.insert("count".to_string(), serde_json::Value::Number(serde_json::Number::from(count)));
+            format_stat.insert("total_deserialize_time".to_string(), serde_json::Value::Number(serde_json::Number::from_f64(total_time).unwrap()));
+            format_stat.insert("avg_deserialize_time".to_string(), serde_json::Value::Number(serde_json::Number::from_f64(total_time / count as f64).unwrap()));
+            format_stat.insert("bytes_per_second".to_string(), serde_json::Value::Number(serde_json::Number::from_f64(if total_time > 0.0 { total_size as f64 / total_time } else { 0.0 }).unwrap()));
+
+            format_stats_json.insert(format.to_string(), serde_json::Value::Object(format_stat));
+        }
+
+        stats.insert("format_stats".to_string(), serde_json::Value::Object(format_stats_json));
+
         stats
     }
 
     // Private helper methods
 
     async fn find_sidecar_files(&self, directory: &Path) -> Result<Vec<std::path::PathBuf>> {
-        let mut sidecar_files = Vec::new();
-
-        for entry in WalkDir::new(directory).into_iter().filter_map(|e| e.ok()) {
-            if entry.file_type().is_file() {
-                let path = entry.path();
-                if let Some(extension) = path.extension() {
-                    let ext_str = extension.to_string_lossy().to_lowercase();
-                    // Look for all supported sidecar formats
-                    if matches!(ext_str.as_str(), "json" | "bin" | "rkyv") {
-                        sidecar_files.push(path.to_path_buf());
+        let directory = directory.to_path_buf();
+        let selection = self.selection;
+
+        // Directory walking is blocking, so keep it off the async worker threads
+        let sidecar_files = tokio::task::spawn_blocking(move || {
+            let mut sidecar_files = Vec::new();
+
+            for entry in WalkDir::new(&directory).into_iter().filter_map(|e| e.ok()) {
+                if entry.file_type().is_file() {
+                    let path = entry.path();
+                    if let Some(extension) = path.extension() {
+                        let ext_str = extension.to_string_lossy().to_lowercase();
+                        // Look for all supported sidecar formats
+                        if matches!(ext_str.as_str(), "json" | "bin" | "rkyv") && !is_badge_file(path) {
+                            sidecar_files.push(path.to_path_buf());
+                        }
                     }
                 }
             }
-        }
+
+            selection.apply(sidecar_files)
+        }).await?;
 
         Ok(sidecar_files)
     }
 
-    fn extract_detection_count(&self, data: &serde_json::Value) -> u32 {
+    pub(crate) fn extract_detection_count(data: &serde_json::Value) -> u32 {
         // Try common detection count fields
         if let Some(count) = data.get("count").and_then(|v| v.as_u64()) {
             return count as u32;
@@ -242,7 +611,7 @@ impl ParallelProcessor {
         // Check nested structures
         for key in &["data", "result", "detection"] {
             if let Some(nested) = data.get(key) {
-                let nested_count = self.extract_detection_count(nested);
+                let nested_count = Self::extract_detection_count(nested);
                 if nested_count > 0 {
                     return nested_count;
                 }
@@ -252,7 +621,7 @@ impl ParallelProcessor {
         0
     }
 
-    fn extract_tool_name(&self, data: &serde_json::Value) -> Option<String> {
+    fn extract_tool_name(data: &serde_json::Value) -> Option<String> {
         // Try common tool name fields
         for key in &["tool_name", "detector", "model", "algorithm"] {
             if let Some(name) = data.get(key).and_then(|v| v.as_str()) {
@@ -263,7 +632,7 @@ impl ParallelProcessor {
         // Check nested structures
         for key in &["data", "result", "metadata"] {
             if let Some(nested) = data.get(key) {
-                if let Some(name) = self.extract_tool_name(nested) {
+                if let Some(name) = Self::extract_tool_name(nested) {
                     return Some(name);
                 }
             }
@@ -272,7 +641,7 @@ impl ParallelProcessor {
         None
     }
 
-    fn extract_operation_type(&self, data: &serde_json::Value) -> Option<OperationType> {
+    fn extract_operation_type(data: &serde_json::Value) -> Option<OperationType> {
         // Check sidecar_info structure
         if let Some(sidecar_info) = data.get("sidecar_info") {
             if let Some(operation_str) = sidecar_info.get("operation_type").and_then(|v| v.as_str()) {
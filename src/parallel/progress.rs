@@ -0,0 +1,75 @@
+/*
+ * Context: Progress reporting for long-running sidecar scans
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: std
+ */
+
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Callback invoked each time a file has been processed: `(done, total, current_path)`
+pub type ProgressCallback = Arc<dyn Fn(usize, usize, &Path) + Send + Sync>;
+
+/// Thread-safe progress counter that forwards updates to an optional callback
+pub struct ProgressTracker {
+    callback: Option<ProgressCallback>,
+    total: usize,
+    done: AtomicUsize,
+}
+
+impl ProgressTracker {
+    /// Create a tracker for `total` work items
+    pub fn new(callback: Option<ProgressCallback>, total: usize) -> Self {
+        Self {
+            callback,
+            total,
+            done: AtomicUsize::new(0),
+        }
+    }
+
+    /// Record one finished item and notify the callback
+    pub fn advance(&self, current_path: &Path) {
+        let done = self.done.fetch_add(1, Ordering::Relaxed) + 1;
+        if let Some(callback) = &self.callback {
+            callback(done, self.total, current_path);
+        }
+    }
+
+    /// Number of items processed so far
+    pub fn done(&self) -> usize {
+        self.done.load(Ordering::Relaxed)
+    }
+
+    /// Total number of items expected
+    pub fn total(&self) -> usize {
+        self.total
+    }
+}
+
+/// Split one callback into `parts` callbacks whose progress is summed before forwarding
+/// Used when several directories are scanned concurrently but reported as one bar
+pub fn split_progress(callback: Option<ProgressCallback>, parts: usize) -> Vec<Option<ProgressCallback>> {
+    let Some(callback) = callback else {
+        return vec![None; parts];
+    };
+    let counts = Arc::new(std::sync::Mutex::new(vec![(0usize, 0usize); parts]));
+
+    (0..parts)
+        .map(|part| {
+            let callback = callback.clone();
+            let counts = counts.clone();
+            let split: ProgressCallback = Arc::new(move |done, total, current_path| {
+                let (done, total) = {
+                    let mut counts = counts.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                    counts[part] = (done, total);
+                    counts.iter().fold((0, 0), |(done, total), (d, t)| (done + d, total + t))
+                };
+                callback(done, total, current_path);
+            });
+            Some(split)
+        })
+        .collect()
+}
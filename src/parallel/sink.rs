@@ -0,0 +1,122 @@
+/*
+ * Context: Streaming output sinks for validation results
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde, serde_json
+ */
+
+use crate::sidecar::types::ValidationResult;
+use crate::sidecar::types::Result;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+/// Destination for results that are produced one at a time
+/// Implementations receive each item as soon as it is ready, so callers never need to
+/// hold the full result set in memory
+pub trait Sink<T>: Send {
+    /// Consume one item
+    fn write(&mut self, item: &T) -> Result<()>;
+
+    /// Called once after the last item has been written
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Collect items in memory, mainly useful for tests and small directories
+impl<T: Clone + Send> Sink<T> for Vec<T> {
+    fn write(&mut self, item: &T) -> Result<()> {
+        self.push(item.clone());
+        Ok(())
+    }
+}
+
+/// Write each item as one line of JSON (newline-delimited JSON)
+/// Lines are handed straight to the writer; wrap files in a `BufWriter`, the buffer is
+/// flushed by `finish`
+pub struct NdjsonSink<W: Write + Send> {
+    writer: W,
+}
+
+impl<W: Write + Send> NdjsonSink<W> {
+    /// Create a sink writing to `writer`
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Recover the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<T: Serialize, W: Write + Send> Sink<T> for NdjsonSink<W> {
+    fn write(&mut self, item: &T) -> Result<()> {
+        serde_json::to_writer(&mut self.writer, item)?;
+        self.writer.write_all(b"\n")?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Forward every item to two sinks, e.g. stream NDJSON while also collecting results
+pub struct Tee<A, B>(pub A, pub B);
+
+impl<T, A: Sink<T>, B: Sink<T>> Sink<T> for Tee<A, B> {
+    fn write(&mut self, item: &T) -> Result<()> {
+        self.0.write(item)?;
+        self.1.write(item)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.0.finish()?;
+        self.1.finish()
+    }
+}
+
+/// Running totals for a streamed validation
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ValidationSummary {
+    pub total_files: usize,
+    pub valid_files: usize,
+    pub invalid_files: usize,
+    /// Invalid files refused unread for being over the maximum sidecar size
+    #[serde(default)]
+    pub oversized_files: usize,
+}
+
+impl ValidationSummary {
+    /// Account for one validation result
+    pub fn record(&mut self, result: &ValidationResult) {
+        self.total_files += 1;
+        if result.is_valid {
+            self.valid_files += 1;
+        } else {
+            self.invalid_files += 1;
+        }
+        if result.oversized {
+            self.oversized_files += 1;
+        }
+    }
+
+    /// Add another run's totals to this one
+    pub fn merge(&mut self, other: &ValidationSummary) {
+        self.total_files += other.total_files;
+        self.valid_files += other.valid_files;
+        self.invalid_files += other.invalid_files;
+        self.oversized_files += other.oversized_files;
+    }
+
+    /// Share of invalid files, 0-100; an empty run counts as 0%
+    pub fn invalid_percent(&self) -> f64 {
+        if self.total_files == 0 {
+            return 0.0;
+        }
+        self.invalid_files as f64 * 100.0 / self.total_files as f64
+    }
+}
@@ -0,0 +1,117 @@
+/*
+ * Context: IO rate limits shared by parallel validation and format conversion
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: tokio
+ */
+
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tokio::time::Instant;
+
+/// Ceilings on how hard a scan or conversion may hit storage; `None` means unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct IoLimits {
+    /// Files opened per second
+    pub max_files_per_sec: Option<f64>,
+    /// Files open at the same time
+    pub max_open_files: Option<usize>,
+    /// Bytes read per second
+    pub max_read_bytes_per_sec: Option<u64>,
+}
+
+impl IoLimits {
+    pub fn is_unlimited(&self) -> bool {
+        self.max_files_per_sec.is_none() && self.max_open_files.is_none() && self.max_read_bytes_per_sec.is_none()
+    }
+}
+
+/// Hands out time slots at a fixed rate, without bursts
+#[derive(Debug)]
+struct Pace {
+    per_sec: f64,
+    next_slot: Mutex<Instant>,
+}
+
+impl Pace {
+    fn new(per_sec: f64) -> Self {
+        Self { per_sec, next_slot: Mutex::new(Instant::now()) }
+    }
+
+    /// Book `amount` units and wait until their slot starts
+    async fn take(&self, amount: f64) {
+        let start = {
+            let mut next_slot = self.next_slot.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            let start = (*next_slot).max(Instant::now());
+            *next_slot = start + Duration::from_secs_f64(amount / self.per_sec);
+            start
+        };
+        tokio::time::sleep_until(start).await;
+    }
+}
+
+/// Enforces `IoLimits` across every task sharing it
+///
+/// Share one throttle (via `Arc`) between a `ParallelProcessor` and a `SidecarManager` to
+/// cap their combined load. With no limits set, every call returns immediately.
+#[derive(Debug)]
+pub struct Throttle {
+    limits: IoLimits,
+    files: Option<Pace>,
+    open_files: Option<Semaphore>,
+    bytes: Option<Pace>,
+}
+
+/// Slot for one open file; dropping it lets the next file open
+#[derive(Debug)]
+pub struct OpenFile<'a> {
+    _permit: Option<SemaphorePermit<'a>>,
+}
+
+impl Throttle {
+    /// Non-positive rates and a zero file count are treated as unlimited
+    pub fn new(limits: IoLimits) -> Self {
+        Self {
+            limits,
+            files: limits.max_files_per_sec.filter(|rate| *rate > 0.0).map(Pace::new),
+            open_files: limits.max_open_files.filter(|count| *count > 0).map(Semaphore::new),
+            bytes: limits.max_read_bytes_per_sec.filter(|rate| *rate > 0).map(|rate| Pace::new(rate as f64)),
+        }
+    }
+
+    pub fn unlimited() -> Self {
+        Self::new(IoLimits::default())
+    }
+
+    pub fn limits(&self) -> IoLimits {
+        self.limits
+    }
+
+    /// Wait for the file rate and a free file handle before opening a file
+    pub async fn open_file(&self) -> OpenFile<'_> {
+        if let Some(files) = &self.files {
+            files.take(1.0).await;
+        }
+        let permit = match &self.open_files {
+            // The semaphore is never closed, so acquiring only fails if that changes
+            Some(open_files) => open_files.acquire().await.ok(),
+            None => None,
+        };
+        OpenFile { _permit: permit }
+    }
+
+    /// Account for `bytes` just read; waits while reads are ahead of the byte rate
+    pub async fn record_read(&self, bytes: u64) {
+        if let Some(pace) = &self.bytes {
+            pace.take(bytes as f64).await;
+        }
+    }
+}
+
+impl Default for Throttle {
+    fn default() -> Self {
+        Self::unlimited()
+    }
+}
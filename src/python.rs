@@ -1,4 +1,4 @@
-/**
+/*
  * This code written by Claude Sonnet 4 (claude-3-5-sonnet-20241022)
  * Generated via Cursor IDE (cursor.sh) with AI assistance
  * Model: Anthropic Claude 3.5 Sonnet
@@ -10,21 +10,65 @@
  * - IDE: Cursor (cursor.sh)
  * - Generation method: AI-assisted pair programming
  * - Code style: Rust idiomatic with PyO3 integration
- * - Dependencies: pyo3, tokio, serde, rayon, anyhow
+ * - Dependencies: pyo3, tokio, serde, rayon
  */
 
 use pyo3::prelude::*;
-use pyo3::types::PyDict;
-use pyo3::exceptions::PyRuntimeError;
-use std::path::Path;
-use std::collections::HashMap;
+use pyo3::types::{PyBool, PyByteArray, PyDict, PyFloat, PyList, PyLong, PyString, PyTuple};
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyRuntimeError, PyValueError};
+use std::path::{Path, PathBuf};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
 use serde_json::Value;
 use tokio::runtime::Runtime;
+use walkdir::WalkDir;
 
 use crate::{
-    ImageSidecar, SidecarFormat, OperationType, SidecarInfo,
-    ValidationResult, StatisticsResult
+    ImageSidecar, SidecarFormat, OperationType, SidecarInfo, SidecarError,
+    ValidationResult, StatisticsResult, ProgressCallback, QueryExpr, ExtractPath,
+    VectorIndex,
 };
+use crate::testing::SidecarTree;
+
+create_exception!(image_sidecar_rust, SidecarException, PyException, "Base class for sidecar errors");
+create_exception!(image_sidecar_rust, SidecarIOError, SidecarException, "Filesystem error while reading or writing sidecars");
+create_exception!(image_sidecar_rust, SidecarNotFoundError, SidecarException, "Sidecar file does not exist");
+create_exception!(image_sidecar_rust, ImageNotFoundError, SidecarException, "Image file does not exist");
+create_exception!(image_sidecar_rust, SymlinkError, SidecarException, "Image symlink could not be resolved");
+create_exception!(image_sidecar_rust, SidecarValidationError, SidecarException, "Sidecar or input failed validation");
+create_exception!(image_sidecar_rust, SidecarFormatError, SidecarException, "Sidecar could not be encoded or decoded");
+create_exception!(image_sidecar_rust, SidecarSchemaError, SidecarException, "Document does not have the expected structure");
+create_exception!(image_sidecar_rust, SidecarLockError, SidecarException, "Lock could not be acquired");
+create_exception!(image_sidecar_rust, SidecarStorageError, SidecarException, "Storage backend failure");
+create_exception!(image_sidecar_rust, SidecarCancelledError, SidecarException, "Operation was cancelled");
+create_exception!(image_sidecar_rust, SidecarProcessingError, SidecarException, "Processing failed");
+create_exception!(image_sidecar_rust, SidecarReadOnlyError, SidecarException, "Write refused in read-only mode");
+create_exception!(image_sidecar_rust, SidecarTooLargeError, SidecarException, "Sidecar is over the maximum sidecar size");
+
+/// Map a library error to the matching Python exception class
+fn sidecar_error(context: &str, error: SidecarError) -> PyErr {
+    let message = format!("{}: {}", context, error);
+    match error {
+        SidecarError::Io(_) => SidecarIOError::new_err(message),
+        SidecarError::SidecarNotFound(_) => SidecarNotFoundError::new_err(message),
+        SidecarError::ImageNotFound(_) => ImageNotFoundError::new_err(message),
+        SidecarError::SymlinkResolutionFailed(_) => SymlinkError::new_err(message),
+        SidecarError::ValidationFailed(_) | SidecarError::InvalidOperationType(_) => {
+            SidecarValidationError::new_err(message)
+        }
+        SidecarError::Json(_) | SidecarError::SerializationError(_) | SidecarError::Format(_) => {
+            SidecarFormatError::new_err(message)
+        }
+        SidecarError::Schema(_) => SidecarSchemaError::new_err(message),
+        SidecarError::Lock(_) => SidecarLockError::new_err(message),
+        SidecarError::Storage(_) => SidecarStorageError::new_err(message),
+        SidecarError::Cancelled => SidecarCancelledError::new_err(message),
+        SidecarError::ProcessingError(_) => SidecarProcessingError::new_err(message),
+        SidecarError::ReadOnly(_) => SidecarReadOnlyError::new_err(message),
+        SidecarError::TooLarge { .. } => SidecarTooLargeError::new_err(message),
+    }
+}
 
 /// Python wrapper for ImageSidecar
 #[pyclass]
@@ -45,136 +89,271 @@ impl PyImageSidecar {
         
         Ok(Self { inner, runtime })
     }
-    
+
+    /// Create an instance that keeps sidecars in memory, for unit tests that must not touch the disk
+    /// `tree` maps image paths to `{operation: payload}` dicts; every image in it exists,
+    /// with a sidecar holding its payloads in `format`
+    #[staticmethod]
+    #[pyo3(signature = (tree=None, format="json", max_workers=None))]
+    pub fn in_memory(tree: Option<&PyDict>, format: &str, max_workers: Option<usize>) -> PyResult<Self> {
+        let runtime = Runtime::new()
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to create runtime: {}", e)))?;
+        let format = SidecarFormat::from_name(format)
+            .ok_or_else(|| PyValueError::new_err(format!("Unsupported format: {}", format)))?;
+
+        let mut fixture = SidecarTree::new("").format(format);
+        for (image, payloads) in tree.into_iter().flat_map(|tree| tree.iter()) {
+            let image: String = image.extract()?;
+            fixture = fixture.image(&image);
+            for (operation, payload) in payloads.downcast::<PyDict>()?.iter() {
+                let operation: String = operation.extract()?;
+                let operation = match OperationType::from_str(&operation) {
+                    OperationType::Unknown => return Err(PyValueError::new_err(format!("Unknown operation type: {}", operation))),
+                    operation => operation,
+                };
+                fixture = fixture.sidecar(&image, operation, py_to_value(payload)?);
+            }
+        }
+        let storage = fixture.in_memory().map_err(|e| sidecar_error("In-memory tree failed", e))?;
+
+        let mut builder = ImageSidecar::builder().storage(Arc::new(storage));
+        if let Some(workers) = max_workers {
+            builder = builder.max_workers(workers);
+        }
+        Ok(Self { inner: builder.build(), runtime })
+    }
+
     /// Validate JSON sidecar files in parallel
-    pub fn validate_sidecars(&self, directory: &str) -> PyResult<Vec<PyValidationResult>> {
-        let path = Path::new(directory);
-        let results = self.runtime.block_on(async {
-            self.inner.validate_sidecars(path).await
-        }).map_err(|e| PyRuntimeError::new_err(format!("Validation failed: {}", e)))?;
+    /// `progress` is an optional callable invoked as `progress(done, total, current_path)`
+    #[pyo3(signature = (directory, progress=None))]
+    pub fn validate_sidecars(
+        &self,
+        py: Python<'_>,
+        directory: PathBuf,
+        progress: Option<PyObject>,
+    ) -> PyResult<Vec<PyValidationResult>> {
+        let path = directory.as_path();
+        let progress = progress_callback(progress);
+        let results = py.allow_threads(|| self.runtime.block_on(async {
+            self.inner.validate_sidecars_with_progress(path, progress).await
+        })).map_err(|e| sidecar_error("Validation failed", e))?;
         
         Ok(results.into_iter().map(PyValidationResult::from).collect())
     }
     
     /// Get comprehensive statistics about sidecar files
-    pub fn get_statistics(&self, directory: &str) -> PyResult<PyStatisticsResult> {
-        let path = Path::new(directory);
-        let stats = self.runtime.block_on(async {
-            self.inner.get_statistics(path).await
-        }).map_err(|e| PyRuntimeError::new_err(format!("Statistics collection failed: {}", e)))?;
+    /// `progress` is an optional callable invoked as `progress(done, total, current_path)`
+    #[pyo3(signature = (directory, progress=None))]
+    pub fn get_statistics(
+        &self,
+        py: Python<'_>,
+        directory: PathBuf,
+        progress: Option<PyObject>,
+    ) -> PyResult<PyStatisticsResult> {
+        let path = directory.as_path();
+        let progress = progress_callback(progress);
+        let stats = py.allow_threads(|| self.runtime.block_on(async {
+            self.inner.get_statistics_with_progress(path, progress).await
+        })).map_err(|e| sidecar_error("Statistics collection failed", e))?;
         
         Ok(PyStatisticsResult::from(stats))
     }
     
+    /// Validate sidecar files and return the results as a `pyarrow.Table`, one row per file
+    /// Convert with `.to_pandas()` or `polars.from_arrow()`; no per-row Python objects are created
+    #[cfg(feature = "python-arrow")]
+    pub fn validate_sidecars_df(&self, py: Python<'_>, directory: PathBuf) -> PyResult<PyObject> {
+        let path = directory.as_path();
+        let batch = py.allow_threads(|| {
+            let results = self.runtime.block_on(self.inner.validate_sidecars(path))?;
+            crate::arrow::validation_batch(&results)
+        }).map_err(|e| sidecar_error("Validation failed", e))?;
+        
+        arrow_table(py, batch)
+    }
+    
+    /// Scan a directory and return its sidecars as a `pyarrow.Table`, one row per sidecar
+    #[cfg(feature = "python-arrow")]
+    pub fn stats_df(&self, py: Python<'_>, directory: PathBuf) -> PyResult<PyObject> {
+        let path = directory.as_path();
+        let batch = py.allow_threads(|| {
+            let stats = self.runtime.block_on(self.inner.get_statistics(path))?;
+            crate::arrow::sidecar_batch(&stats.sidecars)
+        }).map_err(|e| sidecar_error("Statistics collection failed", e))?;
+        
+        arrow_table(py, batch)
+    }
+    
     /// Find all sidecar files in a directory
-    pub fn find_sidecars(&self, directory: &str) -> PyResult<Vec<PySidecarInfo>> {
-        let path = Path::new(directory);
+    pub fn find_sidecars(&self, directory: PathBuf) -> PyResult<Vec<PySidecarInfo>> {
+        let path = directory.as_path();
         let sidecars = self.runtime.block_on(async {
             self.inner.find_sidecars(path).await
-        }).map_err(|e| PyRuntimeError::new_err(format!("Sidecar search failed: {}", e)))?;
+        }).map_err(|e| sidecar_error("Sidecar search failed", e))?;
         
         Ok(sidecars.into_iter().map(PySidecarInfo::from).collect())
     }
     
+    /// Lazily iterate over sidecar files in a directory
+    /// Results are yielded as the directory tree is walked instead of being collected up front
+    pub fn iter_sidecars(slf: Py<Self>, directory: PathBuf) -> PySidecarIterator {
+        PySidecarIterator::new(slf, &directory)
+    }
+    
+    /// Find sidecars matching a filter expression
+    /// Returns a list of records, each with the sidecar fields plus its decoded `data`
+    pub fn query(&self, py: Python<'_>, directory: PathBuf, expression: &str) -> PyResult<PyObject> {
+        let path = directory.as_path();
+        let expr = QueryExpr::parse(expression)
+            .map_err(|e| PyValueError::new_err(format!("Invalid query: {}", e)))?;
+        
+        let matches = py.allow_threads(|| self.runtime.block_on(async {
+            self.inner.query(path, &expr).await
+        })).map_err(|e| sidecar_error("Query failed", e))?;
+        
+        // Convert the records to Python objects through JSON
+        let json_str = serde_json::to_string(&matches)
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to serialize records: {}", e)))?;
+        let json_module = py.import("json")?;
+        Ok(json_module.call_method1("loads", (json_str,))?.to_object(py))
+    }
+    
+    /// Extract values matching a JSONPath from every sidecar in a directory
+    /// Returns a list of `{image_path, sidecar_path, value}` records, one per matched node
+    pub fn get(&self, py: Python<'_>, directory: PathBuf, path: &str) -> PyResult<PyObject> {
+        let directory = directory.as_path();
+        let path = ExtractPath::parse(path)
+            .map_err(|e| PyValueError::new_err(format!("Invalid path: {}", e)))?;
+        
+        let values = py.allow_threads(|| self.runtime.block_on(async {
+            self.inner.extract(directory, &path).await
+        })).map_err(|e| sidecar_error("Extraction failed", e))?;
+        
+        let json_str = serde_json::to_string(&values)
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to serialize values: {}", e)))?;
+        let json_module = py.import("json")?;
+        Ok(json_module.call_method1("loads", (json_str,))?.to_object(py))
+    }
+    
+    /// Build a face-encoding index over a directory and save it to `index_path`
+    /// Returns the number of encodings indexed
+    pub fn build_vector_index(&self, py: Python<'_>, directory: PathBuf, index_path: PathBuf) -> PyResult<usize> {
+        let directory = directory.as_path();
+        
+        let index = py.allow_threads(|| self.runtime.block_on(async {
+            self.inner.build_vector_index(directory).await
+        })).map_err(|e| sidecar_error("Index build failed", e))?;
+        
+        index.save(&index_path)
+            .map_err(|e| sidecar_error("Failed to save index", e))?;
+        Ok(index.len())
+    }
+    
+    /// Find the `k` faces most similar to those in `image_path` using a saved index
+    /// Returns a list of `{image_path, face_index, distance}` records, closest first
+    #[pyo3(signature = (index_path, image_path, k=10))]
+    pub fn find_similar(&self, py: Python<'_>, index_path: PathBuf, image_path: PathBuf, k: usize) -> PyResult<PyObject> {
+        let index = VectorIndex::load(&index_path)
+            .map_err(|e| sidecar_error("Failed to load index", e))?;
+        let neighbors = py.allow_threads(|| index.find_similar(&image_path, k))
+            .map_err(|e| PyValueError::new_err(format!("Similarity search failed: {}", e)))?;
+        
+        let json_str = serde_json::to_string(&neighbors)
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to serialize neighbors: {}", e)))?;
+        let json_module = py.import("json")?;
+        Ok(json_module.call_method1("loads", (json_str,))?.to_object(py))
+    }
+    
     /// Create a new sidecar file
     pub fn create_sidecar(
         &self,
-        image_path: &str,
+        image_path: PathBuf,
         operation: PyOperationType,
         data: &PyDict,
     ) -> PyResult<PySidecarInfo> {
-        let path = Path::new(image_path);
-        
-        // Convert PyDict to JSON string manually
-        let json_str = Python::with_gil(|py| {
-            let json_module = py.import("json")?;
-            let json_str = json_module.call_method1("dumps", (data,))?;
-            json_str.extract::<String>()
-        }).map_err(|e| PyRuntimeError::new_err(format!("Failed to convert data to JSON: {}", e)))?;
+        let path = image_path.as_path();
         
-        let json_value: Value = serde_json::from_str(&json_str)
-            .map_err(|e| PyRuntimeError::new_err(format!("Invalid JSON: {}", e)))?;
+        // NumPy arrays are read straight from their buffer instead of going through lists
+        let json_value = py_to_value(data)?;
         
         let sidecar_info = self.runtime.block_on(async {
             self.inner.create_sidecar(path, operation.into(), json_value).await
-        }).map_err(|e| PyRuntimeError::new_err(format!("Sidecar creation failed: {}", e)))?;
+        }).map_err(|e| sidecar_error("Sidecar creation failed", e))?;
         
         Ok(PySidecarInfo::from(sidecar_info))
     }
 
     /// Save data to a sidecar file, merging with existing data if present
     /// This is the primary method expected by sportball Python code
+    /// Values may include NumPy arrays and scalars, e.g. face encodings
     pub fn save_data(
         &self,
-        image_path: &str,
+        image_path: PathBuf,
         operation: PyOperationType,
         data: &PyDict,
     ) -> PyResult<PySidecarInfo> {
-        let path = Path::new(image_path);
+        let path = image_path.as_path();
         
-        // Convert PyDict to JSON string manually
-        let json_str = Python::with_gil(|py| {
-            let json_module = py.import("json")?;
-            let json_str = json_module.call_method1("dumps", (data,))?;
-            json_str.extract::<String>()
-        }).map_err(|e| PyRuntimeError::new_err(format!("Failed to convert data to JSON: {}", e)))?;
-        
-        let json_value: Value = serde_json::from_str(&json_str)
-            .map_err(|e| PyRuntimeError::new_err(format!("Invalid JSON: {}", e)))?;
+        // NumPy arrays are read straight from their buffer instead of going through lists
+        let json_value = py_to_value(data)?;
         
         let sidecar_info = self.runtime.block_on(async {
             self.inner.save_data(path, operation.into(), json_value).await
-        }).map_err(|e| PyRuntimeError::new_err(format!("Sidecar save failed: {}", e)))?;
+        }).map_err(|e| sidecar_error("Sidecar save failed", e))?;
         
         Ok(PySidecarInfo::from(sidecar_info))
     }
 
     /// Read sidecar data for an image path
     /// Returns empty dict if no sidecar exists (does NOT raise error)
-    pub fn read_data(&self, image_path: &str) -> PyResult<PyObject> {
-        let path = Path::new(image_path);
+    /// With `as_numpy`, numeric lists (and equal-length lists of them) become `numpy.ndarray`s
+    #[pyo3(signature = (image_path, as_numpy=false))]
+    pub fn read_data(&self, py: Python<'_>, image_path: PathBuf, as_numpy: bool) -> PyResult<PyObject> {
+        let path = image_path.as_path();
         
         let data = self.runtime.block_on(async {
             self.inner.read_data(path).await
-        }).map_err(|e| PyRuntimeError::new_err(format!("Sidecar read failed: {}", e)))?;
-        
-        // Convert serde_json::Value to PyObject
-        let json_str = serde_json::to_string(&data)
-            .map_err(|e| PyRuntimeError::new_err(format!("Failed to serialize data: {}", e)))?;
+        }).map_err(|e| sidecar_error("Sidecar read failed", e))?;
         
-        Python::with_gil(|py| {
-            let json_module = py.import("json")?;
-            let py_dict = json_module.call_method1("loads", (json_str,))?;
-            Ok(py_dict.to_object(py))
-        })
+        let numpy = if as_numpy { Some(py.import("numpy")?) } else { None };
+        value_to_py(py, &data, numpy)
     }
     
     /// Clean up orphaned sidecar files
-    pub fn cleanup_orphaned(&self, directory: &str) -> PyResult<usize> {
-        let path = Path::new(directory);
+    pub fn cleanup_orphaned(&self, directory: PathBuf) -> PyResult<usize> {
+        let path = directory.as_path();
         let count = self.runtime.block_on(async {
             self.inner.cleanup_orphaned(path).await
-        }).map_err(|e| PyRuntimeError::new_err(format!("Cleanup failed: {}", e)))?;
+        }).map_err(|e| sidecar_error("Cleanup failed", e))?;
         
         Ok(count)
     }
     
     /// Convert sidecar files between formats
-    pub fn convert_directory_format(&self, directory: &str, target_format: PySidecarFormat) -> PyResult<u32> {
-        let path = Path::new(directory);
-        let count = self.runtime.block_on(async {
-            self.inner.convert_directory_format(path, target_format.into()).await
-        }).map_err(|e| PyRuntimeError::new_err(format!("Format conversion failed: {}", e)))?;
+    /// `progress` is an optional callable invoked as `progress(done, total, current_path)`
+    #[pyo3(signature = (directory, target_format, progress=None))]
+    pub fn convert_directory_format(
+        &self,
+        py: Python<'_>,
+        directory: PathBuf,
+        target_format: PySidecarFormat,
+        progress: Option<PyObject>,
+    ) -> PyResult<u32> {
+        let path = directory.as_path();
+        let progress = progress_callback(progress);
+        let count = py.allow_threads(|| self.runtime.block_on(async {
+            self.inner.convert_directory_format_with_progress(path, target_format.into(), progress).await
+        })).map_err(|e| sidecar_error("Format conversion failed", e))?;
         
         Ok(count)
     }
     
     /// Get format statistics for a directory
-    pub fn get_format_statistics(&self, directory: &str) -> PyResult<HashMap<String, u32>> {
-        let path = Path::new(directory);
+    pub fn get_format_statistics(&self, directory: PathBuf) -> PyResult<HashMap<String, u32>> {
+        let path = directory.as_path();
         let stats = self.runtime.block_on(async {
             self.inner.get_format_statistics(path).await
-        }).map_err(|e| PyRuntimeError::new_err(format!("Format statistics failed: {}", e)))?;
+        }).map_err(|e| sidecar_error("Format statistics failed", e))?;
         
         Ok(stats.into_iter().map(|(k, v)| (k.extension().to_string(), v)).collect())
     }
@@ -190,6 +369,288 @@ impl PyImageSidecar {
     }
 }
 
+/// Hand a record batch to pyarrow through the Arrow C data interface and wrap it in a `pyarrow.Table`
+#[cfg(feature = "python-arrow")]
+fn arrow_table(py: Python<'_>, batch: crate::arrow::RecordBatch) -> PyResult<PyObject> {
+    use arrow_array::{Array, StructArray};
+    use arrow_data::ffi::FFI_ArrowArray;
+    use arrow_schema::ffi::FFI_ArrowSchema;
+    
+    let data = StructArray::from(batch).to_data();
+    let mut array = FFI_ArrowArray::new(&data);
+    let mut schema = FFI_ArrowSchema::try_from(data.data_type())
+        .map_err(|e| PyRuntimeError::new_err(format!("Arrow export failed: {}", e)))?;
+    
+    // pyarrow takes ownership by moving both structs out, so dropping them afterwards is a no-op
+    let pyarrow = py.import("pyarrow")?;
+    let batch = pyarrow.getattr("RecordBatch")?.call_method1(
+        "_import_from_c",
+        (std::ptr::addr_of_mut!(array) as usize, std::ptr::addr_of_mut!(schema) as usize),
+    )?;
+    let table = pyarrow.getattr("Table")?.call_method1("from_batches", (vec![batch],))?;
+    Ok(table.to_object(py))
+}
+
+/// Convert a Python object to JSON
+/// NumPy arrays and scalars (anything with a `dtype`) are read from their raw buffer in one copy
+fn py_to_value(obj: &PyAny) -> PyResult<Value> {
+    if obj.is_none() {
+        return Ok(Value::Null);
+    }
+    if let Ok(b) = obj.downcast::<PyBool>() {
+        return Ok(Value::Bool(b.is_true()));
+    }
+    if let Ok(text) = obj.downcast::<PyString>() {
+        return Ok(Value::String(text.to_str()?.to_string()));
+    }
+    if obj.hasattr("dtype")? && obj.hasattr("shape")? {
+        return numpy_to_value(obj);
+    }
+    if obj.downcast::<PyLong>().is_ok() {
+        if let Ok(n) = obj.extract::<i64>() {
+            return Ok(Value::from(n));
+        }
+        if let Ok(n) = obj.extract::<u64>() {
+            return Ok(Value::from(n));
+        }
+        return float_value(obj.extract::<f64>()?);
+    }
+    if let Ok(f) = obj.downcast::<PyFloat>() {
+        return float_value(f.value());
+    }
+    if let Ok(dict) = obj.downcast::<PyDict>() {
+        let mut map = serde_json::Map::with_capacity(dict.len());
+        for (key, value) in dict {
+            let key = match key.downcast::<PyString>() {
+                Ok(key) => key.to_str()?.to_string(),
+                Err(_) => key.str()?.to_str()?.to_string(),
+            };
+            map.insert(key, py_to_value(value)?);
+        }
+        return Ok(Value::Object(map));
+    }
+    if obj.downcast::<PyList>().is_ok() || obj.downcast::<PyTuple>().is_ok() {
+        return obj.iter()?.map(|item| py_to_value(item?)).collect::<PyResult<Vec<_>>>().map(Value::Array);
+    }
+    
+    Err(PyValueError::new_err(format!(
+        "Cannot store value of type {} in a sidecar",
+        obj.get_type().name()?
+    )))
+}
+
+fn float_value(f: f64) -> PyResult<Value> {
+    serde_json::Number::from_f64(f)
+        .map(Value::Number)
+        .ok_or_else(|| PyValueError::new_err(format!("Cannot store non-finite float {} in a sidecar", f)))
+}
+
+/// Read a NumPy array or scalar as nested JSON arrays, following its shape
+fn numpy_to_value(array: &PyAny) -> PyResult<Value> {
+    let kind: String = array.getattr("dtype")?.getattr("kind")?.extract()?;
+    let shape: Vec<usize> = array.getattr("shape")?.extract()?;
+    
+    // One contiguous little-endian copy of the data, whatever the source layout
+    let (target, width) = match kind.as_str() {
+        "b" => ("|b1", 1),
+        "i" | "u" => ("<i8", 8),
+        "f" => ("<f8", 8),
+        other => return Err(PyValueError::new_err(format!("Unsupported NumPy dtype kind '{}'", other))),
+    };
+    let bytes: Vec<u8> = array.call_method1("astype", (target,))?.call_method0("tobytes")?.extract()?;
+    
+    let mut values = Vec::with_capacity(bytes.len() / width);
+    for chunk in bytes.chunks_exact(width) {
+        values.push(match kind.as_str() {
+            "b" => Value::Bool(chunk[0] != 0),
+            "i" | "u" => Value::from(i64::from_le_bytes(chunk.try_into().expect("8-byte chunk"))),
+            _ => float_value(f64::from_le_bytes(chunk.try_into().expect("8-byte chunk")))?,
+        });
+    }
+    
+    Ok(nest(&mut values.into_iter(), &shape))
+}
+
+/// Rebuild nested arrays from row-major `values`
+fn nest(values: &mut impl Iterator<Item = Value>, shape: &[usize]) -> Value {
+    match shape.split_first() {
+        None => values.next().unwrap_or(Value::Null),
+        Some((&len, rest)) => Value::Array((0..len).map(|_| nest(values, rest)).collect()),
+    }
+}
+
+/// Convert JSON to Python objects
+/// With `numpy`, numeric arrays (1-D, or 2-D with equal-length rows) become `ndarray`s
+fn value_to_py(py: Python<'_>, value: &Value, numpy: Option<&PyModule>) -> PyResult<PyObject> {
+    Ok(match value {
+        Value::Null => py.None(),
+        Value::Bool(b) => b.to_object(py),
+        Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i), _) => i.to_object(py),
+            (None, Some(u)) => u.to_object(py),
+            _ => n.as_f64().unwrap_or(f64::NAN).to_object(py),
+        },
+        Value::String(text) => text.to_object(py),
+        Value::Array(items) => {
+            if let Some(numpy) = numpy {
+                if let Some(array) = numeric_ndarray(py, numpy, items)? {
+                    return Ok(array);
+                }
+            }
+            let items = items.iter().map(|item| value_to_py(py, item, numpy)).collect::<PyResult<Vec<_>>>()?;
+            PyList::new(py, items).to_object(py)
+        }
+        Value::Object(map) => {
+            let dict = PyDict::new(py);
+            for (key, item) in map {
+                dict.set_item(key, value_to_py(py, item, numpy)?)?;
+            }
+            dict.to_object(py)
+        }
+    })
+}
+
+/// Build a float64 (or int64 when every value is an integer) ndarray, or `None` if `items` is not numeric
+fn numeric_ndarray(py: Python<'_>, numpy: &PyModule, items: &[Value]) -> PyResult<Option<PyObject>> {
+    let (flat, shape): (Vec<&serde_json::Number>, Vec<usize>) = match items.first() {
+        None => return Ok(None),
+        Some(Value::Number(_)) => {
+            let Some(flat) = items.iter().map(Value::as_number).collect::<Option<Vec<_>>>() else {
+                return Ok(None);
+            };
+            (flat, vec![items.len()])
+        }
+        Some(Value::Array(first)) if !first.is_empty() => {
+            let mut flat = Vec::with_capacity(items.len() * first.len());
+            for row in items {
+                match row.as_array() {
+                    Some(row) if row.len() == first.len() => {
+                        for item in row {
+                            let Some(n) = item.as_number() else { return Ok(None) };
+                            flat.push(n);
+                        }
+                    }
+                    _ => return Ok(None),
+                }
+            }
+            (flat, vec![items.len(), first.len()])
+        }
+        _ => return Ok(None),
+    };
+    
+    // One copy into a bytearray; frombuffer then views it without copying again
+    let (bytes, dtype): (Vec<u8>, &str) = if flat.iter().all(|n| n.is_i64()) {
+        (flat.iter().flat_map(|n| n.as_i64().unwrap_or_default().to_le_bytes()).collect(), "<i8")
+    } else {
+        (flat.iter().flat_map(|n| n.as_f64().unwrap_or(f64::NAN).to_le_bytes()).collect(), "<f8")
+    };
+    let buffer = PyByteArray::new(py, &bytes);
+    let array = numpy.call_method1("frombuffer", (buffer, dtype))?.call_method1("reshape", (shape,))?;
+    Ok(Some(array.to_object(py)))
+}
+
+/// Wrap an optional Python callable as a Rust progress callback
+/// Exceptions raised by the callable are printed and otherwise ignored so they cannot abort a scan
+fn progress_callback(callback: Option<PyObject>) -> Option<ProgressCallback> {
+    callback.map(|callback| -> ProgressCallback {
+        Arc::new(move |done, total, current_path| {
+            Python::with_gil(|py| {
+                let current_path = current_path.to_path_buf();
+                if let Err(e) = callback.call1(py, (done, total, current_path)) {
+                    e.print(py);
+                }
+            });
+        })
+    })
+}
+
+/// Which walk of the directory tree the iterator is currently in
+enum IteratorPhase {
+    /// Walking image files and looking up the sidecar next to each one
+    Images,
+    /// Walking sidecar files to pick up pattern-named sidecars
+    PatternSidecars,
+    Finished,
+}
+
+/// Python iterator streaming sidecar discovery results
+/// Mirrors `find_sidecars` but yields each `PySidecarInfo` as soon as it is found
+#[pyclass]
+pub struct PySidecarIterator {
+    owner: Py<PyImageSidecar>,
+    directory: PathBuf,
+    walker: walkdir::IntoIter,
+    phase: IteratorPhase,
+    processed_sidecars: HashSet<PathBuf>,
+}
+
+impl PySidecarIterator {
+    fn new(owner: Py<PyImageSidecar>, directory: &Path) -> Self {
+        Self {
+            owner,
+            directory: directory.to_path_buf(),
+            walker: WalkDir::new(directory).into_iter(),
+            phase: IteratorPhase::Images,
+            processed_sidecars: HashSet::new(),
+        }
+    }
+}
+
+#[pymethods]
+impl PySidecarIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+    
+    fn __next__(mut slf: PyRefMut<'_, Self>, py: Python<'_>) -> PyResult<Option<PySidecarInfo>> {
+        let owner = slf.owner.clone_ref(py);
+        let owner = owner.borrow(py);
+        let manager = owner.inner.manager();
+        
+        loop {
+            let entry = match slf.walker.next() {
+                Some(Ok(entry)) => entry,
+                Some(Err(_)) => continue,
+                None => {
+                    // Move on to the next walk once the current one is exhausted
+                    match slf.phase {
+                        IteratorPhase::Images => {
+                            slf.phase = IteratorPhase::PatternSidecars;
+                            slf.walker = WalkDir::new(&slf.directory).into_iter();
+                            continue;
+                        }
+                        _ => {
+                            slf.phase = IteratorPhase::Finished;
+                            return Ok(None);
+                        }
+                    }
+                }
+            };
+            
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            
+            let path = entry.path();
+            let found = match slf.phase {
+                IteratorPhase::Images if manager.is_image_file(path) => {
+                    owner.runtime.block_on(manager.find_sidecar_for_image(path))
+                }
+                IteratorPhase::PatternSidecars if manager.is_sidecar_file(path) => {
+                    owner.runtime.block_on(manager.find_image_for_pattern_sidecar(path, &slf.directory))
+                }
+                _ => continue,
+            }.map_err(|e| sidecar_error("Sidecar search failed", e))?;
+            
+            if let Some(sidecar_info) = found {
+                if slf.processed_sidecars.insert(sidecar_info.sidecar_path.clone()) {
+                    return Ok(Some(PySidecarInfo::from(sidecar_info)));
+                }
+            }
+        }
+    }
+}
+
 /// Python wrapper for SidecarFormat
 #[pyclass]
 #[derive(Clone, Copy)]
@@ -261,6 +722,8 @@ impl PyOperationType {
             "quality_assessment" => OperationType::QualityAssessment,
             "game_detection" => OperationType::GameDetection,
             "yolov8" => OperationType::Yolov8,
+            "image_hash" => OperationType::ImageHash,
+            "tracking" => OperationType::Tracking,
             "unified" => OperationType::Unified,
             _ => return Err(PyRuntimeError::new_err(format!("Unknown operation: {}", op_str))),
         };
@@ -280,9 +743,9 @@ impl PyOperationType {
 #[pyclass]
 pub struct PySidecarInfo {
     #[pyo3(get)]
-    pub image_path: String,
+    pub image_path: PathBuf,
     #[pyo3(get)]
-    pub sidecar_path: String,
+    pub sidecar_path: PathBuf,
     #[pyo3(get)]
     pub operation: PyOperationType,
     #[pyo3(get)]
@@ -291,17 +754,40 @@ pub struct PySidecarInfo {
     pub created_at: String,
     #[pyo3(get)]
     pub is_valid: bool,
+    #[pyo3(get)]
+    pub processing_time: Option<f64>,
+    #[pyo3(get)]
+    pub success: Option<bool>,
+}
+
+#[pymethods]
+impl PySidecarInfo {
+    /// All fields as a plain dict, one row of a `pandas.DataFrame`
+    fn to_dict(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let dict = PyDict::new(py);
+        dict.set_item("image_path", &self.image_path)?;
+        dict.set_item("sidecar_path", &self.sidecar_path)?;
+        dict.set_item("operation", self.operation.inner.as_str())?;
+        dict.set_item("data_size", self.data_size)?;
+        dict.set_item("created_at", &self.created_at)?;
+        dict.set_item("is_valid", self.is_valid)?;
+        dict.set_item("processing_time", self.processing_time)?;
+        dict.set_item("success", self.success)?;
+        Ok(dict.to_object(py))
+    }
 }
 
 impl From<SidecarInfo> for PySidecarInfo {
     fn from(info: SidecarInfo) -> Self {
         Self {
-            image_path: info.image_path.to_string_lossy().to_string(),
-            sidecar_path: info.sidecar_path.to_string_lossy().to_string(),
+            image_path: info.image_path,
+            sidecar_path: info.sidecar_path,
             operation: PyOperationType::from(info.operation),
             data_size: info.data_size,
             created_at: info.created_at.to_rfc3339(),
             is_valid: info.is_valid,
+            processing_time: info.processing_time,
+            success: info.success,
         }
     }
 }
@@ -310,7 +796,7 @@ impl From<SidecarInfo> for PySidecarInfo {
 #[pyclass]
 pub struct PyValidationResult {
     #[pyo3(get)]
-    pub file_path: String,
+    pub file_path: PathBuf,
     #[pyo3(get)]
     pub is_valid: bool,
     #[pyo3(get)]
@@ -319,16 +805,25 @@ pub struct PyValidationResult {
     pub processing_time: f64,
     #[pyo3(get)]
     pub file_size: u64,
+    #[pyo3(get)]
+    pub format: Option<String>,
+    #[pyo3(get)]
+    pub deserialize_time: f64,
+    #[pyo3(get)]
+    pub oversized: bool,
 }
 
 impl From<ValidationResult> for PyValidationResult {
     fn from(result: ValidationResult) -> Self {
         Self {
-            file_path: result.file_path.to_string_lossy().to_string(),
+            file_path: result.file_path,
             is_valid: result.is_valid,
             error: result.error,
             processing_time: result.processing_time,
             file_size: result.file_size,
+            format: result.format.map(|format| format.extension().to_string()),
+            deserialize_time: result.deserialize_time,
+            oversized: result.oversized,
         }
     }
 }
@@ -336,9 +831,15 @@ impl From<ValidationResult> for PyValidationResult {
 /// Python wrapper for StatisticsResult
 #[pyclass]
 pub struct PyStatisticsResult {
+    #[pyo3(get)]
+    pub directory: PathBuf,
     #[pyo3(get)]
     pub total_images: u32,
     #[pyo3(get)]
+    pub symlink_count: u32,
+    #[pyo3(get)]
+    pub broken_symlinks: u32,
+    #[pyo3(get)]
     pub total_sidecars: u32,
     #[pyo3(get)]
     pub coverage_percentage: f64,
@@ -350,32 +851,96 @@ pub struct PyStatisticsResult {
     pub success_rate_percentages: HashMap<String, f64>,
     #[pyo3(get)]
     pub avg_data_sizes: HashMap<String, f64>,
+    #[pyo3(get)]
+    pub filter_applied: Option<String>,
+    /// Detections per class across object detection and YOLOv8 payloads
+    #[pyo3(get)]
+    pub class_counts: BTreeMap<String, u32>,
+    /// Kept as Rust values; converted only when `sidecars` or `to_dict` is called
+    sidecar_infos: Vec<SidecarInfo>,
 }
 
 impl From<StatisticsResult> for PyStatisticsResult {
     fn from(stats: StatisticsResult) -> Self {
         Self {
+            directory: stats.directory,
             total_images: stats.total_images,
+            symlink_count: stats.symlink_count,
+            broken_symlinks: stats.broken_symlinks,
             total_sidecars: stats.total_sidecars,
             coverage_percentage: stats.coverage_percentage,
             operation_counts: stats.operation_counts,
             avg_processing_times: stats.avg_processing_times,
             success_rate_percentages: stats.success_rate_percentages,
             avg_data_sizes: stats.avg_data_sizes,
+            filter_applied: stats.filter_applied,
+            class_counts: stats.class_distribution.counts,
+            sidecar_infos: stats.sidecars,
         }
     }
 }
 
+#[pymethods]
+impl PyStatisticsResult {
+    /// Every sidecar found during the scan
+    #[getter]
+    fn sidecars(&self) -> Vec<PySidecarInfo> {
+        self.sidecar_infos.iter().cloned().map(PySidecarInfo::from).collect()
+    }
+    
+    /// All fields as a plain dict; `sidecars` becomes a list of dicts, ready for `pandas.DataFrame`
+    #[pyo3(signature = (include_sidecars=true))]
+    fn to_dict(&self, py: Python<'_>, include_sidecars: bool) -> PyResult<PyObject> {
+        let dict = PyDict::new(py);
+        dict.set_item("directory", &self.directory)?;
+        dict.set_item("total_images", self.total_images)?;
+        dict.set_item("symlink_count", self.symlink_count)?;
+        dict.set_item("broken_symlinks", self.broken_symlinks)?;
+        dict.set_item("total_sidecars", self.total_sidecars)?;
+        dict.set_item("coverage_percentage", self.coverage_percentage)?;
+        dict.set_item("operation_counts", &self.operation_counts)?;
+        dict.set_item("avg_processing_times", &self.avg_processing_times)?;
+        dict.set_item("success_rate_percentages", &self.success_rate_percentages)?;
+        dict.set_item("avg_data_sizes", &self.avg_data_sizes)?;
+        dict.set_item("filter_applied", &self.filter_applied)?;
+        dict.set_item("class_counts", &self.class_counts)?;
+        if include_sidecars {
+            let sidecars = self.sidecar_infos
+                .iter()
+                .map(|info| PySidecarInfo::from(info.clone()).to_dict(py))
+                .collect::<PyResult<Vec<PyObject>>>()?;
+            dict.set_item("sidecars", sidecars)?;
+        }
+        Ok(dict.to_object(py))
+    }
+}
+
 /// Python module definition
 #[pymodule]
-pub fn image_sidecar_rust(_py: Python, m: &PyModule) -> PyResult<()> {
+pub fn image_sidecar_rust(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<PyImageSidecar>()?;
     m.add_class::<PySidecarFormat>()?;
     m.add_class::<PyOperationType>()?;
     m.add_class::<PySidecarInfo>()?;
+    m.add_class::<PySidecarIterator>()?;
     m.add_class::<PyValidationResult>()?;
     m.add_class::<PyStatisticsResult>()?;
     
+    m.add("SidecarException", py.get_type::<SidecarException>())?;
+    m.add("SidecarIOError", py.get_type::<SidecarIOError>())?;
+    m.add("SidecarNotFoundError", py.get_type::<SidecarNotFoundError>())?;
+    m.add("ImageNotFoundError", py.get_type::<ImageNotFoundError>())?;
+    m.add("SymlinkError", py.get_type::<SymlinkError>())?;
+    m.add("SidecarValidationError", py.get_type::<SidecarValidationError>())?;
+    m.add("SidecarFormatError", py.get_type::<SidecarFormatError>())?;
+    m.add("SidecarSchemaError", py.get_type::<SidecarSchemaError>())?;
+    m.add("SidecarLockError", py.get_type::<SidecarLockError>())?;
+    m.add("SidecarStorageError", py.get_type::<SidecarStorageError>())?;
+    m.add("SidecarCancelledError", py.get_type::<SidecarCancelledError>())?;
+    m.add("SidecarProcessingError", py.get_type::<SidecarProcessingError>())?;
+    m.add("SidecarReadOnlyError", py.get_type::<SidecarReadOnlyError>())?;
+    m.add("SidecarTooLargeError", py.get_type::<SidecarTooLargeError>())?;
+    
     m.add("__version__", "0.1.0")?;
     
     Ok(())
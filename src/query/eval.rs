@@ -0,0 +1,259 @@
+/*
+ * Context: Evaluation of query expressions against sidecar records
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde_json
+ */
+
+use crate::parallel::ParallelProcessor;
+use crate::query::parser::{CompareOp, Operand, QueryExpr};
+use crate::sidecar::skim::SkimKeys;
+use crate::sidecar::types::SidecarInfo;
+use crate::utils::paths;
+use serde::Serialize;
+use serde_json::Value;
+use std::cmp::Ordering;
+
+/// A sidecar together with its decoded contents, as seen by a query
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryMatch {
+    #[serde(flatten)]
+    pub sidecar: SidecarInfo,
+    pub data: Value,
+}
+
+impl QueryExpr {
+    /// Evaluate this expression against one sidecar
+    ///
+    /// Field paths resolve against a few computed fields first (`operation`, `image`,
+    /// `sidecar`, `valid`, `success`, `processing_time`, `data_size`, `detections`,
+    /// `confidence`), then against the raw sidecar data, then against the operation
+    /// payload. Paths that fan out over arrays can end in `count`, `min`, `max`,
+    /// `sum` or `avg`.
+    pub fn matches(&self, sidecar: &SidecarInfo, data: &Value) -> bool {
+        match self {
+            QueryExpr::Or(left, right) => left.matches(sidecar, data) || right.matches(sidecar, data),
+            QueryExpr::And(left, right) => left.matches(sidecar, data) && right.matches(sidecar, data),
+            QueryExpr::Not(inner) => !inner.matches(sidecar, data),
+            QueryExpr::Compare(left, op, right) => {
+                let left = resolve_operand(left, sidecar, data);
+                let right = resolve_operand(right, sidecar, data);
+                compare(&left, *op, &right)
+            }
+            QueryExpr::Truthy(operand) => is_truthy(&resolve_operand(operand, sidecar, data)),
+        }
+    }
+
+    /// Keys an outline of a sidecar must keep whole for `matches` to agree with the document
+    ///
+    /// Outlines drop the numbers inside arrays, so the last key each data path names is kept
+    /// whole; paths rooted at a computed field need nothing beyond the outline.
+    pub fn skim_keys(&self) -> SkimKeys {
+        let mut keys = SkimKeys::new();
+        self.collect_skim_keys(&mut keys);
+        keys
+    }
+
+    fn collect_skim_keys(&self, keys: &mut SkimKeys) {
+        match self {
+            QueryExpr::Or(left, right) | QueryExpr::And(left, right) => {
+                left.collect_skim_keys(keys);
+                right.collect_skim_keys(keys);
+            }
+            QueryExpr::Not(inner) => inner.collect_skim_keys(keys),
+            QueryExpr::Compare(left, _, right) => keys.extend(skim_key(left).into_iter().chain(skim_key(right))),
+            QueryExpr::Truthy(operand) => keys.extend(skim_key(operand)),
+        }
+    }
+}
+
+/// The key a field operand reads numbers under, unless it is a computed field
+fn skim_key(operand: &Operand) -> Option<&str> {
+    let Operand::Field(path) = operand else {
+        return None;
+    };
+    let root = path.first()?;
+    if COMPUTED_FIELDS.contains(&root.as_str()) {
+        return None;
+    }
+    path.iter()
+        .rev()
+        .find(|segment| aggregate(&[], segment).is_none() && segment.parse::<usize>().is_err())
+        .map(String::as_str)
+}
+
+/// Field roots `resolve_field` computes from the sidecar record instead of its data
+const COMPUTED_FIELDS: [&str; 11] = [
+    "operation", "image", "image_path", "sidecar", "sidecar_path", "valid", "success",
+    "processing_time", "data_size", "detections", "confidence",
+];
+
+fn resolve_operand(operand: &Operand, sidecar: &SidecarInfo, data: &Value) -> Value {
+    match operand {
+        Operand::Literal(value) => value.clone(),
+        Operand::Field(path) => resolve_field(path, sidecar, data),
+    }
+}
+
+fn resolve_field(path: &[String], sidecar: &SidecarInfo, data: &Value) -> Value {
+    let Some((root, rest)) = path.split_first() else {
+        return Value::Null;
+    };
+    let payload = sidecar.operation_payload(data);
+
+    let computed = match root.as_str() {
+        "operation" => Some(Value::String(sidecar.operation.as_str().to_string())),
+        "image" | "image_path" => Some(Value::String(paths::encode_path(&sidecar.image_path).into_owned())),
+        "sidecar" | "sidecar_path" => Some(Value::String(paths::encode_path(&sidecar.sidecar_path).into_owned())),
+        "valid" => Some(Value::Bool(sidecar.is_valid)),
+        "success" => Some(Value::Bool(sidecar.get_success_status())),
+        "processing_time" => Some(sidecar.get_processing_time().map(Value::from).unwrap_or(Value::Null)),
+        "data_size" => Some(Value::from(sidecar.data_size)),
+        "detections" => Some(Value::from(ParallelProcessor::extract_detection_count(payload))),
+        "confidence" => {
+            let mut confidences = Vec::new();
+            collect_key(payload, "confidence", &mut confidences);
+            Some(Value::Array(confidences))
+        }
+        _ => None,
+    };
+
+    if let Some(value) = computed {
+        return navigate(&value, rest);
+    }
+
+    match navigate(data, path) {
+        Value::Null => navigate(payload, path),
+        value => value,
+    }
+}
+
+/// Walk a dotted path, fanning out over arrays and applying trailing aggregates
+fn navigate(value: &Value, path: &[String]) -> Value {
+    let Some((segment, rest)) = path.split_first() else {
+        return value.clone();
+    };
+
+    match value {
+        Value::Object(map) => match map.get(segment) {
+            Some(child) => navigate(child, rest),
+            None => Value::Null,
+        },
+        Value::Array(items) => {
+            if let Some(aggregated) = aggregate(items, segment) {
+                return navigate(&aggregated, rest);
+            }
+            if let Ok(index) = segment.parse::<usize>() {
+                return items.get(index).map(|item| navigate(item, rest)).unwrap_or(Value::Null);
+            }
+
+            // Apply this segment to every element, then continue on the collected values
+            let mut fanned_out = Vec::new();
+            for item in items {
+                match navigate(item, std::slice::from_ref(segment)) {
+                    Value::Null => {}
+                    Value::Array(nested) => fanned_out.extend(nested),
+                    other => fanned_out.push(other),
+                }
+            }
+            navigate(&Value::Array(fanned_out), rest)
+        }
+        _ => Value::Null,
+    }
+}
+
+fn aggregate(items: &[Value], name: &str) -> Option<Value> {
+    let numbers = || items.iter().filter_map(|item| item.as_f64());
+
+    let value = match name {
+        "count" | "len" => Value::from(items.len()),
+        "sum" => Value::from(numbers().sum::<f64>()),
+        "min" => numbers().reduce(f64::min).map(Value::from).unwrap_or(Value::Null),
+        "max" => numbers().reduce(f64::max).map(Value::from).unwrap_or(Value::Null),
+        "avg" => {
+            let (sum, count) = numbers().fold((0.0, 0usize), |(sum, count), n| (sum + n, count + 1));
+            if count > 0 { Value::from(sum / count as f64) } else { Value::Null }
+        }
+        _ => return None,
+    };
+    Some(value)
+}
+
+fn collect_key(value: &Value, key: &str, found: &mut Vec<Value>) {
+    match value {
+        Value::Object(map) => {
+            for (name, child) in map {
+                if name == key && child.is_number() {
+                    found.push(child.clone());
+                } else {
+                    collect_key(child, key, found);
+                }
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| collect_key(item, key, found)),
+        _ => {}
+    }
+}
+
+/// Highest `confidence` anywhere in `value`, if it holds any
+#[cfg(any(feature = "sql", feature = "db"))]
+pub(crate) fn max_confidence(value: &Value) -> Option<f64> {
+    match value {
+        Value::Object(map) => map.iter()
+            .map(|(key, child)| match child {
+                Value::Number(n) if key == "confidence" => n.as_f64(),
+                _ => max_confidence(child),
+            })
+            .fold(None, max_of),
+        Value::Array(items) => items.iter().map(max_confidence).fold(None, max_of),
+        _ => None,
+    }
+}
+
+#[cfg(any(feature = "sql", feature = "db"))]
+fn max_of(a: Option<f64>, b: Option<f64>) -> Option<f64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+fn compare(left: &Value, op: CompareOp, right: &Value) -> bool {
+    let ordering = match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64().zip(b.as_f64()).and_then(|(a, b)| a.partial_cmp(&b)),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        _ => {
+            // Mixed or non-ordered types only support equality
+            return match op {
+                CompareOp::Eq => left == right,
+                CompareOp::Ne => left != right,
+                _ => false,
+            };
+        }
+    };
+
+    let Some(ordering) = ordering else {
+        return false;
+    };
+
+    match op {
+        CompareOp::Eq => ordering == Ordering::Equal,
+        CompareOp::Ne => ordering != Ordering::Equal,
+        CompareOp::Lt => ordering == Ordering::Less,
+        CompareOp::Le => ordering != Ordering::Greater,
+        CompareOp::Gt => ordering == Ordering::Greater,
+        CompareOp::Ge => ordering != Ordering::Less,
+    }
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+    }
+}
@@ -0,0 +1,67 @@
+/*
+ * Context: JSONPath field extraction across sidecar directories
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde_json, serde_json_path
+ */
+
+use crate::query::parser::QueryError;
+use crate::sidecar::types::SidecarInfo;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_json_path::JsonPath;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// A compiled JSONPath expression such as `$.face_detection.faces[*].confidence`
+#[derive(Debug, Clone)]
+pub struct ExtractPath {
+    path: JsonPath,
+}
+
+impl ExtractPath {
+    /// Compile a JSONPath expression
+    pub fn parse(path: &str) -> Result<Self, QueryError> {
+        JsonPath::parse(path)
+            .map(|path| Self { path })
+            .map_err(|e| QueryError::InvalidPath(e.to_string()))
+    }
+
+    /// The nodes of `data` the path matches
+    pub fn query<'a>(&self, data: &'a Value) -> Vec<&'a Value> {
+        self.path.query(data).all()
+    }
+
+    /// Evaluate the path against one sidecar, producing one pair per matched node
+    pub fn extract(&self, sidecar: &SidecarInfo, data: &Value) -> Vec<ExtractedValue> {
+        self.path
+            .query(data)
+            .all()
+            .into_iter()
+            .map(|value| ExtractedValue {
+                image_path: sidecar.image_path.clone(),
+                sidecar_path: sidecar.sidecar_path.clone(),
+                value: value.clone(),
+            })
+            .collect()
+    }
+}
+
+impl FromStr for ExtractPath {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// One value extracted from a sidecar, paired with the image it belongs to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExtractedValue {
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub image_path: PathBuf,
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub sidecar_path: PathBuf,
+    pub value: Value,
+}
@@ -0,0 +1,19 @@
+/*
+ * Context: Query subsystem for filtering sidecars with an expression language
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde_json, thiserror
+ */
+
+pub mod eval;
+pub mod extract;
+pub mod parser;
+pub mod project;
+pub mod thresholds;
+
+pub use eval::QueryMatch;
+pub use extract::{ExtractPath, ExtractedValue};
+pub use parser::{CompareOp, Operand, QueryError, QueryExpr};
+pub use project::Projection;
+pub use thresholds::DetectionFilter;
@@ -0,0 +1,321 @@
+/*
+ * Context: Parser for the sidecar query expression language
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde_json, thiserror
+ */
+
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Errors raised while parsing a query expression
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum QueryError {
+    #[error("Unexpected character '{found}' at position {position}")]
+    UnexpectedCharacter { position: usize, found: char },
+    #[error("Unexpected token '{found}' at position {position}")]
+    UnexpectedToken { position: usize, found: String },
+    #[error("Unexpected end of expression")]
+    UnexpectedEnd,
+    #[error("Unterminated string starting at position {0}")]
+    UnterminatedString(usize),
+    #[error("Invalid number '{0}'")]
+    InvalidNumber(String),
+    #[error("Invalid JSONPath: {0}")]
+    InvalidPath(String),
+    #[error("Invalid field path {0}")]
+    InvalidField(String),
+}
+
+/// Comparison operators
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl fmt::Display for CompareOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            CompareOp::Eq => "==",
+            CompareOp::Ne => "!=",
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+        };
+        f.write_str(symbol)
+    }
+}
+
+/// One side of a comparison
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    /// Dotted field path such as `confidence.max` or `face_detection.faces.count`
+    Field(Vec<String>),
+    /// String, number, boolean or null literal
+    Literal(Value),
+}
+
+/// Parsed query expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryExpr {
+    Or(Box<QueryExpr>, Box<QueryExpr>),
+    And(Box<QueryExpr>, Box<QueryExpr>),
+    Not(Box<QueryExpr>),
+    Compare(Operand, CompareOp, Operand),
+    /// A bare operand, true when its value is truthy
+    Truthy(Operand),
+}
+
+impl QueryExpr {
+    /// Parse an expression such as `operation == 'face_detection' && detections > 2`
+    pub fn parse(input: &str) -> Result<Self, QueryError> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser { tokens, position: 0 };
+        let expr = parser.parse_or()?;
+
+        match parser.peek() {
+            None => Ok(expr),
+            Some((position, token)) => Err(QueryError::UnexpectedToken {
+                position: *position,
+                found: token.to_string(),
+            }),
+        }
+    }
+}
+
+impl FromStr for QueryExpr {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Path(String),
+    Literal(Value),
+    Compare(CompareOp),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Path(path) => f.write_str(path),
+            Token::Literal(value) => write!(f, "{}", value),
+            Token::Compare(op) => write!(f, "{}", op),
+            Token::And => f.write_str("&&"),
+            Token::Or => f.write_str("||"),
+            Token::Not => f.write_str("!"),
+            Token::LParen => f.write_str("("),
+            Token::RParen => f.write_str(")"),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, QueryError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        let next = chars.get(i + 1).copied();
+
+        let token = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => {
+                i += 1;
+                Token::LParen
+            }
+            ')' => {
+                i += 1;
+                Token::RParen
+            }
+            '&' if next == Some('&') => {
+                i += 2;
+                Token::And
+            }
+            '|' if next == Some('|') => {
+                i += 2;
+                Token::Or
+            }
+            '=' if next == Some('=') => {
+                i += 2;
+                Token::Compare(CompareOp::Eq)
+            }
+            '!' if next == Some('=') => {
+                i += 2;
+                Token::Compare(CompareOp::Ne)
+            }
+            '!' => {
+                i += 1;
+                Token::Not
+            }
+            '<' | '>' => {
+                let or_equal = next == Some('=');
+                i += if or_equal { 2 } else { 1 };
+                Token::Compare(match (c, or_equal) {
+                    ('<', false) => CompareOp::Lt,
+                    ('<', true) => CompareOp::Le,
+                    ('>', false) => CompareOp::Gt,
+                    _ => CompareOp::Ge,
+                })
+            }
+            '\'' | '"' => {
+                let quote = c;
+                let mut value = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(QueryError::UnterminatedString(start)),
+                        Some('\\') if i + 1 < chars.len() => {
+                            value.push(chars[i + 1]);
+                            i += 2;
+                        }
+                        Some(&ch) if ch == quote => {
+                            i += 1;
+                            break;
+                        }
+                        Some(&ch) => {
+                            value.push(ch);
+                            i += 1;
+                        }
+                    }
+                }
+                Token::Literal(Value::String(value))
+            }
+            c if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || matches!(chars[i], '.' | 'e' | 'E')) {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let number = text.parse::<f64>()
+                    .ok()
+                    .and_then(serde_json::Number::from_f64)
+                    .ok_or_else(|| QueryError::InvalidNumber(text.clone()))?;
+                Token::Literal(Value::Number(number))
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                while i < chars.len() && (chars[i].is_alphanumeric() || matches!(chars[i], '_' | '.')) {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                match word.as_str() {
+                    "true" => Token::Literal(Value::Bool(true)),
+                    "false" => Token::Literal(Value::Bool(false)),
+                    "null" => Token::Literal(Value::Null),
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    _ => Token::Path(word),
+                }
+            }
+            found => return Err(QueryError::UnexpectedCharacter { position: start, found }),
+        };
+
+        tokens.push((start, token));
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&(usize, Token)> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<(usize, Token)> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, expected: &Token) -> bool {
+        if self.peek().map(|(_, token)| token) == Some(expected) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<QueryExpr, QueryError> {
+        let mut expr = self.parse_and()?;
+        while self.eat(&Token::Or) {
+            expr = QueryExpr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<QueryExpr, QueryError> {
+        let mut expr = self.parse_unary()?;
+        while self.eat(&Token::And) {
+            expr = QueryExpr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<QueryExpr, QueryError> {
+        if self.eat(&Token::Not) {
+            return Ok(QueryExpr::Not(Box::new(self.parse_unary()?)));
+        }
+
+        if self.eat(&Token::LParen) {
+            let expr = self.parse_or()?;
+            return match self.next() {
+                Some((_, Token::RParen)) => Ok(expr),
+                Some((position, token)) => Err(QueryError::UnexpectedToken { position, found: token.to_string() }),
+                None => Err(QueryError::UnexpectedEnd),
+            };
+        }
+
+        let left = self.parse_operand()?;
+        if let Some((_, Token::Compare(op))) = self.peek() {
+            let op = *op;
+            self.position += 1;
+            let right = self.parse_operand()?;
+            return Ok(QueryExpr::Compare(left, op, right));
+        }
+
+        Ok(QueryExpr::Truthy(left))
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand, QueryError> {
+        match self.next() {
+            Some((_, Token::Path(path))) => Ok(Operand::Field(
+                path.split('.')
+                    .filter(|segment| !segment.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            )),
+            Some((_, Token::Literal(value))) => Ok(Operand::Literal(value)),
+            Some((position, token)) => Err(QueryError::UnexpectedToken { position, found: token.to_string() }),
+            None => Err(QueryError::UnexpectedEnd),
+        }
+    }
+}
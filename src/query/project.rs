@@ -0,0 +1,191 @@
+/*
+ * Context: Field projection that cuts sidecars down to the parts an export needs
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde_json, thiserror
+ */
+
+use crate::query::parser::QueryError;
+use crate::sidecar::types::SidecarInfo;
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+/// A set of field paths such as `face_detection.faces[*].bbox` to keep from a document
+///
+/// Paths are dot-separated keys, each optionally followed by `[*]` for every array element
+/// or `[N]` for one; a key of `*` matches every key. The projection keeps the shape of the
+/// document, so `faces[*].bbox` and `faces[*].confidence` together give one object per face
+/// holding both. Fields a document lacks are left out.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Projection {
+    fields: Vec<String>,
+    root: Node,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Node {
+    /// Keep the whole value here
+    whole: bool,
+    keys: BTreeMap<String, Node>,
+    any_key: Option<Box<Node>>,
+    items: Option<Box<Node>>,
+    indices: BTreeMap<usize, Node>,
+}
+
+enum Step {
+    Key(String),
+    AnyKey,
+    Items,
+    Index(usize),
+}
+
+fn parse_field(field: &str) -> Result<Vec<Step>, QueryError> {
+    let invalid = |reason: &str| QueryError::InvalidField(format!("'{}': {}", field, reason));
+    let mut steps = Vec::new();
+    for segment in field.split('.') {
+        let (key, mut brackets) = segment.split_at(segment.find('[').unwrap_or(segment.len()));
+        match key {
+            "" => return Err(invalid("empty key")),
+            "*" => steps.push(Step::AnyKey),
+            key => steps.push(Step::Key(key.to_string())),
+        }
+        while !brackets.is_empty() {
+            let close = brackets.find(']').filter(|_| brackets.starts_with('[')).ok_or_else(|| invalid("expected [*] or [N]"))?;
+            steps.push(match &brackets[1..close] {
+                "*" => Step::Items,
+                index => Step::Index(index.parse().map_err(|_| invalid("array index must be a number or *"))?),
+            });
+            brackets = &brackets[close + 1..];
+        }
+    }
+    Ok(steps)
+}
+
+impl Projection {
+    /// Parse comma-separated field paths
+    pub fn parse(fields: &str) -> Result<Self, QueryError> {
+        let fields: Vec<&str> = fields.split(',').map(str::trim).filter(|field| !field.is_empty()).collect();
+        Self::from_fields(&fields)
+    }
+
+    /// Build a projection from one path per field
+    pub fn from_fields<S: AsRef<str>>(fields: &[S]) -> Result<Self, QueryError> {
+        let mut projection = Self::default();
+        for field in fields {
+            let field = field.as_ref().trim();
+            let mut node = &mut projection.root;
+            for step in parse_field(field)? {
+                node = match step {
+                    Step::Key(key) => node.keys.entry(key).or_default(),
+                    Step::AnyKey => node.any_key.get_or_insert_with(Default::default),
+                    Step::Items => node.items.get_or_insert_with(Default::default),
+                    Step::Index(index) => node.indices.entry(index).or_default(),
+                };
+            }
+            node.whole = true;
+            projection.fields.push(field.to_string());
+        }
+        Ok(projection)
+    }
+
+    /// The field paths, as given
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// The parts of `value` the fields select; an empty object if none are present
+    pub fn project(&self, value: &Value) -> Value {
+        project(value, &self.root).unwrap_or_else(|| Value::Object(Map::new()))
+    }
+
+    /// Project one sidecar, its payloads seen beside the `SidecarInfo` fields
+    ///
+    /// `image_path`, `operation` and the other listed fields resolve against the sidecar's
+    /// metadata; any other top-level key, such as `face_detection`, against its contents.
+    pub fn project_sidecar(&self, sidecar: &SidecarInfo, data: &Value) -> Value {
+        let mut document = match serde_json::to_value(sidecar) {
+            Ok(Value::Object(document)) => document,
+            _ => Map::new(),
+        };
+        if let Some(contents) = data.as_object() {
+            for (key, value) in contents {
+                document.entry(key.clone()).or_insert_with(|| value.clone());
+            }
+        }
+        self.project(&Value::Object(document))
+    }
+}
+
+fn project(value: &Value, node: &Node) -> Option<Value> {
+    if node.whole {
+        return Some(value.clone());
+    }
+    match value {
+        Value::Object(object) => {
+            let projected: Map<String, Value> = object
+                .iter()
+                .filter_map(|(key, value)| {
+                    let child = combine(node.keys.get(key), node.any_key.as_deref())?;
+                    project(value, &child).map(|value| (key.clone(), value))
+                })
+                .collect();
+            (!projected.is_empty()).then_some(Value::Object(projected))
+        }
+        Value::Array(items) => {
+            let projected: Vec<Value> = items
+                .iter()
+                .enumerate()
+                .filter_map(|(index, item)| {
+                    let child = combine(node.indices.get(&index), node.items.as_deref())?;
+                    project(item, &child)
+                })
+                .collect();
+            // `[*]` keeps an empty list, so "no faces" stays distinguishable from "no face_detection"
+            (node.items.is_some() || !projected.is_empty()).then_some(Value::Array(projected))
+        }
+        _ => None,
+    }
+}
+
+/// The fields two paths select from the same value, e.g. a named key and `*`
+fn combine<'a>(left: Option<&'a Node>, right: Option<&'a Node>) -> Option<Cow<'a, Node>> {
+    match (left, right) {
+        (Some(left), Some(right)) => Some(Cow::Owned(merge(left.clone(), right))),
+        (left, right) => left.or(right).map(Cow::Borrowed),
+    }
+}
+
+fn merge(mut left: Node, right: &Node) -> Node {
+    fn merge_boxed(left: &mut Option<Box<Node>>, right: &Option<Box<Node>>) {
+        if let Some(right) = right {
+            *left = Some(Box::new(match left.take() {
+                Some(left) => merge(*left, right),
+                None => (**right).clone(),
+            }));
+        }
+    }
+    left.whole |= right.whole;
+    for (key, node) in &right.keys {
+        let merged = match left.keys.remove(key) {
+            Some(existing) => merge(existing, node),
+            None => node.clone(),
+        };
+        left.keys.insert(key.clone(), merged);
+    }
+    for (index, node) in &right.indices {
+        let merged = match left.indices.remove(index) {
+            Some(existing) => merge(existing, node),
+            None => node.clone(),
+        };
+        left.indices.insert(*index, merged);
+    }
+    merge_boxed(&mut left.any_key, &right.any_key);
+    merge_boxed(&mut left.items, &right.items);
+    left
+}
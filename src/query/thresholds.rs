@@ -0,0 +1,126 @@
+/*
+ * Context: Detection-count and confidence thresholds applied while validating and querying
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde_json
+ */
+
+use crate::geometry::detections_in;
+use serde_json::Value;
+
+/// Keep only sidecars with a payload whose detections fall within the bounds
+///
+/// Only detections with a confidence in `min_confidence..=max_confidence` (read from
+/// `confidence`, else `score`) are counted, and the count must lie in
+/// `min_detections..=max_detections`. With a confidence bound and no count bound, at least
+/// one detection must be in range. A payload without a detection list counts its `count`
+/// field, as long as no confidence bound is set. An empty filter keeps every sidecar.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DetectionFilter {
+    /// Only look at this operation's payload; otherwise any payload may match
+    pub operation: Option<String>,
+    pub min_detections: Option<usize>,
+    pub max_detections: Option<usize>,
+    pub min_confidence: Option<f64>,
+    pub max_confidence: Option<f64>,
+}
+
+impl DetectionFilter {
+    pub fn operation(mut self, operation: impl Into<String>) -> Self {
+        self.operation = Some(operation.into());
+        self
+    }
+
+    pub fn min_detections(mut self, count: usize) -> Self {
+        self.min_detections = Some(count);
+        self
+    }
+
+    pub fn max_detections(mut self, count: usize) -> Self {
+        self.max_detections = Some(count);
+        self
+    }
+
+    pub fn min_confidence(mut self, confidence: f64) -> Self {
+        self.min_confidence = Some(confidence);
+        self
+    }
+
+    pub fn max_confidence(mut self, confidence: f64) -> Self {
+        self.max_confidence = Some(confidence);
+        self
+    }
+
+    /// Whether the filter has no bounds and keeps every sidecar
+    pub fn is_empty(&self) -> bool {
+        self.min_detections.is_none()
+            && self.max_detections.is_none()
+            && self.min_confidence.is_none()
+            && self.max_confidence.is_none()
+    }
+
+    fn has_confidence_bound(&self) -> bool {
+        self.min_confidence.is_some() || self.max_confidence.is_some()
+    }
+
+    /// Whether the decoded sidecar `data` has a payload within the bounds
+    ///
+    /// `recorded_operation` is the operation the sidecar was classified as; a raw detector
+    /// file of that operation is its own payload.
+    pub fn matches(&self, data: &Value, recorded_operation: Option<&str>) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+        match &self.operation {
+            Some(operation) => match data.get(operation) {
+                Some(payload) => self.accepts(payload),
+                None => recorded_operation == Some(operation.as_str()) && self.accepts(data),
+            },
+            None => payloads(data).any(|payload| self.accepts(payload)),
+        }
+    }
+
+    /// Whether one operation payload is within the bounds
+    pub fn accepts(&self, payload: &Value) -> bool {
+        let Some(count) = self.count(payload) else {
+            return false;
+        };
+        let min = self.min_detections.unwrap_or(usize::from(self.has_confidence_bound() && self.max_detections.is_none()));
+        count >= min && self.max_detections.is_none_or(|max| count <= max)
+    }
+
+    /// Detections in `payload` within the confidence bounds, or `None` if it holds no detections
+    fn count(&self, payload: &Value) -> Option<usize> {
+        let Some(detections) = detections_in(payload) else {
+            if self.has_confidence_bound() {
+                return None;
+            }
+            return payload.get("count").and_then(Value::as_u64).map(|count| count as usize);
+        };
+        if !self.has_confidence_bound() {
+            return Some(detections.len());
+        }
+        let in_range = |confidence: f64| {
+            self.min_confidence.is_none_or(|min| confidence >= min) && self.max_confidence.is_none_or(|max| confidence <= max)
+        };
+        Some(detections.iter().filter_map(confidence).filter(|confidence| in_range(*confidence)).count())
+    }
+}
+
+/// Confidence of one detection, from `confidence` or `score`
+pub fn confidence(detection: &Value) -> Option<f64> {
+    ["confidence", "score"].iter().find_map(|key| detection.get(key).and_then(Value::as_f64))
+}
+
+/// Every operation payload of a sidecar: its top-level objects other than `sidecar_info`,
+/// and the document itself for raw detector output
+fn payloads(data: &Value) -> impl Iterator<Item = &Value> {
+    let nested = data
+        .as_object()
+        .into_iter()
+        .flat_map(|object| object.iter())
+        .filter(|(key, value)| *key != "sidecar_info" && value.is_object())
+        .map(|(_, value)| value);
+    std::iter::once(data).filter(|data| detections_in(data).is_some()).chain(nested)
+}
@@ -0,0 +1,195 @@
+/*
+ * Context: JUnit XML and SARIF emitters for validation results
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde_json
+ */
+
+use crate::sidecar::types::ValidationResult;
+use crate::sidecar::types::Result;
+use crate::utils::paths;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+
+/// Name reported as the producing tool
+const TOOL_NAME: &str = "image-sidecar-rust";
+
+/// Group name for results whose operation type could not be determined
+const UNKNOWN_OPERATION: &str = "unknown";
+
+/// CI report formats for validation results
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// JUnit XML: one test suite per operation type, one test case per sidecar
+    Junit,
+    /// SARIF 2.1.0: one rule per operation type, one result per invalid sidecar
+    Sarif,
+}
+
+impl ReportFormat {
+    /// Pick the format from a report file name: `.xml` is JUnit, `.sarif` and `.json` are SARIF
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "xml" => Some(ReportFormat::Junit),
+            "sarif" | "json" => Some(ReportFormat::Sarif),
+            _ => None,
+        }
+    }
+
+    /// Write `results` in this format
+    pub fn write<W: Write>(&self, results: &[ValidationResult], writer: W) -> Result<()> {
+        match self {
+            ReportFormat::Junit => write_junit(results, writer),
+            ReportFormat::Sarif => write_sarif(results, writer),
+        }
+    }
+}
+
+/// Write a JUnit XML report with one `<testsuite>` per operation type
+pub fn write_junit<W: Write>(results: &[ValidationResult], mut writer: W) -> Result<()> {
+    let groups = group_by_operation(results);
+    let failures = results.iter().filter(|result| !result.is_valid).count();
+    let total_time: f64 = results.iter().map(|result| result.processing_time).sum();
+
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        writer,
+        r#"<testsuites name="sidecar-validation" tests="{}" failures="{}" time="{:.6}">"#,
+        results.len(),
+        failures,
+        total_time
+    )?;
+
+    for (operation, members) in &groups {
+        let suite_failures = members.iter().filter(|result| !result.is_valid).count();
+        let suite_time: f64 = members.iter().map(|result| result.processing_time).sum();
+        writeln!(
+            writer,
+            r#"  <testsuite name="{}" tests="{}" failures="{}" time="{:.6}">"#,
+            escape_xml(operation),
+            members.len(),
+            suite_failures,
+            suite_time
+        )?;
+
+        for result in members {
+            let name = escape_xml(&paths::encode_path(&result.file_path));
+            let attributes = format!(
+                r#"name="{}" classname="{}" time="{:.6}""#,
+                name,
+                escape_xml(operation),
+                result.processing_time
+            );
+
+            if result.is_valid {
+                writeln!(writer, "    <testcase {} />", attributes)?;
+            } else {
+                let reason = escape_xml(failure_reason(result));
+                let kind = if result.oversized { "oversized_sidecar" } else { "invalid_sidecar" };
+                writeln!(writer, "    <testcase {}>", attributes)?;
+                writeln!(writer, r#"      <failure message="{}" type="{}">{}</failure>"#, reason, kind, reason)?;
+                writeln!(writer, "    </testcase>")?;
+            }
+        }
+
+        writeln!(writer, "  </testsuite>")?;
+    }
+
+    writeln!(writer, "</testsuites>")?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Write a SARIF 2.1.0 log with one rule per operation type and one result per invalid sidecar
+pub fn write_sarif<W: Write>(results: &[ValidationResult], mut writer: W) -> Result<()> {
+    let groups = group_by_operation(results);
+
+    let rules: Vec<Value> = groups
+        .keys()
+        .map(|operation| json!({
+            "id": rule_id(operation),
+            "name": "InvalidSidecar",
+            "shortDescription": { "text": format!("Invalid {} sidecar", operation) },
+            "properties": { "operation_type": operation },
+        }))
+        .collect();
+
+    let findings: Vec<Value> = groups
+        .iter()
+        .flat_map(|(operation, members)| members.iter().map(move |result| (operation, result)))
+        .filter(|(_, result)| !result.is_valid)
+        .map(|(operation, result)| json!({
+            "ruleId": rule_id(operation),
+            "level": "error",
+            "message": { "text": failure_reason(result) },
+            "locations": [{
+                "physicalLocation": {
+                    "artifactLocation": { "uri": paths::encode_path(&result.file_path) }
+                }
+            }],
+            "properties": {
+                "operation_type": operation,
+                "file_size": result.file_size,
+            },
+        }))
+        .collect();
+
+    let log = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": TOOL_NAME,
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                }
+            },
+            "results": findings,
+        }],
+    });
+
+    serde_json::to_writer_pretty(&mut writer, &log)?;
+    writeln!(writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Results keyed by operation type name, in a stable order
+fn group_by_operation(results: &[ValidationResult]) -> BTreeMap<&str, Vec<&ValidationResult>> {
+    let mut groups: BTreeMap<&str, Vec<&ValidationResult>> = BTreeMap::new();
+    for result in results {
+        let operation = result.operation_type.as_ref().map_or(UNKNOWN_OPERATION, |op| op.as_str());
+        groups.entry(operation).or_default().push(result);
+    }
+    groups
+}
+
+fn rule_id(operation: &str) -> String {
+    format!("invalid-sidecar/{}", operation)
+}
+
+fn failure_reason(result: &ValidationResult) -> &str {
+    result.error.as_deref().unwrap_or("Sidecar failed validation")
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than tab/newline are not allowed in XML 1.0
+            c if c.is_control() && c != '\t' && c != '\n' && c != '\r' => escaped.push('\u{FFFD}'),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
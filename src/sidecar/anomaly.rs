@@ -0,0 +1,205 @@
+/*
+ * Context: Statistical outliers among a directory's sidecars, as a pipeline health check
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde, serde_json
+ *
+ * Sidecars are compared with the others of their operation: files many times the median
+ * size, processing times beyond a high percentile, and payloads with more detections than
+ * any real image holds. A healthy run reports none.
+ */
+
+use crate::geometry::{detections_in, sidecar_payloads};
+use crate::sidecar::types::{Result, SidecarError, SidecarInfo};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Sidecars this many times their operation's median size are outliers
+pub const DEFAULT_SIZE_FACTOR: f64 = 10.0;
+/// Processing times above this percentile of their operation are outliers
+pub const DEFAULT_TIME_PERCENTILE: f64 = 99.9;
+/// Payloads with more detections than this are outliers
+pub const DEFAULT_MAX_DETECTIONS: usize = 300;
+
+/// Where the outlier thresholds lie
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnomalyOptions {
+    pub size_factor: f64,
+    pub time_percentile: f64,
+    pub max_detections: usize,
+}
+
+impl Default for AnomalyOptions {
+    fn default() -> Self {
+        Self { size_factor: DEFAULT_SIZE_FACTOR, time_percentile: DEFAULT_TIME_PERCENTILE, max_detections: DEFAULT_MAX_DETECTIONS }
+    }
+}
+
+impl AnomalyOptions {
+    pub fn size_factor(mut self, factor: f64) -> Result<Self> {
+        if factor.is_nan() || factor <= 1.0 {
+            return Err(SidecarError::ProcessingError(format!("Size factor must be above 1, got {}", factor)));
+        }
+        self.size_factor = factor;
+        Ok(self)
+    }
+
+    pub fn time_percentile(mut self, percentile: f64) -> Result<Self> {
+        if !(percentile > 0.0 && percentile < 100.0) {
+            return Err(SidecarError::ProcessingError(format!("Time percentile must be in (0, 100), got {}", percentile)));
+        }
+        self.time_percentile = percentile;
+        Ok(self)
+    }
+
+    pub fn max_detections(mut self, max_detections: usize) -> Self {
+        self.max_detections = max_detections;
+        self
+    }
+}
+
+/// Why a sidecar was flagged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyKind {
+    /// At least `size_factor` times its operation's median size
+    Oversized,
+    /// Processing time above its operation's `time_percentile`
+    SlowProcessing,
+    /// More than `max_detections` detections in one payload
+    DetectionCount,
+}
+
+/// One outlying sidecar
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Anomaly {
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub sidecar_path: PathBuf,
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub image_path: PathBuf,
+    pub operation: String,
+    pub kind: AnomalyKind,
+    /// The size in bytes, processing time in seconds or detection count that was flagged
+    pub value: f64,
+    /// The limit it crossed
+    pub threshold: f64,
+    pub reason: String,
+}
+
+/// What an operation's sidecars are compared with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OperationBaseline {
+    pub sidecars: usize,
+    pub median_size: u64,
+    /// `None` when no sidecar of the operation recorded a processing time
+    pub time_percentile: Option<f64>,
+}
+
+/// Outliers among a directory's sidecars
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnomalyReport {
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub directory: PathBuf,
+    pub sidecars: usize,
+    pub baselines: BTreeMap<String, OperationBaseline>,
+    /// Sorted by sidecar path, then kind
+    pub anomalies: Vec<Anomaly>,
+}
+
+/// A decoded sidecar reduced to what anomaly detection reads
+#[derive(Debug, Clone)]
+pub struct AnomalySample {
+    pub sidecar: SidecarInfo,
+    /// Detections per payload holding a detection list
+    pub detections: Vec<(String, usize)>,
+}
+
+impl AnomalySample {
+    /// A raw detector file counts as a payload of the operation it was classified as
+    pub fn new(sidecar: SidecarInfo, data: &Value) -> Self {
+        let detections = sidecar_payloads(data, sidecar.operation.as_str())
+            .filter_map(|(operation, payload)| Some((operation.to_string(), detections_in(payload)?.len())))
+            .collect();
+        Self { sidecar, detections }
+    }
+}
+
+impl AnomalyReport {
+    /// Compare every sample with the others of its operation
+    pub fn detect(directory: PathBuf, samples: Vec<AnomalySample>, options: &AnomalyOptions) -> Self {
+        let mut operations: BTreeMap<String, Vec<&AnomalySample>> = BTreeMap::new();
+        for sample in &samples {
+            operations.entry(sample.sidecar.operation.as_str().to_string()).or_default().push(sample);
+        }
+        let baselines: BTreeMap<String, OperationBaseline> = operations
+            .iter()
+            .map(|(operation, samples)| {
+                let mut sizes: Vec<u64> = samples.iter().map(|sample| sample.sidecar.data_size).collect();
+                sizes.sort_unstable();
+                let mut times: Vec<f64> = samples.iter().filter_map(|sample| sample.sidecar.processing_time).collect();
+                times.sort_by(f64::total_cmp);
+                let baseline = OperationBaseline {
+                    sidecars: samples.len(),
+                    median_size: sizes[sizes.len() / 2],
+                    time_percentile: percentile(&times, options.time_percentile),
+                };
+                (operation.clone(), baseline)
+            })
+            .collect();
+
+        let mut anomalies = Vec::new();
+        for sample in &samples {
+            let sidecar = &sample.sidecar;
+            let operation = sidecar.operation.as_str();
+            let baseline = &baselines[operation];
+            let anomaly = |operation: &str, kind, value: f64, threshold: f64, reason: String| Anomaly {
+                sidecar_path: sidecar.sidecar_path.clone(),
+                image_path: sidecar.image_path.clone(),
+                operation: operation.to_string(),
+                kind,
+                value,
+                threshold,
+                reason,
+            };
+            let size_limit = baseline.median_size as f64 * options.size_factor;
+            if baseline.median_size > 0 && sidecar.data_size as f64 >= size_limit {
+                anomalies.push(anomaly(operation, AnomalyKind::Oversized, sidecar.data_size as f64, size_limit, format!(
+                    "{} bytes, {:.1}x the {} median of {} bytes",
+                    sidecar.data_size, sidecar.data_size as f64 / baseline.median_size as f64, operation, baseline.median_size
+                )));
+            }
+            let slow = sidecar.processing_time.zip(baseline.time_percentile).filter(|(time, limit)| time > limit);
+            if let Some((time, limit)) = slow {
+                anomalies.push(anomaly(operation, AnomalyKind::SlowProcessing, time, limit, format!(
+                    "processing took {:.3}s, above the {} p{} of {:.3}s", time, operation, options.time_percentile, limit
+                )));
+            }
+            for (payload, count) in &sample.detections {
+                if *count > options.max_detections {
+                    anomalies.push(anomaly(payload, AnomalyKind::DetectionCount, *count as f64, options.max_detections as f64, format!(
+                        "{} detections in {}, more than {}", count, payload, options.max_detections
+                    )));
+                }
+            }
+        }
+        anomalies.sort_by(|a, b| (&a.sidecar_path, a.kind).cmp(&(&b.sidecar_path, b.kind)));
+        Self { directory, sidecars: samples.len(), baselines, anomalies }
+    }
+
+    /// Whether nothing was flagged
+    pub fn is_healthy(&self) -> bool {
+        self.anomalies.is_empty()
+    }
+}
+
+/// Nearest-rank `percentile` of ascending `values`
+///
+/// Below 1000 values p99.9 is the largest, so nothing lies above it; small runs only flag
+/// what lower percentiles catch.
+fn percentile(values: &[f64], percentile: f64) -> Option<f64> {
+    let rank = (percentile / 100.0 * values.len() as f64).ceil() as usize;
+    values.get(rank.max(1) - 1).copied()
+}
@@ -0,0 +1,147 @@
+/*
+ * Context: Tar and tar.zst export of a sidecar tree, optionally with its images
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: tar, zstd
+ */
+
+use crate::sidecar::manifest::relative_path;
+use crate::sidecar::types::{Result, SidecarError};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// zstd level used for `tar.zst` exports
+pub const ARCHIVE_ZSTD_LEVEL: i32 = 3;
+
+/// Container written by `export`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveFormat {
+    Tar,
+    TarZst,
+}
+
+impl ArchiveFormat {
+    /// Parse `tar` or `tar.zst`
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "tar" => Some(ArchiveFormat::Tar),
+            "tar.zst" | "tzst" => Some(ArchiveFormat::TarZst),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ArchiveFormat::Tar => "tar",
+            ArchiveFormat::TarZst => "tar.zst",
+        }
+    }
+}
+
+/// What an archive export includes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveOptions {
+    pub format: ArchiveFormat,
+    /// Add the image each exported sidecar belongs to
+    pub include_images: bool,
+    /// Export only sidecars holding at least one of these operations; empty exports all
+    pub operations: Vec<String>,
+}
+
+impl ArchiveOptions {
+    pub fn new(format: ArchiveFormat) -> Self {
+        Self { format, include_images: false, operations: Vec::new() }
+    }
+
+    pub fn include_images(mut self, include: bool) -> Self {
+        self.include_images = include;
+        self
+    }
+
+    pub fn operations<I, S>(mut self, operations: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.operations = operations.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+/// What an archive export wrote
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveSummary {
+    pub sidecars: usize,
+    pub images: usize,
+    /// Sidecars left out by the operation filter
+    pub filtered: usize,
+    /// Sidecars and images left out because they resolve, through a symlink, to outside the root
+    pub outside_root: Vec<PathBuf>,
+}
+
+enum ArchiveSink {
+    Plain(BufWriter<File>),
+    Zstd(zstd::Encoder<'static, BufWriter<File>>),
+}
+
+impl Write for ArchiveSink {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            ArchiveSink::Plain(writer) => writer.write(buf),
+            ArchiveSink::Zstd(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            ArchiveSink::Plain(writer) => writer.flush(),
+            ArchiveSink::Zstd(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// Streams files into a tarball under their path relative to a root
+///
+/// Files are copied as they are added, so memory use does not grow with the tree.
+pub struct ArchiveWriter {
+    builder: tar::Builder<ArchiveSink>,
+    root: PathBuf,
+}
+
+impl ArchiveWriter {
+    /// Start an archive at `path` for files under `root`
+    pub fn create(path: &Path, root: &Path, format: ArchiveFormat) -> Result<Self> {
+        let writer = BufWriter::new(File::create(path)?);
+        let sink = match format {
+            ArchiveFormat::Tar => ArchiveSink::Plain(writer),
+            ArchiveFormat::TarZst => ArchiveSink::Zstd(zstd::Encoder::new(writer, ARCHIVE_ZSTD_LEVEL)?),
+        };
+        let mut builder = tar::Builder::new(sink);
+        builder.follow_symlinks(true);
+        Ok(Self { builder, root: root.to_path_buf() })
+    }
+
+    /// Append the file at `path`, named relative to the root
+    pub fn add(&mut self, path: &Path) -> Result<()> {
+        if !path.starts_with(&self.root) {
+            return Err(SidecarError::Schema(format!("{} is outside {}", path.display(), self.root.display())));
+        }
+        self.builder.append_path_with_name(path, relative_path(&self.root, path))?;
+        Ok(())
+    }
+
+    /// Write the end-of-archive marker and sync the file to disk
+    pub fn finish(self) -> Result<()> {
+        let writer = match self.builder.into_inner()? {
+            ArchiveSink::Plain(writer) => writer,
+            ArchiveSink::Zstd(encoder) => encoder.finish()?,
+        };
+        let file = writer.into_inner().map_err(|e| e.into_error())?;
+        file.sync_all()?;
+        Ok(())
+    }
+}
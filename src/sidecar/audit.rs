@@ -0,0 +1,111 @@
+/*
+ * Context: Append-only log of every change made to sidecar files
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde_json, chrono; libc for the host name on Unix
+ */
+
+use crate::sidecar::types::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Kind of change recorded in a `MutationLog`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mutation {
+    /// A sidecar was created or rewritten
+    Write,
+    /// A sidecar was converted to another format
+    Convert,
+    /// A sidecar was removed
+    Delete,
+    /// A corrupt sidecar was repaired
+    Repair,
+    /// An orphaned sidecar was renamed to follow its moved image
+    Move,
+}
+
+/// One line of the mutation log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MutationRecord {
+    pub timestamp: DateTime<Utc>,
+    pub mutation: Mutation,
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub sidecar_path: PathBuf,
+    /// Where the sidecar ended up when the change moved it, e.g. a conversion
+    #[serde(with = "crate::utils::paths::lossless_opt")]
+    pub new_path: Option<PathBuf>,
+    /// Operation whose payload was written; `None` when the whole file changed
+    pub operation: Option<String>,
+    pub tool: String,
+    pub tool_version: String,
+    pub host: String,
+}
+
+/// Append-only JSONL record of every sidecar write, conversion, deletion and repair
+///
+/// Each record is written with a single append as soon as the change is on disk, so
+/// several processes can share one log.
+#[derive(Debug)]
+pub struct MutationLog {
+    path: PathBuf,
+    host: String,
+    file: Mutex<File>,
+}
+
+impl MutationLog {
+    /// Open `path` for appending, creating it if needed
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let file = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&path)
+            .map_err(|e| std::io::Error::new(e.kind(), format!("Cannot open audit log {}: {}", path.display(), e)))?;
+        Ok(Self { path, host: hostname(), file: Mutex::new(file) })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record(&self, mutation: Mutation, sidecar_path: &Path, new_path: Option<&Path>, operation: Option<&str>) -> Result<()> {
+        let record = MutationRecord {
+            timestamp: Utc::now(),
+            mutation,
+            sidecar_path: sidecar_path.to_path_buf(),
+            new_path: new_path.map(Path::to_path_buf),
+            operation: operation.map(str::to_string),
+            tool: env!("CARGO_PKG_NAME").to_string(),
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            host: self.host.clone(),
+        };
+        let mut line = serde_json::to_vec(&record)?;
+        line.push(b'\n');
+        self.file.lock()?.write_all(&line)?;
+        Ok(())
+    }
+}
+
+/// Name of the machine this process runs on, or `unknown`
+pub fn hostname() -> String {
+    #[cfg(unix)]
+    {
+        let mut buffer = [0u8; 256];
+        // SAFETY: gethostname writes at most `buffer.len()` bytes into the buffer we own
+        if unsafe { libc::gethostname(buffer.as_mut_ptr().cast(), buffer.len()) } == 0 {
+            let end = buffer.iter().position(|&byte| byte == 0).unwrap_or(buffer.len());
+            if end > 0 {
+                return String::from_utf8_lossy(&buffer[..end]).into_owned();
+            }
+        }
+    }
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
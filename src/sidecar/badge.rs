@@ -0,0 +1,212 @@
+/*
+ * Context: Per-directory statistics badge files, updated incrementally and rolled up without rescanning
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde_json, chrono, walkdir
+ */
+
+use crate::sidecar::formats::to_canonical_json;
+use crate::sidecar::manifest;
+use crate::sidecar::types::{OperationTotals, OperationType, Result, SidecarInfo, StatisticsResult, DEFAULT_IMBALANCE_RATIO};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Badge file written at the root of a directory by `stats --write-badge`
+pub const STATS_BADGE_FILE: &str = ".sidecar-stats.json";
+
+/// Badge layout version, bumped on incompatible changes
+pub const STATS_BADGE_VERSION: u32 = 3;
+
+/// Whether `path` is a badge file, which scans must not mistake for a sidecar
+pub fn is_badge_file(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name == STATS_BADGE_FILE)
+}
+
+/// Size and modification time of a sidecar, compared to decide whether its cached metrics still hold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileStamp {
+    pub size: u64,
+    /// Nanoseconds since the Unix epoch
+    pub modified: u64,
+}
+
+impl FileStamp {
+    /// Stamp of the file at `path`, or `None` if its metadata cannot be read
+    pub fn of(path: &Path) -> Option<Self> {
+        let metadata = std::fs::metadata(path).ok()?;
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+        Some(Self { size: metadata.len(), modified: modified.as_nanos() as u64 })
+    }
+}
+
+/// Metrics of one sidecar as cached in a badge file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BadgeEntry {
+    #[serde(flatten)]
+    pub stamp: FileStamp,
+    pub operation: String,
+    pub is_valid: bool,
+    pub data_size: u64,
+    #[serde(default)]
+    pub processing_time: Option<f64>,
+    #[serde(default)]
+    pub success: Option<bool>,
+    #[serde(default)]
+    pub recorded_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub class_counts: BTreeMap<String, u32>,
+}
+
+impl BadgeEntry {
+    pub fn new(stamp: FileStamp, info: &SidecarInfo) -> Self {
+        Self {
+            stamp,
+            operation: info.operation.as_str().to_string(),
+            is_valid: info.is_valid,
+            data_size: info.data_size,
+            processing_time: info.processing_time,
+            success: info.success,
+            recorded_at: info.recorded_at,
+            class_counts: info.class_counts.clone(),
+        }
+    }
+
+    /// The sidecar info a fresh read of the unchanged sidecar would give
+    pub fn to_info(&self, image_path: PathBuf, sidecar_path: PathBuf) -> SidecarInfo {
+        let mut info = SidecarInfo::new(image_path, sidecar_path, OperationType::from_str(&self.operation), None);
+        info.is_valid = self.is_valid;
+        info.data_size = self.data_size;
+        info.processing_time = self.processing_time;
+        info.success = self.success;
+        info.recorded_at = self.recorded_at;
+        info.class_counts = self.class_counts.clone();
+        info
+    }
+}
+
+/// Summary of a directory's sidecars, kept at its root so later runs and rollups can skip unchanged work
+///
+/// The totals add up across directories, unlike the averages in `StatisticsResult`. `files` caches
+/// the metrics of every sidecar, keyed by `/`-separated path relative to the directory.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatsBadge {
+    pub version: u32,
+    pub updated_at: DateTime<Utc>,
+    pub total_images: u32,
+    pub symlink_count: u32,
+    pub broken_symlinks: u32,
+    pub total_sidecars: u32,
+    pub operations: BTreeMap<String, OperationTotals>,
+    #[serde(default)]
+    pub files: BTreeMap<String, BadgeEntry>,
+}
+
+impl StatsBadge {
+    pub fn new(stats: &StatisticsResult, files: BTreeMap<String, BadgeEntry>) -> Self {
+        Self {
+            version: STATS_BADGE_VERSION,
+            updated_at: Utc::now(),
+            total_images: stats.total_images,
+            symlink_count: stats.symlink_count,
+            broken_symlinks: stats.broken_symlinks,
+            total_sidecars: stats.total_sidecars,
+            operations: stats.operation_totals(),
+            files,
+        }
+    }
+
+    /// Read the badge file at `path`; `None` if it is missing or was written by another version
+    pub fn load(path: &Path) -> Option<Self> {
+        let bytes = std::fs::read(path).ok()?;
+        serde_json::from_slice::<Self>(&bytes).ok().filter(|badge| badge.version == STATS_BADGE_VERSION)
+    }
+
+    /// Write the badge as canonical JSON, through a temporary file so readers never see half of it
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        std::fs::write(&temporary, to_canonical_json(&serde_json::to_value(self)?) + "\n")?;
+        std::fs::rename(&temporary, path)?;
+        Ok(())
+    }
+
+    /// The statistics this badge records, without the per-sidecar list
+    pub fn statistics(&self, directory: PathBuf) -> StatisticsResult {
+        let mut stats = StatisticsResult::new(directory);
+        stats.total_images = self.total_images;
+        stats.symlink_count = self.symlink_count;
+        stats.broken_symlinks = self.broken_symlinks;
+        stats.apply_totals(self.total_sidecars, &self.operations);
+        for (relative, entry) in &self.files {
+            let directory = relative.rsplit_once('/').map_or(".", |(directory, _)| directory);
+            stats.class_distribution.add(directory.to_string(), &entry.class_counts);
+        }
+        stats.class_distribution.check_balance(DEFAULT_IMBALANCE_RATIO);
+        stats
+    }
+}
+
+/// What refreshing a badge file reused and reread
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BadgeRefresh {
+    pub path: PathBuf,
+    /// Sidecars whose cached metrics were reused because their size and modification time were unchanged
+    pub reused: usize,
+    /// Sidecars read and decoded
+    pub read: usize,
+}
+
+/// One badge file counted by a rollup
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BadgeSource {
+    pub directory: PathBuf,
+    pub updated_at: DateTime<Utc>,
+    pub total_sidecars: u32,
+}
+
+/// Statistics summed from the badge files under a directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BadgeRollup {
+    /// Sorted by directory
+    pub badges: Vec<BadgeSource>,
+    pub aggregate: StatisticsResult,
+}
+
+/// Sum the badge files under `directory` into one result, reading no sidecars
+///
+/// A badge covers its whole subtree, so directories below one are not searched and nothing
+/// is counted twice. Unreadable badges are skipped.
+pub fn rollup(directory: &Path) -> Result<BadgeRollup> {
+    let mut badges = Vec::new();
+    let mut totals = StatsBadge::new(&StatisticsResult::new(directory.to_path_buf()), BTreeMap::new());
+    let mut walker = walkdir::WalkDir::new(directory).sort_by_file_name().into_iter();
+    while let Some(entry) = walker.next() {
+        let entry = entry.map_err(std::io::Error::from)?;
+        if !entry.file_type().is_dir() {
+            continue;
+        }
+        let Some(badge) = StatsBadge::load(&entry.path().join(STATS_BADGE_FILE)) else {
+            continue;
+        };
+        walker.skip_current_dir();
+        totals.total_images += badge.total_images;
+        totals.symlink_count += badge.symlink_count;
+        totals.broken_symlinks += badge.broken_symlinks;
+        totals.total_sidecars += badge.total_sidecars;
+        for (operation, operation_totals) in &badge.operations {
+            totals.operations.entry(operation.clone()).or_default().add(operation_totals);
+        }
+        // Only the class counts are needed from each file, keyed by its path under the rollup
+        let prefix = manifest::relative_path(directory, entry.path());
+        for (relative, file) in badge.files {
+            let relative = if prefix.is_empty() { relative } else { format!("{}/{}", prefix, relative) };
+            totals.files.insert(relative, file);
+        }
+        badges.push(BadgeSource { directory: entry.into_path(), updated_at: badge.updated_at, total_sidecars: badge.total_sidecars });
+    }
+    Ok(BadgeRollup { badges, aggregate: totals.statistics(directory.to_path_buf()) })
+}
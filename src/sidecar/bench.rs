@@ -0,0 +1,252 @@
+/*
+ * Context: Read, parse and validate throughput per sidecar format, measured on real data
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde, serde_json
+ *
+ * Every sidecar of the sample is read, decoded in the format it is stored in and run
+ * through the payload checks validation does, timing each stage. Each decoded document is
+ * then encoded in every compared format in memory and decoded again, which shows what
+ * parsing would cost after a conversion without converting anything on disk.
+ */
+
+use crate::sidecar::formats::{FormatManager, SidecarFormat};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+const MB: f64 = 1024.0 * 1024.0;
+
+/// Which sidecars a benchmark reads and which formats it compares
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchOptions {
+    /// Read at most this many sidecars, spread evenly over the sorted list
+    pub limit: Option<usize>,
+    /// Formats every decoded document is re-encoded in; empty compares none
+    pub formats: Vec<SidecarFormat>,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        Self { limit: None, formats: vec![SidecarFormat::Json, SidecarFormat::Binary, SidecarFormat::Rkyv] }
+    }
+}
+
+impl BenchOptions {
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn formats(mut self, formats: Vec<SidecarFormat>) -> Self {
+        self.formats = formats;
+        self
+    }
+
+    /// The sidecars to read out of all of them, in order
+    pub fn sample(&self, mut files: Vec<PathBuf>) -> Vec<PathBuf> {
+        match self.limit {
+            Some(limit) if limit > 0 && files.len() > limit => {
+                let step = files.len().div_ceil(limit);
+                files.into_iter().step_by(step).collect()
+            }
+            Some(0) => {
+                files.clear();
+                files
+            }
+            _ => files,
+        }
+    }
+}
+
+/// Throughput and latency of one stage over a set of files
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StageStats {
+    pub files: usize,
+    pub bytes: u64,
+    /// Time spent in the stage alone, summed over the files
+    pub seconds: f64,
+    pub files_per_sec: f64,
+    pub mb_per_sec: f64,
+    /// Per-file latency percentiles, in milliseconds
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+/// The sidecars stored in one format
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FormatBench {
+    pub format: SidecarFormat,
+    pub files: usize,
+    /// Files that could not be read or decoded
+    pub failed: usize,
+    pub read: StageStats,
+    pub parse: StageStats,
+    pub validate: StageStats,
+}
+
+/// Every decoded document encoded in one format and parsed back
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConversionBench {
+    pub format: SidecarFormat,
+    /// Encoded size over the size the same documents have on disk
+    pub size_ratio: f64,
+    /// Documents this format cannot encode
+    pub failed: usize,
+    pub parse: StageStats,
+}
+
+/// Results of a benchmark over a directory
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BenchReport {
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub directory: PathBuf,
+    /// Sidecars read, after sampling
+    pub sidecars: usize,
+    /// Formats found on disk, in `SidecarFormat` order
+    pub formats: Vec<FormatBench>,
+    /// Formats compared, in the order asked for
+    pub conversions: Vec<ConversionBench>,
+}
+
+#[derive(Default)]
+struct Samples {
+    seconds: Vec<f64>,
+    bytes: u64,
+}
+
+impl Samples {
+    fn push(&mut self, seconds: f64, bytes: usize) {
+        self.seconds.push(seconds);
+        self.bytes += bytes as u64;
+    }
+
+    fn stats(mut self) -> StageStats {
+        self.seconds.sort_by(f64::total_cmp);
+        let total: f64 = self.seconds.iter().sum();
+        let rate = |amount: f64| if total > 0.0 { amount / total } else { 0.0 };
+        let percentile = |p: f64| {
+            let rank = (p / 100.0 * self.seconds.len() as f64).ceil() as usize;
+            self.seconds.get(rank.max(1) - 1).map_or(0.0, |seconds| seconds * 1000.0)
+        };
+        StageStats {
+            files: self.seconds.len(),
+            bytes: self.bytes,
+            seconds: total,
+            files_per_sec: rate(self.seconds.len() as f64),
+            mb_per_sec: rate(self.bytes as f64 / MB),
+            p50_ms: percentile(50.0),
+            p90_ms: percentile(90.0),
+            p99_ms: percentile(99.0),
+            max_ms: percentile(100.0),
+        }
+    }
+}
+
+#[derive(Default)]
+struct FormatSamples {
+    files: usize,
+    failed: usize,
+    read: Samples,
+    parse: Samples,
+    validate: Samples,
+}
+
+/// Time every stage over `files`, one file at a time so latencies are not skewed by contention
+///
+/// `validate` runs the checks validation does on a decoded document. Reads go through the
+/// page cache, so a second run measures warm reads. This is blocking.
+pub fn run<F, T>(directory: PathBuf, files: &[PathBuf], format_manager: &FormatManager, options: &BenchOptions, validate: F) -> BenchReport
+where
+    F: Fn(&Path, &Value) -> T,
+{
+    let mut stored: BTreeMap<u8, (SidecarFormat, FormatSamples)> = BTreeMap::new();
+    // Parse timings, documents that failed, and the on-disk size of those that did not
+    let mut converted: Vec<(Samples, usize, u64)> = options.formats.iter().map(|_| Default::default()).collect();
+
+    for path in files {
+        let started = Instant::now();
+        let bytes = std::fs::read(path);
+        let read_seconds = started.elapsed().as_secs_f64();
+        let Ok(bytes) = bytes else {
+            let format = SidecarFormat::from_path(path).unwrap_or(SidecarFormat::Json);
+            let samples = &mut stored.entry(format_order(format)).or_insert_with(|| (format, Default::default())).1;
+            samples.files += 1;
+            samples.failed += 1;
+            continue;
+        };
+        let format = SidecarFormat::from_path(path)
+            .or_else(|| format_manager.detect_format_from_content(&bytes).ok())
+            .unwrap_or(SidecarFormat::Json);
+        let samples = &mut stored.entry(format_order(format)).or_insert_with(|| (format, Default::default())).1;
+        samples.files += 1;
+        samples.read.push(read_seconds, bytes.len());
+
+        let started = Instant::now();
+        let data = format_manager.get_serializer(format).deserialize(&bytes);
+        let parse_seconds = started.elapsed().as_secs_f64();
+        let Ok(data) = data else {
+            samples.failed += 1;
+            continue;
+        };
+        samples.parse.push(parse_seconds, bytes.len());
+
+        let started = Instant::now();
+        std::hint::black_box(validate(path, &data));
+        samples.validate.push(started.elapsed().as_secs_f64(), bytes.len());
+
+        for (target, (samples, failed, source_bytes)) in options.formats.iter().zip(converted.iter_mut()) {
+            let serializer = format_manager.get_serializer(*target);
+            let Ok(encoded) = serializer.serialize(&data) else {
+                *failed += 1;
+                continue;
+            };
+            let started = Instant::now();
+            let parsed = serializer.deserialize(&encoded);
+            let seconds = started.elapsed().as_secs_f64();
+            match parsed {
+                Ok(parsed) => {
+                    std::hint::black_box(parsed);
+                    samples.push(seconds, encoded.len());
+                    *source_bytes += bytes.len() as u64;
+                }
+                Err(_) => *failed += 1,
+            }
+        }
+    }
+
+    let formats = stored
+        .into_values()
+        .map(|(format, samples)| FormatBench {
+            format,
+            files: samples.files,
+            failed: samples.failed,
+            read: samples.read.stats(),
+            parse: samples.parse.stats(),
+            validate: samples.validate.stats(),
+        })
+        .collect();
+    let conversions = options
+        .formats
+        .iter()
+        .zip(converted)
+        .map(|(format, (samples, failed, source_bytes))| {
+            let size_ratio = if source_bytes > 0 { samples.bytes as f64 / source_bytes as f64 } else { 0.0 };
+            ConversionBench { format: *format, size_ratio, failed, parse: samples.stats() }
+        })
+        .collect();
+    BenchReport { directory, sidecars: files.len(), formats, conversions }
+}
+
+fn format_order(format: SidecarFormat) -> u8 {
+    match format {
+        SidecarFormat::Json => 0,
+        SidecarFormat::Binary => 1,
+        SidecarFormat::Rkyv => 2,
+    }
+}
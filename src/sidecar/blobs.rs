@@ -0,0 +1,156 @@
+/*
+ * Context: Content-addressed store for operation payloads shared by many sidecars
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: sha2, serde_json
+ */
+
+use crate::sidecar::formats::to_canonical_json;
+use crate::sidecar::manifest::checksum;
+use crate::sidecar::types::{Result, SidecarError};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Key of a payload stored in a `BlobStore`: the sidecar holds `{"$blob": "<sha256>"}`
+pub const BLOB_POINTER_KEY: &str = "$blob";
+
+/// Extension of stored blobs; not a sidecar extension, so a store inside a scanned tree is ignored
+pub const BLOB_EXTENSION: &str = "blob";
+
+/// Directory of payloads keyed by the SHA-256 of their canonical JSON
+///
+/// Identical payloads, such as the empty result of a detector that found nothing, are
+/// stored once however many sidecars refer to them. Blobs live at `<root>/<ab>/<hash>.blob`.
+#[derive(Debug, Clone)]
+pub struct BlobStore {
+    root: PathBuf,
+}
+
+impl BlobStore {
+    /// Use `root` as the store, creating it if needed
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self> {
+        let root = root.into();
+        std::fs::create_dir_all(&root)?;
+        Ok(Self { root })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn path_for(&self, hash: &str) -> PathBuf {
+        self.root.join(hash.get(..2).unwrap_or(hash)).join(format!("{}.{}", hash, BLOB_EXTENSION))
+    }
+
+    pub fn contains(&self, hash: &str) -> bool {
+        self.path_for(hash).exists()
+    }
+
+    /// Store `payload` unless an identical one is already present, returning its hash
+    pub fn put(&self, payload: &Value) -> Result<String> {
+        let bytes = to_canonical_json(payload).into_bytes();
+        let hash = checksum(&bytes);
+        let path = self.path_for(&hash);
+        if !path.exists() {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            // Written beside the blob and renamed, so readers never see a partial one
+            let temp_path = path.with_extension(format!("{}.{}.tmp", BLOB_EXTENSION, std::process::id()));
+            std::fs::write(&temp_path, &bytes)?;
+            std::fs::rename(&temp_path, &path)?;
+        }
+        Ok(hash)
+    }
+
+    /// Read the blob `hash`, checking that its contents still match the hash
+    pub fn get(&self, hash: &str) -> Result<Value> {
+        let path = self.path_for(hash);
+        let bytes = std::fs::read(&path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => SidecarError::Storage(format!("Blob {} is missing from {}", hash, self.root.display())),
+            _ => e.into(),
+        })?;
+        if checksum(&bytes) != hash {
+            return Err(SidecarError::Storage(format!("Blob {} is corrupt: its contents no longer match the hash", hash)));
+        }
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    /// Hashes of every blob in the store, sorted
+    pub fn hashes(&self) -> Result<Vec<String>> {
+        let mut hashes = Vec::new();
+        for shard in std::fs::read_dir(&self.root)? {
+            let shard = shard?.path();
+            if !shard.is_dir() {
+                continue;
+            }
+            for entry in std::fs::read_dir(&shard)? {
+                let path = entry?.path();
+                if path.extension().and_then(|ext| ext.to_str()) == Some(BLOB_EXTENSION) {
+                    if let Some(hash) = path.file_stem().and_then(|stem| stem.to_str()) {
+                        hashes.push(hash.to_string());
+                    }
+                }
+            }
+        }
+        hashes.sort();
+        Ok(hashes)
+    }
+}
+
+/// The pointer stored in a sidecar in place of a payload
+pub fn pointer(hash: &str) -> Value {
+    serde_json::json!({ BLOB_POINTER_KEY: hash })
+}
+
+/// The blob hash `payload` points to, if it is a pointer
+pub fn pointer_hash(payload: &Value) -> Option<&str> {
+    match payload.as_object() {
+        Some(object) if object.len() == 1 => object.get(BLOB_POINTER_KEY).and_then(Value::as_str),
+        _ => None,
+    }
+}
+
+/// Fill in every payload of the decoded sidecar at `sidecar_path` that points into `store`
+pub fn resolve_pointers(store: &BlobStore, sidecar_path: &Path, data: &mut Value) -> Result<()> {
+    let Some(object) = data.as_object_mut() else { return Ok(()) };
+    for (key, payload) in object.iter_mut().filter(|(key, _)| *key != "sidecar_info") {
+        if let Some(hash) = pointer_hash(payload) {
+            let resolved = store.get(hash).map_err(|e| SidecarError::Storage(format!("{} in {:?}: {}", key, sidecar_path, e)))?;
+            *payload = resolved;
+        }
+    }
+    Ok(())
+}
+
+/// A pointer whose blob could not be read
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobIssue {
+    pub sidecar_path: PathBuf,
+    pub operation: String,
+    pub hash: String,
+    pub error: String,
+}
+
+/// Result of checking every pointer under a directory against a blob store
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobReport {
+    /// Sidecars holding at least one pointer
+    pub sidecars: usize,
+    pub pointers: usize,
+    /// Distinct blobs referenced
+    pub referenced: usize,
+    /// Pointers whose blob is missing or corrupt
+    pub issues: Vec<BlobIssue>,
+    /// Blobs in the store that no sidecar under the directory refers to; the store may be
+    /// shared with other directories, so these are reported rather than removed
+    pub unreferenced: Vec<String>,
+}
+
+impl BlobReport {
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
@@ -0,0 +1,103 @@
+/*
+ * Context: On-disk record of finished sidecars so interrupted runs can resume
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: std
+ */
+
+use crate::sidecar::types::Result;
+use crate::utils::paths;
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Name of the checkpoint a directory conversion keeps in that directory
+pub const CHECKPOINT_FILE: &str = ".sidecar-convert.checkpoint";
+
+/// Append-only list of sidecar paths a long run has finished, one per line
+///
+/// Each entry is written as soon as its file is done, so a crash loses at most the file in
+/// progress. Paths are kept with their directory canonicalized, so a run resumed from another
+/// working directory or through another spelling of the input still matches them. Remove the
+/// checkpoint with `finish` once the run has completed.
+#[derive(Debug)]
+pub struct Checkpoint {
+    path: PathBuf,
+    done: HashSet<PathBuf>,
+    log: File,
+}
+
+impl Checkpoint {
+    /// Open the checkpoint at `path`, creating it or loading the entries of an interrupted run
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let mut log = OpenOptions::new().read(true).append(true).create(true).open(&path)?;
+
+        // Entries are raw file names, which need not be UTF-8
+        let mut contents = Vec::new();
+        log.read_to_end(&mut contents)?;
+        // A crash mid-write leaves a partial last line; drop it so the next entry starts cleanly
+        let complete = contents.iter().rposition(|&byte| byte == b'\n').map_or(0, |newline| newline + 1);
+        if complete < contents.len() {
+            log.set_len(complete as u64)?;
+        }
+        let done = contents[..complete]
+            .split(|&byte| byte == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| canonical(&paths::path_from_bytes(line)))
+            .collect();
+
+        Ok(Self { path, done, log })
+    }
+
+    /// Open the conversion checkpoint kept in `directory`
+    pub fn for_directory(directory: &Path) -> Result<Self> {
+        Self::open(directory.join(CHECKPOINT_FILE))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of finished entries, including those of earlier runs
+    pub fn len(&self) -> usize {
+        self.done.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.done.is_empty()
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.done.contains(&canonical(path))
+    }
+
+    /// Mark `path` finished
+    pub fn record(&mut self, path: &Path) -> Result<()> {
+        let path = canonical(path);
+        if !self.done.contains(&path) {
+            paths::write_path_line(&mut self.log, &path)?;
+            self.done.insert(path);
+        }
+        Ok(())
+    }
+
+    /// Delete the checkpoint after a completed run
+    pub fn finish(self) -> Result<()> {
+        drop(self.log);
+        std::fs::remove_file(&self.path)?;
+        Ok(())
+    }
+}
+
+/// `path` with its directory canonicalized; the file itself may already be gone, such as a
+/// source removed by its conversion
+fn canonical(path: &Path) -> PathBuf {
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        return path.to_path_buf();
+    };
+    let parent = if parent.as_os_str().is_empty() { Path::new(".") } else { parent };
+    parent.canonicalize().map(|parent| parent.join(name)).unwrap_or_else(|_| path.to_path_buf())
+}
@@ -0,0 +1,148 @@
+/*
+ * Context: Rule-based classification of sidecar payloads into operation types
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: regex, serde_json
+ *
+ * Sidecars that record their operation in `sidecar_info` need no classifier. Raw detector
+ * output does, and a rule can recognise it by a top-level key, by a JSON pointer that
+ * resolves, or by a regex on the tool name the output reports.
+ */
+
+use crate::sidecar::skim::SkimKeys;
+use crate::sidecar::types::{OperationType, Result, SidecarError};
+use regex::Regex;
+use serde_json::Value;
+
+/// What a rule looks for in a decoded sidecar
+#[derive(Debug, Clone)]
+pub enum RuleCondition {
+    /// A top-level key, e.g. `Face_detector`
+    Key(String),
+    /// A JSON pointer that resolves, e.g. `/result/detections`
+    Pointer(String),
+    /// A regex matching the reported tool name
+    ToolName(Regex),
+}
+
+/// One classifier rule: sidecars meeting `condition` hold `operation`
+#[derive(Debug, Clone)]
+pub struct ClassifierRule {
+    pub condition: RuleCondition,
+    pub operation: OperationType,
+}
+
+impl ClassifierRule {
+    /// Sidecars with the top-level key `key`
+    pub fn key(key: impl Into<String>, operation: OperationType) -> Self {
+        Self { condition: RuleCondition::Key(key.into()), operation }
+    }
+
+    /// Sidecars in which the JSON pointer `pointer` (`/a/0/b`) resolves
+    pub fn pointer(pointer: &str, operation: OperationType) -> Result<Self> {
+        if !pointer.starts_with('/') {
+            return Err(SidecarError::ProcessingError(format!("Invalid JSON pointer '{}': must start with '/'", pointer)));
+        }
+        Ok(Self { condition: RuleCondition::Pointer(pointer.to_string()), operation })
+    }
+
+    /// Sidecars whose tool name matches the regex `pattern`
+    pub fn tool_name(pattern: &str, operation: OperationType) -> Result<Self> {
+        let regex = Regex::new(pattern)
+            .map_err(|e| SidecarError::ProcessingError(format!("Invalid tool name pattern '{}': {}", pattern, e)))?;
+        Ok(Self { condition: RuleCondition::ToolName(regex), operation })
+    }
+
+    pub fn matches(&self, data: &Value) -> bool {
+        match &self.condition {
+            RuleCondition::Key(key) => data.get(key).is_some(),
+            RuleCondition::Pointer(pointer) => data.pointer(pointer).is_some(),
+            RuleCondition::ToolName(regex) => tool_name(data).is_some_and(|name| regex.is_match(name)),
+        }
+    }
+}
+
+/// The tool name a payload reports under `tool_name`, `detector`, `model` or `algorithm`,
+/// at the top level or nested under `data`, `result` or `metadata`
+pub fn tool_name(data: &Value) -> Option<&str> {
+    ["tool_name", "detector", "model", "algorithm"]
+        .iter()
+        .find_map(|key| data.get(key).and_then(Value::as_str))
+        .or_else(|| ["data", "result", "metadata"].iter().find_map(|key| data.get(key).and_then(tool_name)))
+}
+
+/// Rules deciding the operation of sidecars that do not record one
+///
+/// Rules added with `rule` are tried in the order added, before the built-in detector keys,
+/// so a custom rule can also override how a built-in key is classified.
+#[derive(Debug, Clone)]
+pub struct OperationClassifier {
+    rules: Vec<ClassifierRule>,
+    custom: usize,
+}
+
+impl Default for OperationClassifier {
+    /// The detector keys written by the sportball tools
+    fn default() -> Self {
+        let rules = [
+            ("Face_detector", OperationType::FaceDetection),
+            ("Object_detector", OperationType::ObjectDetection),
+            ("Ball_detector", OperationType::BallDetection),
+            ("Quality_assessor", OperationType::QualityAssessment),
+            ("Game_detector", OperationType::GameDetection),
+            ("yolov8", OperationType::Yolov8),
+            ("Object_tracker", OperationType::Tracking),
+        ];
+        Self { rules: rules.into_iter().map(|(key, operation)| ClassifierRule::key(key, operation)).collect(), custom: 0 }
+    }
+}
+
+impl OperationClassifier {
+    /// A classifier without any rules, not even the built-in keys
+    pub fn empty() -> Self {
+        Self { rules: Vec::new(), custom: 0 }
+    }
+
+    /// Try `rule` after the rules added so far and before the built-in ones
+    pub fn rule(mut self, rule: ClassifierRule) -> Self {
+        self.push(rule);
+        self
+    }
+
+    pub fn push(&mut self, rule: ClassifierRule) {
+        self.rules.insert(self.custom, rule);
+        self.custom += 1;
+    }
+
+    pub fn rules(&self) -> &[ClassifierRule] {
+        &self.rules
+    }
+
+    /// The operation of the first rule `data` meets
+    pub fn classify(&self, data: &Value) -> Option<OperationType> {
+        self.rules.iter().find(|rule| rule.matches(data)).map(|rule| rule.operation.clone())
+    }
+
+    /// Keys an outline of a sidecar must keep whole for `classify` to match as on the document
+    ///
+    /// A pointer rule can resolve to a number inside an array, which outlines drop, so the
+    /// first key of every pointer is kept.
+    pub fn skim_keys(&self) -> SkimKeys {
+        self.rules
+            .iter()
+            .filter_map(|rule| match &rule.condition {
+                RuleCondition::Pointer(pointer) => pointer.split('/').nth(1).map(|token| token.replace("~1", "/").replace("~0", "~")),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The operation a key rule gives the top-level key `key`
+    pub fn operation_for_key(&self, key: &str) -> Option<&OperationType> {
+        self.rules.iter().find_map(|rule| match &rule.condition {
+            RuleCondition::Key(rule_key) if rule_key == key => Some(&rule.operation),
+            _ => None,
+        })
+    }
+}
@@ -0,0 +1,91 @@
+/*
+ * Context: Per-operation coverage of a directory's images, with lists of images still missing each operation
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde
+ */
+
+use crate::sidecar::types::{Result, SidecarError};
+use crate::utils::paths;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Extension of the missing-image lists written by `CoverageReport::write_missing_lists`
+pub const MISSING_LIST_EXTENSION: &str = "missing.txt";
+
+/// How many images one operation has been run on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OperationCoverage {
+    pub operation: String,
+    pub covered: usize,
+    pub coverage_percentage: f64,
+    /// Images with no sidecar holding this operation, in the order the images were given
+    #[serde(with = "crate::utils::paths::lossless_vec")]
+    pub missing: Vec<PathBuf>,
+}
+
+/// Coverage of every requested operation across the images of a directory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoverageReport {
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub directory: PathBuf,
+    pub total_images: usize,
+    /// In the order the operations were requested
+    pub operations: Vec<OperationCoverage>,
+}
+
+impl CoverageReport {
+    /// Build the report from every image and the operations recorded for it
+    pub fn compute(directory: PathBuf, images: &[PathBuf], recorded: &HashMap<PathBuf, HashSet<String>>, operations: &[String]) -> Self {
+        let operations = operations
+            .iter()
+            .map(|operation| {
+                let missing: Vec<PathBuf> = images
+                    .iter()
+                    .filter(|image| !recorded.get(*image).is_some_and(|done| done.contains(operation)))
+                    .cloned()
+                    .collect();
+                let covered = images.len() - missing.len();
+                let coverage_percentage = if images.is_empty() { 0.0 } else { covered as f64 / images.len() as f64 * 100.0 };
+                OperationCoverage { operation: operation.clone(), covered, coverage_percentage, missing }
+            })
+            .collect();
+        Self { directory, total_images: images.len(), operations }
+    }
+
+    /// Write `<operation>.missing.txt` into `directory` for every operation, one image path per line
+    ///
+    /// Lists are written even when empty, so a job queue reading them never sees a stale list.
+    pub fn write_missing_lists(&self, directory: &Path) -> Result<Vec<PathBuf>> {
+        for coverage in &self.operations {
+            check_operation_name(&coverage.operation)?;
+        }
+        std::fs::create_dir_all(directory)?;
+        let mut written = Vec::new();
+        for coverage in &self.operations {
+            let path = directory.join(format!("{}.{}", coverage.operation, MISSING_LIST_EXTENSION));
+            // Through a temporary file, so a job reading the list never sees half of it
+            let mut temporary = path.as_os_str().to_owned();
+            temporary.push(".tmp");
+            let mut writer = std::io::BufWriter::new(std::fs::File::create(&temporary)?);
+            for image in &coverage.missing {
+                paths::write_path_line(&mut writer, image)?;
+            }
+            let file = writer.into_inner().map_err(|e| e.into_error())?;
+            file.sync_all()?;
+            std::fs::rename(&temporary, &path)?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+}
+
+/// Fail unless `operation` can be used as a file name inside an output directory
+pub(crate) fn check_operation_name(operation: &str) -> Result<()> {
+    if operation.is_empty() || operation.starts_with('.') || operation.contains(['/', '\\']) {
+        return Err(SidecarError::Schema(format!("Operation {:?} cannot name a file", operation)));
+    }
+    Ok(())
+}
@@ -0,0 +1,109 @@
+/*
+ * Context: Coverage and payload comparison between two copies of a dataset
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde_json
+ */
+
+use crate::utils::json::{JsonPatch, JsonUtils};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Operation payloads recorded for one image, keyed by operation name
+pub type OperationPayloads = BTreeMap<String, Value>;
+
+/// An operation present on both sides with a different payload
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OperationChange {
+    pub operation: String,
+    /// JSON Patch turning the left payload into the right one
+    pub patch: JsonPatch,
+}
+
+/// Operation-level differences for one image covered on both sides
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImageDiff {
+    /// Image path relative to each root, `/`-separated
+    pub image: String,
+    /// Operations only on the right
+    pub added_operations: Vec<String>,
+    /// Operations only on the left
+    pub removed_operations: Vec<String>,
+    pub changed_operations: Vec<OperationChange>,
+}
+
+impl ImageDiff {
+    fn is_empty(&self) -> bool {
+        self.added_operations.is_empty() && self.removed_operations.is_empty() && self.changed_operations.is_empty()
+    }
+}
+
+/// Differences between the sidecars of two directories, matched by relative image path
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DirectoryDiff {
+    pub left: PathBuf,
+    pub right: PathBuf,
+    /// Images with sidecar data only on the left
+    pub only_left: Vec<String>,
+    /// Images with sidecar data only on the right
+    pub only_right: Vec<String>,
+    /// Images covered on both sides whose operations differ
+    pub changed: Vec<ImageDiff>,
+    /// Images covered on both sides with identical operations
+    pub unchanged: usize,
+}
+
+impl DirectoryDiff {
+    /// Compare per-image operation payloads from two directories
+    pub fn compute(
+        left: PathBuf,
+        right: PathBuf,
+        mut left_images: BTreeMap<String, OperationPayloads>,
+        right_images: BTreeMap<String, OperationPayloads>,
+    ) -> Self {
+        let mut diff = DirectoryDiff { left, right, ..Default::default() };
+
+        for (image, right_operations) in right_images {
+            let Some(left_operations) = left_images.remove(&image) else {
+                diff.only_right.push(image);
+                continue;
+            };
+
+            let image_diff = diff_operations(image, left_operations, right_operations);
+            if image_diff.is_empty() {
+                diff.unchanged += 1;
+            } else {
+                diff.changed.push(image_diff);
+            }
+        }
+        diff.only_left = left_images.into_keys().collect();
+
+        diff
+    }
+
+    /// True when both directories hold the same sidecar data
+    pub fn is_empty(&self) -> bool {
+        self.only_left.is_empty() && self.only_right.is_empty() && self.changed.is_empty()
+    }
+}
+
+fn diff_operations(image: String, mut left: OperationPayloads, right: OperationPayloads) -> ImageDiff {
+    let mut image_diff = ImageDiff { image, ..Default::default() };
+
+    for (operation, right_payload) in right {
+        match left.remove(&operation) {
+            None => image_diff.added_operations.push(operation),
+            Some(left_payload) if left_payload == right_payload => {}
+            Some(left_payload) => {
+                let patch = JsonUtils::diff(&left_payload, &right_payload);
+                image_diff.changed_operations.push(OperationChange { operation, patch });
+            }
+        }
+    }
+    image_diff.removed_operations = left.into_keys().collect();
+
+    image_diff
+}
@@ -0,0 +1,254 @@
+/*
+ * Context: DVC-compatible md5 metadata for sidecar sets, and checks against DVC lock data
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: md-5, serde_yaml, serde_json
+ */
+
+use crate::sidecar::types::{Result, SidecarError};
+use md5::{Digest, Md5};
+use serde::{Deserialize, Serialize};
+use serde_yaml::Mapping;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Suffix DVC appends to the md5 of a directory listing
+pub const DVC_DIR_SUFFIX: &str = ".dir";
+
+/// Hash name DVC 3 records for plain md5 outputs
+pub const DVC_HASH_NAME: &str = "md5";
+
+/// Lowercase hex md5 of `bytes`
+pub fn md5_hex(bytes: &[u8]) -> String {
+    Md5::digest(bytes).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// One file in a DVC directory listing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DvcEntry {
+    pub md5: String,
+    /// Path relative to the directory, `/`-separated
+    pub relpath: String,
+}
+
+/// The sidecars under a directory as DVC sees a tracked directory
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DvcDirectory {
+    /// Sorted the way DVC sorts listings, by path component
+    pub entries: Vec<DvcEntry>,
+    /// Total size of the files in bytes
+    pub size: u64,
+}
+
+impl DvcDirectory {
+    pub fn new(mut entries: Vec<DvcEntry>, size: u64) -> Self {
+        entries.sort_by(|a, b| a.relpath.split('/').cmp(b.relpath.split('/')));
+        Self { entries, size }
+    }
+
+    /// The listing as DVC writes it to its cache, Python `json.dumps(..., sort_keys=True)`
+    pub fn listing(&self) -> String {
+        let items: Vec<String> = self
+            .entries
+            .iter()
+            .map(|entry| format!("{{\"md5\": {}, \"relpath\": {}}}", python_json_string(&entry.md5), python_json_string(&entry.relpath)))
+            .collect();
+        format!("[{}]", items.join(", "))
+    }
+
+    /// The directory hash DVC records, the md5 of `listing` followed by `.dir`
+    pub fn md5(&self) -> String {
+        format!("{}{}", md5_hex(self.listing().as_bytes()), DVC_DIR_SUFFIX)
+    }
+
+    /// The output entry of a `.dvc` file tracking this directory at `path`
+    pub fn output(&self, path: impl Into<String>) -> DvcOutput {
+        DvcOutput {
+            md5: self.md5(),
+            size: Some(self.size),
+            nfiles: Some(self.entries.len()),
+            hash: Some(DVC_HASH_NAME.to_string()),
+            path: path.into(),
+            extra: Mapping::new(),
+        }
+    }
+}
+
+/// A JSON string literal escaped like Python's `json.dumps` with its default `ensure_ascii`
+fn python_json_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for character in text.chars() {
+        match character {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '\u{08}' => quoted.push_str("\\b"),
+            '\u{0c}' => quoted.push_str("\\f"),
+            ' '..='~' => quoted.push(character),
+            other => {
+                let mut units = [0u16; 2];
+                for unit in other.encode_utf16(&mut units) {
+                    quoted.push_str(&format!("\\u{:04x}", unit));
+                }
+            }
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// One entry of `outs` in a `.dvc` file or a `dvc.lock` stage
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DvcOutput {
+    pub md5: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nfiles: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+    /// Relative to the directory holding the `.dvc` or lock file
+    pub path: String,
+    /// Keys this crate does not use, such as `cache`, `remote` or `desc`, kept as they were read
+    #[serde(flatten)]
+    pub extra: Mapping,
+}
+
+/// A `.dvc` file as written by `dvc add`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DvcFile {
+    pub outs: Vec<DvcOutput>,
+    /// Keys other than `outs`, such as `deps`, `meta` or `frozen`, kept as they were read
+    #[serde(flatten)]
+    pub extra: Mapping,
+}
+
+impl DvcFile {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        serde_yaml::from_str(&text).map_err(|e| SidecarError::Schema(format!("{} is not DVC metadata: {}", path.display(), e)))
+    }
+
+    /// Make `output` the only output, keeping the other keys and those of an earlier output at its path
+    pub fn replace_outputs(&mut self, mut output: DvcOutput) {
+        if let Some(earlier) = self.outs.drain(..).find(|earlier| earlier.path == output.path) {
+            for (key, value) in earlier.extra {
+                output.extra.entry(key).or_insert(value);
+            }
+        }
+        self.outs = vec![output];
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let text = serde_yaml::to_string(self).map_err(|e| SidecarError::SerializationError(e.to_string()))?;
+        std::fs::write(path, text)?;
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+struct DvcLock {
+    #[serde(default)]
+    stages: BTreeMap<String, DvcStage>,
+}
+
+#[derive(Deserialize)]
+struct DvcStage {
+    #[serde(default)]
+    outs: Vec<DvcOutput>,
+}
+
+/// The `.dvc` file `dvc add` would write for `directory`: a `<directory>.dvc` sibling
+pub fn default_dvc_file(directory: &Path) -> PathBuf {
+    let directory = directory.canonicalize().unwrap_or_else(|_| directory.to_path_buf());
+    let mut name = directory.file_name().map(|name| name.to_os_string()).unwrap_or_else(|| "sidecars".into());
+    name.push(".dvc");
+    directory.with_file_name(name)
+}
+
+/// Every output recorded in a `.dvc` file or in any stage of a `dvc.lock`
+pub fn read_outputs(path: &Path) -> Result<Vec<DvcOutput>> {
+    let text = std::fs::read_to_string(path)?;
+    let invalid = |e: serde_yaml::Error| SidecarError::Schema(format!("{} is not DVC metadata: {}", path.display(), e));
+    if path.file_name().is_some_and(|name| name == "dvc.lock") {
+        let lock: DvcLock = serde_yaml::from_str(&text).map_err(invalid)?;
+        Ok(lock.stages.into_values().flat_map(|stage| stage.outs).collect())
+    } else {
+        let file: DvcFile = serde_yaml::from_str(&text).map_err(invalid)?;
+        Ok(file.outs)
+    }
+}
+
+/// The listing of directory hash `md5` from the DVC cache of the repo holding `start`, if present
+///
+/// Both the DVC 3 layout (`.dvc/cache/files/md5/..`) and the older one (`.dvc/cache/..`) are searched.
+pub fn cached_listing(start: &Path, md5: &str) -> Option<Vec<DvcEntry>> {
+    if md5.len() < 3 {
+        return None;
+    }
+    let (prefix, rest) = md5.split_at(2);
+    for repo in start.ancestors() {
+        let cache = repo.join(".dvc").join("cache");
+        for candidate in [cache.join("files").join("md5").join(prefix).join(rest), cache.join(prefix).join(rest)] {
+            if let Ok(bytes) = std::fs::read(&candidate) {
+                return serde_json::from_slice(&bytes).ok();
+            }
+        }
+    }
+    None
+}
+
+/// How the sidecars under a directory compare to a DVC output
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DvcVerification {
+    /// The directory checked
+    pub path: PathBuf,
+    pub expected_md5: String,
+    pub actual_md5: String,
+    pub expected_nfiles: Option<usize>,
+    pub actual_nfiles: usize,
+    pub expected_size: Option<u64>,
+    pub actual_size: u64,
+    /// Whether the recorded listing was found in the DVC cache, which the file lists below need
+    pub listing_found: bool,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub modified: Vec<String>,
+}
+
+impl DvcVerification {
+    /// Compare `actual` with `output`, listing changed files when the recorded listing is known
+    pub fn compare(path: PathBuf, output: &DvcOutput, actual: &DvcDirectory, recorded: Option<Vec<DvcEntry>>) -> Self {
+        let mut verification = Self {
+            path,
+            expected_md5: output.md5.clone(),
+            actual_md5: actual.md5(),
+            expected_nfiles: output.nfiles,
+            actual_nfiles: actual.entries.len(),
+            expected_size: output.size,
+            actual_size: actual.size,
+            listing_found: recorded.is_some(),
+            ..Default::default()
+        };
+        if let Some(recorded) = recorded {
+            let mut recorded: BTreeMap<String, String> = recorded.into_iter().map(|entry| (entry.relpath, entry.md5)).collect();
+            for entry in &actual.entries {
+                match recorded.remove(&entry.relpath) {
+                    None => verification.added.push(entry.relpath.clone()),
+                    Some(md5) if md5 != entry.md5 => verification.modified.push(entry.relpath.clone()),
+                    Some(_) => {}
+                }
+            }
+            verification.removed = recorded.into_keys().collect();
+        }
+        verification
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.expected_md5 == self.actual_md5
+    }
+}
@@ -0,0 +1,129 @@
+/*
+ * Context: Image fingerprints recorded in sidecars, to tell when an image changed after its sidecar was written
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: xxhash-rust, chrono, serde
+ */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use xxhash_rust::xxh3::Xxh3;
+
+/// Key under `sidecar_info` holding the `ImageFingerprint` of the image as it was when last written
+pub const IMAGE_FINGERPRINT_KEY: &str = "image_fingerprint";
+
+/// Content hash, size and modification time of an image
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageFingerprint {
+    /// XXH3-64 of the file contents, as 16 hex digits
+    pub xxh3: String,
+    pub size: u64,
+    pub modified: Option<DateTime<Utc>>,
+}
+
+impl ImageFingerprint {
+    /// Hash the image at `path`; blocking, call it from `spawn_blocking` inside async code
+    pub fn of(path: &Path) -> std::io::Result<Self> {
+        let mut file = std::fs::File::open(path)?;
+        let metadata = file.metadata()?;
+        let mut hasher = Xxh3::new();
+        let mut buffer = vec![0u8; 1 << 16];
+        loop {
+            let read = file.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+        }
+        Ok(Self {
+            xxh3: format!("{:016x}", hasher.digest()),
+            size: metadata.len(),
+            modified: metadata.modified().ok().map(DateTime::<Utc>::from),
+        })
+    }
+
+    /// Fingerprint `path`, reusing `previous` without reading the file when its size and mtime still match
+    pub fn refresh(path: &Path, previous: Option<&Self>) -> std::io::Result<Self> {
+        match previous {
+            Some(previous) if previous.matches_metadata(path) => Ok(previous.clone()),
+            _ => Self::of(path),
+        }
+    }
+
+    /// The fingerprint recorded in a sidecar document, if any
+    pub fn from_sidecar(data: &Value) -> Option<Self> {
+        serde_json::from_value(data.get("sidecar_info")?.get(IMAGE_FINGERPRINT_KEY)?.clone()).ok()
+    }
+
+    /// Whether the size and mtime of `path` are still those recorded
+    fn matches_metadata(&self, path: &Path) -> bool {
+        std::fs::metadata(path).is_ok_and(|metadata| {
+            metadata.len() == self.size && metadata.modified().ok().map(DateTime::<Utc>::from) == self.modified
+        })
+    }
+
+    /// How `path` compares with this recorded fingerprint
+    ///
+    /// Unless `deep`, an image whose size and mtime still match is taken as unchanged without
+    /// reading it. A touched file whose contents hash the same is not stale.
+    pub fn check(&self, path: &Path, deep: bool) -> Staleness {
+        if !deep && self.matches_metadata(path) {
+            return Staleness::Fresh;
+        }
+        match Self::of(path) {
+            Ok(current) if current.xxh3 == self.xxh3 => Staleness::Fresh,
+            Ok(current) => Staleness::Changed(current),
+            Err(_) => Staleness::ImageMissing,
+        }
+    }
+}
+
+/// Outcome of comparing an image with the fingerprint its sidecar recorded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Staleness {
+    Fresh,
+    /// The contents differ; holds the image's current fingerprint
+    Changed(ImageFingerprint),
+    ImageMissing,
+}
+
+/// Why a sidecar is reported as stale
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StaleReason {
+    /// The image's contents changed after the sidecar was written
+    ImageChanged,
+    /// The image can no longer be read
+    ImageMissing,
+    /// The sidecar predates fingerprints, so a change cannot be ruled out
+    Unrecorded,
+}
+
+impl fmt::Display for StaleReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StaleReason::ImageChanged => write!(f, "image changed"),
+            StaleReason::ImageMissing => write!(f, "image missing"),
+            StaleReason::Unrecorded => write!(f, "no fingerprint recorded"),
+        }
+    }
+}
+
+/// A sidecar whose image may no longer be the one its payloads describe
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StaleSidecar {
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub sidecar_path: PathBuf,
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub image_path: PathBuf,
+    pub reason: StaleReason,
+    /// What the sidecar recorded, when it recorded anything
+    pub recorded: Option<ImageFingerprint>,
+    /// The image now, when it changed
+    pub current: Option<ImageFingerprint>,
+}
@@ -13,9 +13,10 @@
  * - Dependencies: serde, bincode, rkyv, bytecheck
  */
 
+use bincode::Options;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
-use anyhow::Result;
+use crate::sidecar::types::Result;
 use thiserror::Error;
 
 /// Supported sidecar file formats
@@ -49,6 +50,14 @@ impl SidecarFormat {
         }
     }
 
+    /// Parse a user-facing format name; accepts the extensions plus `binary`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "binary" => Some(SidecarFormat::Binary),
+            other => Self::from_extension(other),
+        }
+    }
+
     /// Detect format from file path
     pub fn from_path(path: &Path) -> Option<Self> {
         path.extension()
@@ -138,10 +147,7 @@ impl SidecarSerializer for BinarySerializer {
     }
 
     fn deserialize(&self, bytes: &[u8]) -> Result<serde_json::Value, SerializationError> {
-        // Deserialize the JSON string first, then parse it
-        let json_str: String = bincode::deserialize(bytes)?;
-        let value = serde_json::from_str(&json_str)?;
-        Ok(value)
+        decode_wrapped_json(bytes)
     }
 
     fn format(&self) -> SidecarFormat {
@@ -149,6 +155,24 @@ impl SidecarSerializer for BinarySerializer {
     }
 }
 
+/// Parse the JSON text wrapped by the binary formats
+fn decode_wrapped_json(bytes: &[u8]) -> Result<serde_json::Value, SerializationError> {
+    Ok(serde_json::from_str(wrapped_json(bytes)?)?)
+}
+
+/// The JSON text wrapped by the binary formats, borrowed from `bytes`
+///
+/// Sidecars come off disk and may be truncated or corrupt, so the length prefix is checked
+/// against the input before anything is read, and bytes left after the string are an error
+/// instead of being ignored.
+pub(crate) fn wrapped_json(bytes: &[u8]) -> Result<&str, SerializationError> {
+    Ok(bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .with_limit(bytes.len() as u64)
+        .reject_trailing_bytes()
+        .deserialize(bytes)?)
+}
+
 /// Rkyv serializer for zero-copy deserialization
 /// Note: Simplified implementation - rkyv support can be added later
 pub struct RkyvSerializer;
@@ -163,10 +187,7 @@ impl SidecarSerializer for RkyvSerializer {
     }
 
     fn deserialize(&self, bytes: &[u8]) -> Result<serde_json::Value, SerializationError> {
-        // Deserialize the JSON string, then parse it back to Value
-        let json_str: String = bincode::deserialize(bytes)?;
-        let value = serde_json::from_str(&json_str)?;
-        Ok(value)
+        decode_wrapped_json(bytes)
     }
 
     fn format(&self) -> SidecarFormat {
@@ -174,12 +195,125 @@ impl SidecarSerializer for RkyvSerializer {
     }
 }
 
+/// Serializer producing the same bytes for equal documents
+///
+/// Object keys are sorted at every level, JSON carries no whitespace and negative zero is
+/// written as `0.0`. The binary formats wrap that canonical JSON text, so their layout is
+/// fixed as well. Reads are the same as for the plain serializers.
+pub struct CanonicalSerializer {
+    format: SidecarFormat,
+    pretty: bool,
+}
+
+impl CanonicalSerializer {
+    pub fn new(format: SidecarFormat) -> Self {
+        Self { format, pretty: false }
+    }
+
+    /// Like `new`, but JSON is indented one key per line, see `to_canonical_json_pretty`
+    pub fn pretty(format: SidecarFormat) -> Self {
+        Self { format, pretty: true }
+    }
+}
+
+impl SidecarSerializer for CanonicalSerializer {
+    fn serialize(&self, data: &serde_json::Value) -> Result<Vec<u8>, SerializationError> {
+        match self.format {
+            SidecarFormat::Json if self.pretty => Ok(to_canonical_json_pretty(data).into_bytes()),
+            SidecarFormat::Json => Ok(to_canonical_json(data).into_bytes()),
+            SidecarFormat::Binary | SidecarFormat::Rkyv => Ok(bincode::serialize(&to_canonical_json(data))?),
+        }
+    }
+
+    fn deserialize(&self, bytes: &[u8]) -> Result<serde_json::Value, SerializationError> {
+        match self.format {
+            SidecarFormat::Json => JsonSerializer.deserialize(bytes),
+            SidecarFormat::Binary => BinarySerializer.deserialize(bytes),
+            SidecarFormat::Rkyv => RkyvSerializer.deserialize(bytes),
+        }
+    }
+
+    fn format(&self) -> SidecarFormat {
+        self.format
+    }
+}
+
+/// Compact JSON with object keys sorted at every level and negative zero written as `0.0`
+///
+/// Floats use the shortest representation that reads back to the same value, so equal
+/// documents always produce equal text.
+pub fn to_canonical_json(value: &serde_json::Value) -> String {
+    let mut text = String::new();
+    write_canonical(value, &mut text, None);
+    text
+}
+
+/// Canonical JSON indented by two spaces with one key or element per line, ending in a newline
+///
+/// Meant for sidecars kept in git, where a changed value shows up as a one-line diff.
+pub fn to_canonical_json_pretty(value: &serde_json::Value) -> String {
+    let mut text = String::new();
+    write_canonical(value, &mut text, Some(0));
+    text.push('\n');
+    text
+}
+
+/// Start a new line at `depth` when pretty printing
+fn write_break(text: &mut String, depth: Option<usize>) {
+    if let Some(depth) = depth {
+        text.push('\n');
+        text.push_str(&"  ".repeat(depth));
+    }
+}
+
+fn write_canonical(value: &serde_json::Value, text: &mut String, depth: Option<usize>) {
+    use serde_json::Value;
+
+    let inner = depth.map(|depth| depth + 1);
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            // Sorted explicitly, since serde_json keeps insertion order when `preserve_order` is enabled
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+            text.push('{');
+            for (index, (key, item)) in entries.into_iter().enumerate() {
+                if index > 0 {
+                    text.push(',');
+                }
+                write_break(text, inner);
+                text.push_str(&Value::from(key.as_str()).to_string());
+                text.push_str(if depth.is_some() { ": " } else { ":" });
+                write_canonical(item, text, inner);
+            }
+            write_break(text, depth);
+            text.push('}');
+        }
+        Value::Array(items) if !items.is_empty() => {
+            text.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    text.push(',');
+                }
+                write_break(text, inner);
+                write_canonical(item, text, inner);
+            }
+            write_break(text, depth);
+            text.push(']');
+        }
+        Value::Object(_) => text.push_str("{}"),
+        Value::Array(_) => text.push_str("[]"),
+        Value::Number(number) if number.is_f64() && number.as_f64() == Some(0.0) => text.push_str("0.0"),
+        other => text.push_str(&other.to_string()),
+    }
+}
 
 /// Format manager for handling different serialization formats
 pub struct FormatManager {
     json_serializer: JsonSerializer,
     binary_serializer: BinarySerializer,
     rkyv_serializer: RkyvSerializer,
+    /// Canonical JSON, binary and rkyv serializers, used for writes when set
+    canonical: Option<[CanonicalSerializer; 3]>,
 }
 
 impl FormatManager {
@@ -188,11 +322,43 @@ impl FormatManager {
             json_serializer: JsonSerializer,
             binary_serializer: BinarySerializer,
             rkyv_serializer: RkyvSerializer,
+            canonical: None,
         }
     }
 
+    /// Format manager whose serializers write canonical bytes, see `CanonicalSerializer`
+    pub fn canonical() -> Self {
+        Self {
+            canonical: Some([SidecarFormat::Json, SidecarFormat::Binary, SidecarFormat::Rkyv].map(CanonicalSerializer::new)),
+            ..Self::new()
+        }
+    }
+
+    /// Canonical format manager whose JSON is pretty printed, for sidecars kept in git
+    pub fn git_friendly() -> Self {
+        Self {
+            canonical: Some([
+                CanonicalSerializer::pretty(SidecarFormat::Json),
+                CanonicalSerializer::new(SidecarFormat::Binary),
+                CanonicalSerializer::new(SidecarFormat::Rkyv),
+            ]),
+            ..Self::new()
+        }
+    }
+
+    pub fn is_canonical(&self) -> bool {
+        self.canonical.is_some()
+    }
+
     /// Get serializer for a specific format
     pub fn get_serializer(&self, format: SidecarFormat) -> &dyn SidecarSerializer {
+        if let Some([json, binary, rkyv]) = &self.canonical {
+            return match format {
+                SidecarFormat::Json => json,
+                SidecarFormat::Binary => binary,
+                SidecarFormat::Rkyv => rkyv,
+            };
+        }
         match format {
             SidecarFormat::Json => &self.json_serializer,
             SidecarFormat::Binary => &self.binary_serializer,
@@ -207,16 +373,11 @@ impl FormatManager {
             return Ok(SidecarFormat::Json);
         }
 
-        // Try bincode
-        if let Ok(_) = bincode::deserialize::<serde_json::Value>(bytes) {
+        // Binary and rkyv files share a layout, so either reads as binary
+        if BinarySerializer.deserialize(bytes).is_ok() {
             return Ok(SidecarFormat::Binary);
         }
 
-        // Try rkyv
-        if let Ok(_) = bincode::deserialize::<serde_json::Value>(bytes) {
-            return Ok(SidecarFormat::Rkyv);
-        }
-
         Err(SerializationError::FormatDetectionFailed)
     }
 
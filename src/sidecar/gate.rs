@@ -0,0 +1,215 @@
+/*
+ * Context: Per-image quality gating on quality_assessment scores and detection presence
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde, serde_json
+ *
+ * An image passes the gate when its quality score reaches the minimum and one of its
+ * sidecars meets the detection filter. Passing images can be listed, or symlinked,
+ * hardlinked or copied into a destination that mirrors the input's layout.
+ */
+
+use crate::query::DetectionFilter;
+use crate::sidecar::types::{OperationType, Result, SidecarError, SidecarInfo};
+use crate::utils::paths;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Fields a quality payload may hold its overall score under, in order of preference
+const SCORE_KEYS: [&str; 5] = ["overall_score", "quality_score", "score", "overall", "quality"];
+
+/// What to do with the images that pass the gate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GateAction {
+    /// Only report them
+    List,
+    Symlink,
+    Hardlink,
+    Copy,
+}
+
+impl GateAction {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "list" => Some(GateAction::List),
+            "symlink" => Some(GateAction::Symlink),
+            "hardlink" => Some(GateAction::Hardlink),
+            "copy" => Some(GateAction::Copy),
+            _ => None,
+        }
+    }
+}
+
+/// The conditions an image must meet to pass
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QualityGate {
+    /// Lowest quality score that passes; images without a score fail when this is set
+    pub min_score: Option<f64>,
+    /// Bounds one of the image's sidecars must meet
+    pub detections: DetectionFilter,
+}
+
+impl QualityGate {
+    pub fn min_score(mut self, score: f64) -> Self {
+        self.min_score = Some(score);
+        self
+    }
+
+    pub fn detections(mut self, filter: DetectionFilter) -> Self {
+        self.detections = filter;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min_score.is_none() && self.detections.is_empty()
+    }
+
+    /// Decide every image from its decoded sidecars
+    pub fn evaluate(&self, directory: PathBuf, sidecars: Vec<(SidecarInfo, Value)>) -> GateReport {
+        let mut images: BTreeMap<PathBuf, Vec<(SidecarInfo, Value)>> = BTreeMap::new();
+        for (sidecar, data) in sidecars {
+            images.entry(sidecar.image_path.clone()).or_default().push((sidecar, data));
+        }
+        let mut report = GateReport { directory, ..Default::default() };
+        for (image_path, mut sidecars) in images {
+            sidecars.sort_by(|a, b| a.0.sidecar_path.cmp(&b.0.sidecar_path));
+            let score = sidecars.iter().find_map(|(sidecar, data)| quality_score(sidecar, data));
+            let detected = self.detections.is_empty()
+                || sidecars.iter().any(|(sidecar, data)| self.detections.matches(data, Some(sidecar.operation.as_str())));
+            match (self.min_score, score) {
+                (Some(_), None) => report.unscored += 1,
+                (Some(min), Some(score)) if score < min => report.failed += 1,
+                _ if !detected => report.failed += 1,
+                _ => report.passed.push(GatedImage { image_path, score }),
+            }
+        }
+        report
+    }
+}
+
+/// An image that passed the gate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GatedImage {
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub image_path: PathBuf,
+    pub score: Option<f64>,
+}
+
+/// Which images under a directory passed the gate
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GateReport {
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub directory: PathBuf,
+    /// Sorted by image path
+    pub passed: Vec<GatedImage>,
+    /// Images scored below the minimum or without the detections asked for
+    pub failed: usize,
+    /// Images failed for lack of a quality score
+    pub unscored: usize,
+}
+
+/// What applying a gate's action placed in the destination
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GateSummary {
+    #[serde(with = "crate::utils::paths::lossless_vec")]
+    pub placed: Vec<PathBuf>,
+    /// Destination paths already taken, left alone
+    #[serde(with = "crate::utils::paths::lossless_vec")]
+    pub skipped: Vec<PathBuf>,
+}
+
+impl GateReport {
+    /// Symlink, hardlink or copy every passing image into `destination`, at its path relative
+    /// to the gated directory; `List` places nothing
+    ///
+    /// Existing files are never replaced, so rerunning the gate only adds new passes.
+    /// Images reached outside the gated directory keep only their file name, so two of
+    /// them sharing a name, or one whose name is taken by a different file, is an error
+    /// raised before anything is placed. Symlinks point at the absolute image path.
+    /// This is blocking.
+    pub fn apply(&self, destination: &Path, action: GateAction) -> Result<GateSummary> {
+        let mut summary = GateSummary::default();
+        if action == GateAction::List {
+            return Ok(summary);
+        }
+
+        let mut targets: BTreeMap<PathBuf, &Path> = BTreeMap::new();
+        let mut placements = Vec::with_capacity(self.passed.len());
+        for image in &self.passed {
+            let source = std::path::absolute(&image.image_path)?;
+            let (target, outside) = match image.image_path.strip_prefix(&self.directory) {
+                Ok(relative) => (destination.join(relative), false),
+                Err(_) => (destination.join(image.image_path.file_name().unwrap_or_default()), true),
+            };
+            if let Some(other) = targets.insert(target.clone(), &image.image_path) {
+                return Err(SidecarError::ProcessingError(format!(
+                    "{} and {} would both be placed at {}",
+                    other.display(),
+                    image.image_path.display(),
+                    target.display()
+                )));
+            }
+            if outside && target.symlink_metadata().is_ok() && !already_placed(&source, &target) {
+                return Err(SidecarError::ProcessingError(format!(
+                    "Cannot place {} at {}: a different file is already there",
+                    source.display(),
+                    target.display()
+                )));
+            }
+            placements.push((source, target));
+        }
+
+        for (source, target) in placements {
+            if target.symlink_metadata().is_ok() {
+                summary.skipped.push(target);
+                continue;
+            }
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let placed = match action {
+                GateAction::Symlink => paths::symlink(&source, &target),
+                GateAction::Hardlink => std::fs::hard_link(&source, &target),
+                GateAction::Copy => std::fs::copy(&source, &target).map(|_| ()),
+                GateAction::List => unreachable!(),
+            };
+            placed.map_err(|e| {
+                SidecarError::ProcessingError(format!("Cannot place {} at {}: {}", source.display(), target.display(), e))
+            })?;
+            summary.placed.push(target);
+        }
+        Ok(summary)
+    }
+}
+
+/// Whether `target` is `source` placed by an earlier run: a symlink to it, or the same bytes
+fn already_placed(source: &Path, target: &Path) -> bool {
+    if let Ok(link) = std::fs::read_link(target) {
+        return link == source;
+    }
+    matches!((std::fs::read(source), std::fs::read(target)), (Ok(source), Ok(target)) if source == target)
+}
+
+/// Quality score a sidecar records, from its `quality_assessment` payload
+///
+/// The score is read from the payload or from an object nested one level below it, under
+/// `overall_score`, `quality_score`, `score`, `overall` or `quality`.
+pub fn quality_score(sidecar: &SidecarInfo, data: &Value) -> Option<f64> {
+    let payload = match data.get(OperationType::QualityAssessment.as_str()) {
+        Some(payload) => payload,
+        None if sidecar.operation == OperationType::QualityAssessment => sidecar.operation_payload(data),
+        None => return None,
+    };
+    let score = |object: &Value| SCORE_KEYS.iter().find_map(|key| object.get(*key).and_then(Value::as_f64));
+    score(payload).or_else(|| {
+        payload
+            .as_object()?
+            .iter()
+            .filter(|(key, _)| *key != "sidecar_info")
+            .find_map(|(_, nested)| score(nested))
+    })
+}
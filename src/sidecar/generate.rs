@@ -0,0 +1,316 @@
+/*
+ * Context: Synthetic images and sidecars for load tests and benchmarks
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: image, rayon, serde_json, chrono
+ *
+ * A dataset is a tree of small JPEG frames, each with one sidecar holding a payload per
+ * requested operation, shaped like the sportball detectors' output. Everything derives
+ * from a seed, so the same options always write the same bytes.
+ */
+
+use crate::sidecar::formats::{FormatManager, SidecarFormat};
+use crate::sidecar::types::{OperationType, Result, SidecarError};
+use crate::utils::paths;
+use chrono::{DateTime, TimeDelta, Utc};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::path::{Path, PathBuf};
+
+/// Object classes drawn for `object_detection` and `yolov8` payloads, with their COCO ids
+const CLASSES: [(&str, u32); 5] = [("person", 0), ("bicycle", 1), ("car", 2), ("dog", 16), ("sports ball", 32)];
+
+/// What a generated dataset holds
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenerateOptions {
+    pub count: usize,
+    pub operations: Vec<OperationType>,
+    pub format: SidecarFormat,
+    /// Detections per payload are drawn uniformly from this inclusive range
+    pub detections: (usize, usize),
+    /// Length of each face's `encoding` vector
+    pub encoding_dims: usize,
+    /// Width and height of the generated frames
+    pub image_size: (u32, u32),
+    /// Frames per directory; each directory is one sequence
+    pub per_directory: usize,
+    pub seed: u64,
+}
+
+impl Default for GenerateOptions {
+    fn default() -> Self {
+        Self {
+            count: 1000,
+            operations: vec![OperationType::FaceDetection, OperationType::Yolov8],
+            format: SidecarFormat::Binary,
+            detections: (0, 6),
+            encoding_dims: 128,
+            image_size: (320, 240),
+            per_directory: 1000,
+            seed: 0,
+        }
+    }
+}
+
+impl GenerateOptions {
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// Operations every sidecar holds; `image_hash`, `unified` and `unknown` cannot be generated
+    pub fn operations(mut self, operations: Vec<OperationType>) -> Result<Self> {
+        if let Some(operation) = operations.iter().find(|operation| !can_generate(operation)) {
+            return Err(SidecarError::ProcessingError(format!("Cannot generate {} payloads", operation.as_str())));
+        }
+        self.operations = operations;
+        Ok(self)
+    }
+
+    pub fn format(mut self, format: SidecarFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn detections(mut self, min: usize, max: usize) -> Result<Self> {
+        if min > max {
+            return Err(SidecarError::ProcessingError(format!("Detection range {}-{} is empty", min, max)));
+        }
+        self.detections = (min, max);
+        Ok(self)
+    }
+
+    pub fn encoding_dims(mut self, dims: usize) -> Self {
+        self.encoding_dims = dims;
+        self
+    }
+
+    pub fn image_size(mut self, width: u32, height: u32) -> Result<Self> {
+        if width == 0 || height == 0 {
+            return Err(SidecarError::ProcessingError(format!("Image size {}x{} is empty", width, height)));
+        }
+        self.image_size = (width, height);
+        Ok(self)
+    }
+
+    pub fn per_directory(mut self, per_directory: usize) -> Self {
+        self.per_directory = per_directory.max(1);
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+}
+
+fn can_generate(operation: &OperationType) -> bool {
+    !matches!(operation, OperationType::ImageHash | OperationType::Unified | OperationType::Unknown)
+}
+
+/// What generating a dataset wrote
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenerateSummary {
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub root: PathBuf,
+    pub images: usize,
+    pub sidecars: usize,
+    pub directories: usize,
+    pub detections: u64,
+    pub image_bytes: u64,
+    pub sidecar_bytes: u64,
+}
+
+/// Write `options.count` frames and their sidecars under `root`
+///
+/// Frames go to `game_000/frame_000000.jpg` and on, `per_directory` to a directory, each
+/// recorded one second after the previous one. Existing files are overwritten. This is
+/// blocking and runs on the rayon pool.
+pub fn generate(root: &Path, options: &GenerateOptions, format_manager: &FormatManager) -> Result<GenerateSummary> {
+    let directories = options.count.div_ceil(options.per_directory);
+    for directory in 0..directories {
+        std::fs::create_dir_all(root.join(directory_name(directory)))?;
+    }
+    let written: Vec<(u64, u64, u64)> = (0..options.count)
+        .into_par_iter()
+        .map(|index| {
+            let directory = index / options.per_directory;
+            let frame = index % options.per_directory;
+            let image_path = root.join(directory_name(directory)).join(format!("frame_{:06}.jpg", frame));
+            let mut rng = SplitMix64::new(options.seed ^ (index as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+
+            let image_bytes = write_image(&image_path, options.image_size, &mut rng)?;
+            let (document, detections) = sidecar_document(&image_path, directory, frame, options, &mut rng);
+            let bytes = format_manager.get_serializer(options.format).serialize(&document)?;
+            std::fs::write(image_path.with_extension(options.format.extension()), &bytes)?;
+            Ok((image_bytes, bytes.len() as u64, detections))
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(GenerateSummary {
+        root: root.to_path_buf(),
+        images: written.len(),
+        sidecars: written.len(),
+        directories,
+        detections: written.iter().map(|(_, _, detections)| detections).sum(),
+        image_bytes: written.iter().map(|(image, _, _)| image).sum(),
+        sidecar_bytes: written.iter().map(|(_, sidecar, _)| sidecar).sum(),
+    })
+}
+
+fn directory_name(directory: usize) -> String {
+    format!("game_{:03}", directory)
+}
+
+/// A gradient with a random tint and a few blocks, so frames differ and compress like photos
+fn write_image(path: &Path, (width, height): (u32, u32), rng: &mut SplitMix64) -> Result<u64> {
+    let tint = [rng.below(256) as u8, rng.below(256) as u8, rng.below(256) as u8];
+    let blocks: Vec<(u32, u32, u32, [u8; 3])> = (0..4)
+        .map(|_| (rng.below(width as u64) as u32, rng.below(height as u64) as u32, 8 + rng.below(32) as u32, [rng.below(256) as u8; 3]))
+        .collect();
+    let image = image::RgbImage::from_fn(width, height, |x, y| {
+        if let Some((_, _, _, color)) = blocks.iter().find(|(bx, by, size, _)| x >= *bx && x < bx + size && y >= *by && y < by + size) {
+            return image::Rgb(*color);
+        }
+        let shade = ((x * 255 / width.max(1) + y * 255 / height.max(1)) / 2) as u8;
+        image::Rgb(tint.map(|channel| channel / 2 + shade / 2))
+    });
+    image
+        .save_with_format(path, image::ImageFormat::Jpeg)
+        .map_err(|e| SidecarError::ProcessingError(format!("Cannot write {}: {}", path.display(), e)))?;
+    Ok(std::fs::metadata(path)?.len())
+}
+
+fn sidecar_document(image_path: &Path, directory: usize, frame: usize, options: &GenerateOptions, rng: &mut SplitMix64) -> (Value, u64) {
+    let base: DateTime<Utc> = "2024-12-19T10:00:00Z".parse().expect("valid base time");
+    let recorded = (base + TimeDelta::seconds(frame as i64)).to_rfc3339();
+    let last = options.operations.last().map_or("unknown", OperationType::as_str);
+    let mut document = Map::new();
+    document.insert("sidecar_info".to_string(), json!({
+        "created_at": recorded,
+        "last_updated": recorded,
+        "last_operation": last,
+        "image_path": paths::path_to_json(image_path),
+        "generated": true,
+    }));
+    let mut total = 0u64;
+    for operation in &options.operations {
+        let count = options.detections.0 + rng.below((options.detections.1 - options.detections.0 + 1) as u64) as usize;
+        let (payload, detections) = payload(operation, count, directory, frame, options, rng);
+        total += detections as u64;
+        document.insert(operation.as_str().to_string(), payload);
+    }
+    (Value::Object(document), total)
+}
+
+/// One operation's payload and the detections it holds
+fn payload(operation: &OperationType, count: usize, directory: usize, frame: usize, options: &GenerateOptions, rng: &mut SplitMix64) -> (Value, usize) {
+    let (width, height) = options.image_size;
+    let processing_time = round(0.02 + rng.unit() * 0.2);
+    let (w, h) = (f64::from(width), f64::from(height));
+    match operation {
+        OperationType::FaceDetection => {
+            let faces: Vec<Value> = (0..count)
+                .map(|face_id| {
+                    let (x, y, size) = (rng.unit() * 0.8, rng.unit() * 0.8, 0.05 + rng.unit() * 0.15);
+                    let landmarks: Vec<[f64; 2]> = (0..5).map(|_| [round(x + rng.unit() * size), round(y + rng.unit() * size)]).collect();
+                    let encoding: Vec<f64> = (0..options.encoding_dims).map(|_| round(rng.unit() * 2.0 - 1.0)).collect();
+                    json!({
+                        "face_id": face_id,
+                        "bbox": {"x": round(x), "y": round(y), "width": round(size), "height": round(size)},
+                        "confidence": round(0.5 + rng.unit() * 0.5),
+                        "landmarks": landmarks,
+                        "encoding": encoding,
+                    })
+                })
+                .collect();
+            let payload = json!({
+                "success": true,
+                "faces": faces,
+                "metadata": {"image_width": width, "image_height": height, "faces_found": count, "processing_time": processing_time},
+            });
+            (payload, count)
+        }
+        OperationType::ObjectDetection | OperationType::Yolov8 | OperationType::BallDetection => {
+            let detections: Vec<Value> = (0..count)
+                .map(|_| {
+                    let (class, class_id) = match operation {
+                        OperationType::BallDetection => CLASSES[4],
+                        _ => CLASSES[rng.below(CLASSES.len() as u64) as usize],
+                    };
+                    let (x1, y1) = (rng.unit() * w * 0.8, rng.unit() * h * 0.8);
+                    let (x2, y2) = (x1 + 4.0 + rng.unit() * w * 0.2, y1 + 4.0 + rng.unit() * h * 0.2);
+                    json!({
+                        "class_name": class,
+                        "class_id": class_id,
+                        "bbox": {"x1": round(x1), "y1": round(y1), "x2": round(x2), "y2": round(y2)},
+                        "confidence": round(0.25 + rng.unit() * 0.75),
+                    })
+                })
+                .collect();
+            let key = if *operation == OperationType::ObjectDetection { "objects" } else { "detections" };
+            (json!({"success": true, key: detections, "processing_time": processing_time}), count)
+        }
+        OperationType::Tracking => {
+            // Track ids are stable within a directory and their boxes drift from frame to frame
+            let tracks: Vec<Value> = (0..count)
+                .map(|track| {
+                    let mut start = SplitMix64::new(options.seed ^ ((directory * 1000 + track) as u64).wrapping_mul(0xD1B5_4A32_D192_ED03));
+                    let (x, y) = (start.unit() * w * 0.5, start.unit() * h * 0.5);
+                    let shift = (frame as f64 * 2.0) % (w * 0.4);
+                    json!({
+                        "track_id": track + 1,
+                        "class_name": "person",
+                        "bbox": {"x1": round(x + shift), "y1": round(y), "x2": round(x + shift + 30.0), "y2": round(y + 60.0)},
+                        "confidence": round(0.5 + rng.unit() * 0.5),
+                    })
+                })
+                .collect();
+            (json!({"tracker": "synthetic", "tracks": tracks, "processing_time": processing_time}), count)
+        }
+        OperationType::QualityAssessment => {
+            let (sharpness, exposure) = (rng.unit(), rng.unit());
+            let payload = json!({
+                "overall_score": round((sharpness + exposure) / 2.0),
+                "sharpness": round(sharpness),
+                "exposure": round(exposure),
+                "processing_time": processing_time,
+            });
+            (payload, 0)
+        }
+        _ => (json!({"game": directory_name(directory), "confidence": round(0.5 + rng.unit() * 0.5), "processing_time": processing_time}), 0),
+    }
+}
+
+fn round(value: f64) -> f64 {
+    (value * 10_000.0).round() / 10_000.0
+}
+
+/// splitmix64: small, fast and good enough for fake data
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`
+    fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform in `[0, bound)`; `bound` must not be zero
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+}
@@ -0,0 +1,166 @@
+/*
+ * Context: Comparing working-tree sidecars against a git ref, operation by operation
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: git on the PATH, serde_json
+ */
+
+use crate::sidecar::types::{Result, SidecarError};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Command, Stdio};
+
+/// How a sidecar differs from the version at the git ref
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileChange {
+    Added,
+    Modified,
+    Deleted,
+}
+
+impl FileChange {
+    /// One-letter code as shown by `git status --short`
+    pub fn code(&self) -> char {
+        match self {
+            FileChange::Added => 'A',
+            FileChange::Modified => 'M',
+            FileChange::Deleted => 'D',
+        }
+    }
+}
+
+/// One sidecar whose operations differ from the git ref
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitFileStatus {
+    /// Path relative to the compared directory, `/`-separated
+    pub path: String,
+    pub change: FileChange,
+    pub added_operations: Vec<String>,
+    pub removed_operations: Vec<String>,
+    pub modified_operations: Vec<String>,
+}
+
+impl GitFileStatus {
+    /// Compare the payloads of two versions of a sidecar; `None` when no operation changed
+    ///
+    /// `sidecar_info` is ignored, since every write refreshes its timestamps.
+    pub fn compare(path: String, old: Option<&Value>, new: Option<&Value>) -> Option<Self> {
+        let change = match (old, new) {
+            (None, None) => return None,
+            (None, Some(_)) => FileChange::Added,
+            (Some(_), None) => FileChange::Deleted,
+            (Some(_), Some(_)) => FileChange::Modified,
+        };
+        let payloads = |data: Option<&Value>| -> BTreeMap<String, Value> {
+            data.and_then(Value::as_object)
+                .map(|object| object.iter().filter(|(key, _)| *key != "sidecar_info").map(|(key, payload)| (key.clone(), payload.clone())).collect())
+                .unwrap_or_default()
+        };
+        let (old, new) = (payloads(old), payloads(new));
+        let status = Self {
+            path,
+            change,
+            added_operations: new.keys().filter(|key| !old.contains_key(*key)).cloned().collect(),
+            removed_operations: old.keys().filter(|key| !new.contains_key(*key)).cloned().collect(),
+            modified_operations: new.iter().filter(|(key, payload)| old.get(*key).is_some_and(|before| before != *payload)).map(|(key, _)| key.clone()).collect(),
+        };
+        let changed = !status.added_operations.is_empty() || !status.removed_operations.is_empty() || !status.modified_operations.is_empty();
+        (changed || change != FileChange::Modified).then_some(status)
+    }
+}
+
+/// Sidecars under a directory that differ from a git ref
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitStatus {
+    pub git_ref: String,
+    /// Sorted by path
+    pub files: Vec<GitFileStatus>,
+}
+
+/// Run git in `directory`, returning its standard output
+pub fn git(directory: &Path, args: &[&str]) -> Result<Vec<u8>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(directory)
+        .args(args)
+        .output()
+        .map_err(|e| SidecarError::ProcessingError(format!("Cannot run git: {}", e)))?;
+    if !output.status.success() {
+        return Err(SidecarError::ProcessingError(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+/// Blobs under `directory` at `git_ref` as (path relative to `directory`, object id)
+pub fn tracked_files(directory: &Path, git_ref: &str) -> Result<Vec<(String, String)>> {
+    let listing = git(directory, &["ls-tree", "-r", "-z", git_ref, "--", "."])?;
+    Ok(listing
+        .split(|byte| *byte == 0)
+        .filter_map(|entry| {
+            // `<mode> <type> <object>\t<path>`; submodules are `commit` entries
+            let entry = String::from_utf8_lossy(entry);
+            let (meta, path) = entry.split_once('\t')?;
+            let mut fields = meta.split(' ');
+            let (_, kind, object) = (fields.next()?, fields.next()?, fields.next()?);
+            (kind == "blob").then(|| (path.to_string(), object.to_string()))
+        })
+        .collect())
+}
+
+/// Contents of each blob in `objects`, in order, read through a single `git cat-file --batch`
+pub fn blobs(directory: &Path, objects: &[String]) -> Result<Vec<Vec<u8>>> {
+    if objects.is_empty() {
+        return Ok(Vec::new());
+    }
+    let failed = |e: std::io::Error| SidecarError::ProcessingError(format!("Cannot run git cat-file: {}", e));
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(directory)
+        .args(["cat-file", "--batch"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(failed)?;
+
+    // Feed the requests from another thread so a full stdout pipe cannot stall the writer
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let requests: String = objects.iter().map(|object| format!("{}\n", object)).collect();
+    let writer = std::thread::spawn(move || stdin.write_all(requests.as_bytes()));
+
+    let mut stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+    let mut contents = Vec::with_capacity(objects.len());
+    for object in objects {
+        let mut header = String::new();
+        stdout.read_line(&mut header).map_err(failed)?;
+        let size = match header.trim_end().rsplit_once(' ') {
+            Some((_, size)) if !header.ends_with(" missing\n") => size.parse::<usize>().ok(),
+            _ => None,
+        }
+        .ok_or_else(|| SidecarError::ProcessingError(format!("git cat-file cannot read {}: {}", object, header.trim_end())))?;
+        let mut blob = vec![0; size + 1];
+        stdout.read_exact(&mut blob).map_err(failed)?;
+        blob.pop();
+        contents.push(blob);
+    }
+
+    writer.join().expect("git cat-file writer panicked").map_err(failed)?;
+    drop(stdout);
+    let output = child.wait_with_output().map_err(failed)?;
+    if !output.status.success() {
+        return Err(SidecarError::ProcessingError(format!(
+            "git cat-file --batch failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(contents)
+}
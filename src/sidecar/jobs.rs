@@ -0,0 +1,182 @@
+/*
+ * Context: Job descriptors for images an operation has not been run on, for external work queues
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde_json, chrono, xxhash-rust; ureq for the webhooks feature
+ */
+
+use crate::sidecar::coverage::{check_operation_name, OperationCoverage};
+use crate::sidecar::types::Result;
+#[cfg(feature = "webhooks")]
+use crate::sidecar::types::SidecarError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use xxhash_rust::xxh3::Xxh3;
+
+/// Version of the job descriptor layout, bumped on incompatible changes
+pub const JOB_SCHEMA_VERSION: u32 = 1;
+
+/// One batch of images to run an operation on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobSpec {
+    pub schema_version: u32,
+    /// `<operation>-<hash of the batch's images>`, so a regenerated job for the same images keeps its id
+    pub job_id: String,
+    pub operation: String,
+    /// 1-based position of this batch
+    pub batch: usize,
+    pub batches: usize,
+    /// Directory the images were found in
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub directory: PathBuf,
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "crate::utils::paths::lossless_vec")]
+    pub images: Vec<PathBuf>,
+}
+
+impl JobSpec {
+    /// Split the images `coverage` is missing into jobs of at most `batch_size` images
+    pub fn batches(directory: &Path, coverage: &OperationCoverage, batch_size: usize) -> Vec<Self> {
+        let batch_size = batch_size.max(1);
+        let batches = coverage.missing.len().div_ceil(batch_size);
+        let created_at = Utc::now();
+        coverage
+            .missing
+            .chunks(batch_size)
+            .enumerate()
+            .map(|(index, images)| Self {
+                schema_version: JOB_SCHEMA_VERSION,
+                job_id: job_id(&coverage.operation, images),
+                operation: coverage.operation.clone(),
+                batch: index + 1,
+                batches,
+                directory: directory.to_path_buf(),
+                created_at,
+                images: images.to_vec(),
+            })
+            .collect()
+    }
+}
+
+/// `<operation>-<xxh3>` over the batch's image paths in sorted order
+///
+/// The id only depends on which images are in the batch, so a consumer can tell a resubmitted
+/// job from a new one even after other batches finished and the numbering shifted.
+fn job_id(operation: &str, images: &[PathBuf]) -> String {
+    let mut sorted: Vec<&PathBuf> = images.iter().collect();
+    sorted.sort();
+    let mut hasher = Xxh3::new();
+    for image in sorted {
+        hasher.update(image.as_os_str().as_encoded_bytes());
+        hasher.update(b"\0");
+    }
+    format!("{}-{:016x}", operation, hasher.digest())
+}
+
+/// How `write_jobs` lays out job descriptors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobFormat {
+    /// One `<job_id>.json` file per job
+    Json,
+    /// One `<operation>.jobs.ndjson` file with a job per line
+    Ndjson,
+}
+
+impl JobFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "json" => Some(JobFormat::Json),
+            "ndjson" => Some(JobFormat::Ndjson),
+            _ => None,
+        }
+    }
+}
+
+/// Write `jobs`, all for `operation`, into `directory`, returning the files written
+///
+/// JSON descriptors left over from an earlier run whose batches are no longer outstanding are
+/// removed, so the directory always holds exactly the work still to do.
+pub fn write_jobs(jobs: &[JobSpec], operation: &str, directory: &Path, format: JobFormat) -> Result<Vec<PathBuf>> {
+    check_operation_name(operation)?;
+    std::fs::create_dir_all(directory)?;
+    match format {
+        JobFormat::Json => {
+            let prefix = format!("{}-", operation);
+            for entry in std::fs::read_dir(directory)? {
+                let path = entry?.path();
+                let stale = path.file_name().and_then(|name| name.to_str()).is_some_and(|name| {
+                    name.strip_prefix(&prefix)
+                        .and_then(|rest| rest.strip_suffix(".json"))
+                        .is_some_and(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_hexdigit()))
+                });
+                if stale {
+                    std::fs::remove_file(path)?;
+                }
+            }
+            jobs.iter()
+                .map(|job| {
+                    let path = directory.join(format!("{}.json", job.job_id));
+                    std::fs::write(&path, serde_json::to_string_pretty(job)? + "\n")?;
+                    Ok(path)
+                })
+                .collect()
+        }
+        JobFormat::Ndjson => {
+            let path = directory.join(format!("{}.jobs.ndjson", operation));
+            let mut writer = std::io::BufWriter::new(std::fs::File::create(&path)?);
+            for job in jobs {
+                serde_json::to_writer(&mut writer, job)?;
+                writer.write_all(b"\n")?;
+            }
+            writer.flush()?;
+            Ok(vec![path])
+        }
+    }
+}
+
+/// POST every job as JSON to `url`, stopping at the first job that cannot be delivered
+///
+/// Requests time out after 10 seconds. Connection errors, 429 and 5xx responses are retried
+/// up to 4 times, waiting 500 ms and doubling the wait each time; other non-2xx responses fail
+/// at once. Job ids are derived from the images, so a receiver can drop a job it already took.
+#[cfg(feature = "webhooks")]
+pub fn post_jobs(jobs: &[JobSpec], url: &str) -> Result<usize> {
+    const MAX_RETRIES: u32 = 4;
+    let agent = ureq::AgentBuilder::new().timeout(std::time::Duration::from_secs(10)).build();
+    for job in jobs {
+        let mut backoff = std::time::Duration::from_millis(500);
+        for attempt in 0..=MAX_RETRIES {
+            match agent.post(url).send_json(job) {
+                Ok(_) => break,
+                Err(e) if attempt < MAX_RETRIES && is_transient(&e) => {
+                    tracing::debug!("Posting job {} to {} failed (attempt {}): {}", job.job_id, url, attempt + 1, e);
+                    std::thread::sleep(backoff);
+                    backoff = backoff.saturating_mul(2);
+                }
+                Err(e) => {
+                    return Err(SidecarError::ProcessingError(format!(
+                        "Posting job {} to {} failed after {} attempts: {}",
+                        job.job_id,
+                        url,
+                        attempt + 1,
+                        e
+                    )))
+                }
+            }
+        }
+    }
+    Ok(jobs.len())
+}
+
+/// Whether a failed request may succeed if sent again
+#[cfg(feature = "webhooks")]
+fn is_transient(error: &ureq::Error) -> bool {
+    match error {
+        ureq::Error::Status(status, _) => *status == 429 || *status >= 500,
+        ureq::Error::Transport(_) => true,
+    }
+}
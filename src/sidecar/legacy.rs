@@ -0,0 +1,299 @@
+/*
+ * Context: Recognising sidecars written by the legacy sportball Python tools and rewriting them
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde, chrono
+ *
+ * Each legacy layout is a `LegacyLayout`; an importer tries its layouts in order and the
+ * first to recognise a file as belonging to an existing image decides which payloads it holds.
+ */
+
+use crate::sidecar::types::OperationType;
+use crate::utils::paths;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Key under `sidecar_info` naming, per operation, the legacy sidecar its payload was imported from
+pub const MIGRATED_FROM_KEY: &str = "migrated_from";
+
+/// What a layout found in one legacy sidecar
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegacySidecar {
+    /// Stem of the image the sidecar belongs to, looked up beside the sidecar
+    pub image_stem: OsString,
+    /// Payloads keyed by their canonical operation name
+    pub payloads: BTreeMap<String, Value>,
+}
+
+/// One way the legacy tools laid out sidecars
+pub trait LegacyLayout: Send + Sync {
+    /// Name recorded in the migration report
+    fn name(&self) -> &str;
+
+    /// Recognise the sidecar at `sidecar_path` holding `data`, or `None` if it is not in this layout
+    fn recognize(&self, sidecar_path: &Path, data: &Value) -> Option<LegacySidecar>;
+}
+
+/// `IMG_0001_faces.json`: one operation per file, named by a suffix after the image stem
+///
+/// The payload is the value under the operation's own key when the file has one, and the
+/// whole document otherwise.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuffixLayout {
+    suffixes: BTreeMap<String, String>,
+}
+
+impl Default for SuffixLayout {
+    fn default() -> Self {
+        let suffixes = [
+            ("faces", OperationType::FaceDetection),
+            ("face", OperationType::FaceDetection),
+            ("objects", OperationType::ObjectDetection),
+            ("ball", OperationType::BallDetection),
+            ("quality", OperationType::QualityAssessment),
+            ("game", OperationType::GameDetection),
+            ("yolo", OperationType::Yolov8),
+            ("yolov8", OperationType::Yolov8),
+            ("hash", OperationType::ImageHash),
+            ("tracks", OperationType::Tracking),
+        ];
+        Self { suffixes: suffixes.into_iter().map(|(suffix, operation)| (suffix.to_string(), operation.as_str().to_string())).collect() }
+    }
+}
+
+impl SuffixLayout {
+    /// Treat `{stem}_{suffix}.json` as holding the `operation` payload of `{stem}`
+    pub fn suffix(mut self, suffix: impl Into<String>, operation: impl Into<String>) -> Self {
+        self.suffixes.insert(suffix.into().to_lowercase(), operation.into());
+        self
+    }
+}
+
+impl LegacyLayout for SuffixLayout {
+    fn name(&self) -> &str {
+        "suffix"
+    }
+
+    fn recognize(&self, sidecar_path: &Path, data: &Value) -> Option<LegacySidecar> {
+        let stem = sidecar_path.file_stem()?.to_str()?;
+        let (image_stem, suffix) = stem.rsplit_once('_')?;
+        let operation = self.suffixes.get(&suffix.to_lowercase())?;
+        if image_stem.is_empty() {
+            return None;
+        }
+        let payload = data.get(operation.as_str()).unwrap_or(data).clone();
+        Some(LegacySidecar { image_stem: image_stem.into(), payloads: BTreeMap::from([(operation.clone(), payload)]) })
+    }
+}
+
+/// `IMG_0001.json` keyed by detector (`Face_detector`, `Object_detector`, ...) instead of operation
+///
+/// Detector keys are renamed to their operations; other keys are kept as they are.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetectorKeyedLayout {
+    detectors: BTreeMap<String, String>,
+}
+
+impl Default for DetectorKeyedLayout {
+    fn default() -> Self {
+        let detectors = [
+            ("Face_detector", OperationType::FaceDetection),
+            ("Object_detector", OperationType::ObjectDetection),
+            ("Ball_detector", OperationType::BallDetection),
+            ("Quality_assessor", OperationType::QualityAssessment),
+            ("Game_detector", OperationType::GameDetection),
+        ];
+        Self { detectors: detectors.into_iter().map(|(key, operation)| (key.to_string(), operation.as_str().to_string())).collect() }
+    }
+}
+
+impl DetectorKeyedLayout {
+    /// Rename the top-level key `detector` to `operation`
+    pub fn detector(mut self, detector: impl Into<String>, operation: impl Into<String>) -> Self {
+        self.detectors.insert(detector.into(), operation.into());
+        self
+    }
+}
+
+impl LegacyLayout for DetectorKeyedLayout {
+    fn name(&self) -> &str {
+        "detector_keyed"
+    }
+
+    fn recognize(&self, sidecar_path: &Path, data: &Value) -> Option<LegacySidecar> {
+        let object = data.as_object()?;
+        if !object.keys().any(|key| self.detectors.contains_key(key)) {
+            return None;
+        }
+        let payloads = object
+            .iter()
+            .map(|(key, payload)| (self.detectors.get(key).unwrap_or(key).clone(), payload.clone()))
+            .collect();
+        Some(LegacySidecar { image_stem: sidecar_path.file_stem()?.to_os_string(), payloads })
+    }
+}
+
+/// The legacy layouts an import recognises, tried in order
+#[derive(Clone)]
+pub struct LegacyImporter {
+    layouts: Vec<Arc<dyn LegacyLayout>>,
+}
+
+impl Default for LegacyImporter {
+    /// The detector-keyed and suffix layouts of the sportball Python tools
+    fn default() -> Self {
+        Self::empty().layout(DetectorKeyedLayout::default()).layout(SuffixLayout::default())
+    }
+}
+
+impl std::fmt::Debug for LegacyImporter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.layouts.iter().map(|layout| layout.name())).finish()
+    }
+}
+
+impl LegacyImporter {
+    /// An importer that recognises nothing until layouts are added
+    pub fn empty() -> Self {
+        Self { layouts: Vec::new() }
+    }
+
+    /// Try `layout` after the layouts added so far
+    pub fn layout(mut self, layout: impl LegacyLayout + 'static) -> Self {
+        self.layouts.push(Arc::new(layout));
+        self
+    }
+
+    /// What each layout recognising the sidecar found, in layout order
+    ///
+    /// Sidecars with `sidecar_info` are already in the modern structure and are never recognised.
+    /// The importer uses the first candidate whose image exists, so `game_ball.json` keyed by
+    /// detector still belongs to `game_ball.jpg` rather than to `game.jpg`.
+    pub fn candidates(&self, sidecar_path: &Path, data: &Value) -> Vec<(&str, LegacySidecar)> {
+        if data.get("sidecar_info").is_some() {
+            return Vec::new();
+        }
+        self.layouts
+            .iter()
+            .filter_map(|layout| layout.recognize(sidecar_path, data).map(|found| (layout.name(), found)))
+            .collect()
+    }
+}
+
+/// A legacy sidecar imported into a modern one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LegacySource {
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub path: PathBuf,
+    /// Name of the layout that recognised it
+    pub layout: String,
+}
+
+/// What importing the legacy sidecars of one image did
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MigrationRecord {
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub image_path: PathBuf,
+    /// The modern sidecar written, or that would be in a dry run
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub sidecar_path: PathBuf,
+    pub sources: Vec<LegacySource>,
+    /// Operations imported from the legacy sidecars
+    pub operations: Vec<String>,
+    /// Operations the modern sidecar already held; its payloads are kept
+    pub kept: Vec<String>,
+    /// Legacy sidecars removed once imported
+    #[serde(with = "crate::utils::paths::lossless_vec")]
+    pub removed: Vec<PathBuf>,
+    /// Legacy sidecars left in place because a payload they hold lost to the modern sidecar
+    /// or to another legacy sidecar of the image
+    #[serde(default, with = "crate::utils::paths::lossless_vec")]
+    pub conflicts: Vec<PathBuf>,
+    pub error: Option<String>,
+}
+
+impl MigrationRecord {
+    pub fn new(image_path: PathBuf, sidecar_path: PathBuf) -> Self {
+        Self {
+            image_path,
+            sidecar_path,
+            sources: Vec::new(),
+            operations: Vec::new(),
+            kept: Vec::new(),
+            removed: Vec::new(),
+            conflicts: Vec::new(),
+            error: None,
+        }
+    }
+}
+
+/// What a legacy import of a directory found and did
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MigrationReport {
+    pub records: Vec<MigrationRecord>,
+    /// Legacy sidecars whose image could not be found beside them
+    #[serde(with = "crate::utils::paths::lossless_vec")]
+    pub orphaned: Vec<PathBuf>,
+    /// Sidecars no layout recognised, modern ones aside
+    #[serde(with = "crate::utils::paths::lossless_vec")]
+    pub unrecognized: Vec<PathBuf>,
+    /// Sidecars already in the modern structure
+    pub modern: usize,
+}
+
+impl MigrationReport {
+    pub fn failed(&self) -> usize {
+        self.records.iter().filter(|record| record.error.is_some()).count()
+    }
+}
+
+/// `base` with the imported `payloads` added under their operations
+///
+/// Operations `base` already holds keep its payload. `sidecar_info` records the legacy
+/// sidecar each imported payload came from under `migrated_from`. Returns the document
+/// with the operations imported and kept.
+pub fn migrated_document(
+    base: Option<Value>,
+    payloads: &BTreeMap<String, (Value, PathBuf)>,
+    image_path: &Path,
+) -> (Value, Vec<String>, Vec<String>) {
+    let mut document = match base {
+        Some(Value::Object(object)) => object,
+        _ => Map::new(),
+    };
+    let mut info = match document.remove("sidecar_info") {
+        Some(Value::Object(info)) => info,
+        _ => Map::new(),
+    };
+    let (mut imported, mut kept) = (Vec::new(), Vec::new());
+    let mut sources = match info.remove(MIGRATED_FROM_KEY) {
+        Some(Value::Object(sources)) => sources,
+        _ => Map::new(),
+    };
+    for (operation, (payload, source)) in payloads {
+        if document.contains_key(operation) {
+            kept.push(operation.clone());
+            continue;
+        }
+        document.insert(operation.clone(), payload.clone());
+        sources.insert(operation.clone(), paths::path_to_json(source));
+        imported.push(operation.clone());
+    }
+
+    let now = Value::String(Utc::now().to_rfc3339());
+    info.entry("created_at").or_insert_with(|| now.clone());
+    info.entry("image_path").or_insert_with(|| paths::path_to_json(image_path));
+    info.insert("last_updated".to_string(), now);
+    if let Some(operation) = imported.last() {
+        info.insert("last_operation".to_string(), Value::String(operation.clone()));
+    }
+    info.insert(MIGRATED_FROM_KEY.to_string(), Value::Object(sources));
+    document.insert("sidecar_info".to_string(), Value::Object(info));
+    (Value::Object(document), imported, kept)
+}
@@ -0,0 +1,398 @@
+/*
+ * Context: Lint rules for sidecar contents, with per-rule levels like clippy's
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde, serde_json, chrono
+ *
+ * Each rule is a `LintRule` that inspects one decoded sidecar and reports issues at JSON
+ * pointers. A `Linter` runs its rules in order and gives every issue the level of its
+ * rule: the rule's default unless it was set to allow, warn or deny. Denied findings make
+ * the lint fail.
+ */
+
+use crate::geometry::{read_components, BBox, BoxFormat};
+use crate::sidecar::types::{Result, SidecarError, SidecarInfo};
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// How a rule's findings are treated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintLevel {
+    /// The rule is not run
+    Allow,
+    /// Reported without failing the lint
+    Warn,
+    /// Reported and failing the lint
+    Deny,
+}
+
+impl LintLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LintLevel::Allow => "allow",
+            LintLevel::Warn => "warn",
+            LintLevel::Deny => "deny",
+        }
+    }
+}
+
+/// Something a rule found wrong in a sidecar
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LintIssue {
+    /// JSON pointer to the offending value; empty for the whole document
+    pub pointer: String,
+    pub message: String,
+}
+
+impl LintIssue {
+    pub fn new(pointer: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { pointer: pointer.into(), message: message.into() }
+    }
+}
+
+/// One check of a decoded sidecar
+pub trait LintRule: Send + Sync {
+    /// Name used for `--allow`, `--warn` and `--deny`, in snake_case
+    fn name(&self) -> &str;
+
+    /// One line on what the rule reports
+    fn description(&self) -> &str;
+
+    /// Level of the rule's findings unless the linter sets another
+    fn default_level(&self) -> LintLevel {
+        LintLevel::Warn
+    }
+
+    /// Issues in `data`, the decoded sidecar described by `sidecar`
+    fn check(&self, sidecar: &SidecarInfo, data: &Value) -> Vec<LintIssue>;
+}
+
+/// An issue with the sidecar it was found in, the rule that found it and its level
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LintFinding {
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub sidecar_path: PathBuf,
+    pub rule: String,
+    pub level: LintLevel,
+    pub pointer: String,
+    pub message: String,
+}
+
+/// Findings for a directory of sidecars
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LintReport {
+    /// Sidecars that could be decoded and were linted
+    pub sidecars: usize,
+    /// Sorted by sidecar path, then in rule order
+    pub findings: Vec<LintFinding>,
+}
+
+impl LintReport {
+    pub fn count(&self, level: LintLevel) -> usize {
+        self.findings.iter().filter(|finding| finding.level == level).count()
+    }
+
+    /// Whether no finding was denied
+    pub fn passed(&self) -> bool {
+        self.count(LintLevel::Deny) == 0
+    }
+}
+
+/// The rules a lint runs, with the levels they were given
+#[derive(Clone)]
+pub struct Linter {
+    rules: Vec<Arc<dyn LintRule>>,
+    levels: BTreeMap<String, LintLevel>,
+    deny_warnings: bool,
+}
+
+impl Default for Linter {
+    /// Every built-in rule at its default level
+    fn default() -> Self {
+        Self::empty()
+            .rule(MissingCreatedAt)
+            .rule(ForeignAbsolutePath)
+            .rule(DenormalizedBbox)
+            .rule(NanConfidence)
+            .rule(EmptyOperation)
+    }
+}
+
+impl std::fmt::Debug for Linter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.rules.iter().map(|rule| (rule.name(), self.level_of(rule.as_ref())))).finish()
+    }
+}
+
+impl Linter {
+    /// A linter without any rules, not even the built-in ones
+    pub fn empty() -> Self {
+        Self { rules: Vec::new(), levels: BTreeMap::new(), deny_warnings: false }
+    }
+
+    /// Run `rule` after the rules added so far
+    pub fn rule(mut self, rule: impl LintRule + 'static) -> Self {
+        self.rules.push(Arc::new(rule));
+        self
+    }
+
+    /// Set the level of the rule called `name`
+    ///
+    /// `all` sets every rule. Denying `warnings` raises every finding at `warn` to `deny`,
+    /// as `-D warnings` does for clippy. Unknown names are an error.
+    pub fn level(mut self, name: &str, level: LintLevel) -> Result<Self> {
+        match name {
+            "warnings" if level == LintLevel::Deny => self.deny_warnings = true,
+            "all" => {
+                for rule in &self.rules {
+                    self.levels.insert(rule.name().to_string(), level);
+                }
+            }
+            _ if self.rules.iter().any(|rule| rule.name() == name) => {
+                self.levels.insert(name.to_string(), level);
+            }
+            _ => {
+                let known: Vec<&str> = self.rules.iter().map(|rule| rule.name()).collect();
+                return Err(SidecarError::ProcessingError(format!(
+                    "Unknown lint rule '{}'. Known rules: {}", name, known.join(", ")
+                )));
+            }
+        }
+        Ok(self)
+    }
+
+    /// Each rule with its description and the level its findings get
+    pub fn rules(&self) -> Vec<(&str, &str, LintLevel)> {
+        self.rules.iter().map(|rule| (rule.name(), rule.description(), self.level_of(rule.as_ref()))).collect()
+    }
+
+    fn level_of(&self, rule: &dyn LintRule) -> LintLevel {
+        let level = self.levels.get(rule.name()).copied().unwrap_or_else(|| rule.default_level());
+        if self.deny_warnings && level == LintLevel::Warn { LintLevel::Deny } else { level }
+    }
+
+    /// Findings of every rule not allowed, in rule order
+    pub fn check(&self, sidecar: &SidecarInfo, data: &Value) -> Vec<LintFinding> {
+        self.rules
+            .iter()
+            .map(|rule| (rule, self.level_of(rule.as_ref())))
+            .filter(|(_, level)| *level != LintLevel::Allow)
+            .flat_map(|(rule, level)| {
+                rule.check(sidecar, data).into_iter().map(move |issue| LintFinding {
+                    sidecar_path: sidecar.sidecar_path.clone(),
+                    rule: rule.name().to_string(),
+                    level,
+                    pointer: issue.pointer,
+                    message: issue.message,
+                })
+            })
+            .collect()
+    }
+}
+
+/// `missing_created_at`: `sidecar_info.created_at` is absent or not an RFC 3339 time
+///
+/// Recency filters, time series and retention all read it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MissingCreatedAt;
+
+impl LintRule for MissingCreatedAt {
+    fn name(&self) -> &str {
+        "missing_created_at"
+    }
+
+    fn description(&self) -> &str {
+        "sidecar_info.created_at is missing or not an RFC 3339 time"
+    }
+
+    fn check(&self, _sidecar: &SidecarInfo, data: &Value) -> Vec<LintIssue> {
+        let pointer = "/sidecar_info/created_at";
+        match data.get("sidecar_info").and_then(|info| info.get("created_at")) {
+            None => vec![LintIssue::new(pointer, "no creation time recorded")],
+            Some(Value::String(s)) if DateTime::parse_from_rfc3339(s).is_ok() => Vec::new(),
+            Some(value) => vec![LintIssue::new(pointer, format!("creation time {} is not RFC 3339", value))],
+        }
+    }
+}
+
+/// `foreign_path`: a `path` or `*_path` field holds an absolute path that does not exist here
+///
+/// These are usually left by the machine the sidecar was written on, such as a Windows
+/// drive path or another host's mount point. Checking them reads the filesystem.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ForeignAbsolutePath;
+
+impl LintRule for ForeignAbsolutePath {
+    fn name(&self) -> &str {
+        "foreign_path"
+    }
+
+    fn description(&self) -> &str {
+        "absolute path that does not exist on this host"
+    }
+
+    fn check(&self, _sidecar: &SidecarInfo, data: &Value) -> Vec<LintIssue> {
+        let mut issues = Vec::new();
+        walk(data, String::new(), &mut |key, value, pointer| {
+            let Some(path) = value.as_str() else { return };
+            if (key == "path" || key.ends_with("_path")) && looks_absolute(path) && !Path::new(path).exists() {
+                issues.push(LintIssue::new(pointer, format!("absolute path {} does not exist on this host", path)));
+            }
+        });
+        issues
+    }
+}
+
+/// `denormalized_bbox`: a `bbox` that cannot be read, or with negative coordinates or no area
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DenormalizedBbox;
+
+impl LintRule for DenormalizedBbox {
+    fn name(&self) -> &str {
+        "denormalized_bbox"
+    }
+
+    fn description(&self) -> &str {
+        "bbox in no known layout, with negative coordinates, or empty or inverted"
+    }
+
+    fn default_level(&self) -> LintLevel {
+        LintLevel::Deny
+    }
+
+    fn check(&self, _sidecar: &SidecarInfo, data: &Value) -> Vec<LintIssue> {
+        let mut issues = Vec::new();
+        walk(data, String::new(), &mut |key, value, pointer| {
+            if key != "bbox" || value.is_null() {
+                return;
+            }
+            let Some((format, [a, b, c, d])) = read_components(value) else {
+                issues.push(LintIssue::new(pointer, format!("bbox {} is in no known layout", value)));
+                return;
+            };
+            let bbox = match format {
+                BoxFormat::Xywh => BBox::from_xywh(a, b, c, d),
+                BoxFormat::Xyxy => BBox::from_xyxy(a, b, c, d),
+                BoxFormat::Cxcywh => BBox::from_cxcywh(a, b, c, d),
+            };
+            if bbox.width() <= 0.0 || bbox.height() <= 0.0 {
+                issues.push(LintIssue::new(pointer, format!("bbox {} is empty or inverted", value)));
+            } else if bbox.x1 < 0.0 || bbox.y1 < 0.0 {
+                issues.push(LintIssue::new(pointer, format!("bbox {} has negative coordinates", value)));
+            }
+        });
+        issues
+    }
+}
+
+/// `nan_confidence`: a `confidence` or `score` that is not a number
+///
+/// JSON cannot hold NaN or infinity, so detectors that produce them write `null` or a
+/// string such as `"NaN"` instead.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NanConfidence;
+
+impl LintRule for NanConfidence {
+    fn name(&self) -> &str {
+        "nan_confidence"
+    }
+
+    fn description(&self) -> &str {
+        "confidence or score that is null, NaN, infinite or not a number"
+    }
+
+    fn default_level(&self) -> LintLevel {
+        LintLevel::Deny
+    }
+
+    fn check(&self, _sidecar: &SidecarInfo, data: &Value) -> Vec<LintIssue> {
+        let mut issues = Vec::new();
+        walk(data, String::new(), &mut |key, value, pointer| {
+            // serde_json numbers are always finite; nested score breakdowns are left alone
+            if (key == "confidence" || key == "score") && matches!(value, Value::Null | Value::Bool(_) | Value::String(_)) {
+                issues.push(LintIssue::new(pointer, format!("{} {} is not a finite number", key, value)));
+            }
+        });
+        issues
+    }
+}
+
+/// `empty_operation`: an operation payload that is `null`, `{}` or `[]`, or a sidecar with none
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EmptyOperation;
+
+impl LintRule for EmptyOperation {
+    fn name(&self) -> &str {
+        "empty_operation"
+    }
+
+    fn description(&self) -> &str {
+        "operation payload that is null, {} or [], or a sidecar without any"
+    }
+
+    fn check(&self, _sidecar: &SidecarInfo, data: &Value) -> Vec<LintIssue> {
+        let Some(object) = data.as_object() else {
+            return if is_empty(data) { vec![LintIssue::new("", "sidecar holds no data")] } else { Vec::new() };
+        };
+        // Raw detector output has no sidecar_info and is itself the payload
+        if !object.contains_key("sidecar_info") {
+            return if object.is_empty() { vec![LintIssue::new("", "sidecar holds no data")] } else { Vec::new() };
+        }
+        let payloads: Vec<(&String, &Value)> = object.iter().filter(|(key, _)| *key != "sidecar_info").collect();
+        if payloads.is_empty() {
+            return vec![LintIssue::new("", "sidecar holds no operation payloads")];
+        }
+        payloads
+            .into_iter()
+            .filter(|(_, payload)| is_empty(payload))
+            .map(|(operation, payload)| LintIssue::new(format!("/{}", escape(operation)), format!("{} payload is {}", operation, payload)))
+            .collect()
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Object(object) => object.is_empty(),
+        Value::Array(items) => items.is_empty(),
+        _ => false,
+    }
+}
+
+/// Whether `path` is absolute on POSIX or Windows, whatever the host
+fn looks_absolute(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    path.starts_with('/')
+        || path.starts_with("\\\\")
+        || (bytes.len() > 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && matches!(bytes[2], b'\\' | b'/'))
+}
+
+/// Call `f` with the key, value and JSON pointer of every object member below `value`
+fn walk(value: &Value, pointer: String, f: &mut impl FnMut(&str, &Value, String)) {
+    match value {
+        Value::Object(object) => {
+            for (key, child) in object {
+                let child_pointer = format!("{}/{}", pointer, escape(key));
+                f(key, child, child_pointer.clone());
+                walk(child, child_pointer, f);
+            }
+        }
+        Value::Array(items) => {
+            for (index, child) in items.iter().enumerate() {
+                walk(child, format!("{}/{}", pointer, index), f);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Escape a key for a JSON pointer (RFC 6901)
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
@@ -1,4 +1,4 @@
-/**
+/*
  * This code written by Claude Sonnet 4 (claude-3-5-sonnet-20241022)
  * Generated via Cursor IDE (cursor.sh) with AI assistance
  * Model: Anthropic Claude 3.5 Sonnet
@@ -10,115 +10,1297 @@
  * - IDE: Cursor (cursor.sh)
  * - Generation method: AI-assisted pair programming
  * - Code style: Rust idiomatic with comprehensive error handling
- * - Dependencies: tokio, serde, rayon, anyhow
+ * - Dependencies: tokio, serde, rayon
  */
 
 use crate::sidecar::types::{
-    SidecarInfo, OperationType, SidecarError, StatisticsResult, SymlinkInfo
+    SidecarInfo, OperationType, SidecarError, StatisticsResult, SymlinkInfo, MultiRootStatistics, MergeStrategy, SymlinkPolicy,
+    BrokenSymlink, HardlinkGroup, ValidationResult, DEFAULT_MAX_SIDECAR_SIZE,
+    ConversionSummary, OperationRemoval, common_ancestor,
 };
 use crate::sidecar::formats::{SidecarFormat, FormatManager};
-use anyhow::Result;
-use std::collections::HashMap;
+use crate::sidecar::classify::{ClassifierRule, OperationClassifier};
+use crate::sidecar::skim::{self, SkimKeys};
+use crate::sidecar::patterns::SidecarPatterns;
+use crate::sidecar::scan::{hardlink_id, image_file_name, owning_image, resolve_link_target, sibling_links, DirectoryScan, FileSelection, ScanOptions, SidecarMatch};
+use crate::sidecar::checkpoint::Checkpoint;
+use crate::sidecar::audit::{Mutation, MutationLog};
+use crate::sidecar::provenance::Provenance;
+use crate::sidecar::blobs::{self, BlobIssue, BlobReport, BlobStore};
+use crate::sidecar::pack::{Pack, PackWriter, UnpackSummary};
+use crate::sidecar::archive::{ArchiveOptions, ArchiveSummary, ArchiveWriter};
+use crate::sidecar::redact::{self, RedactKey, RedactRule, RedactionRecord};
+use crate::sidecar::git::{self, GitFileStatus, GitStatus};
+use crate::sidecar::badge::{self, BadgeEntry, BadgeRefresh, BadgeRollup, FileStamp, StatsBadge, STATS_BADGE_FILE};
+use crate::sidecar::coverage::CoverageReport;
+use crate::sidecar::pipeline::{ScanPipeline, ScanReport};
+use crate::sidecar::triage::TriageReport;
+use crate::sidecar::review::{scored_detections, ReviewOptions, ReviewQueue};
+use crate::sidecar::gate::{GateReport, QualityGate};
+use crate::sidecar::timeline::{self, Frame, FrameNaming, Timeline, TimelineOptions};
+use crate::sidecar::tracks::{self, Track};
+use crate::sidecar::lint::{LintReport, Linter};
+use crate::sidecar::anomaly::{AnomalyOptions, AnomalyReport, AnomalySample};
+use crate::sidecar::bench::{self, BenchOptions, BenchReport};
+use crate::sidecar::generate::{self, GenerateOptions, GenerateSummary};
+use crate::sidecar::storage::{self, StorageBackend};
+use crate::sidecar::payload::PayloadSource;
+use crate::sidecar::jobs::JobSpec;
+use crate::sidecar::tfrecord::{self, TfExample, TfObject, TfRecordOptions, TfRecordSummary, TfRecordWriter};
+use crate::sidecar::legacy::{self, LegacyImporter, LegacySource, MigrationRecord, MigrationReport};
+use crate::sidecar::webdataset::{self, WebDatasetOptions, WebDatasetSummary};
+use crate::sidecar::unify::{self, SplitFile, SplitRecord, UnifiedOperation, UnifyRecord};
+use crate::sidecar::transaction::{self, RecoverySummary, SidecarTransaction};
+use crate::sidecar::relocate::{self, MovedFile, RelocationRecord};
+use crate::sidecar::reassociate::{reassociated_path, Reassociation, ReassociationOutcome};
+use crate::sidecar::fingerprint::{ImageFingerprint, StaleReason, StaleSidecar, Staleness, IMAGE_FINGERPRINT_KEY};
+use crate::sidecar::dvc::{self, DvcDirectory, DvcEntry, DvcFile, DvcOutput, DvcVerification};
+use crate::sidecar::snapshot::{RestoreSummary, SnapshotStore, SnapshotSummary};
+use crate::sidecar::signing::{self, SidecarSigner, SignatureIssue, SignatureProblem, SignatureRecord, SignatureReport, SignatureVerifier, SIGNATURES_KEY};
+use crate::sidecar::retention::{GcAction, GcRecord, RetentionPolicy};
+use crate::sidecar::repair::{self, RepairRecord};
+use crate::sidecar::manifest::{self, Manifest, ManifestEntry, ManifestVerification};
+use crate::sidecar::diff::{DirectoryDiff, OperationPayloads};
+use crate::utils::paths;
+use crate::utils::json::{JsonUtils, MergeResult};
+use crate::parallel::progress::{split_progress, ProgressCallback, ProgressTracker};
+use crate::parallel::ParallelProcessor;
+use crate::parallel::throttle::Throttle;
+use crate::query::{DetectionFilter, ExtractPath, ExtractedValue, Projection, QueryExpr, QueryMatch};
+use crate::imaging::{crops, dedupe, Crop, DuplicateGroup, ImageHash};
+use crate::vectors::VectorIndex;
+use crate::geometry::{detections_in, normalize_sidecar_boxes, BoxConvention, NormalizeReport};
+use crate::sidecar::types::Result;
+use crate::events::{EventKind, Notifier, SidecarEvent};
+use crate::metrics::Metrics;
+use rayon::prelude::*;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime};
 use tokio::fs;
+use tokio::io::AsyncWriteExt;
 use walkdir::WalkDir;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use serde_json::Value;
 
+/// Image extensions recognised when no others are configured
+pub const DEFAULT_IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "tiff", "bmp", "webp"];
+
+/// Legacy sidecars found for one image, each with the operation payloads it holds
+type LegacyPayloads = Vec<(LegacySource, BTreeMap<String, Value>)>;
+
 /// Core sidecar manager for handling sidecar files in multiple formats
 pub struct SidecarManager {
     image_extensions: Vec<String>,
-    operation_mapping: HashMap<String, OperationType>,
-    format_manager: FormatManager,
+    classifier: Arc<OperationClassifier>,
+    patterns: Arc<SidecarPatterns>,
+    format_manager: Arc<FormatManager>,
+    default_format: SidecarFormat,
+    merge_strategy: MergeStrategy,
+    scan_options: ScanOptions,
+    selection: FileSelection,
+    detection_filter: DetectionFilter,
+    symlink_policy: SymlinkPolicy,
+    notifier: Option<Arc<Notifier>>,
+    mutation_log: Option<Arc<MutationLog>>,
+    metrics: Option<Arc<Metrics>>,
+    provenance: Provenance,
+    blob_store: Option<Arc<BlobStore>>,
+    signer: Option<Arc<SidecarSigner>>,
+    storage: Option<Arc<dyn StorageBackend>>,
+    throttle: Arc<Throttle>,
+    max_sidecar_size: u64,
+    keep_originals: bool,
+    read_only: bool,
+    git_friendly: bool,
+    /// Known links of each hardlinked file, sorted and keyed by device and inode, when hardlinks are merged
+    hardlink_paths: Mutex<HashMap<(u64, u64), Vec<PathBuf>>>,
+}
+
+/// Fluent configuration for a `SidecarManager`; unset options keep the `new()` defaults
+#[derive(Debug, Clone)]
+pub struct SidecarManagerBuilder {
+    image_extensions: Vec<String>,
+    classifier: OperationClassifier,
+    patterns: SidecarPatterns,
     default_format: SidecarFormat,
+    merge_strategy: MergeStrategy,
+    scan_options: ScanOptions,
+    selection: FileSelection,
+    detection_filter: DetectionFilter,
+    symlink_policy: SymlinkPolicy,
+    notifier: Option<Arc<Notifier>>,
+    mutation_log: Option<Arc<MutationLog>>,
+    metrics: Option<Arc<Metrics>>,
+    provenance: Provenance,
+    blob_store: Option<Arc<BlobStore>>,
+    signer: Option<Arc<SidecarSigner>>,
+    storage: Option<Arc<dyn StorageBackend>>,
+    throttle: Arc<Throttle>,
+    max_sidecar_size: u64,
+    keep_originals: bool,
+    read_only: bool,
+    canonical: bool,
+    git_friendly: bool,
+}
+
+impl Default for SidecarManagerBuilder {
+    fn default() -> Self {
+        Self {
+            image_extensions: DEFAULT_IMAGE_EXTENSIONS.iter().map(|ext| ext.to_string()).collect(),
+            classifier: OperationClassifier::default(),
+            patterns: SidecarPatterns::default(),
+            default_format: SidecarFormat::default(),
+            merge_strategy: MergeStrategy::default(),
+            scan_options: ScanOptions::default(),
+            selection: FileSelection::default(),
+            detection_filter: DetectionFilter::default(),
+            symlink_policy: SymlinkPolicy::default(),
+            notifier: None,
+            mutation_log: None,
+            metrics: None,
+            provenance: Provenance::current(),
+            blob_store: None,
+            signer: None,
+            storage: None,
+            throttle: Arc::new(Throttle::unlimited()),
+            max_sidecar_size: DEFAULT_MAX_SIDECAR_SIZE,
+            keep_originals: false,
+            read_only: false,
+            canonical: false,
+            git_friendly: false,
+        }
+    }
+}
+
+impl SidecarManagerBuilder {
+    /// Replace the recognised image extensions; case and a leading `.` are ignored
+    pub fn image_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.image_extensions.clear();
+        extensions.into_iter().fold(self, |builder, ext| builder.add_image_extension(ext))
+    }
+
+    /// Recognise one more image extension
+    pub fn add_image_extension(mut self, extension: impl AsRef<str>) -> Self {
+        let extension = extension.as_ref().trim_start_matches('.').to_lowercase();
+        if !extension.is_empty() && !self.image_extensions.contains(&extension) {
+            self.image_extensions.push(extension);
+        }
+        self
+    }
+
+    /// Treat sidecars containing the top-level key `key` as belonging to `operation`
+    pub fn operation_key(self, key: impl Into<String>, operation: OperationType) -> Self {
+        self.operation_rule(ClassifierRule::key(key, operation))
+    }
+
+    /// Classify sidecars meeting `rule` as its operation, before the built-in detector keys
+    pub fn operation_rule(mut self, rule: ClassifierRule) -> Self {
+        self.classifier.push(rule);
+        self
+    }
+
+    /// Name patterns such as `{stem}_faces.json` tying sidecars to images and operations
+    ///
+    /// They are tried before the built-in `prefix_IMG.json` rule by discovery, statistics,
+    /// cleanup and every other command that matches sidecars to images.
+    pub fn sidecar_patterns(mut self, patterns: SidecarPatterns) -> Self {
+        self.patterns = patterns;
+        self
+    }
+
+    /// Format used for new and converted sidecars
+    pub fn default_format(mut self, format: SidecarFormat) -> Self {
+        self.default_format = format;
+        self
+    }
+
+    /// How `save_data` combines new payloads with stored ones
+    pub fn merge_strategy(mut self, strategy: MergeStrategy) -> Self {
+        self.merge_strategy = strategy;
+        self
+    }
+
+    /// Symlink and hidden-file handling for directory scans
+    pub fn scan_options(mut self, options: ScanOptions) -> Self {
+        self.scan_options = options;
+        self
+    }
+
+    /// Descend into symlinked directories during scans
+    pub fn follow_links(mut self, follow: bool) -> Self {
+        self.scan_options.follow_links = follow;
+        self
+    }
+
+    /// Skip dot-files and dot-directories during scans
+    pub fn skip_hidden(mut self, skip: bool) -> Self {
+        self.scan_options.skip_hidden = skip;
+        self
+    }
+
+    /// Treat hardlinks to one image file as a single image sharing one sidecar
+    pub fn merge_hardlinks(mut self, merge: bool) -> Self {
+        self.scan_options.merge_hardlinks = merge;
+        self
+    }
+
+    /// Time bounds and order for the files conversion and coverage work on
+    pub fn selection(mut self, selection: FileSelection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Detection-count and confidence bounds `query` results must also meet
+    pub fn detection_filter(mut self, filter: DetectionFilter) -> Self {
+        self.detection_filter = filter;
+        self
+    }
+
+    /// Whether sidecars of symlinked images live next to the target, the link, or both
+    pub fn symlink_policy(mut self, policy: SymlinkPolicy) -> Self {
+        self.symlink_policy = policy;
+        self
+    }
+
+    /// Publish an event for every sidecar written or removed through this manager
+    pub fn notifier(mut self, notifier: Arc<Notifier>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// Record every sidecar write, conversion, deletion and repair in `log`
+    pub fn mutation_log(mut self, log: Arc<MutationLog>) -> Self {
+        self.mutation_log = Some(log);
+        self
+    }
+
+    /// Count every format conversion and its duration in `metrics`
+    pub fn metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Producer identity stamped into `sidecar_info` by `save_data` and `create_sidecar`
+    pub fn provenance(mut self, provenance: Provenance) -> Self {
+        self.provenance = provenance;
+        self
+    }
+
+    /// Keep operation payloads in `store`, leaving pointers to them in the sidecars
+    pub fn blob_store(mut self, store: Arc<BlobStore>) -> Self {
+        self.blob_store = Some(store);
+        self
+    }
+
+    /// Sign every payload written by `save_data`, `create_sidecar` and box normalization
+    pub fn signer(mut self, signer: Arc<SidecarSigner>) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Read, write and scan sidecars through `storage` instead of the filesystem
+    pub fn storage(mut self, storage: Arc<dyn StorageBackend>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Pace the files read by format conversion; share with a `ParallelProcessor` to cap both
+    pub fn throttle(mut self, throttle: Arc<Throttle>) -> Self {
+        self.throttle = throttle;
+        self
+    }
+
+    /// Refuse to read sidecars over `bytes` with `SidecarError::TooLarge` (default 256 MiB)
+    pub fn max_sidecar_size(mut self, bytes: u64) -> Self {
+        self.max_sidecar_size = bytes;
+        self
+    }
+
+    /// Leave the source sidecar in place after a verified format conversion
+    pub fn keep_originals(mut self, keep: bool) -> Self {
+        self.keep_originals = keep;
+        self
+    }
+
+    /// Refuse every operation that would create, change or delete a sidecar
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Write byte-identical sidecars for identical documents, see `CanonicalSerializer`
+    pub fn canonical(mut self, canonical: bool) -> Self {
+        self.canonical = canonical;
+        self
+    }
+
+    /// Write sidecars as sorted, pretty-printed canonical JSON that diffs well in git
+    ///
+    /// Implies `canonical`; new sidecars and `save_data` use `.json` whatever the default format.
+    pub fn git_friendly(mut self, git_friendly: bool) -> Self {
+        self.git_friendly = git_friendly;
+        self
+    }
+
+    /// Create the configured SidecarManager
+    pub fn build(self) -> SidecarManager {
+        let format_manager = if self.git_friendly {
+            FormatManager::git_friendly()
+        } else if self.canonical {
+            FormatManager::canonical()
+        } else {
+            FormatManager::new()
+        };
+        SidecarManager {
+            image_extensions: self.image_extensions,
+            classifier: Arc::new(self.classifier),
+            patterns: Arc::new(self.patterns),
+            format_manager: Arc::new(format_manager),
+            default_format: if self.git_friendly { SidecarFormat::Json } else { self.default_format },
+            merge_strategy: self.merge_strategy,
+            scan_options: self.scan_options,
+            selection: self.selection,
+            detection_filter: self.detection_filter,
+            symlink_policy: self.symlink_policy,
+            notifier: self.notifier,
+            mutation_log: self.mutation_log,
+            metrics: self.metrics,
+            provenance: self.provenance,
+            blob_store: self.blob_store,
+            signer: self.signer,
+            storage: self.storage,
+            throttle: self.throttle,
+            max_sidecar_size: self.max_sidecar_size,
+            keep_originals: self.keep_originals,
+            read_only: self.read_only,
+            git_friendly: self.git_friendly,
+            hardlink_paths: Mutex::new(HashMap::new()),
+        }
+    }
 }
 
 impl SidecarManager {
     /// Create a new SidecarManager instance
     pub fn new() -> Self {
-        let mut operation_mapping = HashMap::new();
-        operation_mapping.insert("Face_detector".to_string(), OperationType::FaceDetection);
-        operation_mapping.insert("Object_detector".to_string(), OperationType::ObjectDetection);
-        operation_mapping.insert("Ball_detector".to_string(), OperationType::BallDetection);
-        operation_mapping.insert("Quality_assessor".to_string(), OperationType::QualityAssessment);
-        operation_mapping.insert("Game_detector".to_string(), OperationType::GameDetection);
-        operation_mapping.insert("yolov8".to_string(), OperationType::Yolov8);
+        Self::builder().build()
+    }
 
-        Self {
-            image_extensions: vec![
-                "jpg".to_string(), "jpeg".to_string(), "png".to_string(),
-                "tiff".to_string(), "bmp".to_string(), "webp".to_string()
-            ],
-            operation_mapping,
-            format_manager: FormatManager::new(),
-            default_format: SidecarFormat::default(),
+    /// Start configuring a SidecarManager with non-default options
+    pub fn builder() -> SidecarManagerBuilder {
+        SidecarManagerBuilder::default()
+    }
+
+    /// Image extensions this manager recognises
+    pub fn image_extensions(&self) -> &[String] {
+        &self.image_extensions
+    }
+
+    /// The configured sidecar name patterns, tried before the built-in `prefix_IMG.json` rule
+    pub fn sidecar_patterns(&self) -> &SidecarPatterns {
+        &self.patterns
+    }
+
+    /// Strategy `save_data` uses for existing payloads
+    pub fn merge_strategy(&self) -> MergeStrategy {
+        self.merge_strategy
+    }
+
+    /// Options used for directory scans
+    pub fn scan_options(&self) -> ScanOptions {
+        self.scan_options
+    }
+
+    /// Time bounds and order applied to scanned files by conversion and coverage
+    pub fn selection(&self) -> FileSelection {
+        self.selection
+    }
+
+    /// Where sidecars of symlinked images are read and written
+    pub fn symlink_policy(&self) -> SymlinkPolicy {
+        self.symlink_policy
+    }
+
+    /// Producer identity stamped on every write
+    pub fn provenance(&self) -> &Provenance {
+        &self.provenance
+    }
+
+    /// Store holding the payloads of pointer sidecars, if configured
+    pub fn blob_store(&self) -> Option<&BlobStore> {
+        self.blob_store.as_deref()
+    }
+
+    /// Key payloads are signed with, if configured
+    pub fn signer(&self) -> Option<&SidecarSigner> {
+        self.signer.as_deref()
+    }
+
+    fn require_blob_store(&self) -> Result<&BlobStore> {
+        self.blob_store().ok_or_else(|| SidecarError::ProcessingError("No blob store configured".to_string()))
+    }
+
+    /// Where this manager's sidecars are read from, for scans and lazily decoded payloads
+    pub(crate) fn payload_source(&self) -> PayloadSource {
+        PayloadSource {
+            storage: self.storage.clone(),
+            formats: Arc::clone(&self.format_manager),
+            blobs: self.blob_store.clone(),
+            max_size: self.max_sidecar_size,
+        }
+    }
+
+    /// The payload a pointer refers to, or `payload` itself if it is not a pointer
+    fn resolve_payload(&self, payload: &Value) -> Result<Value> {
+        match (blobs::pointer_hash(payload), self.blob_store()) {
+            (Some(hash), Some(store)) => store.get(hash),
+            _ => Ok(payload.clone()),
+        }
+    }
+
+    /// Whether sidecars are written as pretty canonical JSON for git
+    pub fn is_git_friendly(&self) -> bool {
+        self.git_friendly
+    }
+
+    /// Format `save_data` writes: binary, or JSON in git-friendly mode
+    fn save_format(&self) -> SidecarFormat {
+        if self.git_friendly { SidecarFormat::Json } else { SidecarFormat::Binary }
+    }
+
+    /// Whether this manager refuses to write sidecars
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Fail with `SidecarError::ReadOnly` if the manager is read-only; `action` names what was refused
+    pub fn ensure_writable(&self, action: &str) -> Result<()> {
+        if self.read_only {
+            return Err(SidecarError::ReadOnly(action.to_string()));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn notify(&self, kind: EventKind, image_path: Option<&Path>, sidecar_path: &Path, operation: Option<&str>) {
+        if let Some(notifier) = &self.notifier {
+            notifier.notify(SidecarEvent::new(kind, image_path, sidecar_path, operation));
+        }
+    }
+
+    /// Append a change to the mutation log, if one is configured
+    pub(crate) fn audit(&self, mutation: Mutation, sidecar_path: &Path, new_path: Option<&Path>, operation: Option<&str>) -> Result<()> {
+        match &self.mutation_log {
+            Some(log) => log.record(mutation, sidecar_path, new_path, operation),
+            None => Ok(()),
         }
     }
 
     /// Find sidecar file for a given image path
     /// Priority: .bin -> .rkyv -> .json (most efficient to least efficient)
     pub async fn find_sidecar_for_image(&self, image_path: &Path) -> Result<Option<SidecarInfo>> {
-        if !image_path.exists() {
+        if !self.file_exists(image_path) {
             return Ok(None);
         }
 
         // Resolve symlink if needed
         let (actual_image_path, symlink_info) = self.resolve_symlink(image_path).await?;
+        let bases = self.sidecar_bases(image_path, &actual_image_path, symlink_info.is_some());
 
-        // Try formats in order of efficiency: bin -> rkyv -> json
-        let formats_to_try = [SidecarFormat::Binary, SidecarFormat::Rkyv, SidecarFormat::Json];
-        
-        for format in &formats_to_try {
-            let sidecar_path = actual_image_path.with_extension(format.extension());
-            
-            if sidecar_path.exists() {
-                let operation = self.detect_operation_type(&sidecar_path).await?;
-                let mut sidecar_info = SidecarInfo::new(
-                    image_path.to_path_buf(),
-                    sidecar_path,
-                    operation,
-                    symlink_info,
-                );
-                
-                // Load and validate the sidecar
-                if let Ok(data) = self.load_sidecar_data(&sidecar_info.sidecar_path).await {
-                    sidecar_info.data_size = data.to_string().len() as u64;
-                    sidecar_info.is_valid = true;
+        let Some(sidecar_path) = self.existing_sidecar(&bases) else {
+            return Ok(None);
+        };
+        let mut sidecar_info = SidecarInfo::new(
+            image_path.to_path_buf(),
+            sidecar_path,
+            OperationType::Unknown,
+            symlink_info,
+        ).with_payload_source(self.payload_source());
+
+        // Load and validate the sidecar
+        if let Ok((outline, size)) = self.load_sidecar_outline(&sidecar_info.sidecar_path).await {
+            sidecar_info.operation = Self::operation_from_data(&self.classifier, &outline);
+            sidecar_info.data_size = size;
+            sidecar_info.is_valid = true;
+            sidecar_info.extract_payload_metrics(&outline);
+        }
+
+        Ok(Some(sidecar_info))
+    }
+
+    /// Find all sidecar files in a directory
+    pub async fn find_all_sidecars(&self, directory: &Path) -> Result<Vec<SidecarInfo>> {
+        self.find_all_sidecars_with_progress(directory, None).await
+    }
+
+    /// Find all sidecar files in a directory, reporting progress per loaded sidecar
+    ///
+    /// Each sidecar is skimmed for its operation, validity and payload metrics; the full
+    /// contents are decoded on demand by `SidecarInfo::payload`. `list_sidecars` reads none.
+    pub async fn find_all_sidecars_with_progress(
+        &self,
+        directory: &Path,
+        progress: Option<ProgressCallback>,
+    ) -> Result<Vec<SidecarInfo>> {
+        let (_, sidecars) = self.scan_directory(directory, progress).await?;
+        Ok(sidecars)
+    }
+
+    /// List all sidecar files in a directory from their metadata alone
+    pub async fn list_sidecars(&self, directory: &Path) -> Result<Vec<SidecarInfo>> {
+        self.list_sidecars_with_progress(directory, None).await
+    }
+
+    /// List all sidecar files in a directory, reporting progress per sidecar found
+    ///
+    /// Only metadata is read: `data_size` is the stored size, the operation comes from the name
+    /// pattern that matched the sidecar, if any, and `is_valid` stays false. `SidecarInfo::payload`
+    /// and `SidecarInfo::check_valid` read a sidecar's contents on demand.
+    pub async fn list_sidecars_with_progress(
+        &self,
+        directory: &Path,
+        progress: Option<ProgressCallback>,
+    ) -> Result<Vec<SidecarInfo>> {
+        let directory = directory.to_path_buf();
+        let image_extensions = self.image_extensions.clone();
+        let patterns = Arc::clone(&self.patterns);
+        let scan_options = self.scan_options;
+        let source = self.payload_source();
+
+        let (scan, sidecars) = tokio::task::spawn_blocking(move || {
+            let scan = Self::walk(source.storage.as_deref(), &directory, &image_extensions, scan_options);
+            let matches = scan.match_sidecars(&directory, &image_extensions, &patterns);
+            let tracker = ProgressTracker::new(progress, matches.len());
+
+            let sidecars: Vec<SidecarInfo> = matches
+                .into_par_iter()
+                .map(|sidecar_match| {
+                    let info = Self::describe_sidecar(&source, sidecar_match);
+                    tracker.advance(&info.sidecar_path);
+                    info
+                })
+                .collect();
+
+            (scan, sidecars)
+        }).await?;
+        self.remember_hardlinks(&scan.hardlinks);
+
+        Ok(sidecars)
+    }
+
+    /// Walk `directory` once, match sidecars to images and load every match in parallel
+    async fn scan_directory(
+        &self,
+        directory: &Path,
+        progress: Option<ProgressCallback>,
+    ) -> Result<(DirectoryScan, Vec<SidecarInfo>)> {
+        let directory = directory.to_path_buf();
+        let image_extensions = self.image_extensions.clone();
+        let patterns = Arc::clone(&self.patterns);
+        let scan_options = self.scan_options;
+        let classifier = Arc::clone(&self.classifier);
+        let skim_keys = self.classifier.skim_keys();
+        let source = self.payload_source();
+
+        let result = tokio::task::spawn_blocking(move || {
+            let scan = Self::walk(source.storage.as_deref(), &directory, &image_extensions, scan_options);
+            let matches = scan.match_sidecars(&directory, &image_extensions, &patterns);
+            let tracker = ProgressTracker::new(progress, matches.len());
+
+            let sidecars: Vec<SidecarInfo> = matches
+                .into_par_iter()
+                .map(|sidecar_match| {
+                    let info = Self::load_sidecar_info(&source, &classifier, &skim_keys, sidecar_match);
+                    tracker.advance(&info.sidecar_path);
+                    info
+                })
+                .collect();
+
+            (scan, sidecars)
+        }).await?;
+        self.remember_hardlinks(&result.0.hardlinks);
+
+        Ok(result)
+    }
+
+    /// Find all sidecars in a directory whose contents match `expr` and the detection filter
+    /// Uses the same single parallel scan as `get_statistics`; unreadable sidecars never match
+    ///
+    /// Each sidecar is skimmed and matched on its outline, and only matches are decoded in full.
+    pub async fn query(&self, directory: &Path, expr: &QueryExpr) -> Result<Vec<QueryMatch>> {
+        let directory = directory.to_path_buf();
+        let image_extensions = self.image_extensions.clone();
+        let patterns = Arc::clone(&self.patterns);
+        let scan_options = self.scan_options;
+        let classifier = Arc::clone(&self.classifier);
+        let mut skim_keys = self.classifier.skim_keys();
+        skim_keys.extend(expr.skim_keys().keys());
+        let (expr, detection_filter) = (expr.clone(), self.detection_filter.clone());
+        let source = self.payload_source();
+
+        let matches = tokio::task::spawn_blocking(move || {
+            let scan = Self::walk(source.storage.as_deref(), &directory, &image_extensions, scan_options);
+            scan.match_sidecars(&directory, &image_extensions, &patterns)
+                .into_par_iter()
+                .filter_map(|sidecar_match| {
+                    let (sidecar, outline) = Self::load_matched(&source, &classifier, sidecar_match, |path, bytes| {
+                        Self::skim_sidecar(&source.formats, &skim_keys, path, bytes)
+                    });
+                    let outline = outline?;
+                    if !expr.matches(&sidecar, &outline) || !detection_filter.matches(&outline, Some(sidecar.operation.as_str())) {
+                        return None;
+                    }
+                    let data = source.load(&sidecar.sidecar_path).ok()?;
+                    Some(QueryMatch { sidecar, data })
+                })
+                .collect()
+        }).await?;
+
+        Ok(matches)
+    }
+
+    /// Load every decodable sidecar in a directory along with its contents
+    pub async fn records(&self, directory: &Path) -> Result<Vec<QueryMatch>> {
+        self.scan_decoded(directory, |sidecar, data| vec![QueryMatch { sidecar, data }]).await
+    }
+
+    /// Every decodable sidecar under `directory`, sent through the returned channel `batch_size`
+    /// records at a time as they are decoded
+    ///
+    /// At most two batches wait to be received, so a slow consumer holds the scan back instead
+    /// of the whole directory piling up in memory. Dropping the receiver stops the scan.
+    pub fn record_batches(&self, directory: &Path, batch_size: usize) -> tokio::sync::mpsc::Receiver<Vec<QueryMatch>> {
+        let (sender, receiver) = tokio::sync::mpsc::channel(2);
+        let directory = directory.to_path_buf();
+        let image_extensions = self.image_extensions.clone();
+        let patterns = Arc::clone(&self.patterns);
+        let scan_options = self.scan_options;
+        let classifier = Arc::clone(&self.classifier);
+        let source = self.payload_source();
+
+        tokio::task::spawn_blocking(move || {
+            let scan = Self::walk(source.storage.as_deref(), &directory, &image_extensions, scan_options);
+            let matches = scan.match_sidecars(&directory, &image_extensions, &patterns);
+            for chunk in matches.chunks(batch_size.max(1)) {
+                let batch: Vec<QueryMatch> = chunk
+                    .par_iter()
+                    .filter_map(|sidecar_match| match Self::load_sidecar(&source, &classifier, sidecar_match.clone()) {
+                        (sidecar, Some(data)) => Some(QueryMatch { sidecar, data }),
+                        (_, None) => None,
+                    })
+                    .collect();
+                if sender.blocking_send(batch).is_err() {
+                    break;
                 }
+            }
+        });
+        receiver
+    }
 
-                return Ok(Some(sidecar_info));
+    /// Extract every value matching a JSONPath from all sidecars in a directory
+    /// Produces one `(image, value)` pair per matched node; sidecars with no match are skipped
+    pub async fn extract(&self, directory: &Path, path: &ExtractPath) -> Result<Vec<ExtractedValue>> {
+        let path = path.clone();
+        self.scan_decoded(directory, move |sidecar, data| path.extract(&sidecar, &data)).await
+    }
+
+    /// Every sidecar under `directory` cut down to the fields of `projection`
+    ///
+    /// With `operations`, only sidecars holding one of them are included, as in `export`.
+    pub async fn project(&self, directory: &Path, projection: &Projection, operations: &[String]) -> Result<Vec<Value>> {
+        let (projection, operations) = (projection.clone(), operations.to_vec());
+        self.scan_decoded(directory, move |sidecar, data| {
+            if !operations.is_empty() && !operations.iter().any(|operation| operation == sidecar.operation.as_str()) {
+                return Vec::new();
             }
+            vec![projection.project_sidecar(&sidecar, &data)]
+        })
+        .await
+    }
+
+    /// Compute perceptual hashes for every image in a directory and store them under `image_hash`
+    /// Images that cannot be decoded are logged and skipped. Returns the number hashed.
+    pub async fn hash_images(&self, directory: &Path) -> Result<usize> {
+        self.ensure_writable("write image hashes")?;
+        let directory = directory.to_path_buf();
+        let image_extensions = self.image_extensions.clone();
+        let scan_options = self.scan_options;
+
+        let hashes = tokio::task::spawn_blocking(move || {
+            DirectoryScan::walk(&directory, &image_extensions, scan_options)
+                .images
+                .into_par_iter()
+                .filter_map(|path| match ImageHash::compute(&path) {
+                    Ok(hash) => Some((path, hash)),
+                    Err(e) => {
+                        tracing::warn!("Failed to hash {:?}: {}", path, e);
+                        None
+                    }
+                })
+                .collect::<Vec<_>>()
+        }).await?;
+
+        for (path, hash) in &hashes {
+            self.save_data(path, OperationType::ImageHash, hash.to_payload()).await?;
         }
 
-        Ok(None)
+        Ok(hashes.len())
     }
 
-    /// Find all sidecar files in a directory
-    pub async fn find_all_sidecars(&self, directory: &Path) -> Result<Vec<SidecarInfo>> {
-        let mut sidecars = Vec::new();
-        let mut processed_sidecars = std::collections::HashSet::new();
+    /// Group near-duplicate images using the hashes stored by `hash_images`
+    /// `threshold` is the largest pHash Hamming distance (out of 64 bits) treated as a duplicate
+    pub async fn find_duplicates(&self, directory: &Path, threshold: u32) -> Result<Vec<DuplicateGroup>> {
+        let hashes = self.scan_decoded(directory, |sidecar, data| {
+            data.get(OperationType::ImageHash.as_str())
+                .and_then(ImageHash::from_payload)
+                .map(|hash| vec![(sidecar.image_path, hash)])
+                .unwrap_or_default()
+        }).await?;
+
+        Ok(tokio::task::spawn_blocking(move || dedupe::cluster(hashes, threshold)).await?)
+    }
 
-        // Find all image files
-        let image_files = self.find_image_files(directory).await?;
+    /// Crop every detection recorded for `operation` out of the images in a directory
+    /// Images are decoded and cropped in parallel; images that fail to decode are logged
+    /// and skipped. Returns the crops written, ordered by image.
+    pub async fn extract_crops(&self, directory: &Path, operation: OperationType, output_dir: &Path) -> Result<Vec<Crop>> {
+        fs::create_dir_all(output_dir).await?;
+
+        let targets = self.scan_decoded(directory, move |sidecar, data| {
+            let payload = data.get(operation.as_str())
+                .or_else(|| (sidecar.operation == operation).then(|| sidecar.operation_payload(&data)));
+            match payload.and_then(detections_in) {
+                Some(detections) if !detections.is_empty() => vec![(sidecar.image_path, detections.clone())],
+                _ => Vec::new(),
+            }
+        }).await?;
+
+        // Names are reserved up front, in scan order, so they are unique and stable across runs
+        let mut taken = HashSet::new();
+        let targets: Vec<_> = targets
+            .into_iter()
+            .map(|(image_path, detections)| {
+                let names = crops::reserve_names(&image_path, &detections, &mut taken);
+                (image_path, detections, names)
+            })
+            .collect();
+
+        let output_dir = output_dir.to_path_buf();
+        let written = tokio::task::spawn_blocking(move || {
+            targets
+                .par_iter()
+                .flat_map_iter(|(image_path, detections, names)| {
+                    crops::crop_detections(image_path, detections, names, &output_dir).unwrap_or_else(|e| {
+                        tracing::warn!("Failed to crop {:?}: {}", image_path, e);
+                        Vec::new()
+                    })
+                })
+                .collect()
+        }).await?;
+
+        Ok(written)
+    }
+
+    /// Rewrite every detection bbox in a directory into one coordinate convention
+    ///
+    /// Each image's header is read for its dimensions, which are used both to convert
+    /// between pixel and normalized coordinates and to reject boxes that are empty or
+    /// fall outside the image. Rejected boxes are left untouched and reported. Sidecars
+    /// keep their format; with `dry_run` nothing is written.
+    pub async fn normalize_boxes(&self, directory: &Path, convention: BoxConvention, dry_run: bool) -> Result<NormalizeReport> {
+        if !dry_run {
+            self.ensure_writable("rewrite bounding boxes")?;
+        }
+        let sidecars = self.scan_decoded(directory, |sidecar, data| {
+            vec![(sidecar.image_path, sidecar.sidecar_path, data)]
+        }).await?;
+        let format_manager = Arc::clone(&self.format_manager);
+        let signer = self.signer.clone();
+
+        let converted = tokio::task::spawn_blocking(move || {
+            sidecars
+                .into_par_iter()
+                .map(|(image_path, sidecar_path, mut data)| {
+                    let mut report = NormalizeReport::default();
+                    let Ok(dimensions) = image::image_dimensions(&image_path) else {
+                        report.unreadable_images.push(image_path);
+                        return Ok((report, None));
+                    };
+
+                    let converted = normalize_sidecar_boxes(&sidecar_path, &mut data, convention, dimensions, &mut report);
+                    if converted == 0 {
+                        return Ok((report, None));
+                    }
+                    report.sidecars_updated += 1;
+                    if dry_run {
+                        return Ok((report, None));
+                    }
+                    if let Some(signer) = &signer {
+                        signer.sign_all(&signing::image_identity(&image_path), &mut data)?;
+                    }
+                    let bytes = Self::encode_sidecar(&format_manager, &sidecar_path, &data)?;
+                    Ok((report, Some((image_path, sidecar_path, bytes))))
+                })
+                .collect::<Result<Vec<_>>>()
+        }).await??;
+
+        let mut total = NormalizeReport::default();
+        for (report, rewrite) in converted {
+            total.merge(report);
+            if let Some((image_path, sidecar_path, bytes)) = rewrite {
+                self.write_file(&sidecar_path, &bytes).await?;
+                self.notify(EventKind::Updated, Some(&image_path), &sidecar_path, None);
+                self.audit(Mutation::Write, &sidecar_path, None, None)?;
+            }
+        }
+        Ok(total)
+    }
+
+    /// Fix recoverable corruption in every sidecar under a directory
+    ///
+    /// Handles trailing garbage after the JSON document, invalid UTF-8, double-encoded
+    /// JSON strings, a missing `sidecar_info` block and JSON content stored under a
+    /// binary extension (moved to `.json`). Every change is logged. Returns one record
+    /// per sidecar that was changed or could not be repaired; with `dry_run` nothing is
+    /// written.
+    pub async fn repair(&self, directory: &Path, dry_run: bool) -> Result<Vec<RepairRecord>> {
+        if !dry_run {
+            self.ensure_writable("repair sidecars")?;
+        }
+        let directory = directory.to_path_buf();
+        let image_extensions = self.image_extensions.clone();
+        let scan_options = self.scan_options;
+        let format_manager = Arc::clone(&self.format_manager);
+
+        let records = tokio::task::spawn_blocking(move || {
+            DirectoryScan::walk(&directory, &image_extensions, scan_options)
+                .sidecars
+                .into_par_iter()
+                .filter_map(|sidecar_path| Self::repair_file(&format_manager, sidecar_path, dry_run))
+                .collect::<Vec<_>>()
+        }).await?;
+
+        if !dry_run {
+            for record in records.iter().filter(|record| record.error.is_none()) {
+                self.audit(Mutation::Repair, &record.sidecar_path, record.new_path.as_deref(), None)?;
+            }
+        }
+        Ok(records)
+    }
+
+    /// Strip or hash the fields matched by `rules` in every sidecar under `directory`
+    /// With `output`, a redacted copy of every sidecar is written below it at the same relative
+    /// path and the originals are left alone; otherwise changed sidecars are rewritten in place.
+    /// Blob pointers are expanded first, so redacted payloads are stored inline. Hashed fields
+    /// are keyed with `key`. Returns one record per sidecar that changed or failed.
+    pub async fn redact(&self, directory: &Path, rules: &[RedactRule], key: &RedactKey, output: Option<&Path>, dry_run: bool) -> Result<Vec<RedactionRecord>> {
+        if output.is_none() && !dry_run {
+            self.ensure_writable("redact sidecars")?;
+        }
+        let mut records = Vec::new();
+        for sidecar_path in self.find_sidecar_files(directory).await? {
+            let mut record = RedactionRecord {
+                output_path: output.map(|output| output.join(manifest::relative_path(directory, &sidecar_path))),
+                sidecar_path,
+                ..Default::default()
+            };
+            if let Err(e) = self.redact_file(rules, key, &mut record, dry_run).await {
+                record.error = Some(e.to_string());
+            }
+            if record.is_changed() || record.error.is_some() {
+                records.push(record);
+            }
+        }
+        Ok(records)
+    }
+
+    async fn redact_file(&self, rules: &[RedactRule], key: &RedactKey, record: &mut RedactionRecord, dry_run: bool) -> Result<()> {
+        let mut data = self.load_sidecar_data(&record.sidecar_path).await?;
+        if let Some(object) = data.as_object_mut() {
+            for (_, payload) in object.iter_mut().filter(|(key, _)| *key != "sidecar_info") {
+                *payload = self.resolve_payload(payload)?;
+            }
+        }
+        redact::redact(&mut data, rules, key, record);
+        if dry_run || (record.output_path.is_none() && !record.is_changed()) {
+            return Ok(());
+        }
+
+        let target = record.output_path.clone().unwrap_or_else(|| record.sidecar_path.clone());
+        if self.storage.is_none() {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).await?;
+            }
+        }
+        self.write_file(&target, &self.encode(&target, &data)?).await?;
+        let kind = if record.output_path.is_some() { EventKind::Created } else { EventKind::Updated };
+        self.notify(kind, None, &target, None);
+        self.audit(Mutation::Write, &target, None, None)
+    }
+
+    /// Checksum every sidecar under `directory` into a manifest
+    pub async fn create_manifest(&self, directory: &Path) -> Result<Manifest> {
+        let entries = self.manifest_entries(directory).await?;
+        Ok(Manifest::new(directory.to_path_buf(), entries))
+    }
+
+    /// Report sidecars added, removed or modified under `directory` since `manifest` was created
+    pub async fn verify_manifest(&self, directory: &Path, manifest: &Manifest) -> Result<ManifestVerification> {
+        let entries = self.manifest_entries(directory).await?;
+        Ok(manifest.verify(entries))
+    }
+
+    /// Record every sidecar under `directory` as snapshot `name` in `store`
+    pub async fn create_snapshot(&self, directory: &Path, store: &SnapshotStore, name: &str) -> Result<SnapshotSummary> {
+        let manifest = self.create_manifest(directory).await?;
+        let (store, directory, name) = (store.clone(), directory.to_path_buf(), name.to_string());
+        tokio::task::spawn_blocking(move || store.create(&name, &directory, &manifest)).await?
+    }
 
-        // Process each image file
-        for image_file in image_files {
-            if let Some(sidecar_info) = self.find_sidecar_for_image(&image_file).await? {
-                if processed_sidecars.insert(sidecar_info.sidecar_path.clone()) {
-                    sidecars.push(sidecar_info);
+    /// Put the sidecars under `directory` back as they were in snapshot `name`
+    /// Sidecars changed or deleted since are written back from the snapshot and sidecars created
+    /// since are removed. Every copy is checked before anything is written.
+    pub async fn restore_snapshot(&self, directory: &Path, store: &SnapshotStore, name: &str) -> Result<RestoreSummary> {
+        self.ensure_writable("restore a snapshot")?;
+        let manifest = store.manifest(name)?;
+        let verification = self.verify_manifest(directory, &manifest).await?;
+        let checksums: HashMap<&str, &str> = manifest.entries.iter().map(|entry| (entry.path.as_str(), entry.checksum.as_str())).collect();
+
+        let mut copies = Vec::new();
+        for path in verification.modified.into_iter().map(|entry| entry.path).chain(verification.removed) {
+            let bytes = store.read(name, &path, checksums[path.as_str()])?;
+            copies.push((path, bytes));
+        }
+
+        let mut summary = RestoreSummary { unchanged: verification.unchanged, ..Default::default() };
+        for (path, bytes) in copies {
+            let target = directory.join(&path);
+            if self.storage.is_none() {
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent).await?;
                 }
             }
+            let kind = if self.file_exists(&target) { EventKind::Updated } else { EventKind::Created };
+            self.write_file(&target, &bytes).await?;
+            self.notify(kind, None, &target, None);
+            self.audit(Mutation::Write, &target, None, None)?;
+            summary.restored.push(path);
         }
+        for path in verification.added {
+            let target = directory.join(&path);
+            self.remove_file(&target).await?;
+            self.notify(EventKind::Deleted, None, &target, None);
+            self.audit(Mutation::Delete, &target, None, None)?;
+            summary.removed.push(path);
+        }
+        summary.restored.sort();
+        Ok(summary)
+    }
 
-        // Also look for pattern-based sidecars
-        let pattern_sidecars = self.find_pattern_sidecars(directory).await?;
-        for sidecar_info in pattern_sidecars {
-            if processed_sidecars.insert(sidecar_info.sidecar_path.clone()) {
-                sidecars.push(sidecar_info);
+    /// Compare sidecar coverage and operation payloads between two directories
+    /// Images are matched by their path relative to each directory
+    pub async fn diff_directories(&self, left: &Path, right: &Path) -> Result<DirectoryDiff> {
+        let (left_images, right_images) = tokio::try_join!(
+            self.operation_payloads(left),
+            self.operation_payloads(right),
+        )?;
+        Ok(DirectoryDiff::compute(left.to_path_buf(), right.to_path_buf(), left_images, right_images))
+    }
+
+    /// Report sidecars under `directory`, a path inside a git work tree, whose operations
+    /// differ from `git_ref`
+    ///
+    /// Untracked sidecars count as added. Changes to `sidecar_info` alone are not reported.
+    pub async fn git_status(&self, directory: &Path, git_ref: &str) -> Result<GitStatus> {
+        let mut working = BTreeMap::new();
+        for path in self.find_sidecar_files(directory).await? {
+            let relative = manifest::relative_path(directory, &path);
+            if relative.split('/').any(|component| component == ".git") {
+                continue;
             }
+            working.insert(relative, self.load_sidecar_data(&path).await?);
         }
 
-        Ok(sidecars)
+        let (root, reference) = (directory.to_path_buf(), git_ref.to_string());
+        let committed = tokio::task::spawn_blocking(move || -> Result<Vec<(String, Vec<u8>)>> {
+            let (paths, objects): (Vec<String>, Vec<String>) = git::tracked_files(&root, &reference)?
+                .into_iter()
+                .filter(|(path, _)| SidecarFormat::from_path(Path::new(path)).is_some())
+                .unzip();
+            Ok(paths.into_iter().zip(git::blobs(&root, &objects)?).collect())
+        }).await??;
+        let mut committed: BTreeMap<String, Value> = committed
+            .into_iter()
+            .map(|(path, bytes)| Ok((path.clone(), Self::decode_sidecar(&self.format_manager, Path::new(&path), &bytes)?)))
+            .collect::<Result<_>>()?;
+
+        let mut files = Vec::new();
+        for (path, data) in &working {
+            files.extend(GitFileStatus::compare(path.clone(), committed.remove(path).as_ref(), Some(data)));
+        }
+        for (path, data) in &committed {
+            files.extend(GitFileStatus::compare(path.clone(), Some(data), None));
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(GitStatus { git_ref: git_ref.to_string(), files })
+    }
+
+    /// The sidecars under `directory` with their md5 checksums, as DVC would track them
+    pub async fn dvc_directory(&self, directory: &Path) -> Result<DvcDirectory> {
+        let directory = directory.to_path_buf();
+        let image_extensions = self.image_extensions.clone();
+        let scan_options = self.scan_options;
+
+        tokio::task::spawn_blocking(move || {
+            let files = DirectoryScan::walk(&directory, &image_extensions, scan_options)
+                .sidecars
+                .into_par_iter()
+                .map(|sidecar_path| {
+                    let bytes = std::fs::read(&sidecar_path)?;
+                    let entry = DvcEntry { md5: dvc::md5_hex(&bytes), relpath: manifest::relative_path(&directory, &sidecar_path) };
+                    Ok((entry, bytes.len() as u64))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let size = files.iter().map(|(_, size)| size).sum();
+            Ok(DvcDirectory::new(files.into_iter().map(|(entry, _)| entry).collect(), size))
+        }).await?
+    }
+
+    /// Write a `.dvc` file at `output` whose single output is the sidecar set under `directory`
+    ///
+    /// `directory` must lie under the folder holding `output`, since DVC paths are relative to it.
+    /// Only sidecars are hashed, so `dvc status` agrees only if the directory holds nothing else.
+    /// An existing `.dvc` file keeps the keys this crate does not write.
+    pub async fn export_dvc(&self, directory: &Path, output: &Path) -> Result<DvcOutput> {
+        let base = output.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let (base, target) = (base.canonicalize()?, directory.canonicalize()?);
+        if !target.starts_with(&base) || target == base {
+            return Err(SidecarError::Schema(format!("{} must be inside {}", directory.display(), base.display())));
+        }
+        let entry = self.dvc_directory(directory).await?.output(manifest::relative_path(&base, &target));
+        let mut file = if output.exists() {
+            DvcFile::load(output)?
+        } else {
+            DvcFile { outs: Vec::new(), extra: Default::default() }
+        };
+        file.replace_outputs(entry.clone());
+        file.save(output)?;
+        Ok(entry)
+    }
+
+    /// Compare the sidecars under `directory` with its output in a `.dvc` file or `dvc.lock`
+    ///
+    /// Added, removed and modified files are listed when the recorded listing is in the DVC cache.
+    pub async fn verify_dvc(&self, directory: &Path, lock: &Path) -> Result<DvcVerification> {
+        let base = lock.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let target = directory.canonicalize()?;
+        let output = dvc::read_outputs(lock)?
+            .into_iter()
+            .find(|output| base.join(&output.path).canonicalize().is_ok_and(|path| path == target))
+            .ok_or_else(|| SidecarError::Schema(format!("{} has no output for {}", lock.display(), directory.display())))?;
+        let actual = self.dvc_directory(directory).await?;
+        let recorded = dvc::cached_listing(&base.canonicalize()?, &output.md5);
+        Ok(DvcVerification::compare(directory.to_path_buf(), &output, &actual, recorded))
+    }
+
+    /// Operation payloads of every decodable sidecar, keyed by relative image path
+    async fn operation_payloads(&self, directory: &Path) -> Result<BTreeMap<String, OperationPayloads>> {
+        let root = directory.to_path_buf();
+        let classifier = Arc::clone(&self.classifier);
+
+        let images = self.scan_decoded(directory, move |sidecar, data| {
+            let payloads: OperationPayloads = Self::operations_in(&classifier, &data)
+                .into_iter()
+                .map(|operation| {
+                    let payload = data.get(&operation).unwrap_or_else(|| sidecar.operation_payload(&data)).clone();
+                    (operation, payload)
+                })
+                .collect();
+            vec![(manifest::relative_path(&root, &sidecar.image_path), payloads)]
+        }).await?;
+
+        Ok(images.into_iter().collect())
+    }
+
+    /// Merge two edited copies of a sidecar into `output`, in the format its extension names
+    ///
+    /// With `base`, this is a three-way merge: fields changed on only one side are taken from
+    /// that side and conflicting fields keep `ours`. Without `base`, `theirs` is overlaid on
+    /// `ours` and nothing is reported as a conflict. The output is written like any other
+    /// sidecar write, so read-only mode refuses it and the mutation log records it; merged
+    /// payloads keep the provenance they were produced with.
+    pub async fn merge_sidecars(
+        &self,
+        base: Option<&Path>,
+        ours: &Path,
+        theirs: &Path,
+        output: &Path,
+    ) -> Result<MergeResult> {
+        self.ensure_writable("merge sidecars")?;
+        let ours = self.read_sidecar(ours).await?;
+        let theirs = self.read_sidecar(theirs).await?;
+
+        let result = match base {
+            Some(base) => JsonUtils::three_way_merge(&self.read_sidecar(base).await?, &ours, &theirs),
+            None => MergeResult { merged: JsonUtils::merge_values(&ours, &theirs), conflicts: Vec::new() },
+        };
+
+        let bytes = Self::encode_sidecar(&self.format_manager, output, &result.merged)?;
+        let kind = if self.file_exists(output) { EventKind::Updated } else { EventKind::Created };
+        self.write_file(output, &bytes).await?;
+        self.notify(kind, None, output, None);
+        self.audit(Mutation::Write, output, None, None)?;
+        Ok(result)
+    }
+
+    /// Read and decode any single sidecar file
+    async fn read_sidecar(&self, sidecar_path: &Path) -> Result<Value> {
+        let bytes = self.read_file(sidecar_path).await?;
+        Self::decode_sidecar(&self.format_manager, sidecar_path, &bytes)
+    }
+
+    async fn manifest_entries(&self, directory: &Path) -> Result<Vec<ManifestEntry>> {
+        let directory = directory.to_path_buf();
+        let image_extensions = self.image_extensions.clone();
+        let scan_options = self.scan_options;
+        let format_manager = Arc::clone(&self.format_manager);
+        let classifier = Arc::clone(&self.classifier);
+        let max_sidecar_size = self.max_sidecar_size;
+
+        tokio::task::spawn_blocking(move || {
+            DirectoryScan::walk(&directory, &image_extensions, scan_options)
+                .sidecars
+                .into_par_iter()
+                .map(|sidecar_path| {
+                    // Read once for both the checksum and the operations; every sidecar is hashed,
+                    // but only those under the size limit are decoded
+                    let bytes = std::fs::read(&sidecar_path)?;
+                    let operations = Some(&bytes)
+                        .filter(|bytes| bytes.len() as u64 <= max_sidecar_size)
+                        .and_then(|bytes| Self::decode_sidecar(&format_manager, &sidecar_path, bytes).ok())
+                        .map(|data| Self::operations_in(&classifier, &data))
+                        .unwrap_or_default();
+                    Ok(ManifestEntry::from_bytes(&directory, &sidecar_path, &bytes, operations))
+                })
+                .collect()
+        }).await?
+    }
+
+    /// Every known operation recorded in a decoded sidecar, sorted
+    fn operations_in(classifier: &OperationClassifier, data: &Value) -> Vec<String> {
+        let mut operations: Vec<String> = data.as_object()
+            .map(|object| {
+                object.keys()
+                    .filter(|key| OperationType::from_str(key) != OperationType::Unknown)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let recorded = Self::operation_from_data(classifier, data);
+        if recorded != OperationType::Unknown {
+            operations.push(recorded.as_str().to_string());
+        }
+
+        operations.sort();
+        operations.dedup();
+        operations
+    }
+
+    /// Repair one sidecar, returning `None` when it is already healthy
+    fn repair_file(format_manager: &FormatManager, sidecar_path: PathBuf, dry_run: bool) -> Option<RepairRecord> {
+        let mut record = RepairRecord {
+            sidecar_path,
+            new_path: None,
+            actions: Vec::new(),
+            error: None,
+        };
+
+        let read = std::fs::read(&record.sidecar_path)
+            .map(|bytes| (bytes, std::fs::metadata(&record.sidecar_path).and_then(|m| m.modified()).ok()));
+        let repaired = match read {
+            Ok((bytes, modified)) => repair::repair_bytes(format_manager, &record.sidecar_path, &bytes, modified),
+            Err(e) => Err(e.to_string()),
+        };
+        let repaired = match repaired {
+            Ok(repaired) if repaired.actions.is_empty() => return None,
+            Ok(repaired) => repaired,
+            Err(reason) => {
+                tracing::warn!("Cannot repair {:?}: {}", record.sidecar_path, reason);
+                record.error = Some(reason);
+                return Some(record);
+            }
+        };
+
+        let target = record.sidecar_path.with_extension(repaired.format.extension());
+        if target != record.sidecar_path {
+            if target.exists() {
+                record.error = Some(format!("{} already exists", target.display()));
+                return Some(record);
+            }
+            record.new_path = Some(target.clone());
+        }
+
+        for action in &repaired.actions {
+            tracing::info!("{}{:?}: {}", if dry_run { "[dry run] " } else { "" }, record.sidecar_path, action);
+        }
+        record.actions = repaired.actions;
+
+        if !dry_run {
+            let written = Self::encode_sidecar(format_manager, &target, &repaired.data)
+                .and_then(|bytes| Ok(std::fs::write(&target, bytes)?))
+                .and_then(|_| {
+                    if target != record.sidecar_path {
+                        std::fs::remove_file(&record.sidecar_path)?;
+                    }
+                    Ok(())
+                });
+            if let Err(e) = written {
+                record.error = Some(e.to_string());
+            }
+        }
+
+        Some(record)
+    }
+
+    /// Build a nearest-neighbor index over every face encoding in a directory
+    pub async fn build_vector_index(&self, directory: &Path) -> Result<VectorIndex> {
+        let sidecars = self.scan_decoded(directory, |sidecar, data| vec![(sidecar.image_path, data)]).await?;
+        Ok(tokio::task::spawn_blocking(move || VectorIndex::build(sidecars)).await?)
+    }
+
+    /// Scan `directory` once and map every decodable sidecar through `f` in parallel
+    /// Output follows the scan's sorted sidecar order
+    async fn scan_decoded<T, F>(&self, directory: &Path, f: F) -> Result<Vec<T>>
+    where
+        T: Send + 'static,
+        F: Fn(SidecarInfo, Value) -> Vec<T> + Send + Sync + 'static,
+    {
+        let directory = directory.to_path_buf();
+        let image_extensions = self.image_extensions.clone();
+        let patterns = Arc::clone(&self.patterns);
+        let scan_options = self.scan_options;
+        let classifier = Arc::clone(&self.classifier);
+        let source = self.payload_source();
+
+        let results = tokio::task::spawn_blocking(move || {
+            let scan = Self::walk(source.storage.as_deref(), &directory, &image_extensions, scan_options);
+            scan.match_sidecars(&directory, &image_extensions, &patterns)
+                .into_par_iter()
+                .flat_map_iter(|sidecar_match| {
+                    match Self::load_sidecar(&source, &classifier, sidecar_match) {
+                        (sidecar, Some(data)) => f(sidecar, data),
+                        (_, None) => Vec::new(),
+                    }
+                })
+                .collect()
+        }).await?;
+
+        Ok(results)
     }
 
     /// Create a new sidecar file for an image using the default format
@@ -139,23 +1321,36 @@ impl SidecarManager {
         operation: OperationType,
         data: Value,
     ) -> Result<SidecarInfo> {
+        self.ensure_writable("save sidecar data")?;
         // Resolve symlink if needed
         let (actual_image_path, symlink_info) = self.resolve_symlink(image_path).await?;
 
-        // Create sidecar path next to actual image with binary format
-        let sidecar_path = actual_image_path.with_extension("bin");
+        // Create sidecar path next to the image the symlink policy picks, with binary format, or JSON for git
+        let bases = self.sidecar_bases(image_path, &actual_image_path, symlink_info.is_some());
+        let sidecar_path = bases[0].with_extension(self.save_format().extension());
 
-        // Load existing data if sidecar exists, otherwise start with empty
-        let mut existing_data = if sidecar_path.exists() {
-            self.load_sidecar_data(&sidecar_path).await.unwrap_or_else(|_| Value::Object(serde_json::Map::new()))
-        } else {
-            Value::Object(serde_json::Map::new())
+        // Load existing data if a sidecar exists at any base, otherwise start with empty
+        let existed = self.file_exists(&sidecar_path);
+        let stored_path = bases.iter().map(|base| base.with_extension(self.save_format().extension())).find(|path| self.file_exists(path));
+        let mut existing_data = match stored_path {
+            Some(stored_path) => self.load_sidecar_data(&stored_path).await.unwrap_or_else(|_| Value::Object(serde_json::Map::new())),
+            None => Value::Object(serde_json::Map::new()),
         };
 
+        let fingerprint = Self::image_fingerprint(&actual_image_path, ImageFingerprint::from_sidecar(&existing_data)).await;
+
         // Merge the new data into existing data
         if let Some(obj) = existing_data.as_object_mut() {
-            // Insert or update the operation data
-            obj.insert(operation.as_str().to_string(), data);
+            // Insert or update the operation data according to the merge strategy
+            let existing = obj.get(operation.as_str()).map(|payload| self.resolve_payload(payload)).transpose()?;
+            let payload = self.merge_strategy.apply(existing.as_ref(), data);
+            let image = fingerprint.as_ref().map_or("", |fingerprint| fingerprint.xxh3.as_str());
+            let signature = self.signer().map(|signer| signer.sign(image, operation.as_str(), &payload));
+            let payload = match self.blob_store() {
+                Some(store) => blobs::pointer(&store.put(&payload)?),
+                None => payload,
+            };
+            obj.insert(operation.as_str().to_string(), payload);
 
             // Update sidecar_info if it exists, otherwise create new
             if let Some(sidecar_info) = obj.get_mut("sidecar_info") {
@@ -173,16 +1368,14 @@ impl SidecarManager {
                     serde_json::Value::String(Utc::now().to_rfc3339()));
                 sidecar_info.insert("last_operation".to_string(), 
                     serde_json::Value::String(operation.as_str().to_string()));
-                sidecar_info.insert("image_path".to_string(), 
-                    serde_json::Value::String(actual_image_path.to_string_lossy().to_string()));
-                sidecar_info.insert("symlink_path".to_string(), 
-                    serde_json::Value::String(image_path.to_string_lossy().to_string()));
+                sidecar_info.insert("image_path".to_string(), paths::path_to_json(&actual_image_path));
+                sidecar_info.insert("symlink_path".to_string(), paths::path_to_json(image_path));
                 
                 // Serialize symlink_info if present
                 if let Some(symlink) = &symlink_info {
                     sidecar_info.insert("symlink_info".to_string(), serde_json::json!({
-                        "symlink_path": symlink.symlink_path.to_string_lossy(),
-                        "target_path": symlink.target_path.to_string_lossy(),
+                        "symlink_path": paths::path_to_json(&symlink.symlink_path),
+                        "target_path": paths::path_to_json(&symlink.target_path),
                         "is_symlink": symlink.is_symlink,
                         "broken": symlink.broken
                     }));
@@ -190,14 +1383,42 @@ impl SidecarManager {
                 
                 obj.insert("sidecar_info".to_string(), Value::Object(sidecar_info));
             }
+
+            // Remember when and by whom each payload was written, so retention can expire them
+            // separately and every payload can be traced to its producer
+            if let Some(sidecar_obj) = obj.get_mut("sidecar_info").and_then(Value::as_object_mut) {
+                let times = sidecar_obj.entry("operation_updated").or_insert_with(|| Value::Object(serde_json::Map::new()));
+                if let Some(times) = times.as_object_mut() {
+                    times.insert(operation.as_str().to_string(), Value::String(Utc::now().to_rfc3339()));
+                }
+                // What the payloads were computed from, so a later change to the image can be noticed
+                if let Some(fingerprint) = &fingerprint {
+                    sidecar_obj.insert(IMAGE_FINGERPRINT_KEY.to_string(), serde_json::to_value(fingerprint)?);
+                }
+                let producers = sidecar_obj.entry("provenance").or_insert_with(|| Value::Object(serde_json::Map::new()));
+                if let Some(producers) = producers.as_object_mut() {
+                    producers.insert(operation.as_str().to_string(), serde_json::to_value(&self.provenance)?);
+                }
+                // Without a signer the old signature no longer matches, so it is dropped
+                let signatures = sidecar_obj.entry(SIGNATURES_KEY).or_insert_with(|| Value::Object(serde_json::Map::new()));
+                if let Some(signatures) = signatures.as_object_mut() {
+                    match &signature {
+                        Some(signature) => signatures.insert(operation.as_str().to_string(), serde_json::to_value(signature)?),
+                        None => signatures.remove(operation.as_str()),
+                    };
+                }
+            }
         }
 
-        // Serialize using binary format
-        let serializer = self.format_manager.get_serializer(SidecarFormat::Binary);
-        let content_bytes = serializer.serialize(&existing_data)
-            .map_err(|e| SidecarError::SerializationError(e.to_string()))?;
+        // Serialize using binary format, or JSON for git
+        let serializer = self.format_manager.get_serializer(self.save_format());
+        let content_bytes = serializer.serialize(&existing_data)?;
         
-        fs::write(&sidecar_path, &content_bytes).await?;
+        self.write_file(&sidecar_path, &content_bytes).await?;
+        let kind = if existed { EventKind::Updated } else { EventKind::Created };
+        self.notify(kind, Some(image_path), &sidecar_path, Some(operation.as_str()));
+        self.audit(Mutation::Write, &sidecar_path, None, Some(operation.as_str()))?;
+        self.write_copies(image_path, &bases[1..], self.save_format(), &content_bytes, operation.as_str()).await?;
 
         let mut sidecar_info = SidecarInfo::new(
             image_path.to_path_buf(),
@@ -207,6 +1428,7 @@ impl SidecarManager {
         );
         sidecar_info.data_size = content_bytes.len() as u64;
         sidecar_info.is_valid = true;
+        sidecar_info.extract_payload_metrics(&existing_data);
 
         Ok(sidecar_info)
     }
@@ -216,22 +1438,20 @@ impl SidecarManager {
     /// Returns empty dict if no sidecar exists (does NOT raise error)
     pub async fn read_data(&self, image_path: &Path) -> Result<Value> {
         // Resolve symlink if needed
-        let (actual_image_path, _) = self.resolve_symlink(image_path).await?;
-
-        // Try formats in order of efficiency: bin -> rkyv -> json
-        let formats_to_try = [SidecarFormat::Binary, SidecarFormat::Rkyv, SidecarFormat::Json];
-        
-        for format in &formats_to_try {
-            let sidecar_path = actual_image_path.with_extension(format.extension());
-            
-            if sidecar_path.exists() {
-                // Load and return the sidecar data
-                return self.load_sidecar_data(&sidecar_path).await;
-            }
-        }
+        let (actual_image_path, symlink_info) = self.resolve_symlink(image_path).await?;
+        let bases = self.sidecar_bases(image_path, &actual_image_path, symlink_info.is_some());
 
         // Return empty dict if no sidecar found
-        Ok(Value::Object(serde_json::Map::new()))
+        let Some(sidecar_path) = self.existing_sidecar(&bases) else {
+            return Ok(Value::Object(serde_json::Map::new()));
+        };
+
+        // Load and return the sidecar data, with payloads kept in the blob store filled in
+        let mut data = self.load_sidecar_data(&sidecar_path).await?;
+        if let Some(store) = self.blob_store() {
+            blobs::resolve_pointers(store, &sidecar_path, &mut data)?;
+        }
+        Ok(data)
     }
 
     /// Create a new sidecar file for an image with a specific format
@@ -242,29 +1462,45 @@ impl SidecarManager {
         data: Value,
         format: SidecarFormat,
     ) -> Result<SidecarInfo> {
+        self.ensure_writable("create sidecars")?;
         // Resolve symlink if needed
         let (actual_image_path, symlink_info) = self.resolve_symlink(image_path).await?;
 
-        // Create sidecar path next to actual image with the specified format
-        let sidecar_path = actual_image_path.with_extension(format.extension());
+        // Create sidecar path next to the image the symlink policy picks, with the specified format
+        let bases = self.sidecar_bases(image_path, &actual_image_path, symlink_info.is_some());
+        let sidecar_path = bases[0].with_extension(format.extension());
 
         // Add metadata to data
         let mut enhanced_data = serde_json::Map::new();
         enhanced_data.insert("sidecar_info".to_string(), serde_json::json!({
             "operation_type": operation.as_str(),
             "created_at": Utc::now().to_rfc3339(),
-            "image_path": actual_image_path.to_string_lossy(),
-            "symlink_path": image_path.to_string_lossy(),
-            "symlink_info": symlink_info
+            "image_path": paths::path_to_json(&actual_image_path),
+            "symlink_path": paths::path_to_json(image_path),
+            "symlink_info": symlink_info,
+            "provenance": { operation.as_str(): &self.provenance }
         }));
+        let fingerprint = Self::image_fingerprint(&actual_image_path, None).await;
+        if let (Some(fingerprint), Some(info)) = (&fingerprint, enhanced_data.get_mut("sidecar_info").and_then(Value::as_object_mut)) {
+            info.insert(IMAGE_FINGERPRINT_KEY.to_string(), serde_json::to_value(fingerprint)?);
+        }
+        if let (Some(signer), Some(info)) = (self.signer(), enhanced_data.get_mut("sidecar_info").and_then(Value::as_object_mut)) {
+            let image = fingerprint.as_ref().map_or("", |fingerprint| fingerprint.xxh3.as_str());
+            info.insert(SIGNATURES_KEY.to_string(), serde_json::json!({ "data": signer.sign(image, "data", &data) }));
+        }
         enhanced_data.insert("data".to_string(), data);
 
+        let enhanced_data = serde_json::Value::Object(enhanced_data);
+
         // Serialize using the specified format
         let serializer = self.format_manager.get_serializer(format);
-        let content_bytes = serializer.serialize(&serde_json::Value::Object(enhanced_data))
-            .map_err(|e| SidecarError::SerializationError(e.to_string()))?;
+        let content_bytes = serializer.serialize(&enhanced_data)?;
         
-        fs::write(&sidecar_path, &content_bytes).await?;
+        let kind = if self.file_exists(&sidecar_path) { EventKind::Updated } else { EventKind::Created };
+        self.write_file(&sidecar_path, &content_bytes).await?;
+        self.notify(kind, Some(image_path), &sidecar_path, Some(operation.as_str()));
+        self.audit(Mutation::Write, &sidecar_path, None, Some(operation.as_str()))?;
+        self.write_copies(image_path, &bases[1..], format, &content_bytes, operation.as_str()).await?;
 
         let mut sidecar_info = SidecarInfo::new(
             image_path.to_path_buf(),
@@ -274,207 +1510,1498 @@ impl SidecarManager {
         );
         sidecar_info.data_size = content_bytes.len() as u64;
         sidecar_info.is_valid = true;
+        sidecar_info.extract_payload_metrics(&enhanced_data);
 
         Ok(sidecar_info)
     }
 
     /// Get comprehensive statistics about sidecar files in a directory
     pub async fn get_statistics(&self, directory: &Path) -> Result<StatisticsResult> {
+        self.get_statistics_with_progress(directory, None).await
+    }
+
+    /// Get statistics about sidecar files in a directory, reporting scan progress
+    pub async fn get_statistics_with_progress(
+        &self,
+        directory: &Path,
+        progress: Option<ProgressCallback>,
+    ) -> Result<StatisticsResult> {
+        let mut stats = StatisticsResult::new(directory.to_path_buf());
+        let (scan, sidecars) = self.scan_directory(directory, progress).await?;
+
+        stats.total_images = scan.images.len() as u32;
+        stats.symlink_count = scan.symlink_count;
+        stats.broken_symlinks = scan.broken_symlinks;
+        stats.hardlink_groups = scan.hardlinks;
+        stats.hardlinks_merged = self.scan_options.merge_hardlinks;
+        stats.sidecars = sidecars;
+        stats.summarize();
+
+        Ok(stats)
+    }
+
+    /// Get statistics for exactly the given sidecar files, without walking any directory
+    /// Each sidecar is paired with the image of the same stem next to it; `total_images`
+    /// counts the images found that way
+    pub async fn get_statistics_for_files(
+        &self,
+        sidecar_paths: &[PathBuf],
+        progress: Option<ProgressCallback>,
+    ) -> Result<StatisticsResult> {
+        let sidecar_paths = sidecar_paths.to_vec();
+        let image_extensions = self.image_extensions.clone();
+        let classifier = Arc::clone(&self.classifier);
+        let skim_keys = self.classifier.skim_keys();
+        let source = self.payload_source();
+        let mut stats = StatisticsResult::new(common_ancestor(
+            &sidecar_paths.iter().filter_map(|path| path.parent().map(Path::to_path_buf)).collect::<Vec<_>>(),
+        ));
+
+        let (images, sidecars) = tokio::task::spawn_blocking(move || {
+            let tracker = ProgressTracker::new(progress, sidecar_paths.len());
+            let matched: Vec<(bool, SidecarInfo)> = sidecar_paths
+                .into_par_iter()
+                .map(|sidecar_path| {
+                    let image_path = image_extensions
+                        .iter()
+                        .map(|ext| sidecar_path.with_extension(ext))
+                        .find(|candidate| match source.storage.as_deref() {
+                            Some(storage) => storage.exists(candidate),
+                            None => candidate.is_file(),
+                        });
+                    let found = image_path.is_some();
+                    let image_path = image_path.unwrap_or_else(|| sidecar_path.with_extension(""));
+                    let sidecar_match = SidecarMatch { image_path, sidecar_path, operation: None };
+                    let info = Self::load_sidecar_info(&source, &classifier, &skim_keys, sidecar_match);
+                    tracker.advance(&info.sidecar_path);
+                    (found, info)
+                })
+                .collect();
+            let images = matched.iter().filter(|(found, _)| *found).count();
+            (images, matched.into_iter().map(|(_, info)| info).collect::<Vec<_>>())
+        }).await?;
+
+        stats.total_images = images as u32;
+        stats.sidecars = sidecars;
+        stats.summarize();
+
+        Ok(stats)
+    }
+
+    /// Get statistics for several directories at once, scanned concurrently
+    /// Returns one result per directory, in the order given, plus an aggregate over all of them.
+    /// Repeated roots and roots inside another are dropped, so the aggregate counts each file once.
+    pub async fn get_statistics_multi(
+        &self,
+        directories: &[PathBuf],
+        progress: Option<ProgressCallback>,
+    ) -> Result<MultiRootStatistics> {
+        let directories = &paths::distinct_roots(directories);
+        let roots = futures::future::try_join_all(
+            directories
+                .iter()
+                .zip(split_progress(progress, directories.len()))
+                .map(|(directory, progress)| self.get_statistics_with_progress(directory, progress)),
+        ).await?;
+
+        Ok(MultiRootStatistics::new(roots))
+    }
+
+    /// Get statistics for a directory and write them to the `.sidecar-stats.json` badge at its root
+    ///
+    /// Sidecars whose size and modification time match the existing badge are not read again;
+    /// their cached metrics are used instead. The badge is a report, so read-only mode allows it.
+    pub async fn get_statistics_with_badge(
+        &self,
+        directory: &Path,
+        progress: Option<ProgressCallback>,
+    ) -> Result<(StatisticsResult, BadgeRefresh)> {
+        let badge_path = directory.join(STATS_BADGE_FILE);
+        let cached = StatsBadge::load(&badge_path).map(|badge| badge.files).unwrap_or_default();
+        let root = directory.to_path_buf();
+        let image_extensions = self.image_extensions.clone();
+        let patterns = Arc::clone(&self.patterns);
+        let scan_options = self.scan_options;
+        let classifier = Arc::clone(&self.classifier);
+        let skim_keys = self.classifier.skim_keys();
+        // The badge is kept beside the files on disk
+        let source = PayloadSource { storage: None, ..self.payload_source() };
+
+        let (scan, scanned) = tokio::task::spawn_blocking(move || {
+            let scan = DirectoryScan::walk(&root, &image_extensions, scan_options);
+            let matches = scan.match_sidecars(&root, &image_extensions, &patterns);
+            let tracker = ProgressTracker::new(progress, matches.len());
+
+            let scanned: Vec<(String, Option<FileStamp>, SidecarInfo, bool)> = matches
+                .into_par_iter()
+                .map(|sidecar_match| {
+                    let relative = manifest::relative_path(&root, &sidecar_match.sidecar_path);
+                    let stamp = FileStamp::of(&sidecar_match.sidecar_path);
+                    let (info, reused) = match cached.get(&relative).filter(|entry| Some(entry.stamp) == stamp) {
+                        Some(entry) => (entry.to_info(sidecar_match.image_path, sidecar_match.sidecar_path), true),
+                        None => (Self::load_sidecar_info(&source, &classifier, &skim_keys, sidecar_match), false),
+                    };
+                    tracker.advance(&info.sidecar_path);
+                    (relative, stamp, info, reused)
+                })
+                .collect();
+
+            (scan, scanned)
+        }).await?;
+
+        let mut refresh = BadgeRefresh { path: badge_path.clone(), ..Default::default() };
+        let mut files = BTreeMap::new();
         let mut stats = StatisticsResult::new(directory.to_path_buf());
-        let sidecars = self.find_all_sidecars(directory).await?;
-
-        // Count images (including symlinks)
-        let image_files = self.find_image_files(directory).await?;
-        let mut symlink_count = 0;
-        let mut broken_symlinks = 0;
-
-        for image_file in &image_files {
-            if image_file.is_symlink() {
-                symlink_count += 1;
-                if let Ok(metadata) = fs::symlink_metadata(image_file).await {
-                    if metadata.file_type().is_symlink() {
-                        if !image_file.exists() {
-                            broken_symlinks += 1;
+        for (relative, stamp, info, reused) in scanned {
+            if reused {
+                refresh.reused += 1;
+            } else {
+                refresh.read += 1;
+            }
+            // Without a stamp the sidecar cannot be checked next time, so it is not cached
+            if let Some(stamp) = stamp {
+                files.insert(relative, BadgeEntry::new(stamp, &info));
+            }
+            stats.sidecars.push(info);
+        }
+        stats.total_images = scan.images.len() as u32;
+        stats.symlink_count = scan.symlink_count;
+        stats.broken_symlinks = scan.broken_symlinks;
+        stats.hardlink_groups = scan.hardlinks;
+        stats.hardlinks_merged = self.scan_options.merge_hardlinks;
+        stats.summarize();
+
+        let badge = StatsBadge::new(&stats, files);
+        tokio::task::spawn_blocking(move || badge.save(&badge_path)).await??;
+        Ok((stats, refresh))
+    }
+
+    /// Report, for each of `operations`, the images under `directory` that no sidecar records it for
+    ///
+    /// An operation counts as done for an image when any of its sidecars holds a payload under
+    /// that key or records it as the sidecar's operation. Images without a sidecar miss them all.
+    /// Only images the manager's `FileSelection` admits are counted, listed in its order.
+    pub async fn coverage(&self, directory: &Path, operations: &[String]) -> Result<CoverageReport> {
+        let root = directory.to_path_buf();
+        let image_extensions = self.image_extensions.clone();
+        let patterns = Arc::clone(&self.patterns);
+        let scan_options = self.scan_options;
+        let classifier = Arc::clone(&self.classifier);
+        let selection = self.selection;
+        let operations = operations.to_vec();
+        let source = self.payload_source();
+
+        let report = tokio::task::spawn_blocking(move || {
+            let scan = Self::walk(source.storage.as_deref(), &root, &image_extensions, scan_options);
+            let mut images = scan.images.clone();
+            images.sort();
+            let images = selection.apply(images);
+            let found: Vec<(PathBuf, Vec<String>)> = scan.match_sidecars(&root, &image_extensions, &patterns)
+                .into_par_iter()
+                .filter_map(|sidecar_match| {
+                    let (sidecar, data) = Self::load_sidecar(&source, &classifier, sidecar_match);
+                    let data = data?;
+                    let mut done = Self::operations_in(&classifier, &data);
+                    done.extend(Self::payload_keys(&data).cloned());
+                    Some((sidecar.image_path, done))
+                })
+                .collect();
+
+            let mut recorded: HashMap<PathBuf, HashSet<String>> = HashMap::new();
+            for (image, done) in found {
+                recorded.entry(image).or_default().extend(done);
+            }
+            CoverageReport::compute(root, &images, &recorded, &operations)
+        }).await?;
+
+        Ok(report)
+    }
+
+    /// Walk `directory` once and produce every result `pipeline` asks for
+    ///
+    /// Each sidecar is read at most once, however many consumers need it: in full when it is
+    /// validated, as an outline otherwise. The results match what `validate`, `get_statistics`,
+    /// `coverage` and `get_format_statistics` report for the same directory, except that
+    /// validation and format counts honour the manager's `ScanOptions` like the other scans.
+    pub async fn run_pipeline(&self, directory: &Path, pipeline: &ScanPipeline, progress: Option<ProgressCallback>) -> Result<ScanReport> {
+        let root = directory.to_path_buf();
+        let image_extensions = self.image_extensions.clone();
+        let patterns = Arc::clone(&self.patterns);
+        let scan_options = self.scan_options;
+        let classifier = Arc::clone(&self.classifier);
+        let skim_keys = self.classifier.skim_keys();
+        let selection = self.selection;
+        let detection_filter = self.detection_filter.clone();
+        let merge_hardlinks = self.scan_options.merge_hardlinks;
+        let pipeline = pipeline.clone();
+        let source = self.payload_source();
+
+        let (scan, report) = tokio::task::spawn_blocking(move || {
+            let scan = Self::walk(source.storage.as_deref(), &root, &image_extensions, scan_options);
+            let selected = if pipeline.validates() || pipeline.counts_formats() {
+                selection.apply(scan.sidecars.clone())
+            } else {
+                Vec::new()
+            };
+
+            // Every sidecar to read, in selection order and then match order, with its matches
+            let mut order: Vec<PathBuf> = Vec::new();
+            let mut files: HashMap<PathBuf, (bool, Vec<(usize, SidecarMatch)>)> = HashMap::new();
+            if pipeline.validates() {
+                for path in &selected {
+                    order.push(path.clone());
+                    files.insert(path.clone(), (true, Vec::new()));
+                }
+            }
+            if pipeline.reads_matches() {
+                for (index, sidecar_match) in scan.match_sidecars(&root, &image_extensions, &patterns).into_iter().enumerate() {
+                    let entry = files.entry(sidecar_match.sidecar_path.clone()).or_insert_with(|| {
+                        order.push(sidecar_match.sidecar_path.clone());
+                        (false, Vec::new())
+                    });
+                    entry.1.push((index, sidecar_match));
+                }
+            }
+            let files: Vec<_> = order
+                .into_iter()
+                .map(|path| {
+                    let (validate, matches) = files.remove(&path).unwrap_or_default();
+                    (path, validate, matches)
+                })
+                .collect();
+            let tracker = ProgressTracker::new(progress, files.len());
+
+            let outcomes: Vec<_> = files
+                .into_par_iter()
+                .map(|(path, validate, matches)| {
+                    let start_time = Instant::now();
+                    let read = source.read(&path);
+                    let format = SidecarFormat::from_path(&path).unwrap_or(SidecarFormat::Json);
+                    let deserialize_start = Instant::now();
+                    let decoded = read.as_ref().ok().map(|bytes| match validate {
+                        true => source.formats.get_serializer(format).deserialize(bytes),
+                        false => skim::skim(bytes, format, &skim_keys),
+                    });
+                    let deserialize_time = deserialize_start.elapsed().as_secs_f64();
+
+                    let validation = match (&read, &decoded) {
+                        _ if !validate => None,
+                        (Err(SidecarError::TooLarge { size, limit, .. }), _) => {
+                            Some(ValidationResult::oversized(path.clone(), *size, *limit, start_time.elapsed().as_secs_f64()))
+                        }
+                        (Ok(bytes), Some(decoded)) => ParallelProcessor::validate_decoded(
+                            &detection_filter,
+                            path.clone(),
+                            format,
+                            decoded.as_ref(),
+                            bytes.len() as u64,
+                            start_time,
+                            deserialize_time,
+                        ),
+                        (read, _) => Some(ValidationResult::error(
+                            path.clone(),
+                            format!("File read error: {}", read.as_ref().err().map(ToString::to_string).unwrap_or_default()),
+                            start_time.elapsed().as_secs_f64(),
+                        )),
+                    };
+
+                    let data = match (&read, &decoded) {
+                        (Ok(bytes), Some(Ok(data))) => Some((data, bytes.len() as u64)),
+                        _ => None,
+                    };
+                    let infos: Vec<_> = matches
+                        .into_iter()
+                        .map(|(index, sidecar_match)| {
+                            let info = Self::matched_info(&source, &classifier, sidecar_match, data);
+                            let done = data.map(|(data, _)| {
+                                let mut done = Self::operations_in(&classifier, data);
+                                done.extend(Self::payload_keys(data).cloned());
+                                done
+                            });
+                            (index, info, done)
+                        })
+                        .collect();
+
+                    tracker.advance(&path);
+                    (validation, infos)
+                })
+                .collect();
+
+            let sidecars_read = outcomes.len();
+            let mut validation = Vec::new();
+            let mut matched = Vec::new();
+            for (result, infos) in outcomes {
+                validation.extend(result);
+                matched.extend(infos);
+            }
+            matched.sort_by_key(|(index, _, _)| *index);
+
+            let coverage = pipeline.coverage_operations().map(|operations| {
+                let mut recorded: HashMap<PathBuf, HashSet<String>> = HashMap::new();
+                let mut found = BTreeSet::new();
+                for (_, info, done) in &matched {
+                    if let Some(done) = done {
+                        found.extend(done.iter().filter(|key| OperationType::from_str(key) != OperationType::Unknown).cloned());
+                        recorded.entry(info.image_path.clone()).or_default().extend(done.iter().cloned());
+                    }
+                }
+                let operations = match operations {
+                    [] => found.into_iter().collect(),
+                    operations => operations.to_vec(),
+                };
+                let mut images = scan.images.clone();
+                images.sort();
+                CoverageReport::compute(root.clone(), &selection.apply(images), &recorded, &operations)
+            });
+
+            let statistics = pipeline.summarises().then(|| {
+                let mut stats = StatisticsResult::new(root.clone());
+                stats.total_images = scan.images.len() as u32;
+                stats.symlink_count = scan.symlink_count;
+                stats.broken_symlinks = scan.broken_symlinks;
+                stats.hardlink_groups = scan.hardlinks.clone();
+                stats.hardlinks_merged = merge_hardlinks;
+                stats.sidecars = matched.into_iter().map(|(_, info, _)| info).collect();
+                stats.summarize();
+                stats
+            });
+
+            let formats = pipeline.counts_formats().then(|| {
+                let mut format_counts = HashMap::new();
+                for path in &selected {
+                    *format_counts.entry(SidecarFormat::from_path(path).unwrap_or(SidecarFormat::Json)).or_insert(0) += 1;
+                }
+                format_counts
+            });
+
+            let report = ScanReport {
+                directory: root,
+                sidecars_read,
+                validation: pipeline.validates().then_some(validation),
+                statistics,
+                coverage,
+                formats,
+            };
+            (scan, report)
+        }).await?;
+        self.remember_hardlinks(&scan.hardlinks);
+
+        Ok(report)
+    }
+
+    /// Frame sequences under `directory`, ordered by the index and timestamp in their names,
+    /// with per-interval detection rates and gaps
+    ///
+    /// Every image counts, with or without a sidecar; only images the manager's `FileSelection`
+    /// admits are included.
+    pub async fn timeline(&self, directory: &Path, options: &TimelineOptions) -> Result<Timeline> {
+        let root = directory.to_path_buf();
+        let image_extensions = self.image_extensions.clone();
+        let patterns = Arc::clone(&self.patterns);
+        let scan_options = self.scan_options;
+        let classifier = Arc::clone(&self.classifier);
+        let selection = self.selection;
+        let options = options.clone();
+        let source = self.payload_source();
+
+        let timeline = tokio::task::spawn_blocking(move || {
+            let scan = Self::walk(source.storage.as_deref(), &root, &image_extensions, scan_options);
+            let images = selection.apply(scan.images.clone());
+            let found: Vec<(PathBuf, usize)> = scan.match_sidecars(&root, &image_extensions, &patterns)
+                .into_par_iter()
+                .filter_map(|sidecar_match| {
+                    let (sidecar, data) = Self::load_sidecar(&source, &classifier, sidecar_match);
+                    let detections = timeline::sidecar_detections(&data?, sidecar.operation.as_str(), options.operation.as_deref())?;
+                    Some((sidecar.image_path, detections))
+                })
+                .collect();
+
+            let mut recorded: HashMap<PathBuf, usize> = HashMap::new();
+            for (image, detections) in found {
+                *recorded.entry(image).or_insert(0) += detections;
+            }
+            let (mut frames, mut unparsed) = (Vec::new(), Vec::new());
+            for image_path in images {
+                let Some(key) = options.naming.parse(&image_path) else {
+                    unparsed.push(image_path);
+                    continue;
+                };
+                let detections = recorded.get(&image_path).copied();
+                frames.push(Frame { image_path, key, has_sidecar: detections.is_some(), detections: detections.unwrap_or(0) });
+            }
+            Timeline::compute(root, frames, unparsed, &options)
+        }).await?;
+
+        Ok(timeline)
+    }
+
+    /// Object tracks under `directory`, ordered by track id
+    ///
+    /// Gathers every box with a `track_id` from the sidecars, mostly `tracking` payloads, and
+    /// orders each track's boxes by the frame index or timestamp `naming` reads from the image.
+    pub async fn tracks(&self, directory: &Path, naming: &FrameNaming) -> Result<Vec<Track>> {
+        let naming = naming.clone();
+        let points = self.scan_decoded(directory, move |sidecar, data| tracks::track_points(&sidecar, &data, &naming)).await?;
+        Ok(tracks::group_tracks(points))
+    }
+
+    /// The track with `track_id` under `directory`, if any sidecar records it
+    pub async fn track(&self, directory: &Path, track_id: &str, naming: &FrameNaming) -> Result<Option<Track>> {
+        let naming = naming.clone();
+        let wanted = track_id.to_string();
+        let points = self.scan_decoded(directory, move |sidecar, data| {
+            let mut points = tracks::track_points(&sidecar, &data, &naming);
+            points.retain(|(track_id, _)| *track_id == wanted);
+            points
+        }).await?;
+        Ok(tracks::group_tracks(points).pop())
+    }
+
+    /// Check every decodable sidecar under `directory` against `linter`'s rules
+    ///
+    /// Sidecars that cannot be decoded are left to `validate`.
+    pub async fn lint(&self, directory: &Path, linter: &Linter) -> Result<LintReport> {
+        let linter = linter.clone();
+        let mut checked = self.scan_decoded(directory, move |sidecar, data| {
+            vec![(sidecar.sidecar_path.clone(), linter.check(&sidecar, &data))]
+        }).await?;
+        checked.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(LintReport {
+            sidecars: checked.len(),
+            findings: checked.into_iter().flat_map(|(_, findings)| findings).collect(),
+        })
+    }
+
+    /// Sidecars under `directory` that stand out from the others of their operation
+    ///
+    /// Sizes and processing times are compared per operation; detection counts against a
+    /// fixed limit. Sidecars that cannot be decoded are left to `validate`.
+    pub async fn anomalies(&self, directory: &Path, options: &AnomalyOptions) -> Result<AnomalyReport> {
+        let samples = self.scan_decoded(directory, |sidecar, data| vec![AnomalySample::new(sidecar, &data)]).await?;
+        Ok(AnomalyReport::detect(directory.to_path_buf(), samples, options))
+    }
+
+    /// Read, parse and validate throughput of the sidecars under `directory`, per format
+    ///
+    /// Files are timed one at a time on a blocking thread; see `bench::run`.
+    pub async fn bench(&self, directory: &Path, options: &BenchOptions) -> Result<BenchReport> {
+        let mut files = self.find_sidecar_files(directory).await?;
+        files.sort();
+        let files = options.sample(files);
+        let directory = directory.to_path_buf();
+        let format_manager = Arc::clone(&self.format_manager);
+        let classifier = Arc::clone(&self.classifier);
+        let options = options.clone();
+
+        let report = tokio::task::spawn_blocking(move || {
+            bench::run(directory, &files, &format_manager, &options, |path, data| {
+                let operation = Self::operation_from_data(&classifier, data);
+                let mut sidecar = SidecarInfo::new(path.to_path_buf(), path.to_path_buf(), operation, None);
+                sidecar.extract_payload_metrics(data);
+                sidecar
+            })
+        }).await?;
+
+        Ok(report)
+    }
+
+    /// Write a synthetic dataset of frames and sidecars under `root`, for load tests and benches
+    ///
+    /// Sidecars go through this manager's serializers, so canonical output applies.
+    pub async fn generate_dataset(&self, root: &Path, options: &GenerateOptions) -> Result<GenerateSummary> {
+        self.ensure_writable("generate a dataset")?;
+        let root = root.to_path_buf();
+        let format_manager = Arc::clone(&self.format_manager);
+        let options = options.clone();
+        tokio::task::spawn_blocking(move || generate::generate(&root, &options, &format_manager)).await?
+    }
+
+    /// Sidecars under `directory` the classifier left `Unknown`, grouped by top-level keys
+    ///
+    /// Each group keeps up to `samples` documents, with long arrays cut short, to help write
+    /// operation rules for them.
+    pub async fn triage_unknown(&self, directory: &Path, samples: usize) -> Result<TriageReport> {
+        let unknown = self.scan_decoded(directory, |sidecar, data| {
+            if sidecar.operation == OperationType::Unknown {
+                vec![(sidecar.sidecar_path, data)]
+            } else {
+                Vec::new()
+            }
+        }).await?;
+        Ok(TriageReport::compute(directory.to_path_buf(), unknown, samples))
+    }
+
+    /// Detections under `directory` the models are least sure of, picked as `options` asks
+    ///
+    /// Only detections recording a `confidence` or `score` are considered.
+    pub async fn review_queue(&self, directory: &Path, options: &ReviewOptions) -> Result<ReviewQueue> {
+        let operations = options.operations.clone();
+        let scored = self.scan_decoded(directory, move |sidecar, data| {
+            scored_detections(&sidecar.image_path, &sidecar.sidecar_path, &data, sidecar.operation.as_str(), &operations)
+        }).await?;
+        Ok(ReviewQueue::select(scored, options))
+    }
+
+    /// Which images under `directory` pass `gate`, judged on all of their sidecars
+    ///
+    /// Images without a sidecar are not considered.
+    pub async fn gate(&self, directory: &Path, gate: &QualityGate) -> Result<GateReport> {
+        let sidecars = self.scan_decoded(directory, |sidecar, data| vec![(sidecar, data)]).await?;
+        Ok(gate.evaluate(directory.to_path_buf(), sidecars))
+    }
+
+    /// Sidecars under `directory` whose image changed after they were written
+    ///
+    /// Compares each image with the fingerprint its sidecar recorded. Unless `deep`, images whose
+    /// size and mtime still match are not read. Sidecars written before fingerprints were recorded
+    /// are reported as `Unrecorded`.
+    pub async fn find_stale_sidecars(&self, directory: &Path, deep: bool) -> Result<Vec<StaleSidecar>> {
+        let root = directory.to_path_buf();
+        let image_extensions = self.image_extensions.clone();
+        let patterns = Arc::clone(&self.patterns);
+        let scan_options = self.scan_options;
+        let classifier = Arc::clone(&self.classifier);
+        let source = PayloadSource { storage: None, ..self.payload_source() };
+
+        let stale = tokio::task::spawn_blocking(move || {
+            let scan = DirectoryScan::walk(&root, &image_extensions, scan_options);
+            let mut stale: Vec<StaleSidecar> = scan.match_sidecars(&root, &image_extensions, &patterns)
+                .into_par_iter()
+                .filter_map(|sidecar_match| {
+                    let (sidecar, data) = Self::load_sidecar(&source, &classifier, sidecar_match);
+                    let recorded = ImageFingerprint::from_sidecar(data.as_ref()?);
+                    let (reason, current) = match recorded.as_ref().map(|recorded| recorded.check(&sidecar.image_path, deep)) {
+                        None => (StaleReason::Unrecorded, None),
+                        Some(Staleness::Fresh) => return None,
+                        Some(Staleness::Changed(current)) => (StaleReason::ImageChanged, Some(current)),
+                        Some(Staleness::ImageMissing) => (StaleReason::ImageMissing, None),
+                    };
+                    Some(StaleSidecar { sidecar_path: sidecar.sidecar_path, image_path: sidecar.image_path, reason, recorded, current })
+                })
+                .collect();
+            stale.sort_by(|a, b| a.sidecar_path.cmp(&b.sidecar_path));
+            stale
+        }).await?;
+
+        Ok(stale)
+    }
+
+    /// Rename orphaned sidecars under `directory` to follow images that were renamed or moved
+    ///
+    /// An orphan is matched by the image fingerprint it recorded to an image anywhere under
+    /// `directory` that has no sidecar of its own. Only a single match is acted on; the new
+    /// sidecar records its new image path. Returns one record per orphan.
+    pub async fn reassociate(&self, directory: &Path, dry_run: bool) -> Result<Vec<Reassociation>> {
+        if !dry_run {
+            self.ensure_writable("reassociate sidecars")?;
+        }
+        let root = directory.to_path_buf();
+        let image_extensions = self.image_extensions.clone();
+        let patterns = Arc::clone(&self.patterns);
+        let scan_options = self.scan_options;
+        let format_manager = Arc::clone(&self.format_manager);
+        let max_sidecar_size = self.max_sidecar_size;
+
+        let planned = tokio::task::spawn_blocking(move || {
+            let scan = DirectoryScan::walk(&root, &image_extensions, scan_options);
+            let matches = scan.match_sidecars(&root, &image_extensions, &patterns);
+            let matched: HashSet<&PathBuf> = matches.iter().map(|found| &found.sidecar_path).collect();
+            let claimed: HashSet<&PathBuf> = matches.iter().map(|found| &found.image_path).collect();
+
+            let orphans: Vec<(PathBuf, Option<Value>)> = scan.sidecars
+                .par_iter()
+                .filter(|sidecar_path| !matched.contains(sidecar_path))
+                .map(|sidecar_path| {
+                    let data = storage::read_sidecar(None, sidecar_path, max_sidecar_size)
+                        .and_then(|bytes| Self::decode_sidecar(&format_manager, sidecar_path, &bytes))
+                        .ok();
+                    (sidecar_path.clone(), data)
+                })
+                .collect();
+
+            // Hash only the unclaimed images whose size some orphan recorded
+            let sizes: HashSet<u64> = orphans.iter()
+                .filter_map(|(_, data)| ImageFingerprint::from_sidecar(data.as_ref()?))
+                .map(|fingerprint| fingerprint.size)
+                .collect();
+            let hashed: Vec<(String, PathBuf)> = scan.images
+                .par_iter()
+                .filter(|image| !claimed.contains(image))
+                .filter(|image| std::fs::metadata(image).is_ok_and(|metadata| sizes.contains(&metadata.len())))
+                .filter_map(|image| ImageFingerprint::of(image).ok().map(|fingerprint| (fingerprint.xxh3, image.clone())))
+                .collect();
+            let mut by_hash: HashMap<String, Vec<PathBuf>> = HashMap::new();
+            for (hash, image) in hashed {
+                by_hash.entry(hash).or_default().push(image);
+            }
+
+            let mut planned: Vec<(Reassociation, Option<Value>)> = orphans
+                .into_iter()
+                .filter_map(|(sidecar_path, data)| {
+                    let Some(fingerprint) = data.as_ref().and_then(ImageFingerprint::from_sidecar) else {
+                        return Some((Reassociation::new(sidecar_path, ReassociationOutcome::Unrecorded), None));
+                    };
+                    let mut candidates = by_hash.get(&fingerprint.xxh3).cloned().unwrap_or_default();
+                    candidates.sort();
+                    let mut record = Reassociation::new(sidecar_path, ReassociationOutcome::Unmatched);
+                    match candidates.len() {
+                        0 => {}
+                        1 => {
+                            let recorded_image = data.as_ref()
+                                .and_then(|data| data.get("sidecar_info")?.get("image_path"))
+                                .and_then(paths::path_from_json);
+                            let new_path = reassociated_path(&record.sidecar_path, recorded_image.as_deref(), &candidates[0]);
+                            // Its image is there after all, e.g. a pattern-named sidecar outside the scanned root
+                            if new_path == record.sidecar_path {
+                                return None;
+                            }
+                            record.outcome = ReassociationOutcome::Moved;
+                            record.image_path = Some(candidates.remove(0));
+                            record.new_path = Some(new_path);
+                        }
+                        _ => {
+                            record.outcome = ReassociationOutcome::Ambiguous;
+                            record.candidates = candidates;
                         }
                     }
+                    Some((record, data))
+                })
+                .collect();
+
+            // Two orphans heading for one name cannot both have it
+            let mut targets: HashMap<PathBuf, usize> = HashMap::new();
+            for new_path in planned.iter().filter_map(|(record, _)| record.new_path.clone()) {
+                *targets.entry(new_path).or_default() += 1;
+            }
+            for (record, _) in &mut planned {
+                if record.new_path.as_ref().is_some_and(|new_path| targets[new_path] > 1) {
+                    record.outcome = ReassociationOutcome::Ambiguous;
+                }
+            }
+            planned.sort_by(|(a, _), (b, _)| a.sidecar_path.cmp(&b.sidecar_path));
+            planned
+        }).await?;
+
+        let mut records = Vec::with_capacity(planned.len());
+        for (mut record, data) in planned {
+            if let (ReassociationOutcome::Moved, Some(new_path), Some(mut data)) = (record.outcome, record.new_path.clone(), data) {
+                if self.file_exists(&new_path) {
+                    record.outcome = ReassociationOutcome::Conflict;
+                } else if !dry_run {
+                    if let (Some(info), Some(image_path)) = (data.get_mut("sidecar_info").and_then(Value::as_object_mut), &record.image_path) {
+                        info.insert("image_path".to_string(), paths::path_to_json(image_path));
+                    }
+                    self.write_file(&new_path, &self.encode(&new_path, &data)?).await?;
+                    self.remove_file(&record.sidecar_path).await?;
+                    self.notify(EventKind::Created, record.image_path.as_deref(), &new_path, None);
+                    self.notify(EventKind::Deleted, None, &record.sidecar_path, None);
+                    self.audit(Mutation::Move, &record.sidecar_path, Some(&new_path), None)?;
+                }
+            }
+            records.push(record);
+        }
+        Ok(records)
+    }
+
+    /// Move or rename images together with their sidecars, like `mv` for each `(from, to)` pair
+    ///
+    /// `from` may be an image, whose sidecars (`IMG.<ext>` and `prefix_IMG.<ext>`) are renamed
+    /// along with it, or a directory, which is moved whole. A `to` that is an existing directory
+    /// receives `from` under its own name. The renames of one pair are undone if any fails, and
+    /// nothing is replaced. Moved sidecars have `sidecar_info.image_path` pointed at the new image.
+    pub async fn relocate(&self, moves: &[(PathBuf, PathBuf)], dry_run: bool) -> Result<Vec<RelocationRecord>> {
+        if !dry_run {
+            self.ensure_writable("relocate images")?;
+        }
+        let mut records = Vec::with_capacity(moves.len());
+        for (from, to) in moves {
+            let to = if to.is_dir() { to.join(from.file_name().unwrap_or_default()) } else { to.clone() };
+            let mut record = RelocationRecord::new(from.clone(), to);
+            if let Err(e) = self.relocate_one(&mut record, dry_run).await {
+                record.error = Some(e.to_string());
+            }
+            records.push(record);
+        }
+        Ok(records)
+    }
+
+    async fn relocate_one(&self, record: &mut RelocationRecord, dry_run: bool) -> Result<()> {
+        let (from, to) = (record.from.clone(), record.to.clone());
+        if from.symlink_metadata().is_err() {
+            return Err(SidecarError::ImageNotFound(from));
+        }
+        let is_directory = from.is_dir();
+        let renames = if is_directory {
+            let sidecars = self.find_sidecar_files(&from).await?;
+            record.sidecars = sidecars
+                .into_iter()
+                .map(|sidecar| MovedFile { to: to.join(sidecar.strip_prefix(&from).unwrap_or(&sidecar)), from: sidecar })
+                .collect();
+            vec![(from.clone(), to.clone())]
+        } else {
+            let (image, image_extensions, patterns) = (from.clone(), self.image_extensions.clone(), Arc::clone(&self.patterns));
+            let sidecars = tokio::task::spawn_blocking(move || relocate::sidecars_beside(&image, &image_extensions, &patterns)).await??;
+            record.sidecars = sidecars
+                .into_iter()
+                .map(|sidecar| MovedFile { to: relocate::relocated_sidecar(&sidecar, &from, &to), from: sidecar })
+                .collect();
+            // The image goes last, so a failure leaves it with whatever sidecars are still beside it
+            record.sidecars.iter().map(|moved| (moved.from.clone(), moved.to.clone())).chain([(from.clone(), to.clone())]).collect()
+        };
+        if dry_run {
+            if let Some(taken) = renames.iter().map(|(_, to)| to).find(|to| to.symlink_metadata().is_ok()) {
+                return Err(SidecarError::ProcessingError(format!("{} already exists", taken.display())));
+            }
+            return Ok(());
+        }
+
+        let absolute_from = std::path::absolute(&from)?;
+        let absolute_to = std::path::absolute(&to)?;
+
+        // Recorded image paths may be relative or absolute, like the path the writer was given
+        let retarget = |recorded: &Path| -> Option<PathBuf> {
+            if is_directory {
+                recorded.strip_prefix(&from).map(|rest| to.join(rest))
+                    .or_else(|_| recorded.strip_prefix(&absolute_from).map(|rest| absolute_to.join(rest)))
+                    .ok()
+            } else if recorded == from {
+                Some(to.clone())
+            } else if recorded == absolute_from {
+                Some(absolute_to.clone())
+            } else {
+                None
+            }
+        };
+
+        // Sidecars are pointed at the new location before anything moves, so a failure at any
+        // step can put every file back as it was and the image is either moved or not
+        let mut rewritten = Vec::new();
+        let mut outcome = Ok(());
+        for moved in &record.sidecars {
+            match self.retarget_sidecar(&moved.from, &retarget).await {
+                Ok(Some(original)) => rewritten.push((moved.from.clone(), original)),
+                Ok(None) => {}
+                Err(e) => {
+                    outcome = Err(SidecarError::ProcessingError(format!("Cannot update image_path in {}: {}", moved.from.display(), e)));
+                    break;
+                }
+            }
+        }
+        if outcome.is_ok() {
+            outcome = tokio::task::spawn_blocking(move || relocate::rename_all(&renames)).await?.map_err(SidecarError::from);
+        }
+        if let Err(e) = outcome {
+            for (sidecar_path, original) in rewritten.iter().rev() {
+                if let Err(restore) = self.write_file(sidecar_path, original).await {
+                    tracing::warn!("Could not restore {}: {}", sidecar_path.display(), restore);
+                }
+            }
+            return Err(e);
+        }
+
+        for moved in &record.sidecars {
+            self.notify(EventKind::Deleted, None, &moved.from, None);
+            self.notify(EventKind::Created, None, &moved.to, None);
+            self.audit(Mutation::Move, &moved.from, Some(&moved.to), None)?;
+        }
+        Ok(())
+    }
+
+    /// Rewrite the `image_path` and `symlink_path` recorded in a sidecar through `retarget`
+    /// Returns the sidecar's previous contents if it was rewritten.
+    async fn retarget_sidecar(&self, sidecar_path: &Path, retarget: &impl Fn(&Path) -> Option<PathBuf>) -> Result<Option<Vec<u8>>> {
+        let original = self.read_file(sidecar_path).await?;
+        let mut data = Self::decode_sidecar(&self.format_manager, sidecar_path, &original)?;
+        let Some(info) = data.get_mut("sidecar_info").and_then(Value::as_object_mut) else {
+            return Ok(None);
+        };
+        let mut changed = false;
+        for key in ["image_path", "symlink_path"] {
+            if let Some(new_path) = info.get(key).and_then(paths::path_from_json).and_then(|recorded| retarget(&recorded)) {
+                info.insert(key.to_string(), paths::path_to_json(&new_path));
+                changed = true;
+            }
+        }
+        if !changed {
+            return Ok(None);
+        }
+        self.write_file(sidecar_path, &self.encode(sidecar_path, &data)?).await?;
+        Ok(Some(original))
+    }
+
+    /// Merge the `operations` payloads of the sidecars beside `image_path` into one unified sidecar
+    ///
+    /// Sources are the image's own sidecar in any format and its pattern-named ones, such as
+    /// `faces_IMG.json`. With no `operations`, every payload found is merged. Where several
+    /// sources hold an operation the most recently written payload wins; `sidecar_info.unified_sources`
+    /// names the source of each one. The sources are left in place.
+    pub async fn unify(&self, image_path: &Path, operations: &[OperationType]) -> Result<SidecarInfo> {
+        self.ensure_writable("unify sidecars")?;
+        let (image, image_extensions, patterns) = (image_path.to_path_buf(), self.image_extensions.clone(), Arc::clone(&self.patterns));
+        let sources = tokio::task::spawn_blocking(move || relocate::sidecars_beside(&image, &image_extensions, &patterns)).await??;
+        let (record, data) = self.unify_sources(image_path, sources, operations, false, false).await?;
+
+        let mut sidecar_info = SidecarInfo::new(image_path.to_path_buf(), record.sidecar_path, OperationType::Unified, None);
+        sidecar_info.data_size = fs::metadata(&sidecar_info.sidecar_path).await.map_or(0, |metadata| metadata.len());
+        sidecar_info.is_valid = true;
+        sidecar_info.extract_payload_metrics(&data);
+        Ok(sidecar_info)
+    }
+
+    /// Unify the sidecars of every image under `directory` that has more than its own sidecar
+    ///
+    /// With `remove_sources`, a source is removed along with the write once every payload it
+    /// held has been merged; each image's write and removals are one transaction.
+    pub async fn unify_directory(
+        &self,
+        directory: &Path,
+        operations: &[OperationType],
+        remove_sources: bool,
+        dry_run: bool,
+    ) -> Result<Vec<UnifyRecord>> {
+        if !dry_run {
+            self.ensure_writable("unify sidecars")?;
+        }
+        let root = directory.to_path_buf();
+        let image_extensions = self.image_extensions.clone();
+        let patterns = Arc::clone(&self.patterns);
+        let scan_options = self.scan_options;
+        let groups = tokio::task::spawn_blocking(move || {
+            let scan = DirectoryScan::walk(&root, &image_extensions, scan_options);
+            let mut groups: BTreeMap<PathBuf, Vec<PathBuf>> = BTreeMap::new();
+            for matched in scan.match_sidecars(&root, &image_extensions, &patterns) {
+                groups.entry(matched.image_path).or_default().push(matched.sidecar_path);
+            }
+            groups
+        })
+        .await?;
+
+        let mut records = Vec::new();
+        for (image_path, sources) in groups {
+            let target = image_path.with_extension(self.save_format().extension());
+            if sources == [target.clone()] {
+                continue;
+            }
+            match self.unify_sources(&image_path, sources, operations, remove_sources, dry_run).await {
+                Ok((record, _)) => records.push(record),
+                Err(e) => {
+                    let mut record = UnifyRecord::new(image_path, target);
+                    record.error = Some(e.to_string());
+                    records.push(record);
                 }
             }
         }
+        Ok(records)
+    }
+
+    async fn unify_sources(
+        &self,
+        image_path: &Path,
+        mut sources: Vec<PathBuf>,
+        operations: &[OperationType],
+        remove_sources: bool,
+        dry_run: bool,
+    ) -> Result<(UnifyRecord, Value)> {
+        let target = image_path.with_extension(self.save_format().extension());
+        // The image's own sidecar goes first, so it keeps its payloads on a tie
+        sources.sort_by_key(|source| (*source != target, source.clone()));
+        sources.dedup();
+        let selected = |key: &str| self.selects_operation(operations, key);
+
+        let mut base = None;
+        let mut sections = Vec::new();
+        let mut removable = Vec::new();
+        for source in &sources {
+            let data = self.load_sidecar_data(source).await?;
+            let modified = fs::metadata(source).await.ok().and_then(|metadata| metadata.modified().ok()).map(DateTime::<Utc>::from);
+            let found = unify::sections(source, &data, modified);
+            if *source == target {
+                base = Some(data);
+            } else if found.iter().all(|section| selected(&section.operation)) {
+                removable.push(source.clone());
+            }
+            sections.extend(found);
+        }
+        let chosen = unify::newest(sections, selected);
+        if chosen.is_empty() {
+            return Err(SidecarError::ProcessingError(format!("No payloads to unify for {}", image_path.display())));
+        }
+
+        let previous = base.as_ref().and_then(ImageFingerprint::from_sidecar);
+        let mut data = unify::unified_document(base, &chosen, image_path);
+        if let (Some(fingerprint), Some(info)) = (
+            Self::image_fingerprint(image_path, previous).await,
+            data.get_mut("sidecar_info").and_then(Value::as_object_mut),
+        ) {
+            info.insert(IMAGE_FINGERPRINT_KEY.to_string(), serde_json::to_value(fingerprint)?);
+        }
 
-        // Analyze sidecars
-        let mut operation_counts = HashMap::new();
-        let mut processing_times = HashMap::new();
-        let mut success_rates = HashMap::new();
-        let mut data_sizes = HashMap::new();
+        let mut record = UnifyRecord::new(image_path.to_path_buf(), target.clone());
+        record.operations = chosen
+            .into_iter()
+            .map(|(operation, section)| UnifiedOperation { operation, source: section.source })
+            .collect();
+        if remove_sources {
+            record.removed = removable;
+        }
+        if !dry_run {
+            let directory = image_path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."));
+            let mut transaction = self.transaction(directory);
+            transaction.write(&target, &data)?;
+            for source in &record.removed {
+                transaction.remove(source);
+            }
+            transaction.commit().await?;
+        }
+        Ok((record, data))
+    }
 
-        for sidecar in &sidecars {
-            let operation = sidecar.operation.as_str().to_string();
+    /// Whether the payload under `key` is one of `operations`, or any payload when there are none
+    fn selects_operation(&self, operations: &[OperationType], key: &str) -> bool {
+        operations.is_empty()
+            || operations.iter().any(|operation| operation.as_str() == key || self.classifier.operation_for_key(key) == Some(operation))
+    }
 
-            // Count operations
-            *operation_counts.entry(operation.clone()).or_insert(0) += 1;
+    /// Write the `operations` payloads (all, if empty) of the sidecar of `image_path` to
+    /// per-operation sidecars, `operation_IMG.<ext>` in `format`, leaving the sidecar in place
+    ///
+    /// Each keeps the `sidecar_info` narrowed to its operation, and records the sidecar it was
+    /// split from under `sidecar_info.split_from`. Existing per-operation sidecars are replaced.
+    pub async fn split(&self, image_path: &Path, operations: &[OperationType], format: SidecarFormat) -> Result<SplitRecord> {
+        self.ensure_writable("split sidecars")?;
+        let (actual_image_path, symlink_info) = self.resolve_symlink(image_path).await?;
+        let bases = self.sidecar_bases(image_path, &actual_image_path, symlink_info.is_some());
+        let sidecar_path = self.existing_sidecar(&bases).ok_or_else(|| SidecarError::SidecarNotFound(bases[0].clone()))?;
+        self.split_sidecar(image_path, &sidecar_path, operations, format, false, false).await
+    }
 
-            // Collect processing times
-            if let Some(proc_time) = sidecar.get_processing_time() {
-                processing_times.entry(operation.clone()).or_insert_with(Vec::new).push(proc_time);
+    /// Split the sidecar of every image under `directory` into per-operation sidecars
+    ///
+    /// With `remove_unified`, a sidecar is removed along with the writes once every payload it
+    /// held has been written out; each image's writes and removal are one transaction.
+    pub async fn split_directory(
+        &self,
+        directory: &Path,
+        operations: &[OperationType],
+        format: SidecarFormat,
+        remove_unified: bool,
+        dry_run: bool,
+    ) -> Result<Vec<SplitRecord>> {
+        if !dry_run {
+            self.ensure_writable("split sidecars")?;
+        }
+        let root = directory.to_path_buf();
+        let image_extensions = self.image_extensions.clone();
+        let patterns = Arc::clone(&self.patterns);
+        let scan_options = self.scan_options;
+        // Only each image's own sidecar is split; pattern-named ones already hold one operation each
+        let matches = tokio::task::spawn_blocking(move || {
+            let scan = DirectoryScan::walk(&root, &image_extensions, scan_options);
+            scan.match_sidecars(&root, &image_extensions, &patterns)
+                .into_iter()
+                .filter(|matched| matched.sidecar_path.file_stem() == matched.image_path.file_stem())
+                .collect::<Vec<_>>()
+        })
+        .await?;
+
+        let mut records = Vec::with_capacity(matches.len());
+        for matched in matches {
+            match self.split_sidecar(&matched.image_path, &matched.sidecar_path, operations, format, remove_unified, dry_run).await {
+                Ok(record) => records.push(record),
+                Err(e) => {
+                    let mut record = SplitRecord::new(matched.image_path, matched.sidecar_path);
+                    record.error = Some(e.to_string());
+                    records.push(record);
+                }
             }
+        }
+        Ok(records)
+    }
+
+    async fn split_sidecar(
+        &self,
+        image_path: &Path,
+        sidecar_path: &Path,
+        operations: &[OperationType],
+        format: SidecarFormat,
+        remove_unified: bool,
+        dry_run: bool,
+    ) -> Result<SplitRecord> {
+        let data = self.load_sidecar_data(sidecar_path).await?;
+        let modified = fs::metadata(sidecar_path).await.ok().and_then(|metadata| metadata.modified().ok()).map(DateTime::<Utc>::from);
+        let sections = unify::sections(sidecar_path, &data, modified);
+        let complete = sections.iter().all(|section| self.selects_operation(operations, &section.operation));
+        let sections: Vec<_> = sections.into_iter().filter(|section| self.selects_operation(operations, &section.operation)).collect();
+        if sections.is_empty() {
+            return Err(SidecarError::ProcessingError(format!("No payloads to split in {}", sidecar_path.display())));
+        }
+
+        let mut record = SplitRecord::new(image_path.to_path_buf(), sidecar_path.to_path_buf());
+        let directory = image_path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let mut transaction = self.transaction(directory);
+        for section in &sections {
+            let path = unify::split_path(image_path, &section.operation, format);
+            transaction.write(&path, &unify::split_document(&data, section))?;
+            record.files.push(SplitFile { operation: section.operation.clone(), path });
+        }
+        if remove_unified && complete {
+            transaction.remove(sidecar_path);
+            record.removed = true;
+        }
+        if !dry_run {
+            transaction.commit().await?;
+        }
+        Ok(record)
+    }
+
+    /// Rewrite the legacy sidecars under `directory` that `importer` recognises into modern sidecars
+    ///
+    /// The payloads of every legacy sidecar of an image go into the image's own sidecar, next to
+    /// what it already holds; where both have an operation the modern payload is kept. Unless
+    /// `keep_originals`, the legacy sidecars are removed with the write, as one transaction per image.
+    pub async fn import_legacy(
+        &self,
+        directory: &Path,
+        importer: &LegacyImporter,
+        keep_originals: bool,
+        dry_run: bool,
+    ) -> Result<MigrationReport> {
+        if !dry_run {
+            self.ensure_writable("import legacy sidecars")?;
+        }
+        let mut sidecar_files = self.find_sidecar_files(directory).await?;
+        sidecar_files.sort();
 
-            // Collect success rates
-            let success = sidecar.get_success_status();
-            let rates = success_rates.entry(operation.clone()).or_insert((0, 0));
-            rates.1 += 1;
-            if success {
-                rates.0 += 1;
+        let mut report = MigrationReport::default();
+        let mut images: BTreeMap<PathBuf, LegacyPayloads> = BTreeMap::new();
+        for sidecar_path in sidecar_files {
+            let Ok(data) = self.load_sidecar_data(&sidecar_path).await else {
+                report.unrecognized.push(sidecar_path);
+                continue;
+            };
+            if data.get("sidecar_info").is_some() {
+                report.modern += 1;
+                continue;
+            }
+            let candidates = importer.candidates(&sidecar_path, &data);
+            if candidates.is_empty() {
+                report.unrecognized.push(sidecar_path);
+                continue;
+            }
+            let image_directory = sidecar_path.parent().unwrap_or(directory);
+            let found = candidates.into_iter().find_map(|(layout, found)| {
+                self.image_extensions
+                    .iter()
+                    .map(|ext| image_directory.join(image_file_name(&found.image_stem, ext)))
+                    .find(|image_path| image_path.is_file())
+                    .map(|image_path| (image_path, layout.to_string(), found.payloads))
+            });
+            match found {
+                Some((image_path, layout, payloads)) => {
+                    images.entry(image_path).or_default().push((LegacySource { path: sidecar_path, layout }, payloads));
+                }
+                None => report.orphaned.push(sidecar_path),
             }
-
-            // Collect data sizes
-            data_sizes.entry(operation.clone()).or_insert_with(Vec::new).push(sidecar.data_size);
         }
 
-        // Calculate averages
-        let mut avg_processing_times = HashMap::new();
-        for (operation, times) in processing_times {
-            if !times.is_empty() {
-                let avg = times.iter().sum::<f64>() / times.len() as f64;
-                avg_processing_times.insert(operation, avg);
+        for (image_path, sources) in images {
+            let target = image_path.with_extension(self.save_format().extension());
+            let mut record = MigrationRecord::new(image_path.clone(), target.clone());
+            if let Err(e) = self.import_legacy_image(&mut record, sources, keep_originals, dry_run).await {
+                record.error = Some(e.to_string());
             }
+            report.records.push(record);
         }
+        Ok(report)
+    }
 
-        let mut success_rate_percentages = HashMap::new();
-        for (operation, (success, total)) in success_rates {
-            if total > 0 {
-                let percentage = (success as f64 / total as f64) * 100.0;
-                success_rate_percentages.insert(operation, percentage);
+    async fn import_legacy_image(
+        &self,
+        record: &mut MigrationRecord,
+        sources: LegacyPayloads,
+        keep_originals: bool,
+        dry_run: bool,
+    ) -> Result<()> {
+        // A legacy sidecar at the target's own path is replaced, not merged into
+        let base = if record.sidecar_path.exists() && !sources.iter().any(|(source, _)| source.path == record.sidecar_path) {
+            Some(self.load_sidecar_data(&record.sidecar_path).await?)
+        } else {
+            None
+        };
+        let mut payloads = BTreeMap::new();
+        for (source, found) in &sources {
+            for (operation, payload) in found {
+                payloads.entry(operation.clone()).or_insert_with(|| (payload.clone(), source.path.clone()));
             }
         }
+        let (data, imported, kept) = legacy::migrated_document(base, &payloads, &record.image_path);
 
-        let mut avg_data_sizes = HashMap::new();
-        for (operation, sizes) in data_sizes {
-            if !sizes.is_empty() {
-                let avg = sizes.iter().sum::<u64>() as f64 / sizes.len() as f64;
-                avg_data_sizes.insert(operation, avg);
+        // A source may only go once every payload in it made it into the modern sidecar
+        let fully_imported = |source: &Path, found: &BTreeMap<String, Value>| {
+            found.keys().all(|operation| {
+                imported.contains(operation) && payloads.get(operation).is_some_and(|(_, from)| from == source)
+            })
+        };
+        let (removable, conflicts): (Vec<_>, Vec<_>) = sources
+            .iter()
+            .filter(|(source, _)| source.path != record.sidecar_path)
+            .partition(|(source, found)| fully_imported(&source.path, found));
+        record.conflicts = conflicts.into_iter().map(|(source, _)| source.path.clone()).collect();
+        if !keep_originals {
+            record.removed = removable.into_iter().map(|(source, _)| source.path.clone()).collect();
+        }
+        record.operations = imported;
+        record.kept = kept;
+        record.sources = sources.into_iter().map(|(source, _)| source).collect();
+        if !dry_run {
+            let directory = record.image_path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."));
+            let mut transaction = self.transaction(directory);
+            transaction.write(&record.sidecar_path, &data)?;
+            for source in &record.removed {
+                transaction.remove(source);
             }
+            transaction.commit().await?;
         }
+        Ok(())
+    }
 
-        // Populate statistics
-        stats.total_images = image_files.len() as u32;
-        stats.symlink_count = symlink_count;
-        stats.broken_symlinks = broken_symlinks;
-        stats.total_sidecars = sidecars.len() as u32;
-        stats.coverage_percentage = if stats.total_images > 0 {
-            (stats.total_sidecars as f64 / stats.total_images as f64) * 100.0
-        } else {
-            0.0
-        };
-        stats.operation_counts = operation_counts;
-        stats.avg_processing_times = avg_processing_times;
-        stats.success_rate_percentages = success_rate_percentages;
-        stats.avg_data_sizes = avg_data_sizes;
-        stats.sidecars = sidecars;
+    /// Start a transaction that writes, renames and removes sidecars together, journaled in `directory`
+    pub fn transaction(&self, directory: &Path) -> SidecarTransaction<'_> {
+        SidecarTransaction::new(self, directory)
+    }
 
-        Ok(stats)
+    /// Finish the committed transactions interrupted in `directory` and undo the uncommitted ones
+    pub async fn recover_transactions(&self, directory: &Path) -> Result<RecoverySummary> {
+        self.ensure_writable("recover transactions")?;
+        let (storage, directory) = (self.storage.clone(), directory.to_path_buf());
+        tokio::task::spawn_blocking(move || transaction::recover(storage.as_deref(), &directory)).await?
+    }
+
+    /// Split the images under `directory` still missing `operation` into jobs of `batch_size` images
+    pub async fn pending_jobs(&self, directory: &Path, operation: &str, batch_size: usize) -> Result<Vec<JobSpec>> {
+        let report = self.coverage(directory, &[operation.to_string()]).await?;
+        Ok(report.operations.first().map(|coverage| JobSpec::batches(directory, coverage, batch_size)).unwrap_or_default())
+    }
+
+    /// Sum the badge files written by `get_statistics_with_badge` under `directory`, reading no sidecars
+    pub async fn aggregate_statistics_badges(&self, directory: &Path) -> Result<BadgeRollup> {
+        let directory = directory.to_path_buf();
+        tokio::task::spawn_blocking(move || badge::rollup(&directory)).await?
     }
 
     /// Clean up orphaned sidecar files
     pub async fn cleanup_orphaned_sidecars(&self, directory: &Path) -> Result<usize> {
-        let mut removed_count = 0;
+        self.ensure_writable("remove orphaned sidecars")?;
+        let orphaned = self.find_orphaned_sidecars(directory).await?;
+
+        for sidecar_path in &orphaned {
+            fs::remove_file(sidecar_path).await?;
+            tracing::info!("Removed orphaned sidecar: {:?}", sidecar_path);
+            self.notify(EventKind::Deleted, None, sidecar_path, None);
+            self.audit(Mutation::Delete, sidecar_path, None, None)?;
+        }
+
+        Ok(orphaned.len())
+    }
+
+    /// Find sidecar files whose image no longer exists, without removing them
+    pub async fn find_orphaned_sidecars(&self, directory: &Path) -> Result<Vec<PathBuf>> {
+        let mut orphaned = Vec::new();
 
         // Find all sidecar files
         let sidecar_files = self.find_sidecar_files(directory).await?;
 
         for sidecar_path in sidecar_files {
-            // Check if corresponding image exists
-            let image_name = sidecar_path.file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("")
-                .rsplit('_')
-                .next()
-                .unwrap_or("");
-
-            let mut image_exists = false;
-            for ext in &self.image_extensions {
-                let potential_image = directory.join(format!("{}.{}", image_name, ext));
-                if potential_image.exists() {
-                    image_exists = true;
-                    break;
+            // Images sit beside their sidecars, which may be in a subdirectory
+            let image_directory = sidecar_path.parent().unwrap_or(directory);
+            // A sidecar kept at a link belongs to the link, whether or not its target is reachable
+            let image_exists = owning_image(&sidecar_path, image_directory, &self.image_extensions, &self.patterns, |candidate| {
+                match self.symlink_policy {
+                    SymlinkPolicy::FollowTarget => candidate.exists(),
+                    SymlinkPolicy::StayAtLink | SymlinkPolicy::Both => candidate.symlink_metadata().is_ok(),
                 }
-            }
+            })
+            .is_some();
 
             if !image_exists {
-                fs::remove_file(&sidecar_path).await?;
-                removed_count += 1;
-                tracing::info!("Removed orphaned sidecar: {:?}", sidecar_path);
+                orphaned.push(sidecar_path);
             }
         }
 
-        Ok(removed_count)
+        Ok(orphaned)
+    }
+
+    /// Find image symlinks under `directory` whose target no longer exists
+    ///
+    /// Each lists the sidecars beside it named after it (`IMG.json`, `prefix_IMG.json`), unless
+    /// a live image in the same directory shares its name and so still claims them.
+    pub async fn find_broken_symlinks(&self, directory: &Path) -> Result<Vec<BrokenSymlink>> {
+        let root = directory.to_path_buf();
+        let image_extensions = self.image_extensions.clone();
+        let patterns = Arc::clone(&self.patterns);
+        let scan_options = self.scan_options;
+
+        let broken = tokio::task::spawn_blocking(move || {
+            let scan = DirectoryScan::walk(&root, &image_extensions, scan_options);
+            scan.dangling
+                .iter()
+                .map(|link| {
+                    let target = std::fs::read_link(link).unwrap_or_default();
+                    let resolved_target = resolve_link_target(link, &target);
+                    let claimed = image_extensions.iter().any(|ext| scan.is_resolvable(&link.with_extension(ext)));
+                    let sidecars = if claimed {
+                        Vec::new()
+                    } else {
+                        let directory = link.parent().unwrap_or(&root);
+                        scan.sidecars
+                            .iter()
+                            .filter(|sidecar| sidecar.parent() == link.parent())
+                            .filter(|sidecar| {
+                                // The link's own extension may differ in case from the configured ones
+                                let owner = owning_image(sidecar, directory, &image_extensions, &patterns, |candidate| {
+                                    candidate.file_stem() == link.file_stem() || scan.is_resolvable(candidate)
+                                });
+                                owner.is_some_and(|owner| owner.file_stem() == link.file_stem())
+                            })
+                            .cloned()
+                            .collect()
+                    };
+                    BrokenSymlink { link: link.clone(), target, resolved_target, sidecars }
+                })
+                .collect()
+        }).await?;
+
+        Ok(broken)
+    }
+
+    /// Remove the sidecars that belong only to broken image symlinks, whatever the symlink policy
+    pub async fn cleanup_broken_symlink_sidecars(&self, directory: &Path) -> Result<usize> {
+        self.ensure_writable("remove sidecars of broken symlinks")?;
+        let sidecars: BTreeSet<PathBuf> = self.find_broken_symlinks(directory).await?
+            .into_iter()
+            .flat_map(|broken| broken.sidecars)
+            .collect();
+
+        for sidecar_path in &sidecars {
+            fs::remove_file(sidecar_path).await?;
+            tracing::info!("Removed sidecar of broken symlink: {:?}", sidecar_path);
+            self.notify(EventKind::Deleted, None, sidecar_path, None);
+            self.audit(Mutation::Delete, sidecar_path, None, None)?;
+        }
+
+        Ok(sidecars.len())
     }
 
     // Private helper methods
 
+    /// Scan `directory` through `storage` when given, otherwise walk the filesystem
+    fn walk(storage: Option<&dyn StorageBackend>, directory: &Path, image_extensions: &[String], options: ScanOptions) -> DirectoryScan {
+        match storage {
+            Some(storage) => {
+                let files = storage.list(directory).unwrap_or_else(|e| {
+                    tracing::debug!("Cannot list {:?}: {}", directory, e);
+                    Vec::new()
+                });
+                DirectoryScan::from_files(directory, files, image_extensions, options)
+            }
+            None => DirectoryScan::walk(directory, image_extensions, options),
+        }
+    }
+
+    /// Whether a file exists at `path` in the configured storage, or on disk
+    fn file_exists(&self, path: &Path) -> bool {
+        match &self.storage {
+            Some(storage) => storage.exists(path),
+            None => path.exists(),
+        }
+    }
+
+    /// Read a sidecar, refusing it unread if it is over `max_sidecar_size`
+    async fn read_file(&self, path: &Path) -> Result<Vec<u8>> {
+        let storage = self.storage.clone();
+        let path = path.to_path_buf();
+        let max_sidecar_size = self.max_sidecar_size;
+        tokio::task::spawn_blocking(move || storage::read_sidecar(storage.as_deref(), &path, max_sidecar_size)).await?
+    }
+
+    async fn write_file(&self, path: &Path, bytes: &[u8]) -> Result<()> {
+        match &self.storage {
+            Some(storage) => storage.write(path, bytes),
+            None => Ok(fs::write(paths::long_path(path), bytes).await?),
+        }
+    }
+
+    async fn remove_file(&self, path: &Path) -> Result<()> {
+        match &self.storage {
+            Some(storage) => storage.remove(path),
+            None => Ok(fs::remove_file(path).await?),
+        }
+    }
+
+    async fn rename_file(&self, from: &Path, to: &Path) -> Result<()> {
+        match &self.storage {
+            Some(storage) => storage.rename(from, to),
+            None => Ok(fs::rename(paths::long_path(from), paths::long_path(to)).await?),
+        }
+    }
+
+    /// The storage backend sidecars are kept in, if not the filesystem
+    pub(crate) fn storage_backend(&self) -> Option<&Arc<dyn StorageBackend>> {
+        self.storage.as_ref()
+    }
+
     async fn resolve_symlink(&self, path: &Path) -> Result<(PathBuf, Option<SymlinkInfo>)> {
-        if path.is_symlink() {
+        if paths::is_link(path) {
             match fs::read_link(path).await {
                 Ok(target_path) => {
+                    // Relative targets are relative to the link's directory, not the working directory
+                    let target_path = resolve_link_target(path, &target_path);
                     let broken = !target_path.exists();
-                    Ok((target_path, Some(SymlinkInfo {
+                    Ok((target_path.clone(), Some(SymlinkInfo {
                         symlink_path: path.to_path_buf(),
-                        target_path: path.canonicalize().unwrap_or_else(|_| path.to_path_buf()),
+                        target_path: path.canonicalize().map(paths::display_path).unwrap_or(target_path),
                         is_symlink: true,
                         broken,
                     })))
                 }
-                Err(_e) => Err(SidecarError::SymlinkResolutionFailed(path.to_path_buf()).into()),
+                Err(_e) => Err(SidecarError::SymlinkResolutionFailed(path.to_path_buf())),
             }
         } else {
             Ok((path.to_path_buf(), None))
         }
     }
 
-    async fn detect_operation_type(&self, sidecar_path: &Path) -> Result<OperationType> {
-        match self.load_sidecar_data(sidecar_path).await {
-            Ok(data) => {
-                // Check for sidecar_info structure
-                if let Some(sidecar_info) = data.get("sidecar_info") {
-                    if let Some(operation_str) = sidecar_info.get("operation_type").and_then(|v| v.as_str()) {
-                        return Ok(OperationType::from_str(operation_str));
-                    }
-                }
+    /// Fingerprint of the image at `path` as it is now, reusing `previous` while its size and mtime match
+    ///
+    /// `None` when the image cannot be read; the sidecar is then written without one.
+    async fn image_fingerprint(path: &Path, previous: Option<ImageFingerprint>) -> Option<ImageFingerprint> {
+        let path = path.to_path_buf();
+        tokio::task::spawn_blocking(move || ImageFingerprint::refresh(&path, previous.as_ref())).await.ok()?.ok()
+    }
 
-                // Check for detector-specific keys
-                if let Some(obj) = data.as_object() {
-                    for (key, operation_type) in &self.operation_mapping {
-                        if obj.contains_key(key) {
-                            return Ok(operation_type.clone());
-                        }
-                    }
-                }
+    /// Image paths whose sidecars are read and written for `image_path`, preferred first
+    ///
+    /// `target` is where `resolve_symlink` led; a plain file is its own single base.
+    fn sidecar_bases(&self, image_path: &Path, target: &Path, is_symlink: bool) -> Vec<PathBuf> {
+        let bases = match self.symlink_policy {
+            _ if !is_symlink => vec![image_path.to_path_buf()],
+            SymlinkPolicy::FollowTarget => vec![target.to_path_buf()],
+            SymlinkPolicy::StayAtLink => vec![image_path.to_path_buf()],
+            SymlinkPolicy::Both => vec![image_path.to_path_buf(), target.to_path_buf()],
+        };
+        if !self.scan_options.merge_hardlinks {
+            return bases;
+        }
+        bases.into_iter().map(|base| self.hardlink_path(base)).collect()
+    }
 
-                Ok(OperationType::Unknown)
-            }
-            Err(_) => Ok(OperationType::Unknown),
+    /// The path whose sidecar a hardlinked `path` shares
+    ///
+    /// That is the first of its known links, in sorted order, that already has a sidecar, or
+    /// else the first link, as a merging scan counts the image. Links are those in the same
+    /// directory as `path` and any a scan found, so the choice does not depend on which path
+    /// the file was first written or read through.
+    fn hardlink_path(&self, path: PathBuf) -> PathBuf {
+        let Some((device, inode, _)) = hardlink_id(&path) else {
+            return path;
+        };
+        let mut paths = self.hardlink_paths.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let links = paths.entry((device, inode)).or_insert_with(|| sibling_links(&path, device, inode));
+        if let Err(at) = links.binary_search(&path) {
+            links.insert(at, path);
+        }
+        links.iter().find(|link| self.existing_sidecar(std::slice::from_ref(*link)).is_some()).unwrap_or(&links[0]).clone()
+    }
+
+    /// Record the paths a merging scan counted hardlinked images under, for later reads and writes
+    fn remember_hardlinks(&self, groups: &[HardlinkGroup]) {
+        if !self.scan_options.merge_hardlinks || groups.is_empty() {
+            return;
+        }
+        let mut paths = self.hardlink_paths.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        for group in groups {
+            let links = paths.entry((group.device, group.inode)).or_default();
+            links.extend(group.paths.iter().cloned());
+            links.sort();
+            links.dedup();
         }
     }
 
+    /// The first sidecar found for `bases`, in base order and then by efficiency: bin -> rkyv -> json
+    fn existing_sidecar(&self, bases: &[PathBuf]) -> Option<PathBuf> {
+        bases
+            .iter()
+            .flat_map(|base| {
+                [SidecarFormat::Binary, SidecarFormat::Rkyv, SidecarFormat::Json].map(|format| base.with_extension(format.extension()))
+            })
+            .find(|candidate| self.file_exists(candidate))
+    }
+
+    /// Write the sidecar just saved for `image_path` next to each of the remaining `bases` as well
+    async fn write_copies(&self, image_path: &Path, bases: &[PathBuf], format: SidecarFormat, content_bytes: &[u8], operation: &str) -> Result<()> {
+        for base in bases {
+            let copy_path = base.with_extension(format.extension());
+            let kind = if self.file_exists(&copy_path) { EventKind::Updated } else { EventKind::Created };
+            self.write_file(&copy_path, content_bytes).await?;
+            self.notify(kind, Some(image_path), &copy_path, Some(operation));
+            self.audit(Mutation::Write, &copy_path, None, Some(operation))?;
+        }
+        Ok(())
+    }
+
     async fn load_sidecar_data(&self, sidecar_path: &Path) -> Result<Value> {
-        let content_bytes = fs::read(sidecar_path).await?;
-        
+        let content_bytes = self.read_file(sidecar_path).await?;
+        Self::decode_sidecar(&self.format_manager, sidecar_path, &content_bytes)
+    }
+
+    /// Read a sidecar and skim its outline, along with its size in bytes
+    async fn load_sidecar_outline(&self, sidecar_path: &Path) -> Result<(Value, u64)> {
+        let content_bytes = self.read_file(sidecar_path).await?;
+        let format_manager = Arc::clone(&self.format_manager);
+        let keys = self.classifier.skim_keys();
+        let sidecar_path = sidecar_path.to_path_buf();
+        tokio::task::spawn_blocking(move || {
+            let outline = Self::skim_sidecar(&format_manager, &keys, &sidecar_path, &content_bytes)?;
+            Ok((outline, content_bytes.len() as u64))
+        }).await?
+    }
+
+    /// Determine the operation a sidecar belongs to from its contents
+    fn operation_from_data(classifier: &OperationClassifier, data: &Value) -> OperationType {
+        // Check for sidecar_info structure
+        // `save_data` records the most recent operation as `last_operation`
+        if let Some(sidecar_info) = data.get("sidecar_info") {
+            let operation_str = sidecar_info.get("operation_type")
+                .or_else(|| sidecar_info.get("last_operation"))
+                .and_then(|v| v.as_str());
+            if let Some(operation_str) = operation_str {
+                return OperationType::from_str(operation_str);
+            }
+        }
+
+        // Raw detector output: the configured rules, then the built-in detector keys
+        classifier.classify(data).unwrap_or(OperationType::Unknown)
+    }
+
+    /// Deserialize sidecar bytes, using the path extension to pick the format
+    pub(crate) fn decode_sidecar(format_manager: &FormatManager, sidecar_path: &Path, content_bytes: &[u8]) -> Result<Value> {
         // Detect format from file extension first
         if let Some(format) = SidecarFormat::from_path(sidecar_path) {
-            let serializer = self.format_manager.get_serializer(format);
-            return serializer.deserialize(&content_bytes)
-                .map_err(|e| SidecarError::SerializationError(e.to_string()).into());
+            let serializer = format_manager.get_serializer(format);
+            return serializer.deserialize(content_bytes)
+                .map_err(SidecarError::from);
         }
         
         // Fallback: try to detect format from content
-        match self.format_manager.detect_format_from_content(&content_bytes) {
+        match format_manager.detect_format_from_content(content_bytes) {
             Ok(format) => {
-                let serializer = self.format_manager.get_serializer(format);
-                serializer.deserialize(&content_bytes)
-                    .map_err(|e| SidecarError::SerializationError(e.to_string()).into())
+                let serializer = format_manager.get_serializer(format);
+                serializer.deserialize(content_bytes)
+                    .map_err(SidecarError::from)
             }
             Err(_) => {
                 // Final fallback: try as JSON
-                let content_str = std::str::from_utf8(&content_bytes)
+                let content_str = std::str::from_utf8(content_bytes)
                     .map_err(|e| SidecarError::SerializationError(format!("Invalid UTF-8: {}", e)))?;
                 let data: Value = serde_json::from_str(content_str)?;
                 Ok(data)
@@ -482,90 +3009,663 @@ impl SidecarManager {
         }
     }
 
-    async fn find_image_files(&self, directory: &Path) -> Result<Vec<PathBuf>> {
-        let mut image_files = Vec::new();
+    /// Outline of sidecar bytes keeping the subtrees under `keys` whole
+    /// Sidecars whose extension names no format are decoded in full
+    fn skim_sidecar(format_manager: &FormatManager, keys: &SkimKeys, sidecar_path: &Path, content_bytes: &[u8]) -> Result<Value> {
+        match SidecarFormat::from_path(sidecar_path) {
+            Some(format) => Ok(skim::skim(content_bytes, format, keys)?),
+            None => Self::decode_sidecar(format_manager, sidecar_path, content_bytes),
+        }
+    }
+
+    /// Serialize `data` for `sidecar_path` with this manager's formats
+    pub(crate) fn encode(&self, sidecar_path: &Path, data: &Value) -> Result<Vec<u8>> {
+        Self::encode_sidecar(&self.format_manager, sidecar_path, data)
+    }
+
+    /// Serialize sidecar data in the format given by the path extension (JSON if unknown)
+    fn encode_sidecar(format_manager: &FormatManager, sidecar_path: &Path, data: &Value) -> Result<Vec<u8>> {
+        let format = SidecarFormat::from_path(sidecar_path).unwrap_or(SidecarFormat::Json);
+        format_manager.get_serializer(format)
+            .serialize(data)
+            .map_err(SidecarError::from)
+    }
+
+    /// `SidecarInfo` of a matched sidecar from its stored size alone, without reading it
+    fn describe_sidecar(source: &PayloadSource, sidecar_match: SidecarMatch) -> SidecarInfo {
+        let mut sidecar_info = SidecarInfo::new(
+            sidecar_match.image_path,
+            sidecar_match.sidecar_path,
+            sidecar_match.operation.unwrap_or(OperationType::Unknown),
+            None,
+        ).with_payload_source(source.clone());
+        sidecar_info.data_size = storage::sidecar_size(source.storage.as_deref(), &sidecar_info.sidecar_path).unwrap_or(0);
+        sidecar_info
+    }
+
+    /// Read and skim a matched sidecar once, producing its `SidecarInfo`
+    /// Blocking; used from the parallel scan. Only the outline is decoded, which is all the
+    /// operation, payload metrics and class counts need, given the classifier's `skim_keys`.
+    fn load_sidecar_info(
+        source: &PayloadSource,
+        classifier: &OperationClassifier,
+        skim_keys: &SkimKeys,
+        sidecar_match: SidecarMatch,
+    ) -> SidecarInfo {
+        Self::load_matched(source, classifier, sidecar_match, |path, bytes| {
+            Self::skim_sidecar(&source.formats, skim_keys, path, bytes)
+        }).0
+    }
+
+    /// Read and decode a matched sidecar, returning its info and data if it could be decoded
+    /// Sidecars over the source's size limit are not read and come back invalid; payloads kept
+    /// in the blob store are filled in.
+    fn load_sidecar(
+        source: &PayloadSource,
+        classifier: &OperationClassifier,
+        sidecar_match: SidecarMatch,
+    ) -> (SidecarInfo, Option<Value>) {
+        Self::load_matched(source, classifier, sidecar_match, |path, bytes| source.decode(path, bytes))
+    }
+
+    /// Read a matched sidecar and turn its bytes into a value with `decode`
+    fn load_matched(
+        source: &PayloadSource,
+        classifier: &OperationClassifier,
+        sidecar_match: SidecarMatch,
+        decode: impl FnOnce(&Path, &[u8]) -> Result<Value>,
+    ) -> (SidecarInfo, Option<Value>) {
+        let loaded = source.read(&sidecar_match.sidecar_path)
+            .and_then(|bytes| Ok((decode(&sidecar_match.sidecar_path, &bytes)?, bytes.len() as u64)))
+            .ok();
+        let sidecar_info = Self::matched_info(source, classifier, sidecar_match, loaded.as_ref().map(|(data, size)| (data, *size)));
+        (sidecar_info, loaded.map(|(data, _)| data))
+    }
+
+    /// `SidecarInfo` of a matched sidecar from its decoded contents and size, or an invalid
+    /// one if it could not be loaded
+    fn matched_info(
+        source: &PayloadSource,
+        classifier: &OperationClassifier,
+        sidecar_match: SidecarMatch,
+        loaded: Option<(&Value, u64)>,
+    ) -> SidecarInfo {
+        // The contents decide; a configured name pattern fills in for sidecars that do not say
+        let operation = loaded
+            .map(|(data, _)| Self::operation_from_data(classifier, data))
+            .filter(|operation| *operation != OperationType::Unknown)
+            .or(sidecar_match.operation)
+            .unwrap_or(OperationType::Unknown);
+
+        let mut sidecar_info = SidecarInfo::new(
+            sidecar_match.image_path,
+            sidecar_match.sidecar_path,
+            operation,
+            None,
+        ).with_payload_source(source.clone());
+
+        if let Some((data, size)) = loaded {
+            sidecar_info.data_size = size;
+            sidecar_info.is_valid = true;
+            sidecar_info.extract_payload_metrics(data);
+        }
+
+        sidecar_info
+    }
+
+    /// Match a pattern-named sidecar (e.g. `prefix_IMG.json`) to its image in `directory`
+    pub async fn find_image_for_pattern_sidecar(
+        &self,
+        sidecar_path: &Path,
+        directory: &Path,
+    ) -> Result<Option<SidecarInfo>> {
+        // Try to find corresponding image
+        let Some(image_path) = owning_image(sidecar_path, directory, &self.image_extensions, &self.patterns, Path::exists) else {
+            return Ok(None);
+        };
+        let loaded = self.load_sidecar_outline(sidecar_path).await.ok();
+        let operation = loaded.as_ref()
+            .map(|(outline, _)| Self::operation_from_data(&self.classifier, outline))
+            .filter(|operation| *operation != OperationType::Unknown)
+            .or_else(|| self.patterns.operation_for(sidecar_path, &image_path))
+            .unwrap_or(OperationType::Unknown);
+        let mut sidecar_info = SidecarInfo::new(
+            image_path,
+            sidecar_path.to_path_buf(),
+            operation,
+            None,
+        ).with_payload_source(self.payload_source());
+        
+        // Load and validate the sidecar
+        if let Some((outline, size)) = loaded {
+            sidecar_info.data_size = size;
+            sidecar_info.is_valid = true;
+            sidecar_info.extract_payload_metrics(&outline);
+        }
+
+        Ok(Some(sidecar_info))
+    }
+
+    /// Check whether a path has one of the configured image extensions
+    pub fn is_image_file(&self, path: &Path) -> bool {
+        path.extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .map(|ext| self.image_extensions.iter().any(|e| e == &ext))
+            .unwrap_or(false)
+    }
+
+    /// Check whether a path has one of the supported sidecar extensions
+    pub fn is_sidecar_file(&self, path: &Path) -> bool {
+        SidecarFormat::from_path(path).is_some() && !badge::is_badge_file(path)
+    }
+
+    /// The sidecars under `directory` that the manager's `FileSelection` admits, in its order
+    async fn selected_sidecar_files(&self, directory: &Path) -> Result<Vec<PathBuf>> {
+        let sidecar_files = self.find_sidecar_files(directory).await?;
+        if self.selection.is_everything() {
+            return Ok(sidecar_files);
+        }
+        let selection = self.selection;
+        Ok(tokio::task::spawn_blocking(move || selection.apply(sidecar_files)).await?)
+    }
+
+    pub(crate) async fn find_sidecar_files(&self, directory: &Path) -> Result<Vec<PathBuf>> {
+        let files: Vec<PathBuf> = match &self.storage {
+            Some(storage) => {
+                let (storage, directory) = (Arc::clone(storage), directory.to_path_buf());
+                tokio::task::spawn_blocking(move || storage.list(&directory)).await??
+            }
+            None => WalkDir::new(directory)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|entry| entry.file_type().is_file())
+                .map(|entry| entry.into_path())
+                .collect(),
+        };
+
+        // Look for all supported sidecar formats
+        Ok(files
+            .into_iter()
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| matches!(extension.to_string_lossy().to_lowercase().as_str(), "json" | "bin" | "rkyv"))
+                    && !badge::is_badge_file(path)
+            })
+            .collect())
+    }
 
-        for entry in WalkDir::new(directory).into_iter().filter_map(|e| e.ok()) {
-            if entry.file_type().is_file() {
-                let path = entry.path();
-                if let Some(extension) = path.extension() {
-                    let ext_str = extension.to_string_lossy().to_lowercase();
-                    if self.image_extensions.iter().any(|ext| ext == &ext_str) {
-                        image_files.push(path.to_path_buf());
+    /// Apply a retention policy to every sidecar in a directory
+    /// With `NewestVersionOnly`, older sidecars of images that have several are removed first.
+    /// Expired payloads are then removed from the remaining sidecars, and sidecars left with
+    /// no payload are deleted. A dry run writes nothing and reports what would change.
+    pub async fn collect_garbage(&self, directory: &Path, policy: &RetentionPolicy, dry_run: bool) -> Result<Vec<GcRecord>> {
+        if !dry_run {
+            self.ensure_writable("remove expired sidecar data")?;
+        }
+        let mut records = Vec::new();
+        let mut sidecar_files = self.find_sidecar_files(directory).await?;
+
+        if policy.keeps_newest_version_only() {
+            let mut versions: BTreeMap<PathBuf, Vec<(SystemTime, PathBuf)>> = BTreeMap::new();
+            for sidecar_path in sidecar_files.drain(..) {
+                let modified = Self::modified_time(&sidecar_path).await;
+                versions.entry(sidecar_path.with_extension("")).or_default().push((modified, sidecar_path));
+            }
+            for (_, mut group) in versions {
+                group.sort();
+                let Some((_, newest)) = group.pop() else { continue };
+                for (_, older) in group {
+                    let mut record = GcRecord {
+                        sidecar_path: older.clone(),
+                        actions: vec![GcAction::Superseded { newer: newest.clone() }],
+                        error: None,
+                    };
+                    if !dry_run {
+                        match self.remove_file(&older).await {
+                            Ok(()) => {
+                                self.notify(EventKind::Deleted, None, &older, None);
+                                self.audit(Mutation::Delete, &older, None, None)?;
+                            }
+                            Err(e) => record.error = Some(e.to_string()),
+                        }
                     }
+                    records.push(record);
+                }
+                sidecar_files.push(newest);
+            }
+        }
+
+        let now = Utc::now();
+        for sidecar_path in sidecar_files {
+            let data = match self.load_sidecar_data(&sidecar_path).await {
+                Ok(data) => data,
+                Err(e) => {
+                    tracing::debug!("Skipping unreadable sidecar {:?}: {}", sidecar_path, e);
+                    continue;
+                }
+            };
+            let modified = DateTime::<Utc>::from(Self::modified_time(&sidecar_path).await);
+            let expired = policy.expired_operations(&data, modified, now);
+            if expired.is_empty() {
+                continue;
+            }
+
+            let keys: Vec<String> = expired.iter().map(|(key, _)| key.clone()).collect();
+            let mut actions: Vec<GcAction> = expired
+                .into_iter()
+                .map(|(operation, updated_at)| GcAction::ExpiredOperation { operation, updated_at })
+                .collect();
+            let mut error = None;
+            if dry_run {
+                if Self::payload_keys(&data).all(|key| keys.contains(key)) {
+                    actions.push(GcAction::RemovedEmpty);
+                }
+            } else {
+                match self.remove_payloads(&sidecar_path, data, &keys).await {
+                    Ok(true) => actions.push(GcAction::RemovedEmpty),
+                    Ok(false) => {}
+                    Err(e) => error = Some(e.to_string()),
                 }
             }
+            records.push(GcRecord { sidecar_path, actions, error });
         }
 
-        Ok(image_files)
+        Ok(records)
     }
 
-    async fn find_pattern_sidecars(&self, directory: &Path) -> Result<Vec<SidecarInfo>> {
-        let mut sidecars = Vec::new();
-        let sidecar_files = self.find_sidecar_files(directory).await?;
+    /// Remove one operation's payload from an image's sidecar
+    /// `sidecar_info` is updated to match, and the sidecar is deleted once no payload is left
+    pub async fn remove_operation(&self, image_path: &Path, operation: OperationType) -> Result<OperationRemoval> {
+        self.ensure_writable("remove operations")?;
+        let (actual_image_path, symlink_info) = self.resolve_symlink(image_path).await?;
+        let bases = self.sidecar_bases(image_path, &actual_image_path, symlink_info.is_some());
+        // Every copy is stripped, so `SymlinkPolicy::Both` leaves none holding the payload
+        let mut removal = None;
+        for base in &bases {
+            if let Some(sidecar_path) = self.existing_sidecar(std::slice::from_ref(base)) {
+                let stripped = self.strip_sidecar(&sidecar_path, operation.as_str(), false).await?;
+                removal.get_or_insert(stripped);
+            }
+        }
+        Ok(removal.unwrap_or(OperationRemoval::Absent))
+    }
 
-        for sidecar_path in sidecar_files {
-            // Try to find corresponding image
-            let image_name = sidecar_path.file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("")
-                .rsplit('_')
-                .next()
-                .unwrap_or("");
-
-            for ext in &self.image_extensions {
-                let potential_image = directory.join(format!("{}.{}", image_name, ext));
-                if potential_image.exists() {
-                    let operation = self.detect_operation_type(&sidecar_path).await?;
-                    let mut sidecar_info = SidecarInfo::new(
-                        potential_image,
-                        sidecar_path.clone(),
-                        operation,
-                        None,
-                    );
-                    
-                    // Load and validate the sidecar
-                    if let Ok(data) = self.load_sidecar_data(&sidecar_path).await {
-                        sidecar_info.data_size = data.to_string().len() as u64;
-                        sidecar_info.is_valid = true;
+    /// Remove one operation's payload from every sidecar in a directory
+    /// Returns the sidecars that held the operation; unreadable sidecars are skipped.
+    /// A dry run writes nothing and reports what would happen.
+    pub async fn strip_operation(
+        &self,
+        directory: &Path,
+        operation: OperationType,
+        dry_run: bool,
+    ) -> Result<Vec<(PathBuf, OperationRemoval)>> {
+        if !dry_run {
+            self.ensure_writable("remove operations")?;
+        }
+        let mut stripped = Vec::new();
+        for sidecar_path in self.find_sidecar_files(directory).await? {
+            match self.strip_sidecar(&sidecar_path, operation.as_str(), dry_run).await {
+                Ok(OperationRemoval::Absent) => {}
+                Ok(removal) => stripped.push((sidecar_path, removal)),
+                Err(e) => tracing::debug!("Skipping unreadable sidecar {:?}: {}", sidecar_path, e),
+            }
+        }
+        Ok(stripped)
+    }
+
+    async fn strip_sidecar(&self, sidecar_path: &Path, key: &str, dry_run: bool) -> Result<OperationRemoval> {
+        let data = self.load_sidecar_data(sidecar_path).await?;
+        if data.get(key).is_none() {
+            return Ok(OperationRemoval::Absent);
+        }
+        let emptied = if dry_run {
+            Self::payload_keys(&data).all(|other| other == key)
+        } else {
+            self.remove_payloads(sidecar_path, data, &[key.to_string()]).await?
+        };
+        Ok(if emptied { OperationRemoval::SidecarDeleted } else { OperationRemoval::Removed })
+    }
+
+    async fn modified_time(path: &Path) -> SystemTime {
+        fs::metadata(path).await.and_then(|metadata| metadata.modified()).unwrap_or(SystemTime::UNIX_EPOCH)
+    }
+
+    /// Top-level keys holding operation payloads, i.e. everything but `sidecar_info`
+    fn payload_keys(data: &Value) -> impl Iterator<Item = &String> {
+        data.as_object().into_iter().flat_map(|object| object.keys()).filter(|key| *key != "sidecar_info")
+    }
+
+    /// Remove the payloads under `keys` from a sidecar and rewrite it in its own format
+    /// `sidecar_info` keeps a write time for every remaining payload and names the most recent
+    /// one as `last_operation`. Returns `true` if nothing was left and the file was deleted.
+    async fn remove_payloads(&self, sidecar_path: &Path, mut data: Value, keys: &[String]) -> Result<bool> {
+        let Some(object) = data.as_object_mut() else {
+            return Ok(false);
+        };
+        for key in keys {
+            object.remove(key);
+        }
+
+        let remaining: Vec<String> = object.keys().filter(|key| *key != "sidecar_info").cloned().collect();
+        if remaining.is_empty() {
+            self.remove_file(sidecar_path).await?;
+            self.notify(EventKind::Deleted, None, sidecar_path, None);
+            self.audit(Mutation::Delete, sidecar_path, None, None)?;
+            return Ok(true);
+        }
+
+        if let Some(info) = object.get_mut("sidecar_info").and_then(Value::as_object_mut) {
+            if let Some(producers) = info.get_mut("provenance").and_then(Value::as_object_mut) {
+                producers.retain(|key, _| remaining.contains(key));
+            }
+            if let Some(signatures) = info.get_mut(SIGNATURES_KEY).and_then(Value::as_object_mut) {
+                signatures.retain(|key, _| remaining.contains(key));
+            }
+            // Payloads written before per-operation times were recorded keep the sidecar's old time,
+            // so bumping `last_updated` below does not make them look fresh
+            let previous = info.get("last_updated").or_else(|| info.get("created_at")).cloned();
+            let times = info.entry("operation_updated").or_insert_with(|| Value::Object(serde_json::Map::new()));
+            if let Some(times) = times.as_object_mut() {
+                times.retain(|key, _| remaining.contains(key));
+                for key in &remaining {
+                    if let (false, Some(previous)) = (times.contains_key(key), &previous) {
+                        times.insert(key.clone(), previous.clone());
                     }
+                }
+            }
+            let latest = info
+                .get("operation_updated")
+                .and_then(Value::as_object)
+                .and_then(|times| times.iter().max_by(|a, b| a.1.as_str().cmp(&b.1.as_str())).map(|(key, _)| key.clone()))
+                .unwrap_or_else(|| remaining[0].clone());
+            info.insert("last_operation".to_string(), Value::String(latest));
+            info.insert("last_updated".to_string(), Value::String(Utc::now().to_rfc3339()));
+        }
 
-                    sidecars.push(sidecar_info);
-                    break;
+        let format = SidecarFormat::from_path(sidecar_path).unwrap_or(SidecarFormat::Json);
+        let content_bytes = self.format_manager.get_serializer(format).serialize(&data)?;
+        self.write_file(sidecar_path, &content_bytes).await?;
+        self.notify(EventKind::Updated, None, sidecar_path, None);
+        self.audit(Mutation::Write, sidecar_path, None, None)?;
+        Ok(false)
+    }
+
+    /// Move every inline payload under `directory` into the blob store, leaving pointers
+    /// Sidecars keep their format. Returns the number of payloads moved.
+    pub async fn store_payloads(&self, directory: &Path) -> Result<usize> {
+        self.ensure_writable("move payloads to the blob store")?;
+        let store = self.require_blob_store()?;
+        let mut moved = 0;
+        for sidecar_path in self.find_sidecar_files(directory).await? {
+            let mut data = self.load_sidecar_data(&sidecar_path).await?;
+            let Some(object) = data.as_object_mut() else { continue };
+            let mut changed = false;
+            for (_, payload) in object.iter_mut().filter(|(key, _)| *key != "sidecar_info") {
+                if blobs::pointer_hash(payload).is_none() {
+                    *payload = blobs::pointer(&store.put(payload)?);
+                    changed = true;
+                    moved += 1;
                 }
             }
+            if changed {
+                self.write_file(&sidecar_path, &self.encode(&sidecar_path, &data)?).await?;
+                self.notify(EventKind::Updated, None, &sidecar_path, None);
+                self.audit(Mutation::Write, &sidecar_path, None, None)?;
+            }
         }
+        Ok(moved)
+    }
 
-        Ok(sidecars)
+    /// Check that every pointer under `directory` refers to an intact blob
+    pub async fn verify_blobs(&self, directory: &Path) -> Result<BlobReport> {
+        let store = self.require_blob_store()?;
+        let mut report = BlobReport::default();
+        // Each blob is read and hashed once, however many sidecars share it
+        let mut checked: BTreeMap<String, Option<String>> = BTreeMap::new();
+        for sidecar_path in self.find_sidecar_files(directory).await? {
+            let Ok(data) = self.load_sidecar_data(&sidecar_path).await else { continue };
+            let pointers: Vec<(&String, &str)> = Self::payload_keys(&data)
+                .filter_map(|key| blobs::pointer_hash(&data[key.as_str()]).map(|hash| (key, hash)))
+                .collect();
+            if pointers.is_empty() {
+                continue;
+            }
+            report.sidecars += 1;
+            report.pointers += pointers.len();
+            for (operation, hash) in pointers {
+                let error = checked
+                    .entry(hash.to_string())
+                    .or_insert_with(|| store.get(hash).err().map(|e| e.to_string()));
+                if let Some(error) = error {
+                    report.issues.push(BlobIssue {
+                        sidecar_path: sidecar_path.clone(),
+                        operation: operation.clone(),
+                        hash: hash.to_string(),
+                        error: error.clone(),
+                    });
+                }
+            }
+        }
+        report.referenced = checked.len();
+        report.unreferenced = store.hashes()?.into_iter().filter(|hash| !checked.contains_key(hash)).collect();
+        Ok(report)
     }
 
-    async fn find_sidecar_files(&self, directory: &Path) -> Result<Vec<PathBuf>> {
-        let mut sidecar_files = Vec::new();
+    /// Check every payload under `directory` against its signature and the trusted keys
+    ///
+    /// Unsigned payloads are reported too, since nothing vouches for them, and so are sidecars
+    /// that cannot be read. Signatures cover the contents of the image beside the sidecar, so
+    /// a payload copied from another image's sidecar, or one whose image is gone, is invalid.
+    pub async fn verify_signatures(&self, directory: &Path, verifier: &SignatureVerifier) -> Result<SignatureReport> {
+        let mut report = SignatureReport::default();
+        for sidecar_path in self.find_sidecar_files(directory).await? {
+            let data = match self.load_sidecar_data(&sidecar_path).await {
+                Ok(data) => data,
+                Err(e) => {
+                    report.sidecars += 1;
+                    let problem = SignatureProblem::Unreadable { reason: e.to_string() };
+                    report.issues.push(SignatureIssue { sidecar_path, payload: String::new(), problem });
+                    continue;
+                }
+            };
+            let image = match sidecar_path.parent().and_then(|parent| {
+                owning_image(&sidecar_path, parent, &self.image_extensions, &self.patterns, |path| self.file_exists(path))
+            }) {
+                Some(image_path) => tokio::task::spawn_blocking(move || signing::image_identity(&image_path)).await?,
+                None => String::new(),
+            };
+            let signatures = data.get("sidecar_info").and_then(|info| info.get(SIGNATURES_KEY));
+            let mut payloads = 0;
+            for key in Self::payload_keys(&data) {
+                payloads += 1;
+                let record = signatures
+                    .and_then(|signatures| signatures.get(key.as_str()))
+                    .and_then(|record| serde_json::from_value::<SignatureRecord>(record.clone()).ok());
+                let outcome = match self.resolve_payload(&data[key.as_str()]) {
+                    Ok(payload) => verifier.verify(&image, key, &payload, record.as_ref()),
+                    // A payload that cannot be read cannot be vouched for
+                    Err(_) => Err(SignatureProblem::Invalid),
+                };
+                match outcome {
+                    Ok(()) => report.verified += 1,
+                    Err(problem) => report.issues.push(SignatureIssue { sidecar_path: sidecar_path.clone(), payload: key.clone(), problem }),
+                }
+            }
+            if payloads > 0 {
+                report.sidecars += 1;
+                report.payloads += payloads;
+            }
+        }
+        Ok(report)
+    }
 
-        for entry in WalkDir::new(directory).into_iter().filter_map(|e| e.ok()) {
-            if entry.file_type().is_file() {
-                let path = entry.path();
-                if let Some(extension) = path.extension() {
-                    let ext_str = extension.to_string_lossy().to_lowercase();
-                    // Look for all supported sidecar formats
-                    if matches!(ext_str.as_str(), "json" | "bin" | "rkyv") {
-                        sidecar_files.push(path.to_path_buf());
-                    }
+    /// Bundle every sidecar under `directory` into the pack at `output`, returning how many
+    /// With `remove`, the pack is read back first and each sidecar is deleted only once its
+    /// packed copy checks out, so a failed run never loses data.
+    pub async fn pack_directory(&self, directory: &Path, output: &Path, remove: bool) -> Result<usize> {
+        if remove {
+            self.ensure_writable("remove packed sidecars")?;
+        }
+        let sidecar_files = self.find_sidecar_files(directory).await?;
+        let (root, target) = (directory.to_path_buf(), output.to_path_buf());
+        let files = sidecar_files.clone();
+        let packed = tokio::task::spawn_blocking(move || {
+            let mut writer = PackWriter::create(&target, &root)?;
+            for sidecar_path in &files {
+                writer.add(sidecar_path)?;
+            }
+            writer.finish()
+        }).await??;
+
+        if remove {
+            let pack = Pack::open(output)?;
+            for sidecar_path in &sidecar_files {
+                pack.read_bytes(&manifest::relative_path(directory, sidecar_path))?;
+                fs::remove_file(sidecar_path).await?;
+                self.notify(EventKind::Deleted, None, sidecar_path, None);
+                self.audit(Mutation::Delete, sidecar_path, None, None)?;
+            }
+        }
+        Ok(packed)
+    }
+
+    /// Write the sidecars in `pack` below `destination`; existing files are left alone
+    pub async fn unpack(&self, pack: &Pack, destination: &Path) -> Result<UnpackSummary> {
+        self.ensure_writable("unpack sidecars")?;
+        let summary = pack.unpack(destination)?;
+        for sidecar_path in &summary.written {
+            self.notify(EventKind::Created, None, sidecar_path, None);
+            self.audit(Mutation::Write, sidecar_path, None, None)?;
+        }
+        Ok(summary)
+    }
+
+    /// Stream the sidecars under `directory`, and optionally their images, into a tarball
+    /// Entries keep their path relative to `directory`; sidecars are copied as stored. Files
+    /// that a symlink resolves to outside `directory` are left out and listed in the summary.
+    pub async fn export_archive(&self, directory: &Path, output: &Path, options: &ArchiveOptions) -> Result<ArchiveSummary> {
+        let mut sidecars = self.find_all_sidecars(directory).await?;
+        sidecars.sort_by(|a, b| a.sidecar_path.cmp(&b.sidecar_path));
+        let canonical_root = directory.canonicalize()?;
+        let inside_root = |path: &Path| path.canonicalize().is_ok_and(|resolved| resolved.starts_with(&canonical_root));
+
+        let mut summary = ArchiveSummary::default();
+        let mut files = Vec::new();
+        let mut images = std::collections::BTreeSet::new();
+        for info in sidecars {
+            if !inside_root(&info.sidecar_path) {
+                summary.outside_root.push(info.sidecar_path);
+                continue;
+            }
+            if !options.operations.is_empty() {
+                let data = self.load_sidecar_data(&info.sidecar_path).await.unwrap_or(Value::Null);
+                if !Self::payload_keys(&data).any(|key| options.operations.contains(key)) {
+                    summary.filtered += 1;
+                    continue;
+                }
+            }
+            summary.sidecars += 1;
+            files.push(info.sidecar_path);
+            // Several sidecars can belong to one image; it is archived once
+            if options.include_images && info.image_path.is_file() && images.insert(info.image_path.clone()) {
+                if inside_root(&info.image_path) {
+                    files.push(info.image_path);
+                } else {
+                    summary.outside_root.push(info.image_path);
                 }
             }
         }
+        summary.images = images.len() - summary.outside_root.iter().filter(|path| images.contains(*path)).count();
+
+        let (root, target, format) = (directory.to_path_buf(), output.to_path_buf(), options.format);
+        tokio::task::spawn_blocking(move || {
+            let mut writer = ArchiveWriter::create(&target, &root, format)?;
+            for path in &files {
+                writer.add(path)?;
+            }
+            writer.finish()
+        }).await??;
+        Ok(summary)
+    }
+
+    /// Write one tf.Example per image with detections under `directory` into TFRecord shards
+    /// Detections from every sidecar of an image go into one example. Class ids count up from 1
+    /// in class-name order and are written as a label map next to the output (`<output>.pbtxt`).
+    pub async fn export_tfrecord(&self, directory: &Path, output: &Path, options: &TfRecordOptions) -> Result<TfRecordSummary> {
+        let operations = options.operations.clone();
+        let found = self.scan_decoded(directory, move |sidecar, data| {
+            let objects = tfrecord::detection_objects(&data, &operations);
+            if objects.is_empty() { Vec::new() } else { vec![(sidecar.image_path, objects)] }
+        }).await?;
+        let mut images: BTreeMap<PathBuf, Vec<TfObject>> = BTreeMap::new();
+        for (image_path, objects) in found {
+            images.entry(image_path).or_default().extend(objects);
+        }
+        let classes: BTreeSet<String> = images.values().flatten().map(|object| object.class.clone()).collect();
+        let labels: BTreeMap<String, i64> = classes.into_iter().zip(1..).collect();
+
+        let (output, options) = (output.to_path_buf(), options.clone());
+        tokio::task::spawn_blocking(move || {
+            let shards = tfrecord::shard_paths(&output, options.shards);
+            let mut writers = shards.iter().map(|path| TfRecordWriter::create(path)).collect::<Result<Vec<_>>>()?;
+            let mut summary = TfRecordSummary { shards, label_map: output.with_extension("pbtxt"), ..Default::default() };
+            for (image_path, objects) in &images {
+                let Ok(dimensions) = image::image_dimensions(image_path) else {
+                    summary.unreadable_images.push(image_path.clone());
+                    continue;
+                };
+                let encoded = if options.include_images { Some(std::fs::read(image_path)?) } else { None };
+                let (example, kept) = TfExample::detection(image_path, dimensions, objects, &labels, encoded);
+                // Examples are dealt to the shards in turn so they come out the same size
+                let shard = summary.examples % writers.len();
+                writers[shard].write(&example.encode())?;
+                summary.examples += 1;
+                summary.detections += kept;
+                summary.skipped_detections += objects.len() - kept;
+            }
+            for writer in writers {
+                writer.finish()?;
+            }
+            std::fs::write(&summary.label_map, tfrecord::label_map_text(&labels))?;
+            Ok(summary)
+        }).await?
+    }
+
+    /// Write one WebDataset sample per image under `directory` into tar shards next to `output`
+    /// Payloads from every sidecar of an image go into the sample's `<key>.json`; images with
+    /// no payload left after the operation filter are not exported.
+    pub async fn export_webdataset(&self, directory: &Path, output: &Path, options: &WebDatasetOptions) -> Result<WebDatasetSummary> {
+        let operations = options.operations.clone();
+        let found = self.scan_decoded(directory, move |sidecar, data| {
+            let payloads = webdataset::sample_payloads(&data, &operations);
+            if payloads.is_empty() { Vec::new() } else { vec![(sidecar.image_path, payloads)] }
+        }).await?;
+        let mut images: BTreeMap<PathBuf, serde_json::Map<String, Value>> = BTreeMap::new();
+        for (image_path, payloads) in found {
+            images.entry(image_path).or_default().extend(payloads);
+        }
 
-        Ok(sidecar_files)
+        let (root, output, options) = (directory.to_path_buf(), output.to_path_buf(), options.clone());
+        tokio::task::spawn_blocking(move || webdataset::write_shards(&images, &root, &output, &options)).await?
     }
 
     /// Convert a sidecar file from one format to another
+    /// The target is written to a temporary file, synced and read back, and renamed into place
+    /// only once that copy matches the source; the source is then removed unless originals are
+    /// kept. A failed check leaves both the source and any existing target as they were.
     pub async fn convert_sidecar_format(
         &self,
         sidecar_path: &Path,
         target_format: SidecarFormat,
     ) -> Result<PathBuf> {
+        self.ensure_writable("convert sidecars")?;
+        let started = Instant::now();
+        let _file = self.throttle.open_file().await;
+
         // Load the existing sidecar data
-        let data = self.load_sidecar_data(sidecar_path).await?;
+        let content_bytes = self.read_file(sidecar_path).await?;
+        self.throttle.record_read(content_bytes.len() as u64).await;
+        let data = Self::decode_sidecar(&self.format_manager, sidecar_path, &content_bytes)?;
         
         // Determine the current format
         let current_format = SidecarFormat::from_path(sidecar_path)
@@ -580,45 +3680,155 @@ impl SidecarManager {
         
         // Serialize to new format
         let serializer = self.format_manager.get_serializer(target_format);
-        let content_bytes = serializer.serialize(&data)
-            .map_err(|e| SidecarError::SerializationError(e.to_string()))?;
+        let content_bytes = serializer.serialize(&data)?;
         
-        // Write the new file
-        fs::write(&target_path, content_bytes).await?;
+        // Write beside the target, so the source stays the only copy until a complete, synced
+        // target exists
+        let mut temp_name = target_path.file_name().unwrap_or_default().to_os_string();
+        temp_name.push(".tmp");
+        let temp_path = target_path.with_file_name(temp_name);
+        let staged = match &self.storage {
+            Some(storage) => storage.write(&temp_path, &content_bytes),
+            None => Self::write_synced(&temp_path, &content_bytes).await,
+        };
+
+        // Read the copy back and only move it over the target once it decodes to the same payload
+        let moved = match staged {
+            Ok(()) => match self.read_file(&temp_path).await.and_then(|bytes| Ok(serializer.deserialize(&bytes)?)) {
+                Ok(written) if written == data => self.rename_file(&temp_path, &target_path).await,
+                Ok(_) => Err(SidecarError::ProcessingError(format!(
+                    "Converted sidecar {:?} does not match its source; kept {:?}", target_path, sidecar_path
+                ))),
+                Err(e) => Err(SidecarError::ProcessingError(format!(
+                    "Converted sidecar {:?} could not be read back ({}); kept {:?}", target_path, e, sidecar_path
+                ))),
+            },
+            Err(e) => Err(e),
+        };
+        if let Err(e) = moved {
+            let _ = self.remove_file(&temp_path).await;
+            return Err(e);
+        }
         
-        // Remove the old file
-        fs::remove_file(sidecar_path).await?;
+        if !self.keep_originals {
+            self.remove_file(sidecar_path).await?;
+        }
+        self.audit(Mutation::Convert, sidecar_path, Some(&target_path), None)?;
+        if let Some(metrics) = &self.metrics {
+            metrics.record_conversion(1, started.elapsed());
+        }
         
         Ok(target_path)
     }
 
+    async fn write_synced(path: &Path, content_bytes: &[u8]) -> Result<()> {
+        let mut file = fs::File::create(paths::long_path(path)).await?;
+        file.write_all(content_bytes).await?;
+        file.sync_all().await?;
+        Ok(())
+    }
+
     /// Convert all sidecar files in a directory to a target format
     pub async fn convert_directory_format(
         &self,
         directory: &Path,
         target_format: SidecarFormat,
     ) -> Result<u32> {
-        let sidecar_files = self.find_sidecar_files(directory).await?;
-        let mut converted_count = 0;
+        self.convert_directory_format_with_progress(directory, target_format, None).await
+    }
+
+    /// Convert all sidecar files in a directory to a target format, reporting progress per file
+    pub async fn convert_directory_format_with_progress(
+        &self,
+        directory: &Path,
+        target_format: SidecarFormat,
+        progress: Option<ProgressCallback>,
+    ) -> Result<u32> {
+        Ok(self.convert_directory(directory, target_format, None, progress).await?.converted)
+    }
+
+    /// Convert all sidecar files in a directory, skipping those `checkpoint` lists as done
+    /// Each converted file and its replacement are recorded in the checkpoint straight away, so
+    /// rerunning after an interruption picks up where the previous run stopped
+    pub async fn convert_directory_format_checkpointed(
+        &self,
+        directory: &Path,
+        target_format: SidecarFormat,
+        checkpoint: &mut Checkpoint,
+        progress: Option<ProgressCallback>,
+    ) -> Result<ConversionSummary> {
+        self.convert_directory(directory, target_format, Some(checkpoint), progress).await
+    }
+
+    /// Convert the listed sidecar files, such as those a pipeline stage just wrote
+    /// Files already in the target format are skipped; missing or unreadable files count as failed
+    pub async fn convert_files(&self, sidecar_paths: &[PathBuf], target_format: SidecarFormat) -> Result<ConversionSummary> {
+        self.convert_files_with_progress(sidecar_paths, target_format, None).await
+    }
+
+    /// Convert the listed sidecar files, reporting progress per file
+    pub async fn convert_files_with_progress(
+        &self,
+        sidecar_paths: &[PathBuf],
+        target_format: SidecarFormat,
+        progress: Option<ProgressCallback>,
+    ) -> Result<ConversionSummary> {
+        self.convert_paths(sidecar_paths, target_format, None, progress).await
+    }
+
+    async fn convert_directory(
+        &self,
+        directory: &Path,
+        target_format: SidecarFormat,
+        checkpoint: Option<&mut Checkpoint>,
+        progress: Option<ProgressCallback>,
+    ) -> Result<ConversionSummary> {
+        let sidecar_files = self.selected_sidecar_files(directory).await?;
+        self.convert_paths(&sidecar_files, target_format, checkpoint, progress).await
+    }
+
+    async fn convert_paths(
+        &self,
+        sidecar_files: &[PathBuf],
+        target_format: SidecarFormat,
+        mut checkpoint: Option<&mut Checkpoint>,
+        progress: Option<ProgressCallback>,
+    ) -> Result<ConversionSummary> {
+        // Refuse up front rather than counting every file as failed
+        self.ensure_writable("convert sidecars")?;
+        let tracker = ProgressTracker::new(progress, sidecar_files.len());
+        let mut summary = ConversionSummary::default();
         
         for sidecar_path in sidecar_files {
-            let current_format = SidecarFormat::from_path(&sidecar_path)
+            let current_format = SidecarFormat::from_path(sidecar_path)
                 .unwrap_or(SidecarFormat::Json);
-            
-            if current_format != target_format {
-                match self.convert_sidecar_format(&sidecar_path, target_format).await {
-                    Ok(_) => {
-                        converted_count += 1;
+
+            if checkpoint.as_ref().is_some_and(|checkpoint| checkpoint.contains(sidecar_path)) {
+                summary.resumed += 1;
+            } else if current_format == target_format {
+                summary.skipped += 1;
+            } else {
+                match self.convert_sidecar_format(sidecar_path, target_format).await {
+                    Ok(target_path) => {
+                        summary.converted += 1;
                         tracing::info!("Converted {:?} to {:?}", sidecar_path, target_format);
+                        if let Some(checkpoint) = checkpoint.as_deref_mut() {
+                            checkpoint.record(sidecar_path)?;
+                            checkpoint.record(&target_path)?;
+                        }
                     }
                     Err(e) => {
+                        summary.failed += 1;
                         tracing::warn!("Failed to convert {:?}: {}", sidecar_path, e);
                     }
                 }
             }
+
+            // Reported once the file is settled, so `done` never counts a file still in flight
+            tracker.advance(sidecar_path);
         }
         
-        Ok(converted_count)
+        Ok(summary)
     }
 
     /// Set the default format for new sidecar files
@@ -631,9 +3841,9 @@ impl SidecarManager {
         self.default_format
     }
 
-    /// Get format statistics for a directory
+    /// Get format statistics for the sidecars in a directory that the `FileSelection` admits
     pub async fn get_format_statistics(&self, directory: &Path) -> Result<HashMap<SidecarFormat, u32>> {
-        let sidecar_files = self.find_sidecar_files(directory).await?;
+        let sidecar_files = self.selected_sidecar_files(directory).await?;
         let mut format_counts = HashMap::new();
         
         for sidecar_path in sidecar_files {
@@ -1,4 +1,4 @@
-/**
+/*
  * This code written by Claude Sonnet 4 (claude-3-5-sonnet-20241022)
  * Generated via Cursor IDE (cursor.sh) with AI assistance
  * Model: Anthropic Claude 3.5 Sonnet
@@ -10,17 +10,193 @@
  * - IDE: Cursor (cursor.sh)
  * - Generation method: AI-assisted pair programming
  * - Code style: Rust idiomatic with comprehensive error handling
- * - Dependencies: tokio, serde, rayon, anyhow
+ * - Dependencies: tokio, serde, rayon
  */
 
 pub mod formats;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod manager;
 pub mod types;
+pub mod skim;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod operations;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod scan;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod patterns;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod classify;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod triage;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod review;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod gate;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod timeline;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod tracks;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod lint;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod anomaly;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod bench;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod generate;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod storage;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod payload;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod checkpoint;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod watch;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod audit;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod provenance;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod blobs;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod pack;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod archive;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod tfrecord;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod webdataset;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod redact;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod signing;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod snapshot;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod git;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod dvc;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod badge;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod coverage;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod pipeline;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod show;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod jobs;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod legacy;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod fingerprint;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod reassociate;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod relocate;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod transaction;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod unify;
+pub mod repair;
+pub mod retention;
+pub mod manifest;
+pub mod diff;
 
-pub use formats::{SidecarFormat, FormatManager, SidecarSerializer, SerializationError};
-pub use manager::SidecarManager;
+pub use formats::{SidecarFormat, FormatManager, SidecarSerializer, SerializationError, CanonicalSerializer};
+pub use skim::{skim, SkimKeys};
+#[cfg(not(target_arch = "wasm32"))]
+pub use manager::{SidecarManager, SidecarManagerBuilder};
 pub use types::{
-    SidecarInfo, OperationType, SidecarError, ValidationResult, StatisticsResult
+    SidecarInfo, OperationType, SidecarError, ValidationResult, StatisticsResult,
+    MultiRootStatistics, TimeBucket, TimeSeriesPoint, MergeStrategy, ConversionSummary, OperationRemoval,
+    OperationTotals, SymlinkPolicy, BrokenSymlink, HardlinkGroup, ClassDistribution, ClassImbalance, CLASS_OPERATIONS,
+    DEFAULT_IMBALANCE_RATIO, DEFAULT_MAX_SIDECAR_SIZE,
 };
+#[cfg(not(target_arch = "wasm32"))]
 pub use operations::SidecarOperations;
+#[cfg(not(target_arch = "wasm32"))]
+pub use scan::{FileSelection, ScanOptions, ScanOrder};
+#[cfg(not(target_arch = "wasm32"))]
+pub use patterns::{SidecarPattern, SidecarPatterns};
+#[cfg(not(target_arch = "wasm32"))]
+pub use classify::{ClassifierRule, OperationClassifier, RuleCondition};
+#[cfg(not(target_arch = "wasm32"))]
+pub use triage::{TriageReport, UnknownGroup};
+#[cfg(not(target_arch = "wasm32"))]
+pub use review::{ReviewItem, ReviewOptions, ReviewQueue, ReviewSelection};
+#[cfg(not(target_arch = "wasm32"))]
+pub use gate::{GateAction, GateReport, GateSummary, GatedImage, QualityGate};
+#[cfg(not(target_arch = "wasm32"))]
+pub use timeline::{FrameKey, FrameNaming, Gap, GapKind, Sequence, Timeline, TimelineBucket, TimelineInterval, TimelineOptions};
+#[cfg(not(target_arch = "wasm32"))]
+pub use tracks::{Track, TrackPoint, TrackSummary};
+#[cfg(not(target_arch = "wasm32"))]
+pub use lint::{LintFinding, LintIssue, LintLevel, LintReport, LintRule, Linter};
+#[cfg(not(target_arch = "wasm32"))]
+pub use anomaly::{Anomaly, AnomalyKind, AnomalyOptions, AnomalyReport, AnomalySample, OperationBaseline};
+#[cfg(not(target_arch = "wasm32"))]
+pub use bench::{BenchOptions, BenchReport, ConversionBench, FormatBench, StageStats};
+#[cfg(not(target_arch = "wasm32"))]
+pub use generate::{GenerateOptions, GenerateSummary};
+#[cfg(not(target_arch = "wasm32"))]
+pub use storage::StorageBackend;
+#[cfg(not(target_arch = "wasm32"))]
+pub use payload::LazyPayload;
+#[cfg(not(target_arch = "wasm32"))]
+pub use checkpoint::{Checkpoint, CHECKPOINT_FILE};
+#[cfg(not(target_arch = "wasm32"))]
+pub use watch::{AuditLog, ConversionRecord, ConvertWatcher};
+#[cfg(not(target_arch = "wasm32"))]
+pub use audit::{Mutation, MutationLog, MutationRecord};
+#[cfg(not(target_arch = "wasm32"))]
+pub use provenance::Provenance;
+#[cfg(not(target_arch = "wasm32"))]
+pub use blobs::{BlobIssue, BlobReport, BlobStore};
+#[cfg(not(target_arch = "wasm32"))]
+pub use pack::{Pack, PackEntry, PackIndex, PackWriter, UnpackSummary};
+#[cfg(not(target_arch = "wasm32"))]
+pub use archive::{ArchiveFormat, ArchiveOptions, ArchiveSummary, ArchiveWriter};
+#[cfg(not(target_arch = "wasm32"))]
+pub use tfrecord::{Feature, TfExample, TfObject, TfRecordOptions, TfRecordSummary, TfRecordWriter};
+#[cfg(not(target_arch = "wasm32"))]
+pub use webdataset::{ShardWriter, WebDatasetOptions, WebDatasetSummary};
+#[cfg(not(target_arch = "wasm32"))]
+pub use redact::{RedactAction, RedactKey, RedactRule, RedactionRecord};
+#[cfg(not(target_arch = "wasm32"))]
+pub use signing::{SidecarSigner, SignatureIssue, SignatureProblem, SignatureRecord, SignatureReport, SignatureVerifier};
+#[cfg(not(target_arch = "wasm32"))]
+pub use snapshot::{RestoreSummary, SnapshotInfo, SnapshotStore, SnapshotSummary};
+#[cfg(not(target_arch = "wasm32"))]
+pub use git::{FileChange, GitFileStatus, GitStatus};
+#[cfg(not(target_arch = "wasm32"))]
+pub use dvc::{DvcDirectory, DvcEntry, DvcFile, DvcOutput, DvcVerification};
+#[cfg(not(target_arch = "wasm32"))]
+pub use badge::{BadgeRefresh, BadgeRollup, BadgeSource, StatsBadge, STATS_BADGE_FILE};
+#[cfg(not(target_arch = "wasm32"))]
+pub use coverage::{CoverageReport, OperationCoverage};
+#[cfg(not(target_arch = "wasm32"))]
+pub use pipeline::{ScanPipeline, ScanReport};
+#[cfg(not(target_arch = "wasm32"))]
+pub use show::{Compression, ShownSidecar};
+#[cfg(not(target_arch = "wasm32"))]
+pub use jobs::{JobFormat, JobSpec};
+#[cfg(not(target_arch = "wasm32"))]
+pub use fingerprint::{ImageFingerprint, StaleReason, StaleSidecar, IMAGE_FINGERPRINT_KEY};
+#[cfg(not(target_arch = "wasm32"))]
+pub use legacy::{
+    DetectorKeyedLayout, LegacyImporter, LegacyLayout, LegacySidecar, LegacySource, MigrationRecord, MigrationReport, SuffixLayout,
+    MIGRATED_FROM_KEY,
+};
+#[cfg(not(target_arch = "wasm32"))]
+pub use reassociate::{Reassociation, ReassociationOutcome};
+#[cfg(not(target_arch = "wasm32"))]
+pub use relocate::{MovedFile, RelocationRecord};
+#[cfg(not(target_arch = "wasm32"))]
+pub use transaction::{RecoverySummary, SidecarTransaction, TransactionStep};
+#[cfg(not(target_arch = "wasm32"))]
+pub use unify::{SplitFile, SplitRecord, UnifiedOperation, UnifyRecord, SPLIT_FROM_KEY, UNIFIED_SOURCES_KEY};
+pub use repair::{RepairAction, RepairRecord};
+pub use retention::{GcAction, GcRecord, RetentionPolicy, RetentionRule};
+pub use diff::{DirectoryDiff, ImageDiff, OperationChange};
+pub use manifest::{Manifest, ManifestEntry, ManifestVerification, ModifiedEntry};
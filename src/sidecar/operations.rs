@@ -10,11 +10,11 @@
  * - IDE: Cursor (cursor.sh)
  * - Generation method: AI-assisted pair programming
  * - Code style: Rust idiomatic with comprehensive error handling
- * - Dependencies: tokio, serde, anyhow
+ * - Dependencies: tokio, serde
  */
 
 use crate::sidecar::types::{OperationType, SidecarError, ValidationResult};
-use anyhow::Result;
+use crate::sidecar::types::Result;
 use std::path::Path;
 use tokio::fs;
 use serde_json::Value;
@@ -26,7 +26,7 @@ impl SidecarOperations {
     /// Load data from a sidecar file
     pub async fn load_data(sidecar_path: &Path) -> Result<Value> {
         if !sidecar_path.exists() {
-            return Err(SidecarError::SidecarNotFound(sidecar_path.to_path_buf()).into());
+            return Err(SidecarError::SidecarNotFound(sidecar_path.to_path_buf()));
         }
 
         let content = fs::read_to_string(sidecar_path).await?;
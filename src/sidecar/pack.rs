@@ -0,0 +1,228 @@
+/*
+ * Context: Single-file archive of a sidecar tree with random access by image path
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde_json, sha2
+ */
+
+use crate::sidecar::formats::{FormatManager, SidecarFormat};
+use crate::sidecar::manifest::{checksum, relative_path};
+use crate::sidecar::types::{Result, SidecarError};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+/// First and last eight bytes of every pack
+pub const PACK_MAGIC: &[u8; 8] = b"SCPACK01";
+
+/// Pack layout version, bumped on incompatible changes
+pub const PACK_VERSION: u32 = 1;
+
+/// Where one sidecar's bytes sit in the pack
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackEntry {
+    pub offset: u64,
+    pub size: u64,
+    /// Lowercase hex SHA-256 of the sidecar bytes
+    pub checksum: String,
+}
+
+/// Table of contents stored at the end of a pack, keyed by `/`-separated sidecar path
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackIndex {
+    pub version: u32,
+    pub entries: BTreeMap<String, PackEntry>,
+}
+
+/// What `unpack` wrote
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnpackSummary {
+    pub written: Vec<PathBuf>,
+    /// Sidecars left alone because a file already exists at their path
+    pub skipped: Vec<PathBuf>,
+}
+
+/// Writes sidecar files one after another, then the index
+///
+/// Layout: `PACK_MAGIC`, the sidecar bytes unchanged, the JSON index, the index length as a
+/// little-endian `u64`, and `PACK_MAGIC` again, so a reader finds the index from the end.
+pub struct PackWriter {
+    writer: BufWriter<File>,
+    root: PathBuf,
+    offset: u64,
+    entries: BTreeMap<String, PackEntry>,
+}
+
+impl PackWriter {
+    /// Start a pack at `path` for sidecars under `root`
+    pub fn create(path: &Path, root: &Path) -> Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(PACK_MAGIC)?;
+        Ok(Self { writer, root: root.to_path_buf(), offset: PACK_MAGIC.len() as u64, entries: BTreeMap::new() })
+    }
+
+    /// Append the sidecar at `sidecar_path`, recorded relative to the root
+    pub fn add(&mut self, sidecar_path: &Path) -> Result<()> {
+        let bytes = std::fs::read(sidecar_path)?;
+        self.writer.write_all(&bytes)?;
+        let entry = PackEntry { offset: self.offset, size: bytes.len() as u64, checksum: checksum(&bytes) };
+        self.offset += entry.size;
+        self.entries.insert(relative_path(&self.root, sidecar_path), entry);
+        Ok(())
+    }
+
+    /// Write the index and sync the pack to disk, returning the number of sidecars
+    pub fn finish(mut self) -> Result<usize> {
+        let index = PackIndex { version: PACK_VERSION, entries: self.entries };
+        let bytes = serde_json::to_vec(&index)?;
+        self.writer.write_all(&bytes)?;
+        self.writer.write_all(&(bytes.len() as u64).to_le_bytes())?;
+        self.writer.write_all(PACK_MAGIC)?;
+        self.writer.flush()?;
+        self.writer.get_ref().sync_all()?;
+        Ok(index.entries.len())
+    }
+}
+
+/// A pack opened for reading; sidecars are read straight from it without unpacking
+pub struct Pack {
+    path: PathBuf,
+    file: Mutex<File>,
+    index: PackIndex,
+    /// Sidecar path without its extension, i.e. the image path without its extension, to index key
+    by_stem: BTreeMap<String, String>,
+    format_manager: FormatManager,
+}
+
+impl Pack {
+    pub fn open(path: &Path) -> Result<Self> {
+        let invalid = |reason: &str| SidecarError::Schema(format!("{} is not a sidecar pack: {}", path.display(), reason));
+        let mut file = File::open(path)?;
+        let length = file.metadata()?.len();
+        let trailer_size = (PACK_MAGIC.len() + 8) as u64;
+        if length < PACK_MAGIC.len() as u64 + trailer_size {
+            return Err(invalid("file too short"));
+        }
+
+        let mut magic = [0u8; 8];
+        file.read_exact(&mut magic)?;
+        let mut trailer = [0u8; 16];
+        file.seek(SeekFrom::End(-(trailer_size as i64)))?;
+        file.read_exact(&mut trailer)?;
+        if &magic != PACK_MAGIC || &trailer[8..] != PACK_MAGIC {
+            return Err(invalid("missing pack header"));
+        }
+
+        let index_size = u64::from_le_bytes(trailer[..8].try_into().unwrap_or_default());
+        let index_offset = (length - trailer_size).checked_sub(index_size).filter(|offset| *offset >= PACK_MAGIC.len() as u64)
+            .ok_or_else(|| invalid("index length out of range"))?;
+        let mut index_bytes = vec![0u8; index_size as usize];
+        file.seek(SeekFrom::Start(index_offset))?;
+        file.read_exact(&mut index_bytes)?;
+        let index: PackIndex = serde_json::from_slice(&index_bytes).map_err(|e| invalid(&format!("unreadable index ({})", e)))?;
+        if index.version != PACK_VERSION {
+            return Err(invalid(&format!("unsupported version {}", index.version)));
+        }
+
+        // An image with sidecars in several formats reads like `read_data` does: bin, then rkyv, then json
+        let rank = |key: &str| match SidecarFormat::from_path(Path::new(key)) {
+            Some(SidecarFormat::Binary) => 0,
+            Some(SidecarFormat::Rkyv) => 1,
+            _ => 2,
+        };
+        let mut by_stem: BTreeMap<String, String> = BTreeMap::new();
+        for key in index.entries.keys() {
+            let stem = key.rsplit_once('.').map_or(key.as_str(), |(stem, _)| stem).to_string();
+            match by_stem.get(&stem) {
+                Some(current) if rank(current) <= rank(key) => {}
+                _ => {
+                    by_stem.insert(stem, key.clone());
+                }
+            }
+        }
+        Ok(Self { path: path.to_path_buf(), file: Mutex::new(file), index, by_stem, format_manager: FormatManager::new() })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn index(&self) -> &PackIndex {
+        &self.index
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.entries.is_empty()
+    }
+
+    /// Raw bytes of the sidecar stored at `key`, checked against its checksum
+    pub fn read_bytes(&self, key: &str) -> Result<Vec<u8>> {
+        let entry = self.index.entries.get(key).ok_or_else(|| SidecarError::SidecarNotFound(PathBuf::from(key)))?;
+        let mut bytes = vec![0u8; entry.size as usize];
+        {
+            let mut file = self.file.lock()?;
+            file.seek(SeekFrom::Start(entry.offset))?;
+            file.read_exact(&mut bytes)?;
+        }
+        if checksum(&bytes) != entry.checksum {
+            return Err(SidecarError::Storage(format!("{} in {} is corrupt", key, self.path.display())));
+        }
+        Ok(bytes)
+    }
+
+    /// Index key of the sidecar for `image_path`, given relative to the packed root
+    pub fn key_for_image(&self, image_path: &Path) -> Option<&str> {
+        let stem = image_path
+            .with_extension("")
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+        self.by_stem.get(&stem).map(String::as_str)
+    }
+
+    /// Decoded sidecar of `image_path` (relative to the packed root), or `None` if it has none
+    pub fn read_data(&self, image_path: &Path) -> Result<Option<Value>> {
+        let Some(key) = self.key_for_image(image_path) else {
+            return Ok(None);
+        };
+        let bytes = self.read_bytes(key)?;
+        let format = SidecarFormat::from_path(Path::new(key)).unwrap_or(SidecarFormat::Json);
+        Ok(Some(self.format_manager.get_serializer(format).deserialize(&bytes)?))
+    }
+
+    /// Write every sidecar below `destination`, leaving existing files untouched
+    pub fn unpack(&self, destination: &Path) -> Result<UnpackSummary> {
+        let mut summary = UnpackSummary::default();
+        for key in self.index.entries.keys() {
+            let relative = Path::new(key);
+            // A crafted index must not write outside the destination
+            if !relative.components().all(|component| matches!(component, Component::Normal(_))) {
+                return Err(SidecarError::Schema(format!("Refusing to unpack unsafe path {:?}", key)));
+            }
+            let target = destination.join(relative);
+            if target.exists() {
+                summary.skipped.push(target);
+                continue;
+            }
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&target, self.read_bytes(key)?)?;
+            summary.written.push(target);
+        }
+        Ok(summary)
+    }
+}
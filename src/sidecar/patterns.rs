@@ -0,0 +1,115 @@
+/*
+ * Context: Configurable filename patterns tying sidecars to images and operations
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: glob
+ *
+ * A pattern is a file name template with one `{stem}` placeholder for the image's stem,
+ * e.g. `{stem}_faces.json` or `{stem}.yolo.json`. The text around the placeholder may use
+ * glob wildcards (`*`, `?`, `[...]`). Patterns only decide which image a sidecar belongs to
+ * and which operation it holds; files still need a sidecar extension to be scanned.
+ */
+
+use crate::sidecar::scan::candidate_image_stems;
+use crate::sidecar::types::{OperationType, Result, SidecarError};
+use std::ffi::OsStr;
+use std::path::Path;
+
+/// Placeholder standing for the image's stem in a pattern
+pub const STEM_PLACEHOLDER: &str = "{stem}";
+
+/// One configured sidecar name pattern and the operation sidecars matching it hold
+#[derive(Debug, Clone, PartialEq)]
+pub struct SidecarPattern {
+    template: String,
+    before: glob::Pattern,
+    after: glob::Pattern,
+    pub operation: OperationType,
+}
+
+impl SidecarPattern {
+    /// Parse a template such as `{stem}_faces.json`; it must hold `{stem}` exactly once
+    pub fn parse(template: &str, operation: OperationType) -> Result<Self> {
+        let invalid = |reason: String| SidecarError::ProcessingError(format!("Invalid sidecar pattern '{}': {}", template, reason));
+        let (before, after) = template
+            .split_once(STEM_PLACEHOLDER)
+            .filter(|(_, after)| !after.contains(STEM_PLACEHOLDER))
+            .ok_or_else(|| invalid(format!("needs {} exactly once", STEM_PLACEHOLDER)))?;
+        if template.contains(['/', '\\']) {
+            return Err(invalid("patterns match file names, not paths".to_string()));
+        }
+        if operation == OperationType::Unknown {
+            return Err(invalid("needs a known operation".to_string()));
+        }
+        let glob = |text: &str| glob::Pattern::new(text).map_err(|e| invalid(e.to_string()));
+        Ok(Self { template: template.to_string(), before: glob(before)?, after: glob(after)?, operation })
+    }
+
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    /// Every image stem this pattern reads out of `file_name`, longest first
+    pub fn stems<'a>(&self, file_name: &'a str) -> Vec<&'a str> {
+        let boundaries: Vec<usize> = file_name.char_indices().map(|(index, _)| index).chain([file_name.len()]).collect();
+        let mut stems = Vec::new();
+        for &start in boundaries.iter().filter(|&&start| self.before.matches(&file_name[..start])) {
+            for &end in boundaries.iter().filter(|&&end| end > start && self.after.matches(&file_name[end..])) {
+                stems.push(&file_name[start..end]);
+            }
+        }
+        stems.sort_by_key(|stem| std::cmp::Reverse(stem.len()));
+        stems.dedup();
+        stems
+    }
+}
+
+/// The configured sidecar name patterns, tried in order before the built-in `prefix_IMG` rule
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SidecarPatterns {
+    patterns: Vec<SidecarPattern>,
+}
+
+impl SidecarPatterns {
+    /// Try `template` after the patterns added so far
+    pub fn with(mut self, template: &str, operation: OperationType) -> Result<Self> {
+        self.patterns.push(SidecarPattern::parse(template, operation)?);
+        Ok(self)
+    }
+
+    pub fn push(&mut self, pattern: SidecarPattern) {
+        self.patterns.push(pattern);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &SidecarPattern> {
+        self.patterns.iter()
+    }
+
+    /// Every image stem a sidecar name can refer to, in the order they are tried
+    ///
+    /// The stems the configured patterns read out of the name come first, pattern by pattern,
+    /// followed by `candidate_image_stems`: the whole stem, then what follows each `_`.
+    pub fn image_stems<'a>(&self, sidecar_path: &'a Path) -> Vec<&'a OsStr> {
+        let mut stems: Vec<&OsStr> = match sidecar_path.file_name().and_then(OsStr::to_str) {
+            Some(name) => self.patterns.iter().flat_map(|pattern| pattern.stems(name)).map(OsStr::new).collect(),
+            None => Vec::new(),
+        };
+        stems.extend(candidate_image_stems(sidecar_path));
+        stems
+    }
+
+    /// The operation of the first pattern naming `sidecar_path` after `image_path`'s stem
+    pub fn operation_for(&self, sidecar_path: &Path, image_path: &Path) -> Option<OperationType> {
+        let name = sidecar_path.file_name()?.to_str()?;
+        let stem = image_path.file_stem()?.to_str()?;
+        self.patterns
+            .iter()
+            .find(|pattern| pattern.stems(name).contains(&stem))
+            .map(|pattern| pattern.operation.clone())
+    }
+}
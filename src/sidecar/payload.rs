@@ -0,0 +1,104 @@
+/*
+ * Context: Sidecar contents decoded on demand
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde_json
+ *
+ * `list_sidecars` only stats the sidecars it matches, so listing a million of them reads no
+ * contents, and `find_all_sidecars` skims them but keeps nothing. Each `SidecarInfo` carries
+ * a `LazyPayload` remembering the storage its sidecar was found in, the formats, size limit
+ * and blob store of the manager that found it; `SidecarInfo::payload` decodes the sidecar in
+ * full on first use, pointers filled in, and caches the result.
+ */
+
+use crate::sidecar::blobs::{self, BlobStore};
+use crate::sidecar::formats::FormatManager;
+use crate::sidecar::manager::SidecarManager;
+use crate::sidecar::storage::{self, StorageBackend};
+use crate::sidecar::types::{Result, DEFAULT_MAX_SIDECAR_SIZE};
+use serde_json::Value;
+use std::fmt;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+/// Where a manager reads sidecar contents from and how it decodes them
+///
+/// Sidecars are read from `storage` (the filesystem when `None`) up to `max_size` bytes and
+/// decoded with `formats`; payloads kept in `blobs` are filled in from their pointers.
+#[derive(Clone)]
+pub(crate) struct PayloadSource {
+    pub storage: Option<Arc<dyn StorageBackend>>,
+    pub formats: Arc<FormatManager>,
+    pub blobs: Option<Arc<BlobStore>>,
+    pub max_size: u64,
+}
+
+impl Default for PayloadSource {
+    fn default() -> Self {
+        Self { storage: None, formats: Arc::new(FormatManager::new()), blobs: None, max_size: DEFAULT_MAX_SIDECAR_SIZE }
+    }
+}
+
+impl PayloadSource {
+    /// The stored bytes of the sidecar at `path`, refused if over the size limit
+    pub fn read(&self, path: &Path) -> Result<Vec<u8>> {
+        storage::read_sidecar(self.storage.as_deref(), path, self.max_size)
+    }
+
+    /// Decode the bytes of the sidecar at `path`, filling in payloads kept in the blob store
+    pub fn decode(&self, path: &Path, bytes: &[u8]) -> Result<Value> {
+        let mut data = SidecarManager::decode_sidecar(&self.formats, path, bytes)?;
+        if let Some(store) = &self.blobs {
+            blobs::resolve_pointers(store, path, &mut data)?;
+        }
+        Ok(data)
+    }
+
+    /// Read and decode the sidecar at `path`
+    pub fn load(&self, path: &Path) -> Result<Value> {
+        self.decode(path, &self.read(path)?)
+    }
+}
+
+/// Decoded contents of one sidecar, read on first use and then cached
+///
+/// Clones share the cache. Without a source the filesystem is read with the default formats,
+/// refusing sidecars over `DEFAULT_MAX_SIDECAR_SIZE`.
+#[derive(Clone, Default)]
+pub struct LazyPayload {
+    cell: Arc<OnceLock<Value>>,
+    source: Option<PayloadSource>,
+}
+
+impl LazyPayload {
+    /// Contents to be read and decoded from `source`
+    pub(crate) fn new(source: PayloadSource) -> Self {
+        Self { cell: Arc::default(), source: Some(source) }
+    }
+
+    /// Whether the contents have been decoded
+    pub fn is_loaded(&self) -> bool {
+        self.cell.get().is_some()
+    }
+
+    /// The contents of the sidecar at `path`, decoding them unless already cached
+    ///
+    /// Failures are not cached, so a later call reads the sidecar again.
+    pub(crate) fn get(&self, path: &Path) -> Result<&Value> {
+        if let Some(data) = self.cell.get() {
+            return Ok(data);
+        }
+        let data = match &self.source {
+            Some(source) => source.load(path)?,
+            None => PayloadSource::default().load(path)?,
+        };
+        Ok(self.cell.get_or_init(|| data))
+    }
+}
+
+impl fmt::Debug for LazyPayload {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.debug_struct("LazyPayload").field("loaded", &self.is_loaded()).finish()
+    }
+}
@@ -0,0 +1,120 @@
+/*
+ * Context: One directory walk feeding validation, statistics, coverage and format counts
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde
+ *
+ * `validate`, `stats`, `coverage` and `format-stats` each walk the tree and read every
+ * sidecar again. A `ScanPipeline` names the results wanted from a directory, and
+ * `SidecarManager::run_pipeline` walks it once and reads each sidecar at most once, handing
+ * the decoded sidecar to every consumer that needs it. Sidecars that are only summarised
+ * or checked for coverage are skimmed; those being validated are decoded in full.
+ */
+
+use crate::parallel::ValidationSummary;
+use crate::sidecar::coverage::CoverageReport;
+use crate::sidecar::formats::SidecarFormat;
+use crate::sidecar::types::{StatisticsResult, ValidationResult};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// The results one pass over a directory produces
+///
+/// Validation and format counts cover the sidecar files the manager's `FileSelection`
+/// admits, as `validate` and `format-stats` do; statistics and coverage cover the sidecars
+/// matched to images, as `stats` and `coverage` do.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScanPipeline {
+    validation: bool,
+    statistics: bool,
+    coverage: Option<Vec<String>>,
+    formats: bool,
+}
+
+impl ScanPipeline {
+    /// A pipeline producing nothing; add consumers with the builder methods
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every consumer, with coverage of every operation recorded in the directory
+    pub fn all() -> Self {
+        Self::new().validation().statistics().coverage(Vec::new()).formats()
+    }
+
+    /// Validate every selected sidecar file
+    pub fn validation(mut self) -> Self {
+        self.validation = true;
+        self
+    }
+
+    /// Summarise the sidecars matched to images
+    pub fn statistics(mut self) -> Self {
+        self.statistics = true;
+        self
+    }
+
+    /// Report coverage of `operations`, or of every operation recorded when empty
+    pub fn coverage(mut self, operations: Vec<String>) -> Self {
+        self.coverage = Some(operations);
+        self
+    }
+
+    /// Count the selected sidecar files per format
+    pub fn formats(mut self) -> Self {
+        self.formats = true;
+        self
+    }
+
+    pub fn validates(&self) -> bool {
+        self.validation
+    }
+
+    pub fn summarises(&self) -> bool {
+        self.statistics
+    }
+
+    /// The operations coverage is requested for, if it is
+    pub fn coverage_operations(&self) -> Option<&[String]> {
+        self.coverage.as_deref()
+    }
+
+    pub fn counts_formats(&self) -> bool {
+        self.formats
+    }
+
+    /// Whether any consumer needs sidecar contents, so matched sidecars must be read
+    pub(crate) fn reads_matches(&self) -> bool {
+        self.statistics || self.coverage.is_some()
+    }
+}
+
+/// What one `ScanPipeline` pass found; consumers that were not requested are `None`
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanReport {
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub directory: PathBuf,
+    /// Sidecar files read during the pass, each once
+    pub sidecars_read: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validation: Option<Vec<ValidationResult>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub statistics: Option<StatisticsResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<CoverageReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub formats: Option<HashMap<SidecarFormat, u32>>,
+}
+
+impl ScanReport {
+    /// Totals of the validation results, if sidecars were validated
+    pub fn validation_summary(&self) -> Option<ValidationSummary> {
+        self.validation.as_ref().map(|results| {
+            let mut summary = ValidationSummary::default();
+            results.iter().for_each(|result| summary.record(result));
+            summary
+        })
+    }
+}
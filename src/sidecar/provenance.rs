@@ -0,0 +1,63 @@
+/*
+ * Context: Producer identity stamped into every sidecar write
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde
+ */
+
+use crate::sidecar::audit::hostname;
+use serde::{Deserialize, Serialize};
+
+/// Who wrote a payload, recorded under `sidecar_info.provenance.<operation>`
+///
+/// Host and tool version are filled in by `current`; the detector commit, model version and,
+/// if wanted, the process ID come from the caller, which sets them once on the manager.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provenance {
+    pub host: String,
+    /// Left out unless set, so rerunning a detector does not change its sidecars
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    pub tool: String,
+    pub tool_version: String,
+    /// Git commit of the detector that produced the payload
+    pub detector_commit: Option<String>,
+    /// Version of the model the detector ran
+    pub model_version: Option<String>,
+}
+
+impl Provenance {
+    /// Identity of this process, without detector details
+    pub fn current() -> Self {
+        Self {
+            host: hostname(),
+            pid: None,
+            tool: env!("CARGO_PKG_NAME").to_string(),
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            detector_commit: None,
+            model_version: None,
+        }
+    }
+
+    pub fn detector_commit(mut self, commit: impl Into<String>) -> Self {
+        self.detector_commit = Some(commit.into());
+        self
+    }
+
+    pub fn model_version(mut self, version: impl Into<String>) -> Self {
+        self.model_version = Some(version.into());
+        self
+    }
+
+    pub fn pid(mut self, pid: u32) -> Self {
+        self.pid = Some(pid);
+        self
+    }
+}
+
+impl Default for Provenance {
+    fn default() -> Self {
+        Self::current()
+    }
+}
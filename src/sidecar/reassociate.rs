@@ -0,0 +1,89 @@
+/*
+ * Context: Re-associating orphaned sidecars with images that were renamed or moved
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde
+ */
+
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// What happened to one orphaned sidecar during a reassociation run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReassociationOutcome {
+    /// Renamed to follow its image, or would be in a dry run
+    Moved,
+    /// Several images without a sidecar have the recorded contents, or another orphan claims the same new name
+    Ambiguous,
+    /// A sidecar already exists at the new name
+    Conflict,
+    /// No image without a sidecar has the recorded contents
+    Unmatched,
+    /// The sidecar records no image fingerprint to match on
+    Unrecorded,
+}
+
+impl fmt::Display for ReassociationOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReassociationOutcome::Moved => write!(f, "moved"),
+            ReassociationOutcome::Ambiguous => write!(f, "ambiguous"),
+            ReassociationOutcome::Conflict => write!(f, "target exists"),
+            ReassociationOutcome::Unmatched => write!(f, "no matching image"),
+            ReassociationOutcome::Unrecorded => write!(f, "no fingerprint recorded"),
+        }
+    }
+}
+
+/// One orphaned sidecar and the image it was matched to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reassociation {
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub sidecar_path: PathBuf,
+    pub outcome: ReassociationOutcome,
+    /// The image the sidecar now belongs to, when exactly one matched
+    #[serde(with = "crate::utils::paths::lossless_opt")]
+    pub image_path: Option<PathBuf>,
+    /// Where the sidecar was or would be moved
+    #[serde(with = "crate::utils::paths::lossless_opt")]
+    pub new_path: Option<PathBuf>,
+    /// Every image with the recorded contents, for an ambiguous match
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "crate::utils::paths::lossless_vec")]
+    pub candidates: Vec<PathBuf>,
+}
+
+impl Reassociation {
+    pub fn new(sidecar_path: PathBuf, outcome: ReassociationOutcome) -> Self {
+        Self { sidecar_path, outcome, image_path: None, new_path: None, candidates: Vec::new() }
+    }
+}
+
+/// Name `sidecar_path` takes when it follows its image to `image`
+///
+/// A pattern-named sidecar keeps its prefix: `yolo_IMG.json` for `IMG.jpg` becomes
+/// `yolo_NEW.json` beside `NEW.jpg`. The prefix is only recognized when the sidecar
+/// recorded the image it was written for, since plain stems may contain `_` too.
+pub fn reassociated_path(sidecar_path: &Path, recorded_image: Option<&Path>, image: &Path) -> PathBuf {
+    let image_stem = image.file_stem().unwrap_or_default();
+    let mut name = OsString::new();
+    let prefix = recorded_image
+        .and_then(Path::file_stem)
+        .zip(sidecar_path.file_stem())
+        .and_then(|(old_stem, sidecar_stem)| {
+            let (old_stem, sidecar_stem) = (old_stem.to_str()?, sidecar_stem.to_str()?);
+            sidecar_stem.strip_suffix(old_stem).filter(|prefix| prefix.ends_with('_'))
+        });
+    if let Some(prefix) = prefix {
+        name.push(prefix);
+    }
+    name.push(image_stem);
+    if let Some(extension) = sidecar_path.extension() {
+        name.push(".");
+        name.push(extension);
+    }
+    image.with_file_name(name)
+}
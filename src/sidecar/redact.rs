@@ -0,0 +1,208 @@
+/*
+ * Context: Stripping or hashing personal data such as face encodings from sidecars
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde_json, serde_json_path, sha2, getrandom, hex
+ */
+
+use crate::query::QueryError;
+use crate::sidecar::formats::to_canonical_json;
+use crate::sidecar::types::{Result, SidecarError};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_json_path::JsonPath;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// Prefix of the string that replaces a hashed field
+pub const REDACTED_HASH_PREFIX: &str = "hmac-sha256:";
+
+/// Secret key for `RedactAction::Hash`
+///
+/// Hashing with a key means nobody without it can confirm a guess at a redacted value, such as
+/// a known face encoding, by hashing it themselves. Values hashed with the same key still match.
+#[derive(Clone)]
+pub struct RedactKey([u8; 32]);
+
+impl std::fmt::Debug for RedactKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("RedactKey(..)")
+    }
+}
+
+impl RedactKey {
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// A new key from the operating system's random number generator
+    pub fn generate() -> Result<Self> {
+        let mut bytes = [0u8; 32];
+        getrandom::getrandom(&mut bytes).map_err(|e| SidecarError::ProcessingError(format!("No randomness for a new key: {}", e)))?;
+        Ok(Self(bytes))
+    }
+
+    /// Load a key written by `save`
+    pub fn load(path: &Path) -> Result<Self> {
+        let invalid = |reason: String| SidecarError::Schema(format!("{} is not a redaction key: {}", path.display(), reason));
+        let text = std::fs::read_to_string(path)?;
+        let bytes = hex::decode(text.trim()).map_err(|e| invalid(e.to_string()))?;
+        let bytes: [u8; 32] = bytes.try_into().map_err(|bytes: Vec<u8>| invalid(format!("expected 32 bytes, found {}", bytes.len())))?;
+        Ok(Self(bytes))
+    }
+
+    /// Write the key to a new file at `path`, readable by its owner only on Unix
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        std::io::Write::write_all(&mut options.open(path)?, format!("{}\n", hex::encode(self.0)).as_bytes())?;
+        Ok(())
+    }
+
+    /// Load the key at `path`, first generating and saving one there if the file does not exist
+    pub fn load_or_create(path: &Path) -> Result<Self> {
+        if path.exists() {
+            return Self::load(path);
+        }
+        let key = Self::generate()?;
+        key.save(path)?;
+        Ok(key)
+    }
+
+    /// HMAC-SHA256 (RFC 2104) of `message`
+    fn mac(&self, message: &[u8]) -> [u8; 32] {
+        let mut inner_pad = [0x36u8; 64];
+        let mut outer_pad = [0x5cu8; 64];
+        for (index, byte) in self.0.iter().enumerate() {
+            inner_pad[index] ^= byte;
+            outer_pad[index] ^= byte;
+        }
+        let inner = Sha256::new().chain_update(inner_pad).chain_update(message).finalize();
+        Sha256::new().chain_update(outer_pad).chain_update(inner).finalize().into()
+    }
+}
+
+/// What happens to a field matched by a `RedactRule`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactAction {
+    /// The field, or array element, is deleted
+    Remove,
+    /// The value is replaced by `hmac-sha256:<hex>` of its canonical JSON under a `RedactKey`,
+    /// so equal values still compare equal after redaction
+    Hash,
+}
+
+/// A field to redact, e.g. `face_detection.faces[*].encoding`
+///
+/// Paths are JSONPath; the leading `$.` may be left out.
+#[derive(Debug, Clone)]
+pub struct RedactRule {
+    field: String,
+    path: JsonPath,
+    action: RedactAction,
+}
+
+impl RedactRule {
+    pub fn parse(field: &str, action: RedactAction) -> std::result::Result<Self, QueryError> {
+        let expression = if field.starts_with('$') { field.to_string() } else { format!("$.{}", field) };
+        let path = JsonPath::parse(&expression).map_err(|e| QueryError::InvalidPath(e.to_string()))?;
+        Ok(Self { field: field.to_string(), path, action })
+    }
+
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    pub fn action(&self) -> RedactAction {
+        self.action
+    }
+
+    /// Apply the rule to `data`, hashing with `key`, and return the JSON pointer of every field it changed
+    pub fn apply(&self, data: &mut Value, key: &RedactKey) -> Vec<String> {
+        let pointers: Vec<String> = self
+            .path
+            .query_located(data)
+            .locations()
+            .map(|location| location.to_json_pointer())
+            .filter(|pointer| !pointer.is_empty())
+            .filter(|pointer| self.action == RedactAction::Remove || !is_hashed(data.pointer(pointer)))
+            .collect();
+        // Later array elements go first, so removing one never shifts the index of the next
+        for pointer in pointers.iter().rev() {
+            match self.action {
+                RedactAction::Remove => remove_pointer(data, pointer),
+                RedactAction::Hash => {
+                    if let Some(value) = data.pointer_mut(pointer) {
+                        *value = Value::String(hash_value(value, key));
+                    }
+                }
+            }
+        }
+        pointers
+    }
+}
+
+/// The replacement `RedactAction::Hash` writes for `value`
+pub fn hash_value(value: &Value, key: &RedactKey) -> String {
+    format!("{}{}", REDACTED_HASH_PREFIX, hex::encode(key.mac(to_canonical_json(value).as_bytes())))
+}
+
+/// Already hashed values are left alone, so redacting twice changes nothing
+fn is_hashed(value: Option<&Value>) -> bool {
+    matches!(value, Some(Value::String(text)) if text.starts_with(REDACTED_HASH_PREFIX))
+}
+
+fn remove_pointer(data: &mut Value, pointer: &str) {
+    let Some((parent, key)) = pointer.rsplit_once('/') else {
+        return;
+    };
+    let key = key.replace("~1", "/").replace("~0", "~");
+    match data.pointer_mut(parent) {
+        Some(Value::Object(object)) => {
+            object.remove(&key);
+        }
+        Some(Value::Array(array)) => {
+            if let Ok(index) = key.parse::<usize>() {
+                if index < array.len() {
+                    array.remove(index);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// What redaction changed in one sidecar
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RedactionRecord {
+    pub sidecar_path: PathBuf,
+    /// Where the redacted copy was written, when not redacting in place
+    pub output_path: Option<PathBuf>,
+    /// JSON pointers of removed fields
+    pub removed: Vec<String>,
+    /// JSON pointers of fields replaced by their hash
+    pub hashed: Vec<String>,
+    /// Set when the sidecar could not be read or written
+    pub error: Option<String>,
+}
+
+impl RedactionRecord {
+    pub fn is_changed(&self) -> bool {
+        !self.removed.is_empty() || !self.hashed.is_empty()
+    }
+}
+
+/// Apply every rule to `data` in order, hashing with `key`, recording what changed
+pub fn redact(data: &mut Value, rules: &[RedactRule], key: &RedactKey, record: &mut RedactionRecord) {
+    for rule in rules {
+        let changed = rule.apply(data, key);
+        match rule.action {
+            RedactAction::Remove => record.removed.extend(changed),
+            RedactAction::Hash => record.hashed.extend(changed),
+        }
+    }
+}
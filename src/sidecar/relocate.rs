@@ -0,0 +1,206 @@
+/*
+ * Context: Moving and renaming images together with their sidecars
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde, walkdir
+ */
+
+use crate::sidecar::badge::is_badge_file;
+use crate::sidecar::formats::SidecarFormat;
+use crate::sidecar::patterns::SidecarPatterns;
+use crate::sidecar::scan::owning_image;
+use crate::sidecar::types::{Result, SidecarError};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// One file moved as part of a relocation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MovedFile {
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub from: PathBuf,
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub to: PathBuf,
+}
+
+/// What happened to one image, or directory of images, during a relocation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelocationRecord {
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub from: PathBuf,
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub to: PathBuf,
+    /// Sidecars that moved along, with their new paths
+    pub sidecars: Vec<MovedFile>,
+    /// Set when nothing was moved, or the moved sidecars' `image_path` could not be updated
+    pub error: Option<String>,
+}
+
+impl RelocationRecord {
+    pub fn new(from: PathBuf, to: PathBuf) -> Self {
+        Self { from, to, sidecars: Vec::new(), error: None }
+    }
+}
+
+/// Parse a rename mapping: one `from,to` pair of image paths per line
+///
+/// Blank lines, `#` comments and a `from,to` header are skipped. Fields may be wrapped in
+/// double quotes, which are needed for names containing a comma.
+pub fn parse_mapping(text: &str) -> Result<Vec<(PathBuf, PathBuf)>> {
+    let mut moves = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields = split_csv_line(line)
+            .filter(|fields| fields.len() == 2)
+            .ok_or_else(|| SidecarError::ProcessingError(format!("Line {} of the mapping is not a `from,to` pair: {}", number + 1, line)))?;
+        if moves.is_empty() && fields[0] == "from" && fields[1] == "to" {
+            continue;
+        }
+        moves.push((PathBuf::from(&fields[0]), PathBuf::from(&fields[1])));
+    }
+    Ok(moves)
+}
+
+fn split_csv_line(line: &str) -> Option<Vec<String>> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut()?.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => fields.last_mut()?.push(c),
+        }
+    }
+    (!quoted).then(|| fields.into_iter().map(|field| field.trim().to_string()).collect())
+}
+
+/// The sidecars stored beside `image`: `IMG.bin`, `IMG.rkyv`, `IMG.json`, pattern-named `prefix_IMG.<ext>`
+/// and those named by the configured `patterns`
+///
+/// A pattern-named sidecar is left out when it belongs to an image with a longer stem, since
+/// `IMG_001.json` then belongs to `IMG_001.jpg` rather than `001.jpg`. Blocking; reads the image's directory.
+pub fn sidecars_beside(image: &Path, image_extensions: &[String], patterns: &SidecarPatterns) -> Result<Vec<PathBuf>> {
+    let Some(stem) = image.file_stem() else {
+        return Ok(Vec::new());
+    };
+    let directory = image.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let mut sidecars = Vec::new();
+    for entry in std::fs::read_dir(directory)? {
+        let path = entry?.path();
+        let is_sidecar = path.extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| SidecarFormat::from_extension(&extension.to_lowercase()).is_some());
+        if !is_sidecar || is_badge_file(&path) || !path.is_file() {
+            continue;
+        }
+        // The image itself exists, so a sidecar named after a longer stem with an image is someone else's
+        let owner = owning_image(&path, directory, image_extensions, patterns, |candidate| {
+            candidate.file_stem() == Some(stem) || candidate.exists()
+        });
+        if path.file_stem() == Some(stem) || owner.is_some_and(|owner| owner.file_stem() == Some(stem)) {
+            sidecars.push(image.with_file_name(path.file_name().unwrap_or_default()));
+        }
+    }
+    sidecars.sort();
+    Ok(sidecars)
+}
+
+/// Where a sidecar of an image moving to `to` goes: its name with the image's stem swapped for the new one
+pub fn relocated_sidecar(sidecar: &Path, from: &Path, to: &Path) -> PathBuf {
+    let (old_stem, new_stem) = (from.file_stem().unwrap_or_default(), to.file_stem().unwrap_or_default());
+    let name = sidecar.file_name().unwrap_or_default();
+    let renamed = match (name.to_str(), old_stem.to_str(), new_stem.to_str()) {
+        // `prefix_OLD.ext` -> `prefix_NEW.ext`, `OLD.ext` -> `NEW.ext`
+        (Some(name), Some(old_stem), Some(new_stem)) => {
+            let extension = Path::new(name).extension().and_then(|extension| extension.to_str()).unwrap_or_default();
+            let stem = name.strip_suffix(&format!(".{}", extension)).unwrap_or(name);
+            match stem.strip_suffix(old_stem) {
+                Some(prefix) => format!("{}{}.{}", prefix, new_stem, extension).into(),
+                None => name.into(),
+            }
+        }
+        _ => {
+            let mut renamed = new_stem.to_os_string();
+            if let Some(extension) = sidecar.extension() {
+                renamed.push(".");
+                renamed.push(extension);
+            }
+            renamed
+        }
+    };
+    to.with_file_name(renamed)
+}
+
+/// Rename every `(from, to)` pair in order, undoing the renames already made if one fails
+///
+/// Each rename is atomic, so after an error every file is back where it started. Refuses to
+/// replace an existing file. A move to another filesystem is copied and the original removed
+/// once the copy is complete. Blocking.
+pub fn rename_all(moves: &[(PathBuf, PathBuf)]) -> std::io::Result<()> {
+    if let Some((_, taken)) = moves.iter().find(|(_, to)| to.symlink_metadata().is_ok()) {
+        return Err(std::io::Error::new(std::io::ErrorKind::AlreadyExists, format!("{} already exists", taken.display())));
+    }
+    for (done, (from, to)) in moves.iter().enumerate() {
+        let renamed = to.parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| move_path(from, to));
+        if let Err(error) = renamed {
+            for (from, to) in moves[..done].iter().rev() {
+                let _ = move_path(to, from);
+            }
+            return Err(error);
+        }
+    }
+    Ok(())
+}
+
+/// Rename `from` to `to`, falling back to copy and remove across filesystems
+fn move_path(from: &Path, to: &Path) -> std::io::Result<()> {
+    match std::fs::rename(from, to) {
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+            if let Err(e) = copy_path(from, to) {
+                let _ = remove_path(to);
+                return Err(e);
+            }
+            remove_path(from)
+        }
+        renamed => renamed,
+    }
+}
+
+/// Copy a file, symlink or whole directory tree, keeping symlinks as links
+fn copy_path(from: &Path, to: &Path) -> std::io::Result<()> {
+    for entry in walkdir::WalkDir::new(from) {
+        let entry = entry?;
+        let target = match entry.path().strip_prefix(from) {
+            Ok(rest) if !rest.as_os_str().is_empty() => to.join(rest),
+            _ => to.to_path_buf(),
+        };
+        let file_type = entry.file_type();
+        if file_type.is_symlink() {
+            crate::utils::paths::symlink(std::fs::read_link(entry.path())?, &target)?;
+        } else if file_type.is_dir() {
+            std::fs::create_dir_all(&target)?;
+        } else {
+            std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+fn remove_path(path: &Path) -> std::io::Result<()> {
+    if path.symlink_metadata()?.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    }
+}
@@ -0,0 +1,150 @@
+/*
+ * Context: Retention rules for expiring operation payloads and superseded sidecars
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde, chrono
+ */
+
+use crate::sidecar::types::{OperationType, SidecarError};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// One rule of a retention policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RetentionRule {
+    /// Remove an operation's payload once it is older than `max_age`; `None` matches every operation
+    MaxAge { operation: Option<OperationType>, max_age: Duration },
+    /// Where an image has sidecars in several formats, keep only the most recently modified one
+    NewestVersionOnly,
+}
+
+impl RetentionRule {
+    /// Whether this rule expires the payload stored under `key`, last written at `updated_at`
+    pub fn expires(&self, key: &str, updated_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        match self {
+            RetentionRule::MaxAge { operation, max_age } => {
+                operation.as_ref().is_none_or(|operation| operation.as_str() == key) && now - updated_at > *max_age
+            }
+            RetentionRule::NewestVersionOnly => false,
+        }
+    }
+}
+
+/// Parses `newest-version`, or `OPERATION=AGE` where AGE is a number of hours (`h`), days (`d`)
+/// or weeks (`w`) and OPERATION is an operation name or `*`, e.g. `quality_assessment=90d`
+impl FromStr for RetentionRule {
+    type Err = SidecarError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = |reason: &str| SidecarError::ProcessingError(format!("Invalid retention rule '{}': {}", s, reason));
+
+        if matches!(s, "newest-version" | "newest_version") {
+            return Ok(RetentionRule::NewestVersionOnly);
+        }
+        let (operation, age) = s.split_once('=').ok_or_else(|| invalid("expected OPERATION=AGE or newest-version"))?;
+        let operation = match operation.trim() {
+            "*" => None,
+            name => match OperationType::from_str(name) {
+                OperationType::Unknown => return Err(invalid("unknown operation")),
+                operation => Some(operation),
+            },
+        };
+
+        let age = age.trim();
+        let split = age.char_indices().last().map_or(0, |(index, _)| index);
+        let amount: i64 = age[..split].parse().map_err(|_| invalid("AGE must be a whole number followed by h, d or w"))?;
+        let max_age = match &age[split..] {
+            "h" => Duration::hours(amount),
+            "d" => Duration::days(amount),
+            "w" => Duration::weeks(amount),
+            _ => return Err(invalid("AGE must end in h, d or w")),
+        };
+        Ok(RetentionRule::MaxAge { operation, max_age })
+    }
+}
+
+/// Rules applied together by `gc`; a payload is removed if any rule expires it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    pub rules: Vec<RetentionRule>,
+}
+
+impl RetentionPolicy {
+    pub fn new(rules: Vec<RetentionRule>) -> Self {
+        Self { rules }
+    }
+
+    pub fn keeps_newest_version_only(&self) -> bool {
+        self.rules.contains(&RetentionRule::NewestVersionOnly)
+    }
+
+    /// Top-level payload keys of `data` that have expired, with the time each was last written
+    ///
+    /// Payload times come from `sidecar_info.operation_updated`, then `last_updated` and
+    /// `created_at`, then `fallback` (usually the file's modification time).
+    pub fn expired_operations(&self, data: &Value, fallback: DateTime<Utc>, now: DateTime<Utc>) -> Vec<(String, DateTime<Utc>)> {
+        let Some(object) = data.as_object() else {
+            return Vec::new();
+        };
+        let info = object.get("sidecar_info");
+        let timestamp = |value: Option<&Value>| {
+            value
+                .and_then(Value::as_str)
+                .and_then(|text| DateTime::parse_from_rfc3339(text).ok())
+                .map(|time| time.with_timezone(&Utc))
+        };
+        let sidecar_time = timestamp(info.and_then(|info| info.get("last_updated")))
+            .or_else(|| timestamp(info.and_then(|info| info.get("created_at"))))
+            .unwrap_or(fallback);
+
+        object
+            .keys()
+            .filter(|key| *key != "sidecar_info")
+            .filter_map(|key| {
+                let updated_at = timestamp(info.and_then(|info| info.get("operation_updated")).and_then(|times| times.get(key)))
+                    .unwrap_or(sidecar_time);
+                self.rules
+                    .iter()
+                    .any(|rule| rule.expires(key, updated_at, now))
+                    .then(|| (key.clone(), updated_at))
+            })
+            .collect()
+    }
+}
+
+/// One change made (or, in a dry run, planned) by garbage collection
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum GcAction {
+    /// An operation's payload expired and was removed from the sidecar
+    ExpiredOperation { operation: String, updated_at: DateTime<Utc> },
+    /// Nothing was left after expiry, so the file was removed
+    RemovedEmpty,
+    /// A newer sidecar for the same image exists, so this one was removed
+    Superseded { newer: PathBuf },
+}
+
+impl std::fmt::Display for GcAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GcAction::ExpiredOperation { operation, updated_at } => {
+                write!(f, "expired {} written {}", operation, updated_at.format("%Y-%m-%d"))
+            }
+            GcAction::RemovedEmpty => write!(f, "removed, no payload left"),
+            GcAction::Superseded { newer } => write!(f, "superseded by {}", newer.display()),
+        }
+    }
+}
+
+/// What garbage collection did to one sidecar
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GcRecord {
+    pub sidecar_path: PathBuf,
+    pub actions: Vec<GcAction>,
+    /// Set when the sidecar could not be read or rewritten
+    pub error: Option<String>,
+}
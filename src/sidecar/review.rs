@@ -0,0 +1,218 @@
+/*
+ * Context: Review queues of the detections models are least sure of, for human QA
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde, serde_json, image
+ *
+ * A queue is either the bottom N percent of detections by confidence or the detections
+ * whose confidence lies within a margin of a decision threshold. It can be written as a
+ * plain list or as Label Studio tasks with the detections as pre-annotations.
+ */
+
+use crate::geometry::{detection_class, detections_in, sidecar_payloads, BBox};
+use crate::query::thresholds::confidence;
+use crate::sidecar::manifest::relative_path;
+use crate::sidecar::types::{Result, SidecarError};
+use crate::utils::paths;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Margin around `--threshold` used when none is given
+pub const DEFAULT_MARGIN: f64 = 0.05;
+
+/// Which detections go into a review queue
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReviewSelection {
+    /// The lowest-confidence detections, this percentage (0-100] of all scored ones
+    BottomPercent(f64),
+    /// Detections within `margin` of `threshold`, closest first
+    NearThreshold { threshold: f64, margin: f64 },
+}
+
+impl ReviewSelection {
+    pub fn bottom_percent(percent: f64) -> Result<Self> {
+        if !(percent > 0.0 && percent <= 100.0) {
+            return Err(SidecarError::ProcessingError(format!("Review percentage must be in (0, 100], got {}", percent)));
+        }
+        Ok(Self::BottomPercent(percent))
+    }
+
+    pub fn near_threshold(threshold: f64, margin: f64) -> Result<Self> {
+        if threshold.is_nan() || margin.is_nan() || margin < 0.0 {
+            return Err(SidecarError::ProcessingError(format!(
+                "Review threshold needs a number and a non-negative margin, got {} and {}", threshold, margin
+            )));
+        }
+        Ok(Self::NearThreshold { threshold, margin })
+    }
+}
+
+/// What a review queue covers
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReviewOptions {
+    pub selection: ReviewSelection,
+    /// Only detections of these operations; empty takes every operation
+    pub operations: Vec<String>,
+    /// Keep at most this many detections, the most doubtful first
+    pub limit: Option<usize>,
+}
+
+impl ReviewOptions {
+    pub fn new(selection: ReviewSelection) -> Self {
+        Self { selection, operations: Vec::new(), limit: None }
+    }
+
+    pub fn operations<I, S>(mut self, operations: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.operations = operations.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
+/// One detection picked for review
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReviewItem {
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub image_path: PathBuf,
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub sidecar_path: PathBuf,
+    pub operation: String,
+    /// Position of the detection in its payload's detection list
+    pub index: usize,
+    /// From `class_name`, `class`, `label` or `name`, else the operation
+    pub label: String,
+    pub confidence: f64,
+    pub bbox: Option<Value>,
+}
+
+/// The detections picked for review, the most doubtful first
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReviewQueue {
+    /// Detections with a confidence that were considered
+    pub scored: usize,
+    pub items: Vec<ReviewItem>,
+}
+
+/// Every detection with a confidence in a sidecar's payloads, optionally only those of `operations`
+///
+/// `recorded_operation` is the operation the sidecar was classified as; a raw detector file
+/// of that operation is its own payload.
+pub fn scored_detections(
+    image_path: &Path,
+    sidecar_path: &Path,
+    data: &Value,
+    recorded_operation: &str,
+    operations: &[String],
+) -> Vec<ReviewItem> {
+    sidecar_payloads(data, recorded_operation)
+        .filter(|(operation, _)| operations.is_empty() || operations.iter().any(|wanted| wanted == operation))
+        .filter_map(|(operation, payload)| detections_in(payload).map(|detections| (operation, detections)))
+        .flat_map(|(operation, detections)| {
+            detections.iter().enumerate().filter_map(move |(index, detection)| {
+                let label = detection_class(detection).unwrap_or_else(|| operation.to_string());
+                Some(ReviewItem {
+                    image_path: image_path.to_path_buf(),
+                    sidecar_path: sidecar_path.to_path_buf(),
+                    operation: operation.to_string(),
+                    index,
+                    label,
+                    confidence: confidence(detection)?,
+                    bbox: detection.get("bbox").cloned(),
+                })
+            })
+        })
+        .collect()
+}
+
+impl ReviewQueue {
+    /// Pick the detections `options` asks for out of every scored one
+    pub fn select(mut scored: Vec<ReviewItem>, options: &ReviewOptions) -> Self {
+        let total = scored.len();
+        let by_path = |a: &ReviewItem, b: &ReviewItem| a.image_path.cmp(&b.image_path).then(a.index.cmp(&b.index));
+        let mut items = match options.selection {
+            ReviewSelection::BottomPercent(percent) => {
+                scored.sort_by(|a, b| a.confidence.total_cmp(&b.confidence).then_with(|| by_path(a, b)));
+                scored.truncate((total as f64 * percent / 100.0).ceil() as usize);
+                scored
+            }
+            ReviewSelection::NearThreshold { threshold, margin } => {
+                let distance = |item: &ReviewItem| (item.confidence - threshold).abs();
+                scored.retain(|item| distance(item) <= margin);
+                scored.sort_by(|a, b| distance(a).total_cmp(&distance(b)).then_with(|| by_path(a, b)));
+                scored
+            }
+        };
+        if let Some(limit) = options.limit {
+            items.truncate(limit);
+        }
+        Self { scored: total, items }
+    }
+
+    /// Label Studio tasks, one per image, with the picked detections as pre-annotations
+    ///
+    /// `data.image` is the image path, or `image_url_prefix` followed by its path relative to
+    /// `root`. Boxes become `rectanglelabels` regions in percent of the image, so each image's
+    /// dimensions are read; detections whose box cannot be placed are left out of the regions
+    /// but still counted in the task's `review` list. This is blocking.
+    pub fn label_studio_tasks(&self, root: &Path, image_url_prefix: Option<&str>) -> Vec<Value> {
+        let mut images: BTreeMap<&Path, Vec<&ReviewItem>> = BTreeMap::new();
+        for item in &self.items {
+            images.entry(&item.image_path).or_default().push(item);
+        }
+        images
+            .into_iter()
+            .map(|(image_path, items)| {
+                let image = match image_url_prefix {
+                    Some(prefix) => format!("{}{}", prefix, relative_path(root, image_path)),
+                    None => paths::encode_path(image_path).into_owned(),
+                };
+                let dimensions = image::image_dimensions(image_path).ok();
+                let regions: Vec<Value> = items
+                    .iter()
+                    .filter_map(|item| Some((item, region(item, dimensions?)?)))
+                    .map(|(item, region)| region_json(item, region, dimensions.unwrap_or_default()))
+                    .collect();
+                let lowest = items.iter().map(|item| item.confidence).fold(f64::INFINITY, f64::min);
+                let review: Vec<Value> = items
+                    .iter()
+                    .map(|item| json!({"operation": item.operation, "index": item.index, "label": item.label, "confidence": item.confidence}))
+                    .collect();
+                json!({
+                    "data": {"image": image, "sidecar": paths::path_to_json(&items[0].sidecar_path), "review": review},
+                    "predictions": [{"model_version": "sidecar-review", "score": lowest, "result": regions}],
+                })
+            })
+            .collect()
+    }
+}
+
+/// Percent `(x, y, width, height)` of a detection's box within a `width` x `height` image
+fn region(item: &ReviewItem, (width, height): (u32, u32)) -> Option<[f64; 4]> {
+    let bbox = BBox::parse(item.bbox.as_ref()?, width, height).filter(|bbox| bbox.validate(width, height).is_ok())?;
+    let (w, h) = (f64::from(width), f64::from(height));
+    Some([bbox.x1 / w * 100.0, bbox.y1 / h * 100.0, bbox.width() / w * 100.0, bbox.height() / h * 100.0])
+}
+
+fn region_json(item: &ReviewItem, [x, y, width, height]: [f64; 4], (image_width, image_height): (u32, u32)) -> Value {
+    json!({
+        "id": format!("{}-{}", item.operation, item.index),
+        "from_name": "label",
+        "to_name": "image",
+        "type": "rectanglelabels",
+        "original_width": image_width,
+        "original_height": image_height,
+        "value": {"x": x, "y": y, "width": width, "height": height, "rotation": 0, "rectanglelabels": [item.label]},
+        "score": item.confidence,
+    })
+}
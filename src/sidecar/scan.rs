@@ -0,0 +1,445 @@
+/*
+ * Context: Single-pass parallel directory scan for sidecar statistics
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: jwalk, rayon, chrono
+ */
+
+use crate::sidecar::formats::SidecarFormat;
+use crate::sidecar::badge::is_badge_file;
+use crate::sidecar::patterns::SidecarPatterns;
+use crate::sidecar::types::{HardlinkGroup, OperationType, SidecarError};
+use crate::utils::paths;
+use chrono::{DateTime, NaiveDate, Utc};
+use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Every image and sidecar under a directory, classified during one parallel walk
+#[derive(Debug, Default)]
+pub struct DirectoryScan {
+    /// Regular image files
+    pub images: Vec<PathBuf>,
+    /// Files with a supported sidecar extension
+    pub sidecars: Vec<PathBuf>,
+    /// Symlinks with an image extension
+    pub symlink_count: u32,
+    /// Image symlinks whose target does not exist
+    pub broken_symlinks: u32,
+    /// Paths of the broken image symlinks, sorted
+    pub dangling: Vec<PathBuf>,
+    /// Images with more than one hardlink, grouped by file
+    pub hardlinks: Vec<HardlinkGroup>,
+    /// Other paths of each image kept when hardlinks are merged
+    aliases: HashMap<PathBuf, Vec<PathBuf>>,
+    /// Images that can be resolved by path, including symlinks with a live target
+    resolvable_images: HashSet<PathBuf>,
+    /// Lookup set over `sidecars`
+    sidecar_set: HashSet<PathBuf>,
+}
+
+/// How directory walks treat symlinks and hidden entries
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanOptions {
+    /// Descend into symlinked directories and classify symlinked files by their target
+    pub follow_links: bool,
+    /// Skip files and directories whose name starts with `.`
+    pub skip_hidden: bool,
+    /// Count hardlinks to one file as a single image, under its first path in sorted order
+    pub merge_hardlinks: bool,
+}
+
+/// Order in which a `FileSelection` hands scanned files to a command
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScanOrder {
+    /// As the walk found them
+    #[default]
+    Walk,
+    /// Most recently modified first
+    Newest,
+    /// Largest first
+    Largest,
+}
+
+impl FromStr for ScanOrder {
+    type Err = SidecarError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "walk" => Ok(ScanOrder::Walk),
+            "newest" => Ok(ScanOrder::Newest),
+            "largest" => Ok(ScanOrder::Largest),
+            _ => Err(SidecarError::ProcessingError(format!("Invalid order '{}': expected walk, newest or largest", s))),
+        }
+    }
+}
+
+/// Which scanned files validation, conversion and coverage work on, and in what order
+///
+/// `*_since` bounds are inclusive and `*_before` bounds exclusive. Where the filesystem
+/// records no creation time, the modification time stands in for it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FileSelection {
+    pub modified_since: Option<DateTime<Utc>>,
+    pub modified_before: Option<DateTime<Utc>>,
+    pub created_since: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub order: ScanOrder,
+}
+
+impl FileSelection {
+    /// Whether every file is kept in walk order, so `apply` has nothing to do
+    pub fn is_everything(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Keep the files within the time bounds, in the selected order; ties keep their given order
+    ///
+    /// Files whose metadata cannot be read are dropped when a bound is set. This reads
+    /// metadata for every file, so call it from `spawn_blocking` inside async code.
+    pub fn apply(&self, paths: Vec<PathBuf>) -> Vec<PathBuf> {
+        if self.is_everything() {
+            return paths;
+        }
+        let bounded = self.modified_since.is_some()
+            || self.modified_before.is_some()
+            || self.created_since.is_some()
+            || self.created_before.is_some();
+        let mut files: Vec<(PathBuf, Option<std::fs::Metadata>)> = paths
+            .into_par_iter()
+            .map(|path| {
+                let metadata = std::fs::metadata(&path).ok();
+                (path, metadata)
+            })
+            .filter(|(_, metadata)| !bounded || metadata.as_ref().is_some_and(|metadata| self.admits(metadata)))
+            .collect();
+        match self.order {
+            ScanOrder::Walk => {}
+            ScanOrder::Newest => files.sort_by_key(|(_, metadata)| std::cmp::Reverse(metadata.as_ref().and_then(|m| m.modified().ok()))),
+            ScanOrder::Largest => files.sort_by_key(|(_, metadata)| std::cmp::Reverse(metadata.as_ref().map(|m| m.len()))),
+        }
+        files.into_iter().map(|(path, _)| path).collect()
+    }
+
+    fn admits(&self, metadata: &std::fs::Metadata) -> bool {
+        let Ok(modified) = metadata.modified().map(DateTime::<Utc>::from) else {
+            return false;
+        };
+        let created = metadata.created().map(DateTime::<Utc>::from).unwrap_or(modified);
+        let within = |time: DateTime<Utc>, since: Option<DateTime<Utc>>, before: Option<DateTime<Utc>>| {
+            since.is_none_or(|since| time >= since) && before.is_none_or(|before| time < before)
+        };
+        within(modified, self.modified_since, self.modified_before) && within(created, self.created_since, self.created_before)
+    }
+}
+
+/// Where a symlink with the stored target `target` points; relative targets are relative to the link's directory
+pub fn resolve_link_target(link: &Path, target: &Path) -> PathBuf {
+    paths::link_target(link, target)
+}
+
+/// Device, inode and link count of the file at `path`, if it has more than one hardlink
+#[cfg(unix)]
+pub fn hardlink_id(path: &Path) -> Option<(u64, u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = std::fs::metadata(path).ok()?;
+    (metadata.nlink() > 1).then(|| (metadata.dev(), metadata.ino(), metadata.nlink()))
+}
+
+#[cfg(not(unix))]
+pub fn hardlink_id(_path: &Path) -> Option<(u64, u64, u64)> {
+    None
+}
+
+/// The files in the directory of `path` that are hardlinks to the file `device` and `inode`, sorted
+///
+/// Links elsewhere are only found by a scan that reaches them.
+pub fn sibling_links(path: &Path, device: u64, inode: u64) -> Vec<PathBuf> {
+    let mut links: Vec<PathBuf> = path
+        .parent()
+        .and_then(|parent| std::fs::read_dir(parent).ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|candidate| hardlink_id(candidate).is_some_and(|(d, i, _)| (d, i) == (device, inode)))
+        .collect();
+    links.sort();
+    links
+}
+
+/// Group the hardlinked files among `paths` by device and inode, in order of their first path
+///
+/// This reads metadata for every path; call it from `spawn_blocking` inside async code.
+pub fn hardlink_groups(paths: &[PathBuf]) -> Vec<HardlinkGroup> {
+    let linked: Vec<((u64, u64, u64), &PathBuf)> = paths
+        .par_iter()
+        .filter_map(|path| hardlink_id(path).map(|id| (id, path)))
+        .collect();
+    let mut groups: BTreeMap<(u64, u64), HardlinkGroup> = BTreeMap::new();
+    for ((device, inode, links), path) in linked {
+        groups
+            .entry((device, inode))
+            .or_insert_with(|| HardlinkGroup { device, inode, links, paths: Vec::new() })
+            .paths
+            .push(path.clone());
+    }
+    let mut groups: Vec<HardlinkGroup> = groups.into_values().collect();
+    for group in &mut groups {
+        group.paths.sort();
+    }
+    groups.sort_by(|a, b| a.paths[0].cmp(&b.paths[0]));
+    groups
+}
+
+/// Every image stem a sidecar name can refer to, longest first: its whole stem, then what follows each `_`
+///
+/// `face_detection_IMG_001.json` may belong to `face_detection_IMG_001`, `detection_IMG_001`,
+/// `IMG_001` or `001`; see `owning_image`. The split works on the raw name, so stems that are
+/// not valid UTF-8 still match their image.
+pub fn candidate_image_stems(sidecar_path: &Path) -> Vec<&OsStr> {
+    let stem = sidecar_path.file_stem().unwrap_or_default();
+    match stem.to_str() {
+        Some(text) => std::iter::once(text)
+            .chain(text.match_indices('_').map(|(index, _)| &text[index + 1..]))
+            .map(OsStr::new)
+            .collect(),
+        #[cfg(unix)]
+        None => {
+            use std::os::unix::ffi::OsStrExt;
+            let bytes = stem.as_bytes();
+            std::iter::once(bytes)
+                .chain(bytes.iter().enumerate().filter(|(_, &byte)| byte == b'_').map(|(index, _)| &bytes[index + 1..]))
+                .map(OsStr::from_bytes)
+                .collect()
+        }
+        #[cfg(not(unix))]
+        None => vec![stem],
+    }
+}
+
+/// The image a sidecar in `directory` belongs to: the first of the stems `patterns` reads out
+/// of its name with an image that `exists` accepts, trying extensions in order
+///
+/// Configured patterns go first. After them longer stems win, so `IMG_001.json` belongs to
+/// `IMG_001.jpg` even when `001.jpg` exists.
+pub fn owning_image(
+    sidecar_path: &Path,
+    directory: &Path,
+    image_extensions: &[String],
+    patterns: &SidecarPatterns,
+    exists: impl Fn(&Path) -> bool,
+) -> Option<PathBuf> {
+    patterns.image_stems(sidecar_path).into_iter().find_map(|stem| {
+        image_extensions
+            .iter()
+            .map(|ext| directory.join(image_file_name(stem, ext)))
+            .find(|candidate| exists(candidate))
+    })
+}
+
+/// `stem.extension` as a file name, without a lossy trip through `str`
+pub fn image_file_name(stem: &OsStr, extension: &str) -> OsString {
+    let mut name = stem.to_os_string();
+    name.push(".");
+    name.push(extension);
+    name
+}
+
+/// Whether `path` is a symlink whose target does not exist
+pub fn is_dangling(path: &Path) -> bool {
+    paths::is_link(path) && !path.exists()
+}
+
+/// Parse a `--modified-since` style bound: a date such as `2024-12-01` (midnight UTC) or an RFC 3339 timestamp
+pub fn parse_time_bound(text: &str) -> std::result::Result<DateTime<Utc>, SidecarError> {
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).expect("midnight is a valid time").and_utc());
+    }
+    DateTime::parse_from_rfc3339(text)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|_| SidecarError::ProcessingError(format!("Invalid time '{}': expected YYYY-MM-DD or an RFC 3339 timestamp", text)))
+}
+
+/// A sidecar matched to the image it belongs to
+#[derive(Debug, Clone)]
+pub struct SidecarMatch {
+    pub image_path: PathBuf,
+    pub sidecar_path: PathBuf,
+    /// Operation named by the configured pattern the sidecar matched, if any
+    pub operation: Option<OperationType>,
+}
+
+impl DirectoryScan {
+    /// Walk `directory` once, in parallel, classifying entries by extension
+    /// This is blocking; call it from `spawn_blocking` inside async code
+    pub fn walk(directory: &Path, image_extensions: &[String], options: ScanOptions) -> Self {
+        let mut scan = DirectoryScan::default();
+
+        // Walk the long form so trees past MAX_PATH are read on Windows, but report paths as given
+        let root = paths::long_path(directory);
+        let walker = jwalk::WalkDir::new(&root)
+            .skip_hidden(options.skip_hidden)
+            .follow_links(options.follow_links);
+
+        let is_image = |path: &Path| {
+            path.extension()
+                .map(|extension| extension.to_string_lossy().to_lowercase())
+                .is_some_and(|ext_str| image_extensions.iter().any(|ext| ext == &ext_str))
+        };
+
+        for entry in walker.into_iter() {
+            let entry = match entry {
+                Ok(entry) => entry,
+                // When following links, a dangling one surfaces as an error rather than an entry
+                Err(error) => {
+                    if let Some(path) = error.path().filter(|path| is_image(path) && is_dangling(path)) {
+                        scan.symlink_count += 1;
+                        scan.broken_symlinks += 1;
+                        scan.dangling.push(paths::rebase(path.to_path_buf(), &root, directory));
+                    }
+                    continue;
+                }
+            };
+            let file_type = entry.file_type();
+            let path = paths::rebase(entry.path(), &root, directory);
+            let Some(extension) = path.extension() else {
+                continue;
+            };
+            let ext_str = extension.to_string_lossy().to_lowercase();
+            let is_image = image_extensions.iter().any(|ext| ext == &ext_str);
+
+            if file_type.is_file() {
+                if is_image {
+                    // A followed link arrives as the file it points to
+                    if entry.path_is_symlink() {
+                        scan.symlink_count += 1;
+                    }
+                    scan.resolvable_images.insert(path.clone());
+                    scan.images.push(path);
+                } else if SidecarFormat::from_extension(&ext_str).is_some() && !is_badge_file(&path) {
+                    scan.sidecar_set.insert(path.clone());
+                    scan.sidecars.push(path);
+                }
+            } else if file_type.is_symlink() && is_image {
+                scan.symlink_count += 1;
+                if path.exists() {
+                    scan.resolvable_images.insert(path);
+                } else {
+                    scan.broken_symlinks += 1;
+                    scan.dangling.push(path);
+                }
+            }
+        }
+
+        // Parallel walks yield entries in nondeterministic order
+        scan.images.sort_unstable();
+        scan.sidecars.sort_unstable();
+        scan.dangling.sort_unstable();
+
+        scan.hardlinks = hardlink_groups(&scan.images);
+        if options.merge_hardlinks {
+            let mut duplicates = HashSet::new();
+            for group in scan.hardlinks.iter().filter(|group| group.paths.len() > 1) {
+                for alias in &group.paths[1..] {
+                    scan.resolvable_images.remove(alias);
+                    duplicates.insert(alias.clone());
+                }
+                scan.aliases.insert(group.paths[0].clone(), group.paths[1..].to_vec());
+            }
+            scan.images.retain(|image| !duplicates.contains(image));
+        }
+
+        scan
+    }
+
+    /// Classify a listing of the files under `directory`, as `walk` would their entries
+    ///
+    /// Listings hold no symlinks or hardlinks, so only `skip_hidden` applies.
+    pub fn from_files(directory: &Path, files: Vec<PathBuf>, image_extensions: &[String], options: ScanOptions) -> Self {
+        let mut scan = DirectoryScan::default();
+        for path in files {
+            let hidden = path
+                .strip_prefix(directory)
+                .unwrap_or(&path)
+                .components()
+                .any(|component| component.as_os_str().to_string_lossy().starts_with('.'));
+            if options.skip_hidden && hidden {
+                continue;
+            }
+            let Some(ext_str) = path.extension().map(|extension| extension.to_string_lossy().to_lowercase()) else {
+                continue;
+            };
+            if image_extensions.iter().any(|ext| ext == &ext_str) {
+                scan.resolvable_images.insert(path.clone());
+                scan.images.push(path);
+            } else if SidecarFormat::from_extension(&ext_str).is_some() && !is_badge_file(&path) {
+                scan.sidecar_set.insert(path.clone());
+                scan.sidecars.push(path);
+            }
+        }
+        scan.images.sort_unstable();
+        scan.sidecars.sort_unstable();
+        scan
+    }
+
+    /// Whether `path` is an image that resolves, a regular file or a symlink with a live target
+    pub fn is_resolvable(&self, path: &Path) -> bool {
+        self.resolvable_images.contains(path)
+    }
+
+    /// Pair sidecars with images without touching the filesystem
+    ///
+    /// Images are matched first to a sidecar sharing their stem, preferring
+    /// `.bin` -> `.rkyv` -> `.json`. Remaining sidecars are then matched to `IMG.<ext>`
+    /// in `directory` by the configured `patterns` (`IMG_faces.json`), then by the
+    /// built-in pattern (`prefix_IMG.json`).
+    pub fn match_sidecars(&self, directory: &Path, image_extensions: &[String], patterns: &SidecarPatterns) -> Vec<SidecarMatch> {
+        let formats_to_try = [SidecarFormat::Binary, SidecarFormat::Rkyv, SidecarFormat::Json];
+        let mut matched = HashSet::new();
+        let mut matches = Vec::new();
+
+        for image_path in &self.images {
+            // A merged image takes the sidecar of whichever of its paths has one
+            let found = std::iter::once(image_path)
+                .chain(self.aliases.get(image_path).into_iter().flatten())
+                .flat_map(|path| formats_to_try.iter().map(|format| path.with_extension(format.extension())))
+                .find(|candidate| self.sidecar_set.contains(candidate));
+
+            if let Some(sidecar_path) = found {
+                if matched.insert(sidecar_path.clone()) {
+                    matches.push(SidecarMatch {
+                        image_path: image_path.clone(),
+                        sidecar_path,
+                        operation: None,
+                    });
+                }
+            }
+        }
+
+        for sidecar_path in &self.sidecars {
+            if matched.contains(sidecar_path) {
+                continue;
+            }
+
+            // Images sit beside their sidecars, which may be in a subdirectory
+            let image_directory = sidecar_path.parent().unwrap_or(directory);
+            let image_path = owning_image(sidecar_path, image_directory, image_extensions, patterns, |candidate| {
+                self.resolvable_images.contains(candidate)
+            });
+
+            if let Some(image_path) = image_path {
+                matched.insert(sidecar_path.clone());
+                matches.push(SidecarMatch {
+                    operation: patterns.operation_for(sidecar_path, &image_path),
+                    image_path,
+                    sidecar_path: sidecar_path.clone(),
+                });
+            }
+        }
+
+        matches
+    }
+}
@@ -0,0 +1,161 @@
+/*
+ * Context: Reading a single sidecar of any format for display
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde_json, zstd, flate2
+ *
+ * `show` prints one sidecar whatever it is stored as. The format is told from the contents
+ * rather than trusted from the name: zstd and gzip are recognised by their magic bytes and
+ * unpacked first, then the payload is read as JSON or as the bincode-wrapped JSON of the
+ * binary formats, whose extension tells `.bin` from `.rkyv`. Decompressed payloads are held
+ * to the same size limit as the files themselves.
+ */
+
+use crate::sidecar::formats::{FormatManager, SidecarFormat};
+use crate::sidecar::storage;
+use crate::sidecar::types::{Result, SidecarError};
+use serde::Serialize;
+use serde_json::Value;
+use std::borrow::Cow;
+use std::fmt::Write as _;
+use std::io::Read;
+use std::path::Path;
+
+/// Compression wrapped around a sidecar file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    Zstd,
+    Gzip,
+}
+
+impl Compression {
+    /// The compression `bytes` start with, by magic number
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(Compression::Zstd),
+            [0x1f, 0x8b, ..] => Some(Compression::Gzip),
+            _ => None,
+        }
+    }
+
+    /// Extension of files compressed this way
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Compression::Zstd => "zst",
+            Compression::Gzip => "gz",
+        }
+    }
+
+    fn decoder<'a>(&self, bytes: &'a [u8]) -> Result<Box<dyn Read + 'a>> {
+        Ok(match self {
+            Compression::Zstd => Box::new(zstd::Decoder::new(bytes)?),
+            Compression::Gzip => Box::new(flate2::read::GzDecoder::new(bytes)),
+        })
+    }
+}
+
+/// One sidecar as read by `show`
+#[derive(Debug, Clone, Serialize)]
+pub struct ShownSidecar {
+    pub format: SidecarFormat,
+    pub compression: Option<Compression>,
+    /// Size of the file on disk, in bytes
+    pub file_size: u64,
+    pub data: Value,
+}
+
+/// Read and decode the sidecar at `path`, refusing files or payloads over `max_size` bytes
+pub fn open(path: &Path, max_size: u64) -> Result<ShownSidecar> {
+    let bytes = storage::read_sidecar(None, path, max_size)?;
+    decode(path, &bytes, max_size)
+}
+
+/// Decode the sidecar file `bytes` read from `path`, whatever its format and compression
+pub fn decode(path: &Path, bytes: &[u8], max_size: u64) -> Result<ShownSidecar> {
+    let compression = Compression::detect(bytes);
+    let payload = match compression {
+        Some(compression) => {
+            let mut payload = Vec::new();
+            compression.decoder(bytes)?.take(max_size + 1).read_to_end(&mut payload)?;
+            if payload.len() as u64 > max_size {
+                return Err(SidecarError::TooLarge { path: path.to_path_buf(), size: payload.len() as u64, limit: max_size });
+            }
+            Cow::Owned(payload)
+        }
+        None => Cow::Borrowed(bytes),
+    };
+
+    // `a.rkyv.zst` names the format under the compression extension
+    let inner_name = match compression {
+        Some(compression) if path.extension().is_some_and(|extension| extension == compression.extension()) => path.file_stem().map(Path::new),
+        _ => Some(path),
+    };
+    let named = inner_name.and_then(SidecarFormat::from_path);
+
+    // JSON is tried first since a bincode string prefix is never valid JSON
+    let formats = FormatManager::new();
+    let (format, data) = [SidecarFormat::Json, SidecarFormat::Binary]
+        .into_iter()
+        .find_map(|format| formats.get_serializer(format).deserialize(&payload).ok().map(|data| (format, data)))
+        .ok_or_else(|| SidecarError::SerializationError(format!("{} is neither a JSON nor a binary sidecar", path.display())))?;
+    let format = match (format, named) {
+        (SidecarFormat::Binary, Some(SidecarFormat::Rkyv)) => SidecarFormat::Rkyv,
+        (format, _) => format,
+    };
+
+    Ok(ShownSidecar { format, compression, file_size: bytes.len() as u64, data })
+}
+
+const KEY: &str = "\x1b[34m";
+const STRING: &str = "\x1b[32m";
+const NUMBER: &str = "\x1b[36m";
+const LITERAL: &str = "\x1b[35m";
+const RESET: &str = "\x1b[0m";
+
+/// `value` pretty-printed like `serde_json::to_string_pretty`, with ANSI colours for
+/// keys, strings, numbers and literals
+pub fn highlight(value: &Value) -> String {
+    let mut out = String::new();
+    write_highlighted(&mut out, value, 0);
+    out
+}
+
+fn write_highlighted(out: &mut String, value: &Value, depth: usize) {
+    let indent = |out: &mut String, depth: usize| out.extend(std::iter::repeat_n("  ", depth));
+    match value {
+        Value::Null | Value::Bool(_) => {
+            let _ = write!(out, "{}{}{}", LITERAL, value, RESET);
+        }
+        Value::Number(number) => {
+            let _ = write!(out, "{}{}{}", NUMBER, number, RESET);
+        }
+        Value::String(_) => {
+            let _ = write!(out, "{}{}{}", STRING, value, RESET);
+        }
+        Value::Array(items) if items.is_empty() => out.push_str("[]"),
+        Value::Object(map) if map.is_empty() => out.push_str("{}"),
+        Value::Array(items) => {
+            out.push_str("[\n");
+            for (index, item) in items.iter().enumerate() {
+                indent(out, depth + 1);
+                write_highlighted(out, item, depth + 1);
+                out.push_str(if index + 1 < items.len() { ",\n" } else { "\n" });
+            }
+            indent(out, depth);
+            out.push(']');
+        }
+        Value::Object(map) => {
+            out.push_str("{\n");
+            for (index, (key, item)) in map.iter().enumerate() {
+                indent(out, depth + 1);
+                let _ = write!(out, "{}{}{}: ", KEY, Value::from(key.as_str()), RESET);
+                write_highlighted(out, item, depth + 1);
+                out.push_str(if index + 1 < map.len() { ",\n" } else { "\n" });
+            }
+            indent(out, depth);
+            out.push('}');
+        }
+    }
+}
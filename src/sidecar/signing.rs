@@ -0,0 +1,237 @@
+/*
+ * Context: Ed25519 signatures over sidecar payloads, proving which pipeline wrote them
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: ed25519-dalek, getrandom, hex, serde_json
+ */
+
+use crate::sidecar::blobs;
+use crate::sidecar::fingerprint::ImageFingerprint;
+use crate::sidecar::formats::to_canonical_json;
+use crate::sidecar::types::{Result, SidecarError};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Key under `sidecar_info` holding one `SignatureRecord` per payload
+pub const SIGNATURES_KEY: &str = "signatures";
+
+/// Signature of one payload as stored in `sidecar_info.signatures`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignatureRecord {
+    /// Hex public key of the signer
+    pub key_id: String,
+    /// Hex Ed25519 signature
+    pub signature: String,
+}
+
+/// The bytes signed for a payload: the identity of its image, its key and its canonical JSON,
+/// one per line
+///
+/// Including the key stops a signed payload from being moved under another operation, and the
+/// image identity stops it from being copied into the sidecar of another image.
+fn signed_message(image: &str, key: &str, payload: &Value) -> Vec<u8> {
+    format!("{}\n{}\n{}", image, key, to_canonical_json(payload)).into_bytes()
+}
+
+/// Identity of an image as signed with its payloads: the XXH3 of its contents
+///
+/// Unlike the path, it survives moving the image together with its sidecars. An image that
+/// cannot be read has the empty identity. Blocking; call it from `spawn_blocking` inside async code.
+pub fn image_identity(image_path: &Path) -> String {
+    ImageFingerprint::of(image_path).map(|fingerprint| fingerprint.xxh3).unwrap_or_default()
+}
+
+fn key_error(path: &Path, reason: impl std::fmt::Display) -> SidecarError {
+    SidecarError::Schema(format!("{} is not an Ed25519 key: {}", path.display(), reason))
+}
+
+/// Read a file holding 32 hex-encoded bytes
+fn read_key_bytes(path: &Path) -> Result<[u8; 32]> {
+    let text = std::fs::read_to_string(path)?;
+    let bytes = hex::decode(text.trim()).map_err(|e| key_error(path, e))?;
+    bytes.try_into().map_err(|bytes: Vec<u8>| key_error(path, format!("expected 32 bytes, found {}", bytes.len())))
+}
+
+/// Signs payloads as they are written
+pub struct SidecarSigner {
+    key: SigningKey,
+    key_id: String,
+}
+
+impl SidecarSigner {
+    pub fn new(key: SigningKey) -> Self {
+        let key_id = hex::encode(key.verifying_key().as_bytes());
+        Self { key, key_id }
+    }
+
+    /// A new key from the operating system's random number generator
+    pub fn generate() -> Result<Self> {
+        let mut seed = [0u8; 32];
+        getrandom::getrandom(&mut seed).map_err(|e| SidecarError::ProcessingError(format!("No randomness for a new key: {}", e)))?;
+        Ok(Self::new(SigningKey::from_bytes(&seed)))
+    }
+
+    /// Load a secret key written by `save`
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(Self::new(SigningKey::from_bytes(&read_key_bytes(path)?)))
+    }
+
+    /// Write the secret key to `path` and the public key to `path` with `.pub` appended
+    ///
+    /// On Unix the secret key file is readable by its owner only.
+    pub fn save(&self, path: &Path) -> Result<PathBuf> {
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        std::io::Write::write_all(&mut options.open(path)?, format!("{}\n", hex::encode(self.key.to_bytes())).as_bytes())?;
+
+        let mut public_path = path.as_os_str().to_owned();
+        public_path.push(".pub");
+        let public_path = PathBuf::from(public_path);
+        std::fs::write(&public_path, format!("{}\n", self.key_id))?;
+        Ok(public_path)
+    }
+
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.key.verifying_key()
+    }
+
+    /// Hex public key, as recorded in every signature
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Sign the payload stored under `key` in the sidecar of the image with identity `image`
+    pub fn sign(&self, image: &str, key: &str, payload: &Value) -> SignatureRecord {
+        let signature = self.key.sign(&signed_message(image, key, payload));
+        SignatureRecord { key_id: self.key_id.clone(), signature: hex::encode(signature.to_bytes()) }
+    }
+
+    /// Re-sign every inline payload of a sidecar after it was rewritten
+    /// Blob pointers are skipped; their blobs, and so their signatures, are unchanged.
+    pub fn sign_all(&self, image: &str, data: &mut Value) -> Result<()> {
+        let Some(object) = data.as_object_mut() else {
+            return Ok(());
+        };
+        let signatures: Vec<(String, SignatureRecord)> = object
+            .iter()
+            .filter(|(key, payload)| *key != "sidecar_info" && blobs::pointer_hash(payload).is_none())
+            .map(|(key, payload)| (key.clone(), self.sign(image, key, payload)))
+            .collect();
+        let stored = object
+            .entry("sidecar_info")
+            .or_insert_with(|| Value::Object(serde_json::Map::new()))
+            .as_object_mut()
+            .map(|info| info.entry(SIGNATURES_KEY).or_insert_with(|| Value::Object(serde_json::Map::new())))
+            .and_then(Value::as_object_mut);
+        if let Some(stored) = stored {
+            for (key, signature) in signatures {
+                stored.insert(key, serde_json::to_value(signature)?);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for SidecarSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print the secret key
+        f.debug_struct("SidecarSigner").field("key_id", &self.key_id).finish()
+    }
+}
+
+/// Why a payload's signature could not be trusted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "problem", rename_all = "snake_case")]
+pub enum SignatureProblem {
+    /// The payload has no signature
+    Unsigned,
+    /// The signer's key is not one of the trusted keys
+    UnknownKey { key_id: String },
+    /// The signature does not match the payload, e.g. because it was edited after signing or
+    /// copied from the sidecar of another image
+    Invalid,
+    /// The sidecar could not be read, so none of its payloads can be checked
+    Unreadable { reason: String },
+}
+
+impl std::fmt::Display for SignatureProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignatureProblem::Unsigned => write!(f, "not signed"),
+            SignatureProblem::UnknownKey { key_id } => write!(f, "signed by untrusted key {}", key_id),
+            SignatureProblem::Invalid => write!(f, "signature does not match the payload"),
+            SignatureProblem::Unreadable { reason } => write!(f, "sidecar could not be read: {}", reason),
+        }
+    }
+}
+
+/// Checks signatures against a set of trusted public keys
+#[derive(Debug, Clone, Default)]
+pub struct SignatureVerifier {
+    keys: BTreeMap<String, VerifyingKey>,
+}
+
+impl SignatureVerifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Trust `key`
+    pub fn add_key(mut self, key: VerifyingKey) -> Self {
+        self.keys.insert(hex::encode(key.as_bytes()), key);
+        self
+    }
+
+    /// Trust the public key in `path`, as written by `SidecarSigner::save`
+    pub fn add_key_file(self, path: &Path) -> Result<Self> {
+        let key = VerifyingKey::from_bytes(&read_key_bytes(path)?).map_err(|e| key_error(path, e))?;
+        Ok(self.add_key(key))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Check the signature of the payload stored under `key` in the sidecar of the image with
+    /// identity `image`
+    pub fn verify(&self, image: &str, key: &str, payload: &Value, record: Option<&SignatureRecord>) -> std::result::Result<(), SignatureProblem> {
+        let record = record.ok_or(SignatureProblem::Unsigned)?;
+        let trusted = self.keys.get(&record.key_id).ok_or_else(|| SignatureProblem::UnknownKey { key_id: record.key_id.clone() })?;
+        let signature = hex::decode(&record.signature)
+            .ok()
+            .and_then(|bytes| Signature::from_slice(&bytes).ok())
+            .ok_or(SignatureProblem::Invalid)?;
+        trusted.verify(&signed_message(image, key, payload), &signature).map_err(|_| SignatureProblem::Invalid)
+    }
+}
+
+/// A payload whose signature could not be trusted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignatureIssue {
+    pub sidecar_path: PathBuf,
+    /// Payload key, usually the operation name; empty when the sidecar could not be read
+    pub payload: String,
+    #[serde(flatten)]
+    pub problem: SignatureProblem,
+}
+
+/// Result of checking every payload signature under a directory
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignatureReport {
+    pub sidecars: usize,
+    pub payloads: usize,
+    pub verified: usize,
+    pub issues: Vec<SignatureIssue>,
+}
+
+impl SignatureReport {
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
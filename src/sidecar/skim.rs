@@ -0,0 +1,163 @@
+/*
+ * Context: Streaming outlines of large sidecars for statistics, operation detection and queries
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde, serde_json, bincode
+ *
+ * A detector sidecar of several hundred megabytes is mostly numbers: face encodings,
+ * landmarks, keypoints and polygons. Decoding it into a `serde_json::Value` costs several
+ * times its size. `skim` streams through the document instead and keeps its outline: every
+ * key, string, boolean and scalar number, and arrays of objects such as detections, but not
+ * the numbers inside arrays. Arrays under `faces`, `objects` and `detections` keep every
+ * element, so detection counts stay exact, and `sidecar_info` is always kept whole, as is
+ * any subtree under a key the caller names. Operation detection, payload metrics, class
+ * counts and detection filters read the same values from an outline as from the document.
+ */
+
+use crate::sidecar::formats::{wrapped_json, SerializationError, SidecarFormat};
+use serde::de::{DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Keys whose arrays keep every element
+const DETECTION_KEYS: [&str; 3] = ["faces", "objects", "detections"];
+
+/// Keys whose subtrees an outline keeps whole, at any depth; `sidecar_info` always is
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SkimKeys {
+    keys: BTreeSet<String>,
+}
+
+impl SkimKeys {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also keep the subtree under `key`
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.keys.insert(key.into());
+        self
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        key == "sidecar_info" || self.keys.contains(key)
+    }
+
+    /// The keys named besides `sidecar_info`
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.keys.iter().map(String::as_str)
+    }
+}
+
+impl<S: Into<String>> FromIterator<S> for SkimKeys {
+    fn from_iter<I: IntoIterator<Item = S>>(keys: I) -> Self {
+        Self { keys: keys.into_iter().map(Into::into).collect() }
+    }
+}
+
+impl<S: Into<String>> Extend<S> for SkimKeys {
+    fn extend<I: IntoIterator<Item = S>>(&mut self, keys: I) {
+        self.keys.extend(keys.into_iter().map(Into::into));
+    }
+}
+
+/// Outline of a sidecar stored in `format`, keeping the subtrees under `keys` whole
+///
+/// Fails on the same inputs as the format's deserializer.
+pub fn skim(bytes: &[u8], format: SidecarFormat, keys: &SkimKeys) -> Result<Value, SerializationError> {
+    match format {
+        SidecarFormat::Json => {
+            let text = std::str::from_utf8(bytes)
+                .map_err(|e| SerializationError::Json(serde_json::Error::io(std::io::Error::new(std::io::ErrorKind::InvalidData, e))))?;
+            skim_json(serde_json::Deserializer::from_str(text), keys)
+        }
+        SidecarFormat::Binary | SidecarFormat::Rkyv => skim_json(serde_json::Deserializer::from_str(wrapped_json(bytes)?), keys),
+    }
+}
+
+fn skim_json<'de, R: serde_json::de::Read<'de>>(
+    mut deserializer: serde_json::Deserializer<R>,
+    keys: &SkimKeys,
+) -> Result<Value, SerializationError> {
+    let outline = Skim { keys, keep_elements: false }.deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(outline)
+}
+
+/// Deserializes one value into its outline
+#[derive(Clone, Copy)]
+struct Skim<'a> {
+    keys: &'a SkimKeys,
+    /// Keep the numbers of the array being read, as for detection keys
+    keep_elements: bool,
+}
+
+impl<'de> DeserializeSeed<'de> for Skim<'_> {
+    type Value = Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Skim<'_> {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON value")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Value, E> {
+        Ok(Value::Bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Value, E> {
+        Ok(Value::from(value))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Value, E> {
+        Ok(Value::from(value))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Value, E> {
+        Ok(Value::from(value))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Value, E> {
+        Ok(Value::String(value.to_string()))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Value, E> {
+        Ok(Value::String(value))
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let element = Skim { keys: self.keys, keep_elements: false };
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element_seed(element)? {
+            if self.keep_elements || !item.is_number() {
+                items.push(item);
+            }
+        }
+        Ok(Value::Array(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut object = Map::new();
+        while let Some(key) = map.next_key::<String>()? {
+            let value = if self.keys.contains(&key) {
+                map.next_value::<Value>()?
+            } else {
+                map.next_value_seed(Skim { keys: self.keys, keep_elements: DETECTION_KEYS.contains(&key.as_str()) })?
+            };
+            object.insert(key, value);
+        }
+        Ok(Value::Object(object))
+    }
+}
@@ -0,0 +1,169 @@
+/*
+ * Context: Named snapshots of a directory's sidecars for rolling back a bad run
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde_json, sha2, chrono
+ */
+
+use crate::sidecar::manifest::{checksum, Manifest};
+use crate::sidecar::types::{Result, SidecarError};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+/// Manifest of a snapshot, written last so an interrupted snapshot is never listed
+pub const SNAPSHOT_MANIFEST: &str = "manifest.json";
+
+/// Directory inside a snapshot holding the sidecar copies
+const SNAPSHOT_FILES: &str = "sidecars";
+
+/// A snapshot as listed by `SnapshotStore::list`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub files: usize,
+}
+
+/// What `snapshot create` stored
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotSummary {
+    pub name: String,
+    pub files: usize,
+    /// Sidecars unchanged since an earlier snapshot, hardlinked to its copy instead of copied
+    pub linked: usize,
+    pub bytes_copied: u64,
+}
+
+/// What `snapshot restore` changed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RestoreSummary {
+    /// Sidecars written back because they changed or disappeared since the snapshot
+    pub restored: Vec<String>,
+    /// Sidecars created since the snapshot, removed
+    pub removed: Vec<String>,
+    pub unchanged: usize,
+}
+
+/// Directory of named snapshots, each a manifest plus a copy of every sidecar
+///
+/// Snapshot copies are never modified, so a sidecar that has not changed since an earlier
+/// snapshot is hardlinked to that snapshot's copy and costs no extra space. Live sidecars
+/// are rewritten in place, so they are always copied rather than linked.
+#[derive(Debug, Clone)]
+pub struct SnapshotStore {
+    root: PathBuf,
+}
+
+impl SnapshotStore {
+    /// Use `root` as the store, creating it if needed
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self> {
+        let root = root.into();
+        std::fs::create_dir_all(&root)?;
+        Ok(Self { root })
+    }
+
+    /// The default store for `directory`: a `<directory>.snapshots` sibling, outside the scanned tree
+    pub fn default_for(directory: &Path) -> PathBuf {
+        // `.` has no name of its own; its real path does
+        let directory = directory.canonicalize().unwrap_or_else(|_| directory.to_path_buf());
+        let mut name = directory.file_name().map(|name| name.to_os_string()).unwrap_or_else(|| "sidecars".into());
+        name.push(".snapshots");
+        directory.with_file_name(name)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn snapshot_dir(&self, name: &str) -> Result<PathBuf> {
+        let valid = !name.starts_with('.') && matches!(Path::new(name).components().collect::<Vec<_>>()[..], [Component::Normal(_)]);
+        if !valid {
+            return Err(SidecarError::Schema(format!("Invalid snapshot name {:?}", name)));
+        }
+        Ok(self.root.join(name))
+    }
+
+    /// Copy of the sidecar at `path` (relative, `/`-separated) in snapshot `name`
+    fn file_path(&self, name: &str, path: &str) -> Result<PathBuf> {
+        if !Path::new(path).components().all(|component| matches!(component, Component::Normal(_))) {
+            return Err(SidecarError::Schema(format!("Refusing unsafe snapshot path {:?}", path)));
+        }
+        Ok(self.snapshot_dir(name)?.join(SNAPSHOT_FILES).join(path))
+    }
+
+    pub fn manifest(&self, name: &str) -> Result<Manifest> {
+        let path = self.snapshot_dir(name)?.join(SNAPSHOT_MANIFEST);
+        if !path.exists() {
+            return Err(SidecarError::SidecarNotFound(path));
+        }
+        Manifest::load(&path)
+    }
+
+    /// Every complete snapshot, oldest first
+    pub fn list(&self) -> Result<Vec<SnapshotInfo>> {
+        let mut snapshots = Vec::new();
+        for entry in std::fs::read_dir(&self.root)? {
+            let entry = entry?;
+            let Some(name) = entry.file_name().to_str().map(str::to_string) else { continue };
+            if !entry.path().join(SNAPSHOT_MANIFEST).exists() {
+                continue;
+            }
+            let manifest = self.manifest(&name)?;
+            snapshots.push(SnapshotInfo { name, created_at: manifest.created_at, files: manifest.entries.len() });
+        }
+        snapshots.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.name.cmp(&b.name)));
+        Ok(snapshots)
+    }
+
+    /// Store the sidecars listed in `manifest`, read from `directory`, as snapshot `name`
+    /// Blocking; fails if the snapshot exists or a sidecar changed since `manifest` was made.
+    pub fn create(&self, name: &str, directory: &Path, manifest: &Manifest) -> Result<SnapshotSummary> {
+        let snapshot_dir = self.snapshot_dir(name)?;
+        if snapshot_dir.exists() {
+            return Err(SidecarError::Schema(format!("Snapshot {} already exists", name)));
+        }
+
+        // Earlier copies by path and checksum, which unchanged sidecars link to
+        let mut earlier: HashMap<(String, String), PathBuf> = HashMap::new();
+        for snapshot in self.list()? {
+            for entry in self.manifest(&snapshot.name)?.entries {
+                let copy = self.file_path(&snapshot.name, &entry.path)?;
+                earlier.insert((entry.path, entry.checksum), copy);
+            }
+        }
+
+        let mut summary = SnapshotSummary { name: name.to_string(), files: manifest.entries.len(), ..Default::default() };
+        for entry in &manifest.entries {
+            let target = self.file_path(name, &entry.path)?;
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            if let Some(copy) = earlier.get(&(entry.path.clone(), entry.checksum.clone())) {
+                if std::fs::hard_link(copy, &target).is_ok() {
+                    summary.linked += 1;
+                    continue;
+                }
+            }
+            let bytes = std::fs::read(directory.join(&entry.path))?;
+            if checksum(&bytes) != entry.checksum {
+                return Err(SidecarError::Storage(format!("{} changed while the snapshot was taken", entry.path)));
+            }
+            std::fs::write(&target, &bytes)?;
+            summary.bytes_copied += bytes.len() as u64;
+        }
+        manifest.save(&snapshot_dir.join(SNAPSHOT_MANIFEST))?;
+        Ok(summary)
+    }
+
+    /// Contents of the sidecar at `path` in snapshot `name`, checked against `expected_checksum`
+    pub fn read(&self, name: &str, path: &str, expected_checksum: &str) -> Result<Vec<u8>> {
+        let bytes = std::fs::read(self.file_path(name, path)?)?;
+        if checksum(&bytes) != expected_checksum {
+            return Err(SidecarError::Storage(format!("Snapshot {} copy of {} is corrupt", name, path)));
+        }
+        Ok(bytes)
+    }
+}
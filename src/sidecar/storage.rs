@@ -0,0 +1,97 @@
+/*
+ * Context: Pluggable storage for sidecar reads, writes and directory scans
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: none
+ *
+ * A manager reads and writes the local filesystem unless it is given a `StorageBackend`.
+ * The backend then serves sidecar reads and writes, existence checks, format conversion,
+ * transactions and the directory scans behind statistics, queries and the other analytics,
+ * so those can run against something else entirely, such as `testing::MemoryStorage`. Image
+ * hashing, packing and the other commands that work on image files in place keep using the
+ * filesystem.
+ *
+ * Sidecars read for decoding go through `read_sidecar`, which refuses files over the
+ * configured maximum size before loading them.
+ */
+
+use crate::sidecar::types::{Result, SidecarError};
+use std::fmt::Debug;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Where a manager keeps sidecars instead of the local filesystem
+///
+/// Paths are the ones the manager is given, joined as usual; a backend decides what they
+/// mean. Methods are blocking and called from blocking threads during scans.
+pub trait StorageBackend: Debug + Send + Sync {
+    /// Contents of the file at `path`
+    fn read(&self, path: &Path) -> Result<Vec<u8>>;
+
+    /// Replace the file at `path`, creating it and any parents
+    fn write(&self, path: &Path, bytes: &[u8]) -> Result<()>;
+
+    /// Delete the file at `path`
+    fn remove(&self, path: &Path) -> Result<()>;
+
+    /// Whether a file exists at `path`
+    fn exists(&self, path: &Path) -> bool;
+
+    /// Every file under `directory`, at any depth, in any order
+    fn list(&self, directory: &Path) -> Result<Vec<PathBuf>>;
+
+    /// Move the file at `from` to `to`, replacing `to`; copies then removes unless a backend
+    /// can move files itself
+    fn rename(&self, from: &Path, to: &Path) -> Result<()> {
+        let bytes = self.read(from)?;
+        self.write(to, &bytes)?;
+        self.remove(from)
+    }
+
+    /// Size in bytes of the file at `path`; reads the file unless a backend knows better
+    /// Reads check this first, so backends that can answer without reading refuse oversized
+    /// sidecars unread.
+    fn size(&self, path: &Path) -> Result<u64> {
+        Ok(self.read(path)?.len() as u64)
+    }
+}
+
+/// Size of the sidecar at `path` in `storage`, or on the filesystem when `None`
+pub fn sidecar_size(storage: Option<&dyn StorageBackend>, path: &Path) -> Result<u64> {
+    match storage {
+        Some(storage) => storage.size(path),
+        None => Ok(std::fs::metadata(path)?.len()),
+    }
+}
+
+/// Contents of the sidecar at `path` from `storage`, or the filesystem when `None`
+///
+/// Files over `max_size` bytes fail with `SidecarError::TooLarge`. The size is checked before
+/// reading, and on disk a file growing while it is read stops at the limit.
+pub fn read_sidecar(storage: Option<&dyn StorageBackend>, path: &Path, max_size: u64) -> Result<Vec<u8>> {
+    let too_large = |size| SidecarError::TooLarge { path: path.to_path_buf(), size, limit: max_size };
+    let bytes = match storage {
+        Some(storage) => {
+            let size = storage.size(path)?;
+            if size > max_size {
+                return Err(too_large(size));
+            }
+            storage.read(path)?
+        }
+        None => {
+            let file = std::fs::File::open(path)?;
+            let size = file.metadata()?.len();
+            if size > max_size {
+                return Err(too_large(size));
+            }
+            let mut bytes = Vec::with_capacity(size as usize);
+            file.take(max_size + 1).read_to_end(&mut bytes)?;
+            bytes
+        }
+    };
+    if bytes.len() as u64 > max_size {
+        return Err(too_large(bytes.len() as u64));
+    }
+    Ok(bytes)
+}
@@ -0,0 +1,317 @@
+/*
+ * Context: TFRecord export of detection sidecars as tf.Example records
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: crc32c, image, serde_json
+ */
+
+use crate::geometry::{detections_in, BBox};
+use crate::sidecar::types::{Result, SidecarError};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// What a TFRecord export includes and how it is split
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TfRecordOptions {
+    /// Number of output files; samples are dealt to them in turn
+    pub shards: usize,
+    /// Embed the encoded image bytes instead of only its path
+    pub include_images: bool,
+    /// Export only detections of these operations; empty exports every operation with a detection list
+    pub operations: Vec<String>,
+}
+
+impl Default for TfRecordOptions {
+    fn default() -> Self {
+        Self { shards: 1, include_images: false, operations: Vec::new() }
+    }
+}
+
+impl TfRecordOptions {
+    pub fn shards(mut self, shards: usize) -> Self {
+        self.shards = shards.max(1);
+        self
+    }
+
+    pub fn include_images(mut self, include: bool) -> Self {
+        self.include_images = include;
+        self
+    }
+
+    pub fn operations<I, S>(mut self, operations: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.operations = operations.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+/// What a TFRecord export wrote
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TfRecordSummary {
+    /// One tf.Example per image
+    pub examples: usize,
+    pub detections: usize,
+    /// Detections left out because their box could not be read or lies outside the image
+    pub skipped_detections: usize,
+    /// Images whose dimensions could not be read; they have no example
+    pub unreadable_images: Vec<PathBuf>,
+    pub shards: Vec<PathBuf>,
+    /// `label_map.pbtxt`-style file mapping class names to the ids in `image/object/class/label`
+    pub label_map: PathBuf,
+}
+
+/// One detection as found in a sidecar, before its box is normalized
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TfObject {
+    pub class: String,
+    pub confidence: f32,
+    pub bbox: Value,
+}
+
+/// Every detection with a bbox in a sidecar's payloads, optionally only those of `operations`
+///
+/// The class comes from `class_name`, `class`, `label` or `name`, falling back to the
+/// operation's name; the confidence from `confidence` or `score`, falling back to 1.0.
+pub fn detection_objects(data: &Value, operations: &[String]) -> Vec<TfObject> {
+    let Some(payloads) = data.as_object() else {
+        return Vec::new();
+    };
+    payloads
+        .iter()
+        .filter(|(key, _)| *key != "sidecar_info" && (operations.is_empty() || operations.contains(key)))
+        .filter_map(|(operation, payload)| detections_in(payload).map(|detections| (operation, detections)))
+        .flat_map(|(operation, detections)| {
+            detections.iter().filter_map(move |detection| {
+                let bbox = detection.get("bbox")?.clone();
+                let class = ["class_name", "class", "label", "name"]
+                    .iter()
+                    .find_map(|key| match detection.get(*key)? {
+                        Value::String(s) => Some(s.clone()),
+                        Value::Number(n) => Some(n.to_string()),
+                        _ => None,
+                    })
+                    .unwrap_or_else(|| operation.clone());
+                let confidence = ["confidence", "score"]
+                    .iter()
+                    .find_map(|key| detection.get(*key)?.as_f64())
+                    .unwrap_or(1.0) as f32;
+                Some(TfObject { class, confidence, bbox })
+            })
+        })
+        .collect()
+}
+
+/// File names of `shards` shards of `output`: the path itself for one shard,
+/// `output-00000-of-00004` style otherwise
+pub fn shard_paths(output: &Path, shards: usize) -> Vec<PathBuf> {
+    if shards <= 1 {
+        return vec![output.to_path_buf()];
+    }
+    (0..shards)
+        .map(|index| {
+            let mut name = output.as_os_str().to_os_string();
+            name.push(format!("-{:05}-of-{:05}", index, shards));
+            PathBuf::from(name)
+        })
+        .collect()
+}
+
+/// A tf.train.Feature value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Feature {
+    Bytes(Vec<Vec<u8>>),
+    Float(Vec<f32>),
+    Int64(Vec<i64>),
+}
+
+/// A tf.train.Example: named feature lists, encoded as protobuf by `encode`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TfExample {
+    pub features: BTreeMap<String, Feature>,
+}
+
+impl TfExample {
+    pub fn insert(&mut self, name: &str, feature: Feature) {
+        self.features.insert(name.to_string(), feature);
+    }
+
+    /// Object-detection example in the TensorFlow Object Detection API layout
+    ///
+    /// Boxes are stored as normalized corners in `image/object/bbox/{xmin,ymin,xmax,ymax}`,
+    /// `image/object/class/text` and `/label` hold class names and label-map ids, and
+    /// `image/object/score` the confidences. Objects whose box cannot be read or falls
+    /// outside the image are left out; the number kept is returned with the example.
+    pub fn detection(
+        image_path: &Path,
+        (width, height): (u32, u32),
+        objects: &[TfObject],
+        labels: &BTreeMap<String, i64>,
+        encoded: Option<Vec<u8>>,
+    ) -> (Self, usize) {
+        let mut corners: [Vec<f32>; 4] = Default::default();
+        let (mut classes, mut ids, mut scores) = (Vec::new(), Vec::new(), Vec::new());
+        for object in objects {
+            let Some(bbox) = BBox::parse(&object.bbox, width, height).filter(|bbox| bbox.validate(width, height).is_ok()) else {
+                continue;
+            };
+            let (w, h) = (f64::from(width), f64::from(height));
+            let normalized = [bbox.x1 / w, bbox.y1 / h, bbox.x2 / w, bbox.y2 / h];
+            for (list, value) in corners.iter_mut().zip(normalized) {
+                list.push(value.clamp(0.0, 1.0) as f32);
+            }
+            classes.push(object.class.as_bytes().to_vec());
+            ids.push(labels.get(&object.class).copied().unwrap_or(0));
+            scores.push(object.confidence);
+        }
+        let kept = classes.len();
+
+        let mut example = Self::default();
+        let filename = crate::utils::paths::encode_path(image_path).into_owned();
+        let format = image_path.extension().unwrap_or_default().to_string_lossy().to_lowercase();
+        example.insert("image/filename", Feature::Bytes(vec![filename.clone().into_bytes()]));
+        example.insert("image/source_id", Feature::Bytes(vec![filename.into_bytes()]));
+        example.insert("image/format", Feature::Bytes(vec![if format == "jpg" { "jpeg".into() } else { format.into_bytes() }]));
+        example.insert("image/width", Feature::Int64(vec![i64::from(width)]));
+        example.insert("image/height", Feature::Int64(vec![i64::from(height)]));
+        if let Some(encoded) = encoded {
+            example.insert("image/encoded", Feature::Bytes(vec![encoded]));
+        }
+        let [xmin, ymin, xmax, ymax] = corners;
+        example.insert("image/object/bbox/xmin", Feature::Float(xmin));
+        example.insert("image/object/bbox/ymin", Feature::Float(ymin));
+        example.insert("image/object/bbox/xmax", Feature::Float(xmax));
+        example.insert("image/object/bbox/ymax", Feature::Float(ymax));
+        example.insert("image/object/class/text", Feature::Bytes(classes));
+        example.insert("image/object/class/label", Feature::Int64(ids));
+        example.insert("image/object/score", Feature::Float(scores));
+        (example, kept)
+    }
+
+    /// Protobuf wire encoding of `Example { Features features = 1 }`
+    pub fn encode(&self) -> Vec<u8> {
+        let mut features = Vec::new();
+        for (name, feature) in &self.features {
+            let mut list = Vec::new();
+            let kind = match feature {
+                Feature::Bytes(values) => {
+                    values.iter().for_each(|value| put_bytes(&mut list, 1, value));
+                    1
+                }
+                Feature::Float(values) => {
+                    let packed: Vec<u8> = values.iter().flat_map(|value| value.to_le_bytes()).collect();
+                    put_bytes(&mut list, 1, &packed);
+                    2
+                }
+                Feature::Int64(values) => {
+                    let mut packed = Vec::new();
+                    values.iter().for_each(|value| put_varint(&mut packed, *value as u64));
+                    put_bytes(&mut list, 1, &packed);
+                    3
+                }
+            };
+            let mut value = Vec::new();
+            put_bytes(&mut value, kind, &list);
+            let mut entry = Vec::new();
+            put_bytes(&mut entry, 1, name.as_bytes());
+            put_bytes(&mut entry, 2, &value);
+            put_bytes(&mut features, 1, &entry);
+        }
+        let mut example = Vec::new();
+        put_bytes(&mut example, 1, &features);
+        example
+    }
+}
+
+fn put_varint(buffer: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buffer.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+/// A length-delimited field
+fn put_bytes(buffer: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    put_varint(buffer, (field << 3) | 2);
+    put_varint(buffer, bytes.len() as u64);
+    buffer.extend_from_slice(bytes);
+}
+
+fn masked_crc(bytes: &[u8]) -> u32 {
+    let crc = crc32c::crc32c(bytes);
+    crc.rotate_right(15).wrapping_add(0xa282_ead8)
+}
+
+/// Writes records in TFRecord framing: length, masked CRC-32C of the length, data, masked CRC-32C of the data
+pub struct TfRecordWriter {
+    writer: BufWriter<File>,
+}
+
+impl TfRecordWriter {
+    pub fn create(path: &Path) -> Result<Self> {
+        Ok(Self { writer: BufWriter::new(File::create(path)?) })
+    }
+
+    pub fn write(&mut self, record: &[u8]) -> Result<()> {
+        let length = (record.len() as u64).to_le_bytes();
+        self.writer.write_all(&length)?;
+        self.writer.write_all(&masked_crc(&length).to_le_bytes())?;
+        self.writer.write_all(record)?;
+        self.writer.write_all(&masked_crc(record).to_le_bytes())?;
+        Ok(())
+    }
+
+    /// Flush and sync the file to disk
+    pub fn finish(self) -> Result<()> {
+        let file = self.writer.into_inner().map_err(|e| e.into_error())?;
+        file.sync_all()?;
+        Ok(())
+    }
+}
+
+/// Read every record of a TFRecord file, checking both checksums of each
+pub fn read_records(path: &Path) -> Result<Vec<Vec<u8>>> {
+    let corrupt = |what: &str| SidecarError::ValidationFailed(format!("{}: {}", path.display(), what));
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut records = Vec::new();
+    loop {
+        let mut header = [0u8; 12];
+        match reader.read_exact(&mut header[..8]) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(records),
+            Err(e) => return Err(e.into()),
+        }
+        reader.read_exact(&mut header[8..])?;
+        let (length, crc) = header.split_at(8);
+        if masked_crc(length).to_le_bytes() != crc {
+            return Err(corrupt("length checksum mismatch"));
+        }
+        let length = u64::from_le_bytes(length.try_into().expect("eight bytes"));
+        let mut record = vec![0u8; usize::try_from(length).map_err(|_| corrupt("record too large"))?];
+        reader.read_exact(&mut record)?;
+        let mut crc = [0u8; 4];
+        reader.read_exact(&mut crc)?;
+        if masked_crc(&record).to_le_bytes() != crc {
+            return Err(corrupt("data checksum mismatch"));
+        }
+        records.push(record);
+    }
+}
+
+/// Label map in the `label_map.pbtxt` text format of the Object Detection API
+pub fn label_map_text(labels: &BTreeMap<String, i64>) -> String {
+    let mut items: Vec<(&String, &i64)> = labels.iter().collect();
+    items.sort_by_key(|(_, id)| **id);
+    items
+        .into_iter()
+        .map(|(name, id)| format!("item {{\n  id: {}\n  name: {}\n}}\n", id, serde_json::to_string(name).unwrap_or_default()))
+        .collect()
+}
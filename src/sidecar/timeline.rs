@@ -0,0 +1,450 @@
+/*
+ * Context: Frame-sequence analytics over game footage named by frame index or timestamp
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: chrono, regex, serde
+ *
+ * Each directory is one sequence. Frames are ordered by the timestamp and frame index read
+ * from their names, then bucketed into fixed intervals with their detection rates, and
+ * checked for gaps: frames missing from the numbering, stretches without frames, and runs
+ * of frames without a sidecar.
+ */
+
+use crate::geometry::{detections_in, sidecar_payloads};
+use crate::sidecar::types::{Result, SidecarError};
+use chrono::{DateTime, NaiveDateTime, TimeDelta, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Default `--time-format` for the `time` group of a custom frame pattern
+pub const DEFAULT_TIME_FORMAT: &str = "%Y%m%d_%H%M%S";
+
+/// Where a frame sits in its sequence, read from its file name
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrameKey {
+    pub index: Option<u64>,
+    pub time: Option<DateTime<Utc>>,
+}
+
+/// How frame indices and timestamps are read from image file stems
+///
+/// The built-in naming finds a `YYYYMMDD_HHMMSS[.fraction]` timestamp (the separator may
+/// also be `T`, `-` or nothing) and takes the last run of digits after it as the frame
+/// index, so `cam1_20241219_103000_000123` is frame 123 at 10:30:00. A custom pattern is a
+/// regex with named groups `index` and/or `time`, the latter parsed with a chrono format.
+#[derive(Debug, Clone)]
+pub struct FrameNaming {
+    pattern: Regex,
+    custom: bool,
+    time_format: String,
+}
+
+impl Default for FrameNaming {
+    fn default() -> Self {
+        let pattern = Regex::new(r"(?P<time>\d{8}[_T-]?\d{6})(?:\.(?P<fraction>\d{1,9}))?").expect("built-in frame pattern is valid");
+        Self { pattern, custom: false, time_format: "%Y%m%d%H%M%S".to_string() }
+    }
+}
+
+impl FrameNaming {
+    /// A regex with named groups `index` and/or `time`; `time_format` reads the `time` group
+    pub fn pattern(pattern: &str, time_format: Option<&str>) -> Result<Self> {
+        let invalid = |reason: String| SidecarError::ProcessingError(format!("Invalid frame pattern '{}': {}", pattern, reason));
+        let regex = Regex::new(pattern).map_err(|e| invalid(e.to_string()))?;
+        let groups: Vec<&str> = regex.capture_names().flatten().collect();
+        if !groups.contains(&"index") && !groups.contains(&"time") {
+            return Err(invalid("needs a named group `index` or `time`".to_string()));
+        }
+        Ok(Self { pattern: regex, custom: true, time_format: time_format.unwrap_or(DEFAULT_TIME_FORMAT).to_string() })
+    }
+
+    /// The index and timestamp in `path`'s file stem, if either can be read
+    pub fn parse(&self, path: &Path) -> Option<FrameKey> {
+        let stem = path.file_stem()?.to_str()?;
+        let key = if self.custom { self.parse_custom(stem) } else { self.parse_builtin(stem) };
+        (key.index.is_some() || key.time.is_some()).then_some(key)
+    }
+
+    fn parse_custom(&self, stem: &str) -> FrameKey {
+        let Some(captures) = self.pattern.captures(stem) else {
+            return FrameKey::default();
+        };
+        FrameKey {
+            index: captures.name("index").and_then(|index| index.as_str().parse().ok()),
+            time: captures
+                .name("time")
+                .and_then(|time| NaiveDateTime::parse_from_str(time.as_str(), &self.time_format).ok())
+                .map(|time| time.and_utc()),
+        }
+    }
+
+    fn parse_builtin(&self, stem: &str) -> FrameKey {
+        let (time, rest) = match self.pattern.captures(stem) {
+            Some(captures) => {
+                let digits: String = captures["time"].chars().filter(char::is_ascii_digit).collect();
+                let time = NaiveDateTime::parse_from_str(&digits, &self.time_format).ok().map(|time| {
+                    let nanos = captures.name("fraction").map_or(0, |fraction| {
+                        let digits = fraction.as_str();
+                        digits.parse::<i64>().unwrap_or(0) * 10_i64.pow(9 - digits.len() as u32)
+                    });
+                    time.and_utc() + TimeDelta::nanoseconds(nanos)
+                });
+                // Digits that only look like a timestamp are left for the index
+                let rest = if time.is_some() { &stem[captures.get(0).map_or(0, |whole| whole.end())..] } else { stem };
+                (time, rest)
+            }
+            None => (None, stem),
+        };
+        let index = rest
+            .rsplit(|c: char| !c.is_ascii_digit())
+            .find(|digits| !digits.is_empty())
+            .and_then(|digits| digits.parse().ok());
+        FrameKey { index, time }
+    }
+}
+
+/// Bucket width of a timeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineInterval {
+    /// Seconds of recorded time; sequences without timestamps get no buckets
+    Seconds(u64),
+    /// Frames, counted by index where every frame has one and by position otherwise
+    Frames(u64),
+}
+
+impl Default for TimelineInterval {
+    fn default() -> Self {
+        TimelineInterval::Seconds(60)
+    }
+}
+
+impl TimelineInterval {
+    /// Parse `30s`, `5m`, `1h` or a bare frame count such as `250`
+    pub fn parse(text: &str) -> Result<Self> {
+        let invalid = || SidecarError::ProcessingError(format!("Invalid interval '{}': expected 30s, 5m, 1h or a frame count", text));
+        let text = text.trim();
+        let split = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+        let (number, unit) = text.split_at(split);
+        let number: u64 = number.parse().ok().filter(|number| *number > 0).ok_or_else(invalid)?;
+        match unit {
+            "" | "f" => Ok(TimelineInterval::Frames(number)),
+            "s" => Ok(TimelineInterval::Seconds(number)),
+            "m" => number.checked_mul(60).map(TimelineInterval::Seconds).ok_or_else(invalid),
+            "h" => number.checked_mul(3600).map(TimelineInterval::Seconds).ok_or_else(invalid),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// What a timeline covers
+#[derive(Debug, Clone, Default)]
+pub struct TimelineOptions {
+    pub naming: FrameNaming,
+    pub interval: TimelineInterval,
+    /// Only count this operation's detections and sidecars; otherwise any sidecar counts
+    pub operation: Option<String>,
+    /// Longest stretch between timestamped frames before it is a gap; by default three times
+    /// the sequence's median frame spacing. Only used for sequences without frame indices.
+    pub max_gap: Option<f64>,
+    /// Only frames recorded at or after this time
+    pub from: Option<DateTime<Utc>>,
+    /// Only frames recorded before this time
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl TimelineOptions {
+    pub fn naming(mut self, naming: FrameNaming) -> Self {
+        self.naming = naming;
+        self
+    }
+
+    pub fn interval(mut self, interval: TimelineInterval) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn operation(mut self, operation: impl Into<String>) -> Self {
+        self.operation = Some(operation.into());
+        self
+    }
+
+    pub fn max_gap(mut self, seconds: f64) -> Self {
+        self.max_gap = Some(seconds);
+        self
+    }
+
+    pub fn window(mut self, from: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> Self {
+        self.from = from;
+        self.until = until;
+        self
+    }
+}
+
+/// One image of a sequence with what its sidecars hold
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub image_path: PathBuf,
+    pub key: FrameKey,
+    /// Whether a sidecar (holding the operation, if one was asked for) exists
+    pub has_sidecar: bool,
+    pub detections: usize,
+}
+
+/// Frames of one interval
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineBucket {
+    /// Start of the interval, for time intervals
+    pub start_time: Option<DateTime<Utc>>,
+    /// First frame index (or position) of the interval, for frame intervals
+    pub start_frame: Option<u64>,
+    pub frames: usize,
+    pub frames_with_sidecars: usize,
+    pub frames_with_detections: usize,
+    pub detections: usize,
+    /// Detections per frame
+    pub detection_rate: f64,
+}
+
+/// What is missing at a gap
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GapKind {
+    /// Frame indices skipped between two frames
+    MissingFrames,
+    /// A longer stretch between two timestamped frames than the sequence's spacing allows
+    TimeGap,
+    /// A run of frames without a sidecar
+    MissingSidecars,
+}
+
+/// One gap in a sequence
+///
+/// For missing frames and time gaps, `first` and `last` are the frames on either side; for
+/// missing sidecars they are the first and last frame of the run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Gap {
+    pub kind: GapKind,
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub first: PathBuf,
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub last: PathBuf,
+    /// Frames missing, or frames in the run without a sidecar; 0 for time gaps
+    pub frames: u64,
+    /// Recorded time between `first` and `last`, when both are timestamped
+    pub seconds: Option<f64>,
+}
+
+/// One directory's frames, in order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sequence {
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub directory: PathBuf,
+    pub frames: usize,
+    pub first: FrameKey,
+    pub last: FrameKey,
+    /// Most common difference between consecutive frame indices
+    pub index_step: Option<u64>,
+    pub buckets: Vec<TimelineBucket>,
+    pub gaps: Vec<Gap>,
+}
+
+/// Sequences under a directory, ready to plot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Timeline {
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub directory: PathBuf,
+    pub interval: TimelineInterval,
+    /// Sorted by directory
+    pub sequences: Vec<Sequence>,
+    /// Images whose names hold neither a frame index nor a timestamp
+    #[serde(with = "crate::utils::paths::lossless_vec")]
+    pub unparsed: Vec<PathBuf>,
+}
+
+impl Timeline {
+    /// Order `frames` into one sequence per directory and analyse each
+    pub fn compute(directory: PathBuf, frames: Vec<Frame>, unparsed: Vec<PathBuf>, options: &TimelineOptions) -> Self {
+        let mut directories: BTreeMap<PathBuf, Vec<Frame>> = BTreeMap::new();
+        let in_window = |frame: &Frame| match frame.key.time {
+            Some(time) => options.from.is_none_or(|from| time >= from) && options.until.is_none_or(|until| time < until),
+            None => options.from.is_none() && options.until.is_none(),
+        };
+        for frame in frames.into_iter().filter(in_window) {
+            let parent = frame.image_path.parent().map(Path::to_path_buf).unwrap_or_default();
+            directories.entry(parent).or_default().push(frame);
+        }
+        let mut unparsed = unparsed;
+        unparsed.sort();
+        let sequences = directories.into_iter().map(|(directory, frames)| Sequence::compute(directory, frames, options)).collect();
+        Self { directory, interval: options.interval, sequences, unparsed }
+    }
+}
+
+impl Sequence {
+    fn compute(directory: PathBuf, mut frames: Vec<Frame>, options: &TimelineOptions) -> Self {
+        let timed = frames.iter().all(|frame| frame.key.time.is_some());
+        let indexed = frames.iter().all(|frame| frame.key.index.is_some());
+        if timed {
+            frames.sort_by(|a, b| (a.key.time, a.key.index, &a.image_path).cmp(&(b.key.time, b.key.index, &b.image_path)));
+        } else {
+            frames.sort_by(|a, b| (a.key.index, a.key.time, &a.image_path).cmp(&(b.key.index, b.key.time, &b.image_path)));
+        }
+        let index_step = indexed.then(|| most_common_step(&frames)).flatten();
+        let mut gaps = Vec::new();
+        match index_step {
+            Some(step) => gaps.extend(missing_frames(&frames, step)),
+            None if timed => gaps.extend(time_gaps(&frames, options.max_gap)),
+            None => {}
+        }
+        gaps.extend(missing_sidecars(&frames));
+        Self {
+            directory,
+            frames: frames.len(),
+            first: frames.first().map(|frame| frame.key).unwrap_or_default(),
+            last: frames.last().map(|frame| frame.key).unwrap_or_default(),
+            index_step,
+            buckets: buckets(&frames, options.interval, timed, indexed),
+            gaps,
+        }
+    }
+}
+
+/// Most common positive difference between consecutive frame indices, smallest on a tie
+fn most_common_step(frames: &[Frame]) -> Option<u64> {
+    let mut steps: BTreeMap<u64, usize> = BTreeMap::new();
+    for pair in frames.windows(2) {
+        if let (Some(a), Some(b)) = (pair[0].key.index, pair[1].key.index) {
+            if b > a {
+                *steps.entry(b - a).or_insert(0) += 1;
+            }
+        }
+    }
+    steps.into_iter().max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0))).map(|(step, _)| step)
+}
+
+/// Detections one sidecar records, or `None` if it does not hold `operation`
+///
+/// Without an operation every payload counts. A payload without a detection list counts its
+/// `count` field. `recorded_operation` is the operation the sidecar was classified as; a raw
+/// detector file of that operation is its own payload.
+pub fn sidecar_detections(data: &Value, recorded_operation: &str, operation: Option<&str>) -> Option<usize> {
+    let count = |payload: &Value| match detections_in(payload) {
+        Some(detections) => detections.len(),
+        None => payload.get("count").and_then(Value::as_u64).unwrap_or(0) as usize,
+    };
+    match operation {
+        Some(operation) => match data.get(operation) {
+            Some(payload) => Some(count(payload)),
+            None => (recorded_operation == operation).then(|| count(data)),
+        },
+        None => Some(
+            sidecar_payloads(data, recorded_operation)
+                .filter(|(_, payload)| payload.is_object())
+                .map(|(_, payload)| count(payload))
+                .sum(),
+        ),
+    }
+}
+
+fn seconds_between(a: &Frame, b: &Frame) -> Option<f64> {
+    Some((b.key.time? - a.key.time?).num_milliseconds() as f64 / 1000.0)
+}
+
+fn missing_frames(frames: &[Frame], step: u64) -> Vec<Gap> {
+    frames
+        .windows(2)
+        .filter_map(|pair| {
+            let delta = pair[1].key.index?.checked_sub(pair[0].key.index?)?;
+            (delta > step).then(|| Gap {
+                kind: GapKind::MissingFrames,
+                first: pair[0].image_path.clone(),
+                last: pair[1].image_path.clone(),
+                frames: delta.div_ceil(step) - 1,
+                seconds: seconds_between(&pair[0], &pair[1]),
+            })
+        })
+        .collect()
+}
+
+fn time_gaps(frames: &[Frame], max_gap: Option<f64>) -> Vec<Gap> {
+    let mut spacings: Vec<f64> = frames.windows(2).filter_map(|pair| seconds_between(&pair[0], &pair[1])).filter(|s| *s > 0.0).collect();
+    spacings.sort_by(f64::total_cmp);
+    let Some(max_gap) = max_gap.or_else(|| spacings.get(spacings.len() / 2).map(|median| median * 3.0)) else {
+        return Vec::new();
+    };
+    frames
+        .windows(2)
+        .filter_map(|pair| {
+            let seconds = seconds_between(&pair[0], &pair[1])?;
+            (seconds > max_gap).then(|| Gap {
+                kind: GapKind::TimeGap,
+                first: pair[0].image_path.clone(),
+                last: pair[1].image_path.clone(),
+                frames: 0,
+                seconds: Some(seconds),
+            })
+        })
+        .collect()
+}
+
+fn missing_sidecars(frames: &[Frame]) -> Vec<Gap> {
+    frames
+        .chunk_by(|a, b| a.has_sidecar == b.has_sidecar)
+        .filter(|run| !run[0].has_sidecar)
+        .map(|run| {
+            let (first, last) = (&run[0], &run[run.len() - 1]);
+            Gap {
+                kind: GapKind::MissingSidecars,
+                first: first.image_path.clone(),
+                last: last.image_path.clone(),
+                frames: run.len() as u64,
+                seconds: seconds_between(first, last),
+            }
+        })
+        .collect()
+}
+
+fn buckets(frames: &[Frame], interval: TimelineInterval, timed: bool, indexed: bool) -> Vec<TimelineBucket> {
+    let mut buckets: BTreeMap<i64, TimelineBucket> = BTreeMap::new();
+    for (position, frame) in frames.iter().enumerate() {
+        let (slot, start_time, start_frame) = match interval {
+            TimelineInterval::Seconds(seconds) => {
+                let Some(time) = frame.key.time.filter(|_| timed) else {
+                    return Vec::new();
+                };
+                let slot = time.timestamp().div_euclid(seconds as i64);
+                (slot, DateTime::from_timestamp(slot * seconds as i64, 0), None)
+            }
+            TimelineInterval::Frames(count) => {
+                let index = if indexed { frame.key.index.unwrap_or_default() } else { position as u64 };
+                let slot = index / count;
+                (slot as i64, None, Some(slot * count))
+            }
+        };
+        let bucket = buckets.entry(slot).or_insert_with(|| TimelineBucket {
+            start_time,
+            start_frame,
+            frames: 0,
+            frames_with_sidecars: 0,
+            frames_with_detections: 0,
+            detections: 0,
+            detection_rate: 0.0,
+        });
+        bucket.frames += 1;
+        bucket.frames_with_sidecars += usize::from(frame.has_sidecar);
+        bucket.frames_with_detections += usize::from(frame.detections > 0);
+        bucket.detections += frame.detections;
+    }
+    buckets
+        .into_values()
+        .map(|mut bucket| {
+            bucket.detection_rate = bucket.detections as f64 / bucket.frames as f64;
+            bucket
+        })
+        .collect()
+}
@@ -0,0 +1,171 @@
+/*
+ * Context: Multi-frame object tracks gathered from per-frame tracking sidecars
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde, serde_json
+ *
+ * A tracker writes one sidecar per frame whose `tracking` payload lists the boxes seen in
+ * that frame under `tracks`, each with the `track_id` it keeps across frames. Detections
+ * in other payloads that carry a `track_id` are tracked boxes too. Grouping them by id
+ * over a directory gives every frame and box of each track, ordered by frame.
+ */
+
+use crate::geometry::{detection_class, detections_in, sidecar_payloads, NESTED_KEYS};
+use crate::query::thresholds::confidence;
+use crate::sidecar::timeline::{FrameKey, FrameNaming};
+use crate::sidecar::types::SidecarInfo;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Key a tracking payload lists its tracked boxes under
+pub const TRACKS_KEY: &str = "tracks";
+
+/// One box of a track, in one frame
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrackPoint {
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub image_path: PathBuf,
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub sidecar_path: PathBuf,
+    pub operation: String,
+    /// Read from the image's name; `None` when the name holds no frame index or timestamp
+    pub frame: Option<FrameKey>,
+    pub class: Option<String>,
+    pub confidence: Option<f64>,
+    pub bbox: Option<Value>,
+}
+
+/// Every box recorded for one track id, in frame order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Track {
+    pub track_id: String,
+    pub points: Vec<TrackPoint>,
+}
+
+/// What `tracks list` reports for a track
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrackSummary {
+    pub track_id: String,
+    /// The class most of its boxes carry
+    pub class: Option<String>,
+    pub frames: usize,
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub first_image: PathBuf,
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub last_image: PathBuf,
+    pub first_frame: Option<FrameKey>,
+    pub last_frame: Option<FrameKey>,
+    pub mean_confidence: Option<f64>,
+}
+
+impl Track {
+    pub fn summary(&self) -> TrackSummary {
+        let mut classes: BTreeMap<&str, usize> = BTreeMap::new();
+        for class in self.points.iter().filter_map(|point| point.class.as_deref()) {
+            *classes.entry(class).or_insert(0) += 1;
+        }
+        let class = classes
+            .into_iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+            .map(|(class, _)| class.to_string());
+        let confidences: Vec<f64> = self.points.iter().filter_map(|point| point.confidence).collect();
+        let mean_confidence = (!confidences.is_empty()).then(|| confidences.iter().sum::<f64>() / confidences.len() as f64);
+        let (first, last) = (self.points.first(), self.points.last());
+        TrackSummary {
+            track_id: self.track_id.clone(),
+            class,
+            frames: self.points.len(),
+            first_image: first.map(|point| point.image_path.clone()).unwrap_or_default(),
+            last_image: last.map(|point| point.image_path.clone()).unwrap_or_default(),
+            first_frame: first.and_then(|point| point.frame),
+            last_frame: last.and_then(|point| point.frame),
+            mean_confidence,
+        }
+    }
+}
+
+/// Track id of a tracked box, from `track_id`; numbers and strings are both accepted
+pub fn track_id(detection: &Value) -> Option<String> {
+    match detection.get("track_id")? {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Every tracked box a sidecar records, keyed by track id
+///
+/// The `tracking` payload's `tracks` (or detection list) is read, along with detections of
+/// any other payload that carry a `track_id`. A raw tracker file classified as `tracking`
+/// is its own payload.
+pub fn track_points(sidecar: &SidecarInfo, data: &Value, naming: &FrameNaming) -> Vec<(String, TrackPoint)> {
+    let frame = naming.parse(&sidecar.image_path);
+    sidecar_payloads(data, sidecar.operation.as_str())
+        .filter_map(|(operation, payload)| tracked_boxes(payload).map(|boxes| (operation, boxes)))
+        .flat_map(|(operation, boxes)| {
+            boxes.iter().filter_map(move |detection| {
+                let track_id = track_id(detection)?;
+                let point = TrackPoint {
+                    image_path: sidecar.image_path.clone(),
+                    sidecar_path: sidecar.sidecar_path.clone(),
+                    operation: operation.to_string(),
+                    frame,
+                    class: detection_class(detection),
+                    confidence: confidence(detection),
+                    bbox: detection.get("bbox").cloned(),
+                };
+                Some((track_id, point))
+            })
+        })
+        .collect()
+}
+
+fn tracked_boxes(payload: &Value) -> Option<&Vec<Value>> {
+    tracks_in(payload).or_else(|| detections_in(payload))
+}
+
+fn tracks_in(payload: &Value) -> Option<&Vec<Value>> {
+    payload
+        .get(TRACKS_KEY)
+        .and_then(Value::as_array)
+        .or_else(|| NESTED_KEYS.iter().find_map(|key| payload.get(*key).and_then(tracks_in)))
+}
+
+/// Group tracked boxes into tracks, ordered by id, each box ordered by frame
+///
+/// Numeric ids sort numerically ahead of the others. A track seen twice in one image keeps
+/// both boxes, ordered by sidecar.
+pub fn group_tracks(points: Vec<(String, TrackPoint)>) -> Vec<Track> {
+    let mut tracks: BTreeMap<String, Vec<TrackPoint>> = BTreeMap::new();
+    for (track_id, point) in points {
+        tracks.entry(track_id).or_default().push(point);
+    }
+    let mut tracks: Vec<Track> = tracks
+        .into_iter()
+        .map(|(track_id, mut points)| {
+            points.sort_by(|a, b| frame_order(a).cmp(&frame_order(b)));
+            Track { track_id, points }
+        })
+        .collect();
+    tracks.sort_by(|a, b| compare_ids(&a.track_id, &b.track_id));
+    tracks
+}
+
+fn frame_order(point: &TrackPoint) -> (Option<chrono::DateTime<chrono::Utc>>, Option<u64>, &PathBuf, &PathBuf) {
+    let frame = point.frame.unwrap_or_default();
+    (frame.time, frame.index, &point.image_path, &point.sidecar_path)
+}
+
+fn compare_ids(a: &str, b: &str) -> Ordering {
+    match (a.parse::<u64>(), b.parse::<u64>()) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => a.cmp(b),
+    }
+}
+
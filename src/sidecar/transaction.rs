@@ -0,0 +1,473 @@
+/*
+ * Context: Journaled transactions so related sidecar writes, renames and removals land together
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde, tokio
+ *
+ * A commit first writes the new contents beside their targets, then appends a commit marker
+ * to a journal kept in the transaction's directory, then applies each step and records it as
+ * done. The marker is the commit point: `recover` finishes every journal that has one and
+ * discards the staged files of every journal that does not.
+ *
+ * A manager with a `StorageBackend` keeps the staged files and the journal in the backend, which
+ * then has to make each whole-file write and rename durable itself.
+ */
+
+use crate::events::EventKind;
+use crate::sidecar::audit::Mutation;
+use crate::sidecar::manager::SidecarManager;
+use crate::sidecar::storage::StorageBackend;
+use crate::sidecar::types::{Result, SidecarError};
+use crate::utils::paths;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use walkdir::{DirEntry, WalkDir};
+
+/// File name prefix of the journals kept in a transaction's directory while it commits
+pub const JOURNAL_PREFIX: &str = ".sidecar-txn-";
+
+/// Extension of transaction journals
+pub const JOURNAL_EXTENSION: &str = "journal";
+
+/// One change of a transaction, as recorded in its journal
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionStep {
+    /// Replace `path` with the contents staged at `staged`
+    Write {
+        #[serde(with = "crate::utils::paths::lossless")]
+        path: PathBuf,
+        #[serde(with = "crate::utils::paths::lossless")]
+        staged: PathBuf,
+    },
+    Rename {
+        #[serde(with = "crate::utils::paths::lossless")]
+        from: PathBuf,
+        #[serde(with = "crate::utils::paths::lossless")]
+        to: PathBuf,
+    },
+    Remove {
+        #[serde(with = "crate::utils::paths::lossless")]
+        path: PathBuf,
+    },
+}
+
+/// What `recover` did with the journals it found
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecoverySummary {
+    /// Committed transactions whose remaining steps were applied
+    pub rolled_forward: usize,
+    /// Uncommitted transactions whose staged files were removed
+    pub rolled_back: usize,
+}
+
+/// A batch of sidecar writes, renames and removals that are applied together or not at all
+///
+/// Changes are only checked and buffered until `commit`. Start one with
+/// `SidecarManager::transaction`; the journal lives in the directory given there, and
+/// `SidecarManager::recover_transactions` on that directory completes or undoes a commit
+/// interrupted by a crash.
+pub struct SidecarTransaction<'a> {
+    manager: &'a SidecarManager,
+    directory: PathBuf,
+    changes: Vec<Change>,
+}
+
+enum Change {
+    Write { path: PathBuf, content_bytes: Vec<u8> },
+    Rename { from: PathBuf, to: PathBuf },
+    Remove { path: PathBuf },
+}
+
+impl<'a> SidecarTransaction<'a> {
+    pub(crate) fn new(manager: &'a SidecarManager, directory: &Path) -> Self {
+        Self { manager, directory: directory.to_path_buf(), changes: Vec::new() }
+    }
+
+    /// Write `data` to `sidecar_path`, in the format its extension names
+    pub fn write(&mut self, sidecar_path: impl Into<PathBuf>, data: &Value) -> Result<&mut Self> {
+        let path = sidecar_path.into();
+        let content_bytes = self.manager.encode(&path, data)?;
+        self.changes.push(Change::Write { path, content_bytes });
+        Ok(self)
+    }
+
+    /// Rename `from` to `to`, which must not exist by then
+    pub fn rename(&mut self, from: impl Into<PathBuf>, to: impl Into<PathBuf>) -> &mut Self {
+        self.changes.push(Change::Rename { from: from.into(), to: to.into() });
+        self
+    }
+
+    /// Remove the file at `path`, which must exist by then
+    pub fn remove(&mut self, path: impl Into<PathBuf>) -> &mut Self {
+        self.changes.push(Change::Remove { path: path.into() });
+        self
+    }
+
+    /// Number of changes buffered so far
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Apply every change in order, returning how many were applied
+    ///
+    /// Fails without touching any target if a rename source or removed file is missing, a
+    /// rename target exists, or staging fails. An error after the commit point leaves the
+    /// journal behind for `recover_transactions` to finish.
+    pub async fn commit(self) -> Result<usize> {
+        self.manager.ensure_writable("commit a transaction")?;
+        if self.changes.is_empty() {
+            return Ok(0);
+        }
+        let (directory, changes) = (self.directory, self.changes);
+        let storage = self.manager.storage_backend().cloned();
+        let applied = tokio::task::spawn_blocking(move || commit_changes(Files { storage: storage.as_deref() }, &directory, changes)).await??;
+        for (step, existed) in &applied {
+            match step {
+                TransactionStep::Write { path, .. } => {
+                    let kind = if *existed { EventKind::Updated } else { EventKind::Created };
+                    self.manager.notify(kind, None, path, None);
+                    self.manager.audit(Mutation::Write, path, None, None)?;
+                }
+                TransactionStep::Rename { from, to } => {
+                    self.manager.notify(EventKind::Deleted, None, from, None);
+                    self.manager.notify(EventKind::Created, None, to, None);
+                    self.manager.audit(Mutation::Move, from, Some(to), None)?;
+                }
+                TransactionStep::Remove { path } => {
+                    self.manager.notify(EventKind::Deleted, None, path, None);
+                    self.manager.audit(Mutation::Delete, path, None, None)?;
+                }
+            }
+        }
+        Ok(applied.len())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum JournalEntry {
+    Begin { id: String, steps: Vec<TransactionStep> },
+    Commit,
+    Done(usize),
+}
+
+/// Where a transaction's files live: `storage` when given, otherwise the filesystem
+#[derive(Clone, Copy)]
+struct Files<'a> {
+    storage: Option<&'a dyn StorageBackend>,
+}
+
+impl Files<'_> {
+    fn exists(&self, path: &Path) -> bool {
+        match self.storage {
+            Some(storage) => storage.exists(path),
+            None => path.symlink_metadata().is_ok(),
+        }
+    }
+
+    fn read(&self, path: &Path) -> Result<Vec<u8>> {
+        match self.storage {
+            Some(storage) => storage.read(path),
+            None => Ok(std::fs::read(paths::long_path(path))?),
+        }
+    }
+
+    /// Write a file that must not exist yet, synced before returning
+    fn write_new(&self, path: &Path, content_bytes: &[u8]) -> Result<()> {
+        match self.storage {
+            Some(storage) if storage.exists(path) => {
+                Err(std::io::Error::new(std::io::ErrorKind::AlreadyExists, format!("{} already exists", path.display())).into())
+            }
+            Some(storage) => storage.write(path, content_bytes),
+            None => {
+                create_parent(path)?;
+                let mut file = OpenOptions::new().write(true).create_new(true).open(paths::long_path(path))?;
+                file.write_all(content_bytes)?;
+                file.sync_all()?;
+                Ok(())
+            }
+        }
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<()> {
+        match self.storage {
+            Some(storage) => storage.rename(from, to),
+            None => {
+                create_parent(to)?;
+                Ok(std::fs::rename(paths::long_path(from), paths::long_path(to))?)
+            }
+        }
+    }
+
+    /// Remove the file at `path`; one that is already gone is not an error
+    fn remove(&self, path: &Path) -> Result<()> {
+        let removed = match self.storage {
+            Some(storage) => storage.remove(path),
+            None => std::fs::remove_file(paths::long_path(path)).map_err(SidecarError::from),
+        };
+        match removed {
+            Err(SidecarError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            removed => removed,
+        }
+    }
+
+    /// Journals in `directory` and its subdirectories
+    fn journals(&self, directory: &Path) -> Result<Vec<PathBuf>> {
+        let files = match self.storage {
+            Some(storage) => storage.list(directory)?,
+            None => WalkDir::new(directory)
+                .into_iter()
+                .filter(|entry| entry.as_ref().map_or(true, |entry| entry.file_type().is_file()))
+                .map(|entry| entry.map(DirEntry::into_path).map_err(std::io::Error::from))
+                .collect::<std::io::Result<_>>()?,
+        };
+        Ok(files.into_iter().filter(|path| is_journal(path)).collect())
+    }
+}
+
+struct Journal<'a> {
+    files: Files<'a>,
+    path: PathBuf,
+    /// The journal opened for appending on disk; a backend is given the whole `contents` instead
+    file: Option<File>,
+    contents: Vec<u8>,
+}
+
+impl<'a> Journal<'a> {
+    fn create(files: Files<'a>, path: PathBuf) -> Result<Self> {
+        let file = match files.storage {
+            Some(_) => {
+                files.write_new(&path, &[])?;
+                None
+            }
+            None => Some(OpenOptions::new().append(true).create_new(true).open(&path)?),
+        };
+        Ok(Self { files, path, file, contents: Vec::new() })
+    }
+
+    fn append(&mut self, entry: &JournalEntry) -> Result<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        match (&mut self.file, self.files.storage) {
+            (Some(file), _) => {
+                file.write_all(&line)?;
+                file.sync_data()?;
+            }
+            (None, storage) => {
+                self.contents.extend_from_slice(&line);
+                if let Some(storage) = storage {
+                    storage.write(&self.path, &self.contents)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn remove(self) -> Result<()> {
+        drop(self.file);
+        self.files.remove(&self.path)
+    }
+}
+
+/// Steps of a journal, whether it reached the commit point, and how many steps are done
+struct JournalState {
+    steps: Vec<TransactionStep>,
+    committed: bool,
+    done: usize,
+}
+
+fn new_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_nanos());
+    format!("{:x}-{:x}-{:x}", nanos, std::process::id(), COUNTER.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Check `changes` against the files as each earlier change leaves them
+///
+/// Returns, for each change, whether its target already existed.
+fn check(files: Files, changes: &[Change]) -> Result<Vec<bool>> {
+    let mut overlay: HashMap<&Path, bool> = HashMap::new();
+    let present = |overlay: &HashMap<&Path, bool>, path: &Path| overlay.get(path).copied().unwrap_or_else(|| files.exists(path));
+    let mut existed = Vec::with_capacity(changes.len());
+    for change in changes {
+        match change {
+            Change::Write { path, .. } => {
+                existed.push(present(&overlay, path));
+                overlay.insert(path, true);
+            }
+            Change::Rename { from, to } => {
+                if !present(&overlay, from) {
+                    return Err(SidecarError::ProcessingError(format!("Cannot rename {}: it does not exist", from.display())));
+                }
+                if present(&overlay, to) {
+                    return Err(SidecarError::ProcessingError(format!("Cannot rename to {}: it already exists", to.display())));
+                }
+                existed.push(true);
+                overlay.insert(from, false);
+                overlay.insert(to, true);
+            }
+            Change::Remove { path } => {
+                if !present(&overlay, path) {
+                    return Err(SidecarError::ProcessingError(format!("Cannot remove {}: it does not exist", path.display())));
+                }
+                existed.push(true);
+                overlay.insert(path, false);
+            }
+        }
+    }
+    Ok(existed)
+}
+
+fn create_parent(path: &Path) -> std::io::Result<()> {
+    match path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        Some(parent) => std::fs::create_dir_all(paths::long_path(parent)),
+        None => Ok(()),
+    }
+}
+
+/// Stage, journal and apply `changes`; blocking
+fn commit_changes(files: Files, directory: &Path, changes: Vec<Change>) -> Result<Vec<(TransactionStep, bool)>> {
+    let existed = check(files, &changes)?;
+    let id = new_id();
+    let steps: Vec<TransactionStep> = changes
+        .iter()
+        .map(|change| match change {
+            Change::Write { path, .. } => {
+                let mut staged = path.file_name().unwrap_or_default().to_os_string();
+                staged.push(format!(".txn-{}", id));
+                TransactionStep::Write { path: path.clone(), staged: path.with_file_name(staged) }
+            }
+            Change::Rename { from, to } => TransactionStep::Rename { from: from.clone(), to: to.clone() },
+            Change::Remove { path } => TransactionStep::Remove { path: path.clone() },
+        })
+        .collect();
+
+    let path = directory.join(format!("{}{}.{}", JOURNAL_PREFIX, id, JOURNAL_EXTENSION));
+    let mut journal = Journal::create(files, path)?;
+    let prepared = journal.append(&JournalEntry::Begin { id, steps: steps.clone() }).and_then(|_| {
+        for (change, step) in changes.iter().zip(&steps) {
+            if let (Change::Write { content_bytes, .. }, TransactionStep::Write { staged, .. }) = (change, step) {
+                files.write_new(staged, content_bytes)?;
+            }
+        }
+        journal.append(&JournalEntry::Commit)
+    });
+    if let Err(e) = prepared {
+        roll_back(files, &journal.path, &steps);
+        return Err(e);
+    }
+
+    roll_forward(&mut journal, &steps, 0).map_err(|e| {
+        SidecarError::ProcessingError(format!(
+            "Transaction committed but not fully applied ({}); recover transactions in {} to finish it",
+            e,
+            directory.display()
+        ))
+    })?;
+    journal.remove()?;
+    Ok(steps.into_iter().zip(existed).collect())
+}
+
+/// Apply one step; a step whose effect is already in place is skipped, so replaying is safe
+fn apply(files: Files, step: &TransactionStep) -> Result<()> {
+    match step {
+        TransactionStep::Write { path, staged } if files.exists(staged) => files.rename(staged, path),
+        TransactionStep::Rename { from, to } if files.exists(from) => files.rename(from, to),
+        TransactionStep::Remove { path } => files.remove(path),
+        _ => Ok(()),
+    }
+}
+
+fn roll_forward(journal: &mut Journal, steps: &[TransactionStep], done: usize) -> Result<()> {
+    for (index, step) in steps.iter().enumerate().skip(done) {
+        apply(journal.files, step)?;
+        journal.append(&JournalEntry::Done(index))?;
+    }
+    Ok(())
+}
+
+/// Remove the staged files of an uncommitted transaction, then its journal
+fn roll_back(files: Files, journal_path: &Path, steps: &[TransactionStep]) {
+    for step in steps {
+        if let TransactionStep::Write { staged, .. } = step {
+            let _ = files.remove(staged);
+        }
+    }
+    let _ = files.remove(journal_path);
+}
+
+fn is_journal(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == JOURNAL_EXTENSION)
+        && path.file_name().and_then(|name| name.to_str()).is_some_and(|name| name.starts_with(JOURNAL_PREFIX))
+}
+
+/// Open a journal left by an interrupted commit, dropping a last line cut short by a crash
+///
+/// The state is `None` if the journal never recorded its steps.
+fn open_journal(files: Files, path: PathBuf) -> Result<(Journal, Option<JournalState>)> {
+    let mut contents = files.read(&path)?;
+    let complete = contents.iter().rposition(|&byte| byte == b'\n').map_or(0, |newline| newline + 1);
+    let file = match files.storage {
+        Some(_) => None,
+        None => {
+            let file = OpenOptions::new().append(true).open(&path)?;
+            if complete < contents.len() {
+                file.set_len(complete as u64)?;
+            }
+            Some(file)
+        }
+    };
+    contents.truncate(complete);
+    let journal = Journal { files, path, file, contents: contents.clone() };
+    let mut lines = contents.split(|&byte| byte == b'\n').filter(|line| !line.is_empty());
+    let Some(Ok(JournalEntry::Begin { steps, .. })) = lines.next().map(serde_json::from_slice::<JournalEntry>) else {
+        return Ok((journal, None));
+    };
+    let mut state = JournalState { steps, committed: false, done: 0 };
+    for line in lines {
+        match serde_json::from_slice(line)? {
+            JournalEntry::Commit => state.committed = true,
+            JournalEntry::Done(index) => state.done = state.done.max(index + 1),
+            JournalEntry::Begin { .. } => {}
+        }
+    }
+    Ok((journal, Some(state)))
+}
+
+/// Finish every committed transaction journaled in `directory` or any directory below it, and
+/// undo every uncommitted one
+///
+/// Journals are looked for in `storage` when given, otherwise on the filesystem. Run it when
+/// nothing is committing a transaction in `directory`, e.g. before a batch starts; a commit in
+/// progress would be undone. Blocking.
+pub fn recover(storage: Option<&dyn StorageBackend>, directory: &Path) -> Result<RecoverySummary> {
+    let files = Files { storage };
+    let mut summary = RecoverySummary::default();
+    for path in files.journals(directory)? {
+        match open_journal(files, path)? {
+            (mut journal, Some(state)) if state.committed => {
+                roll_forward(&mut journal, &state.steps, state.done)?;
+                journal.remove()?;
+                summary.rolled_forward += 1;
+            }
+            (journal, state) => {
+                drop(journal.file);
+                roll_back(files, &journal.path, state.map(|state| state.steps).as_deref().unwrap_or_default());
+                summary.rolled_back += 1;
+            }
+        }
+    }
+    Ok(summary)
+}
@@ -0,0 +1,96 @@
+/*
+ * Context: Grouping sidecars of unknown operation by key signature, to help write classifier rules
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde, serde_json
+ */
+
+use crate::sidecar::classify::tool_name;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+/// Sample payloads kept per group when none is given
+pub const DEFAULT_SAMPLES: usize = 3;
+
+/// Array items kept in a sample payload; the rest are summarised by a count
+const SAMPLE_ARRAY_ITEMS: usize = 3;
+
+/// Sidecars of unknown operation sharing one set of top-level keys
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnknownGroup {
+    /// Sorted top-level keys, `sidecar_info` aside; empty for documents that are not objects
+    pub signature: Vec<String>,
+    pub count: usize,
+    /// Tool names the sidecars report, for `tool_name` rules
+    pub tool_names: BTreeSet<String>,
+    #[serde(with = "crate::utils::paths::lossless_vec")]
+    pub sidecars: Vec<PathBuf>,
+    /// The first sidecars' documents, with long arrays cut short
+    pub samples: Vec<Value>,
+}
+
+/// Every sidecar under a directory the classifier could not place, grouped by key signature
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TriageReport {
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub directory: PathBuf,
+    pub total_unknown: usize,
+    /// Largest group first
+    pub groups: Vec<UnknownGroup>,
+}
+
+impl TriageReport {
+    /// Group `unknown` sidecars and their documents, keeping up to `samples` documents per group
+    pub fn compute(directory: PathBuf, mut unknown: Vec<(PathBuf, Value)>, samples: usize) -> Self {
+        unknown.sort_by(|a, b| a.0.cmp(&b.0));
+        let total_unknown = unknown.len();
+        let mut groups: BTreeMap<Vec<String>, UnknownGroup> = BTreeMap::new();
+        for (sidecar_path, data) in unknown {
+            let signature = key_signature(&data);
+            let group = groups.entry(signature.clone()).or_insert_with(|| UnknownGroup {
+                signature,
+                count: 0,
+                tool_names: BTreeSet::new(),
+                sidecars: Vec::new(),
+                samples: Vec::new(),
+            });
+            group.count += 1;
+            group.tool_names.extend(tool_name(&data).map(str::to_string));
+            group.sidecars.push(sidecar_path);
+            if group.samples.len() < samples {
+                group.samples.push(sample_payload(&data));
+            }
+        }
+        let mut groups: Vec<UnknownGroup> = groups.into_values().collect();
+        groups.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.signature.cmp(&b.signature)));
+        Self { directory, total_unknown, groups }
+    }
+}
+
+/// Sorted top-level keys of `data`, `sidecar_info` aside
+pub fn key_signature(data: &Value) -> Vec<String> {
+    let Some(object) = data.as_object() else {
+        return Vec::new();
+    };
+    let mut keys: Vec<String> = object.keys().filter(|key| *key != "sidecar_info").cloned().collect();
+    keys.sort();
+    keys
+}
+
+/// `data` with every array cut to its first items, followed by `"... N more"`
+pub fn sample_payload(data: &Value) -> Value {
+    match data {
+        Value::Array(items) => {
+            let mut sample: Vec<Value> = items.iter().take(SAMPLE_ARRAY_ITEMS).map(sample_payload).collect();
+            if items.len() > SAMPLE_ARRAY_ITEMS {
+                sample.push(Value::String(format!("... {} more", items.len() - SAMPLE_ARRAY_ITEMS)));
+            }
+            Value::Array(sample)
+        }
+        Value::Object(object) => Value::Object(object.iter().map(|(key, value)| (key.clone(), sample_payload(value))).collect()),
+        other => other.clone(),
+    }
+}
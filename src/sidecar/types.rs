@@ -1,4 +1,4 @@
-/**
+/*
  * This code written by Claude Sonnet 4 (claude-3-5-sonnet-20241022)
  * Generated via Cursor IDE (cursor.sh) with AI assistance
  * Model: Anthropic Claude 3.5 Sonnet
@@ -14,11 +14,20 @@
  */
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::PathBuf;
-use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use chrono::{DateTime, Timelike, Utc};
 use uuid::Uuid;
 
+use super::formats::{SerializationError, SidecarFormat};
+use crate::utils::json::JsonUtils;
+use crate::geometry::{detection_class, detections_in};
+use crate::sidecar::manifest::relative_path;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::sidecar::payload::{LazyPayload, PayloadSource};
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum OperationType {
     FaceDetection,
@@ -27,6 +36,8 @@ pub enum OperationType {
     QualityAssessment,
     GameDetection,
     Yolov8,
+    ImageHash,
+    Tracking,
     Unified,
     Unknown,
 }
@@ -40,11 +51,14 @@ impl OperationType {
             OperationType::QualityAssessment => "quality_assessment",
             OperationType::GameDetection => "game_detection",
             OperationType::Yolov8 => "yolov8",
+            OperationType::ImageHash => "image_hash",
+            OperationType::Tracking => "tracking",
             OperationType::Unified => "unified",
             OperationType::Unknown => "unknown",
         }
     }
     
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Self {
         match s {
             "face_detection" => OperationType::FaceDetection,
@@ -53,6 +67,8 @@ impl OperationType {
             "quality_assessment" => OperationType::QualityAssessment,
             "game_detection" => OperationType::GameDetection,
             "yolov8" => OperationType::Yolov8,
+            "image_hash" => OperationType::ImageHash,
+            "tracking" => OperationType::Tracking,
             "unified" => OperationType::Unified,
             _ => OperationType::Unknown,
         }
@@ -61,23 +77,60 @@ impl OperationType {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SymlinkInfo {
+    #[serde(with = "crate::utils::paths::lossless")]
     pub symlink_path: PathBuf,
+    #[serde(with = "crate::utils::paths::lossless")]
     pub target_path: PathBuf,
     pub is_symlink: bool,
     pub broken: bool,
 }
 
+/// An image symlink whose target does not exist
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BrokenSymlink {
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub link: PathBuf,
+    /// The target as stored in the link
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub target: PathBuf,
+    /// `target` resolved against the link's directory
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub resolved_target: PathBuf,
+    /// Sidecars beside the link that no live image in its directory claims
+    #[serde(with = "crate::utils::paths::lossless_vec")]
+    pub sidecars: Vec<PathBuf>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SidecarInfo {
     pub id: Uuid,
+    #[serde(with = "crate::utils::paths::lossless")]
     pub image_path: PathBuf,
+    #[serde(with = "crate::utils::paths::lossless")]
     pub sidecar_path: PathBuf,
     pub operation: OperationType,
     pub symlink_info: Option<SymlinkInfo>,
     pub created_at: DateTime<Utc>,
     pub last_updated: DateTime<Utc>,
+    /// Size of the sidecar file in bytes
     pub data_size: u64,
     pub is_valid: bool,
+    /// Processing time reported in the operation payload, in seconds
+    #[serde(default)]
+    pub processing_time: Option<f64>,
+    /// Success flag reported in the operation payload
+    #[serde(default)]
+    pub success: Option<bool>,
+    /// Creation time recorded in the sidecar's `sidecar_info`
+    #[serde(default)]
+    pub recorded_at: Option<DateTime<Utc>>,
+    /// Detections per class in the sidecar's `CLASS_OPERATIONS` payloads
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub class_counts: BTreeMap<String, u32>,
+    /// Decoded contents, read by `payload` on first use
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(skip)]
+    payload: LazyPayload,
 }
 
 impl SidecarInfo {
@@ -98,22 +151,109 @@ impl SidecarInfo {
             last_updated: now,
             data_size: 0,
             is_valid: false,
+            processing_time: None,
+            success: None,
+            recorded_at: None,
+            class_counts: BTreeMap::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            payload: LazyPayload::default(),
         }
     }
+
+    /// This sidecar's decoded contents, read and cached on the first call
+    ///
+    /// Infos from a manager read through its storage backend and size limit; others read
+    /// the filesystem. Clones share the cache.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn payload(&self) -> Result<&serde_json::Value> {
+        self.payload.get(&self.sidecar_path)
+    }
+
+    /// Whether this sidecar can be read and decoded, decoding it through `payload` if needed
+    ///
+    /// Listings from `list_sidecars` read no contents and leave `is_valid` false; this is their
+    /// validity, computed on demand and cached with the payload.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn check_valid(&self) -> bool {
+        self.is_valid || self.payload().is_ok()
+    }
+
+    /// Whether `payload` has already decoded this sidecar
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn is_payload_loaded(&self) -> bool {
+        self.payload.is_loaded()
+    }
+
+    /// Have `payload` read and decode through `source`
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn with_payload_source(mut self, source: PayloadSource) -> Self {
+        self.payload = LazyPayload::new(source);
+        self
+    }
     
     pub fn get_processing_time(&self) -> Option<f64> {
-        // This would be extracted from the sidecar data
-        // For now, return None as placeholder
-        None
+        self.processing_time
     }
     
+    /// A sidecar counts as successful when it is readable and its payload does not report failure
     pub fn get_success_status(&self) -> bool {
-        self.is_valid
+        self.is_valid && self.success.unwrap_or(true)
     }
+    
+    /// Locate this sidecar's operation payload within the decoded sidecar data
+    /// Looks under the operation key (as written by `save_data`), then under `data`
+    /// (as written by `create_sidecar`), and falls back to the whole document
+    pub fn operation_payload<'a>(&self, data: &'a serde_json::Value) -> &'a serde_json::Value {
+        data.get(self.operation.as_str())
+            .or_else(|| data.get("data"))
+            .unwrap_or(data)
+    }
+    
+    /// Pull `processing_time` and `success` out of this sidecar's operation payload,
+    /// along with the creation time recorded in `sidecar_info`
+    pub fn extract_payload_metrics(&mut self, data: &serde_json::Value) {
+        let payload = self.operation_payload(data);
+        
+        let field = |key: &str| {
+            payload.get(key)
+                .or_else(|| payload.get("metadata").and_then(|metadata| metadata.get(key)))
+                .or_else(|| data.get(key))
+        };
+        
+        self.processing_time = field("processing_time").and_then(|v| v.as_f64());
+        self.success = field("success").and_then(|v| v.as_bool());
+        self.recorded_at = data.get("sidecar_info")
+            .and_then(|info| info.get("created_at"))
+            .and_then(|v| v.as_str())
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|timestamp| timestamp.with_timezone(&Utc));
+        self.class_counts = class_counts(data, self.operation.as_str());
+    }
+}
+
+/// Key of `directory` in a class histogram: its path relative to `root`, or `.` for `root` itself
+pub fn class_directory(root: &Path, directory: &Path) -> String {
+    let relative = relative_path(root, directory);
+    if relative.is_empty() { ".".to_string() } else { relative }
+}
+
+/// Detections per class in the `CLASS_OPERATIONS` payloads of a decoded sidecar
+///
+/// `operation` is the sidecar's own operation; a raw detector file of one of those
+/// operations is its own payload. Detections without a class are not counted.
+pub fn class_counts(data: &serde_json::Value, operation: &str) -> BTreeMap<String, u32> {
+    let raw = CLASS_OPERATIONS.contains(&operation).then_some(data);
+    let nested = CLASS_OPERATIONS.iter().filter_map(|operation| data.get(operation));
+    let mut counts = BTreeMap::new();
+    for class in raw.into_iter().chain(nested).filter_map(detections_in).flatten().filter_map(detection_class) {
+        *counts.entry(class).or_insert(0) += 1;
+    }
+    counts
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidationResult {
+    #[serde(with = "crate::utils::paths::lossless")]
     pub file_path: PathBuf,
     pub is_valid: bool,
     pub error: Option<String>,
@@ -122,6 +262,15 @@ pub struct ValidationResult {
     pub detection_count: u32,
     pub tool_name: Option<String>,
     pub operation_type: Option<OperationType>,
+    /// Serialization format the file was decoded with
+    #[serde(default)]
+    pub format: Option<SidecarFormat>,
+    /// Time spent deserializing the file contents, in seconds
+    #[serde(default)]
+    pub deserialize_time: f64,
+    /// Refused unread for being over the maximum sidecar size
+    #[serde(default)]
+    pub oversized: bool,
 }
 
 impl ValidationResult {
@@ -135,6 +284,9 @@ impl ValidationResult {
             detection_count: 0,
             tool_name: None,
             operation_type: None,
+            format: None,
+            deserialize_time: 0.0,
+            oversized: false,
         }
     }
     
@@ -148,6 +300,9 @@ impl ValidationResult {
             detection_count: 0,
             tool_name: None,
             operation_type: None,
+            format: None,
+            deserialize_time: 0.0,
+            oversized: false,
         }
     }
     
@@ -161,12 +316,144 @@ impl ValidationResult {
             detection_count: 0,
             tool_name: None,
             operation_type: None,
+            format: None,
+            deserialize_time: 0.0,
+            oversized: false,
+        }
+    }
+    
+    /// A file refused unread for being over `limit` bytes
+    pub fn oversized(file_path: PathBuf, size: u64, limit: u64, processing_time: f64) -> Self {
+        let error = format!("Sidecar is {} bytes, over the {} byte limit", size, limit);
+        Self { file_size: size, oversized: true, ..Self::error(file_path, error, processing_time) }
+    }
+}
+
+/// Outcome of converting a directory, per sidecar file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConversionSummary {
+    pub converted: u32,
+    /// Already in the target format
+    pub skipped: u32,
+    /// Listed in the checkpoint as converted by an interrupted run
+    pub resumed: u32,
+    pub failed: u32,
+}
+
+/// What removing an operation did to a sidecar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationRemoval {
+    /// The sidecar holds no payload for the operation, or there is no sidecar
+    Absent,
+    /// The payload was removed and the rest of the sidecar kept
+    Removed,
+    /// The payload was the last one, so the sidecar file was deleted
+    SidecarDeleted,
+}
+
+/// Sums behind one operation's statistics
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OperationTotals {
+    pub count: u32,
+    pub success_count: u32,
+    pub processing_time_sum: f64,
+    pub processing_time_count: u32,
+    pub data_size_sum: u64,
+}
+
+impl OperationTotals {
+    pub fn add(&mut self, other: &OperationTotals) {
+        self.count += other.count;
+        self.success_count += other.success_count;
+        self.processing_time_sum += other.processing_time_sum;
+        self.processing_time_count += other.processing_time_count;
+        self.data_size_sum += other.data_size_sum;
+    }
+}
+
+/// Operations whose detections statistics count per class
+pub const CLASS_OPERATIONS: [&str; 2] = ["object_detection", "yolov8"];
+
+/// Ratio of the most to the least frequent class above which statistics flag an imbalance
+pub const DEFAULT_IMBALANCE_RATIO: f64 = 10.0;
+
+/// Largest sidecar read, in bytes, unless configured otherwise; bigger files are refused unread
+pub const DEFAULT_MAX_SIDECAR_SIZE: u64 = 256 * 1024 * 1024;
+
+/// The most and least frequent classes of an imbalanced label distribution
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClassImbalance {
+    pub majority: String,
+    pub majority_count: u32,
+    pub minority: String,
+    pub minority_count: u32,
+    /// `majority_count / minority_count`
+    pub ratio: f64,
+}
+
+/// Detections per class, overall and per directory, across `CLASS_OPERATIONS` payloads
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ClassDistribution {
+    pub counts: BTreeMap<String, u32>,
+    /// Class histogram of each directory holding sidecars, keyed by `/`-separated path
+    /// relative to the scanned directory (`.` for the directory itself)
+    pub directories: BTreeMap<String, BTreeMap<String, u32>>,
+    /// Set when the most frequent class outnumbers the least frequent by more than the ratio checked
+    pub imbalance: Option<ClassImbalance>,
+}
+
+impl ClassDistribution {
+    /// Add the class counts of one sidecar in `directory`
+    pub fn add(&mut self, directory: String, counts: &BTreeMap<String, u32>) {
+        if counts.is_empty() {
+            return;
         }
+        let histogram = self.directories.entry(directory).or_default();
+        for (class, count) in counts {
+            *self.counts.entry(class.clone()).or_insert(0) += count;
+            *histogram.entry(class.clone()).or_insert(0) += count;
+        }
+    }
+
+    /// Flag an imbalance if the most frequent class outnumbers the least frequent by more than `max_ratio`
+    ///
+    /// Needs at least two classes. Ties go to the class that sorts first.
+    pub fn check_balance(&mut self, max_ratio: f64) {
+        let by_count = |a: &(&String, &u32), b: &(&String, &u32)| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0));
+        let majority = self.counts.iter().max_by(by_count);
+        let minority = self.counts.iter().min_by(|a, b| a.1.cmp(b.1).then_with(|| a.0.cmp(b.0)));
+        self.imbalance = match (majority, minority) {
+            (Some((majority, &majority_count)), Some((minority, &minority_count))) if majority != minority && minority_count > 0 => {
+                let ratio = f64::from(majority_count) / f64::from(minority_count);
+                (ratio > max_ratio).then(|| ClassImbalance {
+                    majority: majority.clone(),
+                    majority_count,
+                    minority: minority.clone(),
+                    minority_count,
+                    ratio,
+                })
+            }
+            _ => None,
+        };
     }
 }
 
+/// Images that are hardlinks to one file, identified by device and inode
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HardlinkGroup {
+    pub device: u64,
+    pub inode: u64,
+    /// Links the filesystem records for the file, including any outside the scanned directories
+    pub links: u64,
+    /// Sorted; a scan merging hardlinks counts the image under the first
+    #[serde(with = "crate::utils::paths::lossless_vec")]
+    pub paths: Vec<PathBuf>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StatisticsResult {
+    #[serde(with = "crate::utils::paths::lossless")]
     pub directory: PathBuf,
     pub total_images: u32,
     pub symlink_count: u32,
@@ -179,6 +466,18 @@ pub struct StatisticsResult {
     pub avg_data_sizes: HashMap<String, f64>,
     pub filter_applied: Option<String>,
     pub sidecars: Vec<SidecarInfo>,
+    /// Per-bucket series, filled in by `compute_time_series`
+    #[serde(default)]
+    pub time_series: Option<Vec<TimeSeriesPoint>>,
+    /// Every image with more than one hardlink, grouped by file; groups of several paths are duplicates
+    #[serde(default)]
+    pub hardlink_groups: Vec<HardlinkGroup>,
+    /// Whether each group was counted as one image
+    #[serde(default)]
+    pub hardlinks_merged: bool,
+    /// Detections per class, checked against `DEFAULT_IMBALANCE_RATIO` by `summarize`
+    #[serde(default)]
+    pub class_distribution: ClassDistribution,
 }
 
 impl StatisticsResult {
@@ -196,6 +495,343 @@ impl StatisticsResult {
             avg_data_sizes: HashMap::new(),
             filter_applied: None,
             sidecars: Vec::new(),
+            time_series: None,
+            hardlink_groups: Vec::new(),
+            hardlinks_merged: false,
+            class_distribution: ClassDistribution::default(),
+        }
+    }
+    
+    /// Recompute coverage and the per-operation counts, averages and success rates from
+    /// `sidecars` and `total_images`, aggregating in a single pass
+    pub fn summarize(&mut self) {
+        let totals = self.operation_totals();
+        self.apply_totals(self.sidecars.len() as u32, &totals);
+        self.class_distribution = ClassDistribution::default();
+        for sidecar in &self.sidecars {
+            let directory = sidecar.sidecar_path.parent().unwrap_or(&self.directory);
+            self.class_distribution.add(class_directory(&self.directory, directory), &sidecar.class_counts);
+        }
+        self.class_distribution.check_balance(DEFAULT_IMBALANCE_RATIO);
+    }
+    
+    /// Per-operation sums over `sidecars`, which unlike averages can be added across directories
+    pub fn operation_totals(&self) -> BTreeMap<String, OperationTotals> {
+        let mut totals: BTreeMap<String, OperationTotals> = BTreeMap::new();
+        for sidecar in &self.sidecars {
+            let entry = totals.entry(sidecar.operation.as_str().to_string()).or_default();
+            entry.count += 1;
+            if let Some(proc_time) = sidecar.get_processing_time() {
+                entry.processing_time_sum += proc_time;
+                entry.processing_time_count += 1;
+            }
+            if sidecar.get_success_status() {
+                entry.success_count += 1;
+            }
+            entry.data_size_sum += sidecar.data_size;
+        }
+        totals
+    }
+    
+    /// Set the sidecar count, coverage and per-operation figures from precomputed totals
+    pub fn apply_totals(&mut self, total_sidecars: u32, totals: &BTreeMap<String, OperationTotals>) {
+        self.operation_counts.clear();
+        self.avg_processing_times.clear();
+        self.success_rate_percentages.clear();
+        self.avg_data_sizes.clear();
+        for (operation, totals) in totals.iter().filter(|(_, totals)| totals.count > 0) {
+            if totals.processing_time_count > 0 {
+                self.avg_processing_times.insert(
+                    operation.clone(),
+                    totals.processing_time_sum / totals.processing_time_count as f64,
+                );
+            }
+            self.success_rate_percentages.insert(
+                operation.clone(),
+                (totals.success_count as f64 / totals.count as f64) * 100.0,
+            );
+            self.avg_data_sizes.insert(operation.clone(), totals.data_size_sum as f64 / totals.count as f64);
+            self.operation_counts.insert(operation.clone(), totals.count);
+        }
+
+        self.total_sidecars = total_sidecars;
+        self.coverage_percentage = if self.total_images > 0 {
+            (self.total_sidecars as f64 / self.total_images as f64) * 100.0
+        } else {
+            0.0
+        };
+    }
+    
+    /// Roll several results up into one, as if all their directories had been scanned together
+    pub fn combine(directory: PathBuf, parts: &[StatisticsResult]) -> Self {
+        let mut combined = Self::new(directory);
+        let mut groups: BTreeMap<(u64, u64), (HardlinkGroup, u32)> = BTreeMap::new();
+        combined.hardlinks_merged = !parts.is_empty() && parts.iter().all(|part| part.hardlinks_merged);
+        for part in parts {
+            combined.total_images += part.total_images;
+            combined.symlink_count += part.symlink_count;
+            combined.broken_symlinks += part.broken_symlinks;
+            combined.sidecars.extend(part.sidecars.iter().cloned());
+            for group in &part.hardlink_groups {
+                let (merged, seen_in) = groups.entry((group.device, group.inode)).or_insert_with(|| (HardlinkGroup { paths: Vec::new(), ..group.clone() }, 0));
+                merged.paths.extend(group.paths.iter().cloned());
+                *seen_in += 1;
+            }
+        }
+        for (mut group, seen_in) in groups.into_values() {
+            // Each merged part counted the file once already
+            if combined.hardlinks_merged {
+                combined.total_images -= seen_in - 1;
+            }
+            group.paths.sort();
+            group.paths.dedup();
+            combined.hardlink_groups.push(group);
+        }
+        combined.summarize();
+        combined
+    }
+    
+    /// Bucket sidecars by their recorded creation time into a coverage/throughput series
+    /// Sidecars without a recorded `created_at` are left out of the series
+    pub fn compute_time_series(&mut self, bucket: TimeBucket) {
+        let mut buckets: BTreeMap<DateTime<Utc>, TimeSeriesPoint> = BTreeMap::new();
+        
+        for sidecar in &self.sidecars {
+            let Some(recorded_at) = sidecar.recorded_at else {
+                continue;
+            };
+            let bucket_start = bucket.truncate(recorded_at);
+            let point = buckets.entry(bucket_start).or_insert_with(|| TimeSeriesPoint::new(bucket_start));
+            
+            point.sidecar_count += 1;
+            if sidecar.get_success_status() {
+                point.success_count += 1;
+            }
+            if let Some(processing_time) = sidecar.get_processing_time() {
+                point.total_processing_time += processing_time;
+                point.timed_count += 1;
+            }
+        }
+        
+        let mut cumulative_sidecars = 0;
+        let series = buckets
+            .into_values()
+            .map(|mut point| {
+                cumulative_sidecars += point.sidecar_count;
+                point.cumulative_sidecars = cumulative_sidecars;
+                point.coverage_percentage = if self.total_images > 0 {
+                    (cumulative_sidecars as f64 / self.total_images as f64) * 100.0
+                } else {
+                    0.0
+                };
+                point.throughput_per_hour = point.sidecar_count as f64 / bucket.hours();
+                point
+            })
+            .collect();
+        
+        self.time_series = Some(series);
+    }
+    
+    /// Write the time series as CSV, one row per bucket
+    pub fn write_time_series_csv<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writeln!(
+            writer,
+            "bucket_start,sidecar_count,success_count,avg_processing_time,cumulative_sidecars,coverage_percentage,throughput_per_hour"
+        )?;
+        for point in self.time_series.iter().flatten() {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{:.4},{:.4}",
+                point.bucket_start.to_rfc3339(),
+                point.sidecar_count,
+                point.success_count,
+                point.avg_processing_time().map(|t| t.to_string()).unwrap_or_default(),
+                point.cumulative_sidecars,
+                point.coverage_percentage,
+                point.throughput_per_hour,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Statistics for several directories processed in one run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiRootStatistics {
+    /// One result per directory, in the order they were given
+    pub roots: Vec<StatisticsResult>,
+    /// Totals over every directory; its `directory` is their common ancestor
+    pub aggregate: StatisticsResult,
+}
+
+impl MultiRootStatistics {
+    /// Combine per-directory results, labelling the aggregate with their common ancestor
+    pub fn new(roots: Vec<StatisticsResult>) -> Self {
+        let directories: Vec<PathBuf> = roots.iter().map(|root| root.directory.clone()).collect();
+        let aggregate = StatisticsResult::combine(common_ancestor(&directories), &roots);
+        Self { roots, aggregate }
+    }
+}
+
+/// Longest path prefix shared by every directory, used to label aggregate results
+pub(crate) fn common_ancestor(directories: &[PathBuf]) -> PathBuf {
+    let Some((first, rest)) = directories.split_first() else {
+        return PathBuf::new();
+    };
+
+    let mut ancestor = first.clone();
+    for directory in rest {
+        while !directory.starts_with(&ancestor) {
+            if !ancestor.pop() {
+                return PathBuf::new();
+            }
+        }
+    }
+    ancestor
+}
+
+/// How `save_data` combines a new payload with one already stored for the same operation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MergeStrategy {
+    /// The new payload replaces the stored one
+    #[default]
+    Replace,
+    /// Objects are merged key by key; the new payload wins on conflicting leaves
+    Deep,
+    /// The stored payload is kept; the new one is only written if none exists
+    KeepExisting,
+}
+
+impl MergeStrategy {
+    /// Combine the stored payload (if any) with `incoming`
+    pub fn apply(&self, existing: Option<&serde_json::Value>, incoming: serde_json::Value) -> serde_json::Value {
+        match (self, existing) {
+            (MergeStrategy::Deep, Some(existing)) => JsonUtils::merge_values(existing, &incoming),
+            (MergeStrategy::KeepExisting, Some(existing)) => existing.clone(),
+            _ => incoming,
+        }
+    }
+}
+
+impl FromStr for MergeStrategy {
+    type Err = SidecarError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "replace" => Ok(MergeStrategy::Replace),
+            "deep" | "merge" => Ok(MergeStrategy::Deep),
+            "keep" | "keep_existing" | "keep-existing" => Ok(MergeStrategy::KeepExisting),
+            _ => Err(SidecarError::ProcessingError(format!("Unknown merge strategy: {}", s))),
+        }
+    }
+}
+
+/// Where the sidecars of an image reached through a symlink live
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SymlinkPolicy {
+    /// Next to the file the link points to, shared by every link to it
+    #[default]
+    FollowTarget,
+    /// Next to the link, so each symlink farm keeps its own sidecars
+    StayAtLink,
+    /// Written next to both; reads prefer the one next to the link
+    Both,
+}
+
+impl FromStr for SymlinkPolicy {
+    type Err = SidecarError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().replace('_', "-").as_str() {
+            "follow-target" | "target" => Ok(SymlinkPolicy::FollowTarget),
+            "stay-at-link" | "link" => Ok(SymlinkPolicy::StayAtLink),
+            "both" => Ok(SymlinkPolicy::Both),
+            _ => Err(SidecarError::ProcessingError(format!(
+                "Unknown symlink policy: {}. Supported policies: follow-target, stay-at-link, both",
+                s
+            ))),
+        }
+    }
+}
+
+/// Bucket width for time-series statistics
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeBucket {
+    Hour,
+    Day,
+}
+
+impl TimeBucket {
+    /// Round a timestamp down to the start of its bucket
+    pub fn truncate(&self, timestamp: DateTime<Utc>) -> DateTime<Utc> {
+        let date = timestamp.date_naive();
+        let start = match self {
+            TimeBucket::Hour => date.and_hms_opt(timestamp.hour(), 0, 0),
+            TimeBucket::Day => date.and_hms_opt(0, 0, 0),
+        };
+        start.map(|naive| naive.and_utc()).unwrap_or(timestamp)
+    }
+    
+    /// Bucket width in hours
+    pub fn hours(&self) -> f64 {
+        match self {
+            TimeBucket::Hour => 1.0,
+            TimeBucket::Day => 24.0,
+        }
+    }
+}
+
+impl FromStr for TimeBucket {
+    type Err = SidecarError;
+    
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "hour" | "hourly" => Ok(TimeBucket::Hour),
+            "day" | "daily" => Ok(TimeBucket::Day),
+            _ => Err(SidecarError::ProcessingError(format!("Unknown time bucket: {}", s))),
+        }
+    }
+}
+
+/// One bucket of a time-series statistics run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeSeriesPoint {
+    pub bucket_start: DateTime<Utc>,
+    /// Sidecars created within this bucket
+    pub sidecar_count: u32,
+    pub success_count: u32,
+    /// Sum of reported processing times for sidecars in this bucket
+    pub total_processing_time: f64,
+    /// Number of sidecars in this bucket that reported a processing time
+    pub timed_count: u32,
+    /// Sidecars created up to the end of this bucket
+    pub cumulative_sidecars: u32,
+    /// Share of images covered by the end of this bucket
+    pub coverage_percentage: f64,
+    /// Sidecars created per hour within this bucket
+    pub throughput_per_hour: f64,
+}
+
+impl TimeSeriesPoint {
+    fn new(bucket_start: DateTime<Utc>) -> Self {
+        Self {
+            bucket_start,
+            sidecar_count: 0,
+            success_count: 0,
+            total_processing_time: 0.0,
+            timed_count: 0,
+            cumulative_sidecars: 0,
+            coverage_percentage: 0.0,
+            throughput_per_hour: 0.0,
+        }
+    }
+    
+    /// Average reported processing time, if any sidecar in the bucket reported one
+    pub fn avg_processing_time(&self) -> Option<f64> {
+        if self.timed_count > 0 {
+            Some(self.total_processing_time / self.timed_count as f64)
+        } else {
+            None
         }
     }
 }
@@ -228,6 +864,51 @@ pub enum SidecarError {
     
     #[error("Serialization error: {0}")]
     SerializationError(String),
+    
+    #[error("Format error: {0}")]
+    Format(#[from] SerializationError),
+    
+    #[error("Schema error: {0}")]
+    Schema(String),
+    
+    #[error("Lock error: {0}")]
+    Lock(String),
+    
+    #[error("Storage error: {0}")]
+    Storage(String),
+    
+    #[error("Read-only mode: refusing to {0}")]
+    ReadOnly(String),
+    
+    #[error("Sidecar too large: {} is {size} bytes, over the {limit} byte limit", path.display())]
+    TooLarge { path: PathBuf, size: u64, limit: u64 },
+    
+    #[error("Operation cancelled")]
+    Cancelled,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<tokio::task::JoinError> for SidecarError {
+    fn from(error: tokio::task::JoinError) -> Self {
+        if error.is_cancelled() {
+            SidecarError::Cancelled
+        } else {
+            SidecarError::ProcessingError(format!("Background task failed: {}", error))
+        }
+    }
+}
+
+impl From<bincode::Error> for SidecarError {
+    fn from(error: bincode::Error) -> Self {
+        SidecarError::Format(SerializationError::Binary(error))
+    }
+}
+
+impl<T> From<std::sync::PoisonError<T>> for SidecarError {
+    fn from(error: std::sync::PoisonError<T>) -> Self {
+        SidecarError::Lock(error.to_string())
+    }
 }
 
-pub type Result<T> = std::result::Result<T, SidecarError>;
+/// Result type returned throughout the library API
+pub type Result<T, E = SidecarError> = std::result::Result<T, E>;
@@ -0,0 +1,232 @@
+/*
+ * Context: Building one unified sidecar for an image from its per-operation sidecars, and splitting it back
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde, chrono
+ */
+
+use crate::sidecar::formats::SidecarFormat;
+use crate::sidecar::signing::SIGNATURES_KEY;
+use crate::utils::paths;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// Key under `sidecar_info` naming, per operation, the sidecar its payload was unified from
+pub const UNIFIED_SOURCES_KEY: &str = "unified_sources";
+
+/// Key under `sidecar_info` of a per-operation sidecar naming the sidecar it was split from
+pub const SPLIT_FROM_KEY: &str = "split_from";
+
+/// One operation payload found in a source sidecar
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+    pub operation: String,
+    pub payload: Value,
+    pub source: PathBuf,
+    /// When the payload was last written, as recorded or else the source's mtime
+    pub updated: Option<DateTime<Utc>>,
+    provenance: Option<Value>,
+    signature: Option<Value>,
+}
+
+/// An operation in a unified sidecar and the sidecar its payload came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnifiedOperation {
+    pub operation: String,
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub source: PathBuf,
+}
+
+/// What unifying the sidecars of one image did
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnifyRecord {
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub image_path: PathBuf,
+    /// The unified sidecar written, or that would be in a dry run
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub sidecar_path: PathBuf,
+    /// Operations now in the unified sidecar, each with the sidecar it came from
+    pub operations: Vec<UnifiedOperation>,
+    /// Source sidecars removed because every payload they held was unified
+    #[serde(with = "crate::utils::paths::lossless_vec")]
+    pub removed: Vec<PathBuf>,
+    pub error: Option<String>,
+}
+
+impl UnifyRecord {
+    pub fn new(image_path: PathBuf, sidecar_path: PathBuf) -> Self {
+        Self { image_path, sidecar_path, operations: Vec::new(), removed: Vec::new(), error: None }
+    }
+}
+
+/// What splitting the sidecar of one image did
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SplitRecord {
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub image_path: PathBuf,
+    /// The sidecar that was split
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub sidecar_path: PathBuf,
+    /// Per-operation sidecars written, or that would be in a dry run
+    pub files: Vec<SplitFile>,
+    /// Whether the split sidecar was removed, every payload it held having been written out
+    pub removed: bool,
+    pub error: Option<String>,
+}
+
+/// One per-operation sidecar written by a split
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SplitFile {
+    pub operation: String,
+    #[serde(with = "crate::utils::paths::lossless")]
+    pub path: PathBuf,
+}
+
+impl SplitRecord {
+    pub fn new(image_path: PathBuf, sidecar_path: PathBuf) -> Self {
+        Self { image_path, sidecar_path, files: Vec::new(), removed: false, error: None }
+    }
+}
+
+fn timestamp(value: Option<&Value>) -> Option<DateTime<Utc>> {
+    value
+        .and_then(Value::as_str)
+        .and_then(|text| DateTime::parse_from_rfc3339(text).ok())
+        .map(|time| time.with_timezone(&Utc))
+}
+
+/// The operation payloads of a sidecar document
+///
+/// A sidecar written by `save_data` holds one key per operation. One written by
+/// `create_sidecar` holds a single payload under `data`, named by `sidecar_info.operation_type`.
+pub fn sections(source: &Path, data: &Value, modified: Option<DateTime<Utc>>) -> Vec<Section> {
+    let Some(object) = data.as_object() else {
+        return Vec::new();
+    };
+    let info = object.get("sidecar_info");
+    let per_operation = |key: &str| info.and_then(|info| info.get(key)).and_then(Value::as_object);
+    let section = |operation: &str, payload: &Value, updated: Option<DateTime<Utc>>, signature: Option<&Value>| Section {
+        operation: operation.to_string(),
+        payload: payload.clone(),
+        source: source.to_path_buf(),
+        updated: updated.or(modified),
+        provenance: per_operation("provenance").and_then(|producers| producers.get(operation)).cloned(),
+        signature: signature.cloned(),
+    };
+
+    let single = info.and_then(|info| info.get("operation_type")).and_then(Value::as_str);
+    if let (Some(operation), Some(payload)) = (single, object.get("data")) {
+        // Its signature covers the `data` key, so it does not carry over to the operation's key
+        let created = timestamp(info.and_then(|info| info.get("created_at")));
+        return vec![section(operation, payload, created, None)];
+    }
+    let last_updated = timestamp(info.and_then(|info| info.get("last_updated")));
+    object
+        .iter()
+        .filter(|(key, _)| *key != "sidecar_info")
+        .map(|(operation, payload)| {
+            let updated = timestamp(per_operation("operation_updated").and_then(|times| times.get(operation))).or(last_updated);
+            let signature = per_operation(SIGNATURES_KEY).and_then(|signatures| signatures.get(operation));
+            section(operation, payload, updated, signature)
+        })
+        .collect()
+}
+
+/// The newest section of each operation `selected` accepts; on a tie the earlier section wins
+pub fn newest(sections: Vec<Section>, selected: impl Fn(&str) -> bool) -> BTreeMap<String, Section> {
+    let mut chosen: BTreeMap<String, Section> = BTreeMap::new();
+    for section in sections.into_iter().filter(|section| selected(&section.operation)) {
+        match chosen.get(&section.operation) {
+            Some(current) if current.updated >= section.updated => {}
+            _ => {
+                chosen.insert(section.operation.clone(), section);
+            }
+        }
+    }
+    chosen
+}
+
+/// `base` with each chosen section written under its operation, with the section's provenance
+///
+/// A `base` holding a single `data` payload is first turned into one keyed by its operation.
+pub fn unified_document(base: Option<Value>, chosen: &BTreeMap<String, Section>, image_path: &Path) -> Value {
+    let mut document = match base {
+        Some(Value::Object(object)) => object,
+        _ => Map::new(),
+    };
+    let mut info = match document.remove("sidecar_info") {
+        Some(Value::Object(info)) => info,
+        _ => Map::new(),
+    };
+    if let Some(Value::String(operation)) = info.remove("operation_type") {
+        if let Some(payload) = document.remove("data") {
+            document.insert(operation, payload);
+        }
+        info.remove(SIGNATURES_KEY);
+    }
+
+    let now = Value::String(Utc::now().to_rfc3339());
+    info.entry("created_at").or_insert_with(|| now.clone());
+    info.entry("image_path").or_insert_with(|| paths::path_to_json(image_path));
+    info.insert("last_updated".to_string(), now);
+    info.insert("last_operation".to_string(), Value::String("unified".to_string()));
+    for (operation, section) in chosen {
+        document.insert(operation.clone(), section.payload.clone());
+        let mut record = |key: &str, value: Option<Value>| {
+            let entries = info.entry(key).or_insert_with(|| Value::Object(Map::new()));
+            if let Some(entries) = entries.as_object_mut() {
+                match value {
+                    Some(value) => entries.insert(operation.clone(), value),
+                    None => entries.remove(operation),
+                };
+            }
+        };
+        record(UNIFIED_SOURCES_KEY, Some(paths::path_to_json(&section.source)));
+        record("operation_updated", section.updated.map(|updated| Value::String(updated.to_rfc3339())));
+        record("provenance", section.provenance.clone());
+        record(SIGNATURES_KEY, section.signature.clone());
+    }
+    document.insert("sidecar_info".to_string(), Value::Object(info));
+    Value::Object(document)
+}
+
+/// Where a split writes the `operation` payload of the image at `image_path`: `operation_IMG.<ext>` beside it
+pub fn split_path(image_path: &Path, operation: &str, format: SidecarFormat) -> PathBuf {
+    let mut name = OsString::from(operation);
+    name.push("_");
+    name.push(image_path.file_stem().unwrap_or_default());
+    name.push(".");
+    name.push(format.extension());
+    image_path.with_file_name(name)
+}
+
+/// A sidecar holding only `section`, keeping the `sidecar_info` of `data` narrowed to its operation
+pub fn split_document(data: &Value, section: &Section) -> Value {
+    let mut info = data.get("sidecar_info").and_then(Value::as_object).cloned().unwrap_or_default();
+    for key in [UNIFIED_SOURCES_KEY, "operation_type", "operation_updated", "provenance", SIGNATURES_KEY] {
+        info.remove(key);
+    }
+    let operation = &section.operation;
+    let mut narrowed = |key: &str, value: Option<Value>| {
+        if let Some(value) = value {
+            let mut entries = Map::new();
+            entries.insert(operation.clone(), value);
+            info.insert(key.to_string(), Value::Object(entries));
+        }
+    };
+    narrowed("operation_updated", section.updated.map(|updated| Value::String(updated.to_rfc3339())));
+    narrowed("provenance", section.provenance.clone());
+    narrowed(SIGNATURES_KEY, section.signature.clone());
+    info.insert("last_operation".to_string(), Value::String(operation.clone()));
+    info.insert(SPLIT_FROM_KEY.to_string(), paths::path_to_json(&section.source));
+
+    let mut document = Map::new();
+    document.insert(operation.clone(), section.payload.clone());
+    document.insert("sidecar_info".to_string(), Value::Object(info));
+    Value::Object(document)
+}
@@ -0,0 +1,109 @@
+/*
+ * Context: Convert JSON sidecars to another format as producers write them
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: tokio, serde_json, chrono
+ */
+
+use crate::sidecar::formats::SidecarFormat;
+use crate::sidecar::manager::SidecarManager;
+use crate::sidecar::types::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// One conversion attempted by a `ConvertWatcher`, as written to the audit log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConversionRecord {
+    pub timestamp: DateTime<Utc>,
+    pub source: PathBuf,
+    /// The converted sidecar; `None` when the conversion failed
+    pub target: Option<PathBuf>,
+    pub format: SidecarFormat,
+    pub error: Option<String>,
+}
+
+/// Append-only NDJSON log of conversions, one record per line
+#[derive(Debug)]
+pub struct AuditLog {
+    file: File,
+}
+
+impl AuditLog {
+    /// Open `path` for appending, creating it if needed
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new().append(true).create(true).open(path)?;
+        Ok(Self { file })
+    }
+
+    pub fn record(&mut self, record: &ConversionRecord) -> Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        self.file.write_all(&line)?;
+        Ok(())
+    }
+}
+
+/// Converts `.json` sidecars once they have stopped changing
+///
+/// A sidecar is settled when its modification time is at least `settle` in the past, so
+/// files still being written by a producer are left alone until a later poll. A file that
+/// fails to convert is retried only after it is modified again.
+#[derive(Debug)]
+pub struct ConvertWatcher {
+    target_format: SidecarFormat,
+    settle: Duration,
+    failed: HashMap<PathBuf, SystemTime>,
+}
+
+impl ConvertWatcher {
+    pub fn new(target_format: SidecarFormat, settle: Duration) -> Self {
+        Self { target_format, settle, failed: HashMap::new() }
+    }
+
+    pub fn target_format(&self) -> SidecarFormat {
+        self.target_format
+    }
+
+    /// Convert every settled JSON sidecar under `directory` and return what was attempted
+    pub async fn poll(&mut self, manager: &SidecarManager, directory: &Path) -> Result<Vec<ConversionRecord>> {
+        manager.ensure_writable("convert sidecars")?;
+        let mut records = Vec::new();
+        if self.target_format == SidecarFormat::Json {
+            return Ok(records);
+        }
+
+        let now = SystemTime::now();
+        for source in manager.find_sidecar_files(directory).await? {
+            if SidecarFormat::from_path(&source) != Some(SidecarFormat::Json) {
+                continue;
+            }
+            // The file may have been converted or removed since the scan
+            let Ok(modified) = tokio::fs::metadata(&source).await.and_then(|metadata| metadata.modified()) else {
+                continue;
+            };
+            if now.duration_since(modified).unwrap_or_default() < self.settle || self.failed.get(&source) == Some(&modified) {
+                continue;
+            }
+
+            let record = match manager.convert_sidecar_format(&source, self.target_format).await {
+                Ok(target) => {
+                    self.failed.remove(&source);
+                    ConversionRecord { timestamp: Utc::now(), source, target: Some(target), format: self.target_format, error: None }
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to convert {:?}: {}", source, e);
+                    self.failed.insert(source.clone(), modified);
+                    ConversionRecord { timestamp: Utc::now(), source, target: None, format: self.target_format, error: Some(e.to_string()) }
+                }
+            };
+            records.push(record);
+        }
+        Ok(records)
+    }
+}
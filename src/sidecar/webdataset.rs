@@ -0,0 +1,239 @@
+/*
+ * Context: WebDataset tar-shard export of image + sidecar payload samples
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: tar, serde_json
+ */
+
+use crate::sidecar::manifest::relative_path;
+use crate::sidecar::types::{Result, SidecarError};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+/// Shard size used when none is given (1 GB)
+pub const DEFAULT_SHARD_SIZE: u64 = 1_000_000_000;
+
+/// Tar block size; every header and file body is padded to a multiple of it
+const BLOCK: u64 = 512;
+
+/// What a WebDataset export includes and how it is split
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebDatasetOptions {
+    /// Start a new shard once the next sample would take the current one past this many bytes
+    pub shard_size: u64,
+    /// Export only these operations' payloads; empty exports every operation found
+    pub operations: Vec<String>,
+}
+
+impl Default for WebDatasetOptions {
+    fn default() -> Self {
+        Self { shard_size: DEFAULT_SHARD_SIZE, operations: Vec::new() }
+    }
+}
+
+impl WebDatasetOptions {
+    pub fn shard_size(mut self, bytes: u64) -> Self {
+        self.shard_size = bytes.max(1);
+        self
+    }
+
+    pub fn operations<I, S>(mut self, operations: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.operations = operations.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+/// What a WebDataset export wrote
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebDatasetSummary {
+    /// One sample per image: `<key>.<ext>` with the image and `<key>.json` with its payloads
+    pub samples: usize,
+    pub shards: Vec<PathBuf>,
+    /// Brace pattern naming every shard, as `webdataset.WebDataset` takes it
+    pub url: String,
+    /// Bytes written across all shards
+    pub bytes: u64,
+    /// Images that could not be read; they have no sample
+    pub missing_images: Vec<PathBuf>,
+}
+
+/// Parse a size such as `1GB`, `500MB`, `64KiB` or a plain byte count
+///
+/// `KB`, `MB`, `GB` and `TB` are powers of 1000, `KiB` through `TiB` powers of 1024.
+pub fn parse_size(text: &str) -> std::result::Result<u64, SidecarError> {
+    let invalid = || SidecarError::ProcessingError(format!("Invalid size '{}': expected a number with an optional unit such as 1GB or 512MiB", text));
+    let text = text.trim();
+    let split = text.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number.parse().map_err(|_| invalid())?;
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1_000,
+        "m" | "mb" => 1_000_000,
+        "g" | "gb" => 1_000_000_000,
+        "t" | "tb" => 1_000_000_000_000,
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        "tib" => 1 << 40,
+        _ => return Err(invalid()),
+    };
+    let bytes = number * multiplier as f64;
+    if !bytes.is_finite() || bytes < 1.0 {
+        return Err(invalid());
+    }
+    Ok(bytes as u64)
+}
+
+/// Shard `index` of `output`: `season.tar` becomes `season-000003.tar`
+pub fn shard_path(output: &Path, index: usize) -> PathBuf {
+    output.with_file_name(format!("{}-{:06}.tar", shard_prefix(output), index))
+}
+
+/// Brace pattern covering `shards` shards of `output`, e.g. `season-{000000..000011}.tar`
+pub fn shard_url(output: &Path, shards: usize) -> String {
+    let name = match shards {
+        0 | 1 => format!("{}-000000.tar", shard_prefix(output)),
+        _ => format!("{}-{{000000..{:06}}}.tar", shard_prefix(output), shards - 1),
+    };
+    output.with_file_name(name).to_string_lossy().into_owned()
+}
+
+fn shard_prefix(output: &Path) -> String {
+    let name = output.file_name().unwrap_or_default().to_string_lossy();
+    name.strip_suffix(".tar").unwrap_or(&name).to_string()
+}
+
+/// Sample keys for `images`, unique and free of dots in the file name
+///
+/// WebDataset groups tar members into samples by everything before the first dot of the
+/// file name, so `IMG.0001.jpg` gets the key `IMG_0001`. Images sharing a stem in one
+/// directory (`a.jpg`, `a.png`) keep them apart with their extension (`a_png`).
+pub fn sample_keys<'a>(root: &Path, images: impl IntoIterator<Item = &'a PathBuf>) -> Vec<String> {
+    let mut used = BTreeSet::new();
+    images
+        .into_iter()
+        .map(|image_path| {
+            let relative = relative_path(root, &image_path.with_extension(""));
+            let (directory, stem) = relative.rsplit_once('/').unwrap_or(("", relative.as_str()));
+            let stem = stem.replace('.', "_");
+            let mut key = if directory.is_empty() { stem } else { format!("{}/{}", directory, stem) };
+            if !used.insert(key.clone()) {
+                key = format!("{}_{}", key, extension(image_path));
+                used.insert(key.clone());
+            }
+            key
+        })
+        .collect()
+}
+
+fn extension(path: &Path) -> String {
+    path.extension().unwrap_or_default().to_string_lossy().to_lowercase()
+}
+
+/// Operation payloads of one decoded sidecar, optionally only those of `operations`
+pub fn sample_payloads(data: &Value, operations: &[String]) -> Map<String, Value> {
+    let Some(payloads) = data.as_object() else {
+        return Map::new();
+    };
+    payloads
+        .iter()
+        .filter(|(key, _)| *key != "sidecar_info" && (operations.is_empty() || operations.contains(key)))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+/// Bytes a member of `len` bytes takes up in a tar stream
+fn member_size(len: u64) -> u64 {
+    BLOCK + len.div_ceil(BLOCK) * BLOCK
+}
+
+/// Writes samples into numbered tar shards, starting a new one when a shard is full
+pub struct ShardWriter {
+    output: PathBuf,
+    shard_size: u64,
+    current: Option<(tar::Builder<BufWriter<File>>, u64)>,
+    summary: WebDatasetSummary,
+}
+
+impl ShardWriter {
+    pub fn new(output: &Path, shard_size: u64) -> Self {
+        Self { output: output.to_path_buf(), shard_size, current: None, summary: WebDatasetSummary::default() }
+    }
+
+    /// Append one sample; a sample is never split across shards, so one larger than
+    /// `shard_size` gets a shard of its own
+    pub fn write_sample(&mut self, key: &str, members: &[(String, Vec<u8>)]) -> Result<()> {
+        let size: u64 = members.iter().map(|(_, bytes)| member_size(bytes.len() as u64)).sum();
+        if self.current.as_ref().is_some_and(|(_, written)| *written > 0 && written + size > self.shard_size) {
+            self.close_shard()?;
+        }
+        if self.current.is_none() {
+            let path = shard_path(&self.output, self.summary.shards.len());
+            self.current = Some((tar::Builder::new(BufWriter::new(File::create(&path)?)), 0));
+            self.summary.shards.push(path);
+        }
+        let (builder, written) = self.current.as_mut().expect("a shard was just opened");
+        for (extension, bytes) in members {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(bytes.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(0);
+            builder.append_data(&mut header, format!("{}.{}", key, extension), bytes.as_slice())?;
+        }
+        *written += size;
+        self.summary.samples += 1;
+        Ok(())
+    }
+
+    fn close_shard(&mut self) -> Result<()> {
+        if let Some((builder, written)) = self.current.take() {
+            let file = builder.into_inner()?.into_inner().map_err(|e| e.into_error())?;
+            file.sync_all()?;
+            self.summary.bytes += written + 2 * BLOCK;
+        }
+        Ok(())
+    }
+
+    /// Close the last shard and report what was written
+    pub fn finish(mut self) -> Result<WebDatasetSummary> {
+        self.close_shard()?;
+        self.summary.url = shard_url(&self.output, self.summary.shards.len());
+        Ok(self.summary)
+    }
+}
+
+/// Write one sample per image in `images` into tar shards next to `output`
+///
+/// Each sample holds the image as `<key>.<ext>` and its operation payloads as one
+/// `<key>.json` object keyed by operation. Images that cannot be read are listed in
+/// the summary instead. This is blocking; call it from `spawn_blocking`.
+pub fn write_shards(
+    images: &BTreeMap<PathBuf, Map<String, Value>>,
+    root: &Path,
+    output: &Path,
+    options: &WebDatasetOptions,
+) -> Result<WebDatasetSummary> {
+    let mut writer = ShardWriter::new(output, options.shard_size);
+    let mut missing_images = Vec::new();
+    for ((image_path, payloads), key) in images.iter().zip(sample_keys(root, images.keys())) {
+        let Ok(image) = std::fs::read(image_path) else {
+            missing_images.push(image_path.clone());
+            continue;
+        };
+        let json = serde_json::to_vec(payloads)?;
+        writer.write_sample(&key, &[(extension(image_path), image), ("json".to_string(), json)])?;
+    }
+    let mut summary = writer.finish()?;
+    summary.missing_images = missing_images;
+    Ok(summary)
+}
@@ -0,0 +1,129 @@
+/*
+ * Context: SQL queries over a directory of sidecars
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: polars (lazy, sql)
+ */
+
+use crate::parallel::ParallelProcessor;
+use crate::query::eval;
+use crate::query::QueryMatch;
+use crate::sidecar::types::{Result, SidecarError};
+use crate::utils::paths;
+use polars::prelude::*;
+use polars::sql::SQLContext;
+use serde_json::Value;
+
+pub use polars::prelude::DataFrame;
+
+/// Name the sidecar table is registered under
+pub const TABLE_NAME: &str = "sidecars";
+
+impl From<PolarsError> for SidecarError {
+    fn from(error: PolarsError) -> Self {
+        SidecarError::ProcessingError(format!("SQL error: {}", error))
+    }
+}
+
+/// Build the `sidecars` table, one row per decoded sidecar
+///
+/// Columns: `image_path`, `sidecar_path`, `operation`, `is_valid`, `success`,
+/// `processing_time`, `data_size`, `count` (detections in the operation payload),
+/// `max_confidence`, `created_at` (RFC 3339, as recorded in the sidecar) and
+/// `data` (the full sidecar as a JSON string).
+pub fn sidecar_table(records: &[QueryMatch]) -> Result<DataFrame> {
+    let mut image_path = Vec::with_capacity(records.len());
+    let mut sidecar_path = Vec::with_capacity(records.len());
+    let mut operation = Vec::with_capacity(records.len());
+    let mut is_valid = Vec::with_capacity(records.len());
+    let mut success = Vec::with_capacity(records.len());
+    let mut processing_time = Vec::with_capacity(records.len());
+    let mut data_size = Vec::with_capacity(records.len());
+    let mut count = Vec::with_capacity(records.len());
+    let mut max_confidence = Vec::with_capacity(records.len());
+    let mut created_at = Vec::with_capacity(records.len());
+    let mut data = Vec::with_capacity(records.len());
+
+    for record in records {
+        let sidecar = &record.sidecar;
+        let payload = sidecar.operation_payload(&record.data);
+
+        image_path.push(paths::encode_path(&sidecar.image_path).into_owned());
+        sidecar_path.push(paths::encode_path(&sidecar.sidecar_path).into_owned());
+        operation.push(sidecar.operation.as_str());
+        is_valid.push(sidecar.is_valid);
+        success.push(sidecar.get_success_status());
+        processing_time.push(sidecar.get_processing_time());
+        data_size.push(sidecar.data_size);
+        count.push(ParallelProcessor::extract_detection_count(payload) as u64);
+        max_confidence.push(eval::max_confidence(payload));
+        created_at.push(sidecar.recorded_at.map(|t| t.to_rfc3339()));
+        data.push(record.data.to_string());
+    }
+
+    let table = DataFrame::new(vec![
+        Column::new("image_path".into(), image_path),
+        Column::new("sidecar_path".into(), sidecar_path),
+        Column::new("operation".into(), operation),
+        Column::new("is_valid".into(), is_valid),
+        Column::new("success".into(), success),
+        Column::new("processing_time".into(), processing_time),
+        Column::new("data_size".into(), data_size),
+        Column::new("count".into(), count),
+        Column::new("max_confidence".into(), max_confidence),
+        Column::new("created_at".into(), created_at),
+        Column::new("data".into(), data),
+    ])?;
+
+    Ok(table)
+}
+
+/// Run a SQL statement against the `sidecars` table built from `records`
+pub fn execute(records: &[QueryMatch], sql: &str) -> Result<DataFrame> {
+    let mut context = SQLContext::new();
+    context.register(TABLE_NAME, sidecar_table(records)?.lazy());
+    Ok(context.execute(sql)?.collect()?)
+}
+
+/// Write a result set as CSV
+pub fn write_csv<W: std::io::Write>(frame: &mut DataFrame, writer: W) -> Result<()> {
+    CsvWriter::new(writer).finish(frame)?;
+    Ok(())
+}
+
+/// Convert a result set into an array of row objects keyed by column name
+pub fn to_json_rows(frame: &DataFrame) -> Result<Value> {
+    let columns = frame.get_columns();
+    let mut rows = Vec::with_capacity(frame.height());
+
+    for row in 0..frame.height() {
+        let mut object = serde_json::Map::with_capacity(columns.len());
+        for column in columns {
+            object.insert(column.name().to_string(), any_value_to_json(column.get(row)?));
+        }
+        rows.push(Value::Object(object));
+    }
+
+    Ok(Value::Array(rows))
+}
+
+fn any_value_to_json(value: AnyValue) -> Value {
+    match value {
+        AnyValue::Null => Value::Null,
+        AnyValue::Boolean(b) => Value::Bool(b),
+        AnyValue::String(s) => Value::from(s),
+        AnyValue::StringOwned(s) => Value::from(s.as_str()),
+        AnyValue::Int8(n) => Value::from(n),
+        AnyValue::Int16(n) => Value::from(n),
+        AnyValue::Int32(n) => Value::from(n),
+        AnyValue::Int64(n) => Value::from(n),
+        AnyValue::UInt8(n) => Value::from(n),
+        AnyValue::UInt16(n) => Value::from(n),
+        AnyValue::UInt32(n) => Value::from(n),
+        AnyValue::UInt64(n) => Value::from(n),
+        AnyValue::Float32(n) => Value::from(n),
+        AnyValue::Float64(n) => Value::from(n),
+        other => Value::from(other.to_string()),
+    }
+}
@@ -0,0 +1,220 @@
+/*
+ * Context: Fixtures for testing code built on image-sidecar-rust without a real filesystem
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde_json
+ *
+ * `MemoryStorage` keeps files in a map and plugs into `ImageSidecar::builder().storage(...)`,
+ * so sidecars are saved, read, scanned and queried without touching the disk. `SidecarTree`
+ * describes a tree of images and sidecars in a few lines and writes it to any storage, or to
+ * a real directory for the commands that need one.
+ */
+
+use crate::sidecar::formats::{FormatManager, SidecarFormat};
+use crate::sidecar::storage::StorageBackend;
+use crate::sidecar::types::{OperationType, Result, SidecarError};
+use crate::utils::paths;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+/// Time recorded in the `sidecar_info` of every `SidecarTree` sidecar, so fixtures are reproducible
+pub const FIXTURE_TIME: &str = "2024-12-19T10:00:00+00:00";
+
+/// Bytes written for images that are given no contents
+const PLACEHOLDER_IMAGE: &[u8] = b"fake image data";
+
+/// Files kept in memory; clones share them
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    files: Arc<RwLock<BTreeMap<PathBuf, Vec<u8>>>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Contents of the file at `path`, if there is one
+    pub fn get(&self, path: impl AsRef<Path>) -> Option<Vec<u8>> {
+        self.files.read().unwrap_or_else(|poisoned| poisoned.into_inner()).get(path.as_ref()).cloned()
+    }
+
+    /// Every stored path, sorted
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.files.read().unwrap_or_else(|poisoned| poisoned.into_inner()).keys().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.read().unwrap_or_else(|poisoned| poisoned.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn not_found(path: &Path) -> SidecarError {
+    std::io::Error::new(std::io::ErrorKind::NotFound, format!("{} is not in memory storage", path.display())).into()
+}
+
+impl StorageBackend for MemoryStorage {
+    fn read(&self, path: &Path) -> Result<Vec<u8>> {
+        self.get(path).ok_or_else(|| not_found(path))
+    }
+
+    fn write(&self, path: &Path, bytes: &[u8]) -> Result<()> {
+        self.files.write().unwrap_or_else(|poisoned| poisoned.into_inner()).insert(path.to_path_buf(), bytes.to_vec());
+        Ok(())
+    }
+
+    fn remove(&self, path: &Path) -> Result<()> {
+        match self.files.write().unwrap_or_else(|poisoned| poisoned.into_inner()).remove(path) {
+            Some(_) => Ok(()),
+            None => Err(not_found(path)),
+        }
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.files.read().unwrap_or_else(|poisoned| poisoned.into_inner()).contains_key(path)
+    }
+
+    fn list(&self, directory: &Path) -> Result<Vec<PathBuf>> {
+        let files = self.files.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+        Ok(files.keys().filter(|path| path.starts_with(directory)).cloned().collect())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<()> {
+        let mut files = self.files.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let bytes = files.remove(from).ok_or_else(|| not_found(from))?;
+        files.insert(to.to_path_buf(), bytes);
+        Ok(())
+    }
+
+    fn size(&self, path: &Path) -> Result<u64> {
+        let files = self.files.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+        files.get(path).map(|bytes| bytes.len() as u64).ok_or_else(|| not_found(path))
+    }
+}
+
+/// A tree of images and sidecars to write for a test
+///
+/// Paths are relative to the root. Each image gets one sidecar holding every payload added
+/// for it, with the `sidecar_info` that `save_data` writes.
+#[derive(Debug, Clone)]
+pub struct SidecarTree {
+    root: PathBuf,
+    format: SidecarFormat,
+    images: BTreeMap<PathBuf, Vec<u8>>,
+    sidecars: BTreeMap<PathBuf, Vec<(String, Value)>>,
+    raw: BTreeMap<PathBuf, Vec<u8>>,
+}
+
+impl SidecarTree {
+    /// An empty tree under `root`; sidecars are JSON unless `format` says otherwise
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            format: SidecarFormat::Json,
+            images: BTreeMap::new(),
+            sidecars: BTreeMap::new(),
+            raw: BTreeMap::new(),
+        }
+    }
+
+    pub fn format(mut self, format: SidecarFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// An image without a sidecar, holding placeholder bytes
+    pub fn image(self, image: impl AsRef<Path>) -> Self {
+        self.image_bytes(image, PLACEHOLDER_IMAGE)
+    }
+
+    /// An image with the given contents, e.g. a real JPEG for commands that decode images
+    pub fn image_bytes(mut self, image: impl AsRef<Path>, bytes: impl Into<Vec<u8>>) -> Self {
+        self.images.insert(image.as_ref().to_path_buf(), bytes.into());
+        self
+    }
+
+    /// A payload in the sidecar of `image`, which is added if it was not
+    ///
+    /// Adding an operation twice replaces its payload; the last operation added is the
+    /// sidecar's `last_operation`.
+    pub fn sidecar(mut self, image: impl AsRef<Path>, operation: OperationType, payload: Value) -> Self {
+        let image = image.as_ref().to_path_buf();
+        self.images.entry(image.clone()).or_insert_with(|| PLACEHOLDER_IMAGE.to_vec());
+        let payloads = self.sidecars.entry(image).or_default();
+        payloads.retain(|(key, _)| key != operation.as_str());
+        payloads.push((operation.as_str().to_string(), payload));
+        self
+    }
+
+    /// Any other file, such as raw detector output or a corrupt sidecar
+    pub fn file(mut self, path: impl AsRef<Path>, bytes: impl Into<Vec<u8>>) -> Self {
+        self.raw.insert(path.as_ref().to_path_buf(), bytes.into());
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Where the sidecar of `image` is written
+    pub fn sidecar_path(&self, image: impl AsRef<Path>) -> PathBuf {
+        self.root.join(image).with_extension(self.format.extension())
+    }
+
+    /// Every file of the tree with its contents, by absolute path
+    pub fn files(&self) -> Result<BTreeMap<PathBuf, Vec<u8>>> {
+        let format_manager = FormatManager::new();
+        let serializer = format_manager.get_serializer(self.format);
+        let mut files: BTreeMap<PathBuf, Vec<u8>> =
+            self.images.iter().map(|(image, bytes)| (self.root.join(image), bytes.clone())).collect();
+        for (image, payloads) in &self.sidecars {
+            let image_path = self.root.join(image);
+            let last_operation = payloads.last().map_or("unknown", |(key, _)| key.as_str());
+            let mut document = Map::new();
+            document.insert("sidecar_info".to_string(), json!({
+                "created_at": FIXTURE_TIME,
+                "last_updated": FIXTURE_TIME,
+                "last_operation": last_operation,
+                "image_path": paths::path_to_json(&image_path),
+                "operation_updated": payloads.iter().map(|(key, _)| (key.clone(), json!(FIXTURE_TIME))).collect::<Map<_, _>>(),
+            }));
+            document.extend(payloads.iter().cloned());
+            files.insert(image_path.with_extension(self.format.extension()), serializer.serialize(&Value::Object(document))?);
+        }
+        files.extend(self.raw.iter().map(|(path, bytes)| (self.root.join(path), bytes.clone())));
+        Ok(files)
+    }
+
+    /// Write the tree to `storage`
+    pub fn write_to(&self, storage: &dyn StorageBackend) -> Result<()> {
+        for (path, bytes) in self.files()? {
+            storage.write(&path, &bytes)?;
+        }
+        Ok(())
+    }
+
+    /// The tree in a new `MemoryStorage`
+    pub fn in_memory(&self) -> Result<MemoryStorage> {
+        let storage = MemoryStorage::new();
+        self.write_to(&storage)?;
+        Ok(storage)
+    }
+
+    /// Write the tree to disk under its root, for commands that only work on real files
+    pub fn write_to_disk(&self) -> Result<()> {
+        for (path, bytes) in self.files()? {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, bytes)?;
+        }
+        Ok(())
+    }
+}
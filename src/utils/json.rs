@@ -10,12 +10,64 @@
  * - IDE: Cursor (cursor.sh)
  * - Generation method: AI-assisted pair programming
  * - Code style: Rust idiomatic with comprehensive error handling
- * - Dependencies: serde, anyhow
+ * - Dependencies: serde
  */
 
-use anyhow::Result;
+use crate::sidecar::types::{Result, SidecarError};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+/// One RFC 6902 operation; only the `add`, `remove` and `replace` subset is produced or applied
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+}
+
+impl PatchOperation {
+    /// JSON Pointer this operation targets
+    pub fn path(&self) -> &str {
+        match self {
+            PatchOperation::Add { path, .. } | PatchOperation::Remove { path } | PatchOperation::Replace { path, .. } => path,
+        }
+    }
+}
+
+/// Ordered list of patch operations, serialized as an RFC 6902 JSON Patch document
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct JsonPatch(pub Vec<PatchOperation>);
+
+impl JsonPatch {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+/// A location changed differently on both sides of a three-way merge
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MergeConflict {
+    /// JSON Pointer to the conflicting value
+    pub path: String,
+    /// Our value, `None` if we removed it
+    pub ours: Option<Value>,
+    /// Their value, `None` if they removed it
+    pub theirs: Option<Value>,
+}
+
+/// Result of a three-way merge; conflicting locations keep our value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MergeResult {
+    pub merged: Value,
+    pub conflicts: Vec<MergeConflict>,
+}
+
 /// JSON utilities for sidecar operations
 pub struct JsonUtils;
 
@@ -48,7 +100,7 @@ impl JsonUtils {
             // Basic validation - can be extended
             Ok(())
         } else {
-            Err(anyhow::anyhow!("Invalid JSON structure: expected object"))
+            Err(SidecarError::Schema("Invalid JSON structure: expected object".to_string()))
         }
     }
 
@@ -93,15 +145,187 @@ impl JsonUtils {
         }
     }
 
+    /// Compute a JSON Patch turning `from` into `to`
+    /// Objects are diffed per key and arrays per index, so only changed leaves are replaced
+    pub fn diff(from: &Value, to: &Value) -> JsonPatch {
+        let mut operations = Vec::new();
+        Self::diff_into(from, to, &mut String::new(), &mut operations);
+        JsonPatch(operations)
+    }
+
+    fn diff_into(from: &Value, to: &Value, pointer: &mut String, operations: &mut Vec<PatchOperation>) {
+        match (from, to) {
+            (Value::Object(from_map), Value::Object(to_map)) => {
+                for (key, from_child) in from_map {
+                    with_segment(pointer, key, |pointer| match to_map.get(key) {
+                        Some(to_child) => Self::diff_into(from_child, to_child, pointer, operations),
+                        None => operations.push(PatchOperation::Remove { path: pointer.clone() }),
+                    });
+                }
+                for (key, to_child) in to_map {
+                    if !from_map.contains_key(key) {
+                        with_segment(pointer, key, |pointer| {
+                            operations.push(PatchOperation::Add { path: pointer.clone(), value: to_child.clone() })
+                        });
+                    }
+                }
+            }
+            (Value::Array(from_items), Value::Array(to_items)) => {
+                let shared = from_items.len().min(to_items.len());
+                for index in 0..shared {
+                    with_segment(pointer, &index.to_string(), |pointer| {
+                        Self::diff_into(&from_items[index], &to_items[index], pointer, operations)
+                    });
+                }
+                // Remove from the end so earlier indices stay valid while the patch is applied
+                for index in (shared..from_items.len()).rev() {
+                    with_segment(pointer, &index.to_string(), |pointer| {
+                        operations.push(PatchOperation::Remove { path: pointer.clone() })
+                    });
+                }
+                for (index, item) in to_items.iter().enumerate().skip(shared) {
+                    with_segment(pointer, &index.to_string(), |pointer| {
+                        operations.push(PatchOperation::Add { path: pointer.clone(), value: item.clone() })
+                    });
+                }
+            }
+            _ if from != to => operations.push(PatchOperation::Replace { path: pointer.clone(), value: to.clone() }),
+            _ => {}
+        }
+    }
+
+    /// Apply a JSON Patch in order; `value` is left untouched if any operation fails
+    pub fn apply_patch(value: &mut Value, patch: &JsonPatch) -> Result<()> {
+        let mut patched = value.clone();
+        for operation in &patch.0 {
+            Self::apply_operation(&mut patched, operation)
+                .map_err(|e| SidecarError::Schema(format!("Cannot apply {:?} at '{}': {}", operation, operation.path(), e)))?;
+        }
+        *value = patched;
+        Ok(())
+    }
+
+    fn apply_operation(value: &mut Value, operation: &PatchOperation) -> Result<(), String> {
+        let path = operation.path();
+        if path.is_empty() {
+            return match operation {
+                PatchOperation::Add { value: new, .. } | PatchOperation::Replace { value: new, .. } => {
+                    *value = new.clone();
+                    Ok(())
+                }
+                PatchOperation::Remove { .. } => Err("cannot remove the document root".to_string()),
+            };
+        }
+
+        let (parent_path, last) = path.rsplit_once('/')
+            .ok_or_else(|| "JSON Pointer must start with '/'".to_string())?;
+        let key = unescape_segment(last);
+        let parent = value.pointer_mut(parent_path)
+            .ok_or_else(|| format!("parent '{}' does not exist", parent_path))?;
+
+        match (parent, operation) {
+            (Value::Object(map), PatchOperation::Add { value: new, .. }) => {
+                map.insert(key, new.clone());
+            }
+            (Value::Object(map), PatchOperation::Replace { value: new, .. }) => {
+                *map.get_mut(&key).ok_or_else(|| "key does not exist".to_string())? = new.clone();
+            }
+            (Value::Object(map), PatchOperation::Remove { .. }) => {
+                map.remove(&key).ok_or_else(|| "key does not exist".to_string())?;
+            }
+            (Value::Array(items), operation) => {
+                let index = if key == "-" { items.len() } else { key.parse::<usize>().map_err(|e| e.to_string())? };
+                match operation {
+                    PatchOperation::Add { value: new, .. } if index <= items.len() => items.insert(index, new.clone()),
+                    PatchOperation::Replace { value: new, .. } if index < items.len() => items[index] = new.clone(),
+                    PatchOperation::Remove { .. } if index < items.len() => {
+                        items.remove(index);
+                    }
+                    _ => return Err(format!("index {} out of bounds", index)),
+                }
+            }
+            _ => return Err("parent is not a container".to_string()),
+        }
+        Ok(())
+    }
+
+    /// Merge two edits of the same `base` document
+    ///
+    /// Object keys merge independently, so edits to different fields combine. A value
+    /// changed differently on both sides (including arrays, which merge as a whole) is
+    /// a conflict: the merged document keeps our side and the conflict is reported.
+    pub fn three_way_merge(base: &Value, ours: &Value, theirs: &Value) -> MergeResult {
+        let mut conflicts = Vec::new();
+        let merged = Self::merge3(Some(base), Some(ours), Some(theirs), &mut String::new(), &mut conflicts)
+            .unwrap_or(Value::Null);
+        MergeResult { merged, conflicts }
+    }
+
+    fn merge3(
+        base: Option<&Value>,
+        ours: Option<&Value>,
+        theirs: Option<&Value>,
+        pointer: &mut String,
+        conflicts: &mut Vec<MergeConflict>,
+    ) -> Option<Value> {
+        if ours == theirs || theirs == base {
+            return ours.cloned();
+        }
+        if ours == base {
+            return theirs.cloned();
+        }
+
+        if let (Some(Value::Object(ours_map)), Some(Value::Object(theirs_map))) = (ours, theirs) {
+            let empty = serde_json::Map::new();
+            let base_map = base.and_then(Value::as_object).unwrap_or(&empty);
+
+            let mut keys: Vec<&String> = ours_map.keys().chain(theirs_map.keys()).chain(base_map.keys()).collect();
+            keys.sort();
+            keys.dedup();
+
+            let mut merged = serde_json::Map::new();
+            for key in keys {
+                let value = with_segment(pointer, key, |pointer| {
+                    Self::merge3(base_map.get(key), ours_map.get(key), theirs_map.get(key), pointer, conflicts)
+                });
+                if let Some(value) = value {
+                    merged.insert(key.clone(), value);
+                }
+            }
+            return Some(Value::Object(merged));
+        }
+
+        conflicts.push(MergeConflict {
+            path: pointer.clone(),
+            ours: ours.cloned(),
+            theirs: theirs.cloned(),
+        });
+        ours.cloned()
+    }
+
     /// Pretty print JSON with consistent formatting
     pub fn pretty_print(value: &Value) -> Result<String> {
         serde_json::to_string_pretty(value)
-            .map_err(|e| anyhow::anyhow!("Failed to pretty print JSON: {}", e))
+            .map_err(SidecarError::from)
     }
 
     /// Compact print JSON
     pub fn compact_print(value: &Value) -> Result<String> {
         serde_json::to_string(value)
-            .map_err(|e| anyhow::anyhow!("Failed to compact print JSON: {}", e))
+            .map_err(SidecarError::from)
     }
 }
+
+/// Run `f` with `segment` appended to `pointer`, escaped per RFC 6901
+fn with_segment<T>(pointer: &mut String, segment: &str, f: impl FnOnce(&mut String) -> T) -> T {
+    let length = pointer.len();
+    pointer.push('/');
+    pointer.push_str(&segment.replace('~', "~0").replace('/', "~1"));
+    let result = f(pointer);
+    pointer.truncate(length);
+    result
+}
+
+fn unescape_segment(segment: &str) -> String {
+    segment.replace("~1", "/").replace("~0", "~")
+}
@@ -14,5 +14,6 @@
  */
 
 pub mod json;
+pub mod paths;
 
-pub use json::JsonUtils;
+pub use json::{JsonPatch, JsonUtils, MergeConflict, MergeResult, PatchOperation};
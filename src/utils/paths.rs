@@ -0,0 +1,306 @@
+/*
+ * Context: Platform path handling for sportball-sidecar-rust: Windows long paths and links
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: std only
+ *
+ * The string transforms here are plain functions so every CI host can test them;
+ * only `long_path`, `display_path` and `symlink` change behaviour by platform.
+ *
+ * Paths that are not valid UTF-8 are written out percent-encoded rather than through
+ * `to_string_lossy`, so a name such as Latin-1 `caf\xe9.jpg` survives a trip through JSON.
+ */
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// Key of the JSON object standing in for a path that is not valid UTF-8
+pub const PERCENT_ENCODED_KEY: &str = "percent_encoded";
+
+/// Longest path Win32 APIs accept without the `\\?\` prefix, terminator included
+pub const MAX_PATH: usize = 260;
+
+/// The `\\?\` form of an absolute Windows path, or `None` if it already is one or isn't absolute
+///
+/// Verbatim paths are passed to the file system untouched, so `/` becomes `\` here;
+/// `.` and `..` components must already be resolved.
+pub fn verbatim(path: &str) -> Option<String> {
+    if path.starts_with(r"\\?\") || path.starts_with(r"\\.\") {
+        return None;
+    }
+    let path = path.replace('/', "\\");
+    if let Some(share) = path.strip_prefix(r"\\") {
+        return (!share.is_empty()).then(|| format!(r"\\?\UNC\{}", share));
+    }
+    let bytes = path.as_bytes();
+    let has_drive = bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && bytes[2] == b'\\';
+    has_drive.then(|| format!(r"\\?\{}", path))
+}
+
+/// The ordinary form of a `\\?\` path, or `None` if it isn't one or has no ordinary form
+pub fn without_verbatim(path: &str) -> Option<String> {
+    if let Some(share) = path.strip_prefix(r"\\?\UNC\") {
+        return Some(format!(r"\\{}", share));
+    }
+    let rest = path.strip_prefix(r"\\?\")?;
+    let bytes = rest.as_bytes();
+    let has_drive = bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+    has_drive.then(|| rest.to_string())
+}
+
+/// `path` in a form the OS accepts at any length: `\\?\`-prefixed and absolute on Windows, unchanged elsewhere
+pub fn long_path(path: &Path) -> Cow<'_, Path> {
+    #[cfg(windows)]
+    {
+        if let Some(long) = std::path::absolute(path)
+            .ok()
+            .and_then(|absolute| verbatim(&absolute.to_string_lossy()))
+        {
+            return Cow::Owned(PathBuf::from(long));
+        }
+    }
+    Cow::Borrowed(path)
+}
+
+/// `path` without a `\\?\` prefix when the ordinary form fits in `MAX_PATH`, for output and comparison
+///
+/// `canonicalize` returns verbatim paths on Windows; elsewhere this is the identity.
+pub fn display_path(path: PathBuf) -> PathBuf {
+    match without_verbatim(&path.to_string_lossy()) {
+        Some(short) if short.len() < MAX_PATH => PathBuf::from(short),
+        _ => path,
+    }
+}
+
+/// Swap the `from` prefix of `path` for `to`, keeping `path` as is when the result would exceed `MAX_PATH` on Windows
+///
+/// Used to report paths found under a `long_path` root in the form the caller passed in.
+pub fn rebase(path: PathBuf, from: &Path, to: &Path) -> PathBuf {
+    if from == to {
+        return path;
+    }
+    match path.strip_prefix(from) {
+        Ok(rest) => {
+            let rebased = to.join(rest);
+            if cfg!(windows) && rebased.as_os_str().len() >= MAX_PATH {
+                path
+            } else {
+                rebased
+            }
+        }
+        Err(_) => path,
+    }
+}
+
+/// Whether `path` is a link: a symlink, or on Windows also a junction
+pub fn is_link(path: &Path) -> bool {
+    path.symlink_metadata().is_ok_and(|metadata| metadata.file_type().is_symlink())
+}
+
+/// Create a symlink at `link` pointing to `target`
+///
+/// Windows distinguishes file and directory links, so there the kind follows `target`,
+/// read relative to the link's directory; a missing target gets a file link.
+/// Without Developer Mode or elevation Windows refuses with a permission error.
+pub fn symlink(target: impl AsRef<Path>, link: impl AsRef<Path>) -> std::io::Result<()> {
+    let (target, link) = (target.as_ref(), link.as_ref());
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(target, link)
+    }
+    #[cfg(windows)]
+    {
+        let resolved = link_target(link, target);
+        if resolved.is_dir() {
+            std::os::windows::fs::symlink_dir(target, link)
+        } else {
+            std::os::windows::fs::symlink_file(target, link)
+        }
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = (target, link);
+        Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "symlinks are not supported on this platform"))
+    }
+}
+
+/// Where a link with the stored target `target` points; relative targets are relative to the link's directory
+pub fn link_target(link: &Path, target: &Path) -> PathBuf {
+    match link.parent() {
+        Some(parent) if target.is_relative() => parent.join(target),
+        _ => target.to_path_buf(),
+    }
+}
+
+/// `roots` without repeats or roots nested inside another, so no file is scanned twice
+///
+/// Roots are compared by canonical path, so `data`, `./data/` and a symlink to it are one root;
+/// the first spelling given is kept. Roots that cannot be resolved are compared as given.
+pub fn distinct_roots(roots: &[PathBuf]) -> Vec<PathBuf> {
+    let resolved: Vec<PathBuf> = roots.iter().map(|root| root.canonicalize().unwrap_or_else(|_| root.clone())).collect();
+    roots
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| {
+            !resolved.iter().enumerate().any(|(j, other)| {
+                j != i && resolved[i].starts_with(other) && (resolved[i] != *other || j < i)
+            })
+        })
+        .map(|(_, root)| root.clone())
+        .collect()
+}
+
+/// `path` as text: itself when valid UTF-8, otherwise its raw bytes percent-encoded
+///
+/// Printable ASCII other than `%` is kept, so the encoded name stays readable.
+/// Use this where a column or field can only hold a string; JSON output should
+/// use `path_to_json` or the `lossless` serde module, which mark the encoding.
+pub fn encode_path(path: &Path) -> Cow<'_, str> {
+    match path.to_str() {
+        Some(text) => Cow::Borrowed(text),
+        None => Cow::Owned(percent_encode(path.as_os_str().as_encoded_bytes())),
+    }
+}
+
+/// Reverse `encode_path` for a path known to be encoded; `None` for a malformed escape
+///
+/// Off Unix the decoded bytes must be UTF-8, since Windows names cannot be rebuilt from raw bytes.
+pub fn decode_path(text: &str) -> Option<PathBuf> {
+    let bytes = percent_decode(text)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStringExt;
+        Some(PathBuf::from(std::ffi::OsString::from_vec(bytes)))
+    }
+    #[cfg(not(unix))]
+    {
+        String::from_utf8(bytes).ok().map(PathBuf::from)
+    }
+}
+
+/// `path` as JSON: a string, or `{"percent_encoded": ...}` when it is not valid UTF-8
+pub fn path_to_json(path: &Path) -> Value {
+    match path.to_str() {
+        Some(text) => Value::String(text.to_string()),
+        None => serde_json::json!({ PERCENT_ENCODED_KEY: encode_path(path) }),
+    }
+}
+
+/// Read a path written by `path_to_json`
+pub fn path_from_json(value: &Value) -> Option<PathBuf> {
+    match value {
+        Value::String(text) => Some(PathBuf::from(text)),
+        Value::Object(object) => object.get(PERCENT_ENCODED_KEY)?.as_str().and_then(decode_path),
+        _ => None,
+    }
+}
+
+/// Write `path` and a newline as the raw bytes of the name, for line-oriented lists other tools read
+pub fn write_path_line(writer: &mut impl std::io::Write, path: &Path) -> std::io::Result<()> {
+    writer.write_all(path.as_os_str().as_encoded_bytes())?;
+    writer.write_all(b"\n")
+}
+
+/// Rebuild a path from one line written by `write_path_line`
+///
+/// Off Unix, bytes that are not valid UTF-8 are replaced, as Windows names cannot be rebuilt from them.
+pub fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+    }
+    #[cfg(not(unix))]
+    {
+        PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+    }
+}
+
+fn percent_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len());
+    for &byte in bytes {
+        if byte.is_ascii_graphic() && byte != b'%' || byte == b' ' {
+            encoded.push(byte as char);
+        } else {
+            let _ = write!(encoded, "%{:02X}", byte);
+        }
+    }
+    encoded
+}
+
+fn percent_decode(text: &str) -> Option<Vec<u8>> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(index + 1..index + 3)?).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            index += 3;
+        } else {
+            decoded.push(bytes[index]);
+            index += 1;
+        }
+    }
+    Some(decoded)
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PathRepr {
+    Text(String),
+    Encoded { percent_encoded: String },
+}
+
+impl PathRepr {
+    fn into_path<E: serde::de::Error>(self) -> std::result::Result<PathBuf, E> {
+        match self {
+            PathRepr::Text(text) => Ok(PathBuf::from(text)),
+            PathRepr::Encoded { percent_encoded } => decode_path(&percent_encoded)
+                .ok_or_else(|| E::custom(format!("invalid percent-encoded path '{}'", percent_encoded))),
+        }
+    }
+}
+
+/// Serde adapter for `PathBuf` fields in the `path_to_json` form, so serializing never fails on a non-UTF-8 name
+pub mod lossless {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(path: &Path, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        path_to_json(path).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<PathBuf, D::Error> {
+        PathRepr::deserialize(deserializer)?.into_path()
+    }
+}
+
+/// `lossless` for `Vec<PathBuf>` fields
+pub mod lossless_vec {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(paths: &[PathBuf], serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_seq(paths.iter().map(|path| path_to_json(path)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Vec<PathBuf>, D::Error> {
+        Vec::<PathRepr>::deserialize(deserializer)?.into_iter().map(PathRepr::into_path).collect()
+    }
+}
+
+/// `lossless` for `Option<PathBuf>` fields
+pub mod lossless_opt {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(path: &Option<PathBuf>, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        path.as_deref().map(path_to_json).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Option<PathBuf>, D::Error> {
+        Option::<PathRepr>::deserialize(deserializer)?.map(PathRepr::into_path).transpose()
+    }
+}
@@ -0,0 +1,265 @@
+/*
+ * Context: Hierarchical navigable small world graph for approximate nearest neighbors
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde
+ */
+
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashSet};
+
+/// Approximate nearest-neighbor graph over fixed-dimension vectors (Euclidean distance)
+///
+/// Follows Malkov & Yashunin: each node is assigned a random top layer, upper layers
+/// are searched greedily for an entry point and layer 0 is searched with a beam of
+/// width `ef`. Level assignment uses a fixed-seed generator so builds are reproducible.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hnsw {
+    dimension: usize,
+    /// Maximum links per node on upper layers; layer 0 allows twice as many
+    max_links: usize,
+    ef_construction: usize,
+    vectors: Vec<Vec<f32>>,
+    /// `links[node][layer]` holds the neighbor ids of `node` on `layer`
+    links: Vec<Vec<Vec<u32>>>,
+    entry_point: Option<u32>,
+    rng_state: u64,
+}
+
+impl Hnsw {
+    /// Create an empty graph for vectors of `dimension` components
+    pub fn new(dimension: usize) -> Self {
+        Self::with_params(dimension, 16, 200)
+    }
+
+    /// Create an empty graph with explicit link count and construction beam width
+    pub fn with_params(dimension: usize, max_links: usize, ef_construction: usize) -> Self {
+        Self {
+            dimension,
+            max_links: max_links.max(2),
+            ef_construction: ef_construction.max(1),
+            vectors: Vec::new(),
+            links: Vec::new(),
+            entry_point: None,
+            rng_state: 0x9E37_79B9_7F4A_7C15,
+        }
+    }
+
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    pub fn len(&self) -> usize {
+        self.vectors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vectors.is_empty()
+    }
+
+    /// Describe the first broken invariant, for graphs read back from disk
+    ///
+    /// Searching and inserting index vectors and links directly, so a truncated or corrupt
+    /// graph that passed deserialization must be rejected before use rather than panic later.
+    pub fn check(&self) -> Result<(), String> {
+        if self.links.len() != self.vectors.len() {
+            return Err(format!("{} vectors but {} link lists", self.vectors.len(), self.links.len()));
+        }
+        if self.max_links < 2 || self.ef_construction == 0 {
+            return Err("invalid construction parameters".to_string());
+        }
+        if let Some(node) = self.vectors.iter().position(|vector| vector.len() != self.dimension) {
+            return Err(format!("node {} does not have {} dimensions", node, self.dimension));
+        }
+        for (node, layers) in self.links.iter().enumerate() {
+            if layers.is_empty() {
+                return Err(format!("node {} has no layers", node));
+            }
+            for (layer, neighbors) in layers.iter().enumerate() {
+                let dangling = neighbors.iter().find(|&&neighbor| self.links.get(neighbor as usize).is_none_or(|links| links.len() <= layer));
+                if let Some(neighbor) = dangling {
+                    return Err(format!("node {} links to missing node {} on layer {}", node, neighbor, layer));
+                }
+            }
+        }
+        match self.entry_point {
+            None if !self.vectors.is_empty() => Err("no entry point".to_string()),
+            Some(entry_point) if entry_point as usize >= self.vectors.len() => Err(format!("entry point {} is missing", entry_point)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Vector stored for node `id`
+    pub fn vector(&self, id: usize) -> Option<&[f32]> {
+        self.vectors.get(id).map(Vec::as_slice)
+    }
+
+    /// Add a vector, returning its node id
+    /// Panics if `vector` does not have the graph's dimension
+    pub fn insert(&mut self, vector: Vec<f32>) -> usize {
+        assert_eq!(vector.len(), self.dimension, "vector dimension mismatch");
+
+        let id = self.vectors.len() as u32;
+        let level = self.random_level();
+        self.vectors.push(vector);
+        self.links.push(vec![Vec::new(); level + 1]);
+
+        let Some(entry_point) = self.entry_point else {
+            self.entry_point = Some(id);
+            return id as usize;
+        };
+
+        let query = self.vectors[id as usize].clone();
+        let top_level = self.level_of(entry_point);
+        let mut entry_points = vec![entry_point];
+
+        for layer in (level + 1..=top_level).rev() {
+            entry_points = self.search_layer(&query, &entry_points, 1, layer)
+                .into_iter()
+                .map(|(_, node)| node)
+                .collect();
+        }
+
+        for layer in (0..=level.min(top_level)).rev() {
+            let candidates = self.search_layer(&query, &entry_points, self.ef_construction, layer);
+            let limit = self.link_limit(layer);
+            let neighbors: Vec<u32> = candidates.iter().take(limit).map(|&(_, node)| node).collect();
+
+            for &neighbor in &neighbors {
+                self.links[neighbor as usize][layer].push(id);
+                if self.links[neighbor as usize][layer].len() > limit {
+                    self.prune(neighbor, layer, limit);
+                }
+            }
+            self.links[id as usize][layer] = neighbors;
+            entry_points = candidates.into_iter().map(|(_, node)| node).collect();
+        }
+
+        if level > top_level {
+            self.entry_point = Some(id);
+        }
+
+        id as usize
+    }
+
+    /// Find up to `k` approximate nearest neighbors of `query` as `(node id, distance)` pairs,
+    /// closest first. Larger `ef` trades speed for recall.
+    pub fn search(&self, query: &[f32], k: usize, ef: usize) -> Vec<(usize, f32)> {
+        let Some(entry_point) = self.entry_point else {
+            return Vec::new();
+        };
+        if query.len() != self.dimension || k == 0 {
+            return Vec::new();
+        }
+
+        let mut entry_points = vec![entry_point];
+        for layer in (1..=self.level_of(entry_point)).rev() {
+            entry_points = self.search_layer(query, &entry_points, 1, layer)
+                .into_iter()
+                .map(|(_, node)| node)
+                .collect();
+        }
+
+        self.search_layer(query, &entry_points, ef.max(k), 0)
+            .into_iter()
+            .take(k)
+            .map(|(distance, node)| (node as usize, distance.0))
+            .collect()
+    }
+
+    /// Beam search on one layer, returning `(distance, node)` sorted closest first
+    fn search_layer(&self, query: &[f32], entry_points: &[u32], ef: usize, layer: usize) -> Vec<(Distance, u32)> {
+        let mut visited: HashSet<u32> = entry_points.iter().copied().collect();
+        let mut candidates = BinaryHeap::new();
+        let mut results = BinaryHeap::new();
+
+        for &node in entry_points {
+            let distance = Distance(euclidean(query, &self.vectors[node as usize]));
+            candidates.push(Reverse((distance, node)));
+            results.push((distance, node));
+        }
+
+        while let Some(Reverse((distance, node))) = candidates.pop() {
+            let furthest = results.peek().map(|&(d, _)| d).unwrap_or(distance);
+            if distance > furthest && results.len() >= ef {
+                break;
+            }
+
+            let Some(neighbors) = self.links[node as usize].get(layer) else {
+                continue;
+            };
+            for &neighbor in neighbors {
+                if !visited.insert(neighbor) {
+                    continue;
+                }
+                let distance = Distance(euclidean(query, &self.vectors[neighbor as usize]));
+                let furthest = results.peek().map(|&(d, _)| d);
+                if results.len() < ef || furthest.is_some_and(|f| distance < f) {
+                    candidates.push(Reverse((distance, neighbor)));
+                    results.push((distance, neighbor));
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+
+        results.into_sorted_vec()
+    }
+
+    /// Keep only the `limit` closest links of `node` on `layer`
+    fn prune(&mut self, node: u32, layer: usize, limit: usize) {
+        let origin = &self.vectors[node as usize];
+        let mut scored: Vec<(Distance, u32)> = self.links[node as usize][layer]
+            .iter()
+            .map(|&neighbor| (Distance(euclidean(origin, &self.vectors[neighbor as usize])), neighbor))
+            .collect();
+        scored.sort_unstable();
+        self.links[node as usize][layer] = scored.into_iter().take(limit).map(|(_, n)| n).collect();
+    }
+
+    fn level_of(&self, node: u32) -> usize {
+        self.links[node as usize].len() - 1
+    }
+
+    fn link_limit(&self, layer: usize) -> usize {
+        if layer == 0 { self.max_links * 2 } else { self.max_links }
+    }
+
+    /// Draw a layer from the exponential distribution with normalization 1/ln(M)
+    fn random_level(&mut self) -> usize {
+        // xorshift64*
+        self.rng_state ^= self.rng_state >> 12;
+        self.rng_state ^= self.rng_state << 25;
+        self.rng_state ^= self.rng_state >> 27;
+        let bits = self.rng_state.wrapping_mul(0x2545_F491_4F6C_DD1D);
+        let uniform = ((bits >> 11) as f64 + 1.0) / (1u64 << 53) as f64;
+
+        let level = -uniform.ln() / (self.max_links as f64).ln();
+        (level as usize).min(16)
+    }
+}
+
+/// Totally ordered distance so it can live in a heap
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Distance(f32);
+
+impl Eq for Distance {}
+
+impl PartialOrd for Distance {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Distance {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+fn euclidean(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum::<f32>().sqrt()
+}
@@ -0,0 +1,200 @@
+/*
+ * Context: Persistent face-encoding index keyed by image
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde, serde_json, bincode
+ */
+
+use crate::sidecar::types::SidecarError;
+use crate::vectors::hnsw::Hnsw;
+use crate::sidecar::types::Result;
+use bincode::Options;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Which face of which image a vector in the index belongs to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VectorEntry {
+    pub image_path: PathBuf,
+    /// Position of the face in the sidecar's `faces` array
+    pub face_index: usize,
+}
+
+/// One search hit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Neighbor {
+    pub image_path: PathBuf,
+    pub face_index: usize,
+    /// Euclidean distance between the encodings
+    pub distance: f32,
+}
+
+/// Approximate nearest-neighbor index over face encodings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VectorIndex {
+    entries: Vec<VectorEntry>,
+    graph: Hnsw,
+}
+
+impl VectorIndex {
+    /// Create an empty index for encodings of `dimension` components
+    pub fn new(dimension: usize) -> Self {
+        Self {
+            entries: Vec::new(),
+            graph: Hnsw::new(dimension),
+        }
+    }
+
+    /// Build an index from every face encoding found in the given sidecars
+    /// The dimension is taken from the first encoding; encodings of any other size are skipped
+    pub fn build(sidecars: Vec<(PathBuf, Value)>) -> Self {
+        let dimension = sidecars
+            .iter()
+            .flat_map(|(_, data)| face_encodings(data))
+            .map(|(_, encoding)| encoding.len())
+            .next()
+            .unwrap_or(0);
+        let mut index = Self::new(dimension);
+
+        for (image_path, data) in sidecars {
+            for (face_index, encoding) in face_encodings(&data) {
+                if let Err(e) = index.insert(image_path.clone(), face_index, encoding) {
+                    tracing::warn!("Skipping encoding for {:?}: {}", image_path, e);
+                }
+            }
+        }
+
+        index
+    }
+
+    /// Add one encoding to the index
+    pub fn insert(&mut self, image_path: PathBuf, face_index: usize, encoding: Vec<f32>) -> Result<()> {
+        if encoding.len() != self.graph.dimension() {
+            return Err(SidecarError::ValidationFailed(format!(
+                "expected {} dimensions, got {}",
+                self.graph.dimension(),
+                encoding.len()
+            )));
+        }
+
+        self.graph.insert(encoding);
+        self.entries.push(VectorEntry { image_path, face_index });
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn dimension(&self) -> usize {
+        self.graph.dimension()
+    }
+
+    /// Write the index to disk
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let bytes = bincode::serialize(self)?;
+        std::fs::write(path, bytes)?;
+        Ok(())
+    }
+
+    /// Read an index written by `save`
+    /// A truncated or corrupt file fails with `SidecarError::Schema` instead of panicking on use.
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes = std::fs::read(path)?;
+        let invalid = |reason: String| SidecarError::Schema(format!("{} is not a valid vector index: {}", path.display(), reason));
+        // Length prefixes are bounded by the file, so a corrupt one cannot ask for huge allocations
+        let index: Self = bincode::DefaultOptions::new()
+            .with_fixint_encoding()
+            .with_limit(bytes.len() as u64)
+            .allow_trailing_bytes()
+            .deserialize(&bytes)
+            .map_err(|e| invalid(e.to_string()))?;
+        if index.entries.len() != index.graph.len() {
+            return Err(invalid(format!("{} entries for {} vectors", index.entries.len(), index.graph.len())));
+        }
+        index.graph.check().map_err(invalid)?;
+        Ok(index)
+    }
+
+    /// Find up to `k` faces closest to `encoding`
+    pub fn search(&self, encoding: &[f32], k: usize) -> Vec<Neighbor> {
+        self.graph
+            .search(encoding, k, search_width(k))
+            .into_iter()
+            .map(|(id, distance)| Neighbor {
+                image_path: self.entries[id].image_path.clone(),
+                face_index: self.entries[id].face_index,
+                distance,
+            })
+            .collect()
+    }
+
+    /// Find up to `k` faces in other images closest to any face of `image`
+    pub fn find_similar(&self, image: &Path, k: usize) -> Result<Vec<Neighbor>> {
+        let own: Vec<usize> = self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.image_path == image)
+            .map(|(id, _)| id)
+            .collect();
+        if own.is_empty() {
+            return Err(SidecarError::ImageNotFound(image.to_path_buf()));
+        }
+
+        // Over-fetch so hits on the query image itself can be dropped
+        let mut best: HashMap<(PathBuf, usize), f32> = HashMap::new();
+        for id in own.iter().copied() {
+            let Some(encoding) = self.graph.vector(id) else {
+                continue;
+            };
+            for hit in self.search(encoding, k + own.len()) {
+                if hit.image_path == image {
+                    continue;
+                }
+                let distance = best.entry((hit.image_path, hit.face_index)).or_insert(f32::INFINITY);
+                *distance = distance.min(hit.distance);
+            }
+        }
+
+        let mut neighbors: Vec<Neighbor> = best
+            .into_iter()
+            .map(|((image_path, face_index), distance)| Neighbor { image_path, face_index, distance })
+            .collect();
+        neighbors.sort_by(|a, b| a.distance.total_cmp(&b.distance).then_with(|| a.image_path.cmp(&b.image_path)));
+        neighbors.truncate(k);
+        Ok(neighbors)
+    }
+}
+
+/// Beam width used for queries; wider than `k` for better recall
+fn search_width(k: usize) -> usize {
+    (k * 4).max(64)
+}
+
+/// Face encodings in a sidecar as `(face index, encoding)` pairs
+/// Reads `face_detection.faces[*].encoding`, also accepting a top-level `faces` array
+pub fn face_encodings(data: &Value) -> Vec<(usize, Vec<f32>)> {
+    let faces = data
+        .get("face_detection")
+        .unwrap_or(data)
+        .get("faces")
+        .and_then(Value::as_array);
+
+    faces
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .filter_map(|(face_index, face)| {
+            let encoding = face.get("encoding")?.as_array()?;
+            let vector: Option<Vec<f32>> = encoding.iter().map(|v| v.as_f64().map(|f| f as f32)).collect();
+            vector.filter(|v| !v.is_empty()).map(|v| (face_index, v))
+        })
+        .collect()
+}
@@ -0,0 +1,13 @@
+/*
+ * Context: Embedding storage and nearest-neighbor search over face encodings
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: serde, bincode
+ */
+
+pub mod hnsw;
+pub mod index;
+
+pub use hnsw::Hnsw;
+pub use index::{face_encodings, Neighbor, VectorEntry, VectorIndex};
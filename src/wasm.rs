@@ -0,0 +1,121 @@
+/*
+ * Context: wasm-bindgen wrappers for inspecting in-memory sidecar buffers in the browser
+ *
+ * Technical details:
+ * - Code style: Rust idiomatic with comprehensive error handling
+ * - Dependencies: wasm-bindgen, serde_json, bincode
+ */
+
+//! Only the format and validation layers are available here: no runtime, no filesystem.
+//! Build with `wasm-pack build --target web -- --features wasm`.
+
+use crate::sidecar::formats::{FormatManager, SidecarFormat};
+use crate::sidecar::types::{OperationType, Result, SidecarError};
+use crate::utils::json::JsonUtils;
+use serde::Serialize;
+use serde_json::Value;
+use std::path::Path;
+use wasm_bindgen::prelude::*;
+
+/// Outcome of checking one uploaded sidecar
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Inspection {
+    pub is_valid: bool,
+    pub error: Option<String>,
+    /// `json`, `bin` or `rkyv`; `None` when the buffer could not be decoded
+    pub format: Option<String>,
+    /// Operation keys found at the top level, sorted
+    pub operations: Vec<String>,
+    pub size: usize,
+}
+
+/// Decode `bytes`, taking the format from `file_name`'s extension when there is one
+pub fn decode(bytes: &[u8], file_name: Option<&str>) -> Result<(SidecarFormat, Value)> {
+    let formats = FormatManager::new();
+
+    if let Some(format) = file_name.and_then(|name| SidecarFormat::from_path(Path::new(name))) {
+        return Ok((format, formats.get_serializer(format).deserialize(bytes)?));
+    }
+
+    // Without a usable name, JSON is tried first since a bincode string prefix is never valid JSON
+    [SidecarFormat::Json, SidecarFormat::Binary]
+        .into_iter()
+        .find_map(|format| formats.get_serializer(format).deserialize(bytes).ok().map(|data| (format, data)))
+        .ok_or_else(|| SidecarError::SerializationError("Buffer is neither a JSON nor a binary sidecar".to_string()))
+}
+
+/// Decode and structurally validate `bytes`; never fails, errors are reported in the result
+pub fn inspect(bytes: &[u8], file_name: Option<&str>) -> Inspection {
+    let mut inspection = Inspection {
+        is_valid: false,
+        error: None,
+        format: None,
+        operations: Vec::new(),
+        size: bytes.len(),
+    };
+
+    let data = match decode(bytes, file_name) {
+        Ok((format, data)) => {
+            inspection.format = Some(format.extension().to_string());
+            data
+        }
+        Err(e) => {
+            inspection.error = Some(e.to_string());
+            return inspection;
+        }
+    };
+
+    match JsonUtils::validate_sidecar_structure(&data) {
+        Ok(()) => inspection.is_valid = true,
+        Err(e) => inspection.error = Some(e.to_string()),
+    }
+    if let Some(object) = data.as_object() {
+        inspection.operations = object
+            .keys()
+            .filter(|key| OperationType::from_str(key) != OperationType::Unknown)
+            .cloned()
+            .collect();
+        inspection.operations.sort();
+    }
+
+    inspection
+}
+
+/// Re-encode `bytes` as `target` (`json`, `bin` or `rkyv`)
+pub fn convert(bytes: &[u8], file_name: Option<&str>, target: &str) -> Result<Vec<u8>> {
+    let target = SidecarFormat::from_name(target).ok_or_else(|| {
+        SidecarError::ProcessingError(format!("Unsupported format: {}. Supported formats: json, bin, rkyv", target))
+    })?;
+    let (source, data) = decode(bytes, file_name)?;
+    Ok(FormatManager::new().convert_format(&data, source, target)?)
+}
+
+fn js_error(error: SidecarError) -> JsError {
+    JsError::new(&error.to_string())
+}
+
+/// Format of an uploaded sidecar: `json`, `bin` or `rkyv`
+#[wasm_bindgen(js_name = detectFormat)]
+pub fn detect_format(bytes: &[u8], file_name: Option<String>) -> std::result::Result<String, JsError> {
+    let (format, _) = decode(bytes, file_name.as_deref()).map_err(js_error)?;
+    Ok(format.extension().to_string())
+}
+
+/// Sidecar contents as JSON text, whatever the uploaded format
+#[wasm_bindgen(js_name = parseSidecar)]
+pub fn parse_sidecar(bytes: &[u8], file_name: Option<String>) -> std::result::Result<String, JsError> {
+    let (_, data) = decode(bytes, file_name.as_deref()).map_err(js_error)?;
+    serde_json::to_string(&data).map_err(|e| js_error(e.into()))
+}
+
+/// Validation report as JSON text: `{is_valid, error, format, operations, size}`
+#[wasm_bindgen(js_name = validateSidecar)]
+pub fn validate_sidecar(bytes: &[u8], file_name: Option<String>) -> std::result::Result<String, JsError> {
+    serde_json::to_string(&inspect(bytes, file_name.as_deref())).map_err(|e| js_error(e.into()))
+}
+
+/// The sidecar re-encoded as `target` (`json`, `bin` or `rkyv`), e.g. for download
+#[wasm_bindgen(js_name = convertSidecar)]
+pub fn convert_sidecar(bytes: &[u8], target: &str, file_name: Option<String>) -> std::result::Result<Vec<u8>, JsError> {
+    convert(bytes, file_name.as_deref(), target).map_err(js_error)
+}
@@ -1,4 +1,4 @@
-/**
+/*
  * This code written by Claude Sonnet 4 (claude-3-5-sonnet-20241022)
  * Generated via Cursor IDE (cursor.sh) with AI assistance
  * Model: Anthropic Claude 3.5 Sonnet
@@ -13,12 +13,19 @@
  * - Dependencies: tempfile, tokio
  */
 
-use image_sidecar_rust::ImageSidecar;
-use image_sidecar_rust::sidecar::OperationType;
+use image_sidecar_rust::{
+    ExtractPath, ImageSidecar, NdjsonSink, ParallelProcessor, ProgressCallback, QueryExpr, SidecarFormat, TimeBucket,
+    ValidationResult, VectorIndex, BoxConvention, BoxFormat, CoordinateSpace, ReportFormat, Manifest,
+    JsonPatch, JsonUtils, PatchOperation, Config, MergeStrategy, SidecarError, ValidationSummary, Mutation, MutationLog, MutationRecord,
+    Provenance, BlobStore, Pack, RedactAction, RedactKey, RedactRule, SidecarSigner, SignatureProblem, SignatureVerifier, FormatManager, SnapshotStore, RecoverySummary,
+};
+use image_sidecar_rust::sidecar::{redact, OperationType};
+use image_sidecar_rust::utils::paths;
 use tempfile::TempDir;
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use serde_json::json;
-use bincode;
 
 #[tokio::test]
 async fn test_sidecar_creation_and_validation() {
@@ -166,7 +173,7 @@ async fn test_symlink_handling() {
     
     // Create symlink to image
     let symlink_path = temp_dir.path().join("symlink.jpg");
-    std::os::unix::fs::symlink(&actual_image, &symlink_path).unwrap();
+    paths::symlink(&actual_image, &symlink_path).unwrap();
     
     // Create sidecar next to actual image
     let sidecar_path = temp_dir.path().join("actual.json");
@@ -192,3 +199,4308 @@ async fn test_symlink_handling() {
     // The sidecar is associated with the actual image, not the symlink
     assert!(info.symlink_info.is_none());
 }
+
+#[tokio::test]
+async fn test_progress_callback_reports_every_file() {
+    let temp_dir = TempDir::new().unwrap();
+    
+    for i in 0..4 {
+        let sidecar_path = temp_dir.path().join(format!("test_{}.json", i));
+        fs::write(&sidecar_path, serde_json::to_string(&json!({"count": i})).unwrap()).unwrap();
+    }
+    
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let recorded = calls.clone();
+    let progress: ProgressCallback = Arc::new(move |done, total, _path| {
+        recorded.lock().unwrap().push((done, total));
+    });
+    
+    let sidecar = ImageSidecar::new(Some(2));
+    let results = sidecar.validate_sidecars_with_progress(temp_dir.path(), Some(progress)).await.unwrap();
+    
+    let mut calls = calls.lock().unwrap().clone();
+    calls.sort();
+    assert_eq!(results.len(), 4);
+    assert_eq!(calls, vec![(1, 4), (2, 4), (3, 4), (4, 4)]);
+
+    // Conversion reports a file only once it has been converted
+    let converted_before_report = Arc::new(Mutex::new(Vec::new()));
+    let recorded = converted_before_report.clone();
+    let progress: ProgressCallback = Arc::new(move |_done, _total, path| {
+        recorded.lock().unwrap().push(path.with_extension("bin").exists());
+    });
+    let converted = sidecar.convert_directory_format_with_progress(temp_dir.path(), SidecarFormat::Binary, Some(progress)).await.unwrap();
+    assert_eq!(converted, 4);
+    assert_eq!(*converted_before_report.lock().unwrap(), vec![true; 4]);
+}
+
+#[tokio::test]
+async fn test_max_workers_limits_validation_threads() {
+    let temp_dir = TempDir::new().unwrap();
+    
+    for i in 0..32 {
+        let sidecar_path = temp_dir.path().join(format!("test_{}.json", i));
+        fs::write(&sidecar_path, serde_json::to_string(&json!({"count": i})).unwrap()).unwrap();
+    }
+    
+    let threads = Arc::new(Mutex::new(std::collections::HashSet::new()));
+    let recorded = threads.clone();
+    let progress: ProgressCallback = Arc::new(move |_done, _total, _path| {
+        recorded.lock().unwrap().insert(std::thread::current().id());
+    });
+    
+    let sidecar = ImageSidecar::new(Some(2));
+    assert_eq!(sidecar.max_workers(), 2);
+    
+    let results = sidecar.validate_sidecars_with_progress(temp_dir.path(), Some(progress)).await.unwrap();
+    assert_eq!(results.len(), 32);
+    assert!(threads.lock().unwrap().len() <= 2);
+}
+
+#[tokio::test]
+async fn test_io_limits_pace_validation_and_conversion() {
+    use image_sidecar_rust::IoLimits;
+    use std::time::{Duration, Instant};
+
+    let temp_dir = TempDir::new().unwrap();
+    for i in 0..6 {
+        let sidecar_path = temp_dir.path().join(format!("test_{}.json", i));
+        fs::write(&sidecar_path, serde_json::to_string(&json!({"count": i})).unwrap()).unwrap();
+    }
+
+    // 20 files/sec: the first file opens at once, the other five wait 50ms each
+    let limits = IoLimits { max_files_per_sec: Some(20.0), max_open_files: Some(1), ..IoLimits::default() };
+    let sidecar = ImageSidecar::builder().max_workers(4).io_limits(limits).build();
+
+    let started = Instant::now();
+    let results = sidecar.validate_sidecars(temp_dir.path()).await.unwrap();
+    assert_eq!(results.len(), 6);
+    assert!(results.iter().all(|result| result.is_valid));
+    assert!(started.elapsed() >= Duration::from_millis(240));
+
+    let started = Instant::now();
+    assert_eq!(sidecar.convert_directory_format(temp_dir.path(), SidecarFormat::Binary).await.unwrap(), 6);
+    assert!(started.elapsed() >= Duration::from_millis(240));
+
+    // 0.1 MB/s: each 10 kB read holds back the next one by 100ms
+    let large_dir = TempDir::new().unwrap();
+    for i in 0..3 {
+        let payload = json!({"blob": "x".repeat(10_000 - 12), "i": i});
+        fs::write(large_dir.path().join(format!("large_{}.json", i)), payload.to_string()).unwrap();
+    }
+    let limits = IoLimits { max_read_bytes_per_sec: Some(100_000), ..IoLimits::default() };
+    let sidecar = ImageSidecar::builder().io_limits(limits).build();
+
+    let started = Instant::now();
+    assert_eq!(sidecar.validate_sidecars(large_dir.path()).await.unwrap().len(), 3);
+    assert!(started.elapsed() >= Duration::from_millis(190));
+}
+
+#[tokio::test]
+async fn test_checkpointed_conversion_skips_finished_files_and_background_cli_resumes() {
+    use image_sidecar_rust::Checkpoint;
+    use image_sidecar_rust::sidecar::CHECKPOINT_FILE;
+
+    let temp_dir = TempDir::new().unwrap();
+    for name in ["a", "b", "c"] {
+        fs::write(temp_dir.path().join(format!("{}.json", name)), r#"{"count": 1}"#).unwrap();
+    }
+    let checkpoint_path = temp_dir.path().join(CHECKPOINT_FILE);
+    // An interrupted run finished `a` and crashed while recording the next file
+    fs::write(&checkpoint_path, format!("{}\n{}", temp_dir.path().join("a.json").display(), temp_dir.path().join("b.js").display())).unwrap();
+
+    let mut checkpoint = Checkpoint::open(&checkpoint_path).unwrap();
+    assert_eq!(checkpoint.len(), 1);
+    let sidecar = ImageSidecar::new(None);
+    let summary = sidecar
+        .convert_directory_format_checkpointed(temp_dir.path(), SidecarFormat::Binary, &mut checkpoint, None)
+        .await
+        .unwrap();
+    assert_eq!((summary.converted, summary.resumed, summary.skipped, summary.failed), (2, 1, 0, 0));
+    assert!(temp_dir.path().join("a.json").exists());
+    assert!(temp_dir.path().join("b.bin").exists() && temp_dir.path().join("c.bin").exists());
+    drop(checkpoint);
+    // Both the source and the converted file are recorded
+    assert_eq!(Checkpoint::open(&checkpoint_path).unwrap().len(), 5);
+    // Entries match however the directory is spelled, including through a symlink
+    let alias = TempDir::new().unwrap();
+    paths::symlink(temp_dir.path(), alias.path().join("season")).unwrap();
+    let reopened = Checkpoint::open(&checkpoint_path).unwrap();
+    assert!(reopened.contains(&temp_dir.path().join(".").join("b.json")));
+    assert!(reopened.contains(&alias.path().join("season/c.bin")));
+    drop(reopened);
+
+    // A fresh background run converts what is left and removes the finished checkpoint
+    fs::remove_file(&checkpoint_path).unwrap();
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+        .args(["--background", "--json", "convert", "--input", temp_dir.path().to_str().unwrap(), "--format", "bin"])
+        .output()
+        .unwrap();
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(document["result"]["converted"], 1);
+    assert!(temp_dir.path().join("a.bin").exists());
+    assert!(!checkpoint_path.exists());
+}
+
+#[tokio::test]
+async fn test_conversion_verifies_target_and_keeps_source_on_failure() {
+    use image_sidecar_rust::testing::MemoryStorage;
+    use image_sidecar_rust::StorageBackend;
+
+    let temp_dir = TempDir::new().unwrap();
+    let payload = json!({"Face_detector": {"faces": [{"bbox": [1.5, 2.0, 3.0, 4.0]}]}, "count": 2});
+    for name in ["kept", "blocked"] {
+        fs::write(temp_dir.path().join(format!("{}.json", name)), payload.to_string()).unwrap();
+    }
+
+    let sidecar = ImageSidecar::builder().keep_originals(true).build();
+    let target = sidecar.manager().convert_sidecar_format(&temp_dir.path().join("kept.json"), SidecarFormat::Binary).await.unwrap();
+    assert_eq!(target, temp_dir.path().join("kept.bin"));
+    assert!(temp_dir.path().join("kept.json").exists());
+    assert!(!temp_dir.path().join("kept.bin.tmp").exists());
+
+    // A directory in the way of the target makes the rename fail; the source must survive
+    fs::create_dir(temp_dir.path().join("blocked.bin")).unwrap();
+    let sidecar = ImageSidecar::new(None);
+    assert!(sidecar.manager().convert_sidecar_format(&temp_dir.path().join("blocked.json"), SidecarFormat::Binary).await.is_err());
+    assert!(temp_dir.path().join("blocked.json").exists());
+    assert!(!temp_dir.path().join("blocked.bin.tmp").exists());
+
+    // A copy that reads back wrong is never moved over an existing target
+    #[derive(Debug)]
+    struct Garbling(MemoryStorage);
+    impl StorageBackend for Garbling {
+        fn read(&self, path: &Path) -> image_sidecar_rust::Result<Vec<u8>> {
+            match path.extension() {
+                Some(extension) if extension == "tmp" => Ok(b"garbage".to_vec()),
+                _ => self.0.read(path),
+            }
+        }
+        fn write(&self, path: &Path, bytes: &[u8]) -> image_sidecar_rust::Result<()> { self.0.write(path, bytes) }
+        fn remove(&self, path: &Path) -> image_sidecar_rust::Result<()> { self.0.remove(path) }
+        fn exists(&self, path: &Path) -> bool { self.0.exists(path) }
+        fn list(&self, directory: &Path) -> image_sidecar_rust::Result<Vec<PathBuf>> { self.0.list(directory) }
+    }
+    let storage = MemoryStorage::new();
+    let root = Path::new("/nonexistent");
+    storage.write(&root.join("a.json"), payload.to_string().as_bytes()).unwrap();
+    storage.write(&root.join("a.bin"), b"earlier").unwrap();
+    let sidecar = ImageSidecar::builder().storage(Arc::new(Garbling(storage.clone()))).build();
+    let error = sidecar.manager().convert_sidecar_format(&root.join("a.json"), SidecarFormat::Binary).await.unwrap_err();
+    assert!(error.to_string().contains("could not be read back"), "{error}");
+    assert_eq!(storage.paths(), [root.join("a.bin"), root.join("a.json")]);
+    assert_eq!(storage.get(root.join("a.bin")).unwrap(), b"earlier");
+
+    // The CLI flag keeps the sources as well
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+        .args(["--json", "convert", "--input", temp_dir.path().to_str().unwrap(), "--format", "rkyv", "--keep-original"])
+        .output()
+        .unwrap();
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(document["result"]["failed"], 0);
+    assert!(temp_dir.path().join("blocked.json").exists() && temp_dir.path().join("blocked.rkyv").exists());
+}
+
+#[tokio::test]
+async fn test_convert_files_converts_only_listed_sidecars() {
+    let temp_dir = TempDir::new().unwrap();
+    for name in ["a", "b", "c", "d"] {
+        fs::write(temp_dir.path().join(format!("{}.json", name)), r#"{"count": 1}"#).unwrap();
+    }
+
+    let sidecar = ImageSidecar::new(None);
+    let listed = vec![temp_dir.path().join("a.json"), temp_dir.path().join("missing.json")];
+    let summary = sidecar.convert_files(&listed, SidecarFormat::Binary).await.unwrap();
+    assert_eq!((summary.converted, summary.skipped, summary.failed), (1, 0, 1));
+    assert!(temp_dir.path().join("a.bin").exists());
+    assert!(temp_dir.path().join("b.json").exists());
+
+    // A list file and positional paths combine; a.bin is already converted
+    let list_path = temp_dir.path().join("list.txt");
+    fs::write(&list_path, format!("{}\n\n{}\n", temp_dir.path().join("a.bin").display(), temp_dir.path().join("b.json").display())).unwrap();
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+        .args(["--json", "convert", "--format", "bin", "--files", list_path.to_str().unwrap()])
+        .arg(temp_dir.path().join("c.json"))
+        .output()
+        .unwrap();
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(document["result"]["converted"], 2);
+    assert_eq!(document["result"]["skipped"], 1);
+    assert!(temp_dir.path().join("d.json").exists());
+    assert!(!temp_dir.path().join(image_sidecar_rust::sidecar::CHECKPOINT_FILE).exists());
+}
+
+#[test]
+fn test_cli_reads_file_lists_from_stdin() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("a.json"), r#"{"count": 1}"#).unwrap();
+    fs::write(temp_dir.path().join("a.jpg"), b"").unwrap();
+    fs::write(temp_dir.path().join("b.json"), "{not json").unwrap();
+    fs::write(temp_dir.path().join("unlisted.json"), r#"{"count": 2}"#).unwrap();
+    let list = format!("{}\n{}\n", temp_dir.path().join("a.json").display(), temp_dir.path().join("b.json").display());
+
+    let run = |args: &[&str]| {
+        use std::io::Write;
+        let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+            .arg("--json")
+            .args(args)
+            .args(["--files", "-"])
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(list.as_bytes()).unwrap();
+        let output = child.wait_with_output().unwrap();
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
+
+    let document = run(&["validate"]);
+    assert_eq!(document["result"]["total_files"], 2);
+    assert_eq!(document["result"]["invalid_files"], 1);
+
+    let document = run(&["stats"]);
+    assert_eq!(document["result"]["total_sidecars"], 2);
+    assert_eq!(document["result"]["total_images"], 1);
+
+    let document = run(&["convert", "--format", "bin"]);
+    assert_eq!(document["result"]["converted"], 1);
+    assert_eq!(document["result"]["failed"], 1);
+    assert!(temp_dir.path().join("a.bin").exists());
+    assert!(temp_dir.path().join("unlisted.json").exists());
+}
+
+#[test]
+fn test_cli_expands_input_globs() {
+    let temp_dir = TempDir::new().unwrap();
+    for game in ["Game_1", "Game_2", "Practice"] {
+        let frames = temp_dir.path().join(game).join("frames");
+        fs::create_dir_all(&frames).unwrap();
+        fs::write(frames.join("IMG_0001.json"), r#"{"count": 1}"#).unwrap();
+    }
+    let pattern = temp_dir.path().join("Game_*").join("frames");
+    let run = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+            .arg("--json")
+            .args(args)
+            .output()
+            .unwrap();
+        (output.status.code(), serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap())
+    };
+
+    let (code, document) = run(&["validate", "--input", pattern.to_str().unwrap()]);
+    assert_eq!(code, Some(0));
+    assert_eq!(document["result"]["total_files"], 2);
+    assert_eq!(document["result"]["roots"].as_array().unwrap().len(), 2);
+
+    // Single-directory commands need the pattern to pick out one directory
+    let (code, _) = run(&["format-stats", "--input", pattern.to_str().unwrap()]);
+    assert_eq!(code, Some(2));
+    let (code, _) = run(&["format-stats", "--input", temp_dir.path().join("Game_1*").join("frames").to_str().unwrap()]);
+    assert_eq!(code, Some(0));
+    let (code, _) = run(&["stats", "--input", temp_dir.path().join("Season_*").to_str().unwrap()]);
+    assert_eq!(code, Some(2));
+
+    // A directory whose name holds pattern characters is taken as it is
+    let literal = temp_dir.path().join("Game[1]");
+    fs::create_dir_all(&literal).unwrap();
+    fs::write(literal.join("IMG_0002.json"), r#"{"count": 2}"#).unwrap();
+    let (code, document) = run(&["validate", "--input", literal.to_str().unwrap()]);
+    assert_eq!(code, Some(0));
+    assert_eq!(document["result"]["total_files"], 1);
+}
+
+#[tokio::test]
+async fn test_convert_watcher_waits_for_settled_json_and_audits_conversions() {
+    use image_sidecar_rust::{ConversionRecord, ConvertWatcher};
+    use std::time::Duration;
+
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("a.json"), r#"{"count": 1}"#).unwrap();
+    fs::write(temp_dir.path().join("broken.json"), "{not json").unwrap();
+    let manager = image_sidecar_rust::SidecarManager::new();
+
+    // Freshly written files are still settling
+    let mut watcher = ConvertWatcher::new(SidecarFormat::Binary, Duration::from_secs(3600));
+    assert!(watcher.poll(&manager, temp_dir.path()).await.unwrap().is_empty());
+
+    let mut watcher = ConvertWatcher::new(SidecarFormat::Binary, Duration::ZERO);
+    let records = watcher.poll(&manager, temp_dir.path()).await.unwrap();
+    assert_eq!(records.len(), 2);
+    assert!(temp_dir.path().join("a.bin").exists() && !temp_dir.path().join("a.json").exists());
+    // The broken file is not retried until it changes
+    assert!(watcher.poll(&manager, temp_dir.path()).await.unwrap().is_empty());
+
+    fs::write(temp_dir.path().join("b.json"), r#"{"count": 2}"#).unwrap();
+    let audit_path = temp_dir.path().join("audit.log");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+        .args(["--json", "watch", "--input", temp_dir.path().to_str().unwrap(), "--settle", "0", "--once", "--keep-original"])
+        .args(["--audit-log", audit_path.to_str().unwrap()])
+        .output()
+        .unwrap();
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(document["result"]["converted"], 1);
+    assert_eq!(document["result"]["failed"], 1);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(temp_dir.path().join("b.json").exists() && temp_dir.path().join("b.bin").exists());
+
+    let audit: Vec<ConversionRecord> = fs::read_to_string(&audit_path).unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(audit.len(), 2);
+    let converted = audit.iter().find(|record| record.error.is_none()).unwrap();
+    assert_eq!(converted.target.as_deref(), Some(temp_dir.path().join("b.bin").as_path()));
+}
+
+#[tokio::test]
+async fn test_gc_expires_operations_and_superseded_versions() {
+    use image_sidecar_rust::{GcAction, RetentionPolicy, RetentionRule};
+
+    assert!("quality_assessment=90x".parse::<RetentionRule>().is_err());
+    assert!("no_such_operation=90d".parse::<RetentionRule>().is_err());
+
+    let temp_dir = TempDir::new().unwrap();
+    let old = (chrono::Utc::now() - chrono::Duration::days(200)).to_rfc3339();
+    let mixed = json!({
+        "sidecar_info": {"last_updated": old, "operation_updated": {"quality_assessment": old}},
+        "quality_assessment": {"score": 0.4},
+        "face_detection": {"faces": []},
+    });
+    fs::write(temp_dir.path().join("mixed.json"), mixed.to_string()).unwrap();
+    let stale = json!({"sidecar_info": {"created_at": old}, "quality_assessment": {"score": 0.9}});
+    fs::write(temp_dir.path().join("stale.json"), stale.to_string()).unwrap();
+    // Two versions of one image's sidecar; the binary one is written last
+    fs::write(temp_dir.path().join("twice.json"), r#"{"face_detection": {"faces": []}}"#).unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    let sidecar = ImageSidecar::new(None);
+    sidecar.save_data(&temp_dir.path().join("twice.jpg"), OperationType::FaceDetection, json!({"faces": [1]})).await.unwrap();
+
+    let policy = RetentionPolicy::new(vec!["quality_assessment=90d".parse().unwrap(), "newest-version".parse().unwrap()]);
+    let planned = sidecar.collect_garbage(temp_dir.path(), &policy, true).await.unwrap();
+    assert_eq!(planned.len(), 3);
+    assert!(temp_dir.path().join("stale.json").exists() && temp_dir.path().join("twice.json").exists());
+
+    let records = sidecar.collect_garbage(temp_dir.path(), &policy, false).await.unwrap();
+    assert_eq!(records, planned);
+    let superseded = records.iter().find(|record| record.sidecar_path.ends_with("twice.json")).unwrap();
+    assert_eq!(superseded.actions, vec![GcAction::Superseded { newer: temp_dir.path().join("twice.bin") }]);
+    assert!(!temp_dir.path().join("twice.json").exists() && !temp_dir.path().join("stale.json").exists());
+
+    // Only the expired payload leaves the merged sidecar
+    let mixed: serde_json::Value = serde_json::from_str(&fs::read_to_string(temp_dir.path().join("mixed.json")).unwrap()).unwrap();
+    assert!(mixed.get("quality_assessment").is_none());
+    assert_eq!(mixed["sidecar_info"]["last_operation"], "face_detection");
+    assert_eq!(mixed["sidecar_info"]["operation_updated"]["face_detection"], json!(old));
+
+    // Freshly written payloads carry their own write time and survive
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+        .args(["--json", "gc", "--input", temp_dir.path().to_str().unwrap(), "--rule", "face_detection=90d"])
+        .output()
+        .unwrap();
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(document["result"]["expired_operations"], 1);
+    assert_eq!(document["result"]["removed_files"], 1);
+    assert!(temp_dir.path().join("twice.bin").exists() && !temp_dir.path().join("mixed.json").exists());
+}
+
+#[tokio::test]
+async fn test_remove_operation_updates_sidecar_info_and_deletes_empty_sidecars() {
+    use image_sidecar_rust::OperationRemoval;
+
+    let temp_dir = TempDir::new().unwrap();
+    let image = temp_dir.path().join("a.jpg");
+    let sidecar = ImageSidecar::new(None);
+    sidecar.save_data(&image, OperationType::FaceDetection, json!({"faces": []})).await.unwrap();
+    sidecar.save_data(&image, OperationType::QualityAssessment, json!({"score": 0.5})).await.unwrap();
+
+    assert_eq!(sidecar.remove_operation(&image, OperationType::QualityAssessment).await.unwrap(), OperationRemoval::Removed);
+    let data = sidecar.read_data(&image).await.unwrap();
+    assert!(data.get("quality_assessment").is_none() && data.get("face_detection").is_some());
+    assert_eq!(data["sidecar_info"]["last_operation"], "face_detection");
+    assert!(data["sidecar_info"]["operation_updated"].get("quality_assessment").is_none());
+    assert_eq!(sidecar.remove_operation(&image, OperationType::QualityAssessment).await.unwrap(), OperationRemoval::Absent);
+
+    assert_eq!(sidecar.remove_operation(&image, OperationType::FaceDetection).await.unwrap(), OperationRemoval::SidecarDeleted);
+    assert!(!temp_dir.path().join("a.bin").exists());
+
+    fs::write(temp_dir.path().join("b.json"), r#"{"quality_assessment": {"score": 1}}"#).unwrap();
+    fs::write(temp_dir.path().join("c.json"), r#"{"quality_assessment": {"score": 1}, "yolov8": {"boxes": []}}"#).unwrap();
+    let run = |dry_run: bool| {
+        let mut command = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"));
+        command.args(["--json", "strip", "--input", temp_dir.path().to_str().unwrap(), "--operation", "quality_assessment"]);
+        if dry_run {
+            command.arg("--dry-run");
+        }
+        serde_json::from_slice::<serde_json::Value>(&command.output().unwrap().stdout).unwrap()
+    };
+    let planned = run(true);
+    assert_eq!((planned["result"]["stripped"].clone(), planned["result"]["deleted"].clone()), (json!(2), json!(1)));
+    assert!(temp_dir.path().join("b.json").exists());
+    assert_eq!(run(false)["result"]["sidecars"], planned["result"]["sidecars"]);
+    assert!(!temp_dir.path().join("b.json").exists());
+    let remaining: serde_json::Value = serde_json::from_str(&fs::read_to_string(temp_dir.path().join("c.json")).unwrap()).unwrap();
+    assert_eq!(remaining, json!({"yolov8": {"boxes": []}}));
+}
+
+#[tokio::test]
+async fn test_read_only_mode_refuses_writes_and_leaves_files_untouched() {
+    let temp_dir = TempDir::new().unwrap();
+    let image = temp_dir.path().join("a.jpg");
+    fs::write(&image, b"fake image data").unwrap();
+    let original = r#"{"face_detection": {"faces": []}}"#;
+    fs::write(temp_dir.path().join("a.json"), original).unwrap();
+
+    let sidecar = ImageSidecar::builder().read_only(true).build();
+    let refused = sidecar.save_data(&image, OperationType::QualityAssessment, json!({"score": 1})).await;
+    assert!(matches!(refused, Err(SidecarError::ReadOnly(_))));
+    assert!(matches!(sidecar.convert_directory_format(temp_dir.path(), SidecarFormat::Binary).await, Err(SidecarError::ReadOnly(_))));
+    assert!(matches!(sidecar.repair(temp_dir.path(), false).await, Err(SidecarError::ReadOnly(_))));
+    assert!(sidecar.repair(temp_dir.path(), true).await.is_ok());
+    let a = temp_dir.path().join("a.json");
+    let merged = temp_dir.path().join("merged.json");
+    assert!(matches!(sidecar.merge_sidecars(None, &a, &a, &merged).await, Err(SidecarError::ReadOnly(_))));
+    assert!(!merged.exists());
+    assert_eq!(sidecar.read_data(&image).await.unwrap(), json!({"face_detection": {"faces": []}}));
+    assert_eq!(fs::read_to_string(temp_dir.path().join("a.json")).unwrap(), original);
+
+    let run = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+            .args(["--json", "--read-only"])
+            .args(args)
+            .args(["--input", temp_dir.path().to_str().unwrap()])
+            .output()
+            .unwrap();
+        (output.status.code(), serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap())
+    };
+    let (code, convert) = run(&["convert", "--format", "bin"]);
+    assert_eq!(code, Some(2));
+    assert!(convert["error"].as_str().unwrap().contains("Read-only"));
+    assert!(!temp_dir.path().join(".sidecar-convert.checkpoint").exists());
+    assert!(!temp_dir.path().join("a.bin").exists());
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+        .args(["--json", "--read-only", "merge", "--ours", a.to_str().unwrap(), "--theirs", a.to_str().unwrap()])
+        .args(["--output", merged.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(!merged.exists());
+    let (code, stats) = run(&["stats"]);
+    assert_eq!(code, Some(0));
+    assert_eq!(stats["status"], "success");
+    assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 2);
+}
+
+#[tokio::test]
+async fn test_mutation_log_records_writes_conversions_and_deletions() {
+    let temp_dir = TempDir::new().unwrap();
+    let log_path = temp_dir.path().join("mutations.jsonl");
+    let images = temp_dir.path().join("images");
+    fs::create_dir(&images).unwrap();
+    let image = images.join("a.jpg");
+    fs::write(&image, b"fake image data").unwrap();
+    fs::write(images.join("orphan.json"), r#"{"yolov8": {"boxes": []}}"#).unwrap();
+
+    let sidecar = ImageSidecar::builder().mutation_log(Arc::new(MutationLog::open(&log_path).unwrap())).build();
+    sidecar.save_data(&image, OperationType::FaceDetection, json!({"faces": []})).await.unwrap();
+    sidecar.cleanup_orphaned(&images).await.unwrap();
+    sidecar.manager().convert_sidecar_format(&images.join("a.bin"), SidecarFormat::Json).await.unwrap();
+    sidecar.validate_sidecars(&images).await.unwrap();
+
+    let records: Vec<MutationRecord> = fs::read_to_string(&log_path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let mutations: Vec<Mutation> = records.iter().map(|record| record.mutation).collect();
+    assert_eq!(mutations, vec![Mutation::Write, Mutation::Delete, Mutation::Convert]);
+    assert_eq!(records[0].operation.as_deref(), Some("face_detection"));
+    assert_eq!(records[1].sidecar_path, images.join("orphan.json"));
+    assert_eq!(records[2].new_path.as_deref(), Some(images.join("a.json").as_path()));
+    assert!(records.iter().all(|record| record.tool_version == env!("CARGO_PKG_VERSION") && !record.host.is_empty()));
+
+    // The CLI turns the log on from the config file
+    let config_path = temp_dir.path().join("sidecar.toml");
+    fs::write(&config_path, format!("[defaults]\naudit_log = {:?}\n", log_path.to_str().unwrap())).unwrap();
+    let status = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+        .args(["--json", "--config", config_path.to_str().unwrap(), "convert", "--format", "bin", "--input"])
+        .arg(&images)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let last: MutationRecord = serde_json::from_str(fs::read_to_string(&log_path).unwrap().lines().last().unwrap()).unwrap();
+    assert_eq!((last.mutation, last.sidecar_path), (Mutation::Convert, images.join("a.json")));
+}
+
+#[tokio::test]
+async fn test_provenance_is_stamped_per_operation() {
+    let temp_dir = TempDir::new().unwrap();
+    let image = temp_dir.path().join("a.jpg");
+    fs::write(&image, b"fake image data").unwrap();
+
+    let detector = ImageSidecar::builder()
+        .provenance(Provenance::current().detector_commit("3f2c9ab").model_version("yolov8n-2024.06"))
+        .build();
+    detector.save_data(&image, OperationType::Yolov8, json!({"boxes": []})).await.unwrap();
+    ImageSidecar::new(None).save_data(&image, OperationType::QualityAssessment, json!({"score": 0.5})).await.unwrap();
+
+    let data = detector.read_data(&image).await.unwrap();
+    let producers = &data["sidecar_info"]["provenance"];
+    assert_eq!(producers["yolov8"]["detector_commit"], "3f2c9ab");
+    assert_eq!(producers["yolov8"]["model_version"], "yolov8n-2024.06");
+    assert!(producers["yolov8"].get("pid").is_none());
+    assert_eq!(producers["quality_assessment"]["detector_commit"], json!(null));
+    assert_eq!(producers["quality_assessment"]["tool_version"], env!("CARGO_PKG_VERSION"));
+
+    detector.remove_operation(&image, OperationType::QualityAssessment).await.unwrap();
+    let data = detector.read_data(&image).await.unwrap();
+    assert!(data["sidecar_info"]["provenance"].get("quality_assessment").is_none());
+
+    // The process ID is recorded only when asked for
+    let traced = ImageSidecar::builder().provenance(Provenance::current().pid(std::process::id())).build();
+    traced.save_data(&image, OperationType::Yolov8, json!({"boxes": []})).await.unwrap();
+    assert_eq!(traced.read_data(&image).await.unwrap()["sidecar_info"]["provenance"]["yolov8"]["pid"], json!(std::process::id()));
+}
+
+#[tokio::test]
+async fn test_canonical_mode_writes_identical_bytes_for_identical_documents() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    fs::write(dir.join("a.json"), r#"{"yolov8": {"score": 1.50, "boxes": [-0.0, 2]}, "sidecar_info": {"operation_type": "yolov8"}}"#).unwrap();
+    fs::write(dir.join("b.json"), "{\n  \"sidecar_info\": {\"operation_type\": \"yolov8\"},\n  \"yolov8\": {\"boxes\": [0.0, 2], \"score\": 1.5}\n}").unwrap();
+
+    let sidecar = ImageSidecar::builder().canonical(true).build();
+    let manager = sidecar.manager();
+    let a = manager.convert_sidecar_format(&dir.join("a.json"), SidecarFormat::Binary).await.unwrap();
+    let b = manager.convert_sidecar_format(&dir.join("b.json"), SidecarFormat::Binary).await.unwrap();
+    assert_eq!(fs::read(&a).unwrap(), fs::read(&b).unwrap());
+
+    let json_path = manager.convert_sidecar_format(&a, SidecarFormat::Json).await.unwrap();
+    assert_eq!(
+        fs::read_to_string(json_path).unwrap(),
+        r#"{"sidecar_info":{"operation_type":"yolov8"},"yolov8":{"boxes":[0.0,2],"score":1.5}}"#
+    );
+}
+
+#[tokio::test]
+async fn test_blob_store_shares_identical_payloads_behind_pointers() {
+    let temp_dir = TempDir::new().unwrap();
+    let frames = temp_dir.path().join("frames");
+    fs::create_dir(&frames).unwrap();
+    let store_dir = frames.join(".blobs");
+    let sidecar = ImageSidecar::builder().blob_store(Arc::new(BlobStore::open(&store_dir).unwrap())).build();
+
+    for name in ["a", "b", "c"] {
+        let image = frames.join(format!("{}.jpg", name));
+        fs::write(&image, b"fake image data").unwrap();
+        sidecar.save_data(&image, OperationType::Yolov8, json!({"boxes": []})).await.unwrap();
+    }
+    fs::write(frames.join("d.jpg"), b"fake image data").unwrap();
+    fs::write(frames.join("d.json"), r#"{"yolov8": {"boxes": []}, "quality_assessment": {"score": 0.4}}"#).unwrap();
+
+    let store = sidecar.manager().blob_store().unwrap();
+    assert_eq!(store.hashes().unwrap().len(), 1);
+    let raw = sidecar.manager().convert_sidecar_format(&frames.join("a.bin"), SidecarFormat::Json).await.unwrap();
+    let raw: serde_json::Value = serde_json::from_str(&fs::read_to_string(&raw).unwrap()).unwrap();
+    assert_eq!(raw["yolov8"]["$blob"], json!(store.hashes().unwrap()[0]));
+    assert_eq!(sidecar.read_data(&frames.join("a.jpg")).await.unwrap()["yolov8"], json!({"boxes": []}));
+
+    assert_eq!(sidecar.store_payloads(&frames).await.unwrap(), 2);
+    assert_eq!(store.hashes().unwrap().len(), 2);
+    assert_eq!(sidecar.read_data(&frames.join("d.jpg")).await.unwrap()["quality_assessment"]["score"], json!(0.4));
+    let report = sidecar.verify_blobs(&frames).await.unwrap();
+    assert_eq!((report.sidecars, report.pointers, report.referenced), (4, 5, 2));
+    assert!(report.is_clean() && report.unreferenced.is_empty());
+
+    // Scans and lazily read payloads see what the pointers refer to
+    let found = sidecar.manager().find_sidecar_for_image(&frames.join("d.jpg")).await.unwrap().unwrap();
+    assert_eq!(found.payload().unwrap()["quality_assessment"]["score"], json!(0.4));
+    fs::write(frames.join("e.jpg"), b"fake image data").unwrap();
+    sidecar.save_data(&frames.join("e.jpg"), OperationType::Tracking, json!({"tracks": [{"track_id": 7}]})).await.unwrap();
+    let track = sidecar.track(&frames, "7", &image_sidecar_rust::FrameNaming::default()).await.unwrap();
+    assert_eq!(track.unwrap().points[0].image_path, frames.join("e.jpg"));
+
+    // A damaged blob is reported for every sidecar that points to it
+    let shared = store.path_for(raw["yolov8"]["$blob"].as_str().unwrap());
+    fs::write(&shared, b"{}").unwrap();
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+        .args(["--json", "--blob-store", store_dir.to_str().unwrap(), "blobs", "verify", "--input", frames.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(4));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["result"]["issues"].as_array().unwrap().len(), 4);
+    assert!(sidecar.read_data(&frames.join("b.jpg")).await.is_err());
+}
+
+#[tokio::test]
+async fn test_pack_reads_by_image_path_and_unpacks_the_tree() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("season");
+    fs::create_dir_all(root.join("game1")).unwrap();
+    let sidecar = ImageSidecar::new(None);
+    for name in ["game1/a", "game1/b", "c"] {
+        let image = root.join(format!("{}.jpg", name));
+        fs::write(&image, b"fake image data").unwrap();
+        sidecar.save_data(&image, OperationType::Yolov8, json!({"frame": name})).await.unwrap();
+    }
+    fs::write(root.join("c.json"), r#"{"yolov8": {"frame": "stale json copy"}}"#).unwrap();
+    let originals: Vec<Vec<u8>> = ["game1/a.bin", "game1/b.bin", "c.bin", "c.json"].iter().map(|name| fs::read(root.join(name)).unwrap()).collect();
+
+    let pack_path = temp_dir.path().join("season.scpack");
+    assert_eq!(sidecar.pack_directory(&root, &pack_path, true).await.unwrap(), 4);
+    assert!(!root.join("game1/a.bin").exists() && !root.join("c.json").exists());
+
+    let pack = Pack::open(&pack_path).unwrap();
+    assert_eq!(pack.read_data(Path::new("game1/b.jpg")).unwrap().unwrap()["yolov8"]["frame"], "game1/b");
+    assert_eq!(pack.read_data(Path::new("c.png")).unwrap().unwrap()["yolov8"]["frame"], "c");
+    assert!(pack.read_data(Path::new("missing.jpg")).unwrap().is_none());
+
+    fs::write(root.join("c.bin"), b"newer").unwrap();
+    let summary = sidecar.unpack(&pack, &root).await.unwrap();
+    assert_eq!((summary.written.len(), summary.skipped.clone()), (3, vec![root.join("c.bin")]));
+    assert_eq!(fs::read(root.join("game1/a.bin")).unwrap(), originals[0]);
+    assert_eq!(fs::read(root.join("c.json")).unwrap(), originals[3]);
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+        .args(["--json", "unpack", "--input", pack_path.to_str().unwrap(), "--image", "game1/a.jpg"])
+        .output()
+        .unwrap();
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["result"]["yolov8"]["frame"], "game1/a");
+
+    let mut damaged = fs::read(&pack_path).unwrap();
+    damaged[pack.index().entries["game1/a.bin"].offset as usize] ^= 0xff;
+    fs::write(&pack_path, damaged).unwrap();
+    assert!(Pack::open(&pack_path).unwrap().read_bytes("game1/a.bin").is_err());
+}
+
+#[tokio::test]
+async fn test_export_tar_zst_keeps_layout_and_filters_by_operation() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("season");
+    fs::create_dir_all(root.join("game1")).unwrap();
+    let sidecar = ImageSidecar::new(None);
+    for (name, operation) in [("game1/a", OperationType::Yolov8), ("game1/b", OperationType::FaceDetection), ("c", OperationType::Yolov8)] {
+        let image = root.join(format!("{}.jpg", name));
+        fs::write(&image, format!("pixels of {}", name)).unwrap();
+        sidecar.save_data(&image, operation, json!({"frame": name})).await.unwrap();
+    }
+
+    let archive_path = temp_dir.path().join("subset.tar.zst");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+        .args(["--json", "export", "--input", root.to_str().unwrap(), "--output", archive_path.to_str().unwrap()])
+        .args(["--format", "tar.zst", "--include-images", "--operation-type", "yolov8"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!((report["result"]["exported"].as_u64(), report["result"]["filtered"].as_u64()), (Some(2), Some(1)));
+
+    let decoder = zstd::Decoder::new(fs::File::open(&archive_path).unwrap()).unwrap();
+    let mut archive = tar::Archive::new(decoder);
+    let mut entries = std::collections::BTreeMap::new();
+    for entry in archive.entries().unwrap() {
+        let mut entry = entry.unwrap();
+        let name = entry.path().unwrap().to_string_lossy().into_owned();
+        let mut bytes = Vec::new();
+        std::io::Read::read_to_end(&mut entry, &mut bytes).unwrap();
+        entries.insert(name, bytes);
+    }
+    assert_eq!(entries.keys().collect::<Vec<_>>(), ["c.bin", "c.jpg", "game1/a.bin", "game1/a.jpg"]);
+    assert_eq!(entries["game1/a.bin"], fs::read(root.join("game1/a.bin")).unwrap());
+    assert_eq!(entries["c.jpg"], b"pixels of c");
+
+    // When links are followed, one out of the exported tree is skipped and reported rather than archived
+    fs::write(temp_dir.path().join("secret.json"), r#"{"yolov8": {"token": "hunter2"}}"#).unwrap();
+    fs::write(root.join("d.jpg"), "pixels of d").unwrap();
+    paths::symlink(&temp_dir.path().join("secret.json"), &root.join("d.json")).unwrap();
+    let options = image_sidecar_rust::ArchiveOptions::new(image_sidecar_rust::ArchiveFormat::Tar);
+    let following = ImageSidecar::builder().follow_links(true).build();
+    let summary = following.export_archive(&root, &temp_dir.path().join("all.tar"), &options).await.unwrap();
+    assert_eq!((summary.sidecars, summary.outside_root.clone()), (3, vec![root.join("d.json")]));
+    let mut archive = tar::Archive::new(fs::File::open(temp_dir.path().join("all.tar")).unwrap());
+    assert!(archive.entries().unwrap().all(|entry| entry.unwrap().path().unwrap().to_str() != Some("d.json")));
+}
+
+#[tokio::test]
+async fn test_export_tfrecord_shards_detection_examples() {
+    use image_sidecar_rust::sidecar::tfrecord::{read_records, TfRecordOptions};
+
+    let temp_dir = TempDir::new().unwrap();
+    let sidecar = ImageSidecar::new(None);
+    for i in 0..3 {
+        let image_path = temp_dir.path().join(format!("frame_{}.png", i));
+        image::RgbImage::new(200, 100).save(&image_path).unwrap();
+        sidecar.save_data(&image_path, OperationType::Yolov8, json!({"detections": [
+            {"class_name": "person", "confidence": 0.8, "bbox": {"x1": 20, "y1": 10, "x2": 120, "y2": 90}},
+            {"class_name": "ball", "confidence": 0.6, "bbox": [0.5, 0.5, 0.1, 0.1]},
+            {"class_name": "ball", "confidence": 0.4, "bbox": {"x1": 150, "y1": 50, "x2": 900, "y2": 90}},
+        ]})).await.unwrap();
+    }
+    let unreadable = temp_dir.path().join("broken.jpg");
+    fs::write(&unreadable, b"not an image").unwrap();
+    sidecar.save_data(&unreadable, OperationType::FaceDetection, json!({"faces": [{"bbox": [1, 2, 3, 4]}]})).await.unwrap();
+
+    let output = temp_dir.path().join("train.tfrecord");
+    let options = TfRecordOptions::default().shards(2).include_images(true);
+    let summary = sidecar.export_tfrecord(temp_dir.path(), &output, &options).await.unwrap();
+    assert_eq!((summary.examples, summary.detections, summary.skipped_detections), (3, 6, 3));
+    assert_eq!(summary.unreadable_images, vec![unreadable]);
+    assert_eq!(summary.shards, vec![
+        temp_dir.path().join("train.tfrecord-00000-of-00002"),
+        temp_dir.path().join("train.tfrecord-00001-of-00002"),
+    ]);
+
+    let records: Vec<Vec<u8>> = summary.shards.iter().flat_map(|shard| read_records(shard).unwrap()).collect();
+    assert_eq!((read_records(&summary.shards[0]).unwrap().len(), records.len()), (2, 3));
+    let contains = |haystack: &[u8], needle: &[u8]| haystack.windows(needle.len()).any(|window| window == needle);
+    let png = fs::read(temp_dir.path().join("frame_0.png")).unwrap();
+    assert!(contains(&records[0], &png));
+    assert!(contains(&records[0], b"image/object/bbox/xmin"));
+    assert!(contains(&records[0], b"person"));
+
+    let label_map = fs::read_to_string(&summary.label_map).unwrap();
+    assert_eq!(summary.label_map, temp_dir.path().join("train.pbtxt"));
+    assert!(label_map.starts_with("item {\n  id: 1\n  name: \"ball\"\n}"));
+    assert!(label_map.contains("id: 3\n  name: \"person\""));
+
+    // A corrupted record fails its checksum instead of decoding garbage
+    let mut bytes = fs::read(&summary.shards[1]).unwrap();
+    bytes[20] ^= 0xff;
+    fs::write(&summary.shards[1], bytes).unwrap();
+    assert!(read_records(&summary.shards[1]).is_err());
+}
+
+#[tokio::test]
+async fn test_export_webdataset_pairs_images_with_payloads_in_shards() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("season");
+    fs::create_dir_all(root.join("game1")).unwrap();
+    let sidecar = ImageSidecar::new(None);
+    for name in ["game1/IMG.0001", "game1/IMG.0002", "game1/IMG.0003"] {
+        let image = root.join(format!("{}.jpg", name));
+        fs::write(&image, vec![7u8; 1500]).unwrap();
+        sidecar.save_data(&image, OperationType::FaceDetection, json!({"faces": [{"bbox": [1, 2, 3, 4]}]})).await.unwrap();
+    }
+    sidecar.save_data(&root.join("game1/IMG.0001.jpg"), OperationType::Yolov8, json!({"count": 0})).await.unwrap();
+
+    // Each sample takes 512 + 1536 bytes for the image and 1024 for its JSON, so two fit in 7000 bytes
+    let output = temp_dir.path().join("shards/season.tar");
+    fs::create_dir_all(output.parent().unwrap()).unwrap();
+    let result = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+        .args(["--json", "export", "--input", root.to_str().unwrap(), "--output", output.to_str().unwrap()])
+        .args(["--format", "webdataset", "--shard-size", "7KB"])
+        .output()
+        .unwrap();
+    assert_eq!(result.status.code(), Some(0));
+    let report: serde_json::Value = serde_json::from_slice(&result.stdout).unwrap();
+    assert_eq!(report["result"]["samples"], 3);
+    let url = temp_dir.path().join("shards/season-{000000..000001}.tar");
+    assert_eq!(report["result"]["url"], url.to_str().unwrap());
+
+    let read_shard = |index: usize| {
+        let file = fs::File::open(temp_dir.path().join(format!("shards/season-{:06}.tar", index))).unwrap();
+        let mut entries = std::collections::BTreeMap::new();
+        for entry in tar::Archive::new(file).entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let name = entry.path().unwrap().to_string_lossy().into_owned();
+            let mut bytes = Vec::new();
+            std::io::Read::read_to_end(&mut entry, &mut bytes).unwrap();
+            entries.insert(name, bytes);
+        }
+        entries
+    };
+    let first = read_shard(0);
+    assert_eq!(first.keys().collect::<Vec<_>>(), ["game1/IMG_0001.jpg", "game1/IMG_0001.json", "game1/IMG_0002.jpg", "game1/IMG_0002.json"]);
+    let payloads: serde_json::Value = serde_json::from_slice(&first["game1/IMG_0001.json"]).unwrap();
+    assert_eq!(payloads["yolov8"]["count"], 0);
+    assert_eq!(payloads["face_detection"]["faces"][0]["bbox"], json!([1, 2, 3, 4]));
+    assert!(payloads.get("sidecar_info").is_none());
+    assert_eq!(read_shard(1).keys().collect::<Vec<_>>(), ["game1/IMG_0003.jpg", "game1/IMG_0003.json"]);
+
+    let rejected = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+        .args(["export", "--input", root.to_str().unwrap(), "--output", output.to_str().unwrap()])
+        .args(["--format", "tar", "--shard-size", "1GB"])
+        .output()
+        .unwrap();
+    assert_eq!(rejected.status.code(), Some(2));
+}
+
+#[tokio::test]
+async fn test_export_fields_projects_only_requested_paths() {
+    let temp_dir = TempDir::new().unwrap();
+    let sidecar = ImageSidecar::new(None);
+    let image = temp_dir.path().join("a.jpg");
+    fs::write(&image, "pixels").unwrap();
+    let faces = json!({"faces": [
+        {"bbox": [1, 2, 3, 4], "confidence": 0.9, "encoding": vec![0.5; 128]},
+        {"bbox": [5, 6, 7, 8], "confidence": 0.7, "encoding": vec![0.25; 128]},
+    ]});
+    sidecar.save_data(&image, OperationType::FaceDetection, faces).await.unwrap();
+    let other = temp_dir.path().join("b.jpg");
+    fs::write(&other, "other pixels").unwrap();
+    sidecar.save_data(&other, OperationType::Yolov8, json!({"boxes": []})).await.unwrap();
+
+    let export = |fields: &str| {
+        let output_path = temp_dir.path().join("export.json");
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+            .args(["--json", "export", "--input", temp_dir.path().to_str().unwrap(), "--output", output_path.to_str().unwrap()])
+            .args(["--operation-type", "face_detection", "--fields", fields])
+            .output()
+            .unwrap();
+        (output.status.code(), fs::read(&output_path).ok())
+    };
+
+    let (code, written) = export("image_path,face_detection.faces[*].bbox,face_detection.faces[*].confidence");
+    assert_eq!(code, Some(0));
+    let exported: serde_json::Value = serde_json::from_slice(&written.unwrap()).unwrap();
+    assert_eq!(exported["total_sidecars"], 1);
+    assert_eq!(exported["fields"].as_array().unwrap().len(), 3);
+    assert_eq!(exported["sidecars"][0], json!({
+        "image_path": image.to_str().unwrap(),
+        "face_detection": {"faces": [
+            {"bbox": [1, 2, 3, 4], "confidence": 0.9},
+            {"bbox": [5, 6, 7, 8], "confidence": 0.7},
+        ]},
+    }));
+
+    fs::remove_file(temp_dir.path().join("export.json")).unwrap();
+    let (code, written) = export("face_detection.faces[first].bbox");
+    assert_eq!((code, written), (Some(2), None));
+}
+
+#[tokio::test]
+async fn test_redact_strips_and_hashes_fields_into_a_shareable_copy() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("season");
+    fs::create_dir_all(root.join("game1")).unwrap();
+    let sidecar = ImageSidecar::new(None);
+    let faces = json!({"faces": [
+        {"bbox": [1, 2, 3, 4], "encoding": [0.1, 0.2], "landmarks": {"nose": [5, 6]}},
+        {"bbox": [7, 8, 9, 10], "encoding": [0.3, 0.4], "landmarks": {"nose": [11, 12]}},
+    ]});
+    for name in ["game1/a", "b"] {
+        let image = root.join(format!("{}.jpg", name));
+        fs::write(&image, b"fake image data").unwrap();
+        sidecar.save_data(&image, OperationType::FaceDetection, faces.clone()).await.unwrap();
+    }
+    sidecar.save_data(&root.join("b.jpg"), OperationType::Yolov8, json!({"detections": []})).await.unwrap();
+    let original = fs::read(root.join("game1/a.bin")).unwrap();
+
+    let shared = temp_dir.path().join("shared");
+    let key_path = temp_dir.path().join("redact.key");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+        .args(["--json", "redact", "--input", root.to_str().unwrap(), "--output", shared.to_str().unwrap()])
+        .args(["--fields", "face_detection.faces[*].encoding", "--hash", "face_detection.faces[*].landmarks"])
+        .args(["--hash-key", key_path.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["result"]["redacted"], 2);
+    let record = &report["result"]["records"][0];
+    assert_eq!(record["removed"], json!(["/face_detection/faces/0/encoding", "/face_detection/faces/1/encoding"]));
+    assert_eq!(record["hashed"].as_array().unwrap().len(), 2);
+    assert_eq!(fs::read(root.join("game1/a.bin")).unwrap(), original);
+
+    let copy = ImageSidecar::new(None).read_data(&shared.join("game1/a.jpg")).await.unwrap();
+    let redacted = &copy["face_detection"]["faces"][1];
+    assert!(redacted.get("encoding").is_none());
+    assert_eq!(redacted["bbox"], json!([7, 8, 9, 10]));
+    assert_ne!(copy["face_detection"]["faces"][0]["landmarks"], redacted["landmarks"]);
+
+    // Hashes are keyed with the key file the run created, so they match across runs sharing it
+    let key = RedactKey::load(&key_path).unwrap();
+    assert_eq!(redacted["landmarks"], redact::hash_value(&json!({"nose": [11, 12]}), &key));
+    assert_ne!(redacted["landmarks"], redact::hash_value(&json!({"nose": [11, 12]}), &RedactKey::generate().unwrap()));
+    assert!(redacted["landmarks"].as_str().unwrap().starts_with("hmac-sha256:"));
+
+    let rules = vec![
+        RedactRule::parse("face_detection.faces[*].encoding", RedactAction::Remove).unwrap(),
+        RedactRule::parse("$.face_detection.faces[*].landmarks", RedactAction::Hash).unwrap(),
+    ];
+    assert_eq!(sidecar.redact(&root, &rules, &key, None, false).await.unwrap().len(), 2);
+    assert!(sidecar.redact(&root, &rules, &key, None, false).await.unwrap().is_empty());
+    let in_place = sidecar.read_data(&root.join("b.jpg")).await.unwrap();
+    assert!(in_place["face_detection"]["faces"][0].get("encoding").is_none());
+    assert_eq!(in_place["face_detection"]["faces"][1]["landmarks"], redacted["landmarks"]);
+    assert_eq!(in_place["yolov8"]["detections"], json!([]));
+}
+
+#[tokio::test]
+async fn test_signed_payloads_verify_and_edits_are_caught() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("season");
+    fs::create_dir_all(&root).unwrap();
+    let key_path = temp_dir.path().join("pipeline.key");
+    let cli = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust")).arg("--json").args(args).output().unwrap();
+        (output.status.code(), serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap())
+    };
+    assert_eq!(cli(&["keygen", "--output", key_path.to_str().unwrap()]).0, Some(0));
+    assert_ne!(cli(&["keygen", "--output", key_path.to_str().unwrap()]).0, Some(0));
+
+    let signer = Arc::new(SidecarSigner::load(&key_path).unwrap());
+    let sidecar = ImageSidecar::builder().signer(Arc::clone(&signer)).build();
+    for name in ["a", "b", "c"] {
+        let image = root.join(format!("{}.jpg", name));
+        fs::write(&image, b"fake image data").unwrap();
+        sidecar.save_data(&image, OperationType::Yolov8, json!({"count": 1})).await.unwrap();
+    }
+    let public_key = format!("{}.pub", key_path.display());
+    let validate = ["validate", "--input", root.to_str().unwrap(), "--verify-signatures", "--trusted-key", public_key.as_str()];
+    let (code, report) = cli(&validate);
+    assert_eq!(code, Some(0));
+    assert_eq!(report["result"]["signatures"][0]["verified"], 3);
+
+    let serializer = FormatManager::new();
+    let serializer = serializer.get_serializer(SidecarFormat::Binary);
+    let mut edited = serializer.deserialize(&fs::read(root.join("a.bin")).unwrap()).unwrap();
+    edited["yolov8"]["count"] = json!(7);
+    fs::write(root.join("a.bin"), serializer.serialize(&edited).unwrap()).unwrap();
+    ImageSidecar::new(None).save_data(&root.join("c.jpg"), OperationType::Yolov8, json!({"count": 2})).await.unwrap();
+
+    let (code, report) = cli(&validate);
+    assert_eq!(code, Some(4));
+    let mut problems: Vec<(String, String)> = report["result"]["signatures"][0]["issues"].as_array().unwrap().iter()
+        .map(|issue| (issue["sidecar_path"].as_str().unwrap().rsplit('/').next().unwrap().to_string(), issue["problem"].as_str().unwrap().to_string()))
+        .collect();
+    problems.sort();
+    assert_eq!(problems, [("a.bin".to_string(), "invalid".to_string()), ("c.bin".to_string(), "unsigned".to_string())]);
+
+    let stranger = SignatureVerifier::new().add_key(SidecarSigner::generate().unwrap().verifying_key());
+    let report = sidecar.verify_signatures(&root, &stranger).await.unwrap();
+    assert!(report.issues.iter().any(|issue| issue.problem == SignatureProblem::UnknownKey { key_id: signer.key_id().to_string() }));
+
+    // A signed payload copied beside another image no longer verifies, nor does an unreadable sidecar
+    let trusted = SignatureVerifier::new().add_key(signer.verifying_key());
+    fs::write(root.join("d.jpg"), b"other image data").unwrap();
+    fs::copy(root.join("b.bin"), root.join("d.bin")).unwrap();
+    fs::write(root.join("e.jpg"), b"fake image data").unwrap();
+    fs::write(root.join("e.bin"), b"not a sidecar").unwrap();
+    let report = sidecar.verify_signatures(&root, &trusted).await.unwrap();
+    let problem = |name: &str| report.issues.iter().find(|issue| issue.sidecar_path == root.join(name)).map(|issue| issue.problem.clone());
+    assert_eq!(problem("b.bin"), None);
+    assert_eq!(problem("d.bin"), Some(SignatureProblem::Invalid));
+    assert!(matches!(problem("e.bin"), Some(SignatureProblem::Unreadable { .. })));
+}
+
+#[tokio::test]
+async fn test_snapshot_restore_rolls_back_a_bad_run() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("season");
+    fs::create_dir_all(root.join("game1")).unwrap();
+    let sidecar = ImageSidecar::new(None);
+    for name in ["game1/a", "b", "c"] {
+        fs::write(root.join(format!("{}.jpg", name)), b"fake image data").unwrap();
+    }
+    for name in ["game1/a", "b"] {
+        sidecar.save_data(&root.join(format!("{}.jpg", name)), OperationType::Yolov8, json!({"count": 1})).await.unwrap();
+    }
+    let originals = [fs::read(root.join("game1/a.bin")).unwrap(), fs::read(root.join("b.bin")).unwrap()];
+
+    let snapshot = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+            .args(["--json", "snapshot"])
+            .args(args)
+            .args(["--input", root.to_str().unwrap()])
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()["result"].clone()
+    };
+    assert_eq!(snapshot(&["create", "before"])["linked"], 0);
+    assert_eq!(snapshot(&["create", "again"])["linked"], 2);
+    assert!(temp_dir.path().join("season.snapshots/before/manifest.json").exists());
+
+    // A bad detector run rewrites one sidecar, deletes another and adds a third
+    sidecar.save_data(&root.join("game1/a.jpg"), OperationType::Yolov8, json!({"count": 99})).await.unwrap();
+    fs::remove_file(root.join("b.bin")).unwrap();
+    sidecar.save_data(&root.join("c.jpg"), OperationType::Yolov8, json!({"count": 99})).await.unwrap();
+
+    let restored = snapshot(&["restore", "before"]);
+    assert_eq!(restored["restored"], json!(["b.bin", "game1/a.bin"]));
+    assert_eq!(restored["removed"], json!(["c.bin"]));
+    assert_eq!([fs::read(root.join("game1/a.bin")).unwrap(), fs::read(root.join("b.bin")).unwrap()], originals);
+    assert!(!root.join("c.bin").exists());
+
+    // Restored sidecars are copies, so writing to them leaves the snapshot intact
+    sidecar.save_data(&root.join("game1/a.jpg"), OperationType::Yolov8, json!({"count": 5})).await.unwrap();
+    let store = SnapshotStore::open(temp_dir.path().join("season.snapshots")).unwrap();
+    let summary = sidecar.restore_snapshot(&root, &store, "again").await.unwrap();
+    assert_eq!((summary.restored, summary.unchanged), (vec!["game1/a.bin".to_string()], 1));
+    assert_eq!(fs::read(root.join("game1/a.bin")).unwrap(), originals[0]);
+
+    let listed = snapshot(&["list"]);
+    assert_eq!(listed["snapshots"].as_array().unwrap().iter().map(|s| s["name"].as_str().unwrap()).collect::<Vec<_>>(), ["before", "again"]);
+    assert!(sidecar.create_snapshot(&root, &store, "../escape").await.is_err());
+}
+
+#[tokio::test]
+async fn test_git_friendly_sidecars_and_git_status() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com", "-C", root.to_str().unwrap()])
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    };
+    let sidecar = ImageSidecar::builder().git_friendly(true).build();
+    for name in ["a", "b", "c"] {
+        fs::write(root.join(format!("{}.jpg", name)), b"fake image data").unwrap();
+    }
+    for name in ["a", "b"] {
+        sidecar.save_data(&root.join(format!("{}.jpg", name)), OperationType::Yolov8, json!({"count": 1, "boxes": []})).await.unwrap();
+    }
+
+    // Sorted keys, one per line, so a changed value is a one-line diff
+    let text = fs::read_to_string(root.join("a.json")).unwrap();
+    assert!(text.starts_with("{\n  \"sidecar_info\": {\n"), "{}", text);
+    assert!(text.ends_with("  \"yolov8\": {\n    \"boxes\": [],\n    \"count\": 1\n  }\n}\n"), "{}", text);
+    assert!(!root.join("a.bin").exists());
+
+    git(&["init", "-q"]);
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "annotations"]);
+    assert!(sidecar.git_status(root, "HEAD").await.unwrap().files.is_empty());
+
+    // Rewriting a payload unchanged only touches sidecar_info, which is not reported
+    sidecar.save_data(&root.join("b.jpg"), OperationType::Yolov8, json!({"count": 1, "boxes": []})).await.unwrap();
+    sidecar.save_data(&root.join("a.jpg"), OperationType::BallDetection, json!({"balls": 1})).await.unwrap();
+    sidecar.save_data(&root.join("a.jpg"), OperationType::Yolov8, json!({"count": 2, "boxes": []})).await.unwrap();
+    sidecar.save_data(&root.join("c.jpg"), OperationType::Yolov8, json!({"count": 3})).await.unwrap();
+    fs::remove_file(root.join("b.json")).unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+        .args(["--json", "git-status", "--input", root.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+    let result = serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()["result"].clone();
+    assert_eq!(result["git_ref"], "HEAD");
+    let files = result["files"].as_array().unwrap();
+    assert_eq!(files.iter().map(|f| (f["path"].as_str().unwrap(), f["change"].as_str().unwrap())).collect::<Vec<_>>(),
+        [("a.json", "modified"), ("b.json", "deleted"), ("c.json", "added")]);
+    assert_eq!((&files[0]["added_operations"], &files[0]["modified_operations"]), (&json!(["ball_detection"]), &json!(["yolov8"])));
+    assert_eq!(files[1]["removed_operations"], json!(["yolov8"]));
+    assert!(sidecar.git_status(root, "no-such-ref").await.is_err());
+}
+
+#[tokio::test]
+async fn test_dvc_export_matches_dvc_hashing_and_verify_lists_changes() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = temp_dir.path();
+    let root = repo.join("sidecars");
+    fs::create_dir_all(root.join("a")).unwrap();
+    fs::write(root.join("a.json"), br#"{"yolov8": {"count": 1}}"#).unwrap();
+    fs::write(root.join("a/b.json"), br#"{"yolov8": {"count": 2}}"#).unwrap();
+    // Directory hash as computed by DVC for these two files
+    let dir_md5 = "130db423446eaa123c371647e1701e16.dir";
+
+    let dvc = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+            .args(["--json", "dvc"])
+            .args(args)
+            .args(["--input", root.to_str().unwrap()])
+            .output()
+            .unwrap();
+        let report = serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap();
+        (output.status.code(), report["result"].clone())
+    };
+    let (code, result) = dvc(&["export"]);
+    assert_eq!(code, Some(0));
+    assert_eq!(result["out"], json!({"md5": dir_md5, "size": 48, "nfiles": 2, "hash": "md5", "path": "sidecars"}));
+    let written = fs::read_to_string(repo.join("sidecars.dvc")).unwrap();
+    assert!(written.starts_with("outs:\n- md5: 130db423446eaa123c371647e1701e16.dir\n"), "{}", written);
+
+    let dvc_file = repo.join("sidecars.dvc");
+    assert_eq!(dvc(&["verify", "--lock", dvc_file.to_str().unwrap()]).0, Some(0));
+
+    // Exporting again keeps the keys DVC users add by hand
+    fs::write(&dvc_file, written.replace("  path: sidecars\n", "  path: sidecars\n  remote: nas\n") + "meta:\n  owner: vision\n").unwrap();
+    assert_eq!(dvc(&["export"]).0, Some(0));
+    let rewritten: serde_yaml::Value = serde_yaml::from_str(&fs::read_to_string(&dvc_file).unwrap()).unwrap();
+    assert_eq!((&rewritten["outs"][0]["remote"], &rewritten["meta"]["owner"]), (&"nas".into(), &"vision".into()));
+    assert_eq!(rewritten["outs"][0]["md5"], dir_md5);
+
+    // With the listing in the DVC cache, verification names the changed files
+    let cache = repo.join(".dvc/cache/files/md5/13");
+    fs::create_dir_all(&cache).unwrap();
+    fs::write(cache.join("0db423446eaa123c371647e1701e16.dir"), concat!(
+        r#"[{"md5": "5a44d8a77b48f76da76cead2d2a32d4e", "relpath": "a/b.json"}, "#,
+        r#"{"md5": "08cf3929e513d11c40c6128058eec488", "relpath": "a.json"}]"#,
+    )).unwrap();
+    fs::write(root.join("a.json"), br#"{"yolov8": {"count": 5}}"#).unwrap();
+    fs::write(root.join("c.json"), b"{}").unwrap();
+    let (code, result) = dvc(&["verify", "--lock", dvc_file.to_str().unwrap()]);
+    assert_eq!(code, Some(4));
+    assert_eq!((&result["modified"], &result["added"], &result["listing_found"]), (&json!(["a.json"]), &json!(["c.json"]), &json!(true)));
+
+    // Outputs of pipeline stages are found in dvc.lock
+    fs::write(repo.join("dvc.lock"), format!(
+        "schema: '2.0'\nstages:\n  detect:\n    cmd: detect frames\n    outs:\n    - path: sidecars\n      hash: md5\n      md5: {}\n      size: 48\n      nfiles: 2\n",
+        dir_md5
+    )).unwrap();
+    fs::remove_file(root.join("c.json")).unwrap();
+    fs::write(root.join("a.json"), br#"{"yolov8": {"count": 1}}"#).unwrap();
+    let sidecar = ImageSidecar::new(None);
+    assert!(sidecar.verify_dvc(&root, &repo.join("dvc.lock")).await.unwrap().is_clean());
+    assert!(sidecar.verify_dvc(&root.join("a"), &repo.join("dvc.lock")).await.is_err());
+}
+
+#[tokio::test]
+async fn test_stats_badges_update_incrementally_and_roll_up() {
+    use image_sidecar_rust::sidecar::STATS_BADGE_FILE;
+
+    let temp_dir = TempDir::new().unwrap();
+    let season = temp_dir.path().join("season");
+    let sidecar = ImageSidecar::new(None);
+    for (game, frames) in [("game1", 3), ("game2", 2)] {
+        fs::create_dir_all(season.join(game)).unwrap();
+        for frame in 0..frames {
+            let image = season.join(format!("{}/frame{}.jpg", game, frame));
+            fs::write(&image, b"fake image data").unwrap();
+            sidecar.save_data(&image, OperationType::Yolov8, json!({"count": frame, "success": true})).await.unwrap();
+        }
+    }
+    let stats = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+            .args(["--json", "stats"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()["result"].clone()
+    };
+    let game1 = season.join("game1");
+    let game1 = game1.to_str().unwrap();
+
+    let first = stats(&["--input", game1, "--write-badge"]);
+    assert_eq!((&first["total_sidecars"], &first["badges"][0]["read"]), (&json!(3), &json!(3)));
+    assert!(season.join("game1").join(STATS_BADGE_FILE).exists());
+    // The badge is not a sidecar, so it is neither counted nor treated as an orphan
+    assert!(sidecar.find_orphaned(&season.join("game1")).await.unwrap().is_empty());
+
+    let second = stats(&["--input", game1, "--write-badge"]);
+    assert_eq!((&second["total_sidecars"], &second["badges"][0]["reused"]), (&json!(3), &json!(3)));
+    sidecar.save_data(&season.join("game1/frame0.jpg"), OperationType::Yolov8, json!({"count": 10, "success": false})).await.unwrap();
+    let third = stats(&["--input", game1, "--write-badge"]);
+    assert_eq!((&third["badges"][0]["reused"], &third["badges"][0]["read"]), (&json!(2), &json!(1)));
+    assert_eq!(third["success_rate_percentages"]["yolov8"].as_f64().unwrap().round(), 67.0);
+
+    stats(&["--input", season.join("game2").to_str().unwrap(), "--write-badge"]);
+    let rolled = stats(&["--input", season.to_str().unwrap(), "--aggregate"]);
+    assert_eq!(rolled["badges"].as_array().unwrap().len(), 2);
+    assert_eq!((&rolled["aggregate"]["total_images"], &rolled["aggregate"]["total_sidecars"]), (&json!(5), &json!(5)));
+    assert_eq!(rolled["aggregate"]["success_rate_percentages"]["yolov8"], json!(80.0));
+    assert_eq!(rolled["aggregate"]["coverage_percentage"], json!(100.0));
+}
+
+#[tokio::test]
+async fn test_coverage_lists_images_missing_each_operation() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("frames");
+    fs::create_dir_all(root.join("game1")).unwrap();
+    let sidecar = ImageSidecar::new(None);
+    let image = |name: &str| root.join(format!("{}.jpg", name));
+    for name in ["a", "b", "game1/c"] {
+        fs::write(image(name), b"fake image data").unwrap();
+    }
+    sidecar.save_data(&image("a"), OperationType::Yolov8, json!({"count": 1})).await.unwrap();
+    sidecar.save_data(&image("a"), OperationType::FaceDetection, json!({"faces": []})).await.unwrap();
+    sidecar.save_data(&image("b"), OperationType::Yolov8, json!({"count": 2})).await.unwrap();
+
+    let lists = temp_dir.path().join("lists");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+        .args(["--json", "coverage", "--input", root.to_str().unwrap()])
+        .args(["--operations", "face_detection,yolov8,quality_assessment", "--missing-dir", lists.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+    let result = serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()["result"].clone();
+    assert_eq!(result["total_images"], 3);
+    let covered: Vec<_> = result["operations"].as_array().unwrap().iter()
+        .map(|coverage| (coverage["operation"].as_str().unwrap(), coverage["covered"].as_u64().unwrap()))
+        .collect();
+    assert_eq!(covered, [("face_detection", 1), ("yolov8", 2), ("quality_assessment", 0)]);
+
+    let missing = |operation: &str| fs::read_to_string(lists.join(format!("{}.missing.txt", operation))).unwrap();
+    assert_eq!(missing("yolov8"), format!("{}\n", image("game1/c").display()));
+    assert_eq!(missing("face_detection").lines().count(), 2);
+    assert_eq!(missing("quality_assessment").lines().count(), 3);
+    // Each list is renamed into place whole, leaving no temporary file behind
+    assert_eq!(fs::read_dir(&lists).unwrap().count(), 3);
+}
+
+#[tokio::test]
+async fn test_report_all_matches_separate_scans_from_one_pass() {
+    use image_sidecar_rust::ScanPipeline;
+
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let sidecar = ImageSidecar::new(None);
+    for name in ["a", "b", "c"] {
+        fs::write(root.join(format!("{}.jpg", name)), b"fake image data").unwrap();
+    }
+    sidecar.save_data(&root.join("a.jpg"), OperationType::FaceDetection, json!({"faces": [{"bbox": [1, 2, 3, 4]}]})).await.unwrap();
+    sidecar.save_data(&root.join("b.jpg"), OperationType::Yolov8, json!({"objects": []})).await.unwrap();
+    fs::write(root.join("c.json"), b"{not json").unwrap();
+
+    let report = sidecar.run_pipeline(root, &ScanPipeline::all(), None).await.unwrap();
+    assert_eq!(report.sidecars_read, 3);
+    let validation = report.validation.as_ref().unwrap();
+    let mut separate = sidecar.validate_sidecars(root).await.unwrap();
+    separate.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+    let mut validated: Vec<_> = validation.iter().map(|result| (&result.file_path, result.is_valid)).collect();
+    validated.sort();
+    assert_eq!(validated, separate.iter().map(|result| (&result.file_path, result.is_valid)).collect::<Vec<_>>());
+    assert_eq!(report.validation_summary().unwrap().invalid_files, 1);
+
+    let stats = report.statistics.as_ref().unwrap();
+    let separate = sidecar.get_statistics(root).await.unwrap();
+    assert_eq!((stats.total_images, stats.total_sidecars), (separate.total_images, separate.total_sidecars));
+    assert_eq!(stats.operation_counts, separate.operation_counts);
+    assert_eq!(report.formats.as_ref().unwrap(), &sidecar.get_format_statistics(root).await.unwrap());
+
+    let coverage = report.coverage.as_ref().unwrap();
+    let operations: Vec<_> = coverage.operations.iter().map(|coverage| coverage.operation.clone()).collect();
+    assert_eq!(operations, ["face_detection", "yolov8"]);
+    let separate = sidecar.coverage(root, &operations).await.unwrap();
+    assert_eq!(serde_json::to_value(coverage).unwrap(), serde_json::to_value(&separate).unwrap());
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+        .args(["--json", "report", "--input", root.to_str().unwrap(), "--stats", "--coverage", "yolov8"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+    let result = serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()["result"].clone();
+    assert_eq!(result["statistics"]["total_sidecars"], 3);
+    assert_eq!(result["coverage"]["operations"][0]["covered"], 1);
+    assert!(result.get("validation").is_none() && result.get("formats").is_none());
+
+    // Without a section every one is produced; an .html output gets the dashboard
+    let dashboard = temp_dir.path().join("report.html");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+        .args(["--json", "report", "--input", root.to_str().unwrap(), "--output", dashboard.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+    let result = serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()["result"].clone();
+    assert_eq!(result["validation_summary"]["invalid_files"], 1);
+    let html = fs::read_to_string(&dashboard).unwrap();
+    for section in ["Operations", "Coverage of 3 images", "Format distribution", "Sidecar sizes", "Validation failures (1)"] {
+        assert!(html.contains(&format!("<h2>{}</h2>", section)), "missing {}", section);
+    }
+    assert!(html.contains("c.json") && !html.contains("<script") && !html.contains("http"));
+}
+
+#[tokio::test]
+async fn test_emit_jobs_batches_unprocessed_images_and_drops_stale_jobs() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("frames");
+    fs::create_dir_all(&root).unwrap();
+    let sidecar = ImageSidecar::new(None);
+    for name in ["a", "b", "c"] {
+        fs::write(root.join(format!("{}.jpg", name)), b"fake image data").unwrap();
+    }
+
+    let jobs_dir = temp_dir.path().join("jobs");
+    let emit = |format: &str| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+            .args(["--json", "emit-jobs", "--input", root.to_str().unwrap(), "--operation", "face_detection"])
+            .args(["--batch-size", "2", "--format", format, "--output", jobs_dir.to_str().unwrap()])
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()["result"].clone()
+    };
+    let result = emit("json");
+    assert_eq!((result["jobs"].as_u64(), result["images"].as_u64()), (Some(2), Some(3)));
+    let files: Vec<PathBuf> = serde_json::from_value(result["files"].clone()).unwrap();
+    let job: serde_json::Value = serde_json::from_slice(&fs::read(&files[1]).unwrap()).unwrap();
+    assert_eq!((job["batch"].as_u64(), job["batches"].as_u64()), (Some(2), Some(2)));
+    assert_eq!(job["images"], json!([root.join("c.jpg")]));
+    assert!(job["job_id"].as_str().unwrap().starts_with("face_detection-"));
+
+    // Once two images are processed a single job is left, keeping the id of the batch it was
+    // in, and the descriptor of the finished batch goes away
+    sidecar.save_data(&root.join("a.jpg"), OperationType::FaceDetection, json!({"faces": []})).await.unwrap();
+    sidecar.save_data(&root.join("b.jpg"), OperationType::FaceDetection, json!({"faces": []})).await.unwrap();
+    let result = emit("json");
+    assert_eq!(result["jobs"], 1);
+    assert_eq!(result["files"], json!([files[1]]));
+    assert!(!files[0].exists());
+
+    emit("ndjson");
+    assert_eq!(fs::read_to_string(jobs_dir.join("face_detection.jobs.ndjson")).unwrap().lines().count(), 1);
+}
+
+#[tokio::test]
+async fn test_recency_filters_and_ordering_select_recent_files() {
+    use image_sidecar_rust::{FileSelection, ScanOrder};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    // (name, days since the epoch for the modification time, image size)
+    for (name, days, size) in [("old", 19_723, 10), ("recent", 20_089, 30), ("newest", 20_240, 20)] {
+        let modified = UNIX_EPOCH + Duration::from_secs(days * 86_400);
+        let image = root.join(format!("{}.jpg", name));
+        fs::write(&image, vec![0u8; size]).unwrap();
+        fs::File::options().write(true).open(&image).unwrap().set_modified(modified).unwrap();
+        let sidecar = root.join(format!("{}.json", name));
+        fs::write(&sidecar, r#"{"sidecar_info": {"operation_type": "face_detection"}}"#).unwrap();
+        fs::File::options().write(true).open(&sidecar).unwrap().set_modified(modified).unwrap();
+    }
+
+    let since = "2024-12-01T00:00:00Z".parse().unwrap();
+    let sidecar = ImageSidecar::builder()
+        .selection(FileSelection { modified_since: Some(since), order: ScanOrder::Newest, ..Default::default() })
+        .build();
+    let validated: Vec<_> = sidecar.validate_sidecars(root).await.unwrap().into_iter().map(|result| result.file_path).collect();
+    assert_eq!(validated, [root.join("newest.json"), root.join("recent.json")]);
+
+    let coverage = |extra: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+            .args(["--json", "coverage", "--input", root.to_str().unwrap(), "--operations", "yolov8"])
+            .args(extra)
+            .output()
+            .unwrap()
+    };
+    let output = coverage(&["--modified-since", "2024-12-01", "--order", "largest"]);
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+    let result = serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()["result"].clone();
+    assert_eq!(result["total_images"], 2);
+    assert_eq!(result["operations"][0]["missing"], json!([root.join("recent.jpg"), root.join("newest.jpg")]));
+
+    let output = coverage(&["--created-before", "01/12/2024"]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[tokio::test]
+async fn test_symlink_policy_places_sidecars_at_link_target_or_both() {
+    use image_sidecar_rust::{OperationRemoval, SymlinkPolicy};
+
+    let temp_dir = TempDir::new().unwrap();
+    let originals = temp_dir.path().join("originals");
+    let farm = temp_dir.path().join("best_shots");
+    fs::create_dir_all(&originals).unwrap();
+    fs::create_dir_all(&farm).unwrap();
+    let target = originals.join("img.jpg");
+    let link = farm.join("best.jpg");
+    fs::write(&target, b"fake image data").unwrap();
+    paths::symlink(&target, &link).unwrap();
+    let with_policy = |policy| ImageSidecar::builder().symlink_policy(policy).build();
+    let payload = json!({"score": 0.9});
+
+    let follow = with_policy(SymlinkPolicy::FollowTarget);
+    follow.save_data(&link, OperationType::QualityAssessment, payload.clone()).await.unwrap();
+    assert!(originals.join("img.bin").exists());
+    assert!(!farm.join("best.bin").exists());
+    fs::remove_file(originals.join("img.bin")).unwrap();
+
+    let at_link = with_policy(SymlinkPolicy::StayAtLink);
+    at_link.save_data(&link, OperationType::QualityAssessment, payload.clone()).await.unwrap();
+    assert!(farm.join("best.bin").exists());
+    assert!(!originals.join("img.bin").exists());
+    assert_eq!(at_link.read_data(&link).await.unwrap()["quality_assessment"], payload);
+    assert_eq!(follow.read_data(&link).await.unwrap(), json!({}));
+    fs::remove_file(farm.join("best.bin")).unwrap();
+
+    let both = with_policy(SymlinkPolicy::Both);
+    both.save_data(&link, OperationType::QualityAssessment, payload.clone()).await.unwrap();
+    assert_eq!(fs::read(farm.join("best.bin")).unwrap(), fs::read(originals.join("img.bin")).unwrap());
+
+    // With the target gone the link's sidecar is only an orphan when following targets
+    fs::rename(&target, originals.join("moved.jpg")).unwrap();
+    assert!(at_link.find_orphaned(&farm).await.unwrap().is_empty());
+    assert_eq!(follow.find_orphaned(&farm).await.unwrap(), [farm.join("best.bin")]);
+    fs::rename(originals.join("moved.jpg"), &target).unwrap();
+
+    assert_eq!(both.remove_operation(&link, OperationType::QualityAssessment).await.unwrap(), OperationRemoval::SidecarDeleted);
+    assert!(!farm.join("best.bin").exists());
+    assert!(!originals.join("img.bin").exists());
+}
+
+#[tokio::test]
+async fn test_broken_symlinks_are_resolved_relative_to_the_link_and_cleaned_up() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::write(root.join("img.jpg"), b"fake image data").unwrap();
+    paths::symlink("img.jpg", root.join("alias.jpg")).unwrap();
+    paths::symlink("missing/gone.jpg", root.join("gone.jpg")).unwrap();
+    fs::write(root.join("gone.json"), r#"{"yolov8": {"count": 1}}"#).unwrap();
+    fs::write(root.join("crop_gone.json"), r#"{"yolov8": {"count": 2}}"#).unwrap();
+
+    // A relative target is followed from the link's directory, whatever the working directory
+    let sidecar = ImageSidecar::new(None);
+    let info = sidecar.save_data(&root.join("alias.jpg"), OperationType::Yolov8, json!({"count": 3})).await.unwrap();
+    assert_eq!(info.sidecar_path, root.join("img.bin"));
+    assert!(!info.symlink_info.unwrap().broken);
+
+    // Following links used to drop dangling ones from the counts
+    let stats = ImageSidecar::builder().follow_links(true).build().get_statistics(root).await.unwrap();
+    assert_eq!((stats.symlink_count, stats.broken_symlinks), (2, 1));
+
+    let run = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+            .args(["--json", "--symlink-policy", "stay-at-link"])
+            .args(args)
+            .args(["--input", root.to_str().unwrap()])
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()["result"].clone()
+    };
+    let broken = run(&["find-broken-symlinks"])["broken"].clone();
+    assert_eq!(broken.as_array().unwrap().len(), 1);
+    assert_eq!(broken[0]["link"], json!(root.join("gone.jpg")));
+    assert_eq!(broken[0]["target"], "missing/gone.jpg");
+    assert_eq!(broken[0]["sidecars"], json!([root.join("crop_gone.json"), root.join("gone.json")]));
+
+    // Kept at the link by the policy until asked for explicitly
+    assert_eq!(run(&["cleanup"])["removed"], 0);
+    assert_eq!(run(&["cleanup", "--broken-symlinks", "--dry-run"])["broken_symlink_sidecars"].as_array().unwrap().len(), 2);
+    assert_eq!(run(&["cleanup", "--broken-symlinks"])["removed"], 2);
+    assert!(!root.join("gone.json").exists());
+    assert!(root.join("img.bin").exists());
+}
+
+// Hardlinks are only detected through Unix metadata
+#[cfg(unix)]
+#[tokio::test]
+async fn test_hardlinked_images_are_reported_and_optionally_merged() {
+    let temp_dir = TempDir::new().unwrap();
+    let (x, y) = (temp_dir.path().join("x"), temp_dir.path().join("y"));
+    fs::create_dir_all(&x).unwrap();
+    fs::create_dir_all(&y).unwrap();
+    fs::write(x.join("a.jpg"), b"fake image data").unwrap();
+    fs::hard_link(x.join("a.jpg"), y.join("b.jpg")).unwrap();
+    fs::write(y.join("c.jpg"), b"other image data").unwrap();
+    let roots = [x.clone(), y.clone()];
+
+    let separate = ImageSidecar::new(None);
+    let stats = separate.get_statistics_multi(&roots, None).await.unwrap();
+    assert_eq!(stats.aggregate.total_images, 3);
+    let groups = &stats.aggregate.hardlink_groups;
+    assert_eq!(groups.len(), 1);
+    assert_eq!((groups[0].links, groups[0].paths.clone()), (2, vec![x.join("a.jpg"), y.join("b.jpg")]));
+
+    // Merged, both paths share the first one's sidecar
+    let merged = ImageSidecar::builder().merge_hardlinks(true).build();
+    merged.save_data(&x.join("a.jpg"), OperationType::Yolov8, json!({"count": 1})).await.unwrap();
+    merged.save_data(&y.join("b.jpg"), OperationType::FaceDetection, json!({"faces": []})).await.unwrap();
+    assert!(!y.join("b.bin").exists());
+    let data = merged.read_data(&y.join("b.jpg")).await.unwrap();
+    assert!(data.get("yolov8").is_some() && data.get("face_detection").is_some());
+
+    let stats = merged.get_statistics_multi(&roots, None).await.unwrap();
+    assert_eq!(stats.aggregate.total_images, 2);
+    assert_eq!(stats.aggregate.total_sidecars, 1);
+
+    fs::hard_link(x.join("a.jpg"), x.join("d.jpg")).unwrap();
+    let stats = merged.get_statistics(&x).await.unwrap();
+    assert_eq!((stats.total_images, stats.total_sidecars), (1, 1));
+    assert_eq!(stats.hardlink_groups[0].paths, [x.join("a.jpg"), x.join("d.jpg")]);
+
+    // Without a scan, the sidecar goes beside the first link in sorted order, or the one that has one
+    let z = temp_dir.path().join("z");
+    fs::create_dir_all(&z).unwrap();
+    fs::write(z.join("m.jpg"), b"linked image").unwrap();
+    fs::hard_link(z.join("m.jpg"), z.join("n.jpg")).unwrap();
+    let fresh = ImageSidecar::builder().merge_hardlinks(true).build();
+    fresh.save_data(&z.join("n.jpg"), OperationType::Yolov8, json!({"count": 1})).await.unwrap();
+    assert!(z.join("m.bin").exists() && !z.join("n.bin").exists());
+    fs::write(z.join("p.jpg"), b"another linked image").unwrap();
+    fs::hard_link(z.join("p.jpg"), z.join("q.jpg")).unwrap();
+    ImageSidecar::new(None).save_data(&z.join("q.jpg"), OperationType::Yolov8, json!({"count": 2})).await.unwrap();
+    let fresh = ImageSidecar::builder().merge_hardlinks(true).build();
+    fresh.save_data(&z.join("p.jpg"), OperationType::FaceDetection, json!({"faces": []})).await.unwrap();
+    assert!(!z.join("p.bin").exists());
+    assert!(fresh.read_data(&z.join("p.jpg")).await.unwrap().get("yolov8").is_some());
+}
+
+#[test]
+fn test_windows_long_path_forms_round_trip() {
+    assert_eq!(paths::verbatim(r"C:\photos\game.jpg").as_deref(), Some(r"\\?\C:\photos\game.jpg"));
+    assert_eq!(paths::verbatim("D:/photos/game.jpg").as_deref(), Some(r"\\?\D:\photos\game.jpg"));
+    assert_eq!(paths::verbatim(r"\\nas\share\game.jpg").as_deref(), Some(r"\\?\UNC\nas\share\game.jpg"));
+    assert_eq!(paths::verbatim(r"\\?\C:\photos"), None);
+    assert_eq!(paths::verbatim(r"photos\game.jpg"), None);
+
+    assert_eq!(paths::without_verbatim(r"\\?\C:\photos\game.jpg").as_deref(), Some(r"C:\photos\game.jpg"));
+    assert_eq!(paths::without_verbatim(r"\\?\UNC\nas\share\game.jpg").as_deref(), Some(r"\\nas\share\game.jpg"));
+    assert_eq!(paths::without_verbatim(r"\\?\Volume{1234}\game.jpg"), None);
+    assert_eq!(paths::without_verbatim("/photos/game.jpg"), None);
+
+    // Paths that fit lose the prefix; longer ones keep it, since only the prefix makes them usable
+    assert_eq!(paths::display_path(PathBuf::from(r"\\?\C:\photos\game.jpg")), PathBuf::from(r"C:\photos\game.jpg"));
+    let deep = format!(r"\\?\C:\{}\game.jpg", "d".repeat(paths::MAX_PATH));
+    assert_eq!(paths::display_path(PathBuf::from(&deep)), PathBuf::from(&deep));
+
+    // Paths found under a long root are reported under the root as given
+    let rebased = paths::rebase(PathBuf::from("/long/root/a/b.jpg"), Path::new("/long/root"), Path::new("photos"));
+    assert_eq!(rebased, Path::new("photos").join("a/b.jpg"));
+    assert_eq!(paths::rebase(PathBuf::from("/elsewhere/b.jpg"), Path::new("/long/root"), Path::new("photos")), PathBuf::from("/elsewhere/b.jpg"));
+
+    // Off Windows the long form is the path itself
+    if !cfg!(windows) {
+        assert_eq!(paths::long_path(Path::new("photos/game.jpg")), Path::new("photos/game.jpg"));
+    }
+}
+
+// Linux file names are arbitrary bytes; other platforms cannot create this name
+#[cfg(unix)]
+#[tokio::test]
+async fn test_non_utf8_image_names_survive_json_round_trips() {
+    use std::os::unix::ffi::OsStrExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let image = temp_dir.path().join(std::ffi::OsStr::from_bytes(b"caf\xe9.jpg"));
+    fs::write(&image, b"fake image data").unwrap();
+    // A pattern-named sidecar for the same image
+    fs::write(temp_dir.path().join(std::ffi::OsStr::from_bytes(b"yolo_caf\xe9.json")), r#"{"yolov8": {"detections": []}}"#).unwrap();
+
+    let sidecar = ImageSidecar::new(None);
+    let info = sidecar.save_data(&image, OperationType::Yolov8, json!({"detections": []})).await.unwrap();
+    assert_eq!(info.sidecar_path, image.with_extension("bin"));
+
+    // The sidecar records the image path losslessly instead of through U+FFFD
+    let written = FormatManager::new().get_serializer(SidecarFormat::Binary).deserialize(&fs::read(&info.sidecar_path).unwrap()).unwrap();
+    let recorded = &written["sidecar_info"]["image_path"];
+    assert!(recorded[paths::PERCENT_ENCODED_KEY].as_str().unwrap().ends_with("caf%E9.jpg"));
+    assert_eq!(paths::path_from_json(recorded).unwrap(), image);
+
+    // Result types serialize without error and read back to the same paths
+    let stats = sidecar.get_statistics(temp_dir.path()).await.unwrap();
+    let round_trip: image_sidecar_rust::StatisticsResult = serde_json::from_str(&serde_json::to_string(&stats).unwrap()).unwrap();
+    assert_eq!(round_trip.sidecars.len(), stats.sidecars.len());
+    assert!(round_trip.sidecars.iter().any(|found| found.image_path == image));
+
+    // Neither sidecar counts as an orphan, including the pattern-named one
+    assert!(sidecar.find_orphaned(temp_dir.path()).await.unwrap().is_empty());
+
+    // Path lists and checkpoints keep the raw name
+    let mut list = Vec::new();
+    paths::write_path_line(&mut list, &image).unwrap();
+    assert_eq!(paths::path_from_bytes(&list[..list.len() - 1]), image);
+    let mut checkpoint = image_sidecar_rust::Checkpoint::open(temp_dir.path().join("run.checkpoint")).unwrap();
+    checkpoint.record(&info.sidecar_path).unwrap();
+    drop(checkpoint);
+    let checkpoint = image_sidecar_rust::Checkpoint::open(temp_dir.path().join("run.checkpoint")).unwrap();
+    assert!(checkpoint.contains(&info.sidecar_path));
+
+    // Text-only exports fall back to percent-encoding
+    assert!(paths::encode_path(&image).ends_with("caf%E9.jpg"));
+    assert_eq!(paths::decode_path("caf%E9.jpg").unwrap().as_os_str().as_bytes(), b"caf\xe9.jpg");
+    assert_eq!(paths::encode_path(Path::new("plain.jpg")), "plain.jpg");
+}
+
+#[tokio::test]
+async fn test_sidecars_record_image_fingerprints_and_report_stale_ones() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    for name in ["a.jpg", "b.jpg", "c.jpg"] {
+        fs::write(root.join(name), format!("pixels of {}", name)).unwrap();
+    }
+    let sidecar = ImageSidecar::new(None);
+    let written = sidecar.save_data(&root.join("a.jpg"), OperationType::Yolov8, json!({"count": 1})).await.unwrap();
+    sidecar.save_data(&root.join("b.jpg"), OperationType::Yolov8, json!({"count": 2})).await.unwrap();
+    // A sidecar from before fingerprints were recorded
+    fs::write(root.join("c.json"), r#"{"yolov8": {"count": 3}}"#).unwrap();
+
+    let data = FormatManager::new().get_serializer(SidecarFormat::Binary).deserialize(&fs::read(&written.sidecar_path).unwrap()).unwrap();
+    let fingerprint = image_sidecar_rust::ImageFingerprint::from_sidecar(&data).unwrap();
+    assert_eq!(fingerprint, image_sidecar_rust::ImageFingerprint::of(&root.join("a.jpg")).unwrap());
+    assert_eq!(fingerprint.size, "pixels of a.jpg".len() as u64);
+
+    // A re-exported frame changes the contents; rewriting the same bytes only touches the file
+    fs::write(root.join("a.jpg"), "a different frame").unwrap();
+    fs::write(root.join("b.jpg"), "pixels of b.jpg").unwrap();
+
+    for deep in [false, true] {
+        let stale = sidecar.find_stale_sidecars(root, deep).await.unwrap();
+        let reasons: Vec<(String, image_sidecar_rust::StaleReason)> = stale.iter()
+            .map(|entry| (entry.image_path.file_name().unwrap().to_string_lossy().into_owned(), entry.reason))
+            .collect();
+        assert_eq!(reasons, vec![
+            ("a.jpg".to_string(), image_sidecar_rust::StaleReason::ImageChanged),
+            ("c.jpg".to_string(), image_sidecar_rust::StaleReason::Unrecorded),
+        ]);
+        assert_eq!(stale[0].current.as_ref().unwrap().size, "a different frame".len() as u64);
+    }
+
+    let stale = |root: &Path| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+            .args(["--json", "stale", "--input", root.to_str().unwrap()])
+            .output()
+            .unwrap();
+        (output.status.code(), serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap())
+    };
+    let (code, report) = stale(root);
+    assert_eq!(code, Some(4));
+    assert_eq!(report["result"]["changed"], 1);
+    assert_eq!(report["result"]["stale"][0]["reason"], "image_changed");
+
+    // Saving again records the new image, after which only the unrecorded sidecar is listed
+    sidecar.save_data(&root.join("a.jpg"), OperationType::Yolov8, json!({"count": 4})).await.unwrap();
+    let (code, report) = stale(root);
+    assert_eq!(code, Some(0));
+    assert_eq!(report["result"]["stale"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_reassociate_moves_orphaned_sidecars_to_renamed_images() {
+    use image_sidecar_rust::{ImageFingerprint, ReassociationOutcome};
+
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::create_dir(root.join("sub")).unwrap();
+    fs::write(root.join("a.jpg"), "frame a").unwrap();
+    fs::write(root.join("b.jpg"), "frame b").unwrap();
+    fs::write(root.join("d.jpg"), "frame d").unwrap();
+    let sidecar = ImageSidecar::new(None);
+    sidecar.save_data(&root.join("a.jpg"), OperationType::Yolov8, json!({"count": 1})).await.unwrap();
+    sidecar.save_data(&root.join("d.jpg"), OperationType::Yolov8, json!({"count": 4})).await.unwrap();
+    // A pattern-named sidecar written by another tool for b.jpg
+    let pattern = json!({
+        "face_detection": {"faces": []},
+        "sidecar_info": {"image_path": root.join("b.jpg"), "image_fingerprint": ImageFingerprint::of(&root.join("b.jpg")).unwrap()},
+    });
+    fs::write(root.join("faces_b.json"), pattern.to_string()).unwrap();
+    // An orphan from before fingerprints were recorded
+    fs::write(root.join("e.json"), r#"{"yolov8": {"count": 5}}"#).unwrap();
+
+    // a.jpg moves into sub/ under a new name, b.jpg is renamed, d.jpg is copied twice and removed
+    fs::rename(root.join("a.jpg"), root.join("sub/renamed.jpg")).unwrap();
+    fs::rename(root.join("b.jpg"), root.join("c.jpg")).unwrap();
+    fs::copy(root.join("d.jpg"), root.join("d1.jpg")).unwrap();
+    fs::copy(root.join("d.jpg"), root.join("d2.jpg")).unwrap();
+    fs::remove_file(root.join("d.jpg")).unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+        .args(["--json", "reassociate", "--input", root.to_str().unwrap(), "--dry-run"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["result"]["moved"], 2);
+    assert!(root.join("a.bin").exists());
+
+    let records = sidecar.reassociate(root, false).await.unwrap();
+    let outcomes: Vec<(String, ReassociationOutcome)> = records.iter()
+        .map(|record| (record.sidecar_path.file_name().unwrap().to_string_lossy().into_owned(), record.outcome))
+        .collect();
+    assert_eq!(outcomes, vec![
+        ("a.bin".to_string(), ReassociationOutcome::Moved),
+        ("d.bin".to_string(), ReassociationOutcome::Ambiguous),
+        ("e.json".to_string(), ReassociationOutcome::Unrecorded),
+        ("faces_b.json".to_string(), ReassociationOutcome::Moved),
+    ]);
+    assert_eq!(records[1].candidates, vec![root.join("d1.jpg"), root.join("d2.jpg")]);
+
+    // The data follows the image and records where it now lives
+    assert!(!root.join("a.bin").exists());
+    let data = sidecar.read_data(&root.join("sub/renamed.jpg")).await.unwrap();
+    assert_eq!(data["yolov8"]["count"], 1);
+    let moved = FormatManager::new().get_serializer(SidecarFormat::Binary).deserialize(&fs::read(root.join("sub/renamed.bin")).unwrap()).unwrap();
+    assert_eq!(paths::path_from_json(&moved["sidecar_info"]["image_path"]).unwrap(), root.join("sub/renamed.jpg"));
+    let faces: serde_json::Value = serde_json::from_str(&fs::read_to_string(root.join("faces_c.json")).unwrap()).unwrap();
+    assert_eq!(faces["face_detection"], json!({"faces": []}));
+
+    // Only the orphans that could not be placed are left for cleanup
+    let mut orphaned = sidecar.find_orphaned(root).await.unwrap();
+    orphaned.sort();
+    assert_eq!(orphaned, vec![root.join("d.bin"), root.join("e.json")]);
+}
+
+#[tokio::test]
+async fn test_relocate_moves_images_with_their_sidecars() {
+    let temp_dir = TempDir::new().unwrap();
+    let game = temp_dir.path().join("season/game1");
+    fs::create_dir_all(&game).unwrap();
+    for name in ["a.jpg", "x_a.jpg", "b.jpg"] {
+        fs::write(game.join(name), name).unwrap();
+    }
+    let sidecar = ImageSidecar::new(None);
+    sidecar.save_data(&game.join("a.jpg"), OperationType::Yolov8, json!({"count": 1})).await.unwrap();
+    sidecar.save_data(&game.join("b.jpg"), OperationType::Yolov8, json!({"count": 2})).await.unwrap();
+    fs::write(game.join("faces_a.json"), json!({"face_detection": {}, "sidecar_info": {"image_path": game.join("a.jpg")}}).to_string()).unwrap();
+    // Named like a pattern sidecar of a.jpg, but it belongs to x_a.jpg
+    fs::write(game.join("x_a.json"), r#"{"yolov8": {"count": 3}}"#).unwrap();
+
+    let relocate = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+            .arg("--json")
+            .arg("relocate")
+            .args(args)
+            .output()
+            .unwrap();
+        (output.status.code(), serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap())
+    };
+    let from = game.join("a.jpg");
+    let to = game.join("renamed.jpg");
+    let (code, report) = relocate(&["--from", from.to_str().unwrap(), "--to", to.to_str().unwrap(), "--dry-run"]);
+    assert_eq!(code, Some(0));
+    assert_eq!(report["result"]["records"][0]["sidecars"].as_array().unwrap().len(), 2);
+    assert!(from.exists());
+
+    let (code, _) = relocate(&["--from", from.to_str().unwrap(), "--to", to.to_str().unwrap()]);
+    assert_eq!(code, Some(0));
+    assert!(!from.exists() && to.exists());
+    assert!(!game.join("a.bin").exists() && !game.join("faces_a.json").exists());
+    assert!(game.join("x_a.json").exists());
+    assert_eq!(sidecar.read_data(&to).await.unwrap()["yolov8"]["count"], 1);
+    let faces: serde_json::Value = serde_json::from_str(&fs::read_to_string(game.join("faces_renamed.json")).unwrap()).unwrap();
+    assert_eq!(paths::path_from_json(&faces["sidecar_info"]["image_path"]).unwrap(), to);
+
+    // Nothing is replaced, and a refused move leaves every file in place
+    let (code, report) = relocate(&["--from", to.to_str().unwrap(), "--to", game.join("b.jpg").to_str().unwrap()]);
+    assert_eq!(code, Some(3));
+    assert!(report["result"]["records"][0]["error"].as_str().unwrap().contains("already exists"));
+    assert!(to.exists() && game.join("renamed.bin").exists());
+    let kept = FormatManager::new().get_serializer(SidecarFormat::Binary).deserialize(&fs::read(game.join("renamed.bin")).unwrap()).unwrap();
+    assert_eq!(paths::path_from_json(&kept["sidecar_info"]["image_path"]).unwrap(), to);
+
+    // A sidecar whose image_path cannot be updated keeps its image from moving at all
+    fs::write(game.join("c.jpg"), "c.jpg").unwrap();
+    fs::write(game.join("c.json"), "{ not json").unwrap();
+    let (code, report) = relocate(&["--from", game.join("c.jpg").to_str().unwrap(), "--to", game.join("d.jpg").to_str().unwrap()]);
+    assert_eq!(code, Some(3));
+    assert!(report["result"]["records"][0]["error"].as_str().unwrap().contains("Cannot update image_path"));
+    assert!(game.join("c.jpg").exists() && game.join("c.json").exists() && !game.join("d.jpg").exists());
+    fs::remove_file(game.join("c.json")).unwrap();
+
+    // A mapping moves a whole directory; recorded image paths follow it
+    let mapping = temp_dir.path().join("moves.csv");
+    let moved_game = temp_dir.path().join("season/game, renamed");
+    fs::write(&mapping, format!("from,to\n# reorganize\n{},\"{}\"\n", game.display(), moved_game.display())).unwrap();
+    let (code, report) = relocate(&["--mapping", mapping.to_str().unwrap()]);
+    assert_eq!(code, Some(0));
+    assert_eq!(report["result"]["moved"], 1);
+    assert!(!game.exists());
+    let moved = FormatManager::new().get_serializer(SidecarFormat::Binary).deserialize(&fs::read(moved_game.join("b.bin")).unwrap()).unwrap();
+    assert_eq!(paths::path_from_json(&moved["sidecar_info"]["image_path"]).unwrap(), moved_game.join("b.jpg"));
+    assert_eq!(sidecar.read_data(&moved_game.join("renamed.jpg")).await.unwrap()["yolov8"]["count"], 1);
+}
+
+#[tokio::test]
+async fn test_transactions_apply_all_changes_or_none() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    fs::write(dir.join("old.json"), r#"{"yolov8": {}}"#).unwrap();
+    fs::write(dir.join("stale.json"), r#"{"yolov8": {}}"#).unwrap();
+    let sidecar = ImageSidecar::new(None);
+    let leftovers = || {
+        fs::read_dir(dir).unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name.contains("txn"))
+            .collect::<Vec<_>>()
+    };
+
+    let mut transaction = sidecar.transaction(dir);
+    transaction.write(dir.join("a.json"), &json!({"face_detection": {"faces": 1}})).unwrap();
+    transaction.write(dir.join("a.bin"), &json!({"yolov8": {"count": 2}})).unwrap();
+    transaction.rename(dir.join("old.json"), dir.join("new.json")).remove(dir.join("stale.json"));
+    assert_eq!(transaction.commit().await.unwrap(), 4);
+    let written: serde_json::Value = serde_json::from_str(&fs::read_to_string(dir.join("a.json")).unwrap()).unwrap();
+    assert_eq!(written["face_detection"]["faces"], 1);
+    let binary = FormatManager::new().get_serializer(SidecarFormat::Binary).deserialize(&fs::read(dir.join("a.bin")).unwrap()).unwrap();
+    assert_eq!(binary["yolov8"]["count"], 2);
+    assert!(dir.join("new.json").exists() && !dir.join("old.json").exists() && !dir.join("stale.json").exists());
+    assert!(leftovers().is_empty());
+
+    // A step that cannot be applied stops the whole batch before anything changes
+    let mut transaction = sidecar.transaction(dir);
+    transaction.write(dir.join("b.json"), &json!({})).unwrap();
+    transaction.rename(dir.join("new.json"), dir.join("a.json"));
+    assert!(transaction.commit().await.is_err());
+    assert!(!dir.join("b.json").exists() && dir.join("new.json").exists());
+    assert!(leftovers().is_empty());
+
+    // Journals left by crashes: one past its commit marker with a step done, one before it
+    let path = |name: &str| serde_json::Value::String(dir.join(name).to_string_lossy().into_owned());
+    fs::write(dir.join("c.json.txn-1"), r#"{"pose": {}}"#).unwrap();
+    fs::write(dir.join("d.json.txn-1"), r#"{"pose": {}}"#).unwrap();
+    let begin = json!({"begin": {"id": "1", "steps": [
+        {"write": {"path": path("c.json"), "staged": path("c.json.txn-1")}},
+        {"rename": {"from": path("c.json"), "to": path("e.json")}},
+        {"write": {"path": path("d.json"), "staged": path("d.json.txn-1")}},
+    ]}});
+    fs::write(dir.join(".sidecar-txn-1.journal"), format!("{}\n\"commit\"\n{{\"done\":0}}\n{{\"do", begin)).unwrap();
+    fs::rename(dir.join("c.json.txn-1"), dir.join("c.json")).unwrap();
+    // The second was committing in a subdirectory
+    fs::create_dir(dir.join("nested")).unwrap();
+    fs::write(dir.join("nested/f.json.txn-2"), "{}").unwrap();
+    let begin = json!({"begin": {"id": "2", "steps": [{"write": {"path": path("nested/f.json"), "staged": path("nested/f.json.txn-2")}}]}});
+    fs::write(dir.join("nested/.sidecar-txn-2.journal"), format!("{}\n", begin)).unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+        .args(["--json", "recover", "--input", dir.to_str().unwrap()])
+        .output()
+        .unwrap();
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["result"]["rolled_forward"], 1);
+    assert_eq!(report["result"]["rolled_back"], 1);
+    assert!(dir.join("e.json").exists() && dir.join("d.json").exists() && !dir.join("c.json").exists());
+    assert!(!dir.join("nested/f.json").exists() && fs::read_dir(dir.join("nested")).unwrap().next().is_none());
+    assert!(leftovers().is_empty());
+    assert_eq!(sidecar.recover_transactions(dir).await.unwrap(), RecoverySummary::default());
+}
+
+#[tokio::test]
+async fn test_import_legacy_rewrites_suffix_and_detector_keyed_sidecars() {
+    use image_sidecar_rust::LegacyImporter;
+    use image_sidecar_rust::sidecar::SuffixLayout;
+
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    for name in ["IMG_0001.jpg", "IMG_0002.jpg", "game_ball.jpg"] {
+        fs::write(dir.join(name), name).unwrap();
+    }
+    let sidecar = ImageSidecar::new(None);
+    sidecar.save_data(&dir.join("IMG_0001.jpg"), OperationType::Yolov8, json!({"count": 3})).await.unwrap();
+    fs::write(dir.join("IMG_0001_faces.json"), json!({"faces": [{"bbox": [1, 2, 3, 4]}]}).to_string()).unwrap();
+    fs::write(dir.join("IMG_0001_yolo.json"), json!({"count": 9}).to_string()).unwrap();
+    fs::write(dir.join("IMG_0002.json"), json!({"Face_detector": {"faces": 1}, "Ball_detector": {"balls": 2}}).to_string()).unwrap();
+    fs::write(dir.join("IMG_0002_players.json"), json!({"players": 11}).to_string()).unwrap();
+    // Keyed by detector, so it belongs to game_ball.jpg rather than being a ball payload of game.jpg
+    fs::write(dir.join("game_ball.json"), json!({"Game_detector": {"game": "final"}}).to_string()).unwrap();
+    fs::write(dir.join("IMG_0003_faces.json"), json!({"faces": []}).to_string()).unwrap();
+
+    let importer = LegacyImporter::default();
+    let report = sidecar.import_legacy(dir, &importer, false, true).await.unwrap();
+    assert_eq!(report.records.len(), 3);
+    assert_eq!(report.orphaned, vec![dir.join("IMG_0003_faces.json")]);
+    assert_eq!(report.unrecognized, vec![dir.join("IMG_0002_players.json")]);
+    assert_eq!(report.modern, 1);
+    assert!(dir.join("IMG_0002.json").exists() && !dir.join("IMG_0002.bin").exists());
+
+    let importer = LegacyImporter::default().layout(SuffixLayout::default().suffix("players", "player_tracking"));
+    let report = sidecar.import_legacy(dir, &importer, false, false).await.unwrap();
+    assert_eq!(report.failed(), 0);
+    let first = &report.records[0];
+    assert_eq!(first.image_path, dir.join("IMG_0001.jpg"));
+    assert_eq!((first.operations.clone(), first.kept.clone()), (vec!["face_detection".to_string()], vec!["yolov8".to_string()]));
+    // The legacy yolov8 payload lost to the modern one, so its file stays and is reported
+    assert_eq!(first.removed, vec![dir.join("IMG_0001_faces.json")]);
+    assert_eq!(first.conflicts, vec![dir.join("IMG_0001_yolo.json")]);
+    assert!(dir.join("IMG_0001_yolo.json").exists() && !dir.join("IMG_0001_faces.json").exists());
+
+    let img1 = sidecar.read_data(&dir.join("IMG_0001.jpg")).await.unwrap();
+    assert_eq!(img1["yolov8"]["count"], 3);
+    assert_eq!(img1["face_detection"]["faces"][0]["bbox"], json!([1, 2, 3, 4]));
+    let sources = &img1["sidecar_info"]["migrated_from"];
+    assert_eq!(paths::path_from_json(&sources["face_detection"]).unwrap(), dir.join("IMG_0001_faces.json"));
+
+    let img2 = sidecar.read_data(&dir.join("IMG_0002.jpg")).await.unwrap();
+    assert_eq!(img2["face_detection"]["faces"], 1);
+    assert_eq!(img2["ball_detection"]["balls"], 2);
+    assert_eq!(img2["player_tracking"]["players"], 11);
+    assert!(img2.get("Face_detector").is_none());
+    assert!(!dir.join("IMG_0002.json").exists() && !dir.join("IMG_0002_players.json").exists());
+
+    let game = sidecar.read_data(&dir.join("game_ball.jpg")).await.unwrap();
+    assert_eq!(game["game_detection"]["game"], "final");
+    assert!(dir.join("IMG_0003_faces.json").exists());
+}
+
+#[tokio::test]
+async fn test_sidecar_patterns_tie_named_sidecars_to_images_and_operations() {
+    use image_sidecar_rust::SidecarPatterns;
+
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    fs::write(dir.join("IMG_0001.jpg"), b"fake image data").unwrap();
+    fs::write(dir.join("IMG_0001_faces.json"), json!({"faces": []}).to_string()).unwrap();
+    fs::write(dir.join("IMG_0001.yolo.json"), json!({"count": 2}).to_string()).unwrap();
+
+    // The built-in rule cannot place either file
+    let plain = ImageSidecar::new(None);
+    assert_eq!(plain.find_orphaned(dir).await.unwrap().len(), 2);
+
+    let patterns = SidecarPatterns::default()
+        .with("{stem}_faces.json", OperationType::FaceDetection).unwrap()
+        .with("{stem}.yolo.json", OperationType::Yolov8).unwrap();
+    let sidecar = ImageSidecar::builder().sidecar_patterns(patterns).build();
+    assert!(sidecar.find_orphaned(dir).await.unwrap().is_empty());
+
+    let stats = sidecar.get_statistics(dir).await.unwrap();
+    assert_eq!(stats.total_sidecars, 2);
+    assert!(stats.sidecars.iter().all(|found| found.image_path == dir.join("IMG_0001.jpg")));
+    assert_eq!(stats.operation_counts.get("face_detection"), Some(&1));
+    assert_eq!(stats.operation_counts.get("yolov8"), Some(&1));
+
+    assert!(SidecarPatterns::default().with("faces.json", OperationType::FaceDetection).is_err());
+    assert!(SidecarPatterns::default().with("{stem}_x.json", OperationType::Unknown).is_err());
+
+    // Config patterns keep the order they are listed in, so the specific one wins here
+    fs::write(dir.join("IMG_0001_faces.json"), json!({"items": []}).to_string()).unwrap();
+    let config_path = dir.join("patterns.toml");
+    fs::write(&config_path, r#"
+[[defaults.sidecar_patterns]]
+template = "{stem}_faces.json"
+operation = "face_detection"
+
+[[defaults.sidecar_patterns]]
+template = "{stem}_*.json"
+operation = "quality_assessment"
+"#).unwrap();
+    let patterns = Config::load(&config_path).unwrap().resolve(None).unwrap().sidecar_patterns().unwrap().unwrap();
+    let sidecar = ImageSidecar::builder().sidecar_patterns(patterns).build();
+    let stats = sidecar.get_statistics(dir).await.unwrap();
+    assert_eq!(stats.operation_counts.get("face_detection"), Some(&1), "{:?}", stats.operation_counts);
+    assert_eq!(stats.operation_counts.get("quality_assessment"), None);
+}
+
+#[tokio::test]
+async fn test_operation_rules_classify_custom_detector_output() {
+    use image_sidecar_rust::ClassifierRule;
+
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    let outputs = [
+        ("a", json!({"tool_name": "detectron2-r50", "instances": []})),
+        ("b", json!({"result": {"faces": [{"bbox": [0, 0, 4, 4]}]}})),
+        ("c", json!({"Face_detector": {"faces": []}})),
+        ("d", json!({"something": "else"})),
+    ];
+    for (stem, output) in &outputs {
+        fs::write(dir.join(format!("{}.jpg", stem)), b"fake image data").unwrap();
+        fs::write(dir.join(format!("{}.json", stem)), output.to_string()).unwrap();
+    }
+
+    let config_path = dir.join("rules.toml");
+    fs::write(&config_path, r#"
+[[defaults.operation_rules]]
+operation = "object_detection"
+tool_name = "^detectron2"
+
+[[defaults.operation_rules]]
+operation = "face_detection"
+pointer = "/result/faces"
+"#).unwrap();
+    let rules = Config::load(&config_path).unwrap().resolve(None).unwrap().operation_rules().unwrap();
+    assert_eq!(rules.len(), 2);
+    let sidecar = rules.into_iter().fold(ImageSidecar::builder(), |builder, rule| builder.operation_rule(rule)).build();
+
+    let stats = sidecar.get_statistics(dir).await.unwrap();
+    let operation_of = |stem: &str| {
+        stats.sidecars.iter().find(|found| found.image_path == dir.join(format!("{}.jpg", stem))).unwrap().operation.clone()
+    };
+    assert_eq!(operation_of("a"), OperationType::ObjectDetection);
+    assert_eq!(operation_of("b"), OperationType::FaceDetection);
+    assert_eq!(operation_of("c"), OperationType::FaceDetection);
+    assert_eq!(operation_of("d"), OperationType::Unknown);
+
+    // Custom rules come before the built-in keys
+    let sidecar = ImageSidecar::builder().operation_rule(ClassifierRule::key("Face_detector", OperationType::QualityAssessment)).build();
+    let stats = sidecar.get_statistics(dir).await.unwrap();
+    assert_eq!(stats.operation_counts.get("quality_assessment"), Some(&1));
+
+    assert!(ClassifierRule::tool_name("(", OperationType::Yolov8).is_err());
+    assert!(ClassifierRule::pointer("result", OperationType::Yolov8).is_err());
+}
+
+#[tokio::test]
+async fn test_triage_unknown_groups_by_key_signature() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    let outputs = [
+        ("a", json!({"model": "posenet", "keypoints": [1, 2, 3, 4, 5]})),
+        ("b", json!({"keypoints": [], "model": "posenet-v2"})),
+        ("c", json!({"scores": {"blur": 0.2}})),
+        ("d", json!({"Face_detector": {"faces": []}})),
+    ];
+    for (stem, output) in &outputs {
+        fs::write(dir.join(format!("{}.jpg", stem)), b"fake image data").unwrap();
+        fs::write(dir.join(format!("{}.json", stem)), output.to_string()).unwrap();
+    }
+
+    let report = ImageSidecar::new(None).triage_unknown(dir, 1).await.unwrap();
+    assert_eq!(report.total_unknown, 3);
+    assert_eq!(report.groups.len(), 2);
+    let poses = &report.groups[0];
+    assert_eq!(poses.signature, vec!["keypoints".to_string(), "model".to_string()]);
+    assert_eq!(poses.count, 2);
+    assert_eq!(poses.sidecars, vec![dir.join("a.json"), dir.join("b.json")]);
+    assert_eq!(poses.tool_names.iter().cloned().collect::<Vec<_>>(), vec!["posenet", "posenet-v2"]);
+    assert_eq!(poses.samples, vec![json!({"model": "posenet", "keypoints": [1, 2, 3, "... 2 more"]})]);
+    assert_eq!(report.groups[1].signature, vec!["scores".to_string()]);
+}
+
+#[tokio::test]
+async fn test_detection_filter_bounds_validation_and_queries() {
+    use image_sidecar_rust::DetectionFilter;
+
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    let writer = ImageSidecar::new(None);
+    let payloads = [
+        ("empty", OperationType::Yolov8, json!({"count": 0})),
+        ("busy", OperationType::Yolov8, json!({"count": 4})),
+        ("unsure", OperationType::FaceDetection, json!({"faces": [{"confidence": 0.3}, {"confidence": 0.9}]})),
+        ("sure", OperationType::FaceDetection, json!({"faces": [{"score": 0.95}]})),
+    ];
+    for (stem, operation, payload) in payloads {
+        let image_path = dir.join(format!("{}.jpg", stem));
+        fs::write(&image_path, b"fake image data").unwrap();
+        writer.save_data(&image_path, operation, payload).await.unwrap();
+    }
+    fs::write(dir.join("broken.json"), "{not json").unwrap();
+    let everything = QueryExpr::parse("true").unwrap();
+    let stems = |matches: Vec<image_sidecar_rust::QueryMatch>| {
+        let mut stems: Vec<String> = matches.iter().map(|m| m.sidecar.image_path.file_stem().unwrap().to_string_lossy().into_owned()).collect();
+        stems.sort();
+        stems
+    };
+
+    let nothing_found = ImageSidecar::builder().detection_filter(DetectionFilter::default().operation("yolov8").max_detections(0)).build();
+    assert_eq!(stems(nothing_found.query(dir, &everything).await.unwrap()), ["empty"]);
+
+    let low_confidence = DetectionFilter::default().operation("face_detection").max_confidence(0.5);
+    let sidecar = ImageSidecar::builder().detection_filter(low_confidence.clone()).build();
+    assert_eq!(stems(sidecar.query(dir, &everything).await.unwrap()), ["unsure"]);
+
+    // Validation reports the matching sidecars and every invalid one
+    let mut results: Vec<PathBuf> = sidecar.validate_sidecars(dir).await.unwrap().into_iter().map(|result| result.file_path).collect();
+    results.sort();
+    assert_eq!(results, [dir.join("broken.json"), dir.join("unsure.bin")]);
+
+    let confident = ImageSidecar::builder().detection_filter(DetectionFilter::default().min_confidence(0.9).min_detections(1)).build();
+    assert_eq!(stems(confident.query(dir, &everything).await.unwrap()), ["sure", "unsure"]);
+}
+
+#[tokio::test]
+async fn test_timeline_orders_frames_and_finds_gaps() {
+    use image_sidecar_rust::sidecar::GapKind;
+    use image_sidecar_rust::{FrameNaming, TimelineInterval, TimelineOptions};
+
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    let sidecar = ImageSidecar::new(None);
+    let game = dir.join("game1");
+    fs::create_dir_all(&game).unwrap();
+    // Frame 3 was never written, and frames 4 and 5 are not processed yet
+    for (index, detections) in [(0, Some(2)), (1, Some(0)), (2, Some(1)), (4, None), (5, None)] {
+        let image = game.join(format!("cam1_20241219_10300{}_{:06}.jpg", index, index));
+        fs::write(&image, b"fake image data").unwrap();
+        if let Some(detections) = detections {
+            sidecar.save_data(&image, OperationType::Yolov8, json!({"detections": vec![json!({"class": "person"}); detections]})).await.unwrap();
+        }
+    }
+    let drone = dir.join("drone");
+    fs::create_dir_all(&drone).unwrap();
+    for time in ["100000", "100010", "100020", "100100"] {
+        fs::write(drone.join(format!("20241219T{}.jpg", time)), b"fake image data").unwrap();
+    }
+    fs::write(dir.join("cover.jpg"), b"fake image data").unwrap();
+
+    let options = TimelineOptions::default().interval(TimelineInterval::parse("2s").unwrap());
+    let timeline = sidecar.timeline(dir, &options).await.unwrap();
+    assert_eq!(timeline.unparsed, [dir.join("cover.jpg")]);
+    assert_eq!(timeline.sequences.len(), 2);
+
+    let drone = &timeline.sequences[0];
+    assert_eq!(drone.index_step, None);
+    let time_gaps: Vec<_> = drone.gaps.iter().filter(|gap| gap.kind == GapKind::TimeGap).collect();
+    assert_eq!(time_gaps.len(), 1);
+    assert_eq!(time_gaps[0].seconds, Some(40.0));
+
+    let game = &timeline.sequences[1];
+    assert_eq!((game.frames, game.index_step, game.last.index), (5, Some(1), Some(5)));
+    let gaps: Vec<(GapKind, u64)> = game.gaps.iter().map(|gap| (gap.kind, gap.frames)).collect();
+    assert_eq!(gaps, [(GapKind::MissingFrames, 1), (GapKind::MissingSidecars, 2)]);
+    let buckets: Vec<(usize, usize, f64)> = game.buckets.iter().map(|b| (b.frames, b.detections, b.detection_rate)).collect();
+    assert_eq!(buckets, [(2, 2, 1.0), (1, 1, 1.0), (2, 0, 0.0)]);
+    assert_eq!(game.buckets[2].frames_with_sidecars, 0);
+
+    // A raw detector file wrapping its detections in `result` counts each of them once
+    let raw = json!({"result": {"detections": [{"class": "person"}, {"class": "ball"}]}});
+    assert_eq!(image_sidecar_rust::sidecar::timeline::sidecar_detections(&raw, "unknown", None), Some(2));
+    assert!(TimelineInterval::parse("99999999999999999h").is_err());
+
+    let named = FrameNaming::pattern(r"shot(?P<index>\d+)", None).unwrap();
+    assert_eq!(named.parse(Path::new("shot42_cam3.jpg")).unwrap().index, Some(42));
+    assert!(FrameNaming::pattern(r"\d+", None).is_err());
+}
+
+#[tokio::test]
+async fn test_tracks_gather_boxes_across_frames() {
+    use image_sidecar_rust::FrameNaming;
+
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    let sidecar = ImageSidecar::new(None);
+    // Written out of order; track 17 skips frame 1, and "ref" is only in the detector output
+    for index in [2, 0, 1] {
+        let image = dir.join(format!("cam1_20241219_103000_{:06}.jpg", index));
+        fs::write(&image, b"fake image data").unwrap();
+        let mut tracks = vec![json!({"track_id": 3, "class_name": "ball", "confidence": 0.6, "bbox": [index * 10, 0, index * 10 + 5, 5]})];
+        if index != 1 {
+            tracks.push(json!({"track_id": 17, "class_name": "player", "confidence": 0.9, "bbox": [100, 100, 140, 200]}));
+        }
+        sidecar.save_data(&image, OperationType::Tracking, json!({"tracker": "bytetrack", "tracks": tracks})).await.unwrap();
+        if index == 0 {
+            sidecar.save_data(&image, OperationType::Yolov8, json!({"detections": [{"track_id": "ref", "class": "referee"}, {"class": "person"}]})).await.unwrap();
+        }
+    }
+
+    let naming = FrameNaming::default();
+    let tracks = sidecar.tracks(dir, &naming).await.unwrap();
+    let ids: Vec<&str> = tracks.iter().map(|track| track.track_id.as_str()).collect();
+    assert_eq!(ids, ["3", "17", "ref"]);
+    let frames: Vec<Option<u64>> = tracks[0].points.iter().map(|point| point.frame.unwrap().index).collect();
+    assert_eq!(frames, [Some(0), Some(1), Some(2)]);
+    assert_eq!(tracks[0].points[2].bbox, Some(json!([20, 0, 25, 5])));
+    assert_eq!(tracks[2].points[0].operation, "yolov8");
+
+    let summary = tracks[1].summary();
+    assert_eq!((summary.class.as_deref(), summary.frames), (Some("player"), 2));
+    assert_eq!(summary.last_frame.unwrap().index, Some(2));
+    assert_eq!(summary.mean_confidence, Some(0.9));
+
+    let track = sidecar.track(dir, "17", &naming).await.unwrap().unwrap();
+    assert_eq!(track.points.len(), 2);
+    assert!(track.points.iter().all(|point| point.operation == "tracking"));
+    assert!(sidecar.track(dir, "99", &naming).await.unwrap().is_none());
+    assert_eq!(OperationType::from_str("tracking"), OperationType::Tracking);
+
+    // A raw detector file wrapping its detections in `data` places each box once
+    fs::write(dir.join("cam2_20241219_103000_000000.jpg"), b"fake image data").unwrap();
+    fs::write(dir.join("cam2_20241219_103000_000000.json"), json!({"data": {"detections": [{"track_id": "solo", "bbox": [0, 0, 4, 4]}]}}).to_string()).unwrap();
+    let solo = sidecar.track(dir, "solo", &naming).await.unwrap().unwrap();
+    assert_eq!(solo.summary().frames, 1);
+}
+
+#[tokio::test]
+async fn test_lint_reports_rules_at_their_levels() {
+    use image_sidecar_rust::sidecar::types::SidecarInfo;
+    use image_sidecar_rust::{LintIssue, LintLevel, LintRule, Linter};
+    use serde_json::Value;
+
+    struct NoPlayers;
+    impl LintRule for NoPlayers {
+        fn name(&self) -> &str {
+            "no_players"
+        }
+        fn description(&self) -> &str {
+            "object detection without a player"
+        }
+        fn check(&self, _sidecar: &SidecarInfo, data: &Value) -> Vec<LintIssue> {
+            match data.pointer("/object_detection/objects") {
+                Some(Value::Array(objects)) if !objects.iter().any(|object| object["class"] == "player") => {
+                    vec![LintIssue::new("/object_detection/objects", "no player detected")]
+                }
+                _ => Vec::new(),
+            }
+        }
+    }
+
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    let sidecar = ImageSidecar::new(None);
+    let clean = dir.join("clean.jpg");
+    fs::write(&clean, b"fake image data").unwrap();
+    sidecar.save_data(&clean, OperationType::FaceDetection, json!({"faces": [{"bbox": [10, 10, 20, 20], "confidence": 0.9}]})).await.unwrap();
+    fs::write(dir.join("messy.jpg"), b"fake image data").unwrap();
+    fs::write(dir.join("messy.json"), serde_json::to_string(&json!({
+        "sidecar_info": {"image_path": "C:\\shoots\\messy.jpg"},
+        "object_detection": {"objects": [
+            {"class": "ball", "bbox": {"x1": 50, "y1": 50, "x2": 40, "y2": 60}, "confidence": null},
+            {"class": "ball", "bbox": [0.1, 0.1, 0.2, 0.2], "confidence": "NaN"}
+        ]},
+        "quality_assessment": {}
+    })).unwrap()).unwrap();
+
+    let report = sidecar.lint(dir, &Linter::default()).await.unwrap();
+    assert_eq!(report.sidecars, 2);
+    let found: Vec<(&str, LintLevel, &str)> = report.findings.iter().map(|f| (f.rule.as_str(), f.level, f.pointer.as_str())).collect();
+    assert_eq!(found, [
+        ("missing_created_at", LintLevel::Warn, "/sidecar_info/created_at"),
+        ("foreign_path", LintLevel::Warn, "/sidecar_info/image_path"),
+        ("denormalized_bbox", LintLevel::Deny, "/object_detection/objects/0/bbox"),
+        ("nan_confidence", LintLevel::Deny, "/object_detection/objects/0/confidence"),
+        ("nan_confidence", LintLevel::Deny, "/object_detection/objects/1/confidence"),
+        ("empty_operation", LintLevel::Warn, "/quality_assessment"),
+    ]);
+    assert!(report.findings.iter().all(|finding| finding.sidecar_path == dir.join("messy.json")));
+    assert!(!report.passed());
+
+    let linter = Linter::default().rule(NoPlayers)
+        .level("all", LintLevel::Allow).unwrap()
+        .level("no_players", LintLevel::Warn).unwrap()
+        .level("warnings", LintLevel::Deny).unwrap();
+    let report = sidecar.lint(dir, &linter).await.unwrap();
+    assert_eq!(report.findings.len(), 1);
+    assert_eq!((report.findings[0].rule.as_str(), report.findings[0].level), ("no_players", LintLevel::Deny));
+    assert!(Linter::default().level("no_such_rule", LintLevel::Deny).is_err());
+}
+
+#[tokio::test]
+async fn test_anomalies_flag_outlier_sidecars() {
+    use image_sidecar_rust::{AnomalyKind, AnomalyOptions};
+
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    let sidecar = ImageSidecar::new(None);
+    // img4 took far longer than the rest, img5 carries a huge debug dump
+    for (index, time) in [0.10, 0.11, 0.12, 0.10, 4.0, 0.11].into_iter().enumerate() {
+        let image = dir.join(format!("img{}.jpg", index));
+        fs::write(&image, b"fake image data").unwrap();
+        let mut payload = json!({"faces": [{"confidence": 0.9}], "processing_time": time});
+        if index == 5 {
+            payload["debug"] = json!("x".repeat(20_000));
+        }
+        sidecar.save_data(&image, OperationType::FaceDetection, payload).await.unwrap();
+    }
+    let crowd = dir.join("crowd.jpg");
+    fs::write(&crowd, b"fake image data").unwrap();
+    sidecar.save_data(&crowd, OperationType::Yolov8, json!({"detections": vec![json!({"class": "person"}); 400]})).await.unwrap();
+
+    let healthy = sidecar.anomalies(dir, &AnomalyOptions::default().max_detections(1000)).await.unwrap();
+    assert_eq!(healthy.sidecars, 7);
+    let kinds: Vec<AnomalyKind> = healthy.anomalies.iter().map(|anomaly| anomaly.kind).collect();
+    assert_eq!(kinds, [AnomalyKind::Oversized]);
+    assert_eq!(healthy.anomalies[0].image_path, dir.join("img5.jpg"));
+    assert_eq!(healthy.baselines["face_detection"].sidecars, 6);
+
+    let options = AnomalyOptions::default().time_percentile(80.0).unwrap();
+    let report = sidecar.anomalies(dir, &options).await.unwrap();
+    let flagged: Vec<(String, AnomalyKind)> = report.anomalies.iter()
+        .map(|anomaly| (anomaly.image_path.file_name().unwrap().to_string_lossy().into_owned(), anomaly.kind))
+        .collect();
+    assert_eq!(flagged, [
+        ("crowd.jpg".to_string(), AnomalyKind::DetectionCount),
+        ("img4.jpg".to_string(), AnomalyKind::SlowProcessing),
+        ("img5.jpg".to_string(), AnomalyKind::Oversized),
+    ]);
+    assert_eq!(report.anomalies[0].value, 400.0);
+    assert!(!report.is_healthy());
+    assert!(AnomalyOptions::default().size_factor(0.5).is_err());
+
+    // A raw detector file wrapping its detections in `data` is one payload, not two
+    let raw = image_sidecar_rust::sidecar::SidecarInfo::new(dir.join("raw.jpg"), dir.join("raw.json"), OperationType::Yolov8, None);
+    let sample = image_sidecar_rust::sidecar::AnomalySample::new(raw, &json!({"data": {"detections": [{"class": "person"}]}}));
+    assert_eq!(sample.detections, [("yolov8".to_string(), 1)]);
+}
+
+#[tokio::test]
+async fn test_bench_times_each_stored_and_compared_format() {
+    use image_sidecar_rust::BenchOptions;
+
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    let sidecar = ImageSidecar::new(None);
+    for index in 0..6 {
+        let image = dir.join(format!("img{}.jpg", index));
+        fs::write(&image, b"fake image data").unwrap();
+        sidecar.save_data(&image, OperationType::FaceDetection, json!({"faces": [{"confidence": 0.9, "bbox": [1, 2, 3, 4]}]})).await.unwrap();
+    }
+    for index in 0..4 {
+        fs::write(dir.join(format!("raw{}.jpg", index)), b"fake image data").unwrap();
+        fs::write(dir.join(format!("raw{}.json", index)), json!({"Face_detector": {"faces": []}}).to_string()).unwrap();
+    }
+    fs::write(dir.join("broken.jpg"), b"fake image data").unwrap();
+    fs::write(dir.join("broken.json"), b"{not json").unwrap();
+
+    let report = sidecar.bench(dir, &BenchOptions::default()).await.unwrap();
+    assert_eq!(report.sidecars, 11);
+    let stored: Vec<(SidecarFormat, usize, usize)> = report.formats.iter().map(|f| (f.format, f.files, f.failed)).collect();
+    assert_eq!(stored, [(SidecarFormat::Json, 5, 1), (SidecarFormat::Binary, 6, 0)]);
+    assert_eq!(report.formats[0].parse.files, 4);
+    assert_eq!(report.formats[1].validate.files, 6);
+    assert!(report.formats[1].read.p50_ms <= report.formats[1].read.max_ms);
+    let compared: Vec<SidecarFormat> = report.conversions.iter().map(|c| c.format).collect();
+    assert_eq!(compared, [SidecarFormat::Json, SidecarFormat::Binary, SidecarFormat::Rkyv]);
+    assert!(report.conversions.iter().all(|c| c.parse.files + c.failed == 10 && c.size_ratio > 0.0));
+
+    let sampled = sidecar.bench(dir, &BenchOptions::default().limit(4).formats(vec![SidecarFormat::Json])).await.unwrap();
+    assert_eq!((sampled.sidecars, sampled.conversions.len()), (4, 1));
+}
+
+#[tokio::test]
+async fn test_generate_writes_a_reproducible_dataset() {
+    use image_sidecar_rust::GenerateOptions;
+    use serde_json::Value;
+
+    let temp_dir = TempDir::new().unwrap();
+    let sidecar = ImageSidecar::new(None);
+    let options = GenerateOptions::default()
+        .count(25)
+        .per_directory(10)
+        .format(SidecarFormat::Json)
+        .encoding_dims(8)
+        .operations(vec![OperationType::FaceDetection, OperationType::Tracking])
+        .and_then(|options| options.detections(1, 3))
+        .and_then(|options| options.image_size(32, 24))
+        .unwrap();
+    assert!(GenerateOptions::default().operations(vec![OperationType::ImageHash]).is_err());
+    assert!(GenerateOptions::default().detections(4, 2).is_err());
+
+    let first = temp_dir.path().join("first");
+    let summary = sidecar.generate_dataset(&first, &options).await.unwrap();
+    assert_eq!((summary.images, summary.sidecars, summary.directories), (25, 25, 3));
+    assert!((50..=150).contains(&summary.detections));
+    assert_eq!(image::image_dimensions(first.join("game_002/frame_000004.jpg")).unwrap(), (32, 24));
+    assert!(!first.join("game_002/frame_000005.jpg").exists());
+
+    let read = |root: &std::path::Path| -> Value {
+        serde_json::from_slice(&fs::read(root.join("game_001/frame_000003.json")).unwrap()).unwrap()
+    };
+    let data = read(&first);
+    let faces = data["face_detection"]["faces"].as_array().unwrap();
+    assert!((1..=3).contains(&faces.len()));
+    assert_eq!(faces[0]["encoding"].as_array().unwrap().len(), 8);
+    assert_eq!(data["sidecar_info"]["last_operation"], "tracking");
+    assert_eq!(data["sidecar_info"]["created_at"], "2024-12-19T10:00:03+00:00");
+    let stats = sidecar.get_statistics(&first).await.unwrap();
+    assert_eq!(stats.total_sidecars, 25);
+
+    let second = temp_dir.path().join("second");
+    sidecar.generate_dataset(&second, &options).await.unwrap();
+    assert_eq!(read(&second)["face_detection"], data["face_detection"]);
+    assert_eq!(fs::read(second.join("game_000/frame_000007.jpg")).unwrap(), fs::read(first.join("game_000/frame_000007.jpg")).unwrap());
+    let reseeded = temp_dir.path().join("reseeded");
+    sidecar.generate_dataset(&reseeded, &options.clone().seed(7)).await.unwrap();
+    assert_ne!(read(&reseeded)["face_detection"], data["face_detection"]);
+}
+
+#[tokio::test]
+async fn test_in_memory_storage_serves_reads_writes_and_scans() {
+    use image_sidecar_rust::testing::{MemoryStorage, SidecarTree};
+    use std::sync::Arc;
+
+    let root = std::path::Path::new("/nonexistent/season");
+    let tree = SidecarTree::new(root)
+        .format(SidecarFormat::Binary)
+        .sidecar("game1/a.jpg", OperationType::FaceDetection, json!({"faces": [{"confidence": 0.9}]}))
+        .sidecar("game1/a.jpg", OperationType::Yolov8, json!({"detections": [{"class_name": "person", "confidence": 0.8}]}))
+        .sidecar("game2/b.jpg", OperationType::QualityAssessment, json!({"overall_score": 0.4}))
+        .image("game2/c.jpg")
+        .file("game2/raw.json", json!({"Face_detector": {"faces": []}}).to_string());
+    let storage: MemoryStorage = tree.in_memory().unwrap();
+    assert_eq!(storage.len(), 6);
+    let sidecar = ImageSidecar::builder().storage(Arc::new(storage.clone())).build();
+
+    let data = sidecar.read_data(&root.join("game1/a.jpg")).await.unwrap();
+    assert_eq!(data["face_detection"]["faces"][0]["confidence"], 0.9);
+    assert_eq!(data["sidecar_info"]["last_operation"], "yolov8");
+
+    sidecar.save_data(&root.join("game2/c.jpg"), OperationType::FaceDetection, json!({"faces": []})).await.unwrap();
+    assert!(storage.get(root.join("game2/c.bin")).is_some());
+    assert!(!root.exists());
+
+    let stats = sidecar.get_statistics(root).await.unwrap();
+    assert_eq!((stats.total_images, stats.total_sidecars), (3, 3));
+    let matches = sidecar.query(root, &QueryExpr::parse("quality_assessment.overall_score < 0.5").unwrap()).await.unwrap();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].sidecar.sidecar_path, tree.sidecar_path("game2/b.jpg"));
+
+    sidecar.remove_operation(&root.join("game2/b.jpg"), OperationType::QualityAssessment).await.unwrap();
+    assert!(storage.get(tree.sidecar_path("game2/b.jpg")).is_none());
+
+    // Conversion, transactions and their recovery stay in the backend too
+    sidecar.convert_directory_format(root, SidecarFormat::Json).await.unwrap();
+    assert!(storage.get(root.join("game1/a.json")).is_some() && storage.get(root.join("game1/a.bin")).is_none());
+    let mut transaction = sidecar.manager().transaction(&root.join("game1"));
+    transaction.write(root.join("game1/d.json"), &json!({"yolov8": {"detections": []}})).unwrap();
+    transaction.rename(root.join("game1/a.json"), root.join("game1/e.json"));
+    assert_eq!(transaction.commit().await.unwrap(), 2);
+    assert!(storage.get(root.join("game1/d.json")).is_some() && storage.get(root.join("game1/e.json")).is_some());
+    assert!(storage.paths().iter().all(|path| !path.to_string_lossy().contains(".sidecar-txn-")));
+    image_sidecar_rust::StorageBackend::write(&storage, &root.join("game1/.sidecar-txn-1.journal"), b"").unwrap();
+    let recovered = sidecar.recover_transactions(&root.join("game1")).await.unwrap();
+    assert_eq!(recovered.rolled_back, 1);
+    assert!(storage.get(root.join("game1/.sidecar-txn-1.journal")).is_none());
+    assert!(!root.exists());
+
+    // The same tree on disk reads the same
+    let temp_dir = TempDir::new().unwrap();
+    let on_disk = SidecarTree::new(temp_dir.path())
+        .sidecar("a.jpg", OperationType::FaceDetection, json!({"faces": []}))
+        .image("b.jpg");
+    on_disk.write_to_disk().unwrap();
+    let stats = ImageSidecar::new(None).get_statistics(temp_dir.path()).await.unwrap();
+    assert_eq!((stats.total_images, stats.total_sidecars), (2, 1));
+}
+
+#[tokio::test]
+async fn test_oversized_sidecars_are_refused_unread() {
+    let temp_dir = TempDir::new().unwrap();
+    for name in ["small.jpg", "huge.jpg"] {
+        fs::write(temp_dir.path().join(name), b"fake image data").unwrap();
+    }
+    fs::write(temp_dir.path().join("small.json"), json!({"face_detection": {"faces": []}}).to_string()).unwrap();
+    let detections: Vec<_> = (0..100).map(|i| json!({"class_name": "person", "confidence": 0.5, "bbox": [i, i, 10, 10]})).collect();
+    fs::write(temp_dir.path().join("huge.json"), json!({"yolov8": {"detections": detections}}).to_string()).unwrap();
+    let huge_size = fs::metadata(temp_dir.path().join("huge.json")).unwrap().len();
+
+    let sidecar = ImageSidecar::builder().max_sidecar_size(1024).build();
+    let error = sidecar.read_data(&temp_dir.path().join("huge.jpg")).await.unwrap_err();
+    assert!(matches!(error, SidecarError::TooLarge { size, limit: 1024, .. } if size == huge_size), "{error}");
+    assert!(sidecar.read_data(&temp_dir.path().join("small.jpg")).await.unwrap()["face_detection"].is_object());
+
+    let results = sidecar.validate_sidecars(temp_dir.path()).await.unwrap();
+    let huge = results.iter().find(|result| result.file_path.ends_with("huge.json")).unwrap();
+    assert!(huge.oversized && !huge.is_valid);
+    assert_eq!(huge.file_size, huge_size);
+    assert!(huge.error.as_deref().unwrap().contains("1024 byte limit"));
+    let small = results.iter().find(|result| result.file_path.ends_with("small.json")).unwrap();
+    assert!(small.is_valid && !small.oversized);
+    let mut summary = ValidationSummary::default();
+    results.iter().for_each(|result| summary.record(result));
+    assert_eq!((summary.valid_files, summary.invalid_files, summary.oversized_files), (1, 1, 1));
+
+    // Scans carry on past the oversized sidecar, counting it invalid
+    let stats = sidecar.get_statistics(temp_dir.path()).await.unwrap();
+    assert_eq!(stats.total_sidecars, 2);
+    assert_eq!(stats.sidecars.iter().filter(|info| info.is_valid).count(), 1);
+
+    // The default limit reads it
+    assert!(ImageSidecar::new(None).read_data(&temp_dir.path().join("huge.jpg")).await.is_ok());
+
+    // --max-sidecar-mb counts MiB, so a sidecar between 10^6 and 2^20 bytes fits under 1
+    let padded = json!({"face_detection": {"faces": [], "note": "x".repeat(1_040_000)}});
+    fs::write(temp_dir.path().join("huge.json"), padded.to_string()).unwrap();
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+        .args(["--json", "--max-sidecar-mb", "1", "validate", "--input", temp_dir.path().to_str().unwrap()])
+        .output()
+        .unwrap();
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(document["result"]["oversized_files"], 0);
+}
+
+#[tokio::test]
+async fn test_skimmed_sidecars_agree_with_full_decode() {
+    use image_sidecar_rust::sidecar::{skim, SkimKeys};
+    use image_sidecar_rust::ClassifierRule;
+
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    let embedding: Vec<f64> = (0..128).map(|i| i as f64 / 128.0).collect();
+    let faces: Vec<_> = [0.95, 0.6, 0.99]
+        .iter()
+        .map(|confidence| json!({"bbox": [10, 20, 30, 40], "confidence": confidence, "embedding": embedding}))
+        .collect();
+    let recorded = json!({
+        "sidecar_info": {"operation_type": "face_detection", "created_at": "2024-06-01T12:00:00Z"},
+        "face_detection": {"faces": faces, "processing_time": 1.5, "success": true},
+    });
+    let raw = json!({"pose": {"keypoints": [0.5, 0.25, 0.125], "tool_name": "posenet"}});
+    for name in ["recorded.jpg", "raw.jpg"] {
+        fs::write(dir.join(name), b"fake image data").unwrap();
+    }
+    let bytes = FormatManager::new().get_serializer(SidecarFormat::Binary).serialize(&recorded).unwrap();
+    fs::write(dir.join("recorded.bin"), &bytes).unwrap();
+    fs::write(dir.join("raw.json"), raw.to_string()).unwrap();
+
+    // The outline keeps every detection and its scalars, but none of the embedding numbers
+    let outline = skim(&bytes, SidecarFormat::Binary, &SkimKeys::new()).unwrap();
+    assert_eq!(outline["sidecar_info"], recorded["sidecar_info"]);
+    assert_eq!(outline["face_detection"]["faces"].as_array().unwrap().len(), 3);
+    assert_eq!(outline["face_detection"]["faces"][2]["confidence"], json!(0.99));
+    assert_eq!(outline["face_detection"]["faces"][0]["embedding"], json!([]));
+    assert!(skim(&bytes, SidecarFormat::Binary, &SkimKeys::new().key("embedding").key("bbox")).unwrap() == recorded);
+    assert!(skim(&bytes[..bytes.len() - 1], SidecarFormat::Binary, &SkimKeys::new()).is_err());
+
+    // A pointer rule into a numeric array still classifies the raw output
+    let sidecar = ImageSidecar::builder()
+        .operation_rule(ClassifierRule::pointer("/pose/keypoints/2", OperationType::QualityAssessment).unwrap())
+        .build();
+    let stats = sidecar.get_statistics(dir).await.unwrap();
+    let info_of = |stem: &str| stats.sidecars.iter().find(|info| info.image_path == dir.join(format!("{}.jpg", stem))).unwrap();
+    let info = info_of("recorded");
+    assert_eq!(info.operation, OperationType::FaceDetection);
+    assert_eq!((info.processing_time, info.success), (Some(1.5), Some(true)));
+    assert_eq!(info.data_size, bytes.len() as u64);
+    assert_eq!(info_of("raw").operation, OperationType::QualityAssessment);
+    let found = sidecar.find_sidecar_for_image(&dir.join("raw.jpg")).await.unwrap().unwrap();
+    assert_eq!(found.operation, OperationType::QualityAssessment);
+
+    // Queries match on the outline and return the full document
+    let matches = sidecar.query(dir, &QueryExpr::parse("detections == 3 && confidence.max > 0.98").unwrap()).await.unwrap();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].data, recorded);
+    let matches = sidecar.query(dir, &QueryExpr::parse("face_detection.faces.0.embedding.count == 128").unwrap()).await.unwrap();
+    assert_eq!(matches.len(), 1);
+    let matches = sidecar.query(dir, &QueryExpr::parse("pose.keypoints.max == 0.5").unwrap()).await.unwrap();
+    assert_eq!(matches.iter().map(|found| &found.data).collect::<Vec<_>>(), [&raw]);
+}
+
+#[tokio::test]
+async fn test_found_sidecars_load_payloads_on_demand() {
+    use image_sidecar_rust::testing::{MemoryStorage, SidecarTree};
+
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    SidecarTree::new(dir)
+        .sidecar("a.jpg", OperationType::FaceDetection, json!({"faces": [{"confidence": 0.9}]}))
+        .image("b.jpg")
+        .write_to_disk()
+        .unwrap();
+    fs::write(dir.join("b.json"), b"{not json").unwrap();
+
+    // Listing classifies and validates each sidecar but keeps none of its contents
+    let found = ImageSidecar::new(None).find_sidecars(dir).await.unwrap();
+    assert_eq!(found.len(), 2);
+    let info_of = |name: &str| found.iter().find(|info| info.image_path == dir.join(name)).unwrap();
+    let a = info_of("a.jpg");
+    assert_eq!(a.data_size, fs::metadata(&a.sidecar_path).unwrap().len());
+    assert_eq!(a.operation, OperationType::FaceDetection);
+    assert!(a.is_valid && !a.is_payload_loaded());
+    assert!(!info_of("b.jpg").is_valid);
+
+    // A metadata-only listing stats each sidecar and checks validity on demand
+    let listed = ImageSidecar::new(None).list_sidecars(dir).await.unwrap();
+    let listed_of = |name: &str| listed.iter().find(|info| info.image_path == dir.join(name)).unwrap();
+    assert_eq!(listed_of("a.jpg").data_size, a.data_size);
+    assert!(listed.iter().all(|info| !info.is_valid && info.operation == OperationType::Unknown && !info.is_payload_loaded()));
+    assert!(listed_of("a.jpg").check_valid() && listed_of("a.jpg").is_payload_loaded());
+    assert!(!listed_of("b.jpg").check_valid());
+
+    // The payload is decoded on first use and shared with clones
+    let copy = a.clone();
+    assert_eq!(a.payload().unwrap()["face_detection"]["faces"][0]["confidence"], 0.9);
+    assert!(copy.is_payload_loaded());
+    fs::remove_file(&a.sidecar_path).unwrap();
+    assert_eq!(copy.payload().unwrap()["sidecar_info"]["last_operation"], "face_detection");
+    let b = info_of("b.jpg");
+    assert!(b.payload().is_err() && !b.is_payload_loaded());
+
+    // Sidecars found in a storage backend load from it, under the manager's size limit
+    let root = Path::new("/nonexistent/season");
+    let tree = SidecarTree::new(root).sidecar("c.jpg", OperationType::FaceDetection, json!({"faces": []}));
+    let storage: MemoryStorage = tree.in_memory().unwrap();
+    let found = ImageSidecar::builder().storage(Arc::new(storage.clone())).build().find_sidecars(root).await.unwrap();
+    assert_eq!(found[0].data_size, storage.get(tree.sidecar_path("c.jpg")).unwrap().len() as u64);
+    assert_eq!(found[0].payload().unwrap()["face_detection"], json!({"faces": []}));
+    let listed = ImageSidecar::builder().storage(Arc::new(storage.clone())).build().list_sidecars(root).await.unwrap();
+    assert_eq!(listed[0].data_size, found[0].data_size);
+    let limited = ImageSidecar::builder().storage(Arc::new(storage)).max_sidecar_size(8).build();
+    let found = limited.find_sidecars(root).await.unwrap();
+    assert!(matches!(found[0].payload(), Err(SidecarError::TooLarge { limit: 8, .. })));
+}
+
+/// Sidecar-shaped documents: objects of scalars, arrays and nested objects
+///
+/// Floats have at most three decimals, like detector output, so they parse back exactly
+/// without serde_json's `float_roundtrip`.
+fn sidecar_document() -> impl proptest::strategy::Strategy<Value = serde_json::Value> {
+    use proptest::prelude::*;
+    let leaf = prop_oneof![
+        Just(serde_json::Value::Null),
+        any::<bool>().prop_map(serde_json::Value::from),
+        any::<i64>().prop_map(serde_json::Value::from),
+        (-1_000_000_000i64..1_000_000_000).prop_map(|n| serde_json::Value::from(n as f64 / 1000.0)),
+        ".*".prop_map(serde_json::Value::from),
+    ];
+    let value = leaf.prop_recursive(4, 64, 8, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..8).prop_map(serde_json::Value::from),
+            prop::collection::btree_map(".*", inner, 0..8).prop_map(|map| serde_json::Value::Object(map.into_iter().collect())),
+        ]
+    });
+    prop::collection::btree_map("[a-z_]{1,16}", value, 0..8).prop_map(|map| serde_json::Value::Object(map.into_iter().collect()))
+}
+
+proptest::proptest! {
+    #![proptest_config(proptest::prelude::ProptestConfig::with_cases(128))]
+
+    #[test]
+    fn test_every_format_round_trips_documents(document in sidecar_document()) {
+        for format_manager in [FormatManager::new(), FormatManager::canonical()] {
+            for format in [SidecarFormat::Json, SidecarFormat::Binary, SidecarFormat::Rkyv] {
+                let serializer = format_manager.get_serializer(format);
+                let bytes = serializer.serialize(&document).unwrap();
+                proptest::prop_assert_eq!(&serializer.deserialize(&bytes).unwrap(), &document);
+                proptest::prop_assert_eq!(format_manager.detect_format_from_content(&bytes).unwrap().is_binary(), format.is_binary());
+            }
+        }
+    }
+
+    #[test]
+    fn test_truncated_and_padded_sidecars_are_errors(document in sidecar_document(), cut in proptest::prelude::any::<proptest::sample::Index>(), padding in proptest::collection::vec(proptest::prelude::any::<u8>(), 1..16)) {
+        let format_manager = FormatManager::new();
+        for format in [SidecarFormat::Json, SidecarFormat::Binary, SidecarFormat::Rkyv] {
+            let serializer = format_manager.get_serializer(format);
+            let bytes = serializer.serialize(&document).unwrap();
+            proptest::prop_assert!(serializer.deserialize(&bytes[..cut.index(bytes.len())]).is_err());
+            if format.is_binary() {
+                proptest::prop_assert!(serializer.deserialize(&[bytes.as_slice(), &padding].concat()).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn test_garbage_never_panics_a_deserializer(bytes in proptest::collection::vec(proptest::prelude::any::<u8>(), 0..512)) {
+        let format_manager = FormatManager::new();
+        let _ = format_manager.detect_format_from_content(&bytes);
+        for format in [SidecarFormat::Json, SidecarFormat::Binary, SidecarFormat::Rkyv] {
+            let _ = format_manager.get_serializer(format).deserialize(&bytes);
+        }
+        // A binary length prefix far beyond the input is refused before anything is allocated
+        let mut huge = u64::MAX.to_le_bytes().to_vec();
+        huge.extend_from_slice(&bytes);
+        proptest::prop_assert!(format_manager.get_serializer(SidecarFormat::Binary).deserialize(&huge).is_err());
+    }
+}
+
+#[tokio::test]
+async fn test_gate_selects_images_by_quality_and_detections() {
+    use image_sidecar_rust::{DetectionFilter, GateAction, QualityGate};
+
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path().join("frames");
+    fs::create_dir_all(dir.join("game1")).unwrap();
+    let sidecar = ImageSidecar::new(None);
+    for (name, score, faces) in [("sharp", 0.9, 2), ("game1/sharp", 0.75, 1), ("blurry", 0.3, 3), ("empty", 0.95, 0)] {
+        let image = dir.join(format!("{}.jpg", name));
+        fs::write(&image, name).unwrap();
+        sidecar.save_data(&image, OperationType::QualityAssessment, json!({"overall_score": score})).await.unwrap();
+        sidecar.save_data(&image, OperationType::FaceDetection, json!({"faces": vec![json!({"confidence": 0.9}); faces]})).await.unwrap();
+    }
+    fs::write(dir.join("unscored.jpg"), b"fake image data").unwrap();
+    sidecar.save_data(&dir.join("unscored.jpg"), OperationType::FaceDetection, json!({"faces": [{}]})).await.unwrap();
+    // Raw assessor output keeps its score one level down
+    fs::write(dir.join("raw.jpg"), b"fake image data").unwrap();
+    fs::write(dir.join("raw.json"), json!({"Quality_assessor": {"score": 0.8}}).to_string()).unwrap();
+
+    let quality = sidecar.gate(&dir, &QualityGate::default().min_score(0.7)).await.unwrap();
+    let passed: Vec<PathBuf> = quality.passed.iter().map(|image| image.image_path.clone()).collect();
+    assert_eq!(passed, [dir.join("empty.jpg"), dir.join("game1/sharp.jpg"), dir.join("raw.jpg"), dir.join("sharp.jpg")]);
+    assert_eq!((quality.failed, quality.unscored), (1, 1));
+
+    let faces = DetectionFilter::default().operation("face_detection").min_detections(1);
+    let gated = sidecar.gate(&dir, &QualityGate::default().min_score(0.7).detections(faces)).await.unwrap();
+    assert_eq!(gated.passed.len(), 2);
+    assert_eq!(gated.passed[0].score, Some(0.75));
+
+    let good = temp_dir.path().join("good");
+    let summary = gated.apply(&good, GateAction::Copy).unwrap();
+    assert_eq!(summary.placed, [good.join("game1/sharp.jpg"), good.join("sharp.jpg")]);
+    assert_eq!(fs::read(good.join("game1/sharp.jpg")).unwrap(), b"game1/sharp");
+    // Rerunning leaves what is already there
+    assert_eq!(gated.apply(&good, GateAction::Copy).unwrap().skipped.len(), 2);
+
+    // Images outside the gated directory keep only their name, which must not clash
+    let elsewhere = temp_dir.path().join("elsewhere");
+    fs::create_dir_all(elsewhere.join("a")).unwrap();
+    fs::create_dir_all(elsewhere.join("b")).unwrap();
+    fs::write(elsewhere.join("a/shot.jpg"), b"a").unwrap();
+    fs::write(elsewhere.join("b/shot.jpg"), b"b").unwrap();
+    let outside = |names: &[&str]| image_sidecar_rust::GateReport {
+        directory: dir.clone(),
+        passed: names.iter().map(|name| image_sidecar_rust::GatedImage { image_path: elsewhere.join(name), score: None }).collect(),
+        ..Default::default()
+    };
+    let clash = temp_dir.path().join("clash");
+    assert!(outside(&["a/shot.jpg", "b/shot.jpg"]).apply(&clash, GateAction::Copy).is_err());
+    assert!(!clash.join("shot.jpg").exists());
+    assert_eq!(outside(&["a/shot.jpg"]).apply(&clash, GateAction::Copy).unwrap().placed, [clash.join("shot.jpg")]);
+    assert_eq!(outside(&["a/shot.jpg"]).apply(&clash, GateAction::Copy).unwrap().skipped, [clash.join("shot.jpg")]);
+    assert!(outside(&["b/shot.jpg"]).apply(&clash, GateAction::Copy).is_err());
+    assert_eq!(fs::read(clash.join("shot.jpg")).unwrap(), b"a");
+}
+
+#[tokio::test]
+async fn test_review_queue_picks_least_certain_detections() {
+    use image_sidecar_rust::{ReviewOptions, ReviewSelection};
+
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    let sidecar = ImageSidecar::new(None);
+    let goal = dir.join("goal.png");
+    image::RgbImage::new(200, 100).save(&goal).unwrap();
+    sidecar.save_data(&goal, OperationType::Yolov8, json!({"detections": [
+        {"class_name": "ball", "confidence": 0.42, "bbox": {"x1": 20, "y1": 10, "x2": 60, "y2": 60}},
+        {"class_name": "person", "confidence": 0.97, "bbox": [0, 0, 10, 10]},
+    ]})).await.unwrap();
+    let save = dir.join("save.png");
+    image::RgbImage::new(100, 100).save(&save).unwrap();
+    sidecar.save_data(&save, OperationType::FaceDetection, json!({"faces": [{"score": 0.55}, {"bbox": [1, 1, 5, 5]}]})).await.unwrap();
+
+    // The face without a score is never reviewed
+    let bottom = sidecar.review_queue(dir, &ReviewOptions::new(ReviewSelection::bottom_percent(50.0).unwrap())).await.unwrap();
+    assert_eq!(bottom.scored, 3);
+    let picked: Vec<(&str, f64)> = bottom.items.iter().map(|item| (item.label.as_str(), item.confidence)).collect();
+    assert_eq!(picked, [("ball", 0.42), ("face_detection", 0.55)]);
+
+    let near = ReviewOptions::new(ReviewSelection::near_threshold(0.5, 0.1).unwrap()).operations(["face_detection"]);
+    let near = sidecar.review_queue(dir, &near).await.unwrap();
+    assert_eq!(near.items.len(), 1);
+    assert_eq!(near.items[0].image_path, save);
+    assert!(ReviewSelection::bottom_percent(0.0).is_err());
+
+    let tasks = bottom.label_studio_tasks(dir, Some("/data/local-files/?d="));
+    assert_eq!(tasks.len(), 2);
+    assert_eq!(tasks[0]["data"]["image"], "/data/local-files/?d=goal.png");
+    let region = &tasks[0]["predictions"][0]["result"][0];
+    assert_eq!(region["type"], "rectanglelabels");
+    assert_eq!(region["value"]["rectanglelabels"], json!(["ball"]));
+    assert_eq!((region["value"]["x"].as_f64(), region["value"]["width"].as_f64()), (Some(10.0), Some(20.0)));
+    assert_eq!(region["value"]["height"].as_f64(), Some(50.0));
+    // A detection without a box is listed for review but has no region
+    assert_eq!(tasks[1]["predictions"][0]["result"], json!([]));
+    assert_eq!(tasks[1]["data"]["review"][0]["confidence"], 0.55);
+
+    // A raw detector file wrapping its detections in `result` lists each of them once
+    let kick = dir.join("kick.png");
+    image::RgbImage::new(10, 10).save(&kick).unwrap();
+    fs::write(dir.join("kick.json"), json!({"result": {"detections": [{"class_name": "ball", "confidence": 0.3}]}}).to_string()).unwrap();
+    let raw = ReviewOptions::new(ReviewSelection::near_threshold(0.3, 0.01).unwrap());
+    let raw = sidecar.review_queue(dir, &raw).await.unwrap();
+    assert_eq!(raw.items.len(), 1);
+    assert_eq!((raw.items[0].image_path.as_path(), raw.items[0].operation.as_str()), (kick.as_path(), "unknown"));
+}
+
+#[tokio::test]
+async fn test_unify_merges_per_operation_sidecars() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    for name in ["goal.jpg", "save.jpg"] {
+        fs::write(dir.join(name), name).unwrap();
+    }
+    let sidecar = ImageSidecar::new(None);
+    sidecar.save_data(&dir.join("goal.jpg"), OperationType::Yolov8, json!({"count": 3})).await.unwrap();
+    fs::write(dir.join("faces_goal.json"), json!({
+        "face_detection": {"faces": 2},
+        "sidecar_info": {"provenance": {"face_detection": {"tool": "faces"}}},
+    }).to_string()).unwrap();
+    // An older yolov8 run loses to the one in goal.bin
+    fs::write(dir.join("old_goal.json"), json!({
+        "yolov8": {"count": 1},
+        "sidecar_info": {"operation_updated": {"yolov8": "2001-01-01T00:00:00Z"}},
+    }).to_string()).unwrap();
+    fs::write(dir.join("quality_goal.json"), json!({
+        "data": {"score": 0.9},
+        "sidecar_info": {"operation_type": "quality_assessment"},
+    }).to_string()).unwrap();
+
+    let info = sidecar.unify(&dir.join("goal.jpg"), &[]).await.unwrap();
+    assert_eq!(info.operation, OperationType::Unified);
+    assert_eq!(info.sidecar_path, dir.join("goal.bin"));
+    let unified = sidecar.read_data(&dir.join("goal.jpg")).await.unwrap();
+    assert_eq!(unified["yolov8"]["count"], 3);
+    assert_eq!(unified["face_detection"]["faces"], 2);
+    assert_eq!(unified["quality_assessment"]["score"], 0.9);
+    let sources = &unified["sidecar_info"]["unified_sources"];
+    assert_eq!(paths::path_from_json(&sources["face_detection"]).unwrap(), dir.join("faces_goal.json"));
+    assert_eq!(paths::path_from_json(&sources["yolov8"]).unwrap(), dir.join("goal.bin"));
+    assert_eq!(unified["sidecar_info"]["provenance"]["face_detection"]["tool"], "faces");
+    assert!(dir.join("faces_goal.json").exists());
+
+    let unify = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+            .args(["--json", "unify", "--input", dir.to_str().unwrap()])
+            .args(args)
+            .output()
+            .unwrap();
+        (output.status.code(), serde_json::from_slice::<serde_json::Value>(&output.stdout).ok())
+    };
+    fs::write(dir.join("faces_save.json"), r#"{"face_detection": {"faces": 1}}"#).unwrap();
+    assert_eq!(unify(&["--operations", "face_detection", "--remove-sources", "--dry-run"]).0, Some(0));
+    assert!(!dir.join("save.bin").exists() && dir.join("faces_save.json").exists());
+
+    let (code, report) = unify(&["--operations", "face_detection", "--remove-sources"]);
+    assert_eq!(code, Some(0));
+    let report = report.unwrap();
+    assert_eq!(report["result"]["unified"], 2);
+    // Only sources holding nothing but the merged operations are removed
+    assert!(!dir.join("faces_goal.json").exists() && !dir.join("faces_save.json").exists());
+    assert!(dir.join("old_goal.json").exists() && dir.join("quality_goal.json").exists());
+    assert_eq!(sidecar.read_data(&dir.join("save.jpg")).await.unwrap()["face_detection"]["faces"], 1);
+
+    assert_eq!(unify(&["--operations", "pose"]).0, Some(2));
+}
+
+#[tokio::test]
+async fn test_split_writes_per_operation_sidecars_that_discovery_matches() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    // `001.jpg` must not claim the per-operation sidecars of `IMG_001.jpg`
+    for name in ["IMG_001.jpg", "001.jpg"] {
+        fs::write(dir.join(name), name).unwrap();
+    }
+    let image = dir.join("IMG_001.jpg");
+    let sidecar = ImageSidecar::new(None);
+    sidecar.save_data(&image, OperationType::Yolov8, json!({"count": 3})).await.unwrap();
+    sidecar.save_data(&image, OperationType::FaceDetection, json!({"faces": 2})).await.unwrap();
+
+    let record = sidecar.split(&image, &[OperationType::Yolov8], SidecarFormat::Json).await.unwrap();
+    assert_eq!(record.files.len(), 1);
+    assert_eq!(record.files[0].path, dir.join("yolov8_IMG_001.json"));
+    let split: serde_json::Value = serde_json::from_str(&fs::read_to_string(dir.join("yolov8_IMG_001.json")).unwrap()).unwrap();
+    assert_eq!(split["yolov8"]["count"], 3);
+    assert!(split.get("face_detection").is_none());
+    assert_eq!(split["sidecar_info"]["last_operation"], "yolov8");
+    assert_eq!(paths::path_from_json(&split["sidecar_info"]["split_from"]).unwrap(), dir.join("IMG_001.bin"));
+    assert!(split["sidecar_info"]["provenance"].get("face_detection").is_none());
+    assert!(split["sidecar_info"]["provenance"].get("yolov8").is_some());
+
+    // Both layouts are discovered for the same image
+    assert!(sidecar.find_orphaned(dir).await.unwrap().is_empty());
+    let found = sidecar.find_sidecars(dir).await.unwrap();
+    let split_info = found.iter().find(|info| info.sidecar_path == dir.join("yolov8_IMG_001.json")).unwrap();
+    assert_eq!(split_info.image_path, image);
+
+    let run = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+            .arg("--json")
+            .args(args)
+            .args(["--input", dir.to_str().unwrap()])
+            .output()
+            .unwrap()
+    };
+    let output = run(&["split", "--remove-unified"]);
+    assert_eq!(output.status.code(), Some(0));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["result"]["split"], 1);
+    assert!(!dir.join("IMG_001.bin").exists());
+    assert!(dir.join("face_detection_IMG_001.json").exists());
+    assert!(sidecar.find_orphaned(dir).await.unwrap().is_empty());
+
+    // Unifying again restores the original payloads
+    assert_eq!(run(&["unify", "--remove-sources"]).status.code(), Some(0));
+    let unified = sidecar.read_data(&image).await.unwrap();
+    assert_eq!(unified["yolov8"]["count"], 3);
+    assert_eq!(unified["face_detection"]["faces"], 2);
+    assert!(!dir.join("yolov8_IMG_001.json").exists() && !dir.join("face_detection_IMG_001.json").exists());
+}
+
+#[test]
+fn test_cli_convert_resume_and_restart() {
+    use image_sidecar_rust::sidecar::CHECKPOINT_FILE;
+
+    let temp_dir = TempDir::new().unwrap();
+    let input = temp_dir.path().to_str().unwrap();
+    let checkpoint_path = temp_dir.path().join(CHECKPOINT_FILE);
+    let run = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+            .args(["--json", "convert", "--input", input, "--format", "bin"])
+            .args(args)
+            .output()
+            .unwrap();
+        let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        (output.status.code(), document)
+    };
+
+    let (code, document) = run(&["--resume"]);
+    assert_eq!(code, Some(2));
+    assert!(document["error"].as_str().unwrap().contains("Nothing to resume"));
+
+    // An interrupted run had already handled a.json; the default picks up from there
+    fs::write(temp_dir.path().join("a.json"), r#"{"count": 1}"#).unwrap();
+    fs::write(temp_dir.path().join("b.json"), r#"{"count": 2}"#).unwrap();
+    fs::write(&checkpoint_path, format!("{}\n", temp_dir.path().join("a.json").display())).unwrap();
+    let (code, document) = run(&[]);
+    assert_eq!(code, Some(0));
+    assert_eq!(document["result"]["converted"], 1);
+    assert_eq!(document["result"]["resumed"], 1);
+    assert!(temp_dir.path().join("a.json").exists() && temp_dir.path().join("b.bin").exists());
+    assert!(!checkpoint_path.exists());
+
+    // --restart ignores the recorded progress
+    fs::write(&checkpoint_path, format!("{}\n", temp_dir.path().join("a.json").display())).unwrap();
+    let (code, document) = run(&["--restart"]);
+    assert_eq!(code, Some(0));
+    assert_eq!(document["result"]["converted"], 1);
+    assert_eq!(document["result"]["skipped"], 1);
+    assert_eq!(document["result"]["resumed"], 0);
+    assert!(temp_dir.path().join("a.bin").exists());
+
+    // A run resumed with a relative --input from inside the directory matches the absolute entries
+    fs::write(temp_dir.path().join("c.json"), r#"{"count": 3}"#).unwrap();
+    fs::write(temp_dir.path().join("d.json"), r#"{"count": 4}"#).unwrap();
+    fs::write(&checkpoint_path, format!("{}\n", temp_dir.path().join("c.json").display())).unwrap();
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+        .args(["--json", "convert", "--input", ".", "--format", "bin", "--resume"])
+        .current_dir(temp_dir.path())
+        .output()
+        .unwrap();
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!((&document["result"]["resumed"], &document["result"]["converted"]), (&json!(1), &json!(1)));
+    assert!(temp_dir.path().join("c.json").exists() && temp_dir.path().join("d.bin").exists());
+}
+
+#[tokio::test]
+async fn test_validate_to_ndjson_sink_streams_one_line_per_file() {
+    let temp_dir = TempDir::new().unwrap();
+    
+    for i in 0..5 {
+        let sidecar_path = temp_dir.path().join(format!("test_{}.json", i));
+        fs::write(&sidecar_path, serde_json::to_string(&json!({"count": i})).unwrap()).unwrap();
+    }
+    fs::write(temp_dir.path().join("broken.json"), b"{not json").unwrap();
+    
+    let sidecar = ImageSidecar::new(Some(2));
+    let mut sink = NdjsonSink::new(Vec::new());
+    let summary = sidecar.validate_sidecars_to_sink(temp_dir.path(), &mut sink, None).await.unwrap();
+    
+    assert_eq!(summary.total_files, 6);
+    assert_eq!(summary.valid_files, 5);
+    assert_eq!(summary.invalid_files, 1);
+    
+    let output = String::from_utf8(sink.into_inner()).unwrap();
+    let lines: Vec<ValidationResult> = output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 6);
+    assert_eq!(lines.iter().filter(|r| !r.is_valid).count(), 1);
+}
+
+#[tokio::test]
+async fn test_validation_records_format_and_deserialize_time() {
+    let temp_dir = TempDir::new().unwrap();
+    
+    fs::write(temp_dir.path().join("a.json"), serde_json::to_string(&json!({"count": 1})).unwrap()).unwrap();
+    let json_str = serde_json::to_string(&json!({"count": 2})).unwrap();
+    fs::write(temp_dir.path().join("b.bin"), bincode::serialize(&json_str).unwrap()).unwrap();
+    
+    let processor = ParallelProcessor::new(2);
+    let results = processor.validate_directory(temp_dir.path()).await.unwrap();
+    assert_eq!(results.len(), 2);
+    
+    for result in &results {
+        assert!(result.is_valid);
+        assert!(result.deserialize_time >= 0.0);
+        assert!(result.deserialize_time <= result.processing_time);
+    }
+    let mut formats: Vec<SidecarFormat> = results.iter().filter_map(|r| r.format).collect();
+    formats.sort_by_key(|format| format.extension());
+    assert_eq!(formats, vec![SidecarFormat::Binary, SidecarFormat::Json]);
+    
+    let stats = processor.get_validation_statistics(&results);
+    let format_stats = stats["format_stats"].as_object().unwrap();
+    assert_eq!(format_stats["json"]["count"], 1);
+    assert_eq!(format_stats["bin"]["count"], 1);
+}
+
+#[tokio::test]
+async fn test_validate_files_parallel_keeps_input_order_and_flags_bad_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = |name: String| temp_dir.path().join(name);
+    
+    // Enough files to span more than one io_uring batch, listed out of name order
+    let mut files = Vec::new();
+    for i in (0..1030).rev() {
+        let file = path(format!("frame{:04}.json", i));
+        if i % 7 == 0 {
+            fs::write(&file, "{ not json").unwrap();
+        } else {
+            fs::write(&file, json!({"count": i, "padding": "x".repeat(i % 5 * 500)}).to_string()).unwrap();
+        }
+        files.push(file);
+    }
+    let json_str = serde_json::to_string(&json!({"count": 2})).unwrap();
+    fs::write(path("a.bin".to_string()), bincode::serialize(&json_str).unwrap()).unwrap();
+    fs::write(path("huge.json".to_string()), json!({"padding": "x".repeat(8192)}).to_string()).unwrap();
+    files.splice(3..3, ["a.bin", "missing.json", "huge.json"].map(|name| path(name.to_string())));
+    
+    let processor = ParallelProcessor::new(4).with_max_sidecar_size(4096);
+    let results = processor.validate_files_parallel(&files).await.unwrap();
+    
+    let paths: Vec<&PathBuf> = results.iter().map(|result| &result.file_path).collect();
+    assert_eq!(paths, files.iter().collect::<Vec<_>>());
+    for result in &results {
+        let name = result.file_path.file_name().unwrap().to_str().unwrap();
+        let expected_valid = match name {
+            "a.bin" => true,
+            "missing.json" | "huge.json" => false,
+            _ => name[5..9].parse::<usize>().unwrap() % 7 != 0,
+        };
+        assert_eq!(result.is_valid, expected_valid, "{}", name);
+        assert_eq!(result.error.is_some(), !expected_valid, "{}", name);
+    }
+    assert_eq!(results[3].format, Some(SidecarFormat::Binary));
+    assert_eq!(results[4].error.as_deref(), Some("File does not exist"));
+    assert_eq!(results[5].file_size, fs::metadata(&files[5]).unwrap().len());
+    assert!(processor.validate_files_parallel(&[]).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_statistics_single_pass_scan_classifies_tree() {
+    let temp_dir = TempDir::new().unwrap();
+    let nested = temp_dir.path().join("nested").join("deeper");
+    fs::create_dir_all(&nested).unwrap();
+    
+    let sidecar_data = serde_json::to_string(&json!({
+        "sidecar_info": {"operation_type": "ball_detection"}
+    })).unwrap();
+    
+    // Images with same-stem sidecars, one of them nested
+    for dir in [temp_dir.path(), nested.as_path()] {
+        fs::write(dir.join("img.jpg"), b"fake image data").unwrap();
+        fs::write(dir.join("img.json"), &sidecar_data).unwrap();
+    }
+    // Pattern-named sidecar for a root image, and an image without any sidecar
+    fs::write(temp_dir.path().join("other.png"), b"fake image data").unwrap();
+    fs::write(temp_dir.path().join("run1_other.json"), &sidecar_data).unwrap();
+    fs::write(temp_dir.path().join("lonely.jpg"), b"fake image data").unwrap();
+    // One live and one broken image symlink
+    paths::symlink(temp_dir.path().join("img.jpg"), temp_dir.path().join("link.jpg")).unwrap();
+    paths::symlink(temp_dir.path().join("missing.jpg"), temp_dir.path().join("dead.jpg")).unwrap();
+    
+    let sidecar = ImageSidecar::new(None);
+    let stats = sidecar.get_statistics(temp_dir.path()).await.unwrap();
+    
+    assert_eq!(stats.total_images, 4);
+    assert_eq!(stats.total_sidecars, 3);
+    assert_eq!(stats.symlink_count, 2);
+    assert_eq!(stats.broken_symlinks, 1);
+    assert_eq!(stats.operation_counts.get("ball_detection"), Some(&3));
+    assert_eq!(stats.success_rate_percentages.get("ball_detection"), Some(&100.0));
+}
+
+#[tokio::test]
+async fn test_statistics_use_payload_processing_time_and_success() {
+    let temp_dir = TempDir::new().unwrap();
+    
+    let payloads = [
+        json!({"sidecar_info": {"operation_type": "face_detection"}, "face_detection": {"success": true, "processing_time": 1.0}}),
+        json!({"sidecar_info": {"operation_type": "face_detection"}, "face_detection": {"success": false, "processing_time": 3.0}}),
+        json!({"sidecar_info": {"operation_type": "face_detection"}, "face_detection": {"faces": []}}),
+    ];
+    for (i, payload) in payloads.iter().enumerate() {
+        fs::write(temp_dir.path().join(format!("img_{}.jpg", i)), b"fake image data").unwrap();
+        fs::write(temp_dir.path().join(format!("img_{}.json", i)), serde_json::to_string(payload).unwrap()).unwrap();
+    }
+    
+    let sidecar = ImageSidecar::new(None);
+    let stats = sidecar.get_statistics(temp_dir.path()).await.unwrap();
+    
+    // Only sidecars that report a processing time contribute to the average
+    assert_eq!(stats.avg_processing_times.get("face_detection"), Some(&2.0));
+    // A payload without a success flag counts as successful
+    let success_rate = stats.success_rate_percentages["face_detection"];
+    assert!((success_rate - 200.0 / 3.0).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_statistics_time_series_buckets_by_created_at() {
+    let temp_dir = TempDir::new().unwrap();
+    
+    let created = ["2024-12-19T10:05:00Z", "2024-12-19T10:55:00Z", "2024-12-19T13:30:00Z"];
+    for (i, created_at) in created.iter().enumerate() {
+        let payload = json!({"sidecar_info": {"operation_type": "face_detection", "created_at": created_at}});
+        fs::write(temp_dir.path().join(format!("img_{}.jpg", i)), b"fake image data").unwrap();
+        fs::write(temp_dir.path().join(format!("img_{}.json", i)), serde_json::to_string(&payload).unwrap()).unwrap();
+    }
+    fs::write(temp_dir.path().join("uncovered.jpg"), b"fake image data").unwrap();
+    
+    let sidecar = ImageSidecar::new(None);
+    let mut stats = sidecar.get_statistics(temp_dir.path()).await.unwrap();
+    
+    stats.compute_time_series(TimeBucket::Hour);
+    let hourly = stats.time_series.clone().unwrap();
+    assert_eq!(hourly.len(), 2);
+    assert_eq!(hourly[0].bucket_start.to_rfc3339(), "2024-12-19T10:00:00+00:00");
+    assert_eq!(hourly[0].sidecar_count, 2);
+    assert_eq!(hourly[1].cumulative_sidecars, 3);
+    assert_eq!(hourly[1].coverage_percentage, 75.0);
+    
+    stats.compute_time_series(TimeBucket::Day);
+    assert_eq!(stats.time_series.as_ref().unwrap().len(), 1);
+    
+    let mut csv = Vec::new();
+    stats.write_time_series_csv(&mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    assert_eq!(csv.lines().count(), 2);
+    assert!(csv.lines().nth(1).unwrap().starts_with("2024-12-19T00:00:00+00:00,3,3,"));
+}
+
+#[tokio::test]
+async fn test_statistics_count_detection_classes_per_directory() {
+    let temp_dir = TempDir::new().unwrap();
+    let season = temp_dir.path();
+    let sidecar = ImageSidecar::new(None);
+    let people = |count: usize| vec![json!({"class_name": "person", "confidence": 0.9}); count];
+    for (image, count, ball) in [("game1/a.jpg", 6, true), ("game2/b.jpg", 5, false)] {
+        let image = season.join(image);
+        fs::create_dir_all(image.parent().unwrap()).unwrap();
+        fs::write(&image, b"fake image data").unwrap();
+        let mut detections = people(count);
+        if ball {
+            detections.push(json!({"class": "ball"}));
+        }
+        sidecar.save_data(&image, OperationType::Yolov8, json!({"detections": detections})).await.unwrap();
+        // Faces are not classes
+        sidecar.save_data(&image, OperationType::FaceDetection, json!({"faces": [{"label": "face"}]})).await.unwrap();
+    }
+    // Raw detector output of a class operation is its own payload
+    fs::write(season.join("c.jpg"), b"fake image data").unwrap();
+    fs::write(season.join("c.json"), json!({"Object_detector": {}, "objects": [{"label": "goal"}, {"score": 0.2}]}).to_string()).unwrap();
+
+    let stats = sidecar.get_statistics(season).await.unwrap();
+    let distribution = &stats.class_distribution;
+    assert_eq!(distribution.counts, [("ball".to_string(), 1), ("goal".to_string(), 1), ("person".to_string(), 11)].into());
+    assert_eq!(distribution.directories["game2"], [("person".to_string(), 5)].into());
+    assert_eq!(distribution.directories["."], [("goal".to_string(), 1)].into());
+    let imbalance = distribution.imbalance.as_ref().unwrap();
+    assert_eq!((imbalance.majority.as_str(), imbalance.minority.as_str(), imbalance.ratio), ("person", "ball", 11.0));
+
+    let mut relaxed = distribution.clone();
+    relaxed.check_balance(20.0);
+    assert!(relaxed.imbalance.is_none());
+
+    // Badges cache the class counts, and rollups keep the directories apart
+    for game in ["game1", "game2"] {
+        sidecar.get_statistics_with_badge(&season.join(game), None).await.unwrap();
+    }
+    let rolled = sidecar.aggregate_statistics_badges(season).await.unwrap().aggregate.class_distribution;
+    assert_eq!(rolled.counts["person"], 11);
+    assert_eq!(rolled.directories.keys().collect::<Vec<_>>(), ["game1", "game2"]);
+}
+
+#[tokio::test]
+async fn test_multi_root_statistics_and_validation_roll_up() {
+    let season = TempDir::new().unwrap();
+    let games = [season.path().join("game_01"), season.path().join("game_02")];
+    let sidecar_data = json!({
+        "sidecar_info": { "operation_type": "face_detection" },
+        "face_detection": { "success": true, "faces": [] }
+    });
+    
+    // game_01: two images, both processed; game_02: two images, one processed, one broken sidecar
+    for (game, sidecars) in games.iter().zip([2, 1]) {
+        fs::create_dir(game).unwrap();
+        for i in 0..2 {
+            fs::write(game.join(format!("IMG_{}.jpg", i)), b"fake image data").unwrap();
+        }
+        for i in 0..sidecars {
+            fs::write(game.join(format!("IMG_{}.json", i)), sidecar_data.to_string()).unwrap();
+        }
+    }
+    fs::write(games[1].join("broken.json"), "{ not json").unwrap();
+    
+    let sidecar = ImageSidecar::new(None);
+    let multi = sidecar.get_statistics_multi(&games, None).await.unwrap();
+    
+    assert_eq!(multi.roots.len(), 2);
+    assert_eq!(multi.roots[0].directory, games[0]);
+    assert_eq!(multi.roots[0].coverage_percentage, 100.0);
+    assert_eq!(multi.roots[1].coverage_percentage, 50.0);
+    assert_eq!(multi.aggregate.directory, season.path());
+    assert_eq!(multi.aggregate.total_images, 4);
+    assert_eq!(multi.aggregate.total_sidecars, 3);
+    assert_eq!(multi.aggregate.coverage_percentage, 75.0);
+    assert_eq!(multi.aggregate.operation_counts["face_detection"], 3);
+    
+    let per_root = sidecar.validate_sidecars_multi(&games, None).await.unwrap();
+    assert_eq!(per_root.len(), 2);
+    assert_eq!(per_root[0].0, games[0]);
+    assert_eq!(per_root[0].1.len(), 2);
+    assert!(per_root[0].1.iter().all(|result| result.is_valid && result.file_path.starts_with(&games[0])));
+    assert_eq!(per_root[1].1.len(), 2);
+    assert_eq!(per_root[1].1.iter().filter(|result| !result.is_valid).count(), 1);
+
+    // A repeated root and one inside another are scanned once, under the outer root
+    let overlapping = [games[0].clone(), season.path().to_path_buf(), games[1].join(".")];
+    let multi = sidecar.get_statistics_multi(&overlapping, None).await.unwrap();
+    assert_eq!(multi.roots.len(), 1);
+    assert_eq!((multi.aggregate.total_images, multi.aggregate.total_sidecars), (4, 3));
+    let per_root = sidecar.validate_sidecars_multi(&overlapping, None).await.unwrap();
+    assert_eq!(per_root.iter().map(|(root, results)| (root.clone(), results.len())).collect::<Vec<_>>(), [(season.path().to_path_buf(), 4)]);
+    let streamed = season.path().join("results.ndjson");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+        .args(["--json", "validate", "--input", games[0].to_str().unwrap(), "--input", games[0].to_str().unwrap()])
+        .args(["--format", "ndjson", "--output", streamed.to_str().unwrap()])
+        .output()
+        .unwrap();
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!((&document["result"]["total_files"], document["result"]["roots"].as_array().unwrap().len()), (&json!(2), 1));
+}
+
+#[tokio::test]
+async fn test_query_filters_sidecars_by_expression() {
+    let temp_dir = TempDir::new().unwrap();
+    let sidecar = ImageSidecar::new(None);
+    
+    let payloads = [
+        (OperationType::FaceDetection, json!({"faces": [{"confidence": 0.95}, {"confidence": 0.7}, {"confidence": 0.8}]})),
+        (OperationType::FaceDetection, json!({"faces": [{"confidence": 0.6}, {"confidence": 0.7}, {"confidence": 0.8}]})),
+        (OperationType::ObjectDetection, json!({"objects": [{"confidence": 0.99}, {"confidence": 0.9}, {"confidence": 0.9}]})),
+    ];
+    for (i, (operation, payload)) in payloads.into_iter().enumerate() {
+        let image_path = temp_dir.path().join(format!("img_{}.jpg", i));
+        fs::write(&image_path, b"fake image data").unwrap();
+        sidecar.save_data(&image_path, operation, payload).await.unwrap();
+    }
+    
+    let expr = QueryExpr::parse("operation == 'face_detection' && detections > 2 && confidence.max >= 0.9").unwrap();
+    let matches = sidecar.query(temp_dir.path(), &expr).await.unwrap();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].sidecar.image_path, temp_dir.path().join("img_0.jpg"));
+    
+    let expr = QueryExpr::parse("!(operation == 'face_detection') || face_detection.faces.confidence.min < 0.65").unwrap();
+    let mut names: Vec<_> = sidecar.query(temp_dir.path(), &expr).await.unwrap()
+        .into_iter()
+        .map(|m| m.sidecar.image_path.file_name().unwrap().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    assert_eq!(names, vec!["img_1.jpg", "img_2.jpg"]);
+    
+    assert!(QueryExpr::parse("detections >").is_err());
+    assert!(QueryExpr::parse("operation == 'unterminated").is_err());
+}
+
+#[tokio::test]
+async fn test_extract_emits_one_value_per_matched_node() {
+    let temp_dir = TempDir::new().unwrap();
+    let sidecar = ImageSidecar::new(None);
+    
+    let payloads = [
+        (OperationType::FaceDetection, json!({"faces": [{"confidence": 0.9}, {"confidence": 0.7}]})),
+        (OperationType::ObjectDetection, json!({"objects": [{"confidence": 0.99}]})),
+    ];
+    for (i, (operation, payload)) in payloads.into_iter().enumerate() {
+        let image_path = temp_dir.path().join(format!("img_{}.jpg", i));
+        fs::write(&image_path, b"fake image data").unwrap();
+        sidecar.save_data(&image_path, operation, payload).await.unwrap();
+    }
+    
+    let path = ExtractPath::parse("$.face_detection.faces[*].confidence").unwrap();
+    let values = sidecar.extract(temp_dir.path(), &path).await.unwrap();
+    assert_eq!(values.len(), 2);
+    assert!(values.iter().all(|v| v.image_path == temp_dir.path().join("img_0.jpg")));
+    assert_eq!(values[0].value, json!(0.9));
+    assert_eq!(values[1].value, json!(0.7));
+    
+    assert!(ExtractPath::parse("$.faces[").is_err());
+}
+
+#[test]
+fn test_show_prints_any_format_and_jsonpath_subsets() {
+    use std::io::Write;
+
+    let temp_dir = TempDir::new().unwrap();
+    let document = json!({"sidecar_info": {"operation_type": "face_detection"}, "face_detection": {"faces": [{"confidence": 0.9}, {"confidence": 0.7}]}});
+    let formats = FormatManager::new();
+    let binary = formats.get_serializer(SidecarFormat::Binary).serialize(&document).unwrap();
+    fs::write(temp_dir.path().join("a.json"), serde_json::to_vec(&document).unwrap()).unwrap();
+    fs::write(temp_dir.path().join("b.rkyv.zst"), zstd::encode_all(binary.as_slice(), 3).unwrap()).unwrap();
+    let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    gzip.write_all(&binary).unwrap();
+    // Misnamed: the contents decide
+    fs::write(temp_dir.path().join("c.json"), gzip.finish().unwrap()).unwrap();
+
+    let show = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust")).arg("show").args(args).output().unwrap()
+    };
+    for (name, format, compression) in [("a.json", "Json", json!(null)), ("b.rkyv.zst", "Rkyv", json!("zstd")), ("c.json", "Binary", json!("gzip"))] {
+        let file = temp_dir.path().join(name);
+        let output = show(&[file.to_str().unwrap(), "--raw"]);
+        assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+        assert_eq!(serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap(), document);
+        assert_eq!(output.stdout.iter().filter(|byte| **byte == b'\n').count(), 1);
+
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+            .args(["--json", "show", file.to_str().unwrap(), "--path", "$.face_detection.faces[*].confidence"])
+            .output()
+            .unwrap();
+        let result = serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()["result"].clone();
+        assert_eq!((&result["format"], &result["compression"], &result["data"]), (&json!(format), &compression, &json!([0.9, 0.7])));
+    }
+
+    let file = temp_dir.path().join("a.json");
+    let output = show(&[file.to_str().unwrap(), "--path", "$.sidecar_info", "--color", "always"]);
+    let text = String::from_utf8(output.stdout).unwrap();
+    assert!(text.contains("\x1b[34m\"operation_type\"\x1b[0m: \x1b[32m\"face_detection\"\x1b[0m"));
+    let plain = show(&[file.to_str().unwrap(), "--path", "$.sidecar_info"]);
+    assert_eq!(String::from_utf8(plain.stdout).unwrap(), format!("{}\n", serde_json::to_string_pretty(&document["sidecar_info"]).unwrap()));
+    assert_eq!(show(&[file.to_str().unwrap(), "--path", "$.missing"]).status.code(), Some(1));
+    assert_eq!(show(&[file.to_str().unwrap(), "--path", "$.faces["]).status.code(), Some(2));
+}
+
+#[tokio::test]
+async fn test_find_duplicates_groups_near_identical_images() {
+    let temp_dir = TempDir::new().unwrap();
+    let sidecar = ImageSidecar::new(None);
+    
+    let waves = |offset: f64| {
+        image::GrayImage::from_fn(64, 64, move |x, y| {
+            image::Luma([(120.0 + 60.0 * (x as f64 / 5.0).sin() + 60.0 * (y as f64 / 7.0).cos() + offset) as u8])
+        })
+    };
+    let checkers = image::GrayImage::from_fn(64, 64, |x, y| image::Luma([if (x / 8 + y / 8) % 2 == 0 { 0 } else { 255 }]));
+    waves(0.0).save(temp_dir.path().join("a.png")).unwrap();
+    waves(6.0).save(temp_dir.path().join("b.png")).unwrap();
+    checkers.save(temp_dir.path().join("c.png")).unwrap();
+    
+    assert_eq!(sidecar.hash_images(temp_dir.path()).await.unwrap(), 3);
+    
+    let groups = sidecar.find_duplicates(temp_dir.path(), 4).await.unwrap();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].keep, temp_dir.path().join("a.png"));
+    assert_eq!(groups[0].duplicates, vec![temp_dir.path().join("b.png")]);
+    
+    let replaced = image_sidecar_rust::imaging::dedupe::hardlink_group(&groups[0]).await.unwrap();
+    assert_eq!(replaced, 1);
+    assert_eq!(fs::read(temp_dir.path().join("b.png")).unwrap(), fs::read(temp_dir.path().join("a.png")).unwrap());
+}
+
+#[test]
+fn test_duplicate_clustering_matches_comparing_every_pair() {
+    use image_sidecar_rust::imaging::dedupe::cluster;
+    use image_sidecar_rust::ImageHash;
+
+    // Random hashes, each followed by a copy with a few bits flipped
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    let mut hashes = Vec::new();
+    for i in 0..300 {
+        let phash = next();
+        let flipped = phash ^ (1u64 << (next() % 64)) ^ (1u64 << (next() % 64)) ^ (1u64 << (next() % 64));
+        for (suffix, phash) in [("a", phash), ("b", flipped)] {
+            hashes.push((PathBuf::from(format!("{:03}{}.jpg", i, suffix)), ImageHash { phash, dhash: 0, width: 1, height: 1 }));
+        }
+    }
+
+    for threshold in [0, 3, 12, 64] {
+        // Connected components over every pair, smallest path first
+        let mut components: Vec<Vec<usize>> = Vec::new();
+        let mut seen = vec![false; hashes.len()];
+        for start in 0..hashes.len() {
+            if std::mem::replace(&mut seen[start], true) {
+                continue;
+            }
+            let mut component = vec![start];
+            let mut next_member = 0;
+            while next_member < component.len() {
+                let i = component[next_member];
+                next_member += 1;
+                for j in 0..hashes.len() {
+                    if !seen[j] && hashes[i].1.distance(&hashes[j].1) <= threshold {
+                        seen[j] = true;
+                        component.push(j);
+                    }
+                }
+            }
+            component.sort();
+            components.push(component);
+        }
+        let expected: Vec<Vec<PathBuf>> = components
+            .into_iter()
+            .filter(|component| component.len() > 1)
+            .map(|component| component.into_iter().map(|i| hashes[i].0.clone()).collect())
+            .collect();
+        let groups: Vec<Vec<PathBuf>> = cluster(hashes.clone(), threshold)
+            .into_iter()
+            .map(|group| std::iter::once(group.keep).chain(group.duplicates).collect())
+            .collect();
+        assert_eq!(groups, expected, "threshold {}", threshold);
+    }
+}
+
+#[tokio::test]
+async fn test_extract_crops_writes_one_file_per_detection() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_dir = temp_dir.path().join("crops");
+    let sidecar = ImageSidecar::new(None);
+    
+    let image_path = temp_dir.path().join("frame.png");
+    image::RgbImage::from_pixel(100, 80, image::Rgb([200, 10, 10])).save(&image_path).unwrap();
+    sidecar.save_data(&image_path, OperationType::FaceDetection, json!({
+        "faces": [
+            {"face_id": 7, "bbox": {"x": 0.1, "y": 0.1, "width": 0.5, "height": 0.5}},
+            {"bbox": [10, 10, 20, 30]},
+            {"confidence": 0.4}
+        ]
+    })).await.unwrap();
+    
+    let crops = sidecar.extract_crops(temp_dir.path(), OperationType::FaceDetection, &output_dir).await.unwrap();
+    assert_eq!(crops.len(), 2);
+    assert_eq!(crops[0].crop_path, output_dir.join("frame_7.jpg"));
+    assert_eq!(crops[1].crop_path, output_dir.join("frame_1.jpg"));
+    assert_eq!(image::image_dimensions(&crops[0].crop_path).unwrap(), (50, 40));
+    assert_eq!(image::image_dimensions(&crops[1].crop_path).unwrap(), (20, 30));
+
+    // A second image with the same stem, a repeated id and an id that is not a safe file name
+    fs::create_dir(temp_dir.path().join("other")).unwrap();
+    let other_path = temp_dir.path().join("other/frame.jpg");
+    image::RgbImage::from_pixel(100, 80, image::Rgb([10, 200, 10])).save(&other_path).unwrap();
+    sidecar.save_data(&other_path, OperationType::FaceDetection, json!({
+        "faces": [
+            {"face_id": 7, "bbox": [0, 0, 10, 10]},
+            {"face_id": 7, "bbox": [0, 0, 10, 10]},
+            {"id": "../escape", "bbox": [0, 0, 10, 10]}
+        ]
+    })).await.unwrap();
+    let crops = sidecar.extract_crops(temp_dir.path(), OperationType::FaceDetection, &output_dir).await.unwrap();
+    let mut paths: Vec<_> = crops.iter().map(|crop| crop.crop_path.clone()).collect();
+    paths.sort();
+    paths.dedup();
+    assert_eq!(paths.len(), 5, "{:?}", paths);
+    assert!(paths.iter().all(|path| path.parent() == Some(output_dir.as_path()) && path.exists()), "{:?}", paths);
+    assert_eq!(crops.iter().filter(|crop| crop.detection_id == "../escape").count(), 1);
+}
+
+#[tokio::test]
+async fn test_normalize_boxes_rewrites_to_convention_and_reports_invalid() {
+    let temp_dir = TempDir::new().unwrap();
+    let sidecar = ImageSidecar::new(None);
+    
+    let image_path = temp_dir.path().join("frame.png");
+    image::RgbImage::new(200, 100).save(&image_path).unwrap();
+    sidecar.save_data(&image_path, OperationType::FaceDetection, json!({
+        "faces": [{"bbox": {"x": 0.1, "y": 0.2, "width": 0.5, "height": 0.5}}]
+    })).await.unwrap();
+    sidecar.save_data(&image_path, OperationType::ObjectDetection, json!({
+        "objects": [{"bbox": {"xmin": 10, "ymin": 10, "xmax": 300, "ymax": 50}}, {"bbox": [150, 50, 20, 10]}]
+    })).await.unwrap();
+    
+    let convention = BoxConvention { format: BoxFormat::Xyxy, space: CoordinateSpace::Pixel };
+    let report = sidecar.normalize_boxes(temp_dir.path(), convention, true).await.unwrap();
+    assert_eq!(report.boxes_converted, 2);
+    let data = sidecar.read_data(&image_path).await.unwrap();
+    assert_eq!(data["face_detection"]["faces"][0]["bbox"]["x"], json!(0.1));
+    
+    let report = sidecar.normalize_boxes(temp_dir.path(), convention, false).await.unwrap();
+    assert_eq!(report.sidecars_updated, 1);
+    assert_eq!(report.boxes_converted, 2);
+    assert_eq!(report.invalid_boxes.len(), 1);
+    assert_eq!(report.invalid_boxes[0].operation, "object_detection");
+    assert_eq!(report.invalid_boxes[0].detection_index, 0);
+    
+    let data = sidecar.read_data(&image_path).await.unwrap();
+    assert_eq!(data["face_detection"]["faces"][0]["bbox"], json!({"x1": 20.0, "y1": 20.0, "x2": 120.0, "y2": 70.0}));
+    assert_eq!(data["object_detection"]["objects"][1]["bbox"], json!({"x1": 150.0, "y1": 50.0, "x2": 170.0, "y2": 60.0}));
+    assert_eq!(data["object_detection"]["objects"][0]["bbox"]["xmax"], json!(300));
+}
+
+#[tokio::test]
+async fn test_repair_fixes_recoverable_corruption() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    let sidecar = ImageSidecar::new(None);
+    let healthy = json!({"sidecar_info": {"operation_type": "face_detection"}, "face_detection": {"faces": []}});
+    
+    fs::write(dir.join("trailing.json"), format!("{}\n}}garbage", healthy)).unwrap();
+    fs::write(dir.join("double.json"), serde_json::to_string(&healthy.to_string()).unwrap()).unwrap();
+    fs::write(dir.join("misnamed.bin"), healthy.to_string()).unwrap();
+    fs::write(dir.join("no_info.json"), json!({"face_detection": {"faces": []}}).to_string()).unwrap();
+    fs::write(dir.join("bad_utf8.json"), b"{\"sidecar_info\": {}, \"note\": \"\xff\"}").unwrap();
+    fs::write(dir.join("healthy.json"), healthy.to_string()).unwrap();
+    fs::write(dir.join("hopeless.json"), "not json at all").unwrap();
+    
+    let records = sidecar.repair(dir, true).await.unwrap();
+    assert_eq!(records.len(), 6);
+    assert!(dir.join("misnamed.bin").exists());
+    assert!(!dir.join("misnamed.json").exists());
+    
+    let records = sidecar.repair(dir, false).await.unwrap();
+    let failed: Vec<_> = records.iter().filter(|r| r.error.is_some()).collect();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].sidecar_path, dir.join("hopeless.json"));
+    
+    assert!(!dir.join("misnamed.bin").exists());
+    let read = |name: &str| serde_json::from_slice::<serde_json::Value>(&fs::read(dir.join(name)).unwrap()).unwrap();
+    assert_eq!(read("misnamed.json"), healthy);
+    assert_eq!(read("trailing.json"), healthy);
+    assert_eq!(read("double.json"), healthy);
+    assert_eq!(read("no_info.json")["sidecar_info"]["operation_type"], "face_detection");
+    assert_eq!(read("bad_utf8.json")["note"], "\u{fffd}");
+    
+    let records = sidecar.repair(dir, false).await.unwrap();
+    assert_eq!(records.len(), 1);
+}
+
+#[test]
+fn test_validation_reports_group_by_operation_with_failure_reasons() {
+    let mut valid = ValidationResult::new(PathBuf::from("games/a.json"));
+    valid.is_valid = true;
+    valid.operation_type = Some(OperationType::FaceDetection);
+    
+    let mut invalid = ValidationResult::new(PathBuf::from("games/b&c.json"));
+    invalid.error = Some("JSON parse error: <eof>".to_string());
+    invalid.operation_type = Some(OperationType::Yolov8);
+    
+    let results = vec![valid, invalid];
+    
+    let mut junit = Vec::new();
+    ReportFormat::Junit.write(&results, &mut junit).unwrap();
+    let junit = String::from_utf8(junit).unwrap();
+    assert!(junit.contains(r#"<testsuites name="sidecar-validation" tests="2" failures="1""#));
+    assert!(junit.contains(r#"<testsuite name="face_detection" tests="1" failures="0""#));
+    assert!(junit.contains(r#"name="games/b&amp;c.json" classname="yolov8""#));
+    assert!(junit.contains(r#"<failure message="JSON parse error: &lt;eof&gt;""#));
+    
+    let mut sarif = Vec::new();
+    ReportFormat::Sarif.write(&results, &mut sarif).unwrap();
+    let sarif: serde_json::Value = serde_json::from_slice(&sarif).unwrap();
+    let run = &sarif["runs"][0];
+    assert_eq!(sarif["version"], "2.1.0");
+    assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), 2);
+    assert_eq!(run["results"].as_array().unwrap().len(), 1);
+    assert_eq!(run["results"][0]["ruleId"], "invalid-sidecar/yolov8");
+    assert_eq!(run["results"][0]["message"]["text"], "JSON parse error: <eof>");
+    assert_eq!(run["results"][0]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"], "games/b&c.json");
+    
+    assert_eq!(ReportFormat::from_path(Path::new("junit.xml")), Some(ReportFormat::Junit));
+    assert_eq!(ReportFormat::from_path(Path::new("out.sarif")), Some(ReportFormat::Sarif));
+    assert_eq!(ReportFormat::from_path(Path::new("out.txt")), None);
+}
+
+#[test]
+fn test_cli_json_output_and_exit_codes() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("orphaned.json"), r#"{"sidecar_info": {"operation_type": "face_detection"}}"#).unwrap();
+    
+    let run = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+            .args(args)
+            .output()
+            .unwrap();
+        let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        (output.status.code(), document)
+    };
+    let input = temp_dir.path().to_str().unwrap();
+    
+    let (code, document) = run(&["--json", "cleanup", "--input", input, "--dry-run"]);
+    assert_eq!(code, Some(0));
+    assert_eq!(document["command"], "cleanup");
+    assert_eq!(document["status"], "success");
+    assert_eq!(document["result"]["orphaned"].as_array().unwrap().len(), 1);
+    assert!(document["error"].is_null());
+    
+    let (code, document) = run(&["convert", "--input", input, "--format", "xml", "--json"]);
+    assert_eq!(code, Some(2));
+    assert_eq!(document["status"], "invalid_arguments");
+    assert!(document["error"].as_str().unwrap().contains("xml"));
+    
+    // Arguments clap itself rejects get the same envelope
+    let (code, document) = run(&["--json", "cleanup", "--input", input, "--no-such-flag"]);
+    assert_eq!(code, Some(2));
+    assert_eq!(document["command"], "cleanup");
+    assert_eq!(document["status"], "invalid_arguments");
+    assert!(document["error"].as_str().unwrap().contains("--no-such-flag"));
+}
+
+#[test]
+fn test_cli_validate_error_budget_sets_exit_code() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("good.jpg"), b"fake image data").unwrap();
+    fs::write(temp_dir.path().join("good.json"), r#"{"sidecar_info": {"operation_type": "face_detection"}}"#).unwrap();
+    fs::write(temp_dir.path().join("bad.jpg"), b"fake image data").unwrap();
+    fs::write(temp_dir.path().join("bad.json"), "{ not json").unwrap();
+    
+    let run = |extra: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+            .args(["--json", "validate", "--input", temp_dir.path().to_str().unwrap()])
+            .args(extra)
+            .output()
+            .unwrap()
+            .status
+            .code()
+    };
+    
+    assert_eq!(run(&[]), Some(0));
+    assert_eq!(run(&["--fail-on-invalid"]), Some(4));
+    assert_eq!(run(&["--max-invalid-percent", "50"]), Some(0));
+    assert_eq!(run(&["--max-invalid-percent", "49.9"]), Some(4));
+    assert_eq!(run(&["--max-invalid-percent", "150"]), Some(2));
+}
+
+#[tokio::test]
+async fn test_manifest_verify_detects_added_removed_and_modified_sidecars() {
+    let temp_dir = TempDir::new().unwrap();
+    let face = json!({ "sidecar_info": { "operation_type": "face_detection" }, "face_detection": { "faces": [] } });
+    for name in ["IMG_1", "IMG_2", "IMG_3"] {
+        fs::write(temp_dir.path().join(format!("{}.jpg", name)), b"fake image data").unwrap();
+        fs::write(temp_dir.path().join(format!("{}.json", name)), face.to_string()).unwrap();
+    }
+    
+    let sidecar = ImageSidecar::new(None);
+    let manifest = sidecar.create_manifest(temp_dir.path()).await.unwrap();
+    assert_eq!(manifest.entries.len(), 3);
+    assert_eq!(manifest.entries[0].path, "IMG_1.json");
+    assert_eq!(manifest.entries[0].checksum.len(), 64);
+    assert_eq!(manifest.entries[0].operations, vec!["face_detection".to_string()]);
+    
+    let manifest_path = temp_dir.path().join("manifest.out");
+    manifest.save(&manifest_path).unwrap();
+    let manifest = Manifest::load(&manifest_path).unwrap();
+    assert!(sidecar.verify_manifest(temp_dir.path(), &manifest).await.unwrap().is_clean());
+    
+    let mut rerun = face.clone();
+    rerun["yolov8"] = json!({ "detections": [] });
+    fs::write(temp_dir.path().join("IMG_1.json"), rerun.to_string()).unwrap();
+    fs::remove_file(temp_dir.path().join("IMG_2.json")).unwrap();
+    fs::write(temp_dir.path().join("IMG_4.json"), face.to_string()).unwrap();
+    
+    let verification = sidecar.verify_manifest(temp_dir.path(), &manifest).await.unwrap();
+    assert!(!verification.is_clean());
+    assert_eq!(verification.added, vec!["IMG_4.json".to_string()]);
+    assert_eq!(verification.removed, vec!["IMG_2.json".to_string()]);
+    assert_eq!(verification.modified.len(), 1);
+    assert_eq!(verification.modified[0].path, "IMG_1.json");
+    assert_eq!(verification.modified[0].added_operations, vec!["yolov8".to_string()]);
+    assert_eq!(verification.unchanged, 1);
+}
+
+#[tokio::test]
+async fn test_diff_reports_coverage_and_payload_changes_between_directories() {
+    let before = TempDir::new().unwrap();
+    let after = TempDir::new().unwrap();
+    let face = |confidence: f64| json!({
+        "sidecar_info": { "operation_type": "face_detection" },
+        "face_detection": { "faces": [{ "bbox": [1, 2, 3, 4], "confidence": confidence }] }
+    });
+    
+    for dir in [before.path(), after.path()] {
+        for name in ["IMG_1", "IMG_2", "IMG_3", "IMG_4"] {
+            fs::write(dir.join(format!("{}.jpg", name)), b"fake image data").unwrap();
+        }
+        fs::write(dir.join("IMG_1.json"), face(0.9).to_string()).unwrap();
+    }
+    
+    // Rerun changed a confidence on IMG_2, added yolov8 on IMG_3, IMG_4 lost its sidecar
+    fs::write(before.path().join("IMG_2.json"), face(0.5).to_string()).unwrap();
+    fs::write(after.path().join("IMG_2.json"), face(0.7).to_string()).unwrap();
+    fs::write(before.path().join("IMG_3.json"), face(0.9).to_string()).unwrap();
+    let mut rerun = face(0.9);
+    rerun["yolov8"] = json!({ "detections": [] });
+    fs::write(after.path().join("IMG_3.json"), rerun.to_string()).unwrap();
+    fs::write(before.path().join("IMG_4.json"), face(0.9).to_string()).unwrap();
+    
+    let sidecar = ImageSidecar::new(None);
+    let diff = sidecar.diff(before.path(), after.path()).await.unwrap();
+    
+    assert!(!diff.is_empty());
+    assert_eq!(diff.only_left, vec!["IMG_4.jpg".to_string()]);
+    assert!(diff.only_right.is_empty());
+    assert_eq!(diff.unchanged, 1);
+    assert_eq!(diff.changed.len(), 2);
+    
+    let confidence = &diff.changed[0];
+    assert_eq!(confidence.image, "IMG_2.jpg");
+    assert_eq!(confidence.changed_operations[0].operation, "face_detection");
+    assert_eq!(
+        serde_json::to_value(&confidence.changed_operations[0].patch).unwrap(),
+        json!([{ "op": "replace", "path": "/faces/0/confidence", "value": 0.7 }])
+    );
+    
+    assert_eq!(diff.changed[1].image, "IMG_3.jpg");
+    assert_eq!(diff.changed[1].added_operations, vec!["yolov8".to_string()]);
+    assert!(diff.changed[1].changed_operations.is_empty());
+}
+
+#[test]
+fn test_json_patch_round_trips_and_applies_atomically() {
+    let from = json!({ "faces": [{ "confidence": 0.5 }, { "confidence": 0.6 }, { "confidence": 0.7 }], "model": "a/b", "stale": true });
+    let to = json!({ "faces": [{ "confidence": 0.9 }], "model": "a/c", "face_count": 1 });
+    
+    let patch = JsonUtils::diff(&from, &to);
+    assert!(patch.0.contains(&PatchOperation::Replace { path: "/faces/0/confidence".to_string(), value: json!(0.9) }));
+    assert!(patch.0.contains(&PatchOperation::Remove { path: "/stale".to_string() }));
+    
+    let mut patched = from.clone();
+    JsonUtils::apply_patch(&mut patched, &patch).unwrap();
+    assert_eq!(patched, to);
+    assert!(JsonUtils::diff(&to, &to).is_empty());
+    
+    // RFC 6902 documents parse, and a failing operation leaves the value untouched
+    let patch: JsonPatch = serde_json::from_value(json!([
+        { "op": "add", "path": "/faces/-", "value": { "confidence": 0.1 } },
+        { "op": "remove", "path": "/missing" }
+    ])).unwrap();
+    assert!(JsonUtils::apply_patch(&mut patched, &patch).is_err());
+    assert_eq!(patched, to);
+}
+
+#[tokio::test]
+async fn test_three_way_merge_combines_edits_and_reports_conflicts() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = json!({ "sidecar_info": { "operation_type": "face_detection" }, "face_detection": { "faces": [], "reviewed": false, "model": "v1" } });
+    let mut ours = base.clone();
+    ours["face_detection"]["reviewed"] = json!(true);
+    ours["face_detection"]["model"] = json!("v2");
+    let mut theirs = base.clone();
+    theirs["yolov8"] = json!({ "detections": [] });
+    theirs["face_detection"]["model"] = json!("v3");
+    
+    for (name, value) in [("base.json", &base), ("ours.json", &ours), ("theirs.json", &theirs)] {
+        fs::write(temp_dir.path().join(name), value.to_string()).unwrap();
+    }
+    
+    let log_path = temp_dir.path().join("mutations.jsonl");
+    let sidecar = ImageSidecar::builder().mutation_log(Arc::new(MutationLog::open(&log_path).unwrap())).build();
+    let output = temp_dir.path().join("merged.bin");
+    let result = sidecar.merge_sidecars(
+        Some(&temp_dir.path().join("base.json")),
+        &temp_dir.path().join("ours.json"),
+        &temp_dir.path().join("theirs.json"),
+        &output,
+    ).await.unwrap();
+    
+    assert_eq!(result.conflicts.len(), 1);
+    assert_eq!(result.conflicts[0].path, "/face_detection/model");
+    assert_eq!(result.conflicts[0].theirs, Some(json!("v3")));
+    assert_eq!(result.merged["face_detection"]["reviewed"], json!(true));
+    assert_eq!(result.merged["face_detection"]["model"], json!("v2"));
+    assert_eq!(result.merged["yolov8"], json!({ "detections": [] }));
+    
+    // The merged sidecar is written in the format its extension names
+    assert_eq!(SidecarFormat::from_path(&output), Some(SidecarFormat::Binary));
+    let decoded: String = bincode::deserialize(&fs::read(&output).unwrap()).unwrap();
+    assert_eq!(serde_json::from_str::<serde_json::Value>(&decoded).unwrap(), result.merged);
+    let record: MutationRecord = serde_json::from_str(fs::read_to_string(&log_path).unwrap().trim()).unwrap();
+    assert_eq!((record.mutation, record.sidecar_path), (Mutation::Write, output));
+}
+
+#[test]
+fn test_config_profiles_layer_over_defaults_and_drive_cli() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("pipelines.toml");
+    fs::write(&config_path, r#"
+[defaults]
+workers = 8
+format = "json"
+
+[profile.training]
+format = "bin"
+max_invalid_percent = 5.0
+
+[profile.archive]
+fail_on_invalid = true
+
+[profile.phones]
+image_extensions = ["heic"]
+merge_strategy = "deep"
+"#).unwrap();
+    
+    let config = Config::load(&config_path).unwrap();
+    let training = config.resolve(Some("training")).unwrap();
+    assert_eq!(training.workers, Some(8));
+    assert_eq!(training.sidecar_format().unwrap(), Some(SidecarFormat::Binary));
+    assert_eq!(training.max_invalid_percent, Some(5.0));
+    assert_eq!(config.resolve(Some("archive")).unwrap().sidecar_format().unwrap(), Some(SidecarFormat::Json));
+    assert_eq!(config.resolve(None).unwrap().fail_on_invalid, None);
+    assert!(config.resolve(Some("missing")).is_err());
+    assert_eq!(config.resolve(Some("phones")).unwrap().merge_strategy().unwrap(), Some(MergeStrategy::Deep));
+    
+    let data_dir = temp_dir.path().join("game");
+    fs::create_dir(&data_dir).unwrap();
+    fs::write(data_dir.join("IMG_1.jpg"), b"fake image data").unwrap();
+    fs::write(data_dir.join("IMG_1.json"), r#"{"sidecar_info": {"operation_type": "face_detection"}}"#).unwrap();
+    fs::write(data_dir.join("IMG_2.jpg"), b"fake image data").unwrap();
+    fs::write(data_dir.join("IMG_2.json"), "{ not json").unwrap();
+    
+    let run = |profile: &str, args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+            .args(["--json", "--config", config_path.to_str().unwrap()])
+            .args(args)
+            .env("SPORTBALL_SIDECAR_PROFILE", profile)
+            .output()
+            .unwrap();
+        let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        (output.status.code(), document)
+    };
+    let input = data_dir.to_str().unwrap();
+    
+    // The profile supplies the target format and the validation strictness
+    let (code, document) = run("training", &["convert", "--input", input, "--dry-run"]);
+    assert_eq!(code, Some(0));
+    assert_eq!(document["result"]["target_format"], "Binary");
+    assert_eq!(run("archive", &["validate", "--input", input]).0, Some(4));
+    assert_eq!(run("archive", &["validate", "--input", input, "--no-fail-on-invalid"]).0, Some(0));
+    assert_eq!(run("training", &["validate", "--input", input]).0, Some(4));
+    assert_eq!(run("training", &["validate", "--input", input, "--max-invalid-percent", "60"]).0, Some(0));
+    
+    // The profile's image extensions decide which files count as images
+    let (code, document) = run("phones", &["stats", "--input", input]);
+    assert_eq!(code, Some(0));
+    assert_eq!(document["result"]["total_images"], 0);
+    
+    let (code, document) = run("nightly", &["validate", "--input", input]);
+    assert_eq!(code, Some(2));
+    assert!(document["error"].as_str().unwrap().contains("archive, phones, training"));
+}
+
+#[tokio::test]
+async fn test_builder_configures_extensions_merge_strategy_and_scan() {
+    let temp_dir = TempDir::new().unwrap();
+    let sidecar = ImageSidecar::builder()
+        .max_workers(2)
+        .image_extensions(["png", ".HEIC"])
+        .merge_strategy(MergeStrategy::Deep)
+        .skip_hidden(true)
+        .default_format(SidecarFormat::Json)
+        .build();
+    assert_eq!(sidecar.get_default_format(), SidecarFormat::Json);
+    
+    let image_path = temp_dir.path().join("frame.heic");
+    fs::write(&image_path, b"heic").unwrap();
+    sidecar.save_data(&image_path, OperationType::FaceDetection, json!({"faces": [], "model": "a"})).await.unwrap();
+    sidecar.save_data(&image_path, OperationType::FaceDetection, json!({"threshold": 0.5})).await.unwrap();
+    
+    let hidden_dir = temp_dir.path().join(".cache");
+    fs::create_dir(&hidden_dir).unwrap();
+    fs::write(hidden_dir.join("thumb.png"), b"png").unwrap();
+    sidecar.save_data(&hidden_dir.join("thumb.png"), OperationType::FaceDetection, json!({"faces": []})).await.unwrap();
+    
+    let data = sidecar.read_data(&image_path).await.unwrap();
+    assert_eq!(data["face_detection"], json!({"faces": [], "model": "a", "threshold": 0.5}));
+    
+    let sidecars = sidecar.find_sidecars(temp_dir.path()).await.unwrap();
+    assert_eq!(sidecars.len(), 1);
+    assert_eq!(sidecars[0].image_path, image_path);
+    
+    // The simple constructor keeps replacing payloads and scanning hidden directories
+    let plain = ImageSidecar::new(None);
+    plain.save_data(&image_path, OperationType::FaceDetection, json!({"faces": [1]})).await.unwrap();
+    assert_eq!(plain.read_data(&image_path).await.unwrap()["face_detection"], json!({"faces": [1]}));
+    assert_eq!(plain.find_sidecars(hidden_dir.parent().unwrap()).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_library_errors_are_matchable() {
+    let temp_dir = TempDir::new().unwrap();
+    let sidecar = ImageSidecar::new(None);
+    
+    let image_path = temp_dir.path().join("frame.jpg");
+    fs::write(&image_path, b"jpg").unwrap();
+    fs::write(temp_dir.path().join("frame.bin"), b"\x01not bincode").unwrap();
+    let error = sidecar.read_data(&image_path).await.unwrap_err();
+    assert!(matches!(error, SidecarError::Format(_)), "{error:?}");
+    
+    let mut value = json!({});
+    let patch: JsonPatch = serde_json::from_value(json!([{ "op": "remove", "path": "/missing" }])).unwrap();
+    let error = JsonUtils::apply_patch(&mut value, &patch).unwrap_err();
+    assert!(matches!(error, SidecarError::Schema(_)), "{error:?}");
+    
+    let error = VectorIndex::new(4).find_similar(&image_path, 3).unwrap_err();
+    assert!(matches!(error, SidecarError::ImageNotFound(path) if path == image_path));
+    
+    let error = Manifest::load(&temp_dir.path().join("missing.json")).unwrap_err();
+    assert!(matches!(error, SidecarError::Io(ref io) if io.kind() == std::io::ErrorKind::NotFound));
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn test_ffi_save_load_validate_round_trip() {
+    use image_sidecar_rust::ffi::*;
+    use std::ffi::{CStr, CString};
+
+    let temp_dir = TempDir::new().unwrap();
+    let image_path = temp_dir.path().join("frame.jpg");
+    fs::write(&image_path, b"fake image data").unwrap();
+
+    let image = CString::new(image_path.to_str().unwrap()).unwrap();
+    let directory = CString::new(temp_dir.path().to_str().unwrap()).unwrap();
+    let operation = CString::new("face_detection").unwrap();
+    let data = CString::new(r#"{"faces": [{"confidence": 0.9}]}"#).unwrap();
+
+    unsafe {
+        let handle = sidecar_manager_new(0);
+        assert!(!handle.is_null());
+
+        let status = sidecar_save(handle, image.as_ptr(), operation.as_ptr(), data.as_ptr());
+        assert_eq!(status, SidecarStatus::Ok);
+
+        let mut out = std::ptr::null_mut();
+        assert_eq!(sidecar_load(handle, image.as_ptr(), &mut out), SidecarStatus::Ok);
+        let loaded: serde_json::Value = serde_json::from_str(CStr::from_ptr(out).to_str().unwrap()).unwrap();
+        assert_eq!(loaded["face_detection"]["faces"][0]["confidence"], json!(0.9));
+        sidecar_string_free(out);
+
+        let mut out = std::ptr::null_mut();
+        assert_eq!(sidecar_validate(handle, directory.as_ptr(), &mut out), SidecarStatus::Ok);
+        let results: serde_json::Value = serde_json::from_str(CStr::from_ptr(out).to_str().unwrap()).unwrap();
+        assert_eq!(results.as_array().unwrap().len(), 1);
+        sidecar_string_free(out);
+
+        let invalid = CString::new("{not json").unwrap();
+        let status = sidecar_save(handle, image.as_ptr(), operation.as_ptr(), invalid.as_ptr());
+        assert_eq!(status, SidecarStatus::InvalidJson);
+        assert!(CStr::from_ptr(sidecar_last_error()).to_str().unwrap().contains("Invalid data JSON"));
+
+        assert_eq!(sidecar_load(std::ptr::null(), image.as_ptr(), &mut out), SidecarStatus::NullArgument);
+        sidecar_manager_free(handle);
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[test]
+fn test_wasm_inspects_and_converts_in_memory_buffers() {
+    use image_sidecar_rust::wasm::{convert, decode, inspect};
+
+    let data = json!({"face_detection": {"faces": [{"confidence": 0.9}]}, "sidecar_info": {}});
+    let json_bytes = serde_json::to_vec(&data).unwrap();
+
+    let binary = convert(&json_bytes, Some("frame.json"), "bin").unwrap();
+    let (format, decoded) = decode(&binary, None).unwrap();
+    assert_eq!(format, SidecarFormat::Binary);
+    assert_eq!(decoded, data);
+
+    let report = inspect(&binary, Some("frame.bin"));
+    assert!(report.is_valid);
+    assert_eq!(report.format.as_deref(), Some("bin"));
+    assert_eq!(report.operations, vec!["face_detection".to_string()]);
+
+    let report = inspect(b"\x00\x01garbage", None);
+    assert!(!report.is_valid);
+    assert!(report.format.is_none());
+    assert!(report.error.is_some());
+
+    assert!(convert(&json_bytes, None, "xml").is_err());
+}
+
+#[tokio::test]
+async fn test_metrics_endpoint_reports_scan_results() {
+    use image_sidecar_rust::Metrics;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let temp_dir = TempDir::new().unwrap();
+    for name in ["a", "b"] {
+        fs::write(temp_dir.path().join(format!("{}.jpg", name)), b"fake image data").unwrap();
+    }
+    let sidecar = ImageSidecar::new(None);
+    sidecar
+        .save_data(&temp_dir.path().join("a.jpg"), OperationType::FaceDetection, json!({"faces": []}))
+        .await
+        .unwrap();
+
+    let metrics = Arc::new(Metrics::new());
+    metrics.observe_statistics(&sidecar.get_statistics(temp_dir.path()).await.unwrap());
+    metrics.observe_validation(temp_dir.path(), &sidecar.validate_sidecars(temp_dir.path()).await.unwrap());
+    metrics.observe_scan(std::time::Duration::from_millis(300));
+    metrics.record_conversion(5, std::time::Duration::from_secs(2));
+
+    let root = temp_dir.path().display().to_string();
+    let rendered = metrics.render();
+    assert!(rendered.contains(&format!("image_sidecar_images{{root=\"{}\"}} 2\n", root)));
+    assert!(rendered.contains(&format!("image_sidecar_coverage_percent{{root=\"{}\"}} 50\n", root)));
+    assert!(rendered.contains(&format!("image_sidecar_invalid_sidecars{{root=\"{}\"}} 0\n", root)));
+    assert!(rendered.contains("image_sidecar_converted_total 5\n"));
+    assert!(rendered.contains("image_sidecar_scan_duration_seconds_bucket{le=\"0.25\"} 0\n"));
+    assert!(rendered.contains("image_sidecar_scan_duration_seconds_bucket{le=\"0.5\"} 1\n"));
+    assert!(rendered.contains("# TYPE image_sidecar_scan_duration_seconds histogram\n"));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = tokio::spawn(image_sidecar_rust::metrics::serve(listener, Arc::clone(&metrics)));
+
+    let mut stream = tokio::net::TcpStream::connect(address).await.unwrap();
+    stream.write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n").await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.contains("image_sidecar_validation_failures_total 0"));
+
+    let mut stream = tokio::net::TcpStream::connect(address).await.unwrap();
+    stream.write_all(b"GET / HTTP/1.1\r\n\r\n").await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 404"));
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_metrics_count_each_invalid_file_once_and_record_conversions() {
+    use image_sidecar_rust::Metrics;
+
+    let temp_dir = TempDir::new().unwrap();
+    let broken = temp_dir.path().join("broken.json");
+    fs::write(&broken, "{ not json").unwrap();
+    fs::write(temp_dir.path().join("a.json"), r#"{"face_detection": {"faces": []}}"#).unwrap();
+    let metrics = Arc::new(Metrics::new());
+    let sidecar = ImageSidecar::builder().metrics(Arc::clone(&metrics)).build();
+    let failures = |metrics: &Metrics| metrics.render().lines().find_map(|line| line.strip_prefix("image_sidecar_validation_failures_total ").map(str::to_string)).unwrap();
+
+    // Rescanning an unchanged broken file does not count it again; changing it does
+    for _ in 0..3 {
+        metrics.observe_validation(temp_dir.path(), &sidecar.validate_sidecars(temp_dir.path()).await.unwrap());
+    }
+    assert_eq!(failures(&metrics), "1");
+    fs::write(&broken, "{ still not json").unwrap();
+    metrics.observe_validation(temp_dir.path(), &sidecar.validate_sidecars(temp_dir.path()).await.unwrap());
+    assert_eq!(failures(&metrics), "2");
+
+    sidecar.manager().convert_sidecar_format(&temp_dir.path().join("a.json"), SidecarFormat::Binary).await.unwrap();
+    assert!(metrics.render().contains("image_sidecar_converted_total 1\n"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_notifier_streams_sidecar_events_to_unix_socket() {
+    use image_sidecar_rust::events::{EventKind, SidecarEvent, UnixSocketSink};
+    use image_sidecar_rust::{Notifier, NotifierOptions};
+    use std::io::{BufRead, BufReader};
+    use std::os::unix::net::UnixListener;
+
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("events.sock");
+    let listener = UnixListener::bind(&socket_path).unwrap();
+    let reader = std::thread::spawn(move || {
+        let mut events = Vec::new();
+        // Each delivery is one connection; the three events arrive in one batch
+        while events.len() < 3 {
+            let (stream, _) = listener.accept().unwrap();
+            for line in BufReader::new(stream).lines() {
+                events.push(serde_json::from_str::<SidecarEvent>(&line.unwrap()).unwrap());
+            }
+        }
+        events
+    });
+
+    let options = NotifierOptions { max_delay: std::time::Duration::from_millis(200), ..NotifierOptions::default() };
+    let notifier = Arc::new(Notifier::start(vec![Arc::new(UnixSocketSink::new(&socket_path))], options));
+    let sidecar = ImageSidecar::builder().notifier(Arc::clone(&notifier)).build();
+
+    let image_path = temp_dir.path().join("photo.jpg");
+    fs::write(&image_path, b"fake image data").unwrap();
+    sidecar.save_data(&image_path, OperationType::FaceDetection, json!({"faces": []})).await.unwrap();
+    sidecar.save_data(&image_path, OperationType::ObjectDetection, json!({"objects": []})).await.unwrap();
+    let orphan = temp_dir.path().join("gone.bin");
+    fs::write(&orphan, b"{}").unwrap();
+    assert_eq!(sidecar.cleanup_orphaned(temp_dir.path()).await.unwrap(), 1);
+
+    drop(sidecar);
+    Arc::into_inner(notifier).unwrap().shutdown().await.unwrap();
+    let events = reader.join().unwrap();
+
+    let kinds: Vec<EventKind> = events.iter().map(|event| event.kind).collect();
+    assert_eq!(kinds, vec![EventKind::Created, EventKind::Updated, EventKind::Deleted]);
+    assert_eq!(events[0].image_path.as_deref(), Some(image_path.as_path()));
+    assert_eq!(events[0].operation.as_deref(), Some("face_detection"));
+    assert_eq!(events[1].operation.as_deref(), Some("object_detection"));
+    assert_eq!(events[2].image_path, None);
+    assert!(events[2].sidecar_path.ends_with("gone.bin"));
+
+    // The CLI sends the events of a batch command to the socket it is given
+    let listener = UnixListener::bind(temp_dir.path().join("cli.sock")).unwrap();
+    fs::write(temp_dir.path().join("lonely.bin"), b"{}").unwrap();
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_image-sidecar-rust"))
+        .args(["--json", "--notify-socket", temp_dir.path().join("cli.sock").to_str().unwrap()])
+        .args(["cleanup", "--input", temp_dir.path().to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    listener.set_nonblocking(true).unwrap();
+    let (stream, _) = listener.accept().unwrap();
+    stream.set_nonblocking(false).unwrap();
+    let events: Vec<SidecarEvent> = BufReader::new(stream).lines().map(|line| serde_json::from_str(&line.unwrap()).unwrap()).collect();
+    assert_eq!(events.len(), 1);
+    assert!(events[0].kind == EventKind::Deleted && events[0].sidecar_path.ends_with("lonely.bin"));
+}
+
+#[cfg(feature = "kafka")]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_kafka_sink_reports_undeliverable_events() {
+    use image_sidecar_rust::events::{EventBatch, EventKind, EventSink, KafkaSink, SidecarEvent};
+
+    // Nothing listens on port 1, so delivery fails once the message timeout expires
+    let mut config = rdkafka::ClientConfig::new();
+    config.set("bootstrap.servers", "127.0.0.1:1").set("message.timeout.ms", "500");
+    let sink = Arc::new(KafkaSink::with_config(&config, "sidecar-events").unwrap());
+    assert_eq!(sink.describe(), "kafka:sidecar-events");
+
+    let event = SidecarEvent::new(EventKind::Created, Some(Path::new("a.jpg")), Path::new("a.bin"), Some("face_detection"));
+    let batch = EventBatch::new(vec![event]);
+    let error = tokio::task::spawn_blocking(move || sink.deliver(&batch)).await.unwrap().unwrap_err();
+    assert!(error.to_string().contains("Kafka topic sidecar-events failed"));
+}
+
+#[cfg(feature = "huggingface")]
+#[tokio::test]
+async fn test_huggingface_export_writes_typed_parquet_and_dataset_infos() {
+    use image_sidecar_rust::huggingface::HfExportOptions;
+    use polars::prelude::{DataType, ParquetReader, SerReader};
+
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("season");
+    fs::create_dir_all(&root).unwrap();
+    let sidecar = ImageSidecar::new(None);
+    for (i, confidence) in [(0, json!(0.9)), (1, json!(1))] {
+        let image_path = root.join(format!("img_{}.jpg", i));
+        fs::write(&image_path, format!("pixels {}", i)).unwrap();
+        sidecar.save_data(&image_path, OperationType::FaceDetection, json!({
+            "faces": [{"bbox": [1, 2, 3, 4], "confidence": confidence}],
+        })).await.unwrap();
+    }
+    sidecar.save_data(&root.join("img_1.jpg"), OperationType::Yolov8, json!({"count": 2, "model": {"name": "v8n"}})).await.unwrap();
+
+    let output = temp_dir.path().join("dataset");
+    let options = HfExportOptions::default().include_images(true);
+    let summary = sidecar.export_huggingface(&root, &output, &options).await.unwrap();
+    assert_eq!(summary.rows, 2);
+    assert_eq!(summary.operations, ["face_detection", "yolov8"]);
+    assert!(summary.missing_images.is_empty());
+
+    let infos: serde_json::Value = serde_json::from_slice(&fs::read(output.join("dataset_infos.json")).unwrap()).unwrap();
+    let features = &infos["default"]["features"];
+    assert_eq!(features["image"], json!({"_type": "Image"}));
+    assert_eq!(features["face_detection"]["faces"][0]["confidence"], json!({"dtype": "float64", "_type": "Value"}));
+    assert_eq!(features["yolov8"]["count"], json!({"dtype": "int64", "_type": "Value"}));
+    assert_eq!(infos["default"]["splits"]["train"]["num_examples"], 2);
+    assert!(fs::read_to_string(output.join("README.md")).unwrap().contains("path: data/train-*"));
+
+    let file = fs::File::open(output.join("data/train-00000-of-00001.parquet")).unwrap();
+    let frame = ParquetReader::new(file).finish().unwrap();
+    assert_eq!(frame.height(), 2);
+    let image_paths: Vec<_> = frame.column("image_path").unwrap().str().unwrap().into_no_null_iter().collect();
+    assert_eq!(image_paths, ["img_0.jpg", "img_1.jpg"]);
+    assert!(matches!(frame.column("face_detection").unwrap().dtype(), DataType::Struct(_)));
+    assert_eq!(frame.column("yolov8").unwrap().null_count(), 1);
+}
+
+#[cfg(feature = "db")]
+#[tokio::test]
+async fn test_db_export_builds_schema_and_reports_unreachable_databases() {
+    use image_sidecar_rust::db::{DbExportOptions, DbKind, TableSchema};
+
+    let schema = TableSchema::parse("analytics.detections", "image_path, operation, payload=data").unwrap();
+    assert_eq!(
+        schema.create_statement(DbKind::Postgres),
+        r#"CREATE TABLE IF NOT EXISTS "analytics"."detections" ("image_path" TEXT, "operation" TEXT, "payload" JSONB)"#
+    );
+    assert_eq!(
+        schema.create_statement(DbKind::ClickHouse),
+        "CREATE TABLE IF NOT EXISTS `analytics`.`detections` (`image_path` String, `operation` LowCardinality(String), `payload` String) \
+         ENGINE = MergeTree ORDER BY tuple()"
+    );
+    assert!(TableSchema::parse("t", "image_path,bogus").is_err());
+    assert_eq!(DbKind::from_url("clickhouse://default@localhost/db"), Some(DbKind::ClickHouse));
+    assert_eq!(DbKind::from_url("mysql://localhost"), None);
+
+    let temp_dir = TempDir::new().unwrap();
+    let sidecar = ImageSidecar::new(None);
+    let image_path = temp_dir.path().join("a.jpg");
+    fs::write(&image_path, b"fake image data").unwrap();
+    sidecar.save_data(&image_path, OperationType::Yolov8, json!({"count": 2})).await.unwrap();
+
+    // Nothing listens on port 1
+    for url in ["postgres://user@127.0.0.1:1/db", "clickhouse://127.0.0.1:1/db"] {
+        let options = DbExportOptions::new(url).schema(schema.clone());
+        assert!(sidecar.db_export(temp_dir.path(), &options).await.is_err(), "{}", url);
+    }
+
+    // Nothing to insert when the filter leaves no sidecar, so no connection is needed for ClickHouse
+    let options = DbExportOptions::new("http://127.0.0.1:1").operations(["face_detection"]);
+    let summary = sidecar.db_export(temp_dir.path(), &options).await.unwrap();
+    assert_eq!((summary.rows, summary.batches, summary.filtered), (0, 0, 1));
+    let options = DbExportOptions::new("postgres://user@127.0.0.1:1/db?sslmode=require");
+    assert!(sidecar.db_export(temp_dir.path(), &options).await.is_err());
+
+    // Records stream into batches of the requested size; a stand-in ClickHouse counts the rows per INSERT
+    for name in ["b", "c"] {
+        let image_path = temp_dir.path().join(format!("{}.jpg", name));
+        fs::write(&image_path, b"fake image data").unwrap();
+        sidecar.save_data(&image_path, OperationType::Yolov8, json!({"count": 1})).await.unwrap();
+    }
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/db", listener.local_addr().unwrap());
+    let server = std::thread::spawn(move || {
+        use std::io::{BufRead, BufReader, Read, Write};
+        let mut rows = Vec::new();
+        for _ in 0..2 {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            rows.push(body.iter().filter(|&&byte| byte == b'\n').count());
+            reader.get_mut().write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").unwrap();
+        }
+        rows
+    });
+    let summary = sidecar.db_export(temp_dir.path(), &DbExportOptions::new(url).batch_size(2)).await.unwrap();
+    assert_eq!((summary.rows, summary.batches, summary.filtered), (3, 2, 0));
+    assert_eq!(server.join().unwrap(), [2, 1]);
+}
+
+#[tokio::test]
+async fn test_vector_index_finds_similar_faces_and_round_trips() {
+    let temp_dir = TempDir::new().unwrap();
+    let sidecar = ImageSidecar::new(None);
+    
+    // Twenty clusters of two faces each; image 2i and 2i+1 share a direction
+    for i in 0..40 {
+        let cluster = (i / 2) as f64;
+        let encoding: Vec<f64> = (0..128)
+            .map(|d| ((cluster + 1.0) * (d as f64 + 1.0) * 0.37).sin() + (i % 2) as f64 * 0.01)
+            .collect();
+        let image_path = temp_dir.path().join(format!("img_{:02}.jpg", i));
+        fs::write(&image_path, b"fake image data").unwrap();
+        sidecar.save_data(&image_path, OperationType::FaceDetection, json!({
+            "faces": [{"confidence": 0.9, "encoding": encoding}]
+        })).await.unwrap();
+    }
+    
+    let index = sidecar.build_vector_index(temp_dir.path()).await.unwrap();
+    assert_eq!(index.len(), 40);
+    assert_eq!(index.dimension(), 128);
+    
+    let index_path = temp_dir.path().join("faces.idx");
+    index.save(&index_path).unwrap();
+    let index = VectorIndex::load(&index_path).unwrap();
+    
+    let neighbors = index.find_similar(&temp_dir.path().join("img_06.jpg"), 3).unwrap();
+    assert_eq!(neighbors.len(), 3);
+    assert_eq!(neighbors[0].image_path, temp_dir.path().join("img_07.jpg"));
+    assert!(neighbors.windows(2).all(|pair| pair[0].distance <= pair[1].distance));
+    
+    assert!(index.find_similar(&temp_dir.path().join("missing.jpg"), 3).is_err());
+
+    // Truncated or tampered index files are errors, not panics on first use
+    let bytes = fs::read(&index_path).unwrap();
+    fs::write(&index_path, &bytes[..bytes.len() / 2]).unwrap();
+    assert!(matches!(VectorIndex::load(&index_path), Err(SidecarError::Schema(_))));
+    // The entry point sits just before the trailing 8-byte generator state
+    let mut tampered = bytes.clone();
+    let entry_point = tampered.len() - 12;
+    tampered[entry_point..entry_point + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+    fs::write(&index_path, &tampered).unwrap();
+    assert!(matches!(VectorIndex::load(&index_path), Err(SidecarError::Schema(_))));
+}
+
+#[cfg(feature = "sql")]
+#[tokio::test]
+async fn test_sql_selects_from_sidecars_table() {
+    let temp_dir = TempDir::new().unwrap();
+    let sidecar = ImageSidecar::new(None);
+    
+    let payloads = [
+        (OperationType::Yolov8, json!({"count": 0, "success": true})),
+        (OperationType::Yolov8, json!({"count": 3, "success": true})),
+        (OperationType::FaceDetection, json!({"faces": []})),
+    ];
+    for (i, (operation, payload)) in payloads.into_iter().enumerate() {
+        let image_path = temp_dir.path().join(format!("img_{}.jpg", i));
+        fs::write(&image_path, b"fake image data").unwrap();
+        sidecar.save_data(&image_path, operation, payload).await.unwrap();
+    }
+    
+    let frame = sidecar
+        .sql(temp_dir.path(), "SELECT image_path, count FROM sidecars WHERE operation = 'yolov8' AND count = 0")
+        .await
+        .unwrap();
+    assert_eq!(frame.height(), 1);
+    let image_path = frame.column("image_path").unwrap().str().unwrap().get(0).unwrap().to_string();
+    assert!(image_path.ends_with("img_0.jpg"));
+    
+    assert!(sidecar.sql(temp_dir.path(), "SELECT nope FROM sidecars").await.is_err());
+}
@@ -49,7 +49,74 @@ class TestImageSidecar:
             assert isinstance(stats['avg_processing_times'], dict)
             assert isinstance(stats['success_rate_percentages'], dict)
             assert isinstance(stats['avg_data_sizes'], dict)
+            assert stats['symlink_count'] == 0
+            assert stats['broken_symlinks'] == 0
+            assert stats['filter_applied'] is None
+            assert stats['sidecars'] == []
+
+    def test_get_statistics_lists_sidecars(self) -> None:
+        """Test that statistics include one row per sidecar."""
+        sidecar = ImageSidecar()
+        
+        with tempfile.TemporaryDirectory() as temp_dir:
+            image_path = Path(temp_dir) / "test.jpg"
+            image_path.write_bytes(b"fake image data")
+            sidecar.save_data(image_path, OperationType.FACE_DETECTION, {"faces": []})
+            
+            stats = sidecar.get_statistics(temp_dir)
+            assert stats['directory'] == temp_dir
+            assert len(stats['sidecars']) == 1
+            row = stats['sidecars'][0]
+            assert Path(row['image_path']).name == "test.jpg"
+            assert row['operation'] == "face_detection"
+            assert row['is_valid'] is True
     
+    def test_dataframe_constructors_return_arrow_tables(self) -> None:
+        """Test that validate_sidecars_df and stats_df return one row per file."""
+        pa = pytest.importorskip("pyarrow")
+        sidecar = ImageSidecar()
+        if not hasattr(sidecar._rust_impl, "stats_df"):
+            pytest.skip("extension built without the python-arrow feature")
+        
+        with tempfile.TemporaryDirectory() as temp_dir:
+            for name in ("a", "b"):
+                image_path = Path(temp_dir) / f"{name}.jpg"
+                image_path.write_bytes(b"fake image data")
+                sidecar.save_data(image_path, OperationType.FACE_DETECTION, {"faces": []})
+            
+            validation = sidecar.validate_sidecars_df(temp_dir)
+            assert isinstance(validation, pa.Table)
+            assert validation.num_rows == 2
+            assert validation.column("is_valid").to_pylist() == [True, True]
+            
+            stats = sidecar.stats_df(temp_dir)
+            assert stats.num_rows == 2
+            assert set(stats.column("operation").to_pylist()) == {"face_detection"}
+
+    def test_numpy_round_trip(self) -> None:
+        """Test saving ndarrays and reading them back as ndarrays."""
+        np = pytest.importorskip("numpy")
+        sidecar = ImageSidecar()
+        
+        with tempfile.TemporaryDirectory() as temp_dir:
+            image_path = Path(temp_dir) / "face.jpg"
+            image_path.write_bytes(b"fake image data")
+            encoding = np.linspace(-1.0, 1.0, 128, dtype=np.float32)
+            landmarks = np.arange(10, dtype=np.int32).reshape(5, 2)
+            sidecar.save_data(image_path, OperationType.FACE_DETECTION, {
+                "faces": [{"encoding": encoding, "landmarks": landmarks, "score": np.float64(0.5)}],
+            })
+            
+            face = sidecar.read_data(image_path)["face_detection"]["faces"][0]
+            assert isinstance(face["encoding"], list)
+            assert face["score"] == 0.5
+            
+            face = sidecar.read_data(image_path, as_numpy=True)["face_detection"]["faces"][0]
+            assert face["encoding"].dtype == np.float64
+            np.testing.assert_allclose(face["encoding"], encoding, rtol=1e-6)
+            assert face["landmarks"].shape == (5, 2)
+            np.testing.assert_array_equal(face["landmarks"], landmarks)
+
     def test_find_sidecars_empty_directory(self) -> None:
         """Test finding sidecars in empty directory."""
         sidecar = ImageSidecar()
@@ -58,7 +125,56 @@ class TestImageSidecar:
             sidecars = sidecar.find_sidecars(temp_dir)
             assert isinstance(sidecars, list)
             assert len(sidecars) == 0
-    
+
+    def test_iter_sidecars_matches_find_sidecars(self) -> None:
+        """Test that lazy iteration yields the same sidecars as find_sidecars."""
+        sidecar = ImageSidecar()
+
+        with tempfile.TemporaryDirectory() as temp_dir:
+            for i in range(3):
+                image_path = Path(temp_dir) / f"test_{i}.jpg"
+                image_path.write_bytes(b"fake image data")
+                sidecar.create_sidecar(image_path, OperationType.FACE_DETECTION, {"index": i})
+
+            iterated = sorted(info['sidecar_path'] for info in sidecar.iter_sidecars(temp_dir))
+            found = sorted(info['sidecar_path'] for info in sidecar.find_sidecars(temp_dir))
+            assert iterated == found
+            assert len(iterated) == 3
+
+    def test_query_filters_by_expression(self) -> None:
+        """Test that query returns only sidecars matching the expression."""
+        sidecar = ImageSidecar()
+
+        with tempfile.TemporaryDirectory() as temp_dir:
+            for i in range(3):
+                image_path = Path(temp_dir) / f"test_{i}.jpg"
+                image_path.write_bytes(b"fake image data")
+                faces = [{"confidence": 0.5 + 0.2 * i}] * i
+                sidecar.save_data(image_path, OperationType.FACE_DETECTION, {"faces": faces})
+
+            records = sidecar.query(temp_dir, "detections >= 2 && confidence.max >= 0.9")
+            assert [Path(r['image_path']).name for r in records] == ["test_2.jpg"]
+
+            with pytest.raises(ValueError):
+                sidecar.query(temp_dir, "detections >")
+
+    def test_find_similar_uses_saved_index(self) -> None:
+        """Test that a saved vector index returns the closest face first."""
+        sidecar = ImageSidecar()
+
+        with tempfile.TemporaryDirectory() as temp_dir:
+            encodings = {"a": [1.0, 0.0, 0.0], "b": [0.9, 0.1, 0.0], "c": [0.0, 0.0, 1.0]}
+            for name, encoding in encodings.items():
+                image_path = Path(temp_dir) / f"{name}.jpg"
+                image_path.write_bytes(b"fake image data")
+                sidecar.save_data(image_path, OperationType.FACE_DETECTION, {"faces": [{"encoding": encoding}]})
+
+            index_path = Path(temp_dir) / "faces.idx"
+            assert sidecar.build_vector_index(temp_dir, index_path) == 3
+
+            neighbors = sidecar.find_similar(index_path, Path(temp_dir) / "a.jpg", k=2)
+            assert [Path(n['image_path']).name for n in neighbors] == ["b.jpg", "c.jpg"]
+
     def test_create_sidecar(self) -> None:
         """Test creating a sidecar file."""
         sidecar = ImageSidecar()
@@ -125,6 +241,22 @@ class TestImageSidecar:
             # Both operations should be preserved in the merged sidecar
             assert Path(result2['sidecar_path']) == sidecar_path
 
+    def test_in_memory_never_touches_the_disk(self) -> None:
+        """Test that an in-memory instance reads, writes and scans its own tree."""
+        sidecar = ImageSidecar.in_memory({
+            "/data/game1/a.jpg": {"face_detection": {"faces": [{"confidence": 0.9}]}},
+            "/data/game1/b.jpg": {},
+        })
+        
+        assert sidecar.read_data("/data/game1/a.jpg")["face_detection"]["faces"][0]["confidence"] == 0.9
+        assert sidecar.read_data("/data/game1/b.jpg") == {}
+        sidecar.save_data("/data/game1/b.jpg", OperationType.FACE_DETECTION, {"faces": []})
+        assert not Path("/data/game1/b.json").exists()
+        
+        stats = sidecar.get_statistics("/data")
+        assert len(stats['sidecars']) == 2
+        assert len(sidecar.find_sidecars("/data/game1")) == 2
+
     def test_read_data_no_sidecar(self) -> None:
         """Test read_data returns {} when no sidecar exists."""
         sidecar = ImageSidecar()
@@ -140,6 +272,20 @@ class TestImageSidecar:
             assert isinstance(data, dict)
             assert len(data) == 0
 
+    def test_read_data_corrupt_sidecar_reports_error_code(self) -> None:
+        """Test that a corrupt sidecar surfaces the Rust error class as error_code."""
+        sidecar = ImageSidecar()
+        
+        with tempfile.TemporaryDirectory() as temp_dir:
+            image_path = Path(temp_dir) / "test.jpg"
+            image_path.write_bytes(b"fake image data")
+            (Path(temp_dir) / "test.bin").write_bytes(b"\x01not bincode")
+            
+            with pytest.raises(SidecarError) as excinfo:
+                sidecar.read_data(image_path)
+            
+            assert excinfo.value.error_code == "SidecarFormatError"
+
     def test_read_data_binary_format(self) -> None:
         """Test reading binary format sidecars."""
         sidecar = ImageSidecar()